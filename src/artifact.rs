@@ -0,0 +1,132 @@
+use crate::errors::{AtentoError, Result};
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use serde::{Deserialize, Serialize};
+use std::path::{Component, Path, PathBuf};
+
+/// A file a step produces, recorded in `StepResult.artifacts` after the step
+/// ran successfully.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[cfg_attr(
+    feature = "schema",
+    derive(schemars::JsonSchema),
+    schemars(deny_unknown_fields)
+)]
+pub struct Artifact {
+    /// File path, relative to the step's `workdir` when set; supports
+    /// `{{ inputs.x }}` and `{{ parameters.x }}` placeholders
+    pub path: String,
+    /// A missing file does not fail the step; the artifact is left out
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub optional: bool,
+    /// Files of at most this many bytes also have their content recorded,
+    /// base64 encoded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub inline_max_bytes: Option<u64>,
+}
+
+/// An artifact file found after a step ran.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArtifactResult {
+    /// Path of the file, as passed to later steps by `steps.<id>.artifacts.<name>`
+    pub path: String,
+    pub size: u64,
+    /// File content, when the file is no larger than `inline_max_bytes`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_base64: Option<String>,
+}
+
+impl Artifact {
+    /// Looks up the artifact file at the rendered `path`, resolved against
+    /// `workdir`, and records its size and, if small enough, its content.
+    ///
+    /// Returns `None` for a missing `optional` artifact.
+    ///
+    /// # Errors
+    /// Returns an `Execution` error if the file is missing, cannot be read, or
+    /// lies outside `root` when one is set.
+    pub(crate) fn collect(
+        &self,
+        name: &str,
+        path: &str,
+        workdir: Option<&str>,
+        root: Option<&str>,
+    ) -> Result<Option<ArtifactResult>> {
+        let path = match workdir {
+            Some(dir) => Path::new(dir).join(path),
+            None => PathBuf::from(path),
+        };
+
+        if let Some(root) = root
+            && !is_within(&path, root)
+        {
+            return Err(AtentoError::Execution(format!(
+                "Artifact '{name}' at '{}' is outside the artifacts root '{root}'",
+                path.display()
+            )));
+        }
+
+        let metadata = match std::fs::metadata(&path) {
+            Ok(metadata) if metadata.is_file() => metadata,
+            Ok(_) => {
+                return Err(AtentoError::Execution(format!(
+                    "Artifact '{name}' at '{}' is not a file",
+                    path.display()
+                )));
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound && self.optional => {
+                return Ok(None);
+            }
+            Err(e) => {
+                return Err(AtentoError::Execution(format!(
+                    "Artifact '{name}' at '{}' cannot be read: {e}",
+                    path.display()
+                )));
+            }
+        };
+
+        let content_base64 = match self.inline_max_bytes {
+            Some(max) if metadata.len() <= max => {
+                let content = std::fs::read(&path).map_err(|e| {
+                    AtentoError::Execution(format!(
+                        "Artifact '{name}' at '{}' cannot be read: {e}",
+                        path.display()
+                    ))
+                })?;
+                Some(BASE64.encode(content))
+            }
+            _ => None,
+        };
+
+        Ok(Some(ArtifactResult {
+            path: path.display().to_string(),
+            size: metadata.len(),
+            content_base64,
+        }))
+    }
+}
+
+/// Returns whether `path` stays inside `root` once both are made absolute
+/// against the current directory and their `.` and `..` components resolved.
+/// Symlinks are not followed.
+pub(crate) fn is_within(path: &Path, root: &str) -> bool {
+    let absolute = |path: &Path| match std::env::current_dir() {
+        Ok(cwd) => normalize(&cwd.join(path)),
+        Err(_) => normalize(path),
+    };
+    absolute(path).starts_with(absolute(Path::new(root)))
+}
+
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            other => normalized.push(other),
+        }
+    }
+    normalized
+}
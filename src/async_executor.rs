@@ -0,0 +1,115 @@
+use crate::async_runner;
+use crate::errors::Result;
+use crate::executor::{
+    DockerExecutor, ExecutionRequest, ExecutionResult, SystemExecutor, check_remote_input,
+    local_timeout,
+};
+use crate::remote::SshExecutor;
+
+/// Async counterpart of [`CommandExecutor`](crate::CommandExecutor), used by
+/// [`Chain::run_async`](crate::Chain::run_async).
+///
+/// The requests and results are those of `CommandExecutor`; the difference is
+/// that waiting for the script suspends the calling task instead of blocking
+/// its thread. [`SystemExecutor`], [`DockerExecutor`] and [`SshExecutor`]
+/// implement both traits.
+pub trait AsyncCommandExecutor {
+    /// Executes the script of `request`, see [`CommandExecutor::execute`](crate::CommandExecutor::execute).
+    ///
+    /// # Errors
+    /// Returns an error if the script cannot be started.
+    fn execute(
+        &self,
+        request: &ExecutionRequest<'_>,
+    ) -> impl Future<Output = Result<ExecutionResult>> + Send;
+
+    /// Executes a script and also records stdout and stderr merged in the order
+    /// they were written, in `ExecutionResult::combined`.
+    ///
+    /// The default implementation delegates to `execute` and records no
+    /// combined output.
+    ///
+    /// # Errors
+    /// Returns the same errors as [`AsyncCommandExecutor::execute`].
+    fn execute_combined(
+        &self,
+        request: &ExecutionRequest<'_>,
+    ) -> impl Future<Output = Result<ExecutionResult>> + Send {
+        self.execute(request)
+    }
+}
+
+impl AsyncCommandExecutor for SystemExecutor {
+    async fn execute(&self, request: &ExecutionRequest<'_>) -> Result<ExecutionResult> {
+        let mut result = self.execute_combined(request).await?;
+        result.combined = None;
+        Ok(result)
+    }
+
+    async fn execute_combined(&self, request: &ExecutionRequest<'_>) -> Result<ExecutionResult> {
+        let result = async_runner::run(
+            request.script,
+            request.interpreter,
+            request.env,
+            request.workdir,
+            request.stdin,
+            request.max_output_bytes,
+            request.timeout,
+            request.kill,
+        )
+        .await?;
+        Ok(result.into())
+    }
+}
+
+impl AsyncCommandExecutor for DockerExecutor {
+    async fn execute(&self, request: &ExecutionRequest<'_>) -> Result<ExecutionResult> {
+        let mut result = self.execute_combined(request).await?;
+        result.combined = None;
+        Ok(result)
+    }
+
+    async fn execute_combined(&self, request: &ExecutionRequest<'_>) -> Result<ExecutionResult> {
+        let result = async_runner::run_in_container(
+            request.script,
+            request.interpreter,
+            &self.image,
+            request.env,
+            request.workdir,
+            request.stdin,
+            request.max_output_bytes,
+            request.timeout,
+            request.kill,
+        )
+        .await?;
+        Ok(result.into())
+    }
+}
+
+impl AsyncCommandExecutor for SshExecutor {
+    async fn execute(&self, request: &ExecutionRequest<'_>) -> Result<ExecutionResult> {
+        let mut result = self.execute_combined(request).await?;
+        result.combined = None;
+        Ok(result)
+    }
+
+    async fn execute_combined(&self, request: &ExecutionRequest<'_>) -> Result<ExecutionResult> {
+        check_remote_input(request)?;
+        let cmd = self.command(
+            request.interpreter,
+            request.env,
+            request.workdir,
+            request.timeout,
+            request.kill,
+        );
+        let result = async_runner::run_with_input(
+            cmd,
+            &format!("{}\n", request.script),
+            request.max_output_bytes,
+            local_timeout(request.timeout),
+            request.kill,
+        )
+        .await?;
+        self.remote_result(result, request.timeout)
+    }
+}
@@ -0,0 +1,318 @@
+//! Tokio counterparts of the functions in `runner`, used by the
+//! [`AsyncCommandExecutor`](crate::AsyncCommandExecutor) implementations.
+//!
+//! Scripts are written, started and their output collected exactly as in
+//! `runner`, but waiting for the process suspends the task instead of
+//! blocking a thread.
+
+use crate::errors::{AtentoError, Result};
+use crate::interpreter;
+use crate::runner::{
+    self, CollectedOutput, KillPolicy, LineSplitter, OutputStream, Piped, READ_CHUNK_BYTES,
+    RunnerResult, TempScript, TimeoutSignal,
+};
+use std::collections::HashMap;
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWriteExt};
+use tokio::process::Child;
+use tokio::sync::mpsc;
+
+/// How often a running process is checked for its timeout and cancellation
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Runs a script like [`runner::run_combined`], awaiting the process instead
+/// of blocking on it.
+///
+/// # Errors
+/// Returns the same errors as [`runner::run`].
+#[allow(clippy::too_many_arguments)]
+pub async fn run(
+    script: &str,
+    interpreter: &interpreter::Interpreter,
+    env: &HashMap<String, String>,
+    workdir: Option<&str>,
+    stdin: Option<&str>,
+    max_output_bytes: Option<u64>,
+    timeout_secs: u64,
+    kill: &KillPolicy,
+) -> Result<RunnerResult> {
+    let temp_script = write_script(script, interpreter, workdir).await?;
+    let (child, resolved_command) = runner::start_host_with(
+        interpreter,
+        workdir,
+        temp_script.path(),
+        stdin.is_some(),
+        |cmd| spawn(cmd, env),
+    )?;
+    let mut result =
+        wait_for_output(child, None, stdin, max_output_bytes, timeout_secs, kill).await?;
+    result.resolved_command = Some(resolved_command);
+    Ok(result)
+}
+
+/// Runs a script inside a Docker container of `image` like
+/// [`runner::run_in_container`], awaiting the `docker run` process.
+///
+/// # Errors
+/// Returns the same errors as [`runner::run_in_container`].
+#[allow(clippy::too_many_arguments)]
+pub async fn run_in_container(
+    script: &str,
+    interpreter: &interpreter::Interpreter,
+    image: &str,
+    env: &HashMap<String, String>,
+    workdir: Option<&str>,
+    stdin: Option<&str>,
+    max_output_bytes: Option<u64>,
+    timeout_secs: u64,
+    kill: &KillPolicy,
+) -> Result<RunnerResult> {
+    if image.trim().is_empty() {
+        return Err(AtentoError::Runner(
+            "Container image cannot be empty".to_string(),
+        ));
+    }
+
+    let temp_script = write_script(script, interpreter, workdir).await?;
+    let container = runner::container_name();
+    let mut cmd = runner::container_command(
+        &container,
+        image,
+        interpreter,
+        env,
+        workdir,
+        temp_script.path(),
+        stdin.is_some(),
+    );
+    if stdin.is_some() {
+        cmd.stdin(Stdio::piped());
+    }
+    let child = spawn(cmd, env).map_err(|e| runner::docker_start_error(&e))?;
+    wait_for_output(
+        child,
+        Some(&container),
+        stdin,
+        max_output_bytes,
+        timeout_secs,
+        kill,
+    )
+    .await
+}
+
+/// Runs `cmd` with `input` written to its stdin like [`runner::run_with_input`].
+///
+/// # Errors
+/// Returns an error if the command fails to start.
+pub(crate) async fn run_with_input(
+    mut cmd: Command,
+    input: &str,
+    max_output_bytes: Option<u64>,
+    timeout_secs: u64,
+    kill: &KillPolicy,
+) -> Result<RunnerResult> {
+    cmd.stdin(Stdio::piped());
+    let child = spawn(cmd, &HashMap::new()).map_err(|e| runner::start_error(&e))?;
+    wait_for_output(
+        child,
+        None,
+        Some(input),
+        max_output_bytes,
+        timeout_secs,
+        kill,
+    )
+    .await
+}
+
+/// Spawns `cmd` as a tokio process, configured like the blocking runner does.
+/// The process is killed if the future running it is dropped.
+fn spawn(mut cmd: Command, env: &HashMap<String, String>) -> std::io::Result<Child> {
+    runner::configure(&mut cmd, env);
+    tokio::process::Command::from(cmd)
+        .kill_on_drop(true)
+        .spawn()
+}
+
+/// Reads the output of a started process until it exits or `timeout_secs`
+/// passes, writing `stdin` to it first.
+async fn wait_for_output(
+    mut child: Child,
+    container: Option<&str>,
+    stdin: Option<&str>,
+    max_output_bytes: Option<u64>,
+    timeout_secs: u64,
+    kill: &KillPolicy,
+) -> Result<RunnerResult> {
+    let timeout = runner::runner_timeout(timeout_secs);
+    let start = Instant::now();
+    // Taken now, as tokio no longer reports it once the process was reaped
+    let pid = child.id();
+
+    if let (Some(mut pipe), Some(input)) = (child.stdin.take(), stdin) {
+        let input = input.to_string();
+        // A process that exits without reading its input just closes the pipe
+        tokio::spawn(async move {
+            let _ = pipe.write_all(input.as_bytes()).await;
+        });
+    }
+
+    let mut output = CollectedOutput::new(max_output_bytes);
+    let (sender, mut receiver) = mpsc::unbounded_channel();
+    if let Some(stdout) = child.stdout.take() {
+        forward_lines(stdout, OutputStream::Stdout, output.limit, sender.clone());
+    }
+    if let Some(stderr) = child.stderr.take() {
+        forward_lines(stderr, OutputStream::Stderr, output.limit, sender);
+    }
+
+    loop {
+        let wait = timeout.saturating_sub(start.elapsed()).min(POLL_INTERVAL);
+        match tokio::time::timeout(wait, receiver.recv()).await {
+            Ok(Some((stream, piped))) => output.receive(stream, piped, &mut |_, _| {}),
+            // Both pipes are closed, so the process is done writing
+            Ok(None) => break,
+            Err(_) => {}
+        }
+
+        if kill.is_cancelled() {
+            stop(&mut child, pid, container, kill).await;
+            return Err(runner::cancelled());
+        }
+        if start.elapsed() >= timeout {
+            let force_killed = stop(&mut child, pid, container, kill).await;
+            // Keep the lines that were read before the process was stopped
+            while let Ok((stream, piped)) = receiver.try_recv() {
+                output.receive(stream, piped, &mut |_, _| {});
+            }
+            return Ok(output.into_timeout_result(&start, force_killed));
+        }
+    }
+
+    let status = loop {
+        let wait = timeout.saturating_sub(start.elapsed()).min(POLL_INTERVAL);
+        if let Ok(status) = tokio::time::timeout(wait, child.wait()).await {
+            break status
+                .map_err(|e| AtentoError::Execution(format!("Failed to check process: {e}")))?;
+        }
+
+        if kill.is_cancelled() {
+            stop(&mut child, pid, container, kill).await;
+            return Err(runner::cancelled());
+        }
+        if start.elapsed() >= timeout {
+            let force_killed = stop(&mut child, pid, container, kill).await;
+            return Ok(output.into_timeout_result(&start, force_killed));
+        }
+    };
+
+    Ok(output.into_result(&start, status.code().unwrap_or(-1), false))
+}
+
+/// Stops a process that exceeded its timeout as `policy` describes, waiting
+/// out the grace period without blocking. Returns whether it had to be
+/// force-killed.
+#[cfg(unix)]
+async fn stop(
+    child: &mut Child,
+    pid: Option<u32>,
+    container: Option<&str>,
+    policy: &KillPolicy,
+) -> bool {
+    if policy.signal == TimeoutSignal::Kill {
+        kill(child, pid, container).await;
+        return true;
+    }
+    let signal = policy.signal.name();
+    match (container, pid) {
+        (Some(name), _) => status(runner::signal_container_command(name, signal)).await,
+        (None, Some(pid)) => status(runner::signal_tree_command(pid, signal)).await,
+        (None, None) => {}
+    }
+
+    let grace = Duration::from_millis(policy.grace_ms);
+    let exited = tokio::time::timeout(grace, child.wait()).await.is_ok();
+    // Kill whatever is left, such as children the script left behind
+    kill(child, pid, container).await;
+    !exited
+}
+
+#[cfg(not(unix))]
+async fn stop(
+    child: &mut Child,
+    pid: Option<u32>,
+    container: Option<&str>,
+    _policy: &KillPolicy,
+) -> bool {
+    kill(child, pid, container).await;
+    true
+}
+
+/// Kills a process along with its process tree and the container it runs, if any.
+async fn kill(child: &mut Child, pid: Option<u32>, container: Option<&str>) {
+    if let Some(name) = container {
+        status(runner::signal_container_command(
+            name,
+            TimeoutSignal::Kill.name(),
+        ))
+        .await;
+    }
+    if let Some(pid) = pid {
+        status(runner::kill_tree_command(pid)).await;
+    }
+    // Ignore kill errors, the process may have exited in the meantime; this
+    // also reaps it
+    let _ = child.kill().await;
+}
+
+/// Runs one of the runner's `kill` or `docker kill` commands as a tokio
+/// process, ignoring its outcome like the blocking runner does.
+async fn status(cmd: Command) {
+    let _ = tokio::process::Command::from(cmd).status().await;
+}
+
+/// Writes the temp script like [`runner::write_script`], on the blocking
+/// thread pool as it creates and writes a file.
+async fn write_script(
+    script: &str,
+    interpreter: &interpreter::Interpreter,
+    workdir: Option<&str>,
+) -> Result<TempScript> {
+    let script = script.to_string();
+    let interpreter = interpreter.clone();
+    let workdir = workdir.map(ToString::to_string);
+    tokio::task::spawn_blocking(move || {
+        runner::write_script(&script, &interpreter, workdir.as_deref())
+    })
+    .await
+    .map_err(|e| AtentoError::Runner(format!("Failed to write script: {e}")))?
+}
+
+/// Reads `pipe` on a new task and sends each line, or how many bytes past
+/// `limit` were dropped, tagged with `stream`, draining it until it closes
+/// like `runner::forward_lines`.
+fn forward_lines<R: AsyncRead + Unpin + Send + 'static>(
+    mut pipe: R,
+    stream: OutputStream,
+    limit: Option<usize>,
+    sender: mpsc::UnboundedSender<(OutputStream, Piped)>,
+) {
+    tokio::spawn(async move {
+        let mut lines = LineSplitter::new(limit);
+        let mut buf = [0; READ_CHUNK_BYTES];
+        let mut receiving = true;
+        loop {
+            let read = match pipe.read(&mut buf).await {
+                Ok(0) => break,
+                Ok(read) => read,
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+                Err(_) => break,
+            };
+            for piped in lines.feed(&buf[..read]) {
+                receiving = receiving && sender.send((stream, piped)).is_ok();
+            }
+        }
+        for piped in lines.finish() {
+            receiving = receiving && sender.send((stream, piped)).is_ok();
+        }
+    });
+}
@@ -0,0 +1,259 @@
+use crate::chain::Chain;
+use crate::data_type::DataType;
+use crate::input::Input;
+use crate::interpreter::{Interpreter, default_interpreters};
+use crate::output::Output;
+use crate::parameter::Parameter;
+use crate::result_ref::{ResultRef, ResultValue};
+use crate::step::Step;
+use std::collections::HashMap;
+
+/// Builds a [`Chain`] in code instead of parsing YAML.
+///
+/// ```
+/// use atento_core::{ChainBuilder, DataType, StepBuilder};
+///
+/// let chain = ChainBuilder::new()
+///     .name("greet")
+///     .parameter("who", DataType::String, "world")
+///     .step(
+///         "hello",
+///         StepBuilder::new()
+///             .interpreter("bash")
+///             .script("echo Hello {{ inputs.who }}")
+///             .input("who", "parameters.who")
+///             .output("greeting", "(Hello .*)"),
+///     )
+///     .result("greeting", "steps.hello.outputs.greeting")
+///     .build();
+///
+/// assert!(chain.validate().is_ok());
+/// ```
+#[derive(Debug, Default)]
+#[must_use]
+pub struct ChainBuilder {
+    chain: Chain,
+}
+
+impl ChainBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.chain.name = Some(name.into());
+        self
+    }
+
+    /// Adds a `metadata` entry, carried into the result of every run
+    pub fn metadata(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.chain.metadata.insert(key.into(), value.into());
+        self
+    }
+
+    /// Chain timeout in seconds
+    pub fn timeout(mut self, timeout: u64) -> Self {
+        self.chain.timeout = timeout;
+        self
+    }
+
+    /// Declares a parameter with a value of the given type.
+    pub fn parameter(
+        mut self,
+        name: impl Into<String>,
+        type_: DataType,
+        value: impl Into<serde_yaml::Value>,
+    ) -> Self {
+        self.chain.parameters.insert(
+            name.into(),
+            Parameter {
+                type_,
+                value: Some(value.into()),
+                required: false,
+                secret: false,
+                separator: None,
+            },
+        );
+        self
+    }
+
+    /// Declares a parameter with full control over its settings, such as
+    /// `required` or `secret`.
+    pub fn parameter_with(mut self, name: impl Into<String>, parameter: Parameter) -> Self {
+        self.chain.parameters.insert(name.into(), parameter);
+        self
+    }
+
+    /// Declares a custom interpreter, or replaces a default one.
+    pub fn interpreter(mut self, key: impl Into<String>, interpreter: Interpreter) -> Self {
+        self.chain.interpreters.insert(key.into(), interpreter);
+        self
+    }
+
+    /// Interpreter key used by steps that do not set one
+    pub fn default_interpreter(mut self, key: impl Into<String>) -> Self {
+        self.chain.default_interpreter = Some(key.into());
+        self
+    }
+
+    /// Docker image that steps without their own `image` run in
+    pub fn image(mut self, image: impl Into<String>) -> Self {
+        self.chain.image = Some(image.into());
+        self
+    }
+
+    pub fn parallel(mut self, parallel: bool) -> Self {
+        self.chain.parallel = parallel;
+        self
+    }
+
+    /// Adds a step; steps run in the order they are added.
+    pub fn step(mut self, id: impl Into<String>, step: StepBuilder) -> Self {
+        self.chain.steps.insert(id.into(), step.build());
+        self
+    }
+
+    /// Declares a step template that steps instantiate with
+    /// [`StepBuilder::template`].
+    pub fn template(mut self, key: impl Into<String>, template: StepBuilder) -> Self {
+        self.chain.templates.insert(key.into(), template.build());
+        self
+    }
+
+    /// Adds a chain result referencing a step output or parameter, e.g.
+    /// `steps.build.outputs.version`.
+    pub fn result(mut self, name: impl Into<String>, reference: impl Into<String>) -> Self {
+        self.chain.results.insert(
+            name.into(),
+            ResultValue::Ref(ResultRef {
+                ref_: reference.into(),
+            }),
+        );
+        self
+    }
+
+    /// Returns the chain with the default interpreters merged in and step
+    /// templates instantiated, as for a parsed chain. The chain is not
+    /// validated; call [`Chain::validate`].
+    #[must_use]
+    pub fn build(self) -> Chain {
+        let mut interpreters: HashMap<String, Interpreter> =
+            default_interpreters().into_iter().collect();
+        interpreters.extend(self.chain.interpreters);
+
+        let mut chain = Chain {
+            interpreters,
+            ..self.chain
+        };
+        chain.instantiate_templates();
+        chain.expand_matrices();
+        chain
+    }
+}
+
+/// Builds a [`Step`] for [`ChainBuilder::step`].
+#[derive(Debug, Default)]
+#[must_use]
+pub struct StepBuilder {
+    step: Step,
+}
+
+impl StepBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.step.name = Some(name.into());
+        self
+    }
+
+    /// Interpreter key, such as `bash` or `python`; when left out the chain's
+    /// default interpreter is used
+    pub fn interpreter(mut self, key: impl Into<String>) -> Self {
+        self.step.interpreter = Some(key.into());
+        self
+    }
+
+    pub fn script(mut self, script: impl Into<String>) -> Self {
+        self.step.script = script.into();
+        self
+    }
+
+    /// Step timeout in seconds
+    pub fn timeout(mut self, timeout: u64) -> Self {
+        self.step.timeout = timeout;
+        self
+    }
+
+    /// Declares an input referencing a parameter or an earlier step, e.g.
+    /// `parameters.who` or `steps.build.outputs.version`.
+    pub fn input(mut self, name: impl Into<String>, reference: impl Into<String>) -> Self {
+        self.step.inputs.insert(
+            name.into(),
+            Input::Ref {
+                ref_: reference.into(),
+            },
+        );
+        self
+    }
+
+    /// Declares an input with an inline value of the given type.
+    pub fn input_value(
+        mut self,
+        name: impl Into<String>,
+        type_: DataType,
+        value: impl Into<serde_yaml::Value>,
+    ) -> Self {
+        self.step.inputs.insert(
+            name.into(),
+            Input::Inline {
+                type_,
+                value: value.into(),
+                separator: None,
+            },
+        );
+        self
+    }
+
+    /// Declares a string output captured from stdout by the first group of `pattern`.
+    pub fn output(mut self, name: impl Into<String>, pattern: impl Into<String>) -> Self {
+        self.step.outputs.insert(
+            name.into(),
+            Output {
+                pattern: pattern.into(),
+                ..Output::default()
+            },
+        );
+        self
+    }
+
+    /// Declares an output with full control over its settings, such as its
+    /// type, source or `json_path`.
+    pub fn output_with(mut self, name: impl Into<String>, output: Output) -> Self {
+        self.step.outputs.insert(name.into(), output);
+        self
+    }
+
+    /// Instantiates the chain template `key`; only the step's name and
+    /// inputs are applied over the template.
+    pub fn template(mut self, key: impl Into<String>) -> Self {
+        self.step.template = Some(key.into());
+        self
+    }
+
+    pub fn workdir(mut self, workdir: impl Into<String>) -> Self {
+        self.step.workdir = Some(workdir.into());
+        self
+    }
+
+    pub fn continue_on_error(mut self, continue_on_error: bool) -> Self {
+        self.step.continue_on_error = continue_on_error;
+        self
+    }
+
+    #[must_use]
+    pub fn build(self) -> Step {
+        self.step
+    }
+}
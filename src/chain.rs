@@ -1,37 +1,138 @@
+use crate::artifact;
+#[cfg(feature = "async")]
+use crate::async_executor::AsyncCommandExecutor;
+#[cfg(feature = "exec")]
+use crate::cancel::CancellationToken;
+use crate::data_type::{self, rfc3339_timestamp};
+use crate::diagnostic::{Diagnostics, Stop, Subject};
+use crate::env::{EnvValue, is_env_name};
 use crate::errors::{AtentoError, Result};
-use crate::executor::CommandExecutor;
+#[cfg(feature = "exec")]
+use crate::executor::{CommandExecutor, DockerExecutor, SystemExecutor};
+use crate::fields;
 use crate::input::Input;
 use crate::interpreter::{Interpreter, default_interpreters};
+use crate::location::SourceMap;
+#[cfg(feature = "exec")]
+use crate::observer::{ChainObserver, NoopObserver};
 use crate::parameter::Parameter;
-use crate::result_ref::ResultRef;
-use crate::step::{Step, StepResult};
+#[cfg(feature = "exec")]
+use crate::process::KillPolicy;
+use crate::process::TimeoutSignal;
+use crate::remote::SshExecutor;
+use crate::result_ref::ResultValue;
+use crate::step::{self, Step, StepResult};
 use indexmap::IndexMap;
 use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
 use std::collections::{HashMap, HashSet};
-use std::time::Instant;
+use std::path::Path;
+use std::str::FromStr;
+#[cfg(feature = "exec")]
+use std::sync::mpsc;
+#[cfg(feature = "exec")]
+use std::time::{Duration, Instant, SystemTime};
 
 const DEFAULT_CHAIN_TIMEOUT: u64 = 300;
+const DEFAULT_MAX_OUTPUT_BYTES: u64 = 4 * 1024 * 1024;
+#[cfg(feature = "exec")]
+const STATUS_OK: &str = "ok";
+const STATUS_NOK: &str = "nok";
+#[cfg(feature = "exec")]
+const STATUS_PARTIAL: &str = "partial";
+#[cfg(feature = "exec")]
+const STATUS_CANCELLED: &str = "cancelled";
+const STATUS_DRY_RUN: &str = "dry-run";
+/// Environment variable holding the run id of the chain run, set for every step
+pub const RUN_ID_ENV: &str = "ATENTO_RUN_ID";
+const STEP_EXIT_CODE: &str = "exit_code";
+const STEP_STDOUT: &str = "stdout";
+const STEP_STDERR: &str = "stderr";
 
 // Helper function to provide the custom default for serde
 fn default_chain_timeout() -> u64 {
     DEFAULT_CHAIN_TIMEOUT
 }
 
-#[derive(Debug, Deserialize)]
+fn default_max_output_bytes() -> u64 {
+    DEFAULT_MAX_OUTPUT_BYTES
+}
+
+fn default_fail_fast() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Deserialize)]
 #[serde(from = "ChainHelper")]
+#[allow(clippy::struct_excessive_bools)]
 pub struct Chain {
     pub name: Option<String>,
+    /// What the chain does, for people reading it; not used when running it
+    pub description: Option<String>,
+    /// Free-form labels such as owner, ticket or environment, copied into the
+    /// `ChainResult` of every run
+    pub metadata: IndexMap<String, String>,
     pub timeout: u64,
     pub interpreters: HashMap<String, Interpreter>,
     pub parameters: HashMap<String, Parameter>,
+    pub env: HashMap<String, EnvValue>,
     pub steps: IndexMap<String, Step>,
-    pub results: HashMap<String, ResultRef>,
+    pub results: HashMap<String, ResultValue>,
+    /// Run independent steps concurrently when executed with [`Chain::run`]
+    pub parallel: bool,
+    /// Interpreter key used by steps that do not set `type`
+    pub default_interpreter: Option<String>,
+    /// Remote host that [`Chain::run`] executes the steps on over SSH
+    pub remote: Option<SshExecutor>,
+    /// Docker image that steps without their own `image` run in, with
+    /// `DockerExecutor` instead of the executor the chain is run with
+    pub image: Option<String>,
+    /// Check that step interpreters are available before [`Chain::run`] starts
+    pub preflight: bool,
+    /// Stop at the first step that fails (default); when `false` every step
+    /// runs and all errors are reported together
+    pub fail_fast: bool,
+    /// URL the final result is sent to as a JSON `POST`; needs the `http` feature
+    pub on_complete_webhook: Option<String>,
+    /// Directory that step artifact files must stay inside
+    pub artifacts_root: Option<String>,
+    /// Bytes of stdout and of stderr kept per step (default 4 MiB); `0` keeps everything
+    pub max_output_bytes: u64,
+    /// Signal sent to a step's processes when it times out, on Unix; steps
+    /// can override it
+    pub timeout_signal: TimeoutSignal,
+    /// Milliseconds a timed-out step gets to exit after `timeout_signal`
+    /// before it is killed with `SIGKILL`; steps can override it
+    pub timeout_grace_ms: u64,
+    /// Chain files merged into this one by [`Chain::load_with_includes`],
+    /// relative to this chain's file
+    pub include: Vec<String>,
+    /// Step definitions that steps instantiate with `template: <key>`
+    pub templates: HashMap<String, Step>,
+    /// Where the chain's keys are defined, for chains loaded from a file with
+    /// [`Chain::load_with_includes`]; validation errors then name the
+    /// location of the step or result they are about
+    pub source_map: Option<SourceMap>,
+    /// Accept keys that are not chain, step, output, parameter, input or
+    /// result fields instead of rejecting them, e.g. for chain files written
+    /// for a newer version
+    pub lenient: bool,
 }
 
 // Helper struct for deserialization
 #[derive(Deserialize)]
-struct ChainHelper {
+#[cfg_attr(
+    feature = "schema",
+    derive(schemars::JsonSchema),
+    schemars(rename = "Chain", deny_unknown_fields)
+)]
+#[allow(clippy::struct_excessive_bools)]
+pub(crate) struct ChainHelper {
     name: Option<String>,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    metadata: IndexMap<String, String>,
     #[serde(default = "default_chain_timeout")]
     timeout: u64,
     #[serde(default)]
@@ -39,9 +140,43 @@ struct ChainHelper {
     #[serde(default)]
     parameters: HashMap<String, Parameter>,
     #[serde(default)]
+    env: HashMap<String, EnvValue>,
+    #[serde(default)]
     steps: IndexMap<String, Step>,
     #[serde(default)]
-    results: HashMap<String, ResultRef>,
+    results: HashMap<String, ResultValue>,
+    #[serde(default)]
+    parallel: bool,
+    #[cfg_attr(
+        feature = "schema",
+        schemars(schema_with = "crate::schema::interpreter_key")
+    )]
+    #[serde(default)]
+    default_interpreter: Option<String>,
+    #[serde(default)]
+    remote: Option<SshExecutor>,
+    #[serde(default)]
+    image: Option<String>,
+    #[serde(default)]
+    preflight: bool,
+    #[serde(default = "default_fail_fast")]
+    fail_fast: bool,
+    #[serde(default)]
+    on_complete_webhook: Option<String>,
+    #[serde(default)]
+    artifacts_root: Option<String>,
+    #[serde(default = "default_max_output_bytes")]
+    max_output_bytes: u64,
+    #[serde(default)]
+    timeout_signal: TimeoutSignal,
+    #[serde(default)]
+    timeout_grace_ms: u64,
+    #[serde(default)]
+    include: Vec<String>,
+    #[serde(default)]
+    templates: HashMap<String, Step>,
+    #[serde(default)]
+    lenient: bool,
 }
 
 impl From<ChainHelper> for Chain {
@@ -53,21 +188,253 @@ impl From<ChainHelper> for Chain {
         // Override with user-provided interpreters
         interpreters.extend(helper.interpreters);
 
-        Chain {
+        let mut chain = Chain {
             name: helper.name,
+            description: helper.description,
+            metadata: helper.metadata,
             timeout: helper.timeout,
             interpreters,
             parameters: helper.parameters,
+            env: helper.env,
             steps: helper.steps,
             results: helper.results,
+            parallel: helper.parallel,
+            default_interpreter: helper.default_interpreter,
+            remote: helper.remote,
+            image: helper.image,
+            preflight: helper.preflight,
+            fail_fast: helper.fail_fast,
+            on_complete_webhook: helper.on_complete_webhook,
+            artifacts_root: helper.artifacts_root,
+            max_output_bytes: helper.max_output_bytes,
+            timeout_signal: helper.timeout_signal,
+            timeout_grace_ms: helper.timeout_grace_ms,
+            include: helper.include,
+            templates: helper.templates,
+            source_map: None,
+            lenient: helper.lenient,
+        };
+        chain.instantiate_templates();
+        chain.expand_matrices();
+        chain
+    }
+}
+
+/// Bookkeeping shared by the sequential and parallel runners.
+#[cfg(feature = "exec")]
+#[derive(Default)]
+#[allow(clippy::struct_excessive_bools)]
+struct RunState {
+    resolved_outputs: HashMap<String, String>,
+    step_results: IndexMap<String, StepResult>,
+    chain_errors: Vec<AtentoError>,
+    aborted: bool,
+    tolerated_failure: bool,
+    /// Steps keep running after failures, for chains with `fail_fast: false`
+    keep_going: bool,
+    /// A step failed without `continue_on_error` while the run kept going
+    failed: bool,
+    cancelled: bool,
+    /// Steps left out of a [`Chain::run_subset`] run, whose outputs results
+    /// may not find
+    skipped: Vec<String>,
+}
+
+#[cfg(feature = "exec")]
+impl RunState {
+    /// Stores a finished step's outputs and masked result, recording its error if any.
+    fn record_step_result(
+        &mut self,
+        step_name: &str,
+        step: &Step,
+        mut step_result: StepResult,
+        secrets: &[String],
+    ) {
+        let step_error =
+            Chain::process_step_result(step_name, &step_result, &mut self.resolved_outputs);
+        let cancelled = step_result
+            .error
+            .clone()
+            .filter(|err| matches!(err, AtentoError::Cancelled { .. }));
+        step_result.mask_secrets(secrets);
+        self.step_results.insert(step_name.to_string(), step_result);
+
+        if let Some(err) = cancelled {
+            self.record_cancellation(err);
+        } else if let Some(err) = step_error {
+            self.record_step_error(step, err);
+        }
+    }
+
+    /// Records that the run was cancelled, which aborts it whatever the
+    /// steps' `continue_on_error`.
+    fn record_cancellation(&mut self, err: AtentoError) {
+        self.chain_errors.push(err);
+        self.cancelled = true;
+        self.aborted = true;
+    }
+
+    /// Records a step error; the run is aborted unless the step sets
+    /// `continue_on_error` or the chain `fail_fast: false`.
+    fn record_step_error(&mut self, step: &Step, err: AtentoError) {
+        self.chain_errors.push(err);
+        if step.continue_on_error {
+            self.tolerated_failure = true;
+        } else if self.keep_going {
+            self.failed = true;
+        } else {
+            self.aborted = true;
+        }
+    }
+}
+
+// Values fixed for the whole run, shared by every step
+#[cfg(feature = "exec")]
+struct RunContext<'a> {
+    /// Identifies this run in the `ChainResult` and in [`RUN_ID_ENV`]
+    run_id: String,
+    start_time: Instant,
+    /// Wall-clock time the run started, recorded in the `ChainResult`
+    started_at: SystemTime,
+    parameters: &'a HashMap<String, String>,
+    secrets: &'a [String],
+    /// Set for runs started with [`Chain::run_with_cancellation`]
+    cancel: Option<CancellationToken>,
+}
+
+#[cfg(feature = "exec")]
+impl<'a> RunContext<'a> {
+    /// Starts the clocks of a run.
+    fn new(parameters: &'a HashMap<String, String>, secrets: &'a [String]) -> Self {
+        Self {
+            run_id: uuid::Uuid::new_v4().to_string(),
+            start_time: Instant::now(),
+            started_at: SystemTime::now(),
+            parameters,
+            secrets,
+            cancel: None,
+        }
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.cancel
+            .as_ref()
+            .is_some_and(CancellationToken::is_cancelled)
+    }
+}
+
+// Everything a step needs before it can run
+#[cfg(feature = "exec")]
+struct PreparedStep<'a> {
+    inputs: HashMap<String, String>,
+    env: HashMap<String, String>,
+    interpreter: &'a Interpreter,
+    /// Items of a `foreach` step
+    items: Option<Vec<String>>,
+}
+
+// Progress of a `foreach` step through its items
+#[cfg(feature = "exec")]
+struct ForeachRun<'a> {
+    items: &'a [String],
+    time_left: u64,
+    start_time: Instant,
+    started_at: SystemTime,
+    iterations: Vec<StepResult>,
+    /// A failed iteration stopped the remaining ones
+    stopped: bool,
+    timed_out: bool,
+    cancelled: Option<AtentoError>,
+}
+
+#[cfg(feature = "exec")]
+impl<'a> ForeachRun<'a> {
+    fn new(items: &'a [String], time_left: u64) -> Self {
+        Self {
+            items,
+            time_left,
+            start_time: Instant::now(),
+            started_at: SystemTime::now(),
+            iterations: Vec::new(),
+            stopped: false,
+            timed_out: false,
+            cancelled: None,
+        }
+    }
+
+    /// Returns the inputs of the next iteration and the chain time left for
+    /// it, or `None` once every item ran, an iteration stopped the step or the
+    /// chain ran out of time.
+    fn next_item(
+        &mut self,
+        inputs: &HashMap<String, String>,
+    ) -> Option<(HashMap<String, String>, u64)> {
+        let item = self
+            .items
+            .get(self.iterations.len())
+            .filter(|_| !self.stopped)?;
+        let remaining = self
+            .time_left
+            .saturating_sub(self.start_time.elapsed().as_secs());
+        if self.time_left > 0 && remaining == 0 {
+            self.timed_out = true;
+            return None;
+        }
+
+        let mut inputs = inputs.clone();
+        inputs.insert(step::FOREACH_ITEM_INPUT.to_string(), item.clone());
+        Some((inputs, remaining))
+    }
+
+    /// Records the result of an iteration. A failing iteration stops the
+    /// remaining ones unless the step sets `continue_on_error`; a cancelled
+    /// one always does.
+    fn record(&mut self, step: &Step, result: StepResult) {
+        let failed = result.error.is_some();
+        if let Some(err @ AtentoError::Cancelled { .. }) = &result.error {
+            self.cancelled = Some(err.clone());
+        }
+        self.iterations.push(result);
+        self.stopped = failed && (!step.continue_on_error || self.cancelled.is_some());
+    }
+
+    /// Combines the iterations into the result of the step.
+    fn finish(self, step: &Step, prepared: &PreparedStep<'_>) -> StepResult {
+        let mut result = step.combine_iterations(
+            &prepared.inputs,
+            &prepared.env,
+            self.iterations,
+            self.start_time.elapsed().as_millis(),
+        );
+        result.started_at = Some(rfc3339_timestamp(self.started_at));
+        result.finished_at = Some(rfc3339_timestamp(SystemTime::now()));
+        if self.timed_out && result.error.is_none() {
+            result.error = Some(AtentoError::Timeout {
+                context: format!(
+                    "Chain timed out after {} of {} items",
+                    result.iterations.len(),
+                    self.items.len()
+                ),
+                timeout_secs: self.time_left,
+            });
         }
+        if self.cancelled.is_some() {
+            result.error = self.cancelled;
+        }
+        result
     }
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ChainResult {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub name: Option<String>,
+    /// UUID v4 generated when the run starts; not set for dry runs
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub run_id: Option<String>,
+    /// The chain's `metadata`, as declared
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    pub metadata: IndexMap<String, String>,
     pub duration_ms: u128,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub parameters: Option<HashMap<String, String>>,
@@ -75,249 +442,2244 @@ pub struct ChainResult {
     pub steps: Option<IndexMap<String, StepResult>>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub results: Option<HashMap<String, String>>,
-    #[serde(skip_serializing_if = "Vec::is_empty")]
+    /// When the run started, as an RFC 3339 UTC timestamp with milliseconds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub started_at: Option<String>,
+    /// When the run finished
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub finished_at: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub errors: Vec<AtentoError>,
     pub status: String,
 }
 
+/// A step whose interpreter cannot run on this host, see [`Chain::check_interpreters`].
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MissingInterpreter {
+    pub step: String,
+    /// Key of the interpreter the step runs with
+    pub interpreter: String,
+    /// Command of that interpreter
+    pub command: String,
+}
+
+/// Outcome of [`Chain::validate_detailed`].
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct ValidationReport {
+    /// Problems that prevent the chain from running
+    pub errors: Vec<AtentoError>,
+    /// Suspicious configuration the chain still runs with
+    pub warnings: Vec<String>,
+}
+
+impl ValidationReport {
+    /// Returns whether the chain has no errors; warnings are allowed.
+    #[must_use]
+    pub fn is_valid(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
+impl std::fmt::Display for MissingInterpreter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "interpreter '{}' for step '{}' not found on PATH",
+            self.command, self.step
+        )
+    }
+}
+
 impl Default for Chain {
     fn default() -> Self {
         Self {
             name: None,
+            description: None,
+            metadata: IndexMap::new(),
             timeout: default_chain_timeout(),
             parameters: HashMap::new(),
             interpreters: HashMap::new(),
+            env: HashMap::new(),
             steps: IndexMap::new(),
             results: HashMap::new(),
+            parallel: false,
+            default_interpreter: None,
+            remote: None,
+            image: None,
+            preflight: false,
+            fail_fast: true,
+            on_complete_webhook: None,
+            artifacts_root: None,
+            max_output_bytes: default_max_output_bytes(),
+            timeout_signal: TimeoutSignal::default(),
+            timeout_grace_ms: 0,
+            include: Vec::new(),
+            templates: HashMap::new(),
+            source_map: None,
+            lenient: false,
         }
     }
 }
 
+impl FromStr for Chain {
+    type Err = AtentoError;
+
+    /// Parses a chain from a YAML string.
+    ///
+    /// Default interpreters are merged in exactly as for file-loaded chains,
+    /// and unknown fields are rejected unless the chain sets `lenient: true`.
+    fn from_str(yaml: &str) -> Result<Self> {
+        parse_chain(yaml, "<string>", None)
+    }
+}
+
+/// Parses the chain document `yaml`, read from `context`. Unless it sets
+/// `lenient: true`, keys that match no field are a `Validation` error listing
+/// each of them with the closest field name and, with `sources`, its location.
+///
+/// Deserializing a [`Chain`] directly with serde skips the unknown field check.
+pub(crate) fn parse_chain(yaml: &str, context: &str, sources: Option<&SourceMap>) -> Result<Chain> {
+    let document: serde_yaml::Value =
+        serde_yaml::from_str(yaml).map_err(|e| AtentoError::yaml_parse(context, e))?;
+    check_fields(&document, context, sources)?;
+    serde_yaml::from_str(yaml).map_err(|e| AtentoError::yaml_parse(context, e))
+}
+
+/// Rejects the keys of the chain `document` that match no field, unless it
+/// sets `lenient: true`.
+pub(crate) fn check_fields(
+    document: &serde_yaml::Value,
+    context: &str,
+    sources: Option<&SourceMap>,
+) -> Result<()> {
+    if fields::is_lenient(document) {
+        return Ok(());
+    }
+    let unknown = fields::unknown_fields(document);
+    if unknown.is_empty() {
+        return Ok(());
+    }
+    let described: Vec<String> = unknown
+        .iter()
+        .map(|field| field.describe(sources))
+        .collect();
+    Err(AtentoError::Validation(format!(
+        "Unknown fields in '{context}': {}; set 'lenient: true' to ignore them",
+        described.join(", ")
+    )))
+}
+
+/// Describes a dependency cycle among the `pending` steps, none of which has
+/// all its dependencies `placed`, by following unplaced dependencies from the
+/// first of them until a step repeats.
+fn dependency_cycle(
+    pending: &[&String],
+    dependencies: &HashMap<&str, HashSet<&str>>,
+    placed: &HashSet<&str>,
+) -> AtentoError {
+    let mut path: Vec<&str> = Vec::new();
+    let mut current = pending.first().map_or("", |step| step.as_str());
+    while !path.contains(&current) {
+        path.push(current);
+        // Follow the earliest declared dependency, so the reported cycle is stable
+        let Some(next) = pending
+            .iter()
+            .map(|step| step.as_str())
+            .find(|step| dependencies[current].contains(step) && !placed.contains(step))
+        else {
+            break;
+        };
+        current = next;
+    }
+    let start = path.iter().position(|step| *step == current).unwrap_or(0);
+    let mut cycle = path.split_off(start);
+    cycle.push(current);
+    AtentoError::Validation(format!("Step dependency cycle: {}", cycle.join(" -> ")))
+}
+
+/// Removes and returns the first pending step whose dependencies have all finished.
+fn next_ready_step<'a>(
+    pending: &mut Vec<&'a String>,
+    dependencies: &HashMap<&str, HashSet<&str>>,
+    finished: &HashSet<&str>,
+) -> Option<&'a String> {
+    let position = pending.iter().position(|step_name| {
+        dependencies[step_name.as_str()]
+            .iter()
+            .all(|dep| finished.contains(dep))
+    })?;
+    Some(pending.remove(position))
+}
+
+/// Checks an inline input value against its declared `DataType`; references are
+/// checked once resolved.
+fn validate_inline_value(input: &Input, context: impl Fn() -> String) -> Result<()> {
+    match input {
+        Input::Inline { type_, value, .. } => type_
+            .validate_value(value)
+            .map_err(|e| with_value_context(e, context)),
+        Input::Env { env, .. } if env.trim().is_empty() => Err(AtentoError::Validation(format!(
+            "{} names no environment variable",
+            context()
+        ))),
+        Input::Ref { .. } | Input::Env { .. } => Ok(()),
+    }
+}
+
+/// Checks that an env var name can be set in a POSIX shell, which it must be
+/// for steps that run remotely.
+fn validate_env_name(env_key: &str, step_key: &str) -> Result<()> {
+    if is_env_name(env_key) {
+        Ok(())
+    } else {
+        Err(AtentoError::Validation(format!(
+            "Env var '{env_key}' of step '{step_key}' is not a valid name; it must match [A-Za-z_][A-Za-z0-9_]*"
+        )))
+    }
+}
+
+/// Names the offending value in a `TypeConversion` error.
+fn with_value_context(err: AtentoError, context: impl Fn() -> String) -> AtentoError {
+    match err {
+        AtentoError::TypeConversion { expected, got } => AtentoError::TypeConversion {
+            expected,
+            got: format!("{got} for {}", context()),
+        },
+        other => other,
+    }
+}
+
 impl Chain {
-    fn make_output_key(step_key: &str, output_key: &str) -> String {
+    /// Parses a chain from any YAML reader.
+    ///
+    /// Default interpreters are merged in exactly as for file-loaded chains,
+    /// and unknown fields are rejected unless the chain sets `lenient: true`.
+    ///
+    /// # Errors
+    /// Returns `AtentoError::Io` if the content cannot be read,
+    /// `AtentoError::YamlParse` if it cannot be parsed and
+    /// `AtentoError::Validation` for unknown fields.
+    pub fn from_reader<R: std::io::Read>(reader: R) -> Result<Self> {
+        let yaml = std::io::read_to_string(reader).map_err(|e| AtentoError::Io {
+            path: "<reader>".to_string(),
+            source: e,
+        })?;
+        parse_chain(&yaml, "<reader>", None)
+    }
+
+    /// Loads the `script_file` of every step and template into its `script`,
+    /// resolving relative paths against `base_dir`, normally the directory of
+    /// the chain file. Chains loaded with [`Chain::load_with_includes`] are
+    /// already resolved.
+    ///
+    /// # Errors
+    /// Returns a `Validation` error for a step that sets both `script` and
+    /// `script_file`, and an `Io` error naming a script file that cannot be read.
+    pub fn resolve_script_files(&mut self, base_dir: impl AsRef<Path>) -> Result<()> {
+        let base_dir = base_dir.as_ref();
+        for (id, step) in &mut self.steps {
+            step.load_script_file(&format!("Step '{id}'"), base_dir)?;
+        }
+        for (key, template) in &mut self.templates {
+            template.load_script_file(&format!("Template '{key}'"), base_dir)?;
+        }
+        Ok(())
+    }
+
+    pub(crate) fn make_output_key(step_key: &str, output_key: &str) -> String {
         format!("steps.{step_key}.outputs.{output_key}")
     }
 
+    fn make_artifact_key(step_key: &str, artifact_key: &str) -> String {
+        format!("steps.{step_key}.artifacts.{artifact_key}")
+    }
+
+    // Implicit step references (`steps.X.exit_code`, `steps.X.stdout`, `steps.X.stderr`)
+    fn make_step_key(step_key: &str, field: &str) -> String {
+        format!("steps.{step_key}.{field}")
+    }
+
+    /// Returns every reference key a step makes available once it has run.
+    fn step_ref_keys(step_key: &str, step: &Step) -> Vec<String> {
+        [STEP_EXIT_CODE, STEP_STDOUT, STEP_STDERR]
+            .iter()
+            .map(|field| Self::make_step_key(step_key, field))
+            .chain(
+                step.outputs
+                    .keys()
+                    .map(|out_name| Self::make_output_key(step_key, out_name)),
+            )
+            .chain(
+                step.artifacts
+                    .keys()
+                    .map(|name| Self::make_artifact_key(step_key, name)),
+            )
+            .collect()
+    }
+
+    /// Checks that a step has a script and that its `script_file`, if any,
+    /// has been loaded.
+    fn validate_script_source(step_key: &str, step: &Step) -> Result<()> {
+        if step.script_file.is_some() {
+            return Err(AtentoError::Validation(if step.script.is_empty() {
+                format!(
+                    "Step '{step_key}' has a 'script_file' that was not loaded; call Chain::resolve_script_files"
+                )
+            } else {
+                format!("Step '{step_key}' sets both 'script' and 'script_file'")
+            }));
+        }
+        if step.script.trim().is_empty() {
+            return Err(AtentoError::Validation(format!(
+                "Step '{step_key}' has no 'script' or 'script_file'"
+            )));
+        }
+        Ok(())
+    }
+
+    /// Checks that `on_complete_webhook` is an HTTP(S) URL and can be sent.
+    fn validate_webhook(&self) -> Result<()> {
+        let Some(url) = &self.on_complete_webhook else {
+            return Ok(());
+        };
+        if !(url.starts_with("http://") || url.starts_with("https://")) {
+            return Err(AtentoError::Validation(format!(
+                "'on_complete_webhook' must be an http:// or https:// URL, got '{url}'"
+            )));
+        }
+        if cfg!(not(feature = "http")) {
+            return Err(AtentoError::Validation(
+                "'on_complete_webhook' needs atento-core built with the 'http' feature".to_string(),
+            ));
+        }
+        Ok(())
+    }
+
+    /// POSTs the final result to `on_complete_webhook`, if set.
+    ///
+    /// Delivery is best effort: a failure never changes the result and is only
+    /// reported as a warning event with the `tracing` feature.
+    #[cfg(feature = "http")]
+    fn notify_webhook(&self, result: &ChainResult) {
+        let Some(url) = &self.on_complete_webhook else {
+            return;
+        };
+        let delivery = crate::webhook::post_result(url, result);
+        #[cfg(feature = "tracing")]
+        if let Err(e) = delivery {
+            tracing::warn!(error = %e, "webhook delivery failed");
+        }
+        #[cfg(not(feature = "tracing"))]
+        let _ = delivery;
+    }
+
     /// Validates the chain structure.
     ///
+    /// Findings that [`Chain::validate_detailed`] reports as warnings, such as
+    /// unused inputs, are errors here.
+    ///
     /// # Errors
     /// Returns validation errors for unresolved references, forward references, or invalid patterns.
     pub fn validate(&self) -> Result<()> {
+        self.check(&mut Diagnostics::new(
+            Stop::AtFirstFinding,
+            self.source_map.as_ref(),
+        ))
+    }
+
+    /// Validates the chain, separating problems that prevent it from running
+    /// from warnings about suspicious but runnable configuration, such as an
+    /// input that is declared but never used.
+    ///
+    /// Checking stops at the first error, so `errors` holds at most one entry
+    /// and warnings are only gathered up to that point. See
+    /// [`Chain::validate_all`] for every finding.
+    #[must_use]
+    pub fn validate_detailed(&self) -> ValidationReport {
+        let mut diagnostics = Diagnostics::new(Stop::AtFirstError, self.source_map.as_ref());
+        let errors = self.check(&mut diagnostics).err().into_iter().collect();
+        ValidationReport {
+            errors,
+            warnings: diagnostics
+                .findings
+                .into_iter()
+                .map(|finding| finding.message)
+                .collect(),
+        }
+    }
+
+    /// Runs the validation checks, recording their findings in `diagnostics`.
+    ///
+    /// # Errors
+    /// Returns the finding at which `diagnostics` stops the checks.
+    pub(crate) fn check(&self, diagnostics: &mut Diagnostics<'_>) -> Result<()> {
+        diagnostics.check(
+            "invalid-parameter",
+            Subject::Chain,
+            self.validate_parameter_values(),
+        )?;
+        diagnostics.check("invalid-config", Subject::Chain, self.validate_settings())?;
+
+        for (step_key, step) in &self.steps {
+            diagnostics.check(
+                "invalid-template",
+                Subject::Step(step_key),
+                self.validate_template(step_key, step),
+            )?;
+        }
+        for (step_key, step) in &self.steps {
+            diagnostics.check(
+                "invalid-matrix",
+                Subject::Step(step_key),
+                self.validate_matrix(step_key, step),
+            )?;
+        }
+
         let parameter_keys: HashSet<String> = self
             .parameters
             .keys()
             .map(|k| format!("parameters.{k}"))
             .collect();
-
         let mut step_output_keys = HashSet::new();
+        for (step_key, step) in &self.steps {
+            self.check_step(
+                step_key,
+                step,
+                &parameter_keys,
+                &step_output_keys,
+                diagnostics,
+            )?;
+            step_output_keys.extend(Self::step_ref_keys(step_key, step));
+        }
+
+        diagnostics.check("dependency-cycle", Subject::Chain, self.execution_order())?;
+
+        let mut result_keys: Vec<&String> = self.results.keys().collect();
+        result_keys.sort();
+        for result_key in result_keys {
+            diagnostics.check_reference(
+                "invalid-value",
+                Subject::Result(result_key),
+                self.validate_result(result_key, &step_output_keys),
+            )?;
+        }
 
         for (step_key, step) in &self.steps {
-            for (input_key, input) in &step.inputs {
-                if let Input::Ref { ref_ } = input
-                    && !parameter_keys.contains(ref_)
-                    && !step_output_keys.contains(ref_)
-                {
-                    let forward_decl = self
-                        .steps
-                        .keys()
-                        .skip_while(|k| *k != step_key)
-                        .skip(1)
-                        .any(|k| {
-                            self.steps[k]
-                                .outputs
-                                .keys()
-                                .any(|out_name| Self::make_output_key(k, out_name) == *ref_)
-                        });
+            diagnostics.check(
+                "invalid-artifact",
+                Subject::Step(step_key),
+                self.validate_artifacts(step_key, step),
+            )?;
+        }
 
-                    if forward_decl {
-                        return Err(AtentoError::Validation(format!(
-                            "Input '{input_key}' in step '{step_key}' references '{ref_}', which is a future step output"
-                        )));
-                    }
+        let handlers = self.failure_handlers();
+        let positions: HashMap<&str, usize> = self
+            .ordered_steps()
+            .into_iter()
+            .enumerate()
+            .map(|(position, step_key)| (step_key.as_str(), position))
+            .collect();
+        for (step_key, step) in &self.steps {
+            diagnostics.check(
+                "invalid-failure-handler",
+                Subject::Step(step_key),
+                self.validate_failure_handler(step_key, step, &handlers, &positions),
+            )?;
+        }
 
-                    return Err(AtentoError::UnresolvedReference {
-                        reference: ref_.clone(),
-                        context: format!("step '{step_key}'"),
-                    });
-                }
+        Ok(())
+    }
+
+    /// Checks the chain-level settings: `remote`, `image`,
+    /// `on_complete_webhook`, `include` and `default_interpreter`.
+    fn validate_settings(&self) -> Result<()> {
+        if let Some(remote) = &self.remote {
+            if remote.host.trim().is_empty() {
+                return Err(AtentoError::Validation(
+                    "The 'remote' section needs a non-empty 'host'".to_string(),
+                ));
+            }
+            if let Some(field) = [("host", Some(&remote.host)), ("user", remote.user.as_ref())]
+                .into_iter()
+                .find_map(|(field, value)| value.filter(|v| v.starts_with('-')).map(|_| field))
+            {
+                return Err(AtentoError::Validation(format!(
+                    "The 'remote' {field} cannot start with '-'"
+                )));
             }
+        }
 
-            step.validate(step_key)?;
+        if self
+            .image
+            .as_ref()
+            .is_some_and(|image| image.trim().is_empty())
+        {
+            return Err(AtentoError::Validation(
+                "The chain has an empty 'image'".to_string(),
+            ));
+        }
 
-            for (out_key, out) in &step.outputs {
-                if out.pattern.is_empty() {
-                    return Err(AtentoError::Validation(format!(
-                        "Output '{out_key}' in step '{step_key}' has empty capture pattern"
-                    )));
-                }
+        self.validate_webhook()?;
 
-                step_output_keys.insert(Self::make_output_key(step_key, out_key));
-            }
+        if !self.include.is_empty() {
+            return Err(AtentoError::Validation(format!(
+                "The chain includes {}; load it with Chain::load_with_includes",
+                self.include.join(", ")
+            )));
         }
 
-        for (result_key, result) in &self.results {
-            if !step_output_keys.contains(&result.ref_) {
-                return Err(AtentoError::UnresolvedReference {
-                    reference: result.ref_.clone(),
-                    context: format!("chain result '{result_key}'"),
-                });
-            }
+        if let Some(default) = &self.default_interpreter
+            && !self.interpreters.contains_key(default)
+        {
+            return Err(AtentoError::Validation(format!(
+                "Unknown default_interpreter '{default}'; it must be a default interpreter or one declared under 'interpreters'"
+            )));
         }
 
         Ok(())
     }
 
-    fn resolve_input(
+    /// Checks a single step, given the reference keys of the steps declared
+    /// before it.
+    fn check_step(
         &self,
-        input_name: &str,
-        input: &Input,
-        step_name: &str,
-        resolved_outputs: &HashMap<String, String>,
-    ) -> Result<String> {
-        match input {
-            Input::Inline { .. } => input.to_string_value().map_err(|e| {
-                AtentoError::Execution(format!("Input '{input_name}' in step '{step_name}': {e}"))
-            }),
+        step_key: &str,
+        step: &Step,
+        parameter_keys: &HashSet<String>,
+        earlier_keys: &HashSet<String>,
+        diagnostics: &mut Diagnostics<'_>,
+    ) -> Result<()> {
+        let subject = Subject::Step(step_key);
+        diagnostics.check(
+            "missing-script",
+            subject,
+            Self::validate_script_source(step_key, step),
+        )?;
+        diagnostics.check(
+            "invalid-needs",
+            subject,
+            self.validate_needs(step_key, step),
+        )?;
 
-            Input::Ref { ref_ } => {
-                let param_key = ref_.strip_prefix("parameters.").unwrap_or(ref_);
+        if let Some(interpreter) = diagnostics.check(
+            "unknown-interpreter",
+            subject,
+            self.step_interpreter(step, step_key),
+        )? && let Some(extension) = step.quote.extension()
+            && interpreter.extension != extension
+        {
+            diagnostics.check::<()>(
+                "interpreter-mismatch",
+                subject,
+                Err(AtentoError::Validation(format!(
+                    "Step '{step_key}' quotes values for '{extension}' scripts, but its interpreter runs '{}' scripts",
+                    interpreter.extension
+                ))),
+            )?;
+        }
 
-                if let Some(param) = self.parameters.get(param_key) {
-                    param.to_string_value().map_err(|e| {
-                        AtentoError::Execution(format!(
-                            "Parameter '{input_name}' in step '{step_name}': {e}"
-                        ))
-                    })
-                } else if let Some(output) = resolved_outputs.get(ref_) {
-                    Ok(output.clone())
-                } else {
-                    Err(AtentoError::UnresolvedReference {
-                        reference: ref_.clone(),
-                        context: format!("step '{step_name}'"),
-                    })
-                }
+        let available = self.needed_ref_keys(step, earlier_keys);
+        self.check_step_references(step_key, step, parameter_keys, &available, diagnostics)?;
+
+        let step_name = step.name.as_deref().unwrap_or(step_key);
+        let mut warnings = Vec::new();
+        diagnostics.check(
+            "invalid-step",
+            subject,
+            step.validate_definition(step_name, &mut warnings),
+        )?;
+        for warning in warnings {
+            diagnostics.warn("unused-input", subject, warning)?;
+        }
+        let mut outputs: Vec<_> = step.outputs.iter().collect();
+        outputs.sort_by_key(|(out_name, _)| out_name.as_str());
+        for (out_name, out) in outputs {
+            if diagnostics
+                .check(
+                    "invalid-output",
+                    subject,
+                    Step::validate_output(out_name, out, step_name),
+                )?
+                .is_some()
+            {
+                diagnostics.check(
+                    "invalid-regex",
+                    subject,
+                    Step::validate_output_pattern(out_name, out, step_name),
+                )?;
             }
         }
+
+        Ok(())
     }
 
-    fn check_timeout(&self, start_time: &Instant, step_name: &str) -> Result<u64> {
-        if self.timeout == 0 {
-            return Ok(0);
+    /// Checks the inline values and references of a step's inputs, `foreach`
+    /// list, placeholders, env entries and `stdin` against the parameters and
+    /// the reference keys in `available`.
+    fn check_step_references(
+        &self,
+        step_key: &str,
+        step: &Step,
+        parameter_keys: &HashSet<String>,
+        available: &HashSet<String>,
+        diagnostics: &mut Diagnostics<'_>,
+    ) -> Result<()> {
+        let subject = Subject::Step(step_key);
+        let mut inputs: Vec<_> = step.inputs.iter().collect();
+        inputs.sort_by_key(|(input_key, _)| input_key.as_str());
+        for (input_key, input) in inputs {
+            diagnostics.check(
+                "invalid-value",
+                subject,
+                validate_inline_value(input, || {
+                    format!("input '{input_key}' of step '{step_key}'")
+                }),
+            )?;
+            diagnostics.check_reference(
+                "forward-reference",
+                subject,
+                self.validate_ref(
+                    input,
+                    &format!("Input '{input_key}'"),
+                    step_key,
+                    parameter_keys,
+                    available,
+                ),
+            )?;
         }
 
-        let elapsed = start_time.elapsed().as_secs();
-        if elapsed >= self.timeout {
-            return Err(AtentoError::Timeout {
-                context: format!("Chain timed out before step '{step_name}'"),
-                timeout_secs: self.timeout,
-            });
+        if let Some(foreach) = &step.foreach {
+            diagnostics.check_reference(
+                "forward-reference",
+                subject,
+                self.validate_ref(
+                    &Input::Ref {
+                        ref_: foreach.clone(),
+                    },
+                    "Foreach list",
+                    step_key,
+                    parameter_keys,
+                    available,
+                ),
+            )?;
         }
 
-        Ok(self.timeout.saturating_sub(elapsed))
+        diagnostics.check_reference(
+            "forward-reference",
+            subject,
+            self.validate_placeholders(step, step_key, parameter_keys, available),
+        )?;
+
+        let mut env: Vec<_> = self.step_env(step).into_iter().collect();
+        env.sort_by_key(|(env_key, _)| env_key.as_str());
+        for (env_key, env) in env {
+            diagnostics.check(
+                "invalid-value",
+                subject,
+                validate_env_name(env_key, step_key),
+            )?;
+            match env {
+                EnvValue::Input(input) => {
+                    diagnostics.check(
+                        "invalid-value",
+                        subject,
+                        validate_inline_value(input, || {
+                            format!("env var '{env_key}' of step '{step_key}'")
+                        }),
+                    )?;
+                    diagnostics.check_reference(
+                        "forward-reference",
+                        subject,
+                        self.validate_ref(
+                            input,
+                            &format!("Env var '{env_key}'"),
+                            step_key,
+                            parameter_keys,
+                            available,
+                        ),
+                    )?;
+                }
+                EnvValue::Template(template) => {
+                    diagnostics.check_reference(
+                        "undeclared-input",
+                        subject,
+                        self.validate_env_template(env_key, template, step, step_key),
+                    )?;
+                }
+            }
+        }
+
+        if let Some(EnvValue::Input(stdin)) = &step.stdin {
+            diagnostics.check(
+                "invalid-value",
+                subject,
+                validate_inline_value(stdin, || format!("stdin of step '{step_key}'")),
+            )?;
+            diagnostics.check_reference(
+                "forward-reference",
+                subject,
+                self.validate_ref(stdin, "Stdin", step_key, parameter_keys, available),
+            )?;
+        }
+
+        Ok(())
     }
 
-    fn resolve_step_inputs(
+    /// Checks that the `{{ parameters.x }}` and `{{ steps.x.outputs.y }}`
+    /// placeholders of a step name declared parameters and earlier step outputs.
+    fn validate_placeholders(
         &self,
         step: &Step,
-        step_name: &str,
-        resolved_outputs: &HashMap<String, String>,
-    ) -> Result<HashMap<String, String>> {
-        step.inputs
-            .iter()
-            .map(|(input_name, input)| {
-                self.resolve_input(input_name, input, step_name, resolved_outputs)
-                    .map(|val| (input_name.clone(), val))
-            })
-            .collect()
-    }
+        step_key: &str,
+        parameter_keys: &HashSet<String>,
+        step_output_keys: &HashSet<String>,
+    ) -> Result<()> {
+        for param in step.parameter_refs() {
+            if !self.parameters.contains_key(&param) {
+                return Err(AtentoError::UnresolvedReference {
+                    reference: format!("parameters.{param}"),
+                    context: format!("script of step '{step_key}'"),
+                });
+            }
+        }
 
-    fn lookup_interpreter(&self, step: &Step, step_name: &str) -> Result<&Interpreter> {
-        self.interpreters.get(&step.interpreter).ok_or_else(|| {
-            AtentoError::Validation(format!(
-                "Unknown interpreter '{}' in step '{}'",
-                step.interpreter, step_name
-            ))
+        for placeholder in step.step_refs() {
+            let ref_ = self
+                .placeholder_ref(step_key, &placeholder)
+                .ok_or_else(|| AtentoError::UnresolvedReference {
+                    reference: placeholder.clone(),
+                    context: format!("step '{step_key}', which has no previous step"),
+                })?;
+            self.validate_ref(
+                &Input::Ref { ref_ },
+                "Placeholder",
+                step_key,
+                parameter_keys,
+                step_output_keys,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Checks that every step in `needs` exists and is not the step itself.
+    fn validate_needs(&self, step_key: &str, step: &Step) -> Result<()> {
+        for needed in &step.needs {
+            if needed == step_key {
+                return Err(AtentoError::Validation(format!(
+                    "Step '{step_key}' cannot need itself"
+                )));
+            }
+            if !self.steps.contains_key(needed) {
+                return Err(AtentoError::Validation(format!(
+                    "Step '{step_key}' needs step '{needed}' that does not exist"
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Adds the reference keys of the steps `step` needs to the keys of the
+    /// steps declared before it, as both have run by the time it starts.
+    fn needed_ref_keys<'a>(
+        &self,
+        step: &Step,
+        earlier_keys: &'a HashSet<String>,
+    ) -> Cow<'a, HashSet<String>> {
+        if step.needs.is_empty() {
+            return Cow::Borrowed(earlier_keys);
+        }
+        let mut keys = earlier_keys.clone();
+        for needed in &step.needs {
+            if let Some(needed_step) = self.steps.get(needed) {
+                keys.extend(Self::step_ref_keys(needed, needed_step));
+            }
+        }
+        Cow::Owned(keys)
+    }
+
+    /// Checks that a step declares no artifacts on a remote chain, whose files
+    /// stay on the remote host, and that its artifact paths without
+    /// placeholders stay inside `artifacts_root` when one is set.
+    fn validate_artifacts(&self, step_key: &str, step: &Step) -> Result<()> {
+        if step.artifacts.is_empty() {
+            return Ok(());
+        }
+        if self.remote.is_some() {
+            return Err(AtentoError::Validation(format!(
+                "Step '{step_key}' declares 'artifacts', which are not supported with 'remote'"
+            )));
+        }
+        let Some(root) = &self.artifacts_root else {
+            return Ok(());
+        };
+        let workdir = step.workdir.as_deref().unwrap_or_default();
+        for (name, artifact) in &step.artifacts {
+            let path = Path::new(workdir).join(&artifact.path);
+            if !path.to_string_lossy().contains("{{") && !artifact::is_within(&path, root) {
+                return Err(AtentoError::Validation(format!(
+                    "Artifact '{name}' of step '{step_key}' at '{}' is outside the artifacts root '{root}'",
+                    path.display()
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks that the `on_failure` of a step names another existing step
+    /// without a handler of its own, whose references only reach steps that
+    /// run up to the failing step, and that the step does not depend on a
+    /// handler, which may never run. `positions` maps step keys to their
+    /// place in the execution order.
+    fn validate_failure_handler(
+        &self,
+        step_key: &str,
+        step: &Step,
+        handlers: &HashSet<&str>,
+        positions: &HashMap<&str, usize>,
+    ) -> Result<()> {
+        if let Some(handler) = self
+            .step_dependencies(step_key, step)
+            .into_iter()
+            .find(|dep| handlers.contains(dep))
+        {
+            return Err(AtentoError::Validation(format!(
+                "Step '{step_key}' references step '{handler}', which only runs as an 'on_failure' handler"
+            )));
+        }
+
+        let Some(handler_key) = &step.on_failure else {
+            return Ok(());
+        };
+        let Some(handler) = self.steps.get(handler_key) else {
+            return Err(AtentoError::Validation(format!(
+                "Step '{step_key}' has 'on_failure' step '{handler_key}' that does not exist"
+            )));
+        };
+        if handler_key == step_key {
+            return Err(AtentoError::Validation(format!(
+                "Step '{step_key}' cannot be its own 'on_failure' step"
+            )));
+        }
+        if handler.on_failure.is_some() {
+            return Err(AtentoError::Validation(format!(
+                "Step '{handler_key}' is the 'on_failure' step of '{step_key}' and cannot set 'on_failure' itself"
+            )));
+        }
+        if let Some(future) = self
+            .step_dependencies(handler_key, handler)
+            .into_iter()
+            .find(|dep| positions[dep] > positions[step_key])
+        {
+            return Err(AtentoError::Validation(format!(
+                "Step '{handler_key}' is the 'on_failure' step of '{step_key}' but references step '{future}', which has not run when '{step_key}' fails"
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Checks that the chain result `result_key` references a known parameter
+    /// or step output, or holds a valid inline value.
+    fn validate_result(&self, result_key: &str, step_output_keys: &HashSet<String>) -> Result<()> {
+        match &self.results[result_key] {
+            ResultValue::Ref(result_ref) => {
+                let known = match result_ref.ref_.strip_prefix("parameters.") {
+                    Some(param_key) => self.parameters.contains_key(param_key),
+                    None => step_output_keys.contains(&result_ref.ref_),
+                };
+
+                if !known {
+                    return Err(AtentoError::UnresolvedReference {
+                        reference: result_ref.ref_.clone(),
+                        context: format!("chain result '{result_key}'"),
+                    });
+                }
+            }
+            ResultValue::Inline { type_, value } => {
+                data_type::to_string_value(type_, value).map_err(|e| {
+                    AtentoError::Validation(format!("Chain result '{result_key}': {e}"))
+                })?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Checks that every parameter has a value matching its declared `DataType`.
+    fn validate_parameter_values(&self) -> Result<()> {
+        if let Some(err) = self.missing_parameters_error() {
+            return Err(err);
+        }
+
+        let mut keys: Vec<&String> = self.parameters.keys().collect();
+        keys.sort();
+
+        for key in keys {
+            let param = &self.parameters[key];
+            if let Some(value) = &param.value {
+                param
+                    .type_
+                    .validate_value(value)
+                    .map_err(|e| with_value_context(e, || format!("parameter '{key}'")))?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns an error listing every parameter without a value, if any.
+    fn missing_parameters_error(&self) -> Option<AtentoError> {
+        let mut missing: Vec<&str> = self
+            .parameters
+            .iter()
+            .filter(|(_, param)| param.value.is_none())
+            .map(|(key, _)| key.as_str())
+            .collect();
+
+        if missing.is_empty() {
+            return None;
+        }
+
+        missing.sort_unstable();
+        Some(AtentoError::Validation(format!(
+            "Missing value for parameters: {}; set a value or pass an override",
+            missing.join(", ")
+        )))
+    }
+
+    fn validate_ref(
+        &self,
+        input: &Input,
+        label: &str,
+        step_key: &str,
+        parameter_keys: &HashSet<String>,
+        step_output_keys: &HashSet<String>,
+    ) -> Result<()> {
+        let Input::Ref { ref_ } = input else {
+            return Ok(());
+        };
+
+        if parameter_keys.contains(ref_) || step_output_keys.contains(ref_) {
+            return Ok(());
+        }
+
+        let forward_decl = self
+            .steps
+            .keys()
+            .skip_while(|k| *k != step_key)
+            .skip(1)
+            .any(|k| Self::step_ref_keys(k, &self.steps[k]).contains(ref_));
+
+        if forward_decl {
+            return Err(AtentoError::Validation(format!(
+                "{label} in step '{step_key}' references '{ref_}', which is a future step output"
+            )));
+        }
+
+        Err(AtentoError::UnresolvedReference {
+            reference: ref_.clone(),
+            context: format!("step '{step_key}'"),
         })
     }
 
-    fn process_step_result(
+    fn validate_env_template(
+        &self,
+        env_key: &str,
+        template: &str,
+        step: &Step,
+        step_key: &str,
+    ) -> Result<()> {
+        if let Some(input) = step::required_input_placeholders(template)
+            .into_iter()
+            .find(|input| !step.inputs.contains_key(input))
+        {
+            return Err(AtentoError::Validation(format!(
+                "Env var '{env_key}' in step '{step_key}' references input '{input}' that is not declared; {}",
+                step::RAW_BLOCK_HINT
+            )));
+        }
+
+        if let Some(param) = step::parameter_placeholders(template)
+            .into_iter()
+            .find(|param| !self.parameters.contains_key(param))
+        {
+            return Err(AtentoError::UnresolvedReference {
+                reference: format!("parameters.{param}"),
+                context: format!("env var '{env_key}' of step '{step_key}'"),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Returns the effective environment of a step: chain-level entries
+    /// overridden by step-level entries.
+    fn step_env<'a>(&'a self, step: &'a Step) -> HashMap<&'a String, &'a EnvValue> {
+        self.env.iter().chain(step.env.iter()).collect()
+    }
+
+    /// Returns whether `reference` points at an output declared as `optional`.
+    fn is_optional_output(&self, reference: &str) -> bool {
+        self.steps.iter().any(|(step_key, step)| {
+            step.outputs.iter().any(|(out_key, out)| {
+                out.optional && Self::make_output_key(step_key, out_key) == reference
+            })
+        })
+    }
+
+    fn resolve_input(
+        &self,
+        input_name: &str,
+        input: &Input,
+        step_name: &str,
+        resolved_outputs: &HashMap<String, String>,
+    ) -> Result<String> {
+        match input {
+            Input::Inline { .. } => input.to_string_value().map_err(|e| {
+                AtentoError::Execution(format!("Input '{input_name}' in step '{step_name}': {e}"))
+            }),
+
+            Input::Env { env, .. } => input
+                .env_value()
+                .map_err(|e| {
+                    AtentoError::Execution(format!(
+                        "Input '{input_name}' in step '{step_name}': {e}"
+                    ))
+                })?
+                .ok_or_else(|| AtentoError::UnresolvedReference {
+                    reference: format!("environment variable '{env}'"),
+                    context: format!("input '{input_name}' of step '{step_name}'"),
+                }),
+
+            Input::Ref { ref_ } => {
+                let param_key = ref_.strip_prefix("parameters.").unwrap_or(ref_);
+
+                if let Some(param) = self.parameters.get(param_key) {
+                    param.to_string_value().map_err(|e| {
+                        AtentoError::Execution(format!(
+                            "Parameter '{input_name}' in step '{step_name}': {e}"
+                        ))
+                    })
+                } else if let Some(output) = resolved_outputs.get(ref_) {
+                    Ok(output.clone())
+                } else if self.is_optional_output(ref_) {
+                    Err(AtentoError::UnresolvedReference {
+                        reference: ref_.clone(),
+                        context: format!(
+                            "step '{step_name}' (optional output did not match and has no default)"
+                        ),
+                    })
+                } else {
+                    Err(AtentoError::UnresolvedReference {
+                        reference: ref_.clone(),
+                        context: format!("step '{step_name}'"),
+                    })
+                }
+            }
+        }
+    }
+
+    #[cfg(feature = "exec")]
+    fn check_timeout(&self, start_time: &Instant, step_name: &str) -> Result<u64> {
+        if self.timeout == 0 {
+            return Ok(0);
+        }
+
+        let elapsed = start_time.elapsed().as_secs();
+        if elapsed >= self.timeout {
+            return Err(AtentoError::Timeout {
+                context: format!("Chain timed out before step '{step_name}'"),
+                timeout_secs: self.timeout,
+            });
+        }
+
+        Ok(self.timeout.saturating_sub(elapsed))
+    }
+
+    /// Resolves the inputs of a step, along with the step references used as
+    /// placeholders, which are keyed by the reference itself.
+    fn resolve_step_inputs(
+        &self,
+        step: &Step,
+        step_name: &str,
+        resolved_outputs: &HashMap<String, String>,
+    ) -> Result<HashMap<String, String>> {
+        let mut inputs: HashMap<String, String> = step
+            .inputs
+            .iter()
+            .filter_map(|(input_name, input)| {
+                match self.resolve_input(input_name, input, step_name, resolved_outputs) {
+                    Ok(val) => Some(Ok((input_name.clone(), val))),
+                    // Placeholders fall back to their defaults
+                    Err(AtentoError::UnresolvedReference { .. })
+                        if step.input_has_default(input_name) =>
+                    {
+                        None
+                    }
+                    Err(e) => Some(Err(e)),
+                }
+            })
+            .collect::<Result<_>>()?;
+
+        for placeholder in step.step_refs() {
+            let ref_ = self
+                .placeholder_ref(step_name, &placeholder)
+                .ok_or_else(|| AtentoError::UnresolvedReference {
+                    reference: placeholder.clone(),
+                    context: format!("step '{step_name}', which has no previous step"),
+                })?;
+            let value = self.resolve_input(
+                &placeholder,
+                &Input::Ref { ref_ },
+                step_name,
+                resolved_outputs,
+            )?;
+            inputs.insert(placeholder, value);
+        }
+
+        Ok(inputs)
+    }
+
+    fn resolve_step_env(
+        &self,
+        step: &Step,
+        step_name: &str,
+        resolved_outputs: &HashMap<String, String>,
+        inputs: &HashMap<String, String>,
+        parameters: &HashMap<String, String>,
+    ) -> Result<HashMap<String, String>> {
+        self.step_env(step)
+            .into_iter()
+            .map(|(env_name, env)| {
+                let value = match env {
+                    EnvValue::Input(input) => {
+                        self.resolve_input(env_name, input, step_name, resolved_outputs)?
+                    }
+                    EnvValue::Template(template) => {
+                        step::render_template(template, inputs, parameters)
+                    }
+                };
+                Ok((env_name.clone(), value))
+            })
+            .collect()
+    }
+
+    /// Returns the interpreter a step runs with: its own `type`, or the chain's
+    /// `default_interpreter` when the step does not set one.
+    ///
+    /// # Errors
+    /// Returns a `Validation` error if neither is set or the key is not a known interpreter.
+    pub fn step_interpreter(&self, step: &Step, step_name: &str) -> Result<&Interpreter> {
+        let key = self.step_interpreter_key(step, step_name)?;
+        self.interpreters.get(key).ok_or_else(|| {
+            AtentoError::Validation(format!(
+                "Unknown interpreter '{key}' in step '{step_name}'; use a default interpreter or declare it under 'interpreters'"
+            ))
+        })
+    }
+
+    /// Checks that the interpreter of every step can run on this host, see
+    /// [`Interpreter::is_available`].
+    ///
+    /// Each interpreter is probed once however many steps use it. Steps with an
+    /// `image` run in a container and are not checked, and neither is a chain
+    /// with an `image` or a `remote` section, whose steps run elsewhere.
+    ///
+    /// # Errors
+    /// Returns every step whose interpreter is unknown or not available.
+    #[cfg(feature = "exec")]
+    pub fn check_interpreters(&self) -> std::result::Result<(), Vec<MissingInterpreter>> {
+        if self.remote.is_some() || self.image.is_some() {
+            return Ok(());
+        }
+        let mut available: HashMap<&str, bool> = HashMap::new();
+        let mut missing = Vec::new();
+        for (step_name, step) in &self.steps {
+            if step.image.is_some() {
+                continue;
+            }
+            let Ok(key) = self.step_interpreter_key(step, step_name) else {
+                continue;
+            };
+            let interpreter = self.interpreters.get(key);
+            let is_available = *available
+                .entry(key)
+                .or_insert_with(|| interpreter.is_some_and(Interpreter::is_available));
+            if !is_available {
+                missing.push(MissingInterpreter {
+                    step: step_name.clone(),
+                    interpreter: key.to_string(),
+                    command: interpreter.map_or_else(|| key.to_string(), |i| i.command.clone()),
+                });
+            }
+        }
+        if missing.is_empty() {
+            Ok(())
+        } else {
+            Err(missing)
+        }
+    }
+
+    /// Returns the interpreter key a step runs with, see [`Chain::step_interpreter`].
+    fn step_interpreter_key<'a>(&'a self, step: &'a Step, step_name: &str) -> Result<&'a str> {
+        step.interpreter
+            .as_deref()
+            .or(self.default_interpreter.as_deref())
+            .ok_or_else(|| {
+                AtentoError::Validation(format!(
+                    "Step '{step_name}' has no 'type'; set it on the step or set 'default_interpreter' at chain level"
+                ))
+            })
+    }
+
+    #[cfg(feature = "exec")]
+    fn prepare_step(
+        &self,
+        step: &Step,
+        step_name: &str,
+        resolved_outputs: &HashMap<String, String>,
+        context: &RunContext<'_>,
+    ) -> Result<PreparedStep<'_>> {
+        let mut inputs = self.resolve_step_inputs(step, step_name, resolved_outputs)?;
+        let mut env = self.resolve_step_env(
+            step,
+            step_name,
+            resolved_outputs,
+            &inputs,
+            context.parameters,
+        )?;
+        env.insert(RUN_ID_ENV.to_string(), context.run_id.clone());
+        if let Some(EnvValue::Input(stdin)) = &step.stdin {
+            let value =
+                self.resolve_input(step::STDIN_INPUT, stdin, step_name, resolved_outputs)?;
+            inputs.insert(step::STDIN_INPUT.to_string(), value);
+        }
+        let items = step
+            .foreach
+            .as_ref()
+            .map(|ref_| {
+                let list = Input::Ref { ref_: ref_.clone() };
+                self.resolve_input("foreach", &list, step_name, resolved_outputs)
+                    .map(|list| step::foreach_items(&list))
+            })
+            .transpose()?;
+        Ok(PreparedStep {
+            inputs,
+            env,
+            interpreter: self.step_interpreter(step, step_name)?,
+            items,
+        })
+    }
+
+    /// Runs a prepared step, once per item for `foreach` steps.
+    ///
+    /// A failing iteration stops the remaining ones unless the step sets
+    /// `continue_on_error`. Every iteration gets the chain time that is left.
+    /// The artifacts of a step that succeeded are collected afterwards. Steps
+    /// without an `image` of their own run in the chain's `image` when it is set.
+    #[cfg(feature = "exec")]
+    fn execute_step<E: CommandExecutor>(
+        &self,
+        step_name: &str,
+        step: &Step,
+        executor: &E,
+        prepared: &PreparedStep<'_>,
+        context: &RunContext<'_>,
+        time_left: u64,
+    ) -> StepResult {
+        match self.container_image(step) {
+            Some(image) => self.execute_step_with(
+                step_name,
+                step,
+                &DockerExecutor::new(image),
+                prepared,
+                context,
+                time_left,
+            ),
+            None => self.execute_step_with(step_name, step, executor, prepared, context, time_left),
+        }
+    }
+
+    /// The chain's `image` when `step` does not set one itself.
+    #[cfg(feature = "exec")]
+    fn container_image(&self, step: &Step) -> Option<&str> {
+        match step.image {
+            Some(_) => None,
+            None => self.image.as_deref(),
+        }
+    }
+
+    /// Runs a prepared step like [`Chain::execute_step`] on `executor`.
+    #[cfg(feature = "exec")]
+    fn execute_step_with<E: CommandExecutor>(
+        &self,
+        step_name: &str,
+        step: &Step,
+        executor: &E,
+        prepared: &PreparedStep<'_>,
+        context: &RunContext<'_>,
+        time_left: u64,
+    ) -> StepResult {
+        #[cfg(feature = "tracing")]
+        let _step_span = self.step_span(step_name, step, prepared).entered();
+        #[cfg(not(feature = "tracing"))]
+        let _ = step_name;
+
+        let Some(items) = &prepared.items else {
+            let result = step.run(
+                executor,
+                &prepared.inputs,
+                context.parameters,
+                &prepared.env,
+                self.output_limit(step),
+                time_left,
+                &self.kill_policy(step, context),
+                prepared.interpreter,
+            );
+            return self.with_artifacts(step, prepared, context, result);
+        };
+
+        let mut foreach = ForeachRun::new(items, time_left);
+        while let Some((inputs, remaining)) = foreach.next_item(&prepared.inputs) {
+            let result = step.run(
+                executor,
+                &inputs,
+                context.parameters,
+                &prepared.env,
+                self.output_limit(step),
+                remaining,
+                &self.kill_policy(step, context),
+                prepared.interpreter,
+            );
+            foreach.record(step, result);
+        }
+        foreach.finish(step, prepared)
+    }
+
+    /// Returns the span a step is recorded in, noting the interpreter that
+    /// was selected for it.
+    #[cfg(feature = "exec")]
+    #[cfg(feature = "tracing")]
+    fn step_span(
+        &self,
+        step_name: &str,
+        step: &Step,
+        prepared: &PreparedStep<'_>,
+    ) -> tracing::Span {
+        let span = tracing::info_span!(
+            "step",
+            step.id = step_name,
+            step.name = step.name.as_deref().unwrap_or_default(),
+            step.interpreter = prepared.interpreter.command.as_str(),
+        );
+        span.in_scope(|| {
+            tracing::debug!(
+                interpreter = self
+                    .step_interpreter_key(step, step_name)
+                    .unwrap_or_default(),
+                command = prepared.interpreter.command.as_str(),
+                "interpreter selected"
+            );
+        });
+        span
+    }
+
+    /// Collects the artifacts of `step` into its `result` when it succeeded.
+    #[cfg(feature = "exec")]
+    fn with_artifacts(
+        &self,
+        step: &Step,
+        prepared: &PreparedStep<'_>,
+        context: &RunContext<'_>,
+        mut result: StepResult,
+    ) -> StepResult {
+        if result.error.is_none() {
+            match step.collect_artifacts(
+                &prepared.inputs,
+                context.parameters,
+                self.artifacts_root.as_deref(),
+            ) {
+                Ok(artifacts) => result.artifacts = artifacts,
+                Err(e) => result.error = Some(e),
+            }
+        }
+        result
+    }
+
+    /// The `max_output_bytes` passed to `step`, its own when set and otherwise
+    /// the chain's; `None` when that is `0`.
+    #[cfg(feature = "exec")]
+    fn output_limit(&self, step: &Step) -> Option<u64> {
+        let max = step.max_output_bytes.unwrap_or(self.max_output_bytes);
+        (max > 0).then_some(max)
+    }
+
+    /// How `step` is stopped when it times out or the run is cancelled, its
+    /// own settings taking precedence over the chain's.
+    #[cfg(feature = "exec")]
+    fn kill_policy(&self, step: &Step, context: &RunContext<'_>) -> KillPolicy {
+        KillPolicy {
+            signal: step.timeout_signal.unwrap_or(self.timeout_signal),
+            grace_ms: step.timeout_grace_ms.unwrap_or(self.timeout_grace_ms),
+            cancel: context.cancel.clone(),
+        }
+    }
+
+    #[cfg(feature = "exec")]
+    fn process_step_result(
+        step_name: &str,
+        step_result: &StepResult,
+        resolved_outputs: &mut HashMap<String, String>,
+    ) -> Option<AtentoError> {
+        // Store step outputs
+        for (k, text) in &step_result.outputs {
+            resolved_outputs.insert(Self::make_output_key(step_name, k), text.clone());
+        }
+        for (k, artifact) in &step_result.artifacts {
+            resolved_outputs.insert(Self::make_artifact_key(step_name, k), artifact.path.clone());
+        }
+
+        // Store implicit step references
+        resolved_outputs.insert(
+            Self::make_step_key(step_name, STEP_EXIT_CODE),
+            step_result.exit_code.to_string(),
+        );
+        resolved_outputs.insert(
+            Self::make_step_key(step_name, STEP_STDOUT),
+            step_result.stdout.clone().unwrap_or_default(),
+        );
+        resolved_outputs.insert(
+            Self::make_step_key(step_name, STEP_STDERR),
+            step_result.stderr.clone().unwrap_or_default(),
+        );
+
+        // Check for step error
+        step_result
+            .error
+            .as_ref()
+            .map(|err| AtentoError::StepExecution {
+                step: step_name.to_string(),
+                reason: err.to_string(),
+            })
+    }
+
+    fn collect_chain_results(
+        &self,
+        resolved_outputs: &HashMap<String, String>,
+        parameters: &HashMap<String, String>,
+    ) -> (HashMap<String, String>, Vec<AtentoError>) {
+        let mut final_results = HashMap::new();
+        let mut errors = Vec::new();
+
+        for (result_name, result) in &self.results {
+            match result {
+                ResultValue::Ref(result_ref) => {
+                    let value = match result_ref.ref_.strip_prefix("parameters.") {
+                        Some(param_key) => parameters.get(param_key),
+                        None => resolved_outputs.get(&result_ref.ref_),
+                    };
+
+                    if let Some(val) = value {
+                        final_results.insert(result_name.clone(), val.clone());
+                    } else if self.is_optional_output(&result_ref.ref_) {
+                        errors.push(AtentoError::UnresolvedReference {
+                            reference: result_ref.ref_.clone(),
+                            context: format!(
+                                "chain result '{result_name}' (optional output did not match and has no default)"
+                            ),
+                        });
+                    } else {
+                        errors.push(AtentoError::UnresolvedReference {
+                            reference: result_ref.ref_.clone(),
+                            context: format!("Unresolved Reference '{result_name}'"),
+                        });
+                    }
+                }
+                ResultValue::Inline { type_, value } => {
+                    match data_type::to_string_value(type_, value) {
+                        Ok(val) => {
+                            final_results.insert(result_name.clone(), val);
+                        }
+                        Err(e) => errors.push(e),
+                    }
+                }
+            }
+        }
+
+        (final_results, errors)
+    }
+
+    fn serialize_parameters(&self) -> (Option<HashMap<String, String>>, Vec<AtentoError>) {
+        if self.parameters.is_empty() {
+            return (None, Vec::new());
+        }
+
+        // Parameters without a value are reported together and left out
+        let missing = self.missing_parameters_error();
+
+        match self
+            .parameters
+            .iter()
+            .filter(|(_, v)| v.value.is_some())
+            .map(|(k, v)| v.to_string_value().map(|s| (k.clone(), s)))
+            .collect::<Result<HashMap<_, _>>>()
+        {
+            Ok(params) => (Some(params), missing.into_iter().collect()),
+            Err(e) => (None, vec![e]),
+        }
+    }
+
+    /// Returns the string values of all secret parameters.
+    fn secret_values(&self, parameter_values: &HashMap<String, String>) -> Vec<String> {
+        self.parameters
+            .iter()
+            .filter(|(_, param)| param.secret)
+            .filter_map(|(key, _)| parameter_values.get(key).cloned())
+            .collect()
+    }
+
+    /// Replaces the values of secret parameters with a mask for reporting.
+    fn mask_parameters(&self, mut params: HashMap<String, String>) -> HashMap<String, String> {
+        for (key, value) in &mut params {
+            if self.parameters.get(key).is_some_and(|param| param.secret) {
+                *value = step::SECRET_MASK.to_string();
+            }
+        }
+        params
+    }
+
+    /// Executes the chain with a custom executor (useful for testing).
+    ///
+    /// A failing step aborts the chain unless it sets `continue_on_error`, in which
+    /// case its error is recorded and the next step runs. A failing step with
+    /// `on_failure` first runs that step, which is skipped otherwise. With
+    /// `fail_fast: false` no step failure aborts the chain, so every step runs
+    /// and all errors are collected. Steps that depend on the
+    /// outputs of a failed step fail with an unresolved reference. The status is
+    /// `ok` without errors, `partial` when only tolerated failures occurred and
+    /// every step got its turn, and `nok` otherwise.
+    ///
+    /// # Errors
+    /// Returns an error if timeout is exceeded, a step fails, or output resolution fails.
+    #[cfg(feature = "exec")]
+    pub fn run_with_executor<E: CommandExecutor>(&self, executor: &E) -> ChainResult {
+        self.run_with_observer(executor, &mut NoopObserver)
+    }
+
+    /// Executes the chain like [`Chain::run_with_executor`], reporting progress to
+    /// `observer`.
+    ///
+    /// `on_step_start` and `on_step_complete` are called for every step whose
+    /// script runs, including steps that fail or time out; steps skipped because
+    /// the chain stopped earlier are not reported. `on_chain_complete` receives
+    /// the final result.
+    #[cfg(feature = "exec")]
+    pub fn run_with_observer<E: CommandExecutor, O: ChainObserver>(
+        &self,
+        executor: &E,
+        observer: &mut O,
+    ) -> ChainResult {
+        self.run_sequential(
+            executor,
+            observer,
+            None,
+            self.scheduled_steps(),
+            self.run_state(),
+        )
+    }
+
+    /// Executes the chain like [`Chain::run_with_executor`] until `token` is
+    /// cancelled, e.g. from another thread.
+    ///
+    /// The token is checked before every step and, while a step runs,
+    /// alongside its timeout: the step's processes are then stopped as on a
+    /// timeout, `timeout_signal` and grace period included, and the step fails
+    /// with a `Cancelled` error. No further step runs, `on_failure` handlers
+    /// included, and the result has status `cancelled` and a `Cancelled` error.
+    /// Cancelling the token after the run finished has no effect. Steps run one
+    /// after another, even in chains with `parallel: true`.
+    #[cfg(feature = "exec")]
+    pub fn run_with_cancellation<E: CommandExecutor>(
+        &self,
+        executor: &E,
+        token: &CancellationToken,
+    ) -> ChainResult {
+        self.run_sequential(
+            executor,
+            &mut NoopObserver,
+            Some(token),
+            self.scheduled_steps(),
+            self.run_state(),
+        )
+    }
+
+    #[cfg(feature = "exec")]
+    fn run_sequential<E: CommandExecutor, O: ChainObserver>(
+        &self,
+        executor: &E,
+        observer: &mut O,
+        cancel: Option<&CancellationToken>,
+        steps: Vec<&String>,
+        mut state: RunState,
+    ) -> ChainResult {
+        #[cfg(feature = "tracing")]
+        let _chain_span = self.trace_span().entered();
+
+        let (parameters, mut param_errors) = self.serialize_parameters();
+        state.chain_errors.append(&mut param_errors);
+        let parameter_values = parameters.clone().unwrap_or_default();
+        let secrets = self.secret_values(&parameter_values);
+
+        let context = RunContext {
+            cancel: cancel.cloned(),
+            ..RunContext::new(&parameter_values, &secrets)
+        };
+        for step_name in steps {
+            if context.is_cancelled() {
+                state.record_cancellation(AtentoError::Cancelled {
+                    context: format!("before step '{step_name}'"),
+                });
+                break;
+            }
+            if self.run_step(step_name, executor, observer, &mut state, &context)
+                && !state.cancelled
+            {
+                self.run_on_failure(step_name, executor, observer, &mut state, &context);
+            }
+
+            if state.aborted {
+                break;
+            }
+        }
+
+        let result = self.finish_run(&context, state, parameters);
+        #[cfg(feature = "http")]
+        self.notify_webhook(&result);
+        observer.on_chain_complete(&result);
+        result
+    }
+
+    /// Executes the chain like [`Chain::run_with_executor`], awaiting each
+    /// script on `executor`.
+    ///
+    /// Steps run one after another in the same order, with the same timeouts,
+    /// retries, `foreach` items, `on_failure` handlers and result.
+    #[cfg(feature = "async")]
+    pub async fn run_async_with_executor<E: AsyncCommandExecutor>(
+        &self,
+        executor: &E,
+    ) -> ChainResult {
+        let run = self.run_async_steps(executor);
+        #[cfg(feature = "tracing")]
+        let run = tracing::Instrument::instrument(run, self.trace_span());
+        run.await
+    }
+
+    #[cfg(feature = "async")]
+    async fn run_async_steps<E: AsyncCommandExecutor>(&self, executor: &E) -> ChainResult {
+        let mut state = self.run_state();
+
+        let (parameters, mut param_errors) = self.serialize_parameters();
+        state.chain_errors.append(&mut param_errors);
+        let parameter_values = parameters.clone().unwrap_or_default();
+        let secrets = self.secret_values(&parameter_values);
+
+        let context = RunContext::new(&parameter_values, &secrets);
+        for step_name in self.scheduled_steps() {
+            if self
+                .run_step_async(step_name, executor, &mut state, &context)
+                .await
+                && let Some(handler) = &self.steps[step_name].on_failure
+            {
+                self.run_step_async(handler, executor, &mut state, &context)
+                    .await;
+            }
+
+            if state.aborted {
+                break;
+            }
+        }
+
+        let result = self.finish_run(&context, state, parameters);
+        #[cfg(feature = "http")]
+        self.notify_webhook(&result);
+        result
+    }
+
+    /// Prepares and runs one step like [`Chain::run_step`], awaiting it.
+    #[cfg(feature = "async")]
+    async fn run_step_async<E: AsyncCommandExecutor>(
+        &self,
         step_name: &str,
-        step_result: &StepResult,
-        resolved_outputs: &mut HashMap<String, String>,
-    ) -> Option<AtentoError> {
-        // Store step outputs
-        for (k, v) in &step_result.outputs {
-            resolved_outputs.insert(Self::make_output_key(step_name, k), v.clone());
+        executor: &E,
+        state: &mut RunState,
+        context: &RunContext<'_>,
+    ) -> bool {
+        let step = &self.steps[step_name];
+        let Some(time_left) = self.time_left(step_name, state, &context.start_time) else {
+            return false;
+        };
+
+        match self.prepare_step(step, step_name, &state.resolved_outputs, context) {
+            Ok(prepared) => {
+                let run = self.execute_step_async(step, executor, &prepared, context, time_left);
+                #[cfg(feature = "tracing")]
+                let run = tracing::Instrument::instrument(
+                    run,
+                    self.step_span(step_name, step, &prepared),
+                );
+                let step_result = run.await;
+                let failed = step_result.error.is_some();
+                state.record_step_result(step_name, step, step_result, context.secrets);
+                failed
+            }
+            Err(e) => {
+                state.record_step_error(step, e);
+                true
+            }
         }
+    }
 
-        // Check for step error
-        step_result
-            .error
-            .as_ref()
-            .map(|err| AtentoError::StepExecution {
-                step: step_name.to_string(),
-                reason: err.to_string(),
-            })
+    /// Runs a prepared step like [`Chain::execute_step`], awaiting each script.
+    #[cfg(feature = "async")]
+    async fn execute_step_async<E: AsyncCommandExecutor>(
+        &self,
+        step: &Step,
+        executor: &E,
+        prepared: &PreparedStep<'_>,
+        context: &RunContext<'_>,
+        time_left: u64,
+    ) -> StepResult {
+        match self.container_image(step) {
+            Some(image) => {
+                let docker = DockerExecutor::new(image);
+                self.execute_step_async_with(step, &docker, prepared, context, time_left)
+                    .await
+            }
+            None => {
+                self.execute_step_async_with(step, executor, prepared, context, time_left)
+                    .await
+            }
+        }
     }
 
-    fn collect_chain_results(
+    /// Runs a prepared step like [`Chain::execute_step_async`] on `executor`.
+    #[cfg(feature = "async")]
+    async fn execute_step_async_with<E: AsyncCommandExecutor>(
         &self,
-        resolved_outputs: &HashMap<String, String>,
-    ) -> (HashMap<String, String>, Vec<AtentoError>) {
-        let mut final_results = HashMap::new();
-        let mut errors = Vec::new();
+        step: &Step,
+        executor: &E,
+        prepared: &PreparedStep<'_>,
+        context: &RunContext<'_>,
+        time_left: u64,
+    ) -> StepResult {
+        let Some(items) = &prepared.items else {
+            let result = step
+                .run_async(
+                    executor,
+                    &prepared.inputs,
+                    context.parameters,
+                    &prepared.env,
+                    self.output_limit(step),
+                    time_left,
+                    &self.kill_policy(step, context),
+                    prepared.interpreter,
+                )
+                .await;
+            return self.with_artifacts(step, prepared, context, result);
+        };
 
-        for (result_name, result_ref) in &self.results {
-            if let Some(val) = resolved_outputs.get(&result_ref.ref_) {
-                final_results.insert(result_name.clone(), val.clone());
-            } else {
-                errors.push(AtentoError::UnresolvedReference {
-                    reference: result_ref.ref_.clone(),
-                    context: format!("Unresolved Reference '{result_name}'"),
-                });
+        let mut foreach = ForeachRun::new(items, time_left);
+        while let Some((inputs, remaining)) = foreach.next_item(&prepared.inputs) {
+            let result = step
+                .run_async(
+                    executor,
+                    &inputs,
+                    context.parameters,
+                    &prepared.env,
+                    self.output_limit(step),
+                    remaining,
+                    &self.kill_policy(step, context),
+                    prepared.interpreter,
+                )
+                .await;
+            foreach.record(step, result);
+        }
+        foreach.finish(step, prepared)
+    }
+
+    /// Returns the bookkeeping for a new run of this chain.
+    #[cfg(feature = "exec")]
+    fn run_state(&self) -> RunState {
+        RunState {
+            keep_going: !self.fail_fast,
+            ..RunState::default()
+        }
+    }
+
+    /// Prepares and runs one step on the calling thread, recording its result
+    /// in `state`. Returns whether the step failed; a chain timeout before the
+    /// step starts is recorded as a chain error instead.
+    #[cfg(feature = "exec")]
+    fn run_step<E: CommandExecutor, O: ChainObserver>(
+        &self,
+        step_name: &str,
+        executor: &E,
+        observer: &mut O,
+        state: &mut RunState,
+        context: &RunContext<'_>,
+    ) -> bool {
+        let step = &self.steps[step_name];
+        let Some(time_left) = self.time_left(step_name, state, &context.start_time) else {
+            return false;
+        };
+
+        // Resolve inputs, environment and interpreter, then run the step
+        match self.prepare_step(step, step_name, &state.resolved_outputs, context) {
+            Ok(prepared) => {
+                observer.on_step_start(step_name, step.name.as_deref());
+                let step_result =
+                    self.execute_step(step_name, step, executor, &prepared, context, time_left);
+                let failed = step_result.error.is_some();
+                state.record_step_result(step_name, step, step_result, context.secrets);
+                observer.on_step_complete(step_name, &state.step_results[step_name]);
+                failed
+            }
+            Err(e) => {
+                state.record_step_error(step, e);
+                true
             }
         }
+    }
 
-        (final_results, errors)
+    /// Returns the chain time left for `step_name`, see [`Chain::check_timeout`].
+    /// When the chain has timed out, the error is recorded and the run aborted.
+    #[cfg(feature = "exec")]
+    fn time_left(
+        &self,
+        step_name: &str,
+        state: &mut RunState,
+        start_time: &Instant,
+    ) -> Option<u64> {
+        match self.check_timeout(start_time, step_name) {
+            Ok(time) => Some(time),
+            Err(e) => {
+                #[cfg(feature = "tracing")]
+                tracing::error!(error = %e, "chain timed out");
+                state.chain_errors.push(e);
+                state.aborted = true;
+                None
+            }
+        }
     }
 
-    fn serialize_parameters(&self) -> (Option<HashMap<String, String>>, Vec<AtentoError>) {
-        if self.parameters.is_empty() {
-            return (None, Vec::new());
+    /// Runs the `on_failure` handler of the failed step `step_name`, if it sets
+    /// one. The handler's result is recorded under its own key; the failed
+    /// step's `continue_on_error` still decides whether the chain goes on.
+    #[cfg(feature = "exec")]
+    fn run_on_failure<E: CommandExecutor, O: ChainObserver>(
+        &self,
+        step_name: &str,
+        executor: &E,
+        observer: &mut O,
+        state: &mut RunState,
+        context: &RunContext<'_>,
+    ) {
+        if let Some(handler) = &self.steps[step_name].on_failure {
+            self.run_step(handler, executor, observer, state, context);
         }
+    }
 
-        match self
-            .parameters
-            .iter()
-            .map(|(k, v)| v.to_string_value().map(|s| (k.clone(), s)))
-            .collect::<Result<HashMap<_, _>>>()
-        {
-            Ok(params) => (Some(params), Vec::new()),
-            Err(e) => (None, vec![e]),
+    /// Records the result of a step that ran on its own thread. When it failed,
+    /// its `on_failure` handler runs on this thread, before any dependent step
+    /// of the failed step starts.
+    #[cfg(feature = "exec")]
+    fn finish_parallel_step<E: CommandExecutor, O: ChainObserver>(
+        &self,
+        step_name: &str,
+        step_result: StepResult,
+        executor: &E,
+        observer: &mut O,
+        state: &mut RunState,
+        context: &RunContext<'_>,
+    ) {
+        let failed = step_result.error.is_some();
+        state.record_step_result(
+            step_name,
+            &self.steps[step_name],
+            step_result,
+            context.secrets,
+        );
+        observer.on_step_complete(step_name, &state.step_results[step_name]);
+        if failed {
+            self.run_on_failure(step_name, executor, observer, state, context);
         }
     }
 
-    /// Executes the chain with a custom executor (useful for testing).
+    /// Returns the keys of the steps that run in the normal flow, in execution
+    /// order: every step except the `on_failure` handlers.
+    #[cfg(feature = "exec")]
+    fn scheduled_steps(&self) -> Vec<&String> {
+        let handlers = self.failure_handlers();
+        self.ordered_steps()
+            .into_iter()
+            .filter(|step_name| !handlers.contains(step_name.as_str()))
+            .collect()
+    }
+
+    /// Returns every step key in execution order, or in declared order when
+    /// the steps depend on each other in a cycle, which validation rejects.
+    fn ordered_steps(&self) -> Vec<&String> {
+        self.execution_order()
+            .unwrap_or_else(|_| self.steps.keys().collect())
+    }
+
+    /// Returns the keys of the steps named as an `on_failure` handler, which
+    /// only run after the step naming them fails.
+    fn failure_handlers(&self) -> HashSet<&str> {
+        self.steps
+            .values()
+            .filter_map(|step| step.on_failure.as_deref())
+            .collect()
+    }
+
+    /// Executes the chain like [`Chain::run_with_executor`], but runs steps
+    /// concurrently on separate threads as soon as the steps they reference have
+    /// finished.
+    ///
+    /// A step depends on another step when one of its inputs or env entries (or
+    /// a chain-level env entry) references `steps.<id>.*`. Steps without such a
+    /// dependency between them run in parallel. `ChainResult.steps` keeps the
+    /// declared step order. Each step's timeout is capped by the time left on the
+    /// chain, so the chain timeout also stops steps that are still running.
+    #[cfg(feature = "exec")]
+    pub fn run_parallel_with_executor<E: CommandExecutor + Sync>(
+        &self,
+        executor: &E,
+    ) -> ChainResult {
+        self.run_parallel_with_observer(executor, &mut NoopObserver)
+    }
+
+    /// Executes the chain like [`Chain::run_parallel_with_executor`], reporting
+    /// progress to `observer` as described for [`Chain::run_with_observer`].
+    /// Events arrive in the order steps start and finish, so completions of
+    /// concurrent steps may interleave with other steps' starts.
+    #[cfg(feature = "exec")]
+    pub fn run_parallel_with_observer<E: CommandExecutor + Sync, O: ChainObserver>(
+        &self,
+        executor: &E,
+        observer: &mut O,
+    ) -> ChainResult {
+        #[cfg(feature = "tracing")]
+        let chain_span = self.trace_span();
+        #[cfg(feature = "tracing")]
+        let _chain_span = chain_span.enter();
+
+        let mut state = self.run_state();
+
+        let (parameters, mut param_errors) = self.serialize_parameters();
+        state.chain_errors.append(&mut param_errors);
+        let parameter_values = parameters.clone().unwrap_or_default();
+        let secrets = self.secret_values(&parameter_values);
+
+        let context = RunContext::new(&parameter_values, &secrets);
+        let dependencies = self.dependency_graph();
+        let mut pending = self.scheduled_steps();
+        let mut finished: HashSet<&str> = HashSet::new();
+        let mut running = 0usize;
+
+        // Step threads report to the caller's subscriber, under the chain span
+        #[cfg(feature = "tracing")]
+        let trace = &(
+            tracing::dispatcher::get_default(Clone::clone),
+            chain_span.clone(),
+        );
+
+        std::thread::scope(|scope| {
+            let (sender, receiver) = mpsc::channel();
+
+            loop {
+                // Start every step whose dependencies have finished
+                while !state.aborted {
+                    let Some(step_name) = next_ready_step(&mut pending, &dependencies, &finished)
+                    else {
+                        break;
+                    };
+                    let step = &self.steps[step_name];
+                    let Some(time_left) =
+                        self.time_left(step_name, &mut state, &context.start_time)
+                    else {
+                        break;
+                    };
+
+                    match self.prepare_step(step, step_name, &state.resolved_outputs, &context) {
+                        Ok(prepared) => {
+                            observer.on_step_start(step_name, step.name.as_deref());
+                            let sender = sender.clone();
+                            let context = &context;
+                            scope.spawn(move || {
+                                #[cfg(feature = "tracing")]
+                                let _trace =
+                                    (tracing::dispatcher::set_default(&trace.0), trace.1.enter());
+                                let step_result = self.execute_step(
+                                    step_name, step, executor, &prepared, context, time_left,
+                                );
+                                // The receiver outlives every step thread
+                                let _ = sender.send((step_name, step_result));
+                            });
+                            running += 1;
+                        }
+                        Err(e) => {
+                            state.record_step_error(step, e);
+                            self.run_on_failure(
+                                step_name, executor, observer, &mut state, &context,
+                            );
+                            finished.insert(step_name.as_str());
+                        }
+                    }
+                }
+
+                if running == 0 {
+                    break;
+                }
+
+                match receiver.recv_timeout(Duration::from_millis(100)) {
+                    Ok((step_name, step_result)) => {
+                        running -= 1;
+                        self.finish_parallel_step(
+                            step_name,
+                            step_result,
+                            executor,
+                            observer,
+                            &mut state,
+                            &context,
+                        );
+                        finished.insert(step_name.as_str());
+                    }
+                    Err(_) if !state.aborted => {
+                        self.time_left("pending steps", &mut state, &context.start_time);
+                    }
+                    Err(_) => {}
+                }
+            }
+        });
+
+        // Report steps in declared order regardless of completion order
+        state.step_results = self.in_declared_order(std::mem::take(&mut state.step_results));
+
+        let result = self.finish_run(&context, state, parameters);
+        #[cfg(feature = "http")]
+        self.notify_webhook(&result);
+        observer.on_chain_complete(&result);
+        result
+    }
+
+    /// Reorders step results to the order the steps are declared in.
+    #[cfg(feature = "exec")]
+    fn in_declared_order(
+        &self,
+        mut step_results: IndexMap<String, StepResult>,
+    ) -> IndexMap<String, StepResult> {
+        self.steps
+            .keys()
+            .filter_map(|step_name| step_results.shift_remove_entry(step_name))
+            .collect()
+    }
+
+    /// Returns the dependencies of every step, see [`Chain::step_dependencies`].
+    fn dependency_graph(&self) -> HashMap<&str, HashSet<&str>> {
+        self.steps
+            .iter()
+            .map(|(step_name, step)| (step_name.as_str(), self.step_dependencies(step_name, step)))
+            .collect()
+    }
+
+    /// Returns the keys of the steps whose outputs or implicit references are
+    /// used by the inputs, env entries, `stdin`, `foreach` list or placeholders
+    /// of `step`.
+    pub(crate) fn step_dependencies(&self, step_key: &str, step: &Step) -> HashSet<&str> {
+        let refs = self.step_references(step_key, step);
+        self.steps
+            .keys()
+            .filter(|other| {
+                let prefix = format!("steps.{other}.");
+                step.needs.contains(other) || refs.iter().any(|ref_| ref_.starts_with(&prefix))
+            })
+            .map(String::as_str)
+            .collect()
+    }
+
+    /// Returns the references made by the inputs, env entries, `stdin`,
+    /// `foreach` list and placeholders of `step`.
+    pub(crate) fn step_references(&self, step_key: &str, step: &Step) -> Vec<String> {
+        let mut refs: Vec<String> = step
+            .inputs
+            .values()
+            .chain(
+                self.step_env(step)
+                    .into_values()
+                    .chain(step.stdin.as_ref())
+                    .filter_map(|value| match value {
+                        EnvValue::Input(input) => Some(input),
+                        EnvValue::Template(_) => None,
+                    }),
+            )
+            .filter_map(|input| match input {
+                Input::Ref { ref_ } => Some(ref_.clone()),
+                Input::Env { .. } | Input::Inline { .. } => None,
+            })
+            .chain(step.foreach.clone())
+            .collect();
+        refs.extend(
+            step.step_refs()
+                .into_iter()
+                .filter_map(|placeholder| self.placeholder_ref(step_key, &placeholder)),
+        );
+        refs
+    }
+
+    /// Returns the reference a placeholder of the step `step_key` stands for:
+    /// `prev.x` names `steps.<id>.x` of the step declared just before it, and
+    /// has no reference in the first step.
+    fn placeholder_ref(&self, step_key: &str, placeholder: &str) -> Option<String> {
+        let Some(field) = placeholder.strip_prefix(step::PREV_STEP_PREFIX) else {
+            return Some(placeholder.to_string());
+        };
+        let position = self.steps.get_index_of(step_key)?;
+        let (previous, _) = self.steps.get_index(position.checked_sub(1)?)?;
+        Some(Self::make_step_key(previous, field))
+    }
+
+    /// Returns every step key in the order the steps run: after the steps
+    /// they depend on (see [`Chain::step_dependencies`]), with ties kept in
+    /// declared order.
     ///
     /// # Errors
-    /// Returns an error if timeout is exceeded, a step fails, or output resolution fails.
-    pub fn run_with_executor<E: CommandExecutor>(&self, executor: &E) -> ChainResult {
-        let start_time = Instant::now();
+    /// Returns a `Validation` error naming the cycle when steps depend on each other.
+    pub(crate) fn execution_order(&self) -> Result<Vec<&String>> {
+        let dependencies = self.dependency_graph();
+        let mut pending: Vec<&String> = self.steps.keys().collect();
+        let mut placed: HashSet<&str> = HashSet::new();
+        let mut order = Vec::with_capacity(pending.len());
+
+        while !pending.is_empty() {
+            let Some(step_name) = next_ready_step(&mut pending, &dependencies, &placed) else {
+                return Err(dependency_cycle(&pending, &dependencies, &placed));
+            };
+            placed.insert(step_name.as_str());
+            order.push(step_name);
+        }
+
+        Ok(order)
+    }
+
+    /// Collects the chain results and builds the final `ChainResult` of a run.
+    #[cfg(feature = "exec")]
+    fn finish_run(
+        &self,
+        context: &RunContext<'_>,
+        mut state: RunState,
+        parameters: Option<HashMap<String, String>>,
+    ) -> ChainResult {
+        let (final_results, mut result_errors) =
+            self.collect_chain_results(&state.resolved_outputs, context.parameters);
+        // Results of steps a subset run left out are not expected to resolve
+        result_errors.retain(|err| match err {
+            AtentoError::UnresolvedReference { reference, .. } => !state
+                .skipped
+                .iter()
+                .any(|step| reference.starts_with(&format!("steps.{step}."))),
+            _ => true,
+        });
+        state.chain_errors.append(&mut result_errors);
+        let chain_errors: Vec<AtentoError> = state
+            .chain_errors
+            .into_iter()
+            .map(|err| err.masked(context.secrets))
+            .collect();
+        let final_results: HashMap<String, String> = final_results
+            .into_iter()
+            .map(|(k, v)| (k, step::mask_secrets(&v, context.secrets)))
+            .collect();
+
+        let status = if state.cancelled {
+            STATUS_CANCELLED
+        } else if chain_errors.is_empty() {
+            STATUS_OK
+        } else if state.tolerated_failure && !state.aborted && !state.failed {
+            STATUS_PARTIAL
+        } else {
+            STATUS_NOK
+        }
+        .to_string();
+
+        let result = ChainResult {
+            name: self.name.clone(),
+            run_id: Some(context.run_id.clone()),
+            metadata: self.metadata.clone(),
+            duration_ms: context.start_time.elapsed().as_millis(),
+            parameters: parameters.map(|params| self.mask_parameters(params)),
+            steps: if state.step_results.is_empty() {
+                None
+            } else {
+                Some(state.step_results)
+            },
+            results: if final_results.is_empty() {
+                None
+            } else {
+                Some(final_results)
+            },
+            errors: chain_errors,
+            status,
+            started_at: Some(rfc3339_timestamp(context.started_at)),
+            finished_at: Some(rfc3339_timestamp(SystemTime::now())),
+        };
+
+        #[cfg(feature = "tracing")]
+        tracing::info!(
+            status = %result.status,
+            duration_ms = result.duration_ms,
+            errors = result.errors.len(),
+            "chain finished"
+        );
+
+        result
+    }
+
+    /// Returns the span a run of this chain is recorded in, see the README for its fields.
+    #[cfg(feature = "exec")]
+    #[cfg(feature = "tracing")]
+    fn trace_span(&self) -> tracing::Span {
+        tracing::info_span!(
+            "chain",
+            chain.name = self.name.as_deref().unwrap_or_default(),
+            chain.steps = self.steps.len(),
+            chain.parallel = self.parallel,
+        )
+    }
+
+    /// Walks the chain without executing any scripts.
+    ///
+    /// Inputs are resolved and scripts rendered exactly as they would be for a real
+    /// run. Step outputs cannot be known ahead of time, so references to them are
+    /// rendered as `<steps.X.outputs.Y>` placeholders. The returned result has
+    /// status `dry-run` and carries the rendered script of every step.
+    #[must_use]
+    pub fn dry_run(&self) -> ChainResult {
+        let started_at = data_type::now();
         let mut resolved_outputs = HashMap::new();
         let mut step_results = IndexMap::new();
         let mut chain_errors = Vec::new();
 
-        for (step_name, step) in &self.steps {
-            // Check timeout
-            let time_left = match self.check_timeout(&start_time, step_name) {
-                Ok(time) => time,
-                Err(e) => {
-                    chain_errors.push(e);
-                    break;
-                }
-            };
+        let (parameters, mut param_errors) = self.serialize_parameters();
+        chain_errors.append(&mut param_errors);
+        let parameter_values = parameters.clone().unwrap_or_default();
+        let secrets = self.secret_values(&parameter_values);
+        let parameters = parameters.map(|params| self.mask_parameters(params));
 
-            // Resolve step inputs
+        for step_name in self.ordered_steps() {
+            let step = &self.steps[step_name];
             let step_inputs = match self.resolve_step_inputs(step, step_name, &resolved_outputs) {
                 Ok(inputs) => inputs,
                 Err(e) => {
@@ -326,42 +2688,58 @@ impl Chain {
                 }
             };
 
-            // Lookup interpreter
-            let interpreter = match self.lookup_interpreter(step, step_name) {
-                Ok(interp) => interp,
+            let step_env = match self.resolve_step_env(
+                step,
+                step_name,
+                &resolved_outputs,
+                &step_inputs,
+                &parameter_values,
+            ) {
+                Ok(env) => env,
                 Err(e) => {
                     chain_errors.push(e);
                     break;
                 }
             };
 
-            // Run step
-            let step_result = step.run(executor, &step_inputs, time_left, interpreter);
-
-            // Process result and check for errors
-            if let Some(err) =
-                Self::process_step_result(step_name, &step_result, &mut resolved_outputs)
-            {
-                chain_errors.push(err);
-                step_results.insert(step_name.clone(), step_result);
+            if let Err(e) = self.step_interpreter(step, step_name) {
+                chain_errors.push(e);
                 break;
             }
 
+            let mut step_result = step.dry_run(&step_inputs, &parameter_values, &step_env);
+            step_result.mask_secrets(&secrets);
             step_results.insert(step_name.clone(), step_result);
+
+            for key in Self::step_ref_keys(step_name, step) {
+                resolved_outputs.insert(key.clone(), format!("<{key}>"));
+            }
         }
 
-        // Collect chain results and parameters
-        let (final_results, mut result_errors) = self.collect_chain_results(&resolved_outputs);
+        let (final_results, mut result_errors) =
+            self.collect_chain_results(&resolved_outputs, &parameter_values);
         chain_errors.append(&mut result_errors);
+        let chain_errors: Vec<AtentoError> = chain_errors
+            .into_iter()
+            .map(|err| err.masked(&secrets))
+            .collect();
+        let final_results: HashMap<String, String> = final_results
+            .into_iter()
+            .map(|(k, v)| (k, step::mask_secrets(&v, &secrets)))
+            .collect();
 
-        let (parameters, mut param_errors) = self.serialize_parameters();
-        chain_errors.append(&mut param_errors);
-
-        let status = if chain_errors.is_empty() { "ok" } else { "nok" }.to_string();
+        let status = if chain_errors.is_empty() {
+            STATUS_DRY_RUN
+        } else {
+            STATUS_NOK
+        }
+        .to_string();
 
         ChainResult {
             name: self.name.clone(),
-            duration_ms: start_time.elapsed().as_millis(),
+            duration_ms: started_at
+                .and_then(|time| time.elapsed().ok())
+                .map_or(0, |elapsed| elapsed.as_millis()),
             parameters,
             steps: if step_results.is_empty() {
                 None
@@ -375,17 +2753,238 @@ impl Chain {
             },
             errors: chain_errors,
             status,
+            started_at: started_at.map(rfc3339_timestamp),
+            finished_at: data_type::now().map(rfc3339_timestamp),
+            metadata: self.metadata.clone(),
+            run_id: None,
         }
     }
 
-    /// Executes the chain using the system executor.
+    /// Executes the chain using the system executor, or the SSH executor from
+    /// the `remote` section when one is configured.
     ///
     /// # Errors
     /// Returns an error if timeout is exceeded, a step fails, or output resolution fails.
+    /// Chains with `parallel: true` run independent steps concurrently, see
+    /// [`Chain::run_parallel_with_executor`].
+    ///
+    /// With `preflight: true` the interpreters are checked first, see
+    /// [`Chain::check_interpreters`]; if any is missing no step runs and the
+    /// result lists one error per affected step.
+    #[cfg(feature = "exec")]
     #[must_use]
     pub fn run(&self) -> ChainResult {
-        use crate::executor::SystemExecutor;
-        let executor = SystemExecutor;
-        self.run_with_executor(&executor)
+        if let Some(result) = self.preflight_failure() {
+            return result;
+        }
+        match &self.remote {
+            Some(remote) => self.run_dispatch(remote),
+            None => self.run_dispatch(&SystemExecutor),
+        }
+    }
+
+    /// Executes the chain like [`Chain::run`] on the current tokio runtime,
+    /// awaiting every script instead of blocking a thread on it.
+    ///
+    /// Steps run one after another, even in chains with `parallel: true`,
+    /// through [`SystemExecutor`] or the chain's `remote`.
+    #[cfg(feature = "async")]
+    pub async fn run_async(&self) -> ChainResult {
+        if let Some(result) = self.preflight_failure() {
+            return result;
+        }
+        match &self.remote {
+            Some(remote) => self.run_async_with_executor(remote).await,
+            None => self.run_async_with_executor(&SystemExecutor).await,
+        }
+    }
+
+    /// Returns the failed result of a chain with `preflight: true` whose
+    /// interpreters are not all installed.
+    #[cfg(feature = "exec")]
+    fn preflight_failure(&self) -> Option<ChainResult> {
+        if self.preflight
+            && let Err(missing) = self.check_interpreters()
+        {
+            let now = rfc3339_timestamp(SystemTime::now());
+            let result = ChainResult {
+                name: self.name.clone(),
+                duration_ms: 0,
+                parameters: None,
+                steps: None,
+                results: None,
+                errors: missing
+                    .iter()
+                    .map(|m| AtentoError::Validation(m.to_string()))
+                    .collect(),
+                status: STATUS_NOK.to_string(),
+                started_at: Some(now.clone()),
+                finished_at: Some(now),
+                metadata: self.metadata.clone(),
+                run_id: None,
+            };
+            #[cfg(feature = "http")]
+            self.notify_webhook(&result);
+            return Some(result);
+        }
+        None
+    }
+
+    #[cfg(feature = "exec")]
+    fn run_dispatch<E: CommandExecutor + Sync>(&self, executor: &E) -> ChainResult {
+        if self.parallel {
+            self.run_parallel_with_executor(executor)
+        } else {
+            self.run_with_executor(executor)
+        }
+    }
+
+    /// Returns a copy of the chain with the values of declared parameters
+    /// replaced by `overrides`.
+    ///
+    /// Each override is parsed according to the parameter's `DataType`, so
+    /// `"42"` becomes an int, `"true"` a bool and datetimes must be RFC 3339.
+    ///
+    /// # Errors
+    /// Returns a `Validation` error if an override names a parameter the chain
+    /// does not declare, and a `TypeConversion` error if a value does not parse.
+    pub fn with_parameters(&self, overrides: &HashMap<String, String>) -> Result<Self> {
+        let mut chain = self.clone();
+        for (key, text) in overrides {
+            let param = chain.parameters.get_mut(key).ok_or_else(|| {
+                AtentoError::Validation(format!(
+                    "Cannot override parameter '{key}': it is not declared in the chain"
+                ))
+            })?;
+            param.value = Some(
+                param
+                    .parse_value(text)
+                    .map_err(|e| with_value_context(e, || format!("parameter '{key}'")))?,
+            );
+        }
+        Ok(chain)
+    }
+
+    /// Executes the chain like [`Chain::run`] with parameter values taken from
+    /// `overrides`, see [`Chain::with_parameters`].
+    ///
+    /// # Errors
+    /// Returns an error if an override is unknown or does not parse, or if a
+    /// parameter is still without a value once the overrides are applied.
+    #[cfg(feature = "exec")]
+    pub fn run_with_parameters(&self, overrides: &HashMap<String, String>) -> Result<ChainResult> {
+        Ok(self.with_complete_parameters(overrides)?.run())
+    }
+
+    /// Executes the chain like [`Chain::run_with_executor`] with parameter
+    /// values taken from `overrides`, see [`Chain::with_parameters`].
+    ///
+    /// # Errors
+    /// Returns the same errors as [`Chain::run_with_parameters`].
+    #[cfg(feature = "exec")]
+    pub fn run_with_parameters_and_executor<E: CommandExecutor>(
+        &self,
+        executor: &E,
+        overrides: &HashMap<String, String>,
+    ) -> Result<ChainResult> {
+        Ok(self
+            .with_complete_parameters(overrides)?
+            .run_with_executor(executor))
+    }
+
+    /// Executes only part of the chain, e.g. to rerun one step while
+    /// developing a chain without running the steps before it.
+    ///
+    /// `only` restricts the run to the named steps and `from` starts it at the
+    /// named step, running every step after it in execution order; with both,
+    /// the named steps from `from` on run. Steps run one after another, also
+    /// for `parallel` chains, on the `remote` host when one is configured.
+    /// `seed_outputs` provides the values of the steps that do not run, keyed
+    /// by their reference, e.g. `steps.build.outputs.version`. Chain results
+    /// that reference a step that did not run are left out unless seeded.
+    /// `on_failure` handlers still only run after the step naming them fails.
+    ///
+    /// With `preflight: true` the interpreters are checked as in [`Chain::run`],
+    /// once `only` and `from` are known to name existing steps.
+    ///
+    /// # Errors
+    /// Returns a `Validation` error if `only` or `from` names a step the chain
+    /// does not define.
+    #[cfg(feature = "exec")]
+    pub fn run_subset(
+        &self,
+        only: Option<&[String]>,
+        from: Option<&str>,
+        seed_outputs: HashMap<String, String>,
+    ) -> Result<ChainResult> {
+        self.check_subset(only, from)?;
+        if let Some(result) = self.preflight_failure() {
+            return Ok(result);
+        }
+        match &self.remote {
+            Some(remote) => self.run_subset_with_executor(remote, only, from, seed_outputs),
+            None => self.run_subset_with_executor(&SystemExecutor, only, from, seed_outputs),
+        }
+    }
+
+    /// Executes part of the chain like [`Chain::run_subset`] on `executor`.
+    ///
+    /// # Errors
+    /// Returns the same errors as [`Chain::run_subset`].
+    #[cfg(feature = "exec")]
+    pub fn run_subset_with_executor<E: CommandExecutor>(
+        &self,
+        executor: &E,
+        only: Option<&[String]>,
+        from: Option<&str>,
+        seed_outputs: HashMap<String, String>,
+    ) -> Result<ChainResult> {
+        self.check_subset(only, from)?;
+
+        let ordered = self.ordered_steps();
+        let first = from
+            .and_then(|from| ordered.iter().position(|step| *step == from))
+            .unwrap_or(0);
+        let selected: HashSet<&String> = ordered[first..]
+            .iter()
+            .copied()
+            .filter(|step| only.is_none_or(|only| only.contains(step)))
+            .collect();
+        let (steps, skipped): (Vec<_>, Vec<_>) = self
+            .scheduled_steps()
+            .into_iter()
+            .partition(|step| selected.contains(step));
+
+        let mut state = self.run_state();
+        state.resolved_outputs = seed_outputs;
+        state.skipped = skipped.into_iter().cloned().collect();
+        Ok(self.run_sequential(executor, &mut NoopObserver, None, steps, state))
+    }
+
+    /// Checks that the steps named by `only` and `from` exist.
+    #[cfg(feature = "exec")]
+    fn check_subset(&self, only: Option<&[String]>, from: Option<&str>) -> Result<()> {
+        match only
+            .into_iter()
+            .flatten()
+            .map(String::as_str)
+            .chain(from)
+            .find(|step| !self.steps.contains_key(*step))
+        {
+            Some(unknown) => Err(AtentoError::Validation(format!(
+                "Cannot run step '{unknown}': the chain does not define it"
+            ))),
+            None => Ok(()),
+        }
+    }
+
+    /// Applies `overrides` and checks that every parameter then has a value.
+    #[cfg(feature = "exec")]
+    fn with_complete_parameters(&self, overrides: &HashMap<String, String>) -> Result<Self> {
+        let chain = self.with_parameters(overrides)?;
+        match chain.missing_parameters_error() {
+            Some(err) => Err(err),
+            None => Ok(chain),
+        }
     }
 }
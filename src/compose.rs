@@ -0,0 +1,154 @@
+use crate::chain::Chain;
+use crate::env::EnvValue;
+use crate::errors::{AtentoError, Result};
+use crate::input::Input;
+use crate::step::Step;
+use regex::{Captures, Regex};
+
+/// Matches a `{{ steps.<id>... }}` placeholder, capturing the reference
+const STEP_REF_PLACEHOLDER_PATTERN: &str = r"(\{\{\s*)(steps\.[^\s{}]+)(\s*\}\})";
+
+impl Chain {
+    /// Adds the steps, parameters and interpreters of `other` to this chain,
+    /// e.g. to stitch shared setup or teardown chains into the chains that use
+    /// them before running.
+    ///
+    /// Each step of `other` is added after the steps of this chain under the
+    /// key `<prefix><id>`, in its declared order. References to those steps in
+    /// inputs, env entries, `stdin`, `foreach`, `needs`, `on_failure` and
+    /// `{{ steps.<id>... }}` placeholders are rewritten to the new keys. Steps
+    /// of `other` keep its chain-level `env` entries, `default_interpreter` and
+    /// `image` by having them copied into each step. The `results` and settings of
+    /// `other`, such as `name` and `timeout`, are not merged.
+    ///
+    /// # Errors
+    /// Returns a `Validation` error, leaving this chain unchanged, when a
+    /// parameter of `other` is already declared, a prefixed step key is
+    /// already taken, or `other` defines an interpreter key differently.
+    pub fn merge(&mut self, other: Chain, prefix: &str) -> Result<()> {
+        let mut parameters: Vec<&String> = other
+            .parameters
+            .keys()
+            .filter(|name| self.parameters.contains_key(*name))
+            .collect();
+        parameters.sort();
+        if let Some(name) = parameters.first() {
+            return Err(AtentoError::Validation(format!(
+                "Cannot merge chain: parameter '{name}' is already declared"
+            )));
+        }
+        if let Some(id) = other
+            .steps
+            .keys()
+            .find(|id| self.steps.contains_key(&format!("{prefix}{id}")))
+        {
+            return Err(AtentoError::Validation(format!(
+                "Cannot merge chain: step '{prefix}{id}' is already defined"
+            )));
+        }
+        let mut interpreters: Vec<&String> = other
+            .interpreters
+            .iter()
+            .filter(|(key, interpreter)| {
+                self.interpreters
+                    .get(*key)
+                    .is_some_and(|existing| existing != *interpreter)
+            })
+            .map(|(key, _)| key)
+            .collect();
+        interpreters.sort();
+        if let Some(key) = interpreters.first() {
+            return Err(AtentoError::Validation(format!(
+                "Cannot merge chain: interpreter '{key}' is already defined differently"
+            )));
+        }
+
+        let ids: Vec<String> = other.steps.keys().cloned().collect();
+        for (id, mut step) in other.steps {
+            for (name, value) in &other.env {
+                step.env
+                    .entry(name.clone())
+                    .or_insert_with(|| value.clone());
+            }
+            if step.interpreter.is_none() {
+                step.interpreter.clone_from(&other.default_interpreter);
+            }
+            if step.image.is_none() {
+                step.image.clone_from(&other.image);
+            }
+            prefix_step_refs(&mut step, &ids, prefix);
+            self.steps.insert(format!("{prefix}{id}"), step);
+        }
+        self.parameters.extend(other.parameters);
+        for (key, interpreter) in other.interpreters {
+            self.interpreters.entry(key).or_insert(interpreter);
+        }
+        Ok(())
+    }
+}
+
+/// Rewrites the references `step` makes to the steps `ids` to their keys
+/// with `prefix`.
+fn prefix_step_refs(step: &mut Step, ids: &[String], prefix: &str) {
+    for input in step.inputs.values_mut() {
+        if let Input::Ref { ref_ } = input
+            && let Some(prefixed) = prefixed_ref(ref_, ids, prefix)
+        {
+            *ref_ = prefixed;
+        }
+    }
+
+    for value in step.env.values_mut().chain(step.stdin.as_mut()) {
+        match value {
+            EnvValue::Template(text) => *text = prefix_placeholders(text, ids, prefix),
+            EnvValue::Input(Input::Ref { ref_ }) => {
+                if let Some(prefixed) = prefixed_ref(ref_, ids, prefix) {
+                    *ref_ = prefixed;
+                }
+            }
+            EnvValue::Input(_) => {}
+        }
+    }
+
+    step.script = prefix_placeholders(&step.script, ids, prefix);
+    if let Some(workdir) = &mut step.workdir {
+        *workdir = prefix_placeholders(workdir, ids, prefix);
+    }
+    if let Some(foreach) = &mut step.foreach
+        && let Some(prefixed) = prefixed_ref(foreach, ids, prefix)
+    {
+        *foreach = prefixed;
+    }
+    for id in step.needs.iter_mut().chain(step.on_failure.as_mut()) {
+        if ids.contains(id) {
+            *id = format!("{prefix}{id}");
+        }
+    }
+}
+
+/// Rewrites the `{{ steps.<id>... }}` placeholders in `text` that name one of
+/// the steps `ids`.
+fn prefix_placeholders(text: &str, ids: &[String], prefix: &str) -> String {
+    #[allow(clippy::expect_used)]
+    let re = Regex::new(STEP_REF_PLACEHOLDER_PATTERN)
+        .expect("Step reference placeholder regex pattern is valid");
+    re.replace_all(text, |caps: &Captures| {
+        match prefixed_ref(&caps[2], ids, prefix) {
+            Some(prefixed) => format!("{}{prefixed}{}", &caps[1], &caps[3]),
+            None => caps[0].to_string(),
+        }
+    })
+    .into_owned()
+}
+
+/// Returns `reference` with `prefix` added to its step id, if it is a
+/// `steps.<id>.<field>` reference to one of the steps `ids`.
+fn prefixed_ref(reference: &str, ids: &[String], prefix: &str) -> Option<String> {
+    let rest = reference.strip_prefix("steps.")?;
+    ids.iter()
+        .any(|id| {
+            rest.strip_prefix(id.as_str())
+                .is_some_and(|field| field.starts_with('.'))
+        })
+        .then(|| format!("steps.{prefix}{rest}"))
+}
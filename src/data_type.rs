@@ -1,13 +1,17 @@
 use crate::errors::{AtentoError, Result};
+use regex::Regex;
 use serde::{Deserialize, Serialize};
 use serde_yaml::Value;
 use std::fmt;
+use std::time::{SystemTime, UNIX_EPOCH};
 
 /// Represents the data type of a parameter, input, or output value.
-#[derive(PartialEq, Eq, Debug, Clone, Deserialize, Serialize)]
+#[derive(PartialEq, Eq, Debug, Clone, Default, Deserialize, Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
 #[serde(rename_all = "lowercase")]
 pub enum DataType {
     /// UTF-8 string value
+    #[default]
     String,
     /// 64-bit signed integer
     Int,
@@ -17,14 +21,36 @@ pub enum DataType {
     Bool,
     /// ISO 8601 datetime string
     DateTime,
+    /// Sequence of scalar values, substituted as text joined by a separator
+    List,
 }
 
-impl Default for DataType {
-    fn default() -> Self {
-        Self::String
+impl DataType {
+    /// Checks that a YAML value is acceptable for this data type.
+    ///
+    /// Ints and floats accept YAML numbers or numeric strings, bools must be
+    /// YAML booleans, datetimes must be RFC 3339 strings and lists must be
+    /// sequences of scalars.
+    ///
+    /// # Errors
+    /// Returns a `TypeConversion` error if the value does not match.
+    pub fn validate_value(&self, value: &Value) -> Result<()> {
+        to_string_value(self, value)?;
+
+        if *self == Self::DateTime && !value.as_str().is_some_and(|s| is_rfc3339(s.trim())) {
+            return Err(AtentoError::TypeConversion {
+                expected: "RFC 3339 datetime".to_string(),
+                got: format!("{value:?}"),
+            });
+        }
+
+        Ok(())
     }
 }
 
+/// Separator placed between list elements when none is configured.
+pub const DEFAULT_LIST_SEPARATOR: &str = " ";
+
 impl fmt::Display for DataType {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let s = match self {
@@ -33,6 +59,7 @@ impl fmt::Display for DataType {
             Self::Float => "float",
             Self::Bool => "bool",
             Self::DateTime => "datetime",
+            Self::List => "list",
         };
         write!(f, "{s}")
     }
@@ -54,25 +81,23 @@ pub fn to_string_value(type_: &DataType, value: &Value) -> Result<String> {
                 })
         }
 
-        DataType::Int => {
-            value
-                .as_i64()
-                .map(|i| i.to_string())
-                .ok_or_else(|| AtentoError::TypeConversion {
-                    expected: "int".to_string(),
-                    got: format!("{value:?}"),
-                })
-        }
+        DataType::Int => value
+            .as_i64()
+            .or_else(|| value.as_str().and_then(|s| s.trim().parse().ok()))
+            .map(|i| i.to_string())
+            .ok_or_else(|| AtentoError::TypeConversion {
+                expected: "int".to_string(),
+                got: format!("{value:?}"),
+            }),
 
-        DataType::Float => {
-            value
-                .as_f64()
-                .map(|f| f.to_string())
-                .ok_or_else(|| AtentoError::TypeConversion {
-                    expected: "float".to_string(),
-                    got: format!("{value:?}"),
-                })
-        }
+        DataType::Float => value
+            .as_f64()
+            .or_else(|| value.as_str().and_then(|s| s.trim().parse().ok()))
+            .map(|f| f.to_string())
+            .ok_or_else(|| AtentoError::TypeConversion {
+                expected: "float".to_string(),
+                got: format!("{value:?}"),
+            }),
 
         DataType::Bool => {
             value
@@ -93,5 +118,190 @@ pub fn to_string_value(type_: &DataType, value: &Value) -> Result<String> {
                     got: format!("{value:?}"),
                 })
         }
+
+        DataType::List => to_list_string(value, DEFAULT_LIST_SEPARATOR),
+    }
+}
+
+/// Converts a YAML sequence of scalars to text, joining the elements with `separator`.
+///
+/// # Errors
+/// Returns an error if the value is not a sequence or an element is not a scalar.
+pub fn to_list_string(value: &Value, separator: &str) -> Result<String> {
+    let items = value
+        .as_sequence()
+        .ok_or_else(|| AtentoError::TypeConversion {
+            expected: "list".to_string(),
+            got: format!("{value:?}"),
+        })?;
+
+    let elements = items
+        .iter()
+        .map(|item| match item {
+            Value::String(s) => Ok(s.clone()),
+            Value::Number(n) => Ok(n.to_string()),
+            Value::Bool(b) => Ok(b.to_string()),
+            other => Err(AtentoError::TypeConversion {
+                expected: "list of scalars".to_string(),
+                got: format!("{other:?}"),
+            }),
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(elements.join(separator))
+}
+
+/// Converts text captured from a step's stdout into a JSON value of the specified data type.
+///
+/// Ints are parsed as `i64`, floats as `f64`, bools accept `true`/`false`/`1`/`0` and
+/// datetimes must be valid RFC 3339 timestamps; strings and datetimes are kept as text.
+/// Lists split the text on whitespace into an array of strings.
+///
+/// # Errors
+/// Returns an error if the text cannot be converted to the expected `DataType`.
+pub fn to_json_value(type_: &DataType, text: &str) -> Result<serde_json::Value> {
+    let mismatch = || AtentoError::TypeConversion {
+        expected: type_.to_string(),
+        got: format!("'{text}'"),
+    };
+
+    match type_ {
+        DataType::String => Ok(serde_json::Value::String(text.to_string())),
+
+        DataType::Int => text
+            .trim()
+            .parse::<i64>()
+            .map(serde_json::Value::from)
+            .map_err(|_| mismatch()),
+
+        DataType::Float => text
+            .trim()
+            .parse::<f64>()
+            .ok()
+            .and_then(serde_json::Number::from_f64)
+            .map(serde_json::Value::Number)
+            .ok_or_else(mismatch),
+
+        DataType::Bool => match text.trim() {
+            "true" | "1" => Ok(serde_json::Value::Bool(true)),
+            "false" | "0" => Ok(serde_json::Value::Bool(false)),
+            _ => Err(mismatch()),
+        },
+
+        DataType::DateTime => {
+            let trimmed = text.trim();
+            if is_rfc3339(trimmed) {
+                Ok(serde_json::Value::String(trimmed.to_string()))
+            } else {
+                Err(mismatch())
+            }
+        }
+
+        DataType::List => Ok(serde_json::Value::Array(
+            text.split_whitespace()
+                .map(|item| serde_json::Value::String(item.to_string()))
+                .collect(),
+        )),
+    }
+}
+
+/// Parses text, such as a value given on the command line, into a YAML value of
+/// the specified data type.
+///
+/// The text is accepted as described for [`to_json_value`]; lists split on whitespace.
+///
+/// # Errors
+/// Returns an error if the text cannot be converted to the expected `DataType`.
+pub fn from_text(type_: &DataType, text: &str) -> Result<Value> {
+    let json = to_json_value(type_, text)?;
+    serde_yaml::to_value(json).map_err(|e| AtentoError::TypeConversion {
+        expected: type_.to_string(),
+        got: e.to_string(),
+    })
+}
+
+/// Returns the textual form of a JSON value, without quotes for strings.
+#[must_use]
+pub fn to_text(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Checks that `text` is an RFC 3339 timestamp such as `2024-01-15T10:30:00Z`.
+fn is_rfc3339(text: &str) -> bool {
+    #[allow(clippy::expect_used)]
+    let re = Regex::new(
+        r"^(\d{4})-(\d{2})-(\d{2})[Tt ](\d{2}):(\d{2}):(\d{2})(\.\d+)?([Zz]|[+-](\d{2}):(\d{2}))$",
+    )
+    .expect("RFC 3339 regex pattern is valid");
+
+    let Some(caps) = re.captures(text) else {
+        return false;
+    };
+    let field = |i: usize| {
+        caps.get(i)
+            .and_then(|m| m.as_str().parse::<u32>().ok())
+            .unwrap_or(0)
+    };
+
+    let (year, month, day) = (field(1), field(2), field(3));
+    let leap = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+    let days_in_month = match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if leap => 29,
+        2 => 28,
+        _ => return false,
+    };
+
+    (1..=days_in_month).contains(&day)
+        && field(4) <= 23
+        && field(5) <= 59
+        && field(6) <= 60
+        && field(9) <= 23
+        && field(10) <= 59
+}
+
+/// Formats `time` as an RFC 3339 UTC timestamp with milliseconds, such as
+/// `2024-01-15T10:30:00.123Z`; times before 1970 are clamped to the epoch.
+pub(crate) fn rfc3339_timestamp(time: SystemTime) -> String {
+    let since_epoch = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+    let secs = since_epoch.as_secs();
+    let (days, secs_of_day) = (secs / 86_400, secs % 86_400);
+
+    // Civil date from days since the epoch, after Howard Hinnant's `civil_from_days`
+    let z = days + 719_468;
+    let era = z / 146_097;
+    let day_of_era = z % 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + u64::from(month <= 2);
+
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}.{:03}Z",
+        secs_of_day / 3600,
+        secs_of_day % 3600 / 60,
+        secs_of_day % 60,
+        since_epoch.subsec_millis()
+    )
+}
+
+/// Returns the current time, or `None` on `wasm32-unknown-unknown`, where
+/// `SystemTime::now` panics for lack of a clock.
+pub(crate) fn now() -> Option<SystemTime> {
+    if cfg!(all(target_arch = "wasm32", target_os = "unknown")) {
+        None
+    } else {
+        Some(SystemTime::now())
     }
 }
@@ -0,0 +1,236 @@
+use crate::chain::Chain;
+use crate::errors::{AtentoError, Result};
+use crate::location::{SourceLocation, SourceMap};
+use crate::result_ref::ResultValue;
+use serde::Serialize;
+use std::collections::HashSet;
+
+/// How serious a [`Diagnostic`] is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    /// The chain cannot run
+    Error,
+    /// The chain runs, but its configuration looks wrong
+    Warning,
+}
+
+/// One finding of [`Chain::validate_all`].
+///
+/// `code` is stable and meant for tooling; `message` is meant for people and
+/// may change. The codes are:
+///
+/// | Code | Finding |
+/// |------|---------|
+/// | `invalid-parameter` | A parameter has no value or a value of the wrong type |
+/// | `invalid-config` | A chain setting such as `remote`, `webhook`, `include` or `default_interpreter` is invalid |
+/// | `invalid-template` | A step cannot be instantiated from its `template` |
+/// | `invalid-matrix` | A step's `matrix` cannot be expanded |
+/// | `missing-script` | A step has no script, or both `script` and `script_file` |
+/// | `invalid-needs` | A step needs itself or a step that does not exist |
+/// | `unknown-interpreter` | A step's interpreter is not declared |
+/// | `interpreter-mismatch` | A step quotes values for another interpreter's scripts |
+/// | `invalid-value` | An inline input or result value does not match its type |
+/// | `unresolved-reference` | A reference names an unknown parameter or step output |
+/// | `forward-reference` | A reference names the output of a step that runs later |
+/// | `undeclared-input` | A placeholder names an input the step does not declare |
+/// | `invalid-step` | Any other problem with a step's settings |
+/// | `invalid-output` | An output declares no or conflicting extraction modes |
+/// | `invalid-regex` | An output's regex pattern does not compile or lacks its capture group |
+/// | `dependency-cycle` | Steps depend on each other |
+/// | `invalid-artifact` | An artifact is not supported or outside the artifacts root |
+/// | `invalid-failure-handler` | An `on_failure` step is missing or misused |
+/// | `unused-input` | Warning: an input is declared but never used |
+/// | `unreferenced-output` | Warning: no step or result references an output |
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub code: String,
+    /// Id of the step the finding is about, if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub step: Option<String>,
+    /// Key of the chain result the finding is about, if any
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<String>,
+    pub message: String,
+    /// File that defines the step or result, for chains with a
+    /// [`Chain::source_map`]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    /// Where in `file` the step or result is defined
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location: Option<SourceLocation>,
+}
+
+/// What a finding is about.
+#[derive(Clone, Copy)]
+pub(crate) enum Subject<'a> {
+    Chain,
+    Step(&'a str),
+    Result(&'a str),
+}
+
+/// When the validation checks stop.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Stop {
+    /// At the first error or warning, which is returned as an error
+    AtFirstFinding,
+    /// At the first error; warnings are collected
+    AtFirstError,
+    /// Never; errors and warnings are collected
+    Never,
+}
+
+/// Collects the findings of the validation checks, locating them with
+/// `sources` when the chain has a source map.
+pub(crate) struct Diagnostics<'a> {
+    stop: Stop,
+    sources: Option<&'a SourceMap>,
+    pub(crate) findings: Vec<Diagnostic>,
+}
+
+impl<'a> Diagnostics<'a> {
+    pub(crate) fn new(stop: Stop, sources: Option<&'a SourceMap>) -> Self {
+        Self {
+            stop,
+            sources,
+            findings: Vec::new(),
+        }
+    }
+
+    /// Records the error of `result` under `code`. Returns the error when
+    /// checks stop at the first one, and otherwise the value of `result`, if
+    /// there is one.
+    pub(crate) fn check<T>(
+        &mut self,
+        code: &str,
+        subject: Subject<'_>,
+        result: Result<T>,
+    ) -> Result<Option<T>> {
+        match result {
+            Ok(value) => Ok(Some(value)),
+            Err(e) if self.stop == Stop::Never => {
+                self.push(Severity::Error, code, subject, e.to_string());
+                Ok(None)
+            }
+            Err(e) => Err(match self.locate(subject) {
+                Some((file, location)) => e.located(file, location),
+                None => e,
+            }),
+        }
+    }
+
+    /// Like [`Diagnostics::check`], but records `UnresolvedReference` errors
+    /// under `unresolved-reference` and other errors under `code`.
+    pub(crate) fn check_reference<T>(
+        &mut self,
+        code: &str,
+        subject: Subject<'_>,
+        result: Result<T>,
+    ) -> Result<Option<T>> {
+        let code = match &result {
+            Err(AtentoError::UnresolvedReference { .. }) => "unresolved-reference",
+            _ => code,
+        };
+        self.check(code, subject, result)
+    }
+
+    /// Records `message` as a warning under `code`, or returns it as a
+    /// `Validation` error when checks stop at the first finding.
+    pub(crate) fn warn(&mut self, code: &str, subject: Subject<'_>, message: String) -> Result<()> {
+        if self.stop == Stop::AtFirstFinding {
+            let error = AtentoError::Validation(message);
+            return Err(match self.locate(subject) {
+                Some((file, location)) => error.located(file, location),
+                None => error,
+            });
+        }
+        self.push(Severity::Warning, code, subject, message);
+        Ok(())
+    }
+
+    fn push(&mut self, severity: Severity, code: &str, subject: Subject<'_>, message: String) {
+        let (step, result) = match subject {
+            Subject::Chain => (None, None),
+            Subject::Step(step) => (Some(step.to_string()), None),
+            Subject::Result(result) => (None, Some(result.to_string())),
+        };
+        let (file, location) = self
+            .locate(subject)
+            .map(|(file, location)| (Some(file.to_string()), Some(location)))
+            .unwrap_or_default();
+        self.findings.push(Diagnostic {
+            severity,
+            code: code.to_string(),
+            step,
+            result,
+            message,
+            file,
+            location,
+        });
+    }
+
+    /// Returns where the subject of a finding is defined, if known.
+    fn locate(&self, subject: Subject<'_>) -> Option<(&'a str, SourceLocation)> {
+        let sources = self.sources?;
+        match subject {
+            Subject::Chain => None,
+            Subject::Step(step) => sources.step(step),
+            Subject::Result(result) => sources.locate(&format!("results.{result}")),
+        }
+    }
+}
+
+impl Chain {
+    /// Runs every validation check and returns all findings instead of
+    /// stopping at the first error, e.g. for an editor or linter that shows
+    /// them together. Besides the findings of [`Chain::validate_detailed`],
+    /// it warns about step outputs that no step or chain result references.
+    ///
+    /// The chain is valid when no finding has [`Severity::Error`]. A check
+    /// that fails may hide findings that depend on it; a step with an unknown
+    /// interpreter, for example, is not checked for quoting.
+    #[must_use]
+    pub fn validate_all(&self) -> Vec<Diagnostic> {
+        let mut diagnostics = Diagnostics::new(Stop::Never, self.source_map.as_ref());
+        // Nothing stops the checks, so they cannot return an error
+        let _ = self.check(&mut diagnostics);
+        for (step_key, output) in self.unreferenced_outputs() {
+            let _ = diagnostics.warn(
+                "unreferenced-output",
+                Subject::Step(step_key),
+                format!(
+                    "Step '{step_key}' has output '{output}' that no step or chain result references"
+                ),
+            );
+        }
+        diagnostics.findings
+    }
+
+    /// Lists the outputs, by step key, that no step or chain result
+    /// references, in declared step order.
+    fn unreferenced_outputs(&self) -> Vec<(&str, &str)> {
+        let mut referenced: HashSet<String> = self
+            .steps
+            .iter()
+            .flat_map(|(step_key, step)| self.step_references(step_key, step))
+            .collect();
+        referenced.extend(self.results.values().filter_map(|result| match result {
+            ResultValue::Ref(result_ref) => Some(result_ref.ref_.clone()),
+            ResultValue::Inline { .. } => None,
+        }));
+
+        let mut unreferenced = Vec::new();
+        for (step_key, step) in &self.steps {
+            let mut outputs: Vec<&String> = step.outputs.keys().collect();
+            outputs.sort();
+            unreferenced.extend(
+                outputs
+                    .into_iter()
+                    .filter(|output| !referenced.contains(&Self::make_output_key(step_key, output)))
+                    .map(|output| (step_key.as_str(), output.as_str())),
+            );
+        }
+        unreferenced
+    }
+}
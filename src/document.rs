@@ -0,0 +1,84 @@
+use crate::chain::{Chain, check_fields};
+use crate::errors::{AtentoError, Result};
+use crate::location::SourceLocation;
+
+impl Chain {
+    /// Parses a chain from a TOML document with the same fields as the YAML
+    /// format, e.g. `[steps.build]` tables for steps.
+    ///
+    /// Default interpreters are merged in and unknown fields are rejected
+    /// unless the chain sets `lenient = true`, exactly as for YAML chains.
+    ///
+    /// # Errors
+    /// Returns a `Parse` error if the document is not valid TOML or does not
+    /// describe a chain, and a `Validation` error for unknown fields.
+    #[cfg(feature = "toml")]
+    pub fn from_toml(toml: &str) -> Result<Self> {
+        parse_toml(toml, "<string>")
+    }
+
+    /// Parses a chain from a JSON document with the same fields as the YAML
+    /// format.
+    ///
+    /// Default interpreters are merged in and unknown fields are rejected
+    /// unless the chain sets `"lenient": true`, exactly as for YAML chains.
+    ///
+    /// # Errors
+    /// Returns a `Parse` error if the document is not valid JSON or does not
+    /// describe a chain, and a `Validation` error for unknown fields.
+    #[cfg(feature = "json-input")]
+    pub fn from_json(json: &str) -> Result<Self> {
+        parse_json(json, "<string>")
+    }
+}
+
+/// Parses the TOML chain document `toml`, read from `context`.
+#[cfg(feature = "toml")]
+pub(crate) fn parse_toml(toml: &str, context: &str) -> Result<Chain> {
+    let document: serde_yaml::Value = toml::from_str(toml).map_err(|e| {
+        let location = e
+            .span()
+            .map(|span| SourceLocation::at_offset(toml, span.start));
+        let message = match location {
+            Some(location) => format!("{} at {location}", e.message()),
+            None => e.message().to_string(),
+        };
+        parse_error("TOML", context, message, location)
+    })?;
+    from_document(document, "TOML", context)
+}
+
+/// Parses the JSON chain document `json`, read from `context`.
+#[cfg(feature = "json-input")]
+pub(crate) fn parse_json(json: &str, context: &str) -> Result<Chain> {
+    let document: serde_yaml::Value = serde_json::from_str(json).map_err(|e| {
+        let location = (e.line() > 0).then(|| SourceLocation {
+            line: e.line(),
+            column: e.column(),
+        });
+        parse_error("JSON", context, e.to_string(), location)
+    })?;
+    from_document(document, "JSON", context)
+}
+
+/// Builds the chain from a TOML or JSON document read into a YAML value,
+/// which keeps the order of the steps, so both go through the same field
+/// checks and defaults as YAML chains.
+fn from_document(document: serde_yaml::Value, format: &str, context: &str) -> Result<Chain> {
+    check_fields(&document, context, None)?;
+    serde_yaml::from_value(document).map_err(|e| parse_error(format, context, e.to_string(), None))
+}
+
+fn parse_error(
+    format: &str,
+    context: &str,
+    message: String,
+    location: Option<SourceLocation>,
+) -> AtentoError {
+    AtentoError::Parse {
+        format: format.to_string(),
+        context: context.to_string(),
+        message,
+        location,
+    }
+}
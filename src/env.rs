@@ -0,0 +1,23 @@
+use crate::input::Input;
+use serde::{Deserialize, Serialize};
+
+/// Value of an environment variable set for a step.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(untagged)]
+pub enum EnvValue {
+    /// Plain text; `{{ inputs.x }}` and `{{ parameters.x }}` placeholders are substituted
+    Template(String),
+    /// Inline typed value or reference, resolved like a step input
+    Input(Input),
+}
+
+/// Returns whether `name` is a portable environment variable name, matching
+/// `[A-Za-z_][A-Za-z0-9_]*`.
+pub(crate) fn is_env_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
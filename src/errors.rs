@@ -1,22 +1,53 @@
-use serde::Serialize;
+use crate::location::SourceLocation;
+use serde::{Deserialize, Deserializer, Serialize};
 use std::fmt;
 
 /// The main error type for the Atento chain engine.
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, Deserialize)]
 #[serde(tag = "type", content = "data")]
 pub enum AtentoError {
     /// I/O error when reading files
     Io {
         path: String,
-        #[serde(serialize_with = "serialize_io_error")]
+        #[serde(
+            serialize_with = "serialize_io_error",
+            deserialize_with = "deserialize_io_error"
+        )]
+        source: std::io::Error,
+    },
+
+    /// I/O error when writing a result file
+    IoWrite {
+        path: String,
+        #[serde(
+            serialize_with = "serialize_io_error",
+            deserialize_with = "deserialize_io_error"
+        )]
         source: std::io::Error,
     },
 
     /// YAML parsing error
     YamlParse {
         context: String,
-        #[serde(serialize_with = "serialize_yaml_error")]
+        #[serde(
+            serialize_with = "serialize_yaml_error",
+            deserialize_with = "deserialize_yaml_error"
+        )]
         source: serde_yaml::Error,
+        /// Where in the YAML the error was found, when known
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        location: Option<SourceLocation>,
+    },
+
+    /// TOML or JSON chain parsing error
+    Parse {
+        /// `TOML` or `JSON`
+        format: String,
+        context: String,
+        message: String,
+        /// Where in the document the error was found, when known
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        location: Option<SourceLocation>,
     },
 
     /// JSON serialization error
@@ -40,6 +71,9 @@ pub enum AtentoError {
     /// Timeout error
     Timeout { context: String, timeout_secs: u64 },
 
+    /// The run was cancelled through its [`CancellationToken`](crate::CancellationToken)
+    Cancelled { context: String },
+
     /// Script runner error
     Runner(String),
 }
@@ -65,17 +99,318 @@ where
     serializer.serialize_str(&error.to_string())
 }
 
+// Deserialized I/O and YAML errors keep only the message they were serialized with
+fn deserialize_io_error<'de, D>(deserializer: D) -> std::result::Result<std::io::Error, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let message = String::deserialize(deserializer)?;
+    Ok(std::io::Error::other(message))
+}
+
+fn deserialize_yaml_error<'de, D>(
+    deserializer: D,
+) -> std::result::Result<serde_yaml::Error, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let message = String::deserialize(deserializer)?;
+    Ok(serde::de::Error::custom(message))
+}
+
 // Note: JsonSerialize variant stores a message string, see From impl below.
 
+impl AtentoError {
+    /// Builds a `YamlParse` error, keeping the location `source` reports.
+    pub(crate) fn yaml_parse(context: impl Into<String>, source: serde_yaml::Error) -> Self {
+        Self::YamlParse {
+            context: context.into(),
+            location: source.location().map(SourceLocation::from),
+            source,
+        }
+    }
+
+    /// Appends where in `file` the element a `Validation` or
+    /// `UnresolvedReference` error is about is defined; other errors are
+    /// returned as they are.
+    pub(crate) fn located(self, file: &str, location: SourceLocation) -> Self {
+        let place = format!("(at {location} of '{file}')");
+        match self {
+            Self::Validation(message) => Self::Validation(format!("{message} {place}")),
+            Self::UnresolvedReference { reference, context } => Self::UnresolvedReference {
+                reference,
+                context: format!("{context} {place}"),
+            },
+            other => other,
+        }
+    }
+
+    /// Returns the error with every occurrence of the `secrets` values in its
+    /// messages replaced by a mask, for errors reported in a `ChainResult`.
+    pub(crate) fn masked(self, secrets: &[String]) -> Self {
+        if secrets.is_empty() {
+            return self;
+        }
+        let mask = |text: String| crate::step::mask_secrets(&text, secrets);
+        match self {
+            Self::Io { path, source } => Self::Io {
+                path: mask(path),
+                source: std::io::Error::new(source.kind(), mask(source.to_string())),
+            },
+            Self::IoWrite { path, source } => Self::IoWrite {
+                path: mask(path),
+                source: std::io::Error::new(source.kind(), mask(source.to_string())),
+            },
+            Self::YamlParse {
+                context,
+                source,
+                location,
+            } => {
+                let message = source.to_string();
+                let masked = mask(message.clone());
+                Self::YamlParse {
+                    context: mask(context),
+                    source: if masked == message {
+                        source
+                    } else {
+                        serde::de::Error::custom(masked)
+                    },
+                    location,
+                }
+            }
+            Self::Parse {
+                format,
+                context,
+                message,
+                location,
+            } => Self::Parse {
+                format,
+                context: mask(context),
+                message: mask(message),
+                location,
+            },
+            Self::JsonSerialize { message } => Self::JsonSerialize {
+                message: mask(message),
+            },
+            Self::Validation(message) => Self::Validation(mask(message)),
+            Self::Execution(message) => Self::Execution(mask(message)),
+            Self::StepExecution { step, reason } => Self::StepExecution {
+                step,
+                reason: mask(reason),
+            },
+            Self::TypeConversion { expected, got } => Self::TypeConversion {
+                expected,
+                got: mask(got),
+            },
+            Self::UnresolvedReference { reference, context } => Self::UnresolvedReference {
+                reference,
+                context: mask(context),
+            },
+            Self::Timeout {
+                context,
+                timeout_secs,
+            } => Self::Timeout {
+                context: mask(context),
+                timeout_secs,
+            },
+            Self::Cancelled { context } => Self::Cancelled {
+                context: mask(context),
+            },
+            Self::Runner(message) => Self::Runner(mask(message)),
+        }
+    }
+}
+
+// I/O and YAML errors are not `Clone`; their clones keep the error kind and message
+impl Clone for AtentoError {
+    fn clone(&self) -> Self {
+        match self {
+            Self::Io { path, source } => Self::Io {
+                path: path.clone(),
+                source: std::io::Error::new(source.kind(), source.to_string()),
+            },
+            Self::IoWrite { path, source } => Self::IoWrite {
+                path: path.clone(),
+                source: std::io::Error::new(source.kind(), source.to_string()),
+            },
+            Self::YamlParse {
+                context,
+                source,
+                location,
+            } => Self::YamlParse {
+                context: context.clone(),
+                source: serde::de::Error::custom(source.to_string()),
+                location: *location,
+            },
+            Self::Parse {
+                format,
+                context,
+                message,
+                location,
+            } => Self::Parse {
+                format: format.clone(),
+                context: context.clone(),
+                message: message.clone(),
+                location: *location,
+            },
+            Self::JsonSerialize { message } => Self::JsonSerialize {
+                message: message.clone(),
+            },
+            Self::Validation(message) => Self::Validation(message.clone()),
+            Self::Execution(message) => Self::Execution(message.clone()),
+            Self::StepExecution { step, reason } => Self::StepExecution {
+                step: step.clone(),
+                reason: reason.clone(),
+            },
+            Self::TypeConversion { expected, got } => Self::TypeConversion {
+                expected: expected.clone(),
+                got: got.clone(),
+            },
+            Self::UnresolvedReference { reference, context } => Self::UnresolvedReference {
+                reference: reference.clone(),
+                context: context.clone(),
+            },
+            Self::Timeout {
+                context,
+                timeout_secs,
+            } => Self::Timeout {
+                context: context.clone(),
+                timeout_secs: *timeout_secs,
+            },
+            Self::Cancelled { context } => Self::Cancelled {
+                context: context.clone(),
+            },
+            Self::Runner(message) => Self::Runner(message.clone()),
+        }
+    }
+}
+
+// I/O and YAML errors compare by kind and message, matching what survives serialization
+impl PartialEq for AtentoError {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (
+                Self::Io { path, source },
+                Self::Io {
+                    path: other_path,
+                    source: other_source,
+                },
+            )
+            | (
+                Self::IoWrite { path, source },
+                Self::IoWrite {
+                    path: other_path,
+                    source: other_source,
+                },
+            ) => path == other_path && source.to_string() == other_source.to_string(),
+            (
+                Self::YamlParse {
+                    context,
+                    source,
+                    location,
+                },
+                Self::YamlParse {
+                    context: other_context,
+                    source: other_source,
+                    location: other_location,
+                },
+            ) => {
+                context == other_context
+                    && source.to_string() == other_source.to_string()
+                    && location == other_location
+            }
+            (
+                Self::Parse {
+                    format,
+                    context,
+                    message,
+                    location,
+                },
+                Self::Parse {
+                    format: other_format,
+                    context: other_context,
+                    message: other_message,
+                    location: other_location,
+                },
+            ) => {
+                format == other_format
+                    && context == other_context
+                    && message == other_message
+                    && location == other_location
+            }
+            (
+                Self::JsonSerialize { message },
+                Self::JsonSerialize {
+                    message: other_message,
+                },
+            )
+            | (Self::Validation(message), Self::Validation(other_message))
+            | (Self::Execution(message), Self::Execution(other_message))
+            | (Self::Runner(message), Self::Runner(other_message))
+            | (
+                Self::Cancelled { context: message },
+                Self::Cancelled {
+                    context: other_message,
+                },
+            ) => message == other_message,
+            (
+                Self::StepExecution { step, reason },
+                Self::StepExecution {
+                    step: other_step,
+                    reason: other_reason,
+                },
+            ) => step == other_step && reason == other_reason,
+            (
+                Self::TypeConversion { expected, got },
+                Self::TypeConversion {
+                    expected: other_expected,
+                    got: other_got,
+                },
+            ) => expected == other_expected && got == other_got,
+            (
+                Self::UnresolvedReference { reference, context },
+                Self::UnresolvedReference {
+                    reference: other_reference,
+                    context: other_context,
+                },
+            ) => reference == other_reference && context == other_context,
+            (
+                Self::Timeout {
+                    context,
+                    timeout_secs,
+                },
+                Self::Timeout {
+                    context: other_context,
+                    timeout_secs: other_timeout_secs,
+                },
+            ) => context == other_context && timeout_secs == other_timeout_secs,
+            _ => false,
+        }
+    }
+}
+
 impl fmt::Display for AtentoError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Self::Io { path, source } => {
                 write!(f, "Failed to read file '{path}': {source}")
             }
-            Self::YamlParse { context, source } => {
+            Self::IoWrite { path, source } => {
+                write!(f, "Failed to write file '{path}': {source}")
+            }
+            Self::YamlParse {
+                context, source, ..
+            } => {
                 write!(f, "Failed to parse YAML in '{context}': {source}")
             }
+            Self::Parse {
+                format,
+                context,
+                message,
+                ..
+            } => {
+                write!(f, "Failed to parse {format} in '{context}': {message}")
+            }
             Self::JsonSerialize { message } => {
                 write!(f, "Failed to serialize results: {message}")
             }
@@ -100,6 +435,9 @@ impl fmt::Display for AtentoError {
             } => {
                 write!(f, "{context} timeout after {timeout_secs}s")
             }
+            Self::Cancelled { context } => {
+                write!(f, "Cancelled {context}")
+            }
             Self::Runner(msg) => {
                 write!(f, "Runner error: {msg}")
             }
@@ -110,7 +448,7 @@ impl fmt::Display for AtentoError {
 impl std::error::Error for AtentoError {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         match self {
-            Self::Io { source, .. } => Some(source),
+            Self::Io { source, .. } | Self::IoWrite { source, .. } => Some(source),
             Self::YamlParse { source, .. } => Some(source),
             // JsonSerialize now contains a message string; no underlying error to return as source
             _ => None,
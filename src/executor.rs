@@ -1,40 +1,428 @@
-use crate::{Interpreter, errors::Result};
+use crate::Interpreter;
+use crate::env::is_env_name;
+use crate::errors::{AtentoError, Result};
+use crate::remote::SshExecutor;
+use crate::runner::{
+    KillPolicy, OutputStream, ResourceUsage, RunnerResult, TIMEOUT_EXIT_CODE, TimeoutSignal,
+};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt::Write;
+use std::process::Command;
 
-/// Trait for abstracting command execution to enable mocking in tests
+const SSH_COMMAND: &str = "ssh";
+const SSH_CONNECT_TIMEOUT_SECS: u64 = 10;
+/// Exit code `ssh` reports for its own errors, such as failed connections
+const SSH_ERROR_EXIT_CODE: i32 = 255;
+
+/// Everything an executor needs to run one script.
+///
+/// The request carries the rendered script and the full [`Interpreter`] the
+/// step runs with, after its `interpreter_args` were applied, so an executor
+/// can use the interpreter's `command`, `args` and `extension` itself, along
+/// with the step's env, workdir, stdin, output limit and timeout settings.
+#[derive(Debug, Clone, Copy)]
+pub struct ExecutionRequest<'a> {
+    pub script: &'a str,
+    pub interpreter: &'a Interpreter,
+    pub env: &'a HashMap<String, String>,
+    pub workdir: Option<&'a str>,
+    /// Written to the script's standard input, which is then closed
+    pub stdin: Option<&'a str>,
+    /// Stdout and stderr each keep at most this many bytes from their start,
+    /// see [`ExecutionResult::stdout_truncated`]
+    pub max_output_bytes: Option<u64>,
+    /// Timeout in seconds, `0` for the runner default
+    pub timeout: u64,
+    pub kill: &'a KillPolicy,
+}
+
+impl<'a> ExecutionRequest<'a> {
+    /// Creates a request without workdir, stdin, output limit or timeout.
+    #[must_use]
+    pub fn new(
+        script: &'a str,
+        interpreter: &'a Interpreter,
+        env: &'a HashMap<String, String>,
+        kill: &'a KillPolicy,
+    ) -> Self {
+        Self {
+            script,
+            interpreter,
+            env,
+            workdir: None,
+            stdin: None,
+            max_output_bytes: None,
+            timeout: 0,
+            kill,
+        }
+    }
+}
+
+/// Trait for abstracting command execution, e.g. to run scripts elsewhere or to
+/// mock them in tests.
+///
+/// Each call gets an [`ExecutionRequest`] with the script and the settings of
+/// the step that runs it.
 pub trait CommandExecutor {
-    fn execute(
-        &self,
-        script: &str,
-        interpreter: &Interpreter,
-        timeout: u64,
-    ) -> Result<ExecutionResult>;
+    /// Executes the script of `request`.
+    ///
+    /// # Errors
+    /// Returns an error if the script cannot be started.
+    fn execute(&self, request: &ExecutionRequest<'_>) -> Result<ExecutionResult>;
+
+    /// Executes a script and also records stdout and stderr merged in the order
+    /// they were written, in `ExecutionResult::combined`.
+    ///
+    /// The default implementation delegates to `execute` and records no
+    /// combined output.
+    ///
+    /// # Errors
+    /// Returns the same errors as [`CommandExecutor::execute`].
+    fn execute_combined(&self, request: &ExecutionRequest<'_>) -> Result<ExecutionResult> {
+        self.execute(request)
+    }
 }
 
 /// Result of command execution
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, Default, PartialEq)]
+#[allow(clippy::struct_excessive_bools)]
 pub struct ExecutionResult {
     pub stdout: String,
     pub stderr: String,
     pub exit_code: i32,
     pub duration_ms: u64,
+    /// Stdout and stderr merged in write order, filled by `execute_combined`
+    pub combined: Option<String>,
+    /// The process was killed on timeout; stdout and stderr hold the output
+    /// written before the kill
+    pub timed_out: bool,
+    /// The interpreter command that ran the script, set by executors that
+    /// start it on this host, see [`Interpreter::fallbacks`]
+    pub resolved_command: Option<String>,
+    /// Stdout went past `max_output_bytes`; only its beginning was kept
+    pub stdout_truncated: bool,
+    /// Stderr went past `max_output_bytes`; only its beginning was kept
+    pub stderr_truncated: bool,
+    /// The process timed out and was killed with `SIGKILL` after the grace
+    /// period of its [`KillPolicy`], instead of exiting on the signal
+    pub force_killed: bool,
+    /// CPU time and peak memory of the script, recorded with the
+    /// `resource-metrics` feature on Unix for scripts run on this host
+    pub resource_usage: Option<ResourceUsage>,
+}
+
+impl From<RunnerResult> for ExecutionResult {
+    fn from(result: RunnerResult) -> Self {
+        Self {
+            stdout: result.stdout.unwrap_or_default(),
+            stderr: result.stderr.unwrap_or_default(),
+            exit_code: result.exit_code,
+            duration_ms: u64::try_from(result.duration_ms).unwrap_or(u64::MAX),
+            combined: result.combined,
+            timed_out: result.timed_out,
+            resolved_command: result.resolved_command,
+            stdout_truncated: result.stdout_truncated,
+            stderr_truncated: result.stderr_truncated,
+            force_killed: result.force_killed,
+            resource_usage: result.resource_usage,
+        }
+    }
 }
 
 /// Real implementation for production use
 pub struct SystemExecutor;
 
 impl CommandExecutor for SystemExecutor {
-    fn execute(
+    fn execute(&self, request: &ExecutionRequest<'_>) -> Result<ExecutionResult> {
+        let result = crate::runner::run(
+            request.script,
+            request.interpreter,
+            request.env,
+            request.workdir,
+            request.stdin,
+            request.max_output_bytes,
+            request.timeout,
+            request.kill,
+        )?;
+        Ok(result.into())
+    }
+
+    fn execute_combined(&self, request: &ExecutionRequest<'_>) -> Result<ExecutionResult> {
+        let result = crate::runner::run_combined(
+            request.script,
+            request.interpreter,
+            request.env,
+            request.workdir,
+            request.stdin,
+            request.max_output_bytes,
+            request.timeout,
+            request.kill,
+            &mut |_, _| {},
+        )?;
+        Ok(result.into())
+    }
+}
+
+/// Executor that reports each line of stdout and stderr to a callback while the
+/// script runs, for live progress on long-running steps.
+///
+/// The callback receives the stream a line was written to and the line without
+/// its trailing newline. The final `ExecutionResult` is the same as the one
+/// returned by [`SystemExecutor`].
+pub struct StreamingExecutor<F: FnMut(OutputStream, &str)> {
+    on_line: RefCell<F>,
+}
+
+impl<F: FnMut(OutputStream, &str)> StreamingExecutor<F> {
+    pub fn new(on_line: F) -> Self {
+        Self {
+            on_line: RefCell::new(on_line),
+        }
+    }
+}
+
+impl<F: FnMut(OutputStream, &str)> CommandExecutor for StreamingExecutor<F> {
+    fn execute(&self, request: &ExecutionRequest<'_>) -> Result<ExecutionResult> {
+        let mut on_line = self.on_line.borrow_mut();
+        let result = crate::runner::run_streaming(
+            request.script,
+            request.interpreter,
+            request.env,
+            request.workdir,
+            request.stdin,
+            request.max_output_bytes,
+            request.timeout,
+            request.kill,
+            &mut *on_line,
+        )?;
+        Ok(result.into())
+    }
+
+    fn execute_combined(&self, request: &ExecutionRequest<'_>) -> Result<ExecutionResult> {
+        let mut on_line = self.on_line.borrow_mut();
+        let result = crate::runner::run_combined(
+            request.script,
+            request.interpreter,
+            request.env,
+            request.workdir,
+            request.stdin,
+            request.max_output_bytes,
+            request.timeout,
+            request.kill,
+            &mut *on_line,
+        )?;
+        Ok(result.into())
+    }
+}
+
+/// Executor that runs each script inside a Docker container of `image`.
+///
+/// The step's interpreter command becomes the container entrypoint, so the
+/// image must provide it. On timeout the container is killed with
+/// `docker kill`. Steps that set `image` run through this executor.
+pub struct DockerExecutor {
+    pub image: String,
+}
+
+impl DockerExecutor {
+    pub fn new(image: impl Into<String>) -> Self {
+        Self {
+            image: image.into(),
+        }
+    }
+}
+
+impl CommandExecutor for DockerExecutor {
+    fn execute(&self, request: &ExecutionRequest<'_>) -> Result<ExecutionResult> {
+        let mut result = self.execute_combined(request)?;
+        result.combined = None;
+        Ok(result)
+    }
+
+    fn execute_combined(&self, request: &ExecutionRequest<'_>) -> Result<ExecutionResult> {
+        let result = crate::runner::run_in_container(
+            request.script,
+            request.interpreter,
+            &self.image,
+            request.env,
+            request.workdir,
+            request.stdin,
+            request.max_output_bytes,
+            request.timeout,
+            request.kill,
+        )?;
+        Ok(result.into())
+    }
+}
+
+impl SshExecutor {
+    /// Builds the `ssh` command that reads the script from stdin and runs it remotely.
+    pub(crate) fn command(
         &self,
-        script: &str,
         interpreter: &Interpreter,
+        env: &HashMap<String, String>,
+        workdir: Option<&str>,
+        timeout: u64,
+        kill: &KillPolicy,
+    ) -> Command {
+        let mut cmd = Command::new(SSH_COMMAND);
+        cmd.args(["-o", "BatchMode=yes", "-o"])
+            .arg(format!("ConnectTimeout={SSH_CONNECT_TIMEOUT_SECS}"));
+        if let Some(port) = self.port {
+            cmd.arg("-p").arg(port.to_string());
+        }
+        if let Some(key_path) = &self.key_path {
+            cmd.arg("-i").arg(key_path);
+        }
+        // `--` keeps a destination starting with `-` from being read as an option
+        cmd.arg("--").arg(self.destination()).arg(remote_script(
+            interpreter,
+            env,
+            workdir,
+            timeout,
+            kill,
+        ));
+        cmd
+    }
+}
+
+impl CommandExecutor for SshExecutor {
+    fn execute(&self, request: &ExecutionRequest<'_>) -> Result<ExecutionResult> {
+        let mut result = self.execute_combined(request)?;
+        result.combined = None;
+        Ok(result)
+    }
+
+    fn execute_combined(&self, request: &ExecutionRequest<'_>) -> Result<ExecutionResult> {
+        check_remote_input(request)?;
+        let mut cmd = self.command(
+            request.interpreter,
+            request.env,
+            request.workdir,
+            request.timeout,
+            request.kill,
+        );
+        let result = crate::runner::run_with_input(
+            &mut cmd,
+            &format!("{}\n", request.script),
+            request.max_output_bytes,
+            local_timeout(request.timeout),
+            request.kill,
+            &mut |_, _| {},
+        )?;
+        self.remote_result(result, request.timeout)
+    }
+}
+
+impl SshExecutor {
+    /// Turns the result of the local `ssh` process into the result of the
+    /// remote script.
+    ///
+    /// # Errors
+    /// Returns a `Runner` error when `ssh` itself failed, e.g. to connect.
+    pub(crate) fn remote_result(
+        &self,
+        result: RunnerResult,
         timeout: u64,
     ) -> Result<ExecutionResult> {
-        let result = crate::runner::run(script, interpreter, timeout)?;
-        Ok(ExecutionResult {
-            stdout: result.stdout.unwrap_or_default(),
-            stderr: result.stderr.unwrap_or_default(),
-            exit_code: result.exit_code,
-            duration_ms: u64::try_from(result.duration_ms).unwrap_or(u64::MAX),
-        })
+        if result.exit_code == SSH_ERROR_EXIT_CODE {
+            return Err(AtentoError::Runner(format!(
+                "SSH connection to '{}' failed: {}",
+                self.destination(),
+                result.stderr.as_deref().unwrap_or("no error output")
+            )));
+        }
+
+        let mut result = ExecutionResult::from(result);
+        result.timed_out |= timeout > 0 && result.exit_code == TIMEOUT_EXIT_CODE;
+        Ok(result)
     }
 }
+
+/// Rejects what cannot be run over SSH: an empty script, `stdin`, which
+/// carries the script itself, and env names that are not shell identifiers, as
+/// they are spliced into the remote command line.
+pub(crate) fn check_remote_input(request: &ExecutionRequest<'_>) -> Result<()> {
+    if request.script.is_empty() {
+        return Err(AtentoError::Runner("Script cannot be empty".to_string()));
+    }
+    if request.stdin.is_some() {
+        return Err(AtentoError::Runner(
+            "'stdin' is not supported over SSH, stdin carries the script".to_string(),
+        ));
+    }
+    if let Some(name) = request.env.keys().find(|name| !is_env_name(name)) {
+        return Err(AtentoError::Runner(format!(
+            "Invalid env var name '{name}': it must match [A-Za-z_][A-Za-z0-9_]*"
+        )));
+    }
+    Ok(())
+}
+
+/// Returns the timeout of the local `ssh` process for a remote `timeout`.
+///
+/// The remote `timeout` fires first; the local limit only catches a hung connection.
+pub(crate) fn local_timeout(timeout: u64) -> u64 {
+    if timeout > 0 {
+        timeout + SSH_CONNECT_TIMEOUT_SECS
+    } else {
+        0
+    }
+}
+
+/// Builds the remote shell command that stores stdin in a temp file, runs it
+/// with the interpreter under `timeout` and removes the file again.
+///
+/// The file is created with `umask 077` in a fresh `mktemp -d` directory, so
+/// other users can neither read the script nor plant the path in advance, and
+/// keeps the interpreter's extension, which some interpreters require. The
+/// remote `timeout` sends the `kill` signal and force-kills the script after
+/// its grace period, or after 5 seconds when it has none.
+pub(crate) fn remote_script(
+    interpreter: &Interpreter,
+    env: &HashMap<String, String>,
+    workdir: Option<&str>,
+    timeout: u64,
+    kill: &KillPolicy,
+) -> String {
+    let mut run = String::new();
+    if let Some(dir) = workdir {
+        let _ = write!(run, "cd {} && ", shell_quote(dir));
+    }
+
+    let mut names: Vec<&String> = env.keys().collect();
+    names.sort();
+    for name in names {
+        let _ = write!(run, "{name}={} ", shell_quote(&env[name]));
+    }
+
+    if timeout > 0 {
+        run.push_str("timeout ");
+        if kill.signal != TimeoutSignal::Term {
+            let _ = write!(run, "-s {} ", kill.signal.name());
+        }
+        match kill.grace_ms {
+            0 => run.push_str("-k 5 "),
+            ms => {
+                let _ = write!(run, "-k {}.{:03} ", ms / 1000, ms % 1000);
+            }
+        }
+        let _ = write!(run, "{timeout} ");
+    }
+    run.push_str(&shell_quote(&interpreter.command));
+    for arg in &interpreter.args {
+        run.push(' ');
+        run.push_str(&shell_quote(arg));
+    }
+
+    format!(
+        "umask 077; d=$(mktemp -d \"${{TMPDIR:-/tmp}}/atento_XXXXXX\") || exit 1; trap 'rm -rf \"$d\"' EXIT; f=\"$d\"/script{}; cat > \"$f\" || exit 1; {run} \"$f\"",
+        shell_quote(&interpreter.extension)
+    )
+}
+
+/// Quotes `value` for a POSIX shell.
+fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
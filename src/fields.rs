@@ -0,0 +1,268 @@
+use crate::location::SourceMap;
+use serde_yaml::{Mapping, Value};
+use std::fmt::Write;
+
+/// Keys of a chain document, including the `lenient` switch itself
+pub(crate) const CHAIN_FIELDS: &[&str] = &[
+    "name",
+    "description",
+    "metadata",
+    "timeout",
+    "interpreters",
+    "parameters",
+    "env",
+    "steps",
+    "results",
+    "parallel",
+    "default_interpreter",
+    "remote",
+    "image",
+    "preflight",
+    "fail_fast",
+    "on_complete_webhook",
+    "artifacts_root",
+    "max_output_bytes",
+    "timeout_signal",
+    "timeout_grace_ms",
+    "include",
+    "templates",
+    "lenient",
+];
+
+/// Keys of a step or template, including aliases
+pub(crate) const STEP_FIELDS: &[&str] = &[
+    "name",
+    "timeout",
+    "inputs",
+    "type",
+    "script",
+    "script_file",
+    "outputs",
+    "artifacts",
+    "env",
+    "retry",
+    "retries",
+    "retry_delay_ms",
+    "workdir",
+    "continue_on_error",
+    "on_failure",
+    "success_exit_codes",
+    "success_codes",
+    "allow_nonzero_exit",
+    "foreach",
+    "capture",
+    "image",
+    "interpreter_args",
+    "stdin",
+    "max_output_bytes",
+    "timeout_signal",
+    "timeout_grace_ms",
+    "quote",
+    "override",
+    "template",
+    "matrix",
+    "needs",
+];
+
+/// Keys of a step output, including aliases
+pub(crate) const OUTPUT_FIELDS: &[&str] = &[
+    "pattern",
+    "type",
+    "group",
+    "json_path",
+    "key",
+    "source",
+    "from",
+    "multiple",
+    "optional",
+    "default",
+    "delimiter",
+];
+
+pub(crate) const PARAMETER_FIELDS: &[&str] = &["type", "value", "required", "secret", "separator"];
+
+/// Keys of every kind of input: a reference, an environment variable or an
+/// inline value
+pub(crate) const INPUT_FIELDS: &[&str] = &["ref", "env", "default", "type", "value", "separator"];
+
+/// Keys of a chain result: a reference or an inline value
+pub(crate) const RESULT_FIELDS: &[&str] = &["ref", "type", "value"];
+
+/// A key of a chain document that no field matches.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct UnknownField {
+    /// Dotted path of the key, such as `steps.build.outpts`
+    pub(crate) path: String,
+    /// What the key was found in, such as `step 'build'`
+    pub(crate) owner: String,
+    pub(crate) key: String,
+    /// The closest valid field name, if one is close enough
+    pub(crate) suggestion: Option<&'static str>,
+}
+
+impl UnknownField {
+    /// Describes the field, with its location when `sources` has one.
+    pub(crate) fn describe(&self, sources: Option<&SourceMap>) -> String {
+        let mut text = format!("{} has unknown field '{}'", self.owner, self.key);
+        if let Some(suggestion) = self.suggestion {
+            let _ = write!(text, " (did you mean '{suggestion}'?)");
+        }
+        if let Some((_, location)) = sources.and_then(|sources| sources.locate(&self.path)) {
+            let _ = write!(text, " at {location}");
+        }
+        text
+    }
+}
+
+/// Returns whether a chain document sets `lenient: true`.
+pub(crate) fn is_lenient(document: &Value) -> bool {
+    document.get("lenient").and_then(Value::as_bool) == Some(true)
+}
+
+/// Lists the keys of a chain document that are not fields of the chain or
+/// of its steps, templates, inputs, outputs, parameters or results.
+pub(crate) fn unknown_fields(document: &Value) -> Vec<UnknownField> {
+    let mut unknown = Vec::new();
+    let Some(chain) = document.as_mapping() else {
+        return unknown;
+    };
+    check(chain, CHAIN_FIELDS, "", "the chain", &mut unknown);
+
+    for (section, kind) in [("steps", "step"), ("templates", "template")] {
+        for (id, step) in entries(chain, section) {
+            let path = format!("{section}.{id}");
+            let owner = format!("{kind} '{id}'");
+            check(step, STEP_FIELDS, &path, &owner, &mut unknown);
+            check_step_parts(step, &path, &owner, &mut unknown);
+        }
+    }
+
+    for (name, parameter) in entries(chain, "parameters") {
+        let owner = format!("parameter '{name}'");
+        check(
+            parameter,
+            PARAMETER_FIELDS,
+            &format!("parameters.{name}"),
+            &owner,
+            &mut unknown,
+        );
+    }
+    for (name, input) in entries(chain, "env") {
+        let owner = format!("chain env var '{name}'");
+        check(
+            input,
+            INPUT_FIELDS,
+            &format!("env.{name}"),
+            &owner,
+            &mut unknown,
+        );
+    }
+    for (name, result) in entries(chain, "results") {
+        let owner = format!("result '{name}'");
+        check(
+            result,
+            RESULT_FIELDS,
+            &format!("results.{name}"),
+            &owner,
+            &mut unknown,
+        );
+    }
+
+    unknown
+}
+
+/// Checks the inputs, outputs, env entries and `stdin` of the step at `path`.
+fn check_step_parts(step: &Mapping, path: &str, owner: &str, unknown: &mut Vec<UnknownField>) {
+    for (section, kind, fields) in [
+        ("inputs", "input", INPUT_FIELDS),
+        ("outputs", "output", OUTPUT_FIELDS),
+        ("env", "env var", INPUT_FIELDS),
+    ] {
+        for (name, entry) in entries(step, section) {
+            check(
+                entry,
+                fields,
+                &format!("{path}.{section}.{name}"),
+                &format!("{kind} '{name}' of {owner}"),
+                unknown,
+            );
+        }
+    }
+    if let Some(stdin) = step.get("stdin").and_then(Value::as_mapping) {
+        check(
+            stdin,
+            INPUT_FIELDS,
+            &format!("{path}.stdin"),
+            &format!("stdin of {owner}"),
+            unknown,
+        );
+    }
+}
+
+/// Returns the entries of the mapping under `section` whose values are
+/// mappings themselves, sorted by key.
+fn entries<'a>(parent: &'a Mapping, section: &str) -> Vec<(&'a str, &'a Mapping)> {
+    let mut entries: Vec<(&str, &Mapping)> = parent
+        .get(section)
+        .and_then(Value::as_mapping)
+        .into_iter()
+        .flatten()
+        .filter_map(|(key, value)| Some((key.as_str()?, value.as_mapping()?)))
+        .collect();
+    entries.sort_by_key(|(key, _)| *key);
+    entries
+}
+
+/// Records the keys of `mapping` that are not in `fields`.
+fn check(
+    mapping: &Mapping,
+    fields: &'static [&'static str],
+    path: &str,
+    owner: &str,
+    unknown: &mut Vec<UnknownField>,
+) {
+    let mut keys: Vec<&str> = mapping.keys().filter_map(Value::as_str).collect();
+    keys.sort_unstable();
+    for key in keys {
+        if fields.contains(&key) {
+            continue;
+        }
+        unknown.push(UnknownField {
+            path: if path.is_empty() {
+                key.to_string()
+            } else {
+                format!("{path}.{key}")
+            },
+            owner: owner.to_string(),
+            key: key.to_string(),
+            suggestion: closest(key, fields),
+        });
+    }
+}
+
+/// Returns the field closest to `key` by edit distance, if it is at most two
+/// edits away, or a third of the key's length for longer keys.
+fn closest(key: &str, fields: &[&'static str]) -> Option<&'static str> {
+    let limit = (key.chars().count() / 3).max(2);
+    fields
+        .iter()
+        .map(|field| (edit_distance(key, field), *field))
+        .filter(|(distance, _)| *distance <= limit)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, field)| field)
+}
+
+/// Levenshtein distance between `a` and `b`.
+pub(crate) fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != *b_char);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
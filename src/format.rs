@@ -0,0 +1,205 @@
+use crate::chain::ChainResult;
+use crate::errors::{AtentoError, Result};
+use std::fmt::Write;
+
+/// Format used to print a [`ChainResult`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OutputFormat {
+    /// Pretty-printed JSON, the complete result
+    #[default]
+    Json,
+    /// YAML, the complete result
+    Yaml,
+    /// Compact human-readable summary of status, step exit codes and results
+    Text,
+}
+
+/// Renders a chain result in the given format.
+///
+/// # Errors
+/// Returns an error if the result cannot be serialized.
+pub fn format_result(result: &ChainResult, format: OutputFormat) -> Result<String> {
+    match format {
+        OutputFormat::Json => Ok(serde_json::to_string_pretty(result)?),
+        OutputFormat::Yaml => {
+            serde_yaml::to_string(result).map_err(|e| AtentoError::yaml_parse("chain result", e))
+        }
+        OutputFormat::Text => Ok(format_text(result)),
+    }
+}
+
+/// Builds the text summary: a status line, one line per step and the results.
+fn format_text(result: &ChainResult) -> String {
+    let mut text = String::new();
+
+    if let Some(name) = &result.name {
+        let _ = writeln!(text, "Chain: {name}");
+    }
+    let _ = writeln!(
+        text,
+        "Status: {} ({} ms)",
+        result.status, result.duration_ms
+    );
+
+    if let Some(steps) = result.steps.as_ref().filter(|steps| !steps.is_empty()) {
+        text.push_str("Steps:\n");
+        for (step_id, step) in steps {
+            let _ = write!(
+                text,
+                "  {step_id}: exit {} ({} ms)",
+                step.exit_code, step.duration_ms
+            );
+            if let Some(error) = &step.error {
+                let _ = write!(text, " - {error}");
+            }
+            text.push('\n');
+        }
+    }
+
+    if let Some(results) = result
+        .results
+        .as_ref()
+        .filter(|results| !results.is_empty())
+    {
+        text.push_str("Results:\n");
+        let mut keys: Vec<&String> = results.keys().collect();
+        keys.sort();
+        for key in keys {
+            let _ = writeln!(text, "  {key}: {}", results[key]);
+        }
+    }
+
+    // Step failures are already shown next to their step
+    let chain_errors: Vec<&AtentoError> = chain_errors(result).collect();
+    if !chain_errors.is_empty() {
+        text.push_str("Errors:\n");
+        for error in chain_errors {
+            let _ = writeln!(text, "  - {error}");
+        }
+    }
+
+    text.trim_end().to_string()
+}
+
+/// Returns the errors of `result` that are not the error of one of its steps,
+/// such as a chain timeout or an unresolved result.
+fn chain_errors(result: &ChainResult) -> impl Iterator<Item = &AtentoError> {
+    result.errors.iter().filter(|error| match error {
+        AtentoError::StepExecution { step, .. } => result
+            .steps
+            .as_ref()
+            .and_then(|steps| steps.get(step))
+            .is_none_or(|step| step.error.is_none()),
+        _ => true,
+    })
+}
+
+impl ChainResult {
+    /// Renders the result as a `JUnit` XML report, e.g. for CI dashboards.
+    ///
+    /// The chain becomes a `<testsuite>` named after it and each step a
+    /// `<testcase>` with its duration in seconds, stdout and stderr. A failed
+    /// step gets a `<failure>` with its error. Chain errors that belong to no
+    /// step, such as a chain timeout, are reported as failed test cases named
+    /// after the chain, so every error in `errors` shows up as a failure.
+    #[must_use]
+    pub fn to_junit_xml(&self) -> String {
+        let suite = self.name.as_deref().unwrap_or("chain");
+        let mut cases = String::new();
+        let mut tests = 0;
+        let mut failures = 0;
+
+        for (step_id, step) in self.steps.iter().flatten() {
+            let name = step.name.as_deref().unwrap_or(step_id);
+            write_testcase(
+                &mut cases,
+                name,
+                suite,
+                step.duration_ms,
+                step.error.as_ref(),
+            );
+            if let Some(stdout) = step.stdout.as_deref().filter(|out| !out.is_empty()) {
+                let _ = writeln!(
+                    cases,
+                    "      <system-out>{}</system-out>",
+                    escape_xml(stdout)
+                );
+            }
+            if let Some(stderr) = step.stderr.as_deref().filter(|err| !err.is_empty()) {
+                let _ = writeln!(
+                    cases,
+                    "      <system-err>{}</system-err>",
+                    escape_xml(stderr)
+                );
+            }
+            cases.push_str("    </testcase>\n");
+            tests += 1;
+            failures += usize::from(step.error.is_some());
+        }
+        for error in chain_errors(self) {
+            write_testcase(&mut cases, suite, suite, 0, Some(error));
+            cases.push_str("    </testcase>\n");
+            tests += 1;
+            failures += 1;
+        }
+
+        let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<testsuites>\n");
+        let _ = writeln!(
+            xml,
+            "  <testsuite name=\"{}\" tests=\"{tests}\" failures=\"{failures}\" errors=\"0\" skipped=\"0\" time=\"{}\">",
+            escape_xml(suite),
+            seconds(self.duration_ms)
+        );
+        xml.push_str(&cases);
+        xml.push_str("  </testsuite>\n</testsuites>\n");
+        xml
+    }
+}
+
+/// Opens a `<testcase>` element, with a `<failure>` for `error`.
+fn write_testcase(
+    xml: &mut String,
+    name: &str,
+    classname: &str,
+    duration_ms: u128,
+    error: Option<&AtentoError>,
+) {
+    let _ = writeln!(
+        xml,
+        "    <testcase name=\"{}\" classname=\"{}\" time=\"{}\">",
+        escape_xml(name),
+        escape_xml(classname),
+        seconds(duration_ms)
+    );
+    if let Some(error) = error {
+        let message = escape_xml(&error.to_string());
+        let _ = writeln!(
+            xml,
+            "      <failure message=\"{message}\">{message}</failure>"
+        );
+    }
+}
+
+/// Formats milliseconds as seconds with three decimals, as `JUnit` expects.
+fn seconds(duration_ms: u128) -> String {
+    format!("{}.{:03}", duration_ms / 1000, duration_ms % 1000)
+}
+
+/// Escapes `text` for XML text and attribute values, dropping the control
+/// characters XML 1.0 does not allow, such as terminal color codes.
+fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            '\n' | '\r' | '\t' => escaped.push(c),
+            c if c.is_control() => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
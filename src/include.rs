@@ -0,0 +1,183 @@
+use crate::chain::{Chain, parse_chain};
+use crate::errors::{AtentoError, Result};
+use crate::interpreter::{Interpreter, default_interpreters};
+use crate::location::SourceMap;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+impl Chain {
+    /// Loads a chain file and merges in the chain files listed under its
+    /// `include`, resolved relative to the including file. Included files may
+    /// include further files; a file reached again through an include cycle is
+    /// a `Validation` error, while a file included from several places is only
+    /// merged once.
+    ///
+    /// Includes are merged in the order they are listed, followed by the
+    /// including file itself, so later definitions of parameters, env entries,
+    /// interpreters, templates and results replace earlier ones. Steps keep
+    /// the order in which they are first defined. A step whose id is already
+    /// defined must set `override: true`; it then takes the place of the
+    /// earlier step. Chain settings such as `name`, `timeout` or `parallel`
+    /// come from the file passed in.
+    ///
+    /// Each file's `script_file` paths are resolved relative to that file. The
+    /// merged chain is not validated; call [`Chain::validate`].
+    ///
+    /// Files ending in `.toml` are read as TOML, see [`Chain::from_toml`],
+    /// which needs the `toml` feature. With the `json-input` feature, files
+    /// ending in `.json` are read as JSON; otherwise they are read as YAML,
+    /// which JSON documents usually also are. Every other file is YAML.
+    ///
+    /// # Errors
+    /// Returns an `Io`, `YamlParse` or `Parse` error naming the file that
+    /// cannot be read or parsed, and a `Validation` error for include cycles,
+    /// for duplicate step ids without `override: true` and for `.toml` files
+    /// without the `toml` feature.
+    pub fn load_with_includes(path: impl AsRef<Path>) -> Result<Self> {
+        let mut loaded = HashSet::new();
+        Self::load_included(path.as_ref(), &mut Vec::new(), &mut loaded)
+    }
+
+    /// Loads `path` and its includes; `stack` holds the files currently being
+    /// loaded and `loaded` every file merged so far.
+    fn load_included(
+        path: &Path,
+        stack: &mut Vec<PathBuf>,
+        loaded: &mut HashSet<PathBuf>,
+    ) -> Result<Self> {
+        let canonical = path.canonicalize().map_err(|e| AtentoError::Io {
+            path: path.display().to_string(),
+            source: e,
+        })?;
+
+        if let Some(start) = stack.iter().position(|file| *file == canonical) {
+            let cycle: Vec<String> = stack[start..]
+                .iter()
+                .chain(std::iter::once(&canonical))
+                .map(|file| file.display().to_string())
+                .collect();
+            return Err(AtentoError::Validation(format!(
+                "Include cycle: {}",
+                cycle.join(" -> ")
+            )));
+        }
+
+        let mut own = read_chain_file(path)?;
+        let includes = std::mem::take(&mut own.include);
+        let base = path.parent().unwrap_or_else(|| Path::new(""));
+        own.resolve_script_files(base)?;
+
+        stack.push(canonical.clone());
+        let mut merged: Option<Chain> = None;
+        for include in &includes {
+            let include_path = base.join(include);
+            if let Ok(canonical_include) = include_path.canonicalize()
+                && loaded.contains(&canonical_include)
+            {
+                continue;
+            }
+            let chain = Self::load_included(&include_path, stack, loaded)?;
+            merged = Some(match merged {
+                Some(earlier) => earlier.overlay(chain, &include_path)?,
+                None => chain,
+            });
+        }
+        stack.pop();
+        loaded.insert(canonical);
+
+        match merged {
+            Some(earlier) => earlier.overlay(own, path),
+            None => Ok(own),
+        }
+    }
+
+    /// Merges `later`, loaded from `source`, over this chain. The result keeps
+    /// the settings of `later`.
+    fn overlay(self, later: Chain, source: &Path) -> Result<Self> {
+        let mut steps = self.steps;
+        for (id, step) in later.steps {
+            match steps.get_mut(&id) {
+                Some(existing) if step.override_ => *existing = step,
+                Some(_) => {
+                    return Err(AtentoError::Validation(format!(
+                        "Step '{id}' in '{}' is already defined by an included chain; set 'override: true' to replace it",
+                        source.display()
+                    )));
+                }
+                None => {
+                    steps.insert(id, step);
+                }
+            }
+        }
+
+        let mut parameters = self.parameters;
+        parameters.extend(later.parameters);
+        let mut env = self.env;
+        env.extend(later.env);
+        let mut results = self.results;
+        results.extend(later.results);
+        let mut templates = self.templates;
+        templates.extend(later.templates);
+        let source_map = match (self.source_map, later.source_map) {
+            (Some(mut earlier), Some(later)) => {
+                earlier.extend(later);
+                Some(earlier)
+            }
+            (earlier, later) => later.or(earlier),
+        };
+
+        // Every parsed chain carries the default interpreters; those must not
+        // replace an interpreter an earlier file redefined
+        let defaults: HashMap<String, Interpreter> = default_interpreters().into_iter().collect();
+        let mut interpreters = self.interpreters;
+        for (key, interpreter) in later.interpreters {
+            if interpreters.contains_key(&key) && defaults.get(&key) == Some(&interpreter) {
+                continue;
+            }
+            interpreters.insert(key, interpreter);
+        }
+
+        let mut chain = Chain {
+            interpreters,
+            parameters,
+            env,
+            steps,
+            results,
+            templates,
+            source_map,
+            ..later
+        };
+        // Steps may use templates defined in another file
+        chain.instantiate_templates();
+        chain.expand_matrices();
+        Ok(chain)
+    }
+}
+
+/// Reads and parses a single chain file: TOML for `.toml` files, JSON for
+/// `.json` files with the `json-input` feature, and YAML otherwise.
+fn read_chain_file(path: &Path) -> Result<Chain> {
+    let filename = path.display().to_string();
+
+    let contents = std::fs::read_to_string(path).map_err(|e| AtentoError::Io {
+        path: filename.clone(),
+        source: e,
+    })?;
+
+    match path.extension().and_then(|ext| ext.to_str()) {
+        #[cfg(feature = "toml")]
+        Some("toml") => crate::document::parse_toml(&contents, &filename),
+        #[cfg(not(feature = "toml"))]
+        Some("toml") => Err(AtentoError::Validation(format!(
+            "Cannot load '{filename}': TOML chain files need the 'toml' feature"
+        ))),
+        #[cfg(feature = "json-input")]
+        Some("json") => crate::document::parse_json(&contents, &filename),
+        _ => {
+            let sources = SourceMap::scan(&filename, &contents);
+            let mut chain = parse_chain(&contents, &filename, Some(&sources))?;
+            chain.source_map = Some(sources);
+            Ok(chain)
+        }
+    }
+}
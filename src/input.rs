@@ -4,6 +4,11 @@ use serde::{Deserialize, Serialize};
 
 /// Represents an input value for a step, either inline or by reference.
 #[derive(Debug, Clone, Deserialize, Serialize)]
+#[cfg_attr(
+    feature = "schema",
+    derive(schemars::JsonSchema),
+    schemars(deny_unknown_fields)
+)]
 #[serde(untagged)]
 pub enum Input {
     /// Reference to a parameter or step output
@@ -11,11 +16,24 @@ pub enum Input {
         #[serde(rename = "ref")]
         ref_: String,
     },
+    /// Value of an environment variable of the host running the chain, or
+    /// `default` when it is unset
+    Env {
+        env: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        default: Option<String>,
+    },
     /// Inline value with explicit type
     Inline {
         #[serde(default, rename = "type")]
         type_: DataType,
+        /// Left out for template inputs that each step using the template must pass
+        #[serde(default)]
+        #[cfg_attr(feature = "schema", schemars(with = "serde_json::Value"))]
         value: serde_yaml::Value,
+        /// Separator between the elements of a `list` value (default: a space)
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        separator: Option<String>,
     },
 }
 
@@ -26,10 +44,41 @@ impl Input {
     /// Returns an error if this is a `Ref` variant or if the value type doesn't match.
     pub fn to_string_value(&self) -> Result<String> {
         match self {
-            Self::Inline { value, type_ } => data_type::to_string_value(type_, value),
+            Self::Inline {
+                type_: DataType::List,
+                value,
+                separator,
+            } => data_type::to_list_string(
+                value,
+                separator
+                    .as_deref()
+                    .unwrap_or(data_type::DEFAULT_LIST_SEPARATOR),
+            ),
+            Self::Inline { value, type_, .. } => data_type::to_string_value(type_, value),
             Self::Ref { .. } => Err(AtentoError::Execution(
                 "Cannot convert Ref directly to string; must resolve first".to_string(),
             )),
+            Self::Env { .. } => Err(AtentoError::Execution(
+                "Cannot convert Env directly to string; must resolve first".to_string(),
+            )),
+        }
+    }
+
+    /// Reads the value of an `Env` input from the environment, falling back to
+    /// its `default`; `None` for other inputs or when neither is set.
+    ///
+    /// # Errors
+    /// Returns an `Execution` error if the variable is set but not valid Unicode.
+    pub(crate) fn env_value(&self) -> Result<Option<String>> {
+        let Self::Env { env, default } = self else {
+            return Ok(None);
+        };
+        match std::env::var(env) {
+            Ok(value) => Ok(Some(value)),
+            Err(std::env::VarError::NotPresent) => Ok(default.clone()),
+            Err(std::env::VarError::NotUnicode(_)) => Err(AtentoError::Execution(format!(
+                "Environment variable '{env}' is not valid Unicode"
+            ))),
         }
     }
 }
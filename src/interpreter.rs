@@ -1,7 +1,20 @@
+#[cfg(feature = "exec")]
+use crate::runner;
 use serde::{Deserialize, Serialize};
+#[cfg(feature = "exec")]
+use std::collections::HashMap;
+
+/// Seconds an availability probe may take before the interpreter counts as missing
+#[cfg(feature = "exec")]
+const AVAILABILITY_TIMEOUT_SECS: u64 = 5;
 
 /// Interpreter configuration with command, arguments, and file extension
-#[derive(Debug, Clone, Deserialize, Serialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[cfg_attr(
+    feature = "schema",
+    derive(schemars::JsonSchema),
+    schemars(deny_unknown_fields)
+)]
 pub struct Interpreter {
     /// The command to execute (e.g., "bash", "node", "/usr/bin/python3")
     pub command: String,
@@ -10,6 +23,54 @@ pub struct Interpreter {
     pub args: Vec<String>,
     /// File extension for the script (e.g., ".sh", ".js")
     pub extension: String,
+    /// Commands tried in order when `command` is not found on this host, e.g.
+    /// `python` or `py -3` for `python3`; words after the first are passed as
+    /// arguments before `args`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fallbacks: Vec<String>,
+}
+
+/// Extra interpreter arguments for a single step.
+///
+/// Written as a list, the arguments are appended to the interpreter's `args`;
+/// written as `{ args: [...], override: true }` they replace them.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Deserialize, Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(from = "InterpreterArgsHelper")]
+pub struct InterpreterArgs {
+    pub args: Vec<String>,
+    /// Replace the interpreter's `args` instead of appending to them
+    #[serde(rename = "override")]
+    pub override_: bool,
+}
+
+// Helper enum accepting both YAML forms of `interpreter_args`
+#[derive(Deserialize)]
+#[cfg_attr(
+    feature = "schema",
+    derive(schemars::JsonSchema),
+    schemars(deny_unknown_fields)
+)]
+#[serde(untagged)]
+enum InterpreterArgsHelper {
+    List(Vec<String>),
+    Map {
+        args: Vec<String>,
+        #[serde(default, rename = "override")]
+        override_: bool,
+    },
+}
+
+impl From<InterpreterArgsHelper> for InterpreterArgs {
+    fn from(helper: InterpreterArgsHelper) -> Self {
+        match helper {
+            InterpreterArgsHelper::List(args) => Self {
+                args,
+                override_: false,
+            },
+            InterpreterArgsHelper::Map { args, override_ } => Self { args, override_ },
+        }
+    }
 }
 
 /// Returns the default interpreter configurations as (key, Interpreter) pairs
@@ -22,6 +83,25 @@ pub fn default_interpreters() -> Vec<(String, Interpreter)> {
                 command: "bash".to_string(),
                 args: vec![],
                 extension: ".sh".to_string(),
+                fallbacks: vec![],
+            },
+        ),
+        (
+            "sh".to_string(),
+            Interpreter {
+                command: "sh".to_string(),
+                args: vec![],
+                extension: ".sh".to_string(),
+                fallbacks: vec![],
+            },
+        ),
+        (
+            "zsh".to_string(),
+            Interpreter {
+                command: "zsh".to_string(),
+                args: vec![],
+                extension: ".sh".to_string(),
+                fallbacks: vec![],
             },
         ),
         (
@@ -30,6 +110,7 @@ pub fn default_interpreters() -> Vec<(String, Interpreter)> {
                 command: "cmd".to_string(),
                 args: vec!["/c".to_string()],
                 extension: ".bat".to_string(),
+                fallbacks: vec![],
             },
         ),
         (
@@ -45,6 +126,7 @@ pub fn default_interpreters() -> Vec<(String, Interpreter)> {
                     "-File".to_string(),
                 ],
                 extension: ".ps1".to_string(),
+                fallbacks: vec!["pwsh".to_string()],
             },
         ),
         (
@@ -60,6 +142,7 @@ pub fn default_interpreters() -> Vec<(String, Interpreter)> {
                     "-File".to_string(),
                 ],
                 extension: ".ps1".to_string(),
+                fallbacks: vec!["powershell".to_string()],
             },
         ),
         (
@@ -68,6 +151,7 @@ pub fn default_interpreters() -> Vec<(String, Interpreter)> {
                 command: "python3".to_string(),
                 args: vec![],
                 extension: ".py".to_string(),
+                fallbacks: vec!["python".to_string(), "py -3".to_string()],
             },
         ),
         (
@@ -76,6 +160,16 @@ pub fn default_interpreters() -> Vec<(String, Interpreter)> {
                 command: "python3".to_string(),
                 args: vec![],
                 extension: ".py".to_string(),
+                fallbacks: vec!["python".to_string(), "py -3".to_string()],
+            },
+        ),
+        (
+            "node".to_string(),
+            Interpreter {
+                command: "node".to_string(),
+                args: vec![],
+                extension: ".js".to_string(),
+                fallbacks: vec![],
             },
         ),
     ]
@@ -88,9 +182,58 @@ impl Interpreter {
         &self.extension
     }
 
+    /// Returns the commands to try in order: `command` followed by the
+    /// `fallbacks`, each split into the program and its leading arguments.
+    ///
+    /// `command` is used as is, so it may be a path containing spaces.
+    #[must_use]
+    pub fn candidates(&self) -> Vec<(&str, Vec<&str>)> {
+        let mut candidates = vec![(self.command.as_str(), Vec::new())];
+        for fallback in &self.fallbacks {
+            let mut words = fallback.split_whitespace();
+            if let Some(program) = words.next() {
+                candidates.push((program, words.collect()));
+            }
+        }
+        candidates
+    }
+
+    /// Returns a copy of the interpreter with `step_args` appended to its
+    /// `args`, or replacing them when `override` is set.
+    #[must_use]
+    pub fn with_step_args(&self, step_args: &InterpreterArgs) -> Interpreter {
+        let mut interpreter = self.clone();
+        if step_args.override_ {
+            interpreter.args.clone_from(&step_args.args);
+        } else {
+            interpreter.args.extend(step_args.args.iter().cloned());
+        }
+        interpreter
+    }
+
     /// Returns the full command and arguments as a vector of strings
     #[must_use]
     pub fn is_valid(&self) -> bool {
         !self.command.is_empty() && !self.extension.is_empty()
     }
+
+    /// Returns whether the interpreter can run scripts on this host.
+    ///
+    /// Runs an empty script with the interpreter and reports success only when
+    /// it starts and exits with code 0 within a few seconds.
+    #[cfg(feature = "exec")]
+    #[must_use]
+    pub fn is_available(&self) -> bool {
+        runner::run(
+            "\n",
+            self,
+            &HashMap::new(),
+            None,
+            None,
+            None,
+            AVAILABILITY_TIMEOUT_SECS,
+            &runner::KillPolicy::default(),
+        )
+        .is_ok_and(|result| result.exit_code == 0 && !result.timed_out)
+    }
 }
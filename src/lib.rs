@@ -17,7 +17,7 @@
 //!
 //! ## Key Features
 //!
-//! - **Multi-Interpreter Support**: Execute scripts in Bash, Batch, `PowerShell`, Pwsh, and Python
+//! - **Multi-Interpreter Support**: Execute scripts in Bash, sh, Zsh, Batch, `PowerShell`, Pwsh, Python and Node.js
 //! - **Sequential Execution**: Guaranteed step order with dependency management
 //! - **Variable Passing**: Global parameters and step-to-step output chaining
 //! - **Type Safety**: Strongly typed parameters (string, int, float, bool, datetime)
@@ -31,11 +31,14 @@
 //! ```rust,no_run
 //! use atento_core;
 //!
+//! # #[cfg(feature = "exec")]
 //! fn main() -> Result<(), Box<dyn std::error::Error>> {
 //!     // Run a chain from a YAML file
 //!     atento_core::run("chain.yaml")?;
 //!     Ok(())
 //! }
+//! # #[cfg(not(feature = "exec"))]
+//! # fn main() {}
 //! ```
 //!
 //! ## Chain Structure
@@ -57,7 +60,7 @@
 //! steps:
 //!   setup:
 //!     name: "Setup Environment"
-//!     type: bash  # Interpreter: bash, batch, powershell, pwsh, python
+//!     type: bash  # Interpreter: bash, sh, zsh, batch, powershell, pwsh, python
 //!     timeout: 60
 //!     script: |
 //!       echo "Setting up {{ inputs.project }}"
@@ -98,11 +101,14 @@
 //! | Type | Description | Platform |
 //! |------|-------------|----------|
 //! | `bash` | Bash shell scripts | Unix/Linux/macOS |
+//! | `sh` | POSIX shell scripts | Unix/Linux/macOS |
+//! | `zsh` | Zsh scripts | Unix/Linux/macOS |
 //! | `batch` | Windows batch files | Windows |
 //! | `powershell` | `PowerShell` (Windows) | Windows |
 //! | `pwsh` | `PowerShell` Core | Cross-platform |
 //! | `python` | Python scripts | Cross-platform |
 //! | `python3` | Python3 scripts | Cross-platform |
+//! | `node` | JavaScript (Node.js) scripts | Cross-platform |
 //!
 //! ## Variable Substitution
 //!
@@ -114,6 +120,9 @@
 //!   cp "{{ inputs.source }}" "{{ inputs.destination }}"
 //! ```
 //!
+//! Chain parameters can be used directly with `{{ parameters.name }}`, without
+//! declaring them as inputs first.
+//!
 //! ## Output Extraction
 //!
 //! Capture values from command output using regex patterns with capture groups:
@@ -140,6 +149,9 @@
 //!
 //! ```no_run
 //! # use atento_core::{Chain, AtentoError};
+//! # #[cfg(not(feature = "exec"))]
+//! # fn main() {}
+//! # #[cfg(feature = "exec")]
 //! # fn main() -> Result<(), Box<dyn std::error::Error>> {
 //! // Load and validate a chain
 //! let yaml_content = std::fs::read_to_string("chain.yaml")?;
@@ -158,31 +170,86 @@
 //! # }
 //! ```
 
+use std::collections::HashMap;
 use std::path::Path;
 
+mod artifact;
+#[cfg(feature = "async")]
+mod async_executor;
+#[cfg(feature = "async")]
+mod async_runner;
+mod builder;
+mod cancel;
 mod chain;
+mod compose;
 mod data_type;
+mod diagnostic;
+#[cfg(any(feature = "toml", feature = "json-input"))]
+mod document;
+mod env;
 mod errors;
+#[cfg(feature = "exec")]
 mod executor;
+mod fields;
+mod format;
+mod include;
 mod input;
 mod interpreter;
+mod location;
+mod matrix;
+mod observer;
 mod output;
 mod parameter;
+mod plan;
+mod process;
+mod remote;
 mod result_ref;
+mod retry;
+#[cfg(feature = "exec")]
 mod runner;
+#[cfg(feature = "schema")]
+mod schema;
 mod step;
+mod template;
+#[cfg(feature = "http")]
+mod webhook;
 
 #[cfg(test)]
 mod tests;
 
 // Re-export main types for library users
-pub use chain::{Chain, ChainResult};
+pub use artifact::{Artifact, ArtifactResult};
+#[cfg(feature = "async")]
+pub use async_executor::AsyncCommandExecutor;
+pub use builder::{ChainBuilder, StepBuilder};
+pub use cancel::CancellationToken;
+pub use chain::{Chain, ChainResult, MissingInterpreter, RUN_ID_ENV, ValidationReport};
 pub use data_type::DataType;
+pub use diagnostic::{Diagnostic, Severity};
+pub use env::EnvValue;
 pub use errors::{AtentoError, Result};
-pub use interpreter::{Interpreter, default_interpreters};
-pub use step::{Step, StepResult};
+#[cfg(feature = "exec")]
+pub use executor::{
+    CommandExecutor, DockerExecutor, ExecutionRequest, ExecutionResult, StreamingExecutor,
+    SystemExecutor,
+};
+pub use format::{OutputFormat, format_result};
+pub use input::Input;
+pub use interpreter::{Interpreter, InterpreterArgs, default_interpreters};
+pub use location::{SourceLocation, SourceMap};
+pub use observer::{ChainObserver, NoopObserver};
+pub use output::{Output, OutputSource};
+pub use parameter::Parameter;
+pub use plan::{ExecutionPlan, StepPlan};
+pub use process::{KillPolicy, OutputStream, ResourceUsage, TimeoutSignal};
+pub use remote::SshExecutor;
+pub use result_ref::{ResultRef, ResultValue};
+pub use retry::{AttemptSummary, Backoff, RetryPolicy};
+#[cfg(feature = "schema")]
+pub use schema::chain_schema;
+pub use step::{Capture, Quote, Step, StepResult};
 
-/// Runs a chain from a YAML file.
+/// Runs a chain from a YAML file and prints the results as JSON.
 ///
 /// # Arguments
 /// * `filename` - Path to the chain YAML file
@@ -194,27 +261,88 @@ pub use step::{Step, StepResult};
 /// - The chain validation fails
 /// - The chain execution fails
 /// - The results cannot be serialized to JSON
+#[cfg(feature = "exec")]
 pub fn run(filename: &str) -> Result<()> {
-    let path = Path::new(filename);
+    run_with_format(filename, OutputFormat::Json)
+}
 
-    let contents = std::fs::read_to_string(path).map_err(|e| AtentoError::Io {
-        path: filename.to_string(),
-        source: e,
-    })?;
+/// Runs a chain from a YAML file and prints the results in the given format.
+///
+/// `Json` and `Yaml` print the complete [`ChainResult`]; `Text` prints a short
+/// summary with the status, each step's exit code and the chain results.
+///
+/// # Arguments
+/// * `filename` - Path to the chain YAML file
+/// * `format` - Output format for the printed results
+///
+/// # Errors
+/// Returns the same errors as [`run`].
+#[cfg(feature = "exec")]
+pub fn run_with_format(filename: &str, format: OutputFormat) -> Result<()> {
+    run_and_print(filename, &[], format)
+}
 
-    let chain: Chain = serde_yaml::from_str(&contents).map_err(|e| AtentoError::YamlParse {
-        context: filename.to_string(),
-        source: e,
-    })?;
+/// Runs a chain from a YAML file with parameter overrides and prints the results as JSON.
+///
+/// Each override is a `key=value` pair, as typically passed on the command line;
+/// see [`Chain::with_parameters`] for how values are converted.
+///
+/// # Arguments
+/// * `filename` - Path to the chain YAML file
+/// * `overrides` - `key=value` pairs replacing declared parameter values
+///
+/// # Errors
+/// Returns the same errors as [`run`], and an error if an override is not a
+/// `key=value` pair, names an undeclared parameter or does not parse.
+#[cfg(feature = "exec")]
+pub fn run_with_parameters(filename: &str, overrides: &[&str]) -> Result<()> {
+    run_and_print(filename, overrides, OutputFormat::Json)
+}
 
-    chain.validate()?; // Already returns Result<(), AtentoError>
+/// Runs a chain from a YAML file and writes the results as JSON to `output_path`.
+///
+/// Only the serialized [`ChainResult`] goes into the file; nothing is printed.
+/// The file is written to a temporary file next to `output_path` and then
+/// renamed, so readers never see a partially written result. It is written
+/// even when the chain completes with errors.
+///
+/// # Arguments
+/// * `chain_path` - Path to the chain YAML file
+/// * `output_path` - Path of the JSON file to create or replace
+///
+/// # Errors
+/// Returns the same errors as [`run`], and an `IoWrite` error naming `output_path`
+/// if the file cannot be written.
+#[cfg(feature = "exec")]
+pub fn run_to_file(chain_path: &str, output_path: &str) -> Result<()> {
+    let result = load_and_run(chain_path, &[])?;
+    let json = serde_json::to_string_pretty(&result)?;
+    write_atomically(Path::new(output_path), &format!("{json}\n"))?;
+    completion(&result)
+}
 
-    let result = chain.run(); // Returns ChainResult
+/// Loads a chain, applies overrides, runs it and prints the result.
+#[cfg(feature = "exec")]
+fn run_and_print(filename: &str, overrides: &[&str], format: OutputFormat) -> Result<()> {
+    let result = load_and_run(filename, overrides)?;
 
-    let json = serde_json::to_string_pretty(&result)?; // From trait converts to AtentoError
+    println!("{}", format_result(&result, format)?);
 
-    println!("{json}");
+    completion(&result)
+}
 
+/// Loads a chain, applies overrides, validates it and runs it.
+#[cfg(feature = "exec")]
+fn load_and_run(filename: &str, overrides: &[&str]) -> Result<ChainResult> {
+    let overrides = parse_parameter_overrides(overrides)?;
+    let chain = read_chain(Path::new(filename))?.with_parameters(&overrides)?;
+    chain.validate()?;
+    Ok(chain.run())
+}
+
+/// Turns a finished run into the `Ok`/`Err` returned by the printing entry points.
+#[cfg(feature = "exec")]
+fn completion(result: &ChainResult) -> Result<()> {
     if result.errors.is_empty() {
         Ok(())
     } else {
@@ -223,3 +351,158 @@ pub fn run(filename: &str) -> Result<()> {
         ))
     }
 }
+
+/// Writes `contents` to a temporary file in the directory of `path` and renames
+/// it over `path`, removing the temporary file if anything fails.
+#[cfg(feature = "exec")]
+fn write_atomically(path: &Path, contents: &str) -> Result<()> {
+    let io_error = |source| AtentoError::IoWrite {
+        path: path.display().to_string(),
+        source,
+    };
+
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| io_error(std::io::Error::other("not a file path")))?;
+    let mut temp_name = std::ffi::OsString::from(".");
+    temp_name.push(file_name);
+    temp_name.push(format!(".{}.tmp", std::process::id()));
+    let temp_path = path.with_file_name(temp_name);
+
+    let written =
+        std::fs::write(&temp_path, contents).and_then(|()| std::fs::rename(&temp_path, path));
+    if let Err(source) = written {
+        let _ = std::fs::remove_file(&temp_path);
+        return Err(io_error(source));
+    }
+    Ok(())
+}
+
+/// Runs a chain from a YAML file and returns its results.
+///
+/// Unlike [`run`], nothing is printed. A chain that completes with step errors
+/// still returns `Ok`; inspect `status` and `errors` on the returned [`ChainResult`].
+///
+/// # Arguments
+/// * `path` - Path to the chain YAML file
+///
+/// # Errors
+/// Returns an error if:
+/// - The file cannot be read
+/// - The YAML cannot be parsed
+/// - The chain validation fails
+#[cfg(feature = "exec")]
+pub fn run_file(path: impl AsRef<Path>) -> Result<ChainResult> {
+    let chain = load_chain(path.as_ref())?;
+    Ok(chain.run())
+}
+
+/// Runs a chain from a YAML file and returns its results, like [`run_file`].
+///
+/// # Errors
+/// Returns the same errors as [`run_file`].
+#[cfg(feature = "exec")]
+pub fn run_to_result(filename: &str) -> Result<ChainResult> {
+    run_file(filename)
+}
+
+/// Runs a chain from a YAML string and returns its results.
+///
+/// Behaves like [`run_file`] without touching the filesystem for the chain
+/// definition.
+///
+/// # Arguments
+/// * `yaml` - The chain definition
+///
+/// # Errors
+/// Returns an error if:
+/// - The YAML cannot be parsed
+/// - The chain validation fails
+#[cfg(feature = "exec")]
+pub fn run_from_str(yaml: &str) -> Result<ChainResult> {
+    let chain: Chain = yaml.parse()?;
+    chain.validate()?;
+    Ok(chain.run())
+}
+
+/// Renders a chain from a YAML file without executing any scripts.
+///
+/// The chain is loaded and validated exactly as in [`run`], then walked with
+/// [`Chain::dry_run`] and the rendered result is printed as JSON.
+///
+/// # Arguments
+/// * `filename` - Path to the chain YAML file
+///
+/// # Errors
+/// Returns an error if:
+/// - The file cannot be read
+/// - The YAML cannot be parsed
+/// - The chain validation fails
+/// - The results cannot be serialized to JSON
+pub fn dry_run(filename: &str) -> Result<()> {
+    let chain = load_chain(Path::new(filename))?;
+
+    let result = chain.dry_run();
+
+    let json = serde_json::to_string_pretty(&result)?;
+
+    println!("{json}");
+
+    if result.errors.is_empty() {
+        Ok(())
+    } else {
+        Err(AtentoError::Execution(
+            "Chain dry run completed with errors".to_string(),
+        ))
+    }
+}
+
+/// Checks a chain file without running it, e.g. as a lint step in CI.
+///
+/// The chain is loaded with its includes and checked with [`Chain::validate`],
+/// exactly as before [`run`] executes it. No script is executed and no
+/// interpreter is looked up, so interpreters do not need to be installed.
+///
+/// # Arguments
+/// * `filename` - Path to the chain file
+///
+/// # Errors
+/// Returns an error if:
+/// - The file cannot be read (`Io`)
+/// - The chain cannot be parsed (`YamlParse`, or `Parse` for TOML and JSON files)
+/// - The chain validation fails
+pub fn validate(filename: &str) -> Result<()> {
+    load_chain(Path::new(filename)).map(drop)
+}
+
+/// Parses `key=value` pairs into a map of parameter overrides.
+///
+/// The value is everything after the first `=`, so it may itself contain `=`.
+///
+/// # Errors
+/// Returns a `Validation` error for a pair without `=` or with an empty key.
+pub fn parse_parameter_overrides(pairs: &[&str]) -> Result<HashMap<String, String>> {
+    pairs
+        .iter()
+        .map(|pair| match pair.split_once('=') {
+            Some((key, value)) if !key.trim().is_empty() => {
+                Ok((key.trim().to_string(), value.to_string()))
+            }
+            _ => Err(AtentoError::Validation(format!(
+                "Invalid parameter override '{pair}': expected key=value"
+            ))),
+        })
+        .collect()
+}
+
+/// Reads, parses and validates a chain file.
+fn load_chain(path: &Path) -> Result<Chain> {
+    let chain = read_chain(path)?;
+    chain.validate()?;
+    Ok(chain)
+}
+
+/// Reads and parses a chain file and its includes without validating it.
+fn read_chain(path: &Path) -> Result<Chain> {
+    Chain::load_with_includes(path)
+}
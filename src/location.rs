@@ -0,0 +1,153 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+
+/// A position in a chain file; `line` and `column` count from 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SourceLocation {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl fmt::Display for SourceLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {} column {}", self.line, self.column)
+    }
+}
+
+impl SourceLocation {
+    /// Returns the location of byte `offset` in `text`.
+    #[cfg(feature = "toml")]
+    pub(crate) fn at_offset(text: &str, offset: usize) -> Self {
+        let before = &text[..offset.min(text.len())];
+        let line_start = before.rfind('\n').map_or(0, |newline| newline + 1);
+        Self {
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+        }
+    }
+}
+
+impl From<serde_yaml::Location> for SourceLocation {
+    fn from(location: serde_yaml::Location) -> Self {
+        Self {
+            line: location.line(),
+            column: location.column(),
+        }
+    }
+}
+
+/// Where the keys of the chain files a [`Chain`](crate::Chain) was loaded
+/// from are defined, such as `steps.build` or `steps.build.timeout`.
+///
+/// The map comes from scanning the block-style mappings of each file, so it
+/// is approximate: keys inside flow mappings (`{ a: 1 }`) or sequences are
+/// not recorded.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SourceMap {
+    keys: HashMap<String, (String, SourceLocation)>,
+}
+
+impl SourceMap {
+    /// Scans the YAML text of the chain file `file`.
+    #[must_use]
+    pub fn scan(file: &str, yaml: &str) -> Self {
+        let mut keys = HashMap::new();
+        let mut path: Vec<(usize, String)> = Vec::new();
+        let mut block_scalar_indent: Option<usize> = None;
+
+        for (index, line) in yaml.lines().enumerate() {
+            let content = line.trim_start();
+            if content.is_empty() || content.starts_with('#') {
+                continue;
+            }
+            let indent = line.len() - content.len();
+            if let Some(block_indent) = block_scalar_indent {
+                if indent > block_indent {
+                    continue;
+                }
+                block_scalar_indent = None;
+            }
+
+            let Some((key, value)) = mapping_key(content) else {
+                continue;
+            };
+            while path.last().is_some_and(|(parent, _)| *parent >= indent) {
+                path.pop();
+            }
+            path.push((indent, key));
+
+            let dotted: Vec<&str> = path.iter().map(|(_, key)| key.as_str()).collect();
+            keys.insert(
+                dotted.join("."),
+                (
+                    file.to_string(),
+                    SourceLocation {
+                        line: index + 1,
+                        column: indent + 1,
+                    },
+                ),
+            );
+
+            if value.starts_with('|') || value.starts_with('>') {
+                block_scalar_indent = Some(indent);
+            }
+        }
+
+        Self { keys }
+    }
+
+    /// Returns the file and location of the dotted `key`, such as
+    /// `steps.build` or `results.version`.
+    #[must_use]
+    pub fn locate(&self, key: &str) -> Option<(&str, SourceLocation)> {
+        self.keys
+            .get(key)
+            .map(|(file, location)| (file.as_str(), *location))
+    }
+
+    /// Returns where the step `id` is defined; steps expanded from a
+    /// `matrix`, such as `test[3.9]`, point at the matrix step.
+    pub(crate) fn step(&self, id: &str) -> Option<(&str, SourceLocation)> {
+        self.locate(&format!("steps.{id}")).or_else(|| {
+            let (base, _) = id.split_once('[')?;
+            self.locate(&format!("steps.{base}"))
+        })
+    }
+
+    /// Adds the keys of `later`, which replace keys defined in both.
+    pub(crate) fn extend(&mut self, later: SourceMap) {
+        self.keys.extend(later.keys);
+    }
+}
+
+/// Splits a `key: value` line into its unquoted key and the trimmed value.
+fn mapping_key(content: &str) -> Option<(String, &str)> {
+    if content.starts_with(['-', '{', '[']) {
+        return None;
+    }
+    let (key, value) =
+        if let Some(quote) = content.chars().next().filter(|c| matches!(c, '"' | '\'')) {
+            let end = content[1..].find(quote)? + 1;
+            let rest = content[end + 1..].trim_start().strip_prefix(':')?;
+            (content[1..end].to_string(), rest)
+        } else {
+            let colon = content
+                .match_indices(':')
+                .map(|(position, _)| position)
+                .find(|position| {
+                    content[position + 1..]
+                        .chars()
+                        .next()
+                        .is_none_or(char::is_whitespace)
+                })?;
+            (
+                content[..colon].trim_end().to_string(),
+                &content[colon + 1..],
+            )
+        };
+    if !value.is_empty() && !value.starts_with(char::is_whitespace) {
+        return None;
+    }
+    Some((key, value.trim()))
+}
@@ -0,0 +1,136 @@
+use crate::chain::Chain;
+use crate::data_type::DataType;
+use crate::errors::{AtentoError, Result};
+use crate::input::Input;
+use crate::step::Step;
+use indexmap::IndexMap;
+use serde_yaml::Value;
+
+/// Most steps a single `matrix` may expand into
+pub(crate) const MAX_MATRIX_COMBINATIONS: usize = 100;
+
+/// One expansion of a matrix step: its step id and the value of each matrix key.
+type Combination = (String, Vec<(String, String)>);
+
+impl Chain {
+    /// Replaces every step with a `matrix` by one step per combination of
+    /// matrix values, in place. Steps whose matrix cannot be expanded are left
+    /// as they are, for [`Chain::validate`] to report.
+    pub(crate) fn expand_matrices(&mut self) {
+        if self.steps.values().all(|step| step.matrix.is_empty()) {
+            return;
+        }
+
+        let mut steps = IndexMap::with_capacity(self.steps.len());
+        for (id, step) in &self.steps {
+            match self.combinations(id, step) {
+                Ok(combinations) if !step.matrix.is_empty() => {
+                    for (expanded_id, values) in combinations {
+                        let mut expanded = step.clone();
+                        expanded.matrix.clear();
+                        for (key, value) in values {
+                            expanded.inputs.insert(
+                                key,
+                                Input::Inline {
+                                    type_: DataType::String,
+                                    value: Value::String(value),
+                                    separator: None,
+                                },
+                            );
+                        }
+                        steps.insert(expanded_id, expanded);
+                    }
+                }
+                _ => {
+                    steps.insert(id.clone(), step.clone());
+                }
+            }
+        }
+        self.steps = steps;
+    }
+
+    /// Reports the step `id` if it still has a `matrix` after expansion.
+    pub(crate) fn validate_matrix(&self, id: &str, step: &Step) -> Result<()> {
+        if !step.matrix.is_empty() {
+            self.combinations(id, step)?;
+            return Err(AtentoError::Validation(format!(
+                "Step '{id}' has a 'matrix' but was not expanded"
+            )));
+        }
+        Ok(())
+    }
+
+    /// Lists the expansions of the matrix step `id`, named `id[a,b]` after
+    /// their values, with the first matrix key varying slowest.
+    fn combinations(&self, id: &str, step: &Step) -> Result<Vec<Combination>> {
+        let mut count: usize = 1;
+        let mut columns = Vec::with_capacity(step.matrix.len());
+        for (key, values) in &step.matrix {
+            if step.inputs.contains_key(key) {
+                return Err(AtentoError::Validation(format!(
+                    "Step '{id}' declares input '{key}', which is also a matrix key"
+                )));
+            }
+            if values.is_empty() {
+                return Err(AtentoError::Validation(format!(
+                    "Step '{id}' has no values for matrix key '{key}'"
+                )));
+            }
+            let texts = values
+                .iter()
+                .map(|value| {
+                    scalar_text(value).ok_or_else(|| {
+                        AtentoError::Validation(format!(
+                            "Step '{id}' has a non-scalar value for matrix key '{key}'"
+                        ))
+                    })
+                })
+                .collect::<Result<Vec<String>>>()?;
+
+            count = count.saturating_mul(texts.len());
+            if count > MAX_MATRIX_COMBINATIONS {
+                return Err(AtentoError::Validation(format!(
+                    "Step '{id}' has a matrix of more than {MAX_MATRIX_COMBINATIONS} combinations"
+                )));
+            }
+            columns.push((key, texts));
+        }
+
+        let mut combinations: Vec<Vec<(String, String)>> = vec![Vec::new()];
+        for (key, texts) in columns {
+            combinations = combinations
+                .into_iter()
+                .flat_map(|combination| {
+                    texts.iter().map(move |text| {
+                        let mut next = combination.clone();
+                        next.push((key.clone(), text.clone()));
+                        next
+                    })
+                })
+                .collect();
+        }
+
+        combinations
+            .into_iter()
+            .map(|values| {
+                let label: Vec<&str> = values.iter().map(|(_, value)| value.as_str()).collect();
+                let expanded_id = format!("{id}[{}]", label.join(","));
+                if self.steps.contains_key(&expanded_id) {
+                    return Err(AtentoError::Validation(format!(
+                        "Step '{id}' expands to '{expanded_id}', which is already a step"
+                    )));
+                }
+                Ok((expanded_id, values))
+            })
+            .collect()
+    }
+}
+
+fn scalar_text(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        Value::Bool(b) => Some(b.to_string()),
+        _ => None,
+    }
+}
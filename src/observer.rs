@@ -0,0 +1,22 @@
+use crate::chain::ChainResult;
+use crate::step::StepResult;
+
+/// Receives progress notifications while a chain runs.
+///
+/// Every method has an empty default implementation, so observers only
+/// implement the events they care about.
+pub trait ChainObserver {
+    /// Called right before a step's script starts executing
+    fn on_step_start(&mut self, _step_id: &str, _step_name: Option<&str>) {}
+
+    /// Called once a step has finished, including steps that failed or timed out
+    fn on_step_complete(&mut self, _step_id: &str, _result: &StepResult) {}
+
+    /// Called with the final result after the chain has finished
+    fn on_chain_complete(&mut self, _result: &ChainResult) {}
+}
+
+/// Observer that ignores every event, used by [`crate::Chain::run`]
+pub struct NoopObserver;
+
+impl ChainObserver for NoopObserver {}
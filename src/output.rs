@@ -1,11 +1,178 @@
-use crate::data_type::DataType;
+use crate::data_type::{DataType, to_text};
+use regex::Regex;
 use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use std::borrow::Cow;
+use std::sync::OnceLock;
 
-/// Defines how to extract an output value from a step's stdout using a regex pattern.
-#[derive(Debug, Clone, Deserialize, Serialize)]
+/// Defines how to extract an output value from a step's stdout or stderr, either
+/// with a regex `pattern`, a `json_path` into the stream parsed as JSON, or the
+/// `key` of a `KEY=value` line.
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
+#[cfg_attr(
+    feature = "schema",
+    derive(schemars::JsonSchema),
+    schemars(deny_unknown_fields)
+)]
 pub struct Output {
     /// Regex pattern with at least one capture group
+    #[serde(default, skip_serializing_if = "String::is_empty")]
     pub pattern: String,
     #[serde(default, rename = "type")]
     pub type_: DataType,
+    /// Name of the capture group to extract; the first group is used when absent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub group: Option<String>,
+    /// Path such as `$.result.version`, `result.version` or `$.items[0].name` into
+    /// stdout parsed as JSON, or into its last line when the whole stream is not JSON
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub json_path: Option<String>,
+    /// Name of a `KEY=value` line whose value is extracted, e.g. `BUILD_DIR`; the
+    /// last line with the key wins and every such line is removed from the stream
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key: Option<String>,
+    /// Captured stream the value is extracted from, or the process exit code
+    #[serde(default, alias = "from")]
+    pub source: OutputSource,
+    /// Capture every match of `pattern` as a list instead of only the first
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub multiple: bool,
+    /// An unmatched pattern does not fail the step: the output takes its `default`,
+    /// is left out, or is an empty list with `multiple`
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub optional: bool,
+    /// Value of an `optional` output whose pattern did not match
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "schema", schemars(with = "Option<serde_json::Value>"))]
+    pub default: Option<serde_yaml::Value>,
+    /// Separator used to join `multiple` values for downstream references (default newline)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub delimiter: Option<String>,
+    /// The regex compiled on first use, shared by validation and every run of the step
+    #[serde(skip)]
+    pub(crate) regex_cache: RegexCache,
+}
+
+/// A compiled output regex, kept together with the pattern it was compiled from
+/// so that an output whose `pattern` changed afterwards is not matched with a
+/// stale regex.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct RegexCache(pub(crate) OnceLock<(String, Regex)>);
+
+impl Output {
+    /// Returns the regex used to extract the output: its `pattern`, or for a
+    /// `key` output one matching whole `KEY=value` lines, line break included.
+    pub(crate) fn regex_pattern(&self) -> Cow<'_, str> {
+        match &self.key {
+            Some(key) => Cow::Owned(format!(
+                r"(?m)^{}=([^\r\n]*)(?:\r?\n|$)",
+                regex::escape(key)
+            )),
+            None => Cow::Borrowed(&self.pattern),
+        }
+    }
+
+    /// Returns the compiled [`Output::regex_pattern`], compiling it only the first
+    /// time.
+    pub(crate) fn regex(&self) -> Result<Regex, regex::Error> {
+        let pattern = self.regex_pattern();
+        if let Some((cached, re)) = self.regex_cache.0.get()
+            && *cached == *pattern
+        {
+            return Ok(re.clone());
+        }
+
+        let re = Regex::new(&pattern)?;
+        // Keeps the first compiled regex; a changed pattern is compiled per call
+        let _ = self.regex_cache.0.set((pattern.into_owned(), re.clone()));
+        Ok(re)
+    }
+
+    /// Renders an extracted value as the text passed to later steps; lists are
+    /// joined with the output's delimiter.
+    #[must_use]
+    pub fn render_value(&self, value: &JsonValue) -> String {
+        match value {
+            JsonValue::Array(items) => items
+                .iter()
+                .map(to_text)
+                .collect::<Vec<_>>()
+                .join(self.delimiter.as_deref().unwrap_or("\n")),
+            other => to_text(other),
+        }
+    }
+}
+
+/// The captured process stream, or exit code, an output is extracted from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum OutputSource {
+    /// Standard output
+    #[default]
+    Stdout,
+    /// Standard error
+    Stderr,
+    /// Standard output first, falling back to standard error
+    Both,
+    /// The process exit code; no `pattern` or `json_path` is used
+    #[serde(rename = "exit_code")]
+    ExitCode,
+}
+
+/// A single step of a parsed JSON path.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum PathSegment {
+    Key(String),
+    Index(usize),
+}
+
+/// Parses a JSON path of the form `$`, `$.key`, `$.key[0].other`, or the same
+/// without the leading `$`, such as `key[0].other`.
+///
+/// Returns `None` if the path is malformed.
+pub(crate) fn parse_json_path(path: &str) -> Option<Vec<PathSegment>> {
+    let path = path.trim();
+    let dotted = format!(".{path}");
+    let mut rest = match path.strip_prefix('$') {
+        Some(rest) => rest,
+        None if path.starts_with('[') => path,
+        None if !path.is_empty() && !path.starts_with('.') => &dotted,
+        None => return None,
+    };
+    let mut segments = Vec::new();
+
+    while !rest.is_empty() {
+        if let Some(after_dot) = rest.strip_prefix('.') {
+            let end = after_dot.find(['.', '[']).unwrap_or(after_dot.len());
+            let key = &after_dot[..end];
+            if key.is_empty() {
+                return None;
+            }
+            segments.push(PathSegment::Key(key.to_string()));
+            rest = &after_dot[end..];
+        } else if let Some(after_bracket) = rest.strip_prefix('[') {
+            let end = after_bracket.find(']')?;
+            let index = after_bracket[..end].trim().parse::<usize>().ok()?;
+            segments.push(PathSegment::Index(index));
+            rest = &after_bracket[end + 1..];
+        } else {
+            return None;
+        }
+    }
+
+    Some(segments)
+}
+
+/// Navigates `value` along the parsed path segments.
+pub(crate) fn resolve_json_path<'a>(
+    value: &'a JsonValue,
+    segments: &[PathSegment],
+) -> Option<&'a JsonValue> {
+    segments
+        .iter()
+        .try_fold(value, |current, segment| match segment {
+            PathSegment::Key(key) => current.get(key.as_str()),
+            PathSegment::Index(index) => current.get(*index),
+        })
 }
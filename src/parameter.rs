@@ -1,21 +1,73 @@
-use crate::data_type::{DataType, to_string_value};
-use crate::errors::Result;
+use crate::data_type::{
+    DEFAULT_LIST_SEPARATOR, DataType, from_text, to_list_string, to_string_value,
+};
+use crate::errors::{AtentoError, Result};
 use serde::{Deserialize, Serialize};
 
 /// A chain parameter with a typed value.
 #[derive(Debug, Clone, Deserialize, Serialize)]
+#[cfg_attr(
+    feature = "schema",
+    derive(schemars::JsonSchema),
+    schemars(deny_unknown_fields)
+)]
 pub struct Parameter {
     #[serde(default, rename = "type")]
     pub type_: DataType,
-    pub value: serde_yaml::Value,
+    /// The parameter value; may be left out for `required` parameters
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[cfg_attr(feature = "schema", schemars(with = "Option<serde_json::Value>"))]
+    pub value: Option<serde_yaml::Value>,
+    /// Required parameters may omit `value` and must then be given one as an override
+    #[serde(default)]
+    pub required: bool,
+    /// Secret values are masked in chain and step results
+    #[serde(default)]
+    pub secret: bool,
+    /// Separator between the elements of a `list` value (default: a space)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub separator: Option<String>,
 }
 
 impl Parameter {
     /// Converts the parameter value to a string according to its `DataType`.
     ///
     /// # Errors
-    /// Returns an error if the value type doesn't match the declared `DataType`.
+    /// Returns an error if the parameter has no value or the value type doesn't
+    /// match the declared `DataType`.
     pub fn to_string_value(&self) -> Result<String> {
-        to_string_value(&self.type_, &self.value)
+        let value = self
+            .value
+            .as_ref()
+            .ok_or_else(|| AtentoError::Validation("Parameter has no value".to_string()))?;
+
+        match self.type_ {
+            DataType::List => to_list_string(
+                value,
+                self.separator.as_deref().unwrap_or(DEFAULT_LIST_SEPARATOR),
+            ),
+            _ => to_string_value(&self.type_, value),
+        }
+    }
+
+    /// Parses `text` into a value of the parameter's `DataType`.
+    ///
+    /// A `list` is split on its `separator` when one is set, otherwise on whitespace.
+    ///
+    /// # Errors
+    /// Returns an error if the text cannot be converted to the declared `DataType`.
+    pub fn parse_value(&self, text: &str) -> Result<serde_yaml::Value> {
+        match (&self.type_, &self.separator) {
+            (DataType::List, Some(separator)) if !separator.trim().is_empty() => {
+                Ok(serde_yaml::Value::Sequence(
+                    text.split(separator.as_str())
+                        .map(str::trim)
+                        .filter(|item| !item.is_empty())
+                        .map(|item| serde_yaml::Value::String(item.to_string()))
+                        .collect(),
+                ))
+            }
+            _ => from_text(&self.type_, text),
+        }
     }
 }
@@ -0,0 +1,95 @@
+use crate::chain::Chain;
+use crate::data_type::DataType;
+use crate::input::Input;
+use crate::result_ref::ResultValue;
+use serde::Serialize;
+use std::collections::HashMap;
+
+/// The static wiring of a chain, as returned by [`Chain::execution_plan`].
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ExecutionPlan {
+    /// Steps in the order they are declared
+    pub steps: Vec<StepPlan>,
+    /// Declared parameters and their types
+    pub parameters: HashMap<String, DataType>,
+    /// Chain results that reference a parameter or step output, by result name
+    pub results: HashMap<String, String>,
+}
+
+/// One step of an [`ExecutionPlan`].
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StepPlan {
+    pub id: String,
+    /// The step's `name`, or its id when it has none
+    pub name: String,
+    /// Command of the interpreter the step runs with; `None` when the step's
+    /// interpreter is missing or unknown
+    pub interpreter: Option<String>,
+    /// Inputs that reference a parameter or step output, by input name
+    pub inputs: HashMap<String, String>,
+    /// Names of the declared outputs
+    pub outputs: Vec<String>,
+    /// Timeout in seconds, bounded by the chain timeout
+    pub timeout: u64,
+    /// Ids of the steps whose outputs or references this step uses
+    pub depends_on: Vec<String>,
+}
+
+impl Chain {
+    /// Describes the chain's steps, parameters and results without resolving
+    /// any values or running anything, e.g. for rendering a dependency diagram.
+    #[must_use]
+    pub fn execution_plan(&self) -> ExecutionPlan {
+        let steps = self
+            .steps
+            .iter()
+            .map(|(id, step)| {
+                let dependencies = self.step_dependencies(id, step);
+                let mut outputs: Vec<String> = step.outputs.keys().cloned().collect();
+                outputs.sort();
+
+                StepPlan {
+                    id: id.clone(),
+                    name: step.name.clone().unwrap_or_else(|| id.clone()),
+                    interpreter: self
+                        .step_interpreter(step, id)
+                        .ok()
+                        .map(|interpreter| interpreter.command.clone()),
+                    inputs: step
+                        .inputs
+                        .iter()
+                        .filter_map(|(name, input)| match input {
+                            Input::Ref { ref_ } => Some((name.clone(), ref_.clone())),
+                            Input::Env { .. } | Input::Inline { .. } => None,
+                        })
+                        .collect(),
+                    outputs,
+                    timeout: step.calculate_timeout(self.timeout),
+                    depends_on: self
+                        .steps
+                        .keys()
+                        .filter(|key| dependencies.contains(key.as_str()))
+                        .cloned()
+                        .collect(),
+                }
+            })
+            .collect();
+
+        ExecutionPlan {
+            steps,
+            parameters: self
+                .parameters
+                .iter()
+                .map(|(name, parameter)| (name.clone(), parameter.type_.clone()))
+                .collect(),
+            results: self
+                .results
+                .iter()
+                .filter_map(|(name, value)| match value {
+                    ResultValue::Ref(result_ref) => Some((name.clone(), result_ref.ref_.clone())),
+                    ResultValue::Inline { .. } => None,
+                })
+                .collect(),
+        }
+    }
+}
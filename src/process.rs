@@ -0,0 +1,105 @@
+//! Process settings and measurements that are part of the chain model and its
+//! results. They live apart from `runner` so they are available without the
+//! `exec` feature.
+
+use crate::cancel::CancellationToken;
+use serde::{Deserialize, Serialize};
+
+/// CPU time and peak memory used by a script's process, including the child
+/// processes it waited for.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ResourceUsage {
+    /// Time spent running the script's own code, in milliseconds
+    pub user_cpu_ms: u64,
+    /// Time the kernel spent on behalf of the script, in milliseconds
+    pub system_cpu_ms: u64,
+    /// Peak resident set size of the largest process, in kilobytes
+    pub max_rss_kb: u64,
+}
+
+impl ResourceUsage {
+    /// Returns the total CPU time, user and system, in milliseconds.
+    #[must_use]
+    pub fn cpu_ms(&self) -> u64 {
+        self.user_cpu_ms + self.system_cpu_ms
+    }
+
+    /// Adds up the CPU times of both and keeps the larger peak memory.
+    #[cfg(feature = "exec")]
+    #[must_use]
+    pub(crate) fn combine(self, other: Self) -> Self {
+        Self {
+            user_cpu_ms: self.user_cpu_ms + other.user_cpu_ms,
+            system_cpu_ms: self.system_cpu_ms + other.system_cpu_ms,
+            max_rss_kb: self.max_rss_kb.max(other.max_rss_kb),
+        }
+    }
+}
+
+/// Signal sent to a process that exceeded its timeout, before it is
+/// force-killed. Only used on Unix.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+pub enum TimeoutSignal {
+    #[default]
+    #[serde(rename = "SIGTERM")]
+    Term,
+    #[serde(rename = "SIGINT")]
+    Int,
+    #[serde(rename = "SIGHUP")]
+    Hup,
+    #[serde(rename = "SIGQUIT")]
+    Quit,
+    #[serde(rename = "SIGKILL")]
+    Kill,
+}
+
+impl TimeoutSignal {
+    /// The signal name without its `SIG` prefix, as `kill -s` takes it.
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Term => "TERM",
+            Self::Int => "INT",
+            Self::Hup => "HUP",
+            Self::Quit => "QUIT",
+            Self::Kill => "KILL",
+        }
+    }
+}
+
+impl std::fmt::Display for TimeoutSignal {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "SIG{}", self.name())
+    }
+}
+
+/// How a process that exceeded its timeout, or whose run was cancelled, is
+/// stopped.
+///
+/// On Unix its process group is sent `signal` and given `grace_ms`
+/// milliseconds to exit before it is killed with `SIGKILL`. Elsewhere the
+/// process is always killed right away.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct KillPolicy {
+    pub signal: TimeoutSignal,
+    pub grace_ms: u64,
+    /// Stops the process as soon as it is cancelled, checked alongside the timeout
+    pub cancel: Option<CancellationToken>,
+}
+
+impl KillPolicy {
+    #[cfg(feature = "exec")]
+    pub(crate) fn is_cancelled(&self) -> bool {
+        self.cancel
+            .as_ref()
+            .is_some_and(CancellationToken::is_cancelled)
+    }
+}
+
+/// The stream a line of process output was written to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputStream {
+    Stdout,
+    Stderr,
+}
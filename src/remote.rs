@@ -0,0 +1,53 @@
+//! The `remote:` section of a chain, describing the host its steps run on over
+//! SSH. Running scripts through it is up to `executor`, with the `exec` feature.
+
+use serde::{Deserialize, Serialize};
+
+/// Executor that runs each script on a remote host over SSH.
+///
+/// The script is sent over stdin into a private temp file on the remote host
+/// and run with the step's interpreter, which must be installed there. Env
+/// entries are exported on the remote command line, so their names must be
+/// shell identifiers, and `workdir` must exist remotely. The
+/// step timeout is enforced on the remote side with `timeout`, so the remote
+/// command is killed as well. Authentication must not prompt: use `key_path`
+/// or an SSH agent.
+///
+/// Configured at chain level with a `remote:` section; [`crate::Chain::run`]
+/// then runs every step through it.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[cfg_attr(
+    feature = "schema",
+    derive(schemars::JsonSchema),
+    schemars(deny_unknown_fields)
+)]
+pub struct SshExecutor {
+    pub host: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+    /// Private key passed to `ssh -i`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key_path: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub port: Option<u16>,
+}
+
+impl SshExecutor {
+    pub fn new(host: impl Into<String>) -> Self {
+        Self {
+            host: host.into(),
+            user: None,
+            key_path: None,
+            port: None,
+        }
+    }
+
+    /// Returns the `[user@]host` destination passed to `ssh`.
+    #[must_use]
+    pub fn destination(&self) -> String {
+        match &self.user {
+            Some(user) => format!("{user}@{}", self.host),
+            None => self.host.clone(),
+        }
+    }
+}
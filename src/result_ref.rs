@@ -1,8 +1,40 @@
+use crate::data_type::DataType;
 use serde::Deserialize;
 
-/// A reference to a step output that should be included in the chain results.
+/// A reference to a step output or parameter that should be included in the chain results.
 #[derive(Debug, Clone, Deserialize)]
+#[cfg_attr(
+    feature = "schema",
+    derive(schemars::JsonSchema),
+    schemars(deny_unknown_fields)
+)]
 pub struct ResultRef {
     #[serde(rename = "ref")]
     pub ref_: String,
 }
+
+/// A chain result value, either a reference or an inline literal.
+#[derive(Debug, Clone, Deserialize)]
+#[cfg_attr(
+    feature = "schema",
+    derive(schemars::JsonSchema),
+    schemars(deny_unknown_fields)
+)]
+#[serde(untagged)]
+pub enum ResultValue {
+    /// Reference to a step output (`steps.X.outputs.Y`) or parameter (`parameters.X`)
+    Ref(ResultRef),
+    /// Inline value with explicit type
+    Inline {
+        #[serde(default, rename = "type")]
+        type_: DataType,
+        #[cfg_attr(feature = "schema", schemars(with = "serde_json::Value"))]
+        value: serde_yaml::Value,
+    },
+}
+
+impl From<ResultRef> for ResultValue {
+    fn from(result_ref: ResultRef) -> Self {
+        Self::Ref(result_ref)
+    }
+}
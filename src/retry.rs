@@ -0,0 +1,84 @@
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+const DEFAULT_RETRY_ATTEMPTS: u32 = 1;
+
+// Helper function to provide the custom default for serde
+fn default_retry_attempts() -> u32 {
+    DEFAULT_RETRY_ATTEMPTS
+}
+
+/// How the delay between retry attempts grows.
+#[derive(PartialEq, Eq, Debug, Clone, Copy, Default, Deserialize, Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum Backoff {
+    /// The same delay before every retry
+    #[default]
+    Fixed,
+    /// The delay grows by `delay_secs` on every retry
+    Linear,
+    /// The delay doubles on every retry
+    Exponential,
+}
+
+/// Retry policy for a step.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[cfg_attr(
+    feature = "schema",
+    derive(schemars::JsonSchema),
+    schemars(deny_unknown_fields)
+)]
+pub struct RetryPolicy {
+    /// Total number of attempts, including the first one
+    #[serde(default = "default_retry_attempts")]
+    pub attempts: u32,
+    /// Base delay between attempts, in seconds
+    #[serde(default)]
+    pub delay_secs: u64,
+    /// Additional base delay in milliseconds, for sub-second delays
+    #[serde(default)]
+    pub delay_ms: u64,
+    #[serde(default)]
+    pub backoff: Backoff,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            attempts: default_retry_attempts(),
+            delay_secs: 0,
+            delay_ms: 0,
+            backoff: Backoff::default(),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Returns the base delay between attempts.
+    #[must_use]
+    pub fn base_delay(&self) -> Duration {
+        Duration::from_secs(self.delay_secs).saturating_add(Duration::from_millis(self.delay_ms))
+    }
+
+    /// Returns the delay to wait after the given (1-based) failed attempt.
+    #[must_use]
+    pub fn delay_after(&self, attempt: u32) -> Duration {
+        let retry = attempt.max(1);
+        let base = self.base_delay();
+        match self.backoff {
+            Backoff::Fixed => base,
+            Backoff::Linear => base.saturating_mul(retry),
+            Backoff::Exponential => base.saturating_mul(2u32.saturating_pow(retry - 1)),
+        }
+    }
+}
+
+/// Summary of a single execution attempt of a step.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AttemptSummary {
+    pub exit_code: i32,
+    pub duration_ms: u128,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
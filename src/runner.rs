@@ -1,42 +1,532 @@
 use crate::errors::{AtentoError, Result};
 use crate::interpreter;
+pub use crate::process::{KillPolicy, OutputStream, ResourceUsage, TimeoutSignal};
+use std::collections::HashMap;
+use std::ffi::OsString;
+use std::fs::OpenOptions;
+use std::io::{Read, Write};
 #[cfg(unix)]
-use std::fs::Permissions;
+use std::os::unix::fs::OpenOptionsExt;
 #[cfg(unix)]
-use std::os::unix::fs::PermissionsExt;
-use std::path::PathBuf;
-use std::process::{Command, Stdio};
+use std::os::unix::process::CommandExt;
+#[cfg(windows)]
+use std::os::windows::process::CommandExt;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc;
 use std::time::{Duration, Instant};
 
 const TEMP_FILENAME: &str = "atento_temp_file_";
+/// Environment variable naming the directory temp script files are written to,
+/// instead of the system temp directory
+pub const TMPDIR_ENV: &str = "ATENTO_TMPDIR";
 const STDERR_FILTER_PATTERNS: &[&str] = &["[Perftrack", "NamedPipeIPC"];
 const DEFAULT_RUNNER_TIMEOUT_SECS: u64 = 86400; // 1 day
+const DOCKER_COMMAND: &str = "docker";
+/// Bytes read from an output pipe at a time
+pub(crate) const READ_CHUNK_BYTES: usize = 8192;
+/// Directory the script file is mounted into inside a container
+const CONTAINER_SCRIPT_DIR: &str = "/atento";
+#[cfg(windows)]
+const CREATE_NEW_PROCESS_GROUP: u32 = 0x0000_0200;
+/// Exit code reported for processes killed on timeout, as used by coreutils `timeout`
+pub const TIMEOUT_EXIT_CODE: i32 = 124;
 
-// A small RAII guard to remove the temp file when dropped
-struct TempRemover(PathBuf);
-impl Drop for TempRemover {
+/// Counts the script files written by this process, keeping their names
+/// unique when several steps start within the same clock tick
+static SCRIPT_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// A temp script file, removed when the guard is dropped, including while a
+/// panic unwinds.
+pub(crate) struct TempScript(PathBuf);
+
+impl TempScript {
+    pub(crate) fn path(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TempScript {
     fn drop(&mut self) {
         let _ = std::fs::remove_file(&self.0);
     }
 }
 
+#[allow(clippy::struct_excessive_bools)]
 pub struct RunnerResult {
     pub exit_code: i32,
     pub duration_ms: u128,
     pub stdout: Option<String>,
     pub stderr: Option<String>,
+    /// Stdout and stderr lines merged in the order they were written, recorded by [`run_combined`]
+    pub combined: Option<String>,
+    /// The process was killed on timeout; the output holds what it wrote before
+    pub timed_out: bool,
+    /// The interpreter command that ran the script, set when it was started on
+    /// this host; differs from the interpreter's `command` when a fallback was used
+    pub resolved_command: Option<String>,
+    /// Stdout went past the output limit; only its beginning was kept
+    pub stdout_truncated: bool,
+    /// Stderr went past the output limit; only its beginning was kept
+    pub stderr_truncated: bool,
+    /// The process timed out and had to be killed with `SIGKILL` because it
+    /// did not exit within the grace period after the [`KillPolicy`] signal
+    pub force_killed: bool,
+    /// CPU time and peak memory of the script, recorded with the
+    /// `resource-metrics` feature on Unix for scripts run on this host
+    pub resource_usage: Option<ResourceUsage>,
 }
 
 /// Runs a script with a timeout.
 ///
+/// The script is written to a temp file readable only by the current user, in
+/// the directory named by [`TMPDIR_ENV`] or else the system temp directory,
+/// and removed once the process has finished.
+///
+/// Entries in `env` are added to the spawned process environment. When `workdir`
+/// is set, the process runs in that directory, which must exist. `stdin` is
+/// written to the process's standard input on a separate thread, which then
+/// closes the pipe.
+///
+/// Stdout and stderr are read while the process runs. When the timeout is
+/// exceeded the process is stopped together with every process it started, as
+/// described by `kill`, and the output written so far is returned with `timed_out` set and exit code
+/// [`TIMEOUT_EXIT_CODE`]. A process whose `kill` token is cancelled is stopped
+/// the same way and a `Cancelled` error returned. With `max_output_bytes` set, each stream keeps only
+/// its first `max_output_bytes` bytes and is marked as truncated past that; the
+/// rest is still read so the process never blocks on a full pipe.
+///
 /// # Errors
 /// Returns an error if the script or arguments are empty, if the temp file cannot be created,
-/// if the command fails to start, or if the timeout is exceeded.
+/// for example because [`TMPDIR_ENV`] names a missing directory, or if the command fails to start.
+#[allow(clippy::too_many_arguments)]
 pub fn run(
     script: &str,
     interpreter: &interpreter::Interpreter,
+    env: &HashMap<String, String>,
+    workdir: Option<&str>,
+    stdin: Option<&str>,
+    max_output_bytes: Option<u64>,
+    timeout_secs: u64,
+    kill: &KillPolicy,
+) -> Result<RunnerResult> {
+    run_streaming(
+        script,
+        interpreter,
+        env,
+        workdir,
+        stdin,
+        max_output_bytes,
+        timeout_secs,
+        kill,
+        &mut |_, _| {},
+    )
+}
+
+/// Runs a script with a timeout, passing each line of stdout and stderr to
+/// `on_line` as soon as the process writes it.
+///
+/// The pipes are read on separate threads while `on_line` is called on the
+/// calling thread. Stderr lines matching the noise filter are not forwarded.
+/// Every line reaches `on_line`, while the returned result holds the output up
+/// to `max_output_bytes`, as with [`run`].
+///
+/// # Errors
+/// Returns the same errors as [`run`].
+#[allow(clippy::too_many_arguments)]
+pub fn run_streaming(
+    script: &str,
+    interpreter: &interpreter::Interpreter,
+    env: &HashMap<String, String>,
+    workdir: Option<&str>,
+    stdin: Option<&str>,
+    max_output_bytes: Option<u64>,
     timeout_secs: u64,
+    kill: &KillPolicy,
+    on_line: &mut dyn FnMut(OutputStream, &str),
 ) -> Result<RunnerResult> {
+    let mut result = run_combined(
+        script,
+        interpreter,
+        env,
+        workdir,
+        stdin,
+        max_output_bytes,
+        timeout_secs,
+        kill,
+        on_line,
+    )?;
+    result.combined = None;
+    Ok(result)
+}
+
+/// Runs a script like [`run_streaming`] and also records stdout and stderr
+/// merged into a single text, line by line in the order the process wrote them.
+/// The merged text is capped at `max_output_bytes` as well.
+///
+/// # Errors
+/// Returns the same errors as [`run`].
+#[allow(clippy::too_many_arguments)]
+pub fn run_combined(
+    script: &str,
+    interpreter: &interpreter::Interpreter,
+    env: &HashMap<String, String>,
+    workdir: Option<&str>,
+    stdin: Option<&str>,
+    max_output_bytes: Option<u64>,
+    timeout_secs: u64,
+    kill: &KillPolicy,
+    on_line: &mut dyn FnMut(OutputStream, &str),
+) -> Result<RunnerResult> {
+    let temp_script = write_script(script, interpreter, workdir)?;
+    let (mut child, resolved_command) = start_host(
+        interpreter,
+        workdir,
+        temp_script.path(),
+        env,
+        stdin.is_some(),
+    )?;
+    if let Some(input) = stdin {
+        feed_stdin(&mut child, input);
+    }
+    let mut result = wait_for_output(child, None, max_output_bytes, timeout_secs, kill, on_line)?;
+    result.resolved_command = Some(resolved_command);
+    Ok(result)
+}
+
+/// Runs a script inside a Docker container of `image`, with the interpreter
+/// command as the container entrypoint.
+///
+/// The script file is mounted read-only into the container, a `workdir` is
+/// mounted at the same path and used as the container working directory, and
+/// `env` entries are passed through by name so their values stay off the
+/// command line, and `stdin` is forwarded into the container. Output is
+/// collected as in [`run_combined`]. On timeout the container is sent the
+/// signal with `docker kill` before it and the local `docker run` process are
+/// killed.
+///
+/// # Errors
+/// Returns the same errors as [`run`], and a `Runner` error naming `docker`
+/// when it is not installed.
+#[allow(clippy::too_many_arguments)]
+pub fn run_in_container(
+    script: &str,
+    interpreter: &interpreter::Interpreter,
+    image: &str,
+    env: &HashMap<String, String>,
+    workdir: Option<&str>,
+    stdin: Option<&str>,
+    max_output_bytes: Option<u64>,
+    timeout_secs: u64,
+    kill: &KillPolicy,
+) -> Result<RunnerResult> {
+    if image.trim().is_empty() {
+        return Err(AtentoError::Runner(
+            "Container image cannot be empty".to_string(),
+        ));
+    }
+
+    let temp_script = write_script(script, interpreter, workdir)?;
+    let container = container_name();
+    let mut cmd = container_command(
+        &container,
+        image,
+        interpreter,
+        env,
+        workdir,
+        temp_script.path(),
+        stdin.is_some(),
+    );
+    if stdin.is_some() {
+        cmd.stdin(Stdio::piped());
+    }
+    let mut child = spawn(&mut cmd, env).map_err(|e| docker_start_error(&e))?;
+    if let Some(input) = stdin {
+        feed_stdin(&mut child, input);
+    }
+    let mut result = wait_for_output(
+        child,
+        Some(&container),
+        max_output_bytes,
+        timeout_secs,
+        kill,
+        &mut |_, _| {},
+    )?;
+    // What the local `docker run` client used says nothing about the script
+    result.resource_usage = None;
+    Ok(result)
+}
+
+/// Runs `cmd` with `input` written to its stdin, collecting output as in
+/// [`run_combined`].
+///
+/// Used by executors that hand the script to another program, such as `ssh`,
+/// instead of running a local interpreter.
+///
+/// # Errors
+/// Returns an error if the command fails to start.
+pub(crate) fn run_with_input(
+    cmd: &mut Command,
+    input: &str,
+    max_output_bytes: Option<u64>,
+    timeout_secs: u64,
+    kill: &KillPolicy,
+    on_line: &mut dyn FnMut(OutputStream, &str),
+) -> Result<RunnerResult> {
+    cmd.stdin(Stdio::piped());
+    let mut child = start(cmd, &HashMap::new())?;
+    feed_stdin(&mut child, input);
+    let mut result = wait_for_output(child, None, max_output_bytes, timeout_secs, kill, on_line)?;
+    // The script runs elsewhere, so the usage of `cmd` says nothing about it
+    result.resource_usage = None;
+    Ok(result)
+}
+
+/// Writes `input` to the piped stdin of `child` and closes it once written.
+fn feed_stdin(child: &mut Child, input: &str) {
+    if let Some(mut stdin) = child.stdin.take() {
+        let input = input.to_string();
+        // Write on a separate thread so a large input cannot block on full output
+        // pipes; a process that exits without reading it just closes the pipe
+        std::thread::spawn(move || {
+            let _ = stdin.write_all(input.as_bytes());
+        });
+    }
+}
+
+/// Reads the output of a started process until it exits or `timeout_secs` passes.
+fn wait_for_output(
+    mut child: Child,
+    container: Option<&str>,
+    max_output_bytes: Option<u64>,
+    timeout_secs: u64,
+    kill: &KillPolicy,
+    on_line: &mut dyn FnMut(OutputStream, &str),
+) -> Result<RunnerResult> {
+    let timeout = runner_timeout(timeout_secs);
+    let start = Instant::now();
+
+    let mut output = CollectedOutput::new(max_output_bytes);
+    let (sender, receiver) = mpsc::channel();
+    if let Some(stdout) = child.stdout.take() {
+        forward_lines(stdout, OutputStream::Stdout, output.limit, sender.clone());
+    }
+    if let Some(stderr) = child.stderr.take() {
+        forward_lines(stderr, OutputStream::Stderr, output.limit, sender);
+    }
+
+    loop {
+        match receiver.recv_timeout(Duration::from_millis(100)) {
+            Ok((stream, piped)) => output.receive(stream, piped, on_line),
+            // Both pipes are closed, so the process is done writing
+            Err(mpsc::RecvTimeoutError::Disconnected) => break,
+            Err(mpsc::RecvTimeoutError::Timeout) => {}
+        }
+
+        if kill.is_cancelled() {
+            stop(&mut child, container, kill);
+            return Err(cancelled());
+        }
+        if start.elapsed() >= timeout {
+            let force_killed = stop(&mut child, container, kill);
+            // Keep the lines that were read before the process was stopped
+            while let Ok((stream, piped)) = receiver.try_recv() {
+                output.receive(stream, piped, on_line);
+            }
+            return Ok(output.into_timeout_result(&start, force_killed));
+        }
+    }
+
+    let (status, resource_usage) = loop {
+        if let Some(exited) = try_wait(&mut child)
+            .map_err(|e| AtentoError::Execution(format!("Failed to check process: {e}")))?
+        {
+            break exited;
+        }
+
+        if kill.is_cancelled() {
+            stop(&mut child, container, kill);
+            return Err(cancelled());
+        }
+        if start.elapsed() >= timeout {
+            let force_killed = stop(&mut child, container, kill);
+            return Ok(output.into_timeout_result(&start, force_killed));
+        }
+
+        std::thread::sleep(Duration::from_millis(10));
+    };
+
+    let mut result = output.into_result(&start, status.code().unwrap_or(-1), false);
+    result.resource_usage = resource_usage;
+    Ok(result)
+}
+
+/// Checks whether `child` has exited, returning its status and, with the
+/// `resource-metrics` feature, the resources it used.
+#[cfg(all(unix, feature = "resource-metrics"))]
+fn try_wait(child: &mut Child) -> std::io::Result<Option<(ExitStatus, Option<ResourceUsage>)>> {
+    use std::os::unix::process::ExitStatusExt;
+
+    let pid = libc::pid_t::try_from(child.id()).map_err(std::io::Error::other)?;
+    let mut status = 0;
+    // SAFETY: `rusage` is plain data, for which all zeroes is a valid value
+    let mut usage: libc::rusage = unsafe { std::mem::zeroed() };
+    // SAFETY: both pointers are valid for writes for the duration of the call.
+    // The child is reaped here, so it must not be waited for through `child`
+    // afterwards, which `wait_for_output` never does after it exited
+    let reaped = unsafe { libc::wait4(pid, &raw mut status, libc::WNOHANG, &raw mut usage) };
+    match reaped {
+        0 => Ok(None),
+        -1 => Err(std::io::Error::last_os_error()),
+        _ => Ok(Some((
+            ExitStatus::from_raw(status),
+            Some(resource_usage(&usage)),
+        ))),
+    }
+}
+
+#[cfg(not(all(unix, feature = "resource-metrics")))]
+fn try_wait(child: &mut Child) -> std::io::Result<Option<(ExitStatus, Option<ResourceUsage>)>> {
+    Ok(child.try_wait()?.map(|status| (status, None)))
+}
+
+/// Converts what `wait4` reported into a [`ResourceUsage`].
+#[cfg(all(unix, feature = "resource-metrics"))]
+fn resource_usage(usage: &libc::rusage) -> ResourceUsage {
+    let millis = |time: libc::timeval| {
+        let secs = u64::try_from(time.tv_sec).unwrap_or_default();
+        let micros = u64::try_from(time.tv_usec).unwrap_or_default();
+        secs * 1000 + micros / 1000
+    };
+    let max_rss = u64::try_from(usage.ru_maxrss).unwrap_or_default();
+    ResourceUsage {
+        user_cpu_ms: millis(usage.ru_utime),
+        system_cpu_ms: millis(usage.ru_stime),
+        // macOS reports bytes, other systems kilobytes
+        max_rss_kb: if cfg!(target_os = "macos") {
+            max_rss / 1024
+        } else {
+            max_rss
+        },
+    }
+}
+
+/// Output read from a running process, per stream and merged.
+///
+/// With a `limit`, each of the three texts keeps at most that many bytes from
+/// the start of the output. The pipe readers already stop passing on lines
+/// past the limit of their stream, see [`LineSplitter`], and only report how
+/// many bytes they dropped.
+#[derive(Default)]
+pub(crate) struct CollectedOutput {
+    stdout: String,
+    stderr: String,
+    combined: String,
+    pub(crate) limit: Option<usize>,
+    /// Bytes of stdout dropped past the limit
+    stdout_dropped: usize,
+    /// Bytes of stderr dropped past the limit
+    stderr_dropped: usize,
+}
+
+impl CollectedOutput {
+    pub(crate) fn new(max_output_bytes: Option<u64>) -> Self {
+        Self {
+            limit: max_output_bytes.map(|max| usize::try_from(max).unwrap_or(usize::MAX)),
+            ..Self::default()
+        }
+    }
+
+    /// Records what a pipe reader passed on, see [`CollectedOutput::push`].
+    pub(crate) fn receive(
+        &mut self,
+        stream: OutputStream,
+        piped: Piped,
+        on_line: &mut dyn FnMut(OutputStream, &str),
+    ) {
+        match (piped, stream) {
+            (Piped::Line(line), _) => self.push(stream, &line, on_line),
+            (Piped::Dropped(bytes), OutputStream::Stdout) => self.stdout_dropped += bytes,
+            (Piped::Dropped(bytes), OutputStream::Stderr) => self.stderr_dropped += bytes,
+        }
+    }
+
+    /// Records a line, skipping stderr noise, and passes it to `on_line`.
+    pub(crate) fn push(
+        &mut self,
+        stream: OutputStream,
+        line: &str,
+        on_line: &mut dyn FnMut(OutputStream, &str),
+    ) {
+        let (buffer, dropped) = match stream {
+            OutputStream::Stdout => (&mut self.stdout, &mut self.stdout_dropped),
+            OutputStream::Stderr if is_stderr_noise(line) => return,
+            OutputStream::Stderr => (&mut self.stderr, &mut self.stderr_dropped),
+        };
+        on_line(stream, line);
+        *dropped += append_line(buffer, line, self.limit);
+        append_line(&mut self.combined, line, self.limit);
+    }
+
+    pub(crate) fn into_result(
+        self,
+        start: &Instant,
+        exit_code: i32,
+        timed_out: bool,
+    ) -> RunnerResult {
+        let mut result = build_result(start, exit_code, &self.stdout, &self.stderr);
+        result.combined = Some(self.combined.trim_end().to_string()).filter(|s| !s.is_empty());
+        result.timed_out = timed_out;
+        result.stdout_truncated = self.stdout_dropped > 0;
+        result.stderr_truncated = self.stderr_dropped > 0;
+        mark_truncated(&mut result.stdout, self.stdout_dropped);
+        mark_truncated(&mut result.stderr, self.stderr_dropped);
+        result
+    }
+
+    pub(crate) fn into_timeout_result(self, start: &Instant, force_killed: bool) -> RunnerResult {
+        let mut result = self.into_result(start, TIMEOUT_EXIT_CODE, true);
+        result.force_killed = force_killed;
+        result
+    }
+}
+
+/// Appends `line` and a newline to `text`, cut short so `text` stays within
+/// `limit` bytes. Returns the number of bytes that did not fit.
+fn append_line(text: &mut String, line: &str, limit: Option<usize>) -> usize {
+    let room = limit.map_or(usize::MAX, |limit| limit.saturating_sub(text.len()));
+    if line.len() < room {
+        text.push_str(line);
+        text.push('\n');
+        return 0;
+    }
+    let mut end = room.min(line.len());
+    while !line.is_char_boundary(end) {
+        end -= 1;
+    }
+    text.push_str(&line[..end]);
+    line.len() + 1 - end
+}
+
+/// Ends truncated output with a line saying how many bytes were dropped.
+fn mark_truncated(output: &mut Option<String>, dropped: usize) {
+    if dropped > 0 {
+        let marker = format!("...[truncated {dropped} bytes]");
+        *output = Some(match output.take() {
+            Some(kept) => format!("{kept}\n{marker}"),
+            None => marker,
+        });
+    }
+}
+
+/// Validates the inputs and writes the script to a temp file in
+/// [`script_dir`] that is removed when the returned guard is dropped.
+pub(crate) fn write_script(
+    script: &str,
+    interpreter: &interpreter::Interpreter,
+    workdir: Option<&str>,
+) -> Result<TempScript> {
     if script.is_empty() {
         return Err(AtentoError::Runner("Script cannot be empty".to_string()));
     }
@@ -47,108 +537,513 @@ pub fn run(
         ));
     }
 
-    // Create a uniquely-named temporary script file in the OS temp directory.
-    // We write and close the file so the spawned process can access it on Windows.
-    let mut path = std::env::temp_dir();
-    let nanos = std::time::SystemTime::now()
-        .duration_since(std::time::UNIX_EPOCH)
-        .map(|d| d.as_nanos())
-        .unwrap_or(0);
-    let filename = format!("{TEMP_FILENAME}{nanos}{}", interpreter.extension);
-    path.push(filename);
+    if let Some(dir) = workdir
+        && !std::path::Path::new(dir).is_dir()
+    {
+        return Err(AtentoError::Runner(format!(
+            "Invalid workdir '{dir}': not an existing directory"
+        )));
+    }
 
-    std::fs::write(&path, format!("{script}\n"))
-        .map_err(|e| AtentoError::Runner(format!("Failed to write temp script file: {e}")))?;
+    let dir = script_dir(std::env::var_os(TMPDIR_ENV))?;
+    create_script_file(&dir, script, &interpreter.extension)
+}
+
+/// Returns the directory temp script files are written to: `configured`, the
+/// value of [`TMPDIR_ENV`], when set and non-empty, or the system temp directory.
+///
+/// # Errors
+/// Returns an error if the configured directory does not exist.
+pub(crate) fn script_dir(configured: Option<OsString>) -> Result<PathBuf> {
+    match configured.filter(|dir| !dir.is_empty()) {
+        Some(dir) => {
+            let dir = PathBuf::from(dir);
+            if dir.is_dir() {
+                Ok(dir)
+            } else {
+                Err(AtentoError::Runner(format!(
+                    "Invalid {TMPDIR_ENV} '{}': not an existing directory",
+                    dir.display()
+                )))
+            }
+        }
+        None => Ok(std::env::temp_dir()),
+    }
+}
 
-    // Set explicit permissions on Unix-like platforms
+/// Creates a uniquely-named script file in `dir` holding `script`.
+///
+/// On Unix the file is created readable and writable by the owner only, so
+/// substituted secrets are never exposed to other users. The file is closed
+/// before returning so the spawned process can access it on Windows.
+pub(crate) fn create_script_file(dir: &Path, script: &str, extension: &str) -> Result<TempScript> {
+    let path = dir.join(format!(
+        "{TEMP_FILENAME}{}_{}{extension}",
+        unique_suffix(),
+        SCRIPT_COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
     #[cfg(unix)]
-    {
-        let perm = Permissions::from_mode(0o700);
-        std::fs::set_permissions(&path, perm)
-            .map_err(|e| AtentoError::Runner(format!("Failed to set permissions: {e}")))?;
+    options.mode(0o600);
+    let mut file = options
+        .open(&path)
+        .map_err(|e| AtentoError::Runner(format!("Failed to create temp script file: {e}")))?;
+
+    // Guard the file before writing, so a failed write does not leave it behind
+    let temp_script = TempScript(path);
+    file.write_all(format!("{script}\n").as_bytes())
+        .map_err(|e| AtentoError::Runner(format!("Failed to write temp script file: {e}")))?;
+
+    Ok(temp_script)
+}
+
+/// Starts the script file at `path` with the first of the interpreter's
+/// [candidates](interpreter::Interpreter::candidates) that exists on this host,
+/// returning the process and the command that was started.
+///
+/// Only a command that is not found moves on to the next candidate; any other
+/// spawn error is returned right away.
+fn start_host(
+    interpreter: &interpreter::Interpreter,
+    workdir: Option<&str>,
+    path: &Path,
+    env: &HashMap<String, String>,
+    piped_stdin: bool,
+) -> Result<(Child, String)> {
+    start_host_with(interpreter, workdir, path, piped_stdin, |mut cmd| {
+        spawn(&mut cmd, env)
+    })
+}
+
+/// Starts the script file at `path` like [`start_host`], handing each
+/// candidate command to `spawn`, which returns the started process.
+pub(crate) fn start_host_with<C>(
+    interpreter: &interpreter::Interpreter,
+    workdir: Option<&str>,
+    path: &Path,
+    piped_stdin: bool,
+    mut spawn: impl FnMut(Command) -> std::io::Result<C>,
+) -> Result<(C, String)> {
+    let mut not_found = std::io::Error::from(std::io::ErrorKind::NotFound);
+    for (program, leading_args) in interpreter.candidates() {
+        let mut cmd = host_command(program, &leading_args, interpreter, workdir, path);
+        if piped_stdin {
+            cmd.stdin(Stdio::piped());
+        }
+        match spawn(cmd) {
+            Ok(child) => {
+                let resolved = std::iter::once(program)
+                    .chain(leading_args)
+                    .collect::<Vec<_>>()
+                    .join(" ");
+                return Ok((child, resolved));
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => not_found = e,
+            Err(e) => return Err(start_error(&e)),
+        }
     }
 
-    // RAII guard to remove the temp file when the function returns
-    let _remover = TempRemover(path.clone());
+    if interpreter.fallbacks.is_empty() {
+        return Err(start_error(&not_found));
+    }
+    let tried: Vec<String> = std::iter::once(&interpreter.command)
+        .chain(&interpreter.fallbacks)
+        .map(|command| format!("'{command}'"))
+        .collect();
+    Err(AtentoError::Runner(format!(
+        "Failed to start command: none of {} was found: {not_found}",
+        tried.join(", ")
+    )))
+}
 
-    let mut cmd = Command::new(interpreter.command.as_str());
+/// Builds the command that runs the script file at `path` with `program` on
+/// the host, passing `leading_args` before the interpreter's own arguments.
+fn host_command(
+    program: &str,
+    leading_args: &[&str],
+    interpreter: &interpreter::Interpreter,
+    workdir: Option<&str>,
+    path: &Path,
+) -> Command {
+    let mut cmd = Command::new(program);
+    cmd.args(leading_args);
     if !interpreter.args.is_empty() {
         cmd.args(&interpreter.args);
     }
 
+    if let Some(dir) = workdir {
+        cmd.current_dir(dir);
+    }
+
     // PowerShell: opt out of telemetry
     if interpreter.extension == ".ps1" {
         cmd.env("POWERSHELL_TELEMETRY_OPTOUT", "1");
     }
 
-    let mut child = cmd
-        .arg(&path)
-        .stdout(Stdio::piped())
-        .stderr(Stdio::piped())
-        .spawn()
-        .map_err(|e| AtentoError::Runner(format!("Failed to start command: {e}")))?;
+    cmd.arg(path);
+    cmd
+}
 
-    // temp_file will be dropped when it goes out of scope (after spawn)
+/// Builds the `docker run` command that runs the script file at `path` in a
+/// container named `container`. With `interactive` set the container keeps
+/// its stdin open.
+pub(crate) fn container_command(
+    container: &str,
+    image: &str,
+    interpreter: &interpreter::Interpreter,
+    env: &HashMap<String, String>,
+    workdir: Option<&str>,
+    path: &Path,
+    interactive: bool,
+) -> Command {
+    let script_path = format!("{CONTAINER_SCRIPT_DIR}/script{}", interpreter.extension);
 
-    let timeout = if timeout_secs > 0 {
+    let mut cmd = Command::new(DOCKER_COMMAND);
+    cmd.args(["run", "--rm", "--name", container]);
+    if interactive {
+        cmd.arg("-i");
+    }
+    cmd.arg("-v")
+        .arg(format!("{}:{script_path}:ro", path.display()));
+
+    if let Some(dir) = workdir {
+        cmd.arg("-v").arg(format!("{dir}:{dir}")).args(["-w", dir]);
+    }
+
+    // Values come from the docker process environment, see `start`
+    let mut names: Vec<&String> = env.keys().collect();
+    names.sort();
+    for name in names {
+        cmd.args(["-e", name]);
+    }
+
+    if interpreter.extension == ".ps1" {
+        cmd.args(["-e", "POWERSHELL_TELEMETRY_OPTOUT=1"]);
+    }
+
+    cmd.args(["--entrypoint", interpreter.command.as_str(), image])
+        .args(&interpreter.args)
+        .arg(script_path);
+    cmd
+}
+
+/// Starts `cmd` with `env` added to its environment and its output piped.
+fn start(cmd: &mut Command, env: &HashMap<String, String>) -> Result<Child> {
+    spawn(cmd, env).map_err(|e| start_error(&e))
+}
+
+pub(crate) fn start_error(e: &std::io::Error) -> AtentoError {
+    AtentoError::Runner(format!("Failed to start command: {e}"))
+}
+
+/// Like [`start_error`], naming `docker` when it is not installed, since the
+/// step itself only names an image.
+pub(crate) fn docker_start_error(e: &std::io::Error) -> AtentoError {
+    if e.kind() == std::io::ErrorKind::NotFound {
+        AtentoError::Runner(format!(
+            "Failed to start command: '{DOCKER_COMMAND}' was not found, it is needed to run steps with an 'image': {e}"
+        ))
+    } else {
+        start_error(e)
+    }
+}
+
+/// Spawns `cmd` like [`start`], keeping the I/O error so callers can tell a
+/// missing command apart.
+fn spawn(cmd: &mut Command, env: &HashMap<String, String>) -> std::io::Result<Child> {
+    configure(cmd, env).spawn()
+}
+
+/// Adds `env` to the environment of `cmd`, pipes its output and puts it in a
+/// process group of its own.
+pub(crate) fn configure<'a>(
+    cmd: &'a mut Command,
+    env: &HashMap<String, String>,
+) -> &'a mut Command {
+    // Start the process in its own process group so a timeout can kill
+    // the background processes it spawned as well
+    #[cfg(unix)]
+    cmd.process_group(0);
+    #[cfg(windows)]
+    cmd.creation_flags(CREATE_NEW_PROCESS_GROUP);
+
+    cmd.envs(env).stdout(Stdio::piped()).stderr(Stdio::piped())
+}
+
+/// Returns a name for a new container that is unique to this run.
+pub(crate) fn container_name() -> String {
+    format!("atento_{}_{}", std::process::id(), unique_suffix())
+}
+
+fn unique_suffix() -> u128 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |d| d.as_nanos())
+}
+
+pub(crate) fn runner_timeout(timeout_secs: u64) -> Duration {
+    if timeout_secs > 0 {
         Duration::from_secs(timeout_secs)
     } else {
         Duration::from_secs(DEFAULT_RUNNER_TIMEOUT_SECS)
+    }
+}
+
+pub(crate) fn cancelled() -> AtentoError {
+    AtentoError::Cancelled {
+        context: "while the script was running".to_string(),
+    }
+}
+
+/// Stops a process that exceeded its timeout as `policy` describes. Returns
+/// whether it had to be force-killed.
+#[cfg(unix)]
+fn stop(child: &mut Child, container: Option<&str>, policy: &KillPolicy) -> bool {
+    if policy.signal == TimeoutSignal::Kill {
+        kill(child, container);
+        return true;
+    }
+    let signal = policy.signal.name();
+    // The local `docker run` process would forward the signal as well, so
+    // only the container is signalled
+    match container {
+        Some(name) => signal_container(name, signal),
+        None => signal_tree(child.id(), signal),
+    }
+
+    let deadline = Instant::now() + Duration::from_millis(policy.grace_ms);
+    let exited = loop {
+        if matches!(child.try_wait(), Ok(Some(_))) {
+            break true;
+        }
+        if Instant::now() >= deadline {
+            break false;
+        }
+        std::thread::sleep(Duration::from_millis(10));
     };
+    // Kill whatever is left, such as children the script left behind
+    kill(child, container);
+    !exited
+}
 
-    let start = Instant::now();
+#[cfg(not(unix))]
+fn stop(child: &mut Child, container: Option<&str>, _policy: &KillPolicy) -> bool {
+    kill(child, container);
+    true
+}
 
-    loop {
-        //        if let Some(status) = child.try_wait().map_err(Ok(op)|e| Err(format!("Failed to check process: {}", e))) {
-        if let Some(_status) = child
-            .try_wait()
-            .map_err(|e| AtentoError::Execution(format!("Failed to check process: {e}")))?
-        {
-            // Process finished; collect output and return it regardless of exit code.
+/// Kills a process that exceeded its timeout, along with its process tree and
+/// the container it runs, if any.
+fn kill(child: &mut Child, container: Option<&str>) {
+    if let Some(name) = container {
+        signal_container(name, TimeoutSignal::Kill.name());
+    }
+    kill_tree(child.id());
+    // Ignore kill errors, the process may have exited in the meantime
+    let _ = child.kill();
+    // Reap the process so it does not linger as a zombie
+    let _ = child.wait();
+}
+
+/// Sends `signal` to the container `name` with `docker kill`.
+pub(crate) fn signal_container(name: &str, signal: &str) {
+    let _ = signal_container_command(name, signal).status();
+}
+
+/// Builds the `docker kill` command that sends `signal` to the container `name`.
+pub(crate) fn signal_container_command(name: &str, signal: &str) -> Command {
+    let mut cmd = Command::new(DOCKER_COMMAND);
+    cmd.args(["kill", "--signal", signal, name])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+    cmd
+}
+
+/// Kills the process group led by `pid`, which `spawn` created for the
+/// interpreter, or on Windows the process `pid` and every process it started.
+#[cfg(any(unix, windows))]
+pub(crate) fn kill_tree(pid: u32) {
+    let _ = kill_tree_command(pid).status();
+}
+
+#[cfg(not(any(unix, windows)))]
+pub(crate) fn kill_tree(_pid: u32) {}
+
+/// Builds the command [`kill_tree`] runs.
+#[cfg(unix)]
+pub(crate) fn kill_tree_command(pid: u32) -> Command {
+    signal_tree_command(pid, TimeoutSignal::Kill.name())
+}
+
+#[cfg(windows)]
+pub(crate) fn kill_tree_command(pid: u32) -> Command {
+    let mut cmd = Command::new("taskkill");
+    cmd.args(["/T", "/F", "/PID", &pid.to_string()])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+    cmd
+}
 
-            let output = child.wait_with_output().map_err(|e| {
-                AtentoError::Execution(format!("Failed to wait for process output: {e}"))
-            })?;
+/// Sends `signal` to the process group led by `pid`.
+#[cfg(unix)]
+pub(crate) fn signal_tree(pid: u32, signal: &str) {
+    let _ = signal_tree_command(pid, signal).status();
+}
 
-            return Ok(process_result(&start, &output));
+/// Builds the `kill` command that sends `signal` to the process group led by `pid`.
+#[cfg(unix)]
+pub(crate) fn signal_tree_command(pid: u32, signal: &str) -> Command {
+    let mut cmd = Command::new("kill");
+    cmd.args(["-s", signal, "--", &format!("-{pid}")])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null());
+    cmd
+}
+
+/// Reads `pipe` on a new thread and sends each line, or how many bytes past
+/// `limit` were dropped, tagged with `stream`.
+///
+/// The pipe is drained until it closes, even once nobody receives the lines,
+/// so the process never blocks on a full pipe or dies of `SIGPIPE`.
+fn forward_lines<R: Read + Send + 'static>(
+    mut pipe: R,
+    stream: OutputStream,
+    limit: Option<usize>,
+    sender: mpsc::Sender<(OutputStream, Piped)>,
+) {
+    std::thread::spawn(move || {
+        let mut lines = LineSplitter::new(limit);
+        let mut buf = [0; READ_CHUNK_BYTES];
+        let mut receiving = true;
+        loop {
+            let read = match pipe.read(&mut buf) {
+                Ok(0) => break,
+                Ok(read) => read,
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+                Err(_) => break,
+            };
+            for piped in lines.feed(&buf[..read]) {
+                receiving = receiving && sender.send((stream, piped)).is_ok();
+            }
         }
+        for piped in lines.finish() {
+            receiving = receiving && sender.send((stream, piped)).is_ok();
+        }
+    });
+}
 
-        // Check if the timeout has been reached
-        if start.elapsed() >= timeout {
-            // Kill the process if timeout exceeded; ignore kill error
-            let _ = child
-                .kill()
-                .map_err(|e| AtentoError::Execution(format!("Failed to kill process: {e}")));
+/// What a pipe reader passes on: a line of output, or how many bytes past
+/// the output limit it dropped.
+pub(crate) enum Piped {
+    Line(String),
+    Dropped(usize),
+}
 
-            return Err(AtentoError::Timeout {
-                context: "Step execution timed out".to_string(),
-                timeout_secs,
-            });
+/// Splits the bytes read from a pipe into lines, keeping at most `limit`
+/// bytes of them, newlines included.
+///
+/// Bytes past the limit are only counted, so a runaway script cannot make the
+/// reader buffer more than the limit, not even in one long line.
+pub(crate) struct LineSplitter {
+    limit: usize,
+    /// Bytes of the lines passed on so far
+    kept: usize,
+    /// The line being read, up to the limit
+    line: Vec<u8>,
+    /// Bytes dropped since the last `Piped::Dropped`
+    dropped: usize,
+}
+
+impl LineSplitter {
+    pub(crate) fn new(limit: Option<usize>) -> Self {
+        Self {
+            limit: limit.unwrap_or(usize::MAX),
+            kept: 0,
+            line: Vec::new(),
+            dropped: 0,
+        }
+    }
+
+    /// Takes the next bytes read from the pipe, returning the lines they
+    /// completed and the bytes they dropped.
+    pub(crate) fn feed(&mut self, mut bytes: &[u8]) -> Vec<Piped> {
+        let mut piped = Vec::new();
+        while !bytes.is_empty() {
+            let newline = bytes.iter().position(|byte| *byte == b'\n');
+            let segment = &bytes[..newline.unwrap_or(bytes.len())];
+            let room = self
+                .limit
+                .saturating_sub(self.kept.saturating_add(self.line.len()));
+            let take = room.min(segment.len());
+            self.line.extend_from_slice(&segment[..take]);
+            self.dropped += segment.len() - take;
+            bytes = &bytes[segment.len()..];
+            if newline.is_some() {
+                bytes = &bytes[1..];
+                piped.extend(self.end_line(true));
+            }
+        }
+        piped.extend(self.take_dropped());
+        piped
+    }
+
+    /// Passes on the last line when the output does not end with a newline.
+    pub(crate) fn finish(&mut self) -> Vec<Piped> {
+        let mut piped: Vec<Piped> = if self.line.is_empty() {
+            Vec::new()
+        } else {
+            self.end_line(false).into_iter().collect()
+        };
+        piped.extend(self.take_dropped());
+        piped
+    }
+
+    /// Passes on the line read so far. Whether its newline still fits is left
+    /// to [`CollectedOutput::push`], unless nothing of the line fit at all.
+    fn end_line(&mut self, newline: bool) -> Option<Piped> {
+        let length = self.kept.saturating_add(self.line.len());
+        if self.line.is_empty() && length >= self.limit {
+            self.dropped += usize::from(newline);
+            return None;
         }
+        self.kept = length.saturating_add(usize::from(newline));
+        let line = std::mem::take(&mut self.line);
+        let line = line.strip_suffix(b"\r").unwrap_or(&line);
+        Some(Piped::Line(String::from_utf8_lossy(line).into_owned()))
+    }
 
-        // Sleep for a short duration before checking again
-        std::thread::sleep(Duration::from_millis(100)); // Adjust the sleep time as needed
+    fn take_dropped(&mut self) -> Option<Piped> {
+        (self.dropped > 0).then(|| Piped::Dropped(std::mem::take(&mut self.dropped)))
     }
 }
 
-fn process_result(start: &Instant, output: &std::process::Output) -> RunnerResult {
+fn is_stderr_noise(line: &str) -> bool {
+    STDERR_FILTER_PATTERNS.iter().any(|pat| line.contains(pat))
+}
+
+fn build_result(start: &Instant, exit_code: i32, stdout: &str, stderr: &str) -> RunnerResult {
     let elapsed = start.elapsed();
-    let stdout = String::from_utf8_lossy(&output.stdout).to_string();
-    let exit_code = output.status.code().unwrap_or(-1);
 
     // Filter noise from stderr
-    let stderr = {
-        let raw = String::from_utf8_lossy(&output.stderr);
-        raw.lines()
-            .filter(|line| !STDERR_FILTER_PATTERNS.iter().any(|pat| line.contains(pat)))
-            .collect::<Vec<_>>()
-            .join("\n")
-    };
+    let stderr = stderr
+        .lines()
+        .filter(|line| !is_stderr_noise(line))
+        .collect::<Vec<_>>()
+        .join("\n");
 
     RunnerResult {
         exit_code,
         stdout: Some(stdout.trim().to_string()).filter(|s| !s.is_empty()),
         stderr: Some(stderr.trim().to_string()).filter(|s| !s.is_empty()),
         duration_ms: elapsed.as_millis(),
+        combined: None,
+        timed_out: false,
+        resolved_command: None,
+        stdout_truncated: false,
+        stderr_truncated: false,
+        force_killed: false,
+        resource_usage: None,
     }
 }
@@ -0,0 +1,31 @@
+use crate::chain::ChainHelper;
+use crate::interpreter::default_interpreters;
+use schemars::{Schema, SchemaGenerator, json_schema};
+
+/// Returns a JSON Schema (draft 2020-12) of the chain YAML format, e.g. for
+/// autocompletion and checking of chain files in an editor.
+///
+/// The schema is generated from the types chains are parsed into, so it
+/// describes every chain, step, parameter, input, output and result field
+/// with its default value. Unknown keys are not allowed, as when parsing a
+/// chain without `lenient: true`, and interpreter keys list the built-in
+/// interpreters while still accepting custom ones.
+#[must_use]
+pub fn chain_schema() -> serde_json::Value {
+    schemars::schema_for!(ChainHelper).to_value()
+}
+
+/// Schema of an interpreter key: one of the built-in interpreters or the key
+/// of an interpreter declared under `interpreters`.
+pub(crate) fn interpreter_key(_generator: &mut SchemaGenerator) -> Schema {
+    let keys: Vec<String> = default_interpreters()
+        .into_iter()
+        .map(|(key, _)| key)
+        .collect();
+    json_schema!({
+        "anyOf": [
+            { "enum": keys },
+            { "type": "string" }
+        ]
+    })
+}
@@ -1,64 +1,537 @@
+use crate::artifact::{Artifact, ArtifactResult};
+#[cfg(feature = "async")]
+use crate::async_executor::AsyncCommandExecutor;
+use crate::chain::RUN_ID_ENV;
+#[cfg(feature = "exec")]
+use crate::data_type::rfc3339_timestamp;
+use crate::data_type::{DataType, to_json_value, to_string_value, to_text};
+use crate::env::EnvValue;
 use crate::errors::{AtentoError, Result};
-use crate::executor::CommandExecutor;
+#[cfg(feature = "exec")]
+use crate::executor::{CommandExecutor, DockerExecutor, ExecutionRequest, ExecutionResult};
 use crate::input::Input;
-use crate::interpreter::Interpreter;
-use crate::output::Output;
+use crate::interpreter::{Interpreter, InterpreterArgs};
+use crate::output::{Output, OutputSource, parse_json_path, resolve_json_path};
+#[cfg(feature = "exec")]
+use crate::process::KillPolicy;
+use crate::process::{ResourceUsage, TimeoutSignal};
+use crate::retry::{AttemptSummary, RetryPolicy};
+use indexmap::IndexMap;
 use regex::Regex;
 use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use std::borrow::Cow;
 use std::collections::{HashMap, HashSet};
+#[cfg(feature = "exec")]
+use std::fmt::Write;
+#[cfg(feature = "exec")]
+use std::ops::ControlFlow;
+use std::ops::Range;
+use std::path::Path;
+use std::time::Duration;
+#[cfg(feature = "exec")]
+use std::time::{Instant, SystemTime};
 
-const INPUT_PLACEHOLDER_PATTERN: &str = r"\{\{\s*inputs\.(\w+)\s*\}\}";
+/// `{{ inputs.name }}`, optionally with a literal fallback: `{{ inputs.name | default("x") }}`
+const INPUT_PLACEHOLDER_PATTERN: &str =
+    r#"\{\{\s*inputs\.(\w+)\s*(?:\|\s*default\(\s*"([^"]*)"\s*\)\s*)?\}\}"#;
+const PARAMETER_PLACEHOLDER_PATTERN: &str = r"\{\{\s*parameters\.(\w+)\s*\}\}";
+/// `{{ steps.<id>.outputs.<name> }}` or any other key a step reference can name,
+/// such as `{{ steps.<id>.exit_code }}`, or `{{ prev.stdout }}`, `{{ prev.stderr }}`
+/// and `{{ prev.exit_code }}` for the step declared just before
+const STEP_PLACEHOLDER_PATTERN: &str = r"\{\{\s*(steps\.[\w-]+(?:\[[^\]\s{}]+\])?(?:\.[\w-]+){1,2}|prev\.(?:stdout|stderr|exit_code))\s*\}\}";
+/// Prefix of the placeholders that name the step declared just before
+pub(crate) const PREV_STEP_PREFIX: &str = "prev.";
+/// `{{ raw }}...{{ endraw }}` blocks are copied verbatim, without placeholder substitution
+const RAW_BLOCK_PATTERN: &str = r"(?s)\{\{\s*raw\s*\}\}(.*?)\{\{\s*endraw\s*\}\}";
+pub(crate) const RAW_BLOCK_HINT: &str =
+    "wrap literal '{{ }}' text in '{{ raw }}...{{ endraw }}' to keep it as is";
 const DEFAULT_STEP_TIMEOUT: u64 = 60;
+pub(crate) const SECRET_MASK: &str = "***";
+/// Name of the synthetic input bound to the current item of a `foreach` step
+pub(crate) const FOREACH_ITEM_INPUT: &str = "item";
+const FOREACH_ITEM_PLACEHOLDER: &str = "<item>";
+/// Name of the synthetic input bound to the resolved value of a `stdin` given
+/// as an inline value or reference
+pub(crate) const STDIN_INPUT: &str = "stdin";
 
 // Helper function to provide the custom default for serde
 fn default_step_timeout() -> u64 {
     DEFAULT_STEP_TIMEOUT
 }
 
-#[derive(Debug, Deserialize)]
+fn default_success_exit_codes() -> Vec<i32> {
+    vec![0]
+}
+
+/// How a step captures the output streams of its script.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum Capture {
+    /// Record stdout and stderr separately
+    #[default]
+    Separate,
+    /// Also record both streams merged in the order they were written
+    Combined,
+}
+
+/// How values substituted into a step's script are quoted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(rename_all = "lowercase")]
+pub enum Quote {
+    /// Insert values as they are
+    #[default]
+    None,
+    /// Wrap values in POSIX shell single quotes, for `.sh` interpreters such as bash
+    Shell,
+    /// Wrap values in PowerShell single quotes, for `.ps1` interpreters; the
+    /// typographic quotes PowerShell also accepts are escaped too
+    Powershell,
+}
+
+impl Quote {
+    /// Returns `value` quoted so the interpreter reads it back unchanged.
+    #[must_use]
+    pub fn apply(self, value: &str) -> String {
+        match self {
+            Quote::None => value.to_string(),
+            Quote::Shell => format!("'{}'", value.replace('\'', r"'\''")),
+            Quote::Powershell => {
+                let mut quoted = String::with_capacity(value.len() + 2);
+                quoted.push('\'');
+                for c in value.chars() {
+                    // PowerShell ends a single-quoted string at any of these
+                    if matches!(c, '\'' | '\u{2018}'..='\u{201B}') {
+                        quoted.push(c);
+                    }
+                    quoted.push(c);
+                }
+                quoted.push('\'');
+                quoted
+            }
+        }
+    }
+
+    /// Script extension of the interpreters the quoting is meant for.
+    pub(crate) fn extension(self) -> Option<&'static str> {
+        match self {
+            Quote::None => None,
+            Quote::Shell => Some(".sh"),
+            Quote::Powershell => Some(".ps1"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[cfg_attr(
+    feature = "schema",
+    derive(schemars::JsonSchema),
+    schemars(deny_unknown_fields)
+)]
 pub struct Step {
     pub name: Option<String>,
     #[serde(default = "default_step_timeout")]
     pub timeout: u64,
     #[serde(default)]
     pub inputs: HashMap<String, Input>,
-    #[serde(rename = "type")]
-    pub interpreter: String,
+    /// Interpreter key; steps without one use the chain's `default_interpreter`
+    #[cfg_attr(
+        feature = "schema",
+        schemars(schema_with = "crate::schema::interpreter_key")
+    )]
+    #[serde(default, rename = "type")]
+    pub interpreter: Option<String>,
+    /// Left out when the step uses a `template` or sets `script_file`
+    #[serde(default)]
     pub script: String,
+    /// Path of a file holding the script, relative to the chain file; loaded
+    /// into `script` by [`Chain::resolve_script_files`](crate::Chain::resolve_script_files)
+    #[serde(default)]
+    pub script_file: Option<String>,
     #[serde(default)]
     pub outputs: HashMap<String, Output>,
+    /// Files the step produces, collected after it ran successfully
+    #[serde(default)]
+    pub artifacts: HashMap<String, Artifact>,
+    #[serde(default)]
+    pub env: HashMap<String, EnvValue>,
+    #[serde(default)]
+    pub retry: Option<RetryPolicy>,
+    /// Shorthand for a fixed-delay retry policy with `retries + 1` attempts
+    #[serde(default)]
+    pub retries: u32,
+    #[serde(default)]
+    pub retry_delay_ms: u64,
+    /// Working directory for the script; supports `{{ inputs.x }}` placeholders
+    #[serde(default)]
+    pub workdir: Option<String>,
+    /// Keep running the chain when this step fails
+    #[serde(default)]
+    pub continue_on_error: bool,
+    /// Key of a step that runs right after this step fails, for cleanup or
+    /// recovery; that step then only runs as such a handler
+    #[serde(default)]
+    pub on_failure: Option<String>,
+    /// Exit codes that count as success; also accepted as `success_codes`
+    #[serde(default = "default_success_exit_codes", alias = "success_codes")]
+    pub success_exit_codes: Vec<i32>,
+    /// Treat any exit code as success
+    #[serde(default)]
+    pub allow_nonzero_exit: bool,
+    /// Reference to a newline- or comma-separated list; the step runs once per
+    /// item with the current item bound to `{{ inputs.item }}`
+    #[serde(default)]
+    pub foreach: Option<String>,
+    /// `combined` additionally records stdout and stderr interleaved in `combined_output`
+    #[serde(default)]
+    pub capture: Capture,
+    /// Docker image to run the script in, using [`DockerExecutor`] instead of
+    /// the chain's executor
+    #[serde(default)]
+    pub image: Option<String>,
+    /// Extra arguments for this step's interpreter, appended to or replacing its `args`
+    #[serde(default)]
+    pub interpreter_args: Option<InterpreterArgs>,
+    /// Text written to the script's standard input: a template like `script`,
+    /// or an inline value or reference resolved like a step input
+    #[serde(default)]
+    pub stdin: Option<EnvValue>,
+    /// Overrides the chain's `max_output_bytes` for this step; `0` keeps all output
+    #[serde(default)]
+    pub max_output_bytes: Option<u64>,
+    /// Overrides the chain's `timeout_signal` for this step
+    #[serde(default)]
+    pub timeout_signal: Option<TimeoutSignal>,
+    /// Overrides the chain's `timeout_grace_ms` for this step
+    #[serde(default)]
+    pub timeout_grace_ms: Option<u64>,
+    /// Quoting applied to every value substituted into the script
+    #[serde(default)]
+    pub quote: Quote,
+    /// Replaces a step with the same id from an included chain file
+    #[serde(default, rename = "override")]
+    pub override_: bool,
+    /// Key of a chain template this step is instantiated from; only its
+    /// `name` and `inputs` are taken from the step itself
+    #[serde(default)]
+    pub template: Option<String>,
+    /// Values the step is expanded over: one step per combination, named
+    /// `id[value,...]`, with each value bound to the input of its key
+    #[serde(default)]
+    #[cfg_attr(
+        feature = "schema",
+        schemars(with = "IndexMap<String, Vec<serde_json::Value>>")
+    )]
+    pub matrix: IndexMap<String, Vec<serde_yaml::Value>>,
+    /// Keys of steps that must run before this one; their outputs may be
+    /// referenced even when they are declared later
+    #[serde(default)]
+    pub needs: Vec<String>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct StepResult {
     pub name: Option<String>,
     pub duration_ms: u128,
     pub exit_code: i32,
+    /// Number of times the script was executed
+    #[serde(
+        default = "default_attempts",
+        skip_serializing_if = "is_single_attempt"
+    )]
+    pub attempts: u32,
+    /// Per-attempt summaries, recorded only when the step was retried
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub attempt_history: Vec<AttemptSummary>,
     #[serde(default, skip_serializing_if = "HashMap::is_empty")]
     pub inputs: HashMap<String, String>,
+    /// Extracted outputs as the text passed to later steps
     #[serde(default, skip_serializing_if = "HashMap::is_empty")]
     pub outputs: HashMap<String, String>,
+    /// Extracted outputs, converted to JSON values of their declared data types
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub typed_outputs: HashMap<String, JsonValue>,
+    /// Collected artifact files by name
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub artifacts: HashMap<String, ArtifactResult>,
+    /// Names of the environment variables set for the step (values are never
+    /// recorded), apart from `ATENTO_RUN_ID`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub env: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub script: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub stdout: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub stderr: Option<String>,
+    /// Stdout went past `max_output_bytes`; only its beginning was kept, followed
+    /// by a `...[truncated N bytes]` line
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub stdout_truncated: bool,
+    /// Stderr went past `max_output_bytes`, kept like `stdout`
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub stderr_truncated: bool,
+    /// Stdout or stderr went past `max_output_bytes`
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub truncated: bool,
+    /// Stdout and stderr merged in write order, recorded with `capture: combined`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub combined_output: Option<String>,
+    /// The interpreter command that ran the script on this host, which is one
+    /// of the interpreter's `fallbacks` when its `command` was not found
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resolved_command: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub error: Option<AtentoError>,
+    /// When the step started running, as an RFC 3339 UTC timestamp with
+    /// milliseconds; left out for steps that did not run
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub started_at: Option<String>,
+    /// When the step finished, including retries and `foreach` items
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub finished_at: Option<String>,
+    /// Per-item results of a `foreach` step
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub iterations: Vec<StepResult>,
+    /// CPU time and peak memory of the script, recorded with the
+    /// `resource-metrics` feature on Unix; summed over the items of a
+    /// `foreach` step, with the largest peak
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resource_usage: Option<ResourceUsage>,
 }
 
-impl Step {
-    /// Creates a new Step with basic defaults for testing purposes
-    #[cfg(test)]
-    #[must_use]
-    pub fn new(interpreter: &str) -> Self {
+// Everything needed to execute one attempt of a step
+#[cfg(feature = "exec")]
+#[derive(Clone, Copy)]
+struct Invocation<'a> {
+    script: &'a str,
+    interpreter: &'a Interpreter,
+    inputs: &'a HashMap<String, String>,
+    env: &'a HashMap<String, String>,
+    workdir: Option<&'a str>,
+    stdin: Option<&'a str>,
+    max_output_bytes: Option<u64>,
+    kill: &'a KillPolicy,
+}
+
+#[cfg(feature = "exec")]
+impl<'a> Invocation<'a> {
+    /// Returns the request an executor runs this invocation with.
+    fn request(&self, timeout: u64) -> ExecutionRequest<'a> {
+        ExecutionRequest {
+            script: self.script,
+            interpreter: self.interpreter,
+            env: self.env,
+            workdir: self.workdir,
+            stdin: self.stdin,
+            max_output_bytes: self.max_output_bytes,
+            timeout,
+            kill: self.kill,
+        }
+    }
+}
+
+// A step's script and settings rendered for one run
+#[cfg(feature = "exec")]
+struct Rendered<'a> {
+    script: String,
+    workdir: Option<String>,
+    stdin: Option<String>,
+    interpreter: Cow<'a, Interpreter>,
+    inputs: Cow<'a, HashMap<String, String>>,
+}
+
+#[cfg(feature = "exec")]
+impl Rendered<'_> {
+    fn invocation<'a>(
+        &'a self,
+        env: &'a HashMap<String, String>,
+        max_output_bytes: Option<u64>,
+        kill: &'a KillPolicy,
+    ) -> Invocation<'a> {
+        Invocation {
+            script: &self.script,
+            interpreter: &self.interpreter,
+            inputs: &self.inputs,
+            env,
+            workdir: self.workdir.as_deref(),
+            stdin: self.stdin.as_deref(),
+            max_output_bytes,
+            kill,
+        }
+    }
+}
+
+// Retry bookkeeping of one run of a step, shared by `Step::run` and its async
+// counterpart
+#[cfg(feature = "exec")]
+struct Attempts {
+    policy: RetryPolicy,
+    time_left: u64,
+    start_time: Instant,
+    started_at: SystemTime,
+    history: Vec<AttemptSummary>,
+    attempt: u32,
+}
+
+#[cfg(feature = "exec")]
+impl Attempts {
+    fn start(policy: RetryPolicy, time_left: u64) -> Self {
+        #[cfg(feature = "tracing")]
+        tracing::info!(timeout_secs = time_left, "step started");
+        Self {
+            policy,
+            time_left,
+            start_time: Instant::now(),
+            started_at: SystemTime::now(),
+            history: Vec::new(),
+            attempt: 1,
+        }
+    }
+
+    /// The chain time left for the next attempt, at least a second, or `0`
+    /// when the chain has no timeout.
+    fn remaining(&self) -> u64 {
+        if self.time_left > 0 {
+            self.time_left
+                .saturating_sub(self.start_time.elapsed().as_secs())
+                .max(1)
+        } else {
+            0
+        }
+    }
+
+    /// Records the result of the attempt that just finished. Breaks with the
+    /// step result when there is no retry left, and continues with the delay
+    /// before the next attempt otherwise.
+    fn record(&mut self, mut result: StepResult) -> ControlFlow<StepResult, Duration> {
+        self.history.push(AttemptSummary {
+            exit_code: result.exit_code,
+            duration_ms: result.duration_ms,
+            error: result.error.as_ref().map(ToString::to_string),
+        });
+
+        let attempt = self.attempt;
+        let failed = result.error.is_some();
+        let cancelled = matches!(result.error, Some(AtentoError::Cancelled { .. }));
+        let delay = self.policy.delay_after(attempt);
+        let out_of_time = self.time_left > 0
+            && self.start_time.elapsed() + delay >= Duration::from_secs(self.time_left);
+
+        if !failed || cancelled || attempt >= self.policy.attempts || out_of_time {
+            result.duration_ms = self.start_time.elapsed().as_millis();
+            result.started_at = Some(rfc3339_timestamp(self.started_at));
+            result.finished_at = Some(rfc3339_timestamp(SystemTime::now()));
+            result.attempts = attempt;
+            if self.history.len() > 1 {
+                result.attempt_history = std::mem::take(&mut self.history);
+            }
+            #[cfg(feature = "tracing")]
+            {
+                tracing::info!(
+                    duration_ms = result.duration_ms,
+                    exit_code = result.exit_code,
+                    attempts = result.attempts,
+                    failed,
+                    "step finished"
+                );
+                if let Some(e) = &result.error {
+                    tracing::error!(
+                        error = %e,
+                        timed_out = matches!(e, AtentoError::Timeout { .. }),
+                        "step failed"
+                    );
+                }
+            }
+            return ControlFlow::Break(result);
+        }
+
+        #[cfg(feature = "tracing")]
+        tracing::warn!(
+            attempt,
+            delay_ms = delay.as_millis(),
+            error = result.error.as_ref().map(ToString::to_string),
+            "retrying step"
+        );
+
+        self.attempt += 1;
+        ControlFlow::Continue(delay)
+    }
+}
+
+impl Default for Step {
+    fn default() -> Self {
         Step {
             name: None,
             timeout: default_step_timeout(),
             inputs: HashMap::new(),
-            interpreter: interpreter.to_string(),
+            interpreter: None,
             script: String::new(),
+            script_file: None,
             outputs: HashMap::new(),
+            artifacts: HashMap::new(),
+            env: HashMap::new(),
+            retry: None,
+            retries: 0,
+            retry_delay_ms: 0,
+            workdir: None,
+            continue_on_error: false,
+            on_failure: None,
+            success_exit_codes: default_success_exit_codes(),
+            allow_nonzero_exit: false,
+            foreach: None,
+            capture: Capture::default(),
+            image: None,
+            interpreter_args: None,
+            stdin: None,
+            max_output_bytes: None,
+            quote: Quote::None,
+            override_: false,
+            template: None,
+            matrix: IndexMap::new(),
+            needs: Vec::new(),
+            timeout_signal: None,
+            timeout_grace_ms: None,
+        }
+    }
+}
+
+impl Step {
+    /// Creates a new Step with basic defaults for testing purposes
+    #[cfg(test)]
+    #[must_use]
+    pub fn new(interpreter: &str) -> Self {
+        Step {
+            interpreter: Some(interpreter.to_string()),
+            ..Step::default()
+        }
+    }
+
+    /// Loads `script_file`, resolved against `base_dir`, into `script`;
+    /// `owner` names the step or template in errors, e.g. `Step 'build'`.
+    ///
+    /// # Errors
+    /// Returns a `Validation` error if the step also sets `script`, and an
+    /// `Io` error naming the script file if it cannot be read.
+    pub(crate) fn load_script_file(&mut self, owner: &str, base_dir: &Path) -> Result<()> {
+        let Some(file) = &self.script_file else {
+            return Ok(());
+        };
+        if !self.script.is_empty() {
+            return Err(AtentoError::Validation(format!(
+                "{owner} sets both 'script' and 'script_file'"
+            )));
         }
+
+        let path = base_dir.join(file);
+        self.script = std::fs::read_to_string(&path).map_err(|e| AtentoError::Io {
+            path: path.display().to_string(),
+            source: e,
+        })?;
+        self.script_file = None;
+        Ok(())
     }
 
     /// Validates the step configuration.
@@ -66,50 +539,293 @@ impl Step {
     /// # Errors
     /// Returns validation errors for unused inputs, undeclared inputs, or invalid output patterns.
     pub fn validate(&self, id: &str) -> Result<()> {
+        let mut warnings = Vec::new();
+        self.validate_with_warnings(id, &mut warnings)?;
+        match warnings.into_iter().next() {
+            Some(warning) => Err(AtentoError::Validation(warning)),
+            None => Ok(()),
+        }
+    }
+
+    /// Validates the step like [`Step::validate`], but pushes findings that do
+    /// not stop the step from running, such as unused inputs, onto `warnings`.
+    pub(crate) fn validate_with_warnings(
+        &self,
+        id: &str,
+        warnings: &mut Vec<String>,
+    ) -> Result<()> {
         let step_name = self.name.as_deref().unwrap_or(id);
+        self.validate_definition(step_name, warnings)?;
+        for (out_name, out) in &self.outputs {
+            Self::validate_output(out_name, out, step_name)?;
+            Self::validate_output_pattern(out_name, out, step_name)?;
+        }
+        Ok(())
+    }
 
+    /// Validates everything about the step but its outputs, pushing warnings
+    /// about unused inputs onto `warnings`.
+    pub(crate) fn validate_definition(
+        &self,
+        step_name: &str,
+        warnings: &mut Vec<String>,
+    ) -> Result<()> {
         #[allow(clippy::expect_used)]
         let input_ref_regex = Regex::new(INPUT_PLACEHOLDER_PATTERN)
             .expect("Input placeholder regex pattern is valid");
 
         let mut used_inputs: HashSet<String> = HashSet::new();
 
-        for cap in input_ref_regex.captures_iter(&self.script) {
+        if self.foreach.is_some() && self.inputs.contains_key(FOREACH_ITEM_INPUT) {
+            return Err(AtentoError::Validation(format!(
+                "Step '{step_name}' declares input '{FOREACH_ITEM_INPUT}', which is reserved for the 'foreach' item"
+            )));
+        }
+
+        if matches!(self.stdin, Some(EnvValue::Input(_))) && self.inputs.contains_key(STDIN_INPUT) {
+            return Err(AtentoError::Validation(format!(
+                "Step '{step_name}' declares input '{STDIN_INPUT}', which is reserved for its 'stdin' value"
+            )));
+        }
+
+        self.validate_execution_settings(step_name)?;
+
+        for template in self.templates() {
+            for cap in input_ref_regex.captures_iter(&template) {
+                if cap
+                    .get(2)
+                    .is_some_and(|default| default.as_str().contains("{{"))
+                {
+                    return Err(AtentoError::Validation(format!(
+                        "Step '{step_name}' input '{}' has a default containing a placeholder; defaults must be literal strings",
+                        &cap[1]
+                    )));
+                }
+            }
+        }
+
+        for cap in input_ref_regex.captures_iter(&strip_raw_blocks(&self.script)) {
             let ref_key = &cap[1];
-            if !self.inputs.contains_key(ref_key) {
+            if !self.declares_input(ref_key) && cap.get(2).is_none() {
                 return Err(AtentoError::Validation(format!(
-                    "Step '{step_name}' script references input '{ref_key}' that is not declared"
+                    "Step '{step_name}' script references input '{ref_key}' that is not declared; {RAW_BLOCK_HINT}"
                 )));
             }
             used_inputs.insert(ref_key.to_string());
         }
 
+        for (setting, template) in self.setting_templates() {
+            for cap in input_ref_regex.captures_iter(&strip_raw_blocks(template)) {
+                let ref_key = &cap[1];
+                if !self.declares_input(ref_key) && cap.get(2).is_none() {
+                    return Err(AtentoError::Validation(format!(
+                        "Step '{step_name}' {setting} references input '{ref_key}' that is not declared; {RAW_BLOCK_HINT}"
+                    )));
+                }
+                used_inputs.insert(ref_key.to_string());
+            }
+        }
+
+        for (env_name, template) in self.env_templates() {
+            for cap in input_ref_regex.captures_iter(&strip_raw_blocks(template)) {
+                let ref_key = &cap[1];
+                if !self.inputs.contains_key(ref_key) && cap.get(2).is_none() {
+                    return Err(AtentoError::Validation(format!(
+                        "Step '{step_name}' env var '{env_name}' references input '{ref_key}' that is not declared; {RAW_BLOCK_HINT}"
+                    )));
+                }
+                used_inputs.insert(ref_key.to_string());
+            }
+        }
+
         for input_name in self.inputs.keys() {
             if !used_inputs.contains(input_name) {
-                return Err(AtentoError::Validation(format!(
+                warnings.push(format!(
                     "Step '{step_name}' has input '{input_name}' that is declared but never used in the script"
-                )));
+                ));
             }
         }
 
-        for (out_name, out) in &self.outputs {
-            if out.pattern.trim().is_empty() {
+        if self.success_exit_codes.is_empty() {
+            return Err(AtentoError::Validation(format!(
+                "Step '{step_name}' has an empty 'success_exit_codes' list"
+            )));
+        }
+
+        if self.retry.is_some() && (self.retries > 0 || self.retry_delay_ms > 0) {
+            return Err(AtentoError::Validation(format!(
+                "Step '{step_name}' cannot combine a 'retry' block with 'retries'/'retry_delay_ms'"
+            )));
+        }
+
+        let retry = self.retry_policy();
+        if retry.attempts == 0 {
+            return Err(AtentoError::Validation(format!(
+                "Step '{step_name}' retry attempts must be at least 1"
+            )));
+        }
+
+        if self.timeout > 0 && retry.base_delay() > Duration::from_secs(self.timeout) {
+            return Err(AtentoError::Validation(format!(
+                "Step '{step_name}' retry delay of {}ms exceeds the step timeout of {}s",
+                retry.base_delay().as_millis(),
+                self.timeout
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Returns whether the script and workdir may reference the input `name`:
+    /// declared inputs, plus the `item` of a `foreach` step.
+    fn declares_input(&self, name: &str) -> bool {
+        self.inputs.contains_key(name) || (self.foreach.is_some() && name == FOREACH_ITEM_INPUT)
+    }
+
+    /// Checks that a `key` output sets no other extraction mode and names a
+    /// key that can appear before the `=` of a line.
+    fn validate_output_key(out_name: &str, out: &Output, key: &str, step_name: &str) -> Result<()> {
+        if !out.pattern.is_empty() || out.json_path.is_some() {
+            return Err(AtentoError::Validation(format!(
+                "Output '{out_name}' in step '{step_name}' cannot set 'key' together with 'pattern' or 'json_path'"
+            )));
+        }
+
+        if out.group.is_some() {
+            return Err(AtentoError::Validation(format!(
+                "Output '{out_name}' in step '{step_name}' sets 'group', which requires a regex 'pattern'"
+            )));
+        }
+
+        if key.trim().is_empty() || key.contains(['=', '\n', '\r']) {
+            return Err(AtentoError::Validation(format!(
+                "Output '{out_name}' in step '{step_name}' has invalid key '{key}'; keys must be non-empty and cannot contain '=' or line breaks"
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Checks that the output `out_name` declares exactly one extraction mode
+    /// and that its JSON path is well formed; see
+    /// [`Step::validate_output_pattern`] for its regex `pattern`.
+    pub(crate) fn validate_output(out_name: &str, out: &Output, step_name: &str) -> Result<()> {
+        if out.multiple && (out.json_path.is_some() || out.source == OutputSource::ExitCode) {
+            return Err(AtentoError::Validation(format!(
+                "Output '{out_name}' in step '{step_name}' sets 'multiple', which requires a regex 'pattern'"
+            )));
+        }
+
+        if out.optional && (out.json_path.is_some() || out.source == OutputSource::ExitCode) {
+            return Err(AtentoError::Validation(format!(
+                "Output '{out_name}' in step '{step_name}' sets 'optional', which requires a regex 'pattern'"
+            )));
+        }
+
+        if let Some(default) = &out.default {
+            if !out.optional || out.multiple {
                 return Err(AtentoError::Validation(format!(
-                    "Output '{out_name}' in step '{step_name}' has empty capture pattern"
+                    "Output '{out_name}' in step '{step_name}' sets 'default', which requires 'optional' without 'multiple'"
                 )));
             }
 
-            Regex::new(&out.pattern).map_err(|e| {
+            default_output(out_name, out, default).map_err(|e| {
                 AtentoError::Validation(format!(
-                    "Output '{}' in step '{}' has invalid regex pattern '{}': {}",
-                    out_name, step_name, out.pattern, e
+                    "Output '{out_name}' in step '{step_name}' has an invalid default: {e}"
                 ))
             })?;
         }
 
+        if out.source == OutputSource::ExitCode {
+            if !out.pattern.is_empty() || out.json_path.is_some() || out.key.is_some() {
+                return Err(AtentoError::Validation(format!(
+                    "Output '{out_name}' in step '{step_name}' captures the exit code and cannot set 'pattern', 'json_path' or 'key'"
+                )));
+            }
+
+            return Ok(());
+        }
+
+        if let Some(key) = &out.key {
+            return Self::validate_output_key(out_name, out, key, step_name);
+        }
+
+        if let Some(path) = &out.json_path {
+            if !out.pattern.is_empty() {
+                return Err(AtentoError::Validation(format!(
+                    "Output '{out_name}' in step '{step_name}' cannot set both 'pattern' and 'json_path'"
+                )));
+            }
+
+            if parse_json_path(path).is_none() {
+                return Err(AtentoError::Validation(format!(
+                    "Output '{out_name}' in step '{step_name}' has invalid JSON path '{path}'"
+                )));
+            }
+
+            return Ok(());
+        }
+
+        if out.pattern.trim().is_empty() {
+            return Err(AtentoError::Validation(format!(
+                "Output '{out_name}' in step '{step_name}' has empty capture pattern and no 'json_path' or 'key'"
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Checks that the regex `pattern` of the output `out_name`, when it is
+    /// captured by one, compiles and defines the capture `group` it names.
+    pub(crate) fn validate_output_pattern(
+        out_name: &str,
+        out: &Output,
+        step_name: &str,
+    ) -> Result<()> {
+        if out.source == OutputSource::ExitCode
+            || out.key.is_some()
+            || out.json_path.is_some()
+            || out.pattern.trim().is_empty()
+        {
+            return Ok(());
+        }
+
+        let re = out.regex().map_err(|e| {
+            AtentoError::Validation(format!(
+                "Output '{}' in step '{}' has invalid regex pattern '{}': {}",
+                out_name, step_name, out.pattern, e
+            ))
+        })?;
+
+        if let Some(group) = &out.group
+            && !has_named_group(&re, group)
+        {
+            return Err(AtentoError::Validation(format!(
+                "Output '{}' in step '{}' references capture group '{}' not defined in pattern '{}'",
+                out_name, step_name, group, out.pattern
+            )));
+        }
+
         Ok(())
     }
 
+    /// Returns the effective retry policy, built from either the `retry` block
+    /// or the `retries`/`retry_delay_ms` shorthand.
+    #[must_use]
+    pub fn retry_policy(&self) -> RetryPolicy {
+        self.retry.clone().unwrap_or_else(|| RetryPolicy {
+            attempts: self.retries.saturating_add(1),
+            delay_ms: self.retry_delay_ms,
+            ..RetryPolicy::default()
+        })
+    }
+
+    /// Returns whether the given exit code counts as success for this step.
+    #[must_use]
+    pub fn is_success_exit_code(&self, exit_code: i32) -> bool {
+        self.allow_nonzero_exit || self.success_exit_codes.contains(&exit_code)
+    }
+
     /// Calculates the effective timeout for this step.
     #[must_use]
     pub fn calculate_timeout(&self, time_left: u64) -> u64 {
@@ -126,106 +842,708 @@ impl Step {
     /// Builds the script with input substitution.
     #[must_use]
     pub fn build_script(&self, inputs: &HashMap<String, String>) -> String {
+        self.render_script(inputs, &HashMap::new())
+    }
+
+    /// Builds the script substituting both `{{ inputs.x }}` and
+    /// `{{ parameters.x }}` placeholders, quoted as the step's `quote` says.
+    /// Unknown placeholders are left as is.
+    #[must_use]
+    pub fn render_script(
+        &self,
+        inputs: &HashMap<String, String>,
+        parameters: &HashMap<String, String>,
+    ) -> String {
         if self.script.is_empty() {
             return String::new();
         }
 
-        if inputs.is_empty() {
-            return self.script.clone();
+        render_quoted(&self.script, inputs, parameters, self.quote)
+    }
+
+    /// Returns the names of the parameters referenced as `{{ parameters.x }}`
+    /// in the script, workdir, stdin, artifact paths and the step's env templates.
+    #[must_use]
+    pub fn parameter_refs(&self) -> Vec<String> {
+        let mut refs = parameter_placeholders(&self.script);
+        for (_, template) in self.setting_templates() {
+            refs.extend(parameter_placeholders(template));
+        }
+        for (_, template) in self.env_templates() {
+            refs.extend(parameter_placeholders(template));
+        }
+        refs
+    }
+
+    /// Returns the step references, such as `steps.build.outputs.version`, used
+    /// as `{{ ... }}` placeholders in the same places as [`Step::parameter_refs`].
+    #[must_use]
+    pub fn step_refs(&self) -> Vec<String> {
+        let mut refs = step_placeholders(&self.script);
+        for (_, template) in self.setting_templates() {
+            refs.extend(step_placeholders(template));
+        }
+        for (_, template) in self.env_templates() {
+            refs.extend(step_placeholders(template));
+        }
+        refs.sort();
+        refs.dedup();
+        refs
+    }
+
+    /// Looks up the step's artifact files after it ran, with input and parameter
+    /// substitution in their paths, which are relative to the step's workdir.
+    ///
+    /// # Errors
+    /// Returns an `Execution` error for a missing, non-`optional` artifact, an
+    /// unreadable one, or one outside `root` when set.
+    pub fn collect_artifacts(
+        &self,
+        inputs: &HashMap<String, String>,
+        parameters: &HashMap<String, String>,
+        root: Option<&str>,
+    ) -> Result<HashMap<String, ArtifactResult>> {
+        let workdir = self.render_workdir(inputs, parameters);
+        let mut names: Vec<&String> = self.artifacts.keys().collect();
+        names.sort();
+        let mut collected = HashMap::new();
+        for name in names {
+            let artifact = &self.artifacts[name];
+            let path = render_template(&artifact.path, inputs, parameters);
+            if let Some(found) = artifact.collect(name, &path, workdir.as_deref(), root)? {
+                collected.insert(name.clone(), found);
+            }
+        }
+        Ok(collected)
+    }
+
+    /// Renders the working directory, if any, with input and parameter substitution.
+    #[must_use]
+    pub fn render_workdir(
+        &self,
+        inputs: &HashMap<String, String>,
+        parameters: &HashMap<String, String>,
+    ) -> Option<String> {
+        self.workdir
+            .as_deref()
+            .map(|dir| render_template(dir, inputs, parameters))
+    }
+
+    /// Returns the text written to the script's stdin, if any: the rendered
+    /// template, or the value bound to the synthetic `stdin` input for an
+    /// inline value or reference.
+    #[must_use]
+    pub fn render_stdin(
+        &self,
+        inputs: &HashMap<String, String>,
+        parameters: &HashMap<String, String>,
+    ) -> Option<String> {
+        match self.stdin.as_ref()? {
+            EnvValue::Template(template) => Some(render_template(template, inputs, parameters)),
+            EnvValue::Input(_) => inputs.get(STDIN_INPUT).cloned(),
+        }
+    }
+
+    /// Returns the inputs recorded in the step result: `inputs` without the
+    /// synthetic `stdin` input, whose value only goes to the script's stdin.
+    #[cfg(feature = "exec")]
+    fn recorded_inputs<'a>(
+        &self,
+        inputs: &'a HashMap<String, String>,
+    ) -> Cow<'a, HashMap<String, String>> {
+        if matches!(self.stdin, Some(EnvValue::Input(_))) {
+            Cow::Owned(
+                inputs
+                    .iter()
+                    .filter(|(name, _)| name.as_str() != STDIN_INPUT)
+                    .map(|(name, value)| (name.clone(), value.clone()))
+                    .collect(),
+            )
+        } else {
+            Cow::Borrowed(inputs)
+        }
+    }
+
+    fn stdin_template(&self) -> Option<&str> {
+        match &self.stdin {
+            Some(EnvValue::Template(template)) => Some(template),
+            _ => None,
         }
+    }
+
+    /// Returns the script, workdir, stdin, artifact path and env templates that
+    /// may contain placeholders, with raw blocks removed.
+    fn templates(&self) -> impl Iterator<Item = Cow<'_, str>> {
+        std::iter::once(self.script.as_str())
+            .chain(
+                self.setting_templates()
+                    .into_iter()
+                    .map(|(_, template)| template),
+            )
+            .chain(self.env_templates().map(|(_, template)| template))
+            .map(strip_raw_blocks)
+    }
+
+    /// Returns the workdir, stdin and artifact path templates with a label
+    /// naming each for error messages.
+    fn setting_templates(&self) -> Vec<(String, &str)> {
+        let mut templates: Vec<(String, &str)> = self
+            .workdir
+            .as_deref()
+            .map(|workdir| ("workdir".to_string(), workdir))
+            .into_iter()
+            .chain(
+                self.stdin_template()
+                    .map(|stdin| ("stdin".to_string(), stdin)),
+            )
+            .collect();
+        let mut artifacts: Vec<_> = self.artifacts.iter().collect();
+        artifacts.sort_by_key(|(name, _)| name.as_str());
+        templates.extend(
+            artifacts
+                .into_iter()
+                .map(|(name, artifact)| (format!("artifact '{name}'"), artifact.path.as_str())),
+        );
+        templates
+    }
 
+    /// Returns whether every placeholder of input `name` carries a default, so the
+    /// step can still run when the input cannot be resolved.
+    pub(crate) fn input_has_default(&self, name: &str) -> bool {
         #[allow(clippy::expect_used)]
-        let re = Regex::new(INPUT_PLACEHOLDER_PATTERN).expect("Valid regex pattern");
+        let re = Regex::new(INPUT_PLACEHOLDER_PATTERN)
+            .expect("Input placeholder regex pattern is valid");
 
-        re.replace_all(&self.script, |caps: &regex::Captures| {
-            let key = &caps[1];
-            inputs
-                .get(key)
-                .cloned()
-                .unwrap_or_else(|| caps[0].to_string())
+        let mut used = false;
+        for template in self.templates() {
+            for cap in re.captures_iter(&template).filter(|cap| &cap[1] == name) {
+                if cap.get(2).is_none() {
+                    return false;
+                }
+                used = true;
+            }
+        }
+        used
+    }
+
+    fn env_templates(&self) -> impl Iterator<Item = (&String, &str)> {
+        self.env.iter().filter_map(|(name, value)| match value {
+            EnvValue::Template(template) => Some((name, template.as_str())),
+            EnvValue::Input(_) => None,
         })
-        .to_string()
     }
 
-    pub fn extract_outputs(&self, stdout: &mut String) -> Result<HashMap<String, String>> {
+    /// Extracts the declared outputs from the captured `stdout` and `stderr` or the
+    /// process `exit_code`, converting each value to its declared data type. Text
+    /// matched by a regex pattern is removed from the stream it was found in.
+    ///
+    /// # Errors
+    /// Returns an error if a pattern is invalid, does not match or does not capture
+    /// a group, or if a captured value cannot be converted to the declared type.
+    pub fn extract_outputs(
+        &self,
+        stdout: &mut String,
+        stderr: &mut String,
+        exit_code: i32,
+    ) -> Result<HashMap<String, JsonValue>> {
         if self.outputs.is_empty() {
             return Ok(HashMap::new());
         }
 
         let mut step_outputs = HashMap::new();
 
-        for (out_name, out) in &self.outputs {
-            let re = Regex::new(&out.pattern).map_err(|e| {
-                AtentoError::Execution(format!("Invalid regex for output '{out_name}': {e}"))
-            })?;
+        // JSON paths read the untouched streams, since regex outputs strip their matches
+        let json_stdout = stdout.clone();
+        let json_stderr = stderr.clone();
 
-            let caps = re.captures(stdout).ok_or_else(|| {
-                AtentoError::Execution(format!(
-                    "Output '{}' pattern '{}' did not match stdout",
-                    out_name, out.pattern
-                ))
-            })?;
+        for (out_name, out) in &self.outputs {
+            let value = match (&out.json_path, out.source) {
+                (_, OutputSource::ExitCode) => {
+                    convert_output(out_name, out, &exit_code.to_string())?
+                }
+                (Some(path), OutputSource::Stdout) => {
+                    extract_json_output(out_name, out, path, "stdout", &json_stdout)?
+                }
+                (Some(path), OutputSource::Stderr) => {
+                    extract_json_output(out_name, out, path, "stderr", &json_stderr)?
+                }
+                (Some(path), OutputSource::Both) => {
+                    extract_json_output(out_name, out, path, "stdout", &json_stdout).or_else(
+                        |_| extract_json_output(out_name, out, path, "stderr", &json_stderr),
+                    )?
+                }
+                (None, _) => {
+                    let captured = extract_regex_output(out_name, out, stdout, stderr)?;
+                    let mut values = captured
+                        .iter()
+                        .map(|text| convert_output(out_name, out, text))
+                        .collect::<Result<Vec<_>>>()?;
 
-            if caps.len() <= 1 {
-                return Err(AtentoError::Execution(format!(
-                    "Output '{}' regex '{}' did not capture a group",
-                    out_name, out.pattern
-                )));
-            }
+                    if out.multiple {
+                        JsonValue::Array(values)
+                    } else if let Some(value) = values.pop() {
+                        value
+                    } else if let Some(default) = &out.default {
+                        default_output(out_name, out, default)?
+                    } else {
+                        // Optional output without a match or default is left out
+                        continue;
+                    }
+                }
+            };
 
-            step_outputs.insert(out_name.clone(), caps[1].to_string());
-            *stdout = stdout.replace(&caps[0], "");
+            step_outputs.insert(out_name.clone(), value);
         }
 
         Ok(step_outputs)
     }
 
+    /// Returns `typed` outputs as the text passed to later steps: strings
+    /// unquoted, other values in their JSON form and lists joined with the
+    /// output's delimiter.
+    #[cfg(feature = "exec")]
+    fn output_texts(&self, typed: &HashMap<String, JsonValue>) -> HashMap<String, String> {
+        typed
+            .iter()
+            .map(|(name, value)| {
+                let text = self
+                    .outputs
+                    .get(name)
+                    .map_or_else(|| to_text(value), |out| out.render_value(value));
+                (name.clone(), text)
+            })
+            .collect()
+    }
+
+    /// Renders this step's script without executing it.
+    ///
+    /// The returned result carries the fully substituted script and the resolved
+    /// inputs; no outputs are extracted since nothing is run.
+    #[must_use]
+    pub fn dry_run(
+        &self,
+        inputs: &HashMap<String, String>,
+        parameters: &HashMap<String, String>,
+        env: &HashMap<String, String>,
+    ) -> StepResult {
+        let script = if self.foreach.is_some() {
+            let mut item_inputs = inputs.clone();
+            item_inputs.insert(
+                FOREACH_ITEM_INPUT.to_string(),
+                FOREACH_ITEM_PLACEHOLDER.to_string(),
+            );
+            self.render_script(&item_inputs, parameters)
+        } else {
+            self.render_script(inputs, parameters)
+        };
+
+        StepResult {
+            name: self.name.clone(),
+            duration_ms: 0,
+            exit_code: 0,
+            attempts: 0,
+            attempt_history: Vec::new(),
+            inputs: inputs.clone(),
+            outputs: HashMap::new(),
+            typed_outputs: HashMap::new(),
+            env: env_names(env),
+            script: Some(script),
+            stdout: None,
+            stderr: None,
+            error: None,
+            iterations: Vec::new(),
+            combined_output: None,
+            resolved_command: None,
+            artifacts: HashMap::new(),
+            stdout_truncated: false,
+            stderr_truncated: false,
+            truncated: false,
+            started_at: None,
+            finished_at: None,
+            resource_usage: None,
+        }
+    }
+
     /// Runs this step using the provided executor and inputs.
     ///
+    /// `parameters` holds the chain parameter values used for `{{ parameters.x }}`
+    /// placeholders. `env` is passed to the executor as process environment and never
+    /// substituted into the script text. An exit code outside
+    /// `success_exit_codes` fails the step unless `allow_nonzero_exit` is set.
+    /// When a retry policy is configured, a failed attempt (executor error,
+    /// unsuccessful exit code or output extraction failure) is retried until the policy is exhausted or `time_left` runs out.
+    /// With `max_output_bytes` set, stdout and stderr each keep only their first
+    /// `max_output_bytes` bytes, and outputs are extracted from what was kept.
+    ///
     /// # Errors
     /// Returns an error if script execution fails or output extraction fails.
+    #[cfg(feature = "exec")]
+    #[allow(clippy::too_many_arguments)]
     pub fn run<E: CommandExecutor>(
         &self,
         executor: &E,
         inputs: &HashMap<String, String>,
+        parameters: &HashMap<String, String>,
+        env: &HashMap<String, String>,
+        max_output_bytes: Option<u64>,
         time_left: u64,
+        kill: &KillPolicy,
         interpreter: &Interpreter,
     ) -> StepResult {
-        let script = self.build_script(inputs);
+        let rendered = self.render(inputs, parameters, interpreter);
+        let invocation = rendered.invocation(env, max_output_bytes, kill);
+        let mut attempts = Attempts::start(self.retry_policy(), time_left);
 
-        let timeout = self.calculate_timeout(time_left);
+        loop {
+            let timeout = self.calculate_timeout(attempts.remaining());
+            let result = self.run_attempt(executor, &invocation, timeout);
+            match attempts.record(result) {
+                ControlFlow::Break(result) => return result,
+                ControlFlow::Continue(delay) => std::thread::sleep(delay),
+            }
+        }
+    }
+
+    /// Runs this step like [`Step::run`], awaiting the script on an
+    /// [`AsyncCommandExecutor`] instead of blocking the thread; delays between
+    /// retries are awaited as well. Steps with an `image` run through
+    /// [`DockerExecutor`].
+    #[cfg(feature = "async")]
+    #[allow(clippy::too_many_arguments)]
+    pub async fn run_async<E: AsyncCommandExecutor>(
+        &self,
+        executor: &E,
+        inputs: &HashMap<String, String>,
+        parameters: &HashMap<String, String>,
+        env: &HashMap<String, String>,
+        max_output_bytes: Option<u64>,
+        time_left: u64,
+        kill: &KillPolicy,
+        interpreter: &Interpreter,
+    ) -> StepResult {
+        let rendered = self.render(inputs, parameters, interpreter);
+        let invocation = rendered.invocation(env, max_output_bytes, kill);
+        let mut attempts = Attempts::start(self.retry_policy(), time_left);
 
-        let start_time = std::time::Instant::now();
-        match executor.execute(&script, interpreter, timeout) {
+        loop {
+            let timeout = self.calculate_timeout(attempts.remaining());
+            let start_time = Instant::now();
+            let outcome = match &self.image {
+                Some(image) => {
+                    self.execute_async(&DockerExecutor::new(image), &invocation, timeout)
+                        .await
+                }
+                None => self.execute_async(executor, &invocation, timeout).await,
+            };
+            let result = self.attempt_result(&invocation, timeout, outcome, &start_time);
+            match attempts.record(result) {
+                ControlFlow::Break(result) => return result,
+                ControlFlow::Continue(delay) => tokio::time::sleep(delay).await,
+            }
+        }
+    }
+
+    /// Renders the script, workdir and stdin of one run of this step.
+    #[cfg(feature = "exec")]
+    fn render<'a>(
+        &self,
+        inputs: &'a HashMap<String, String>,
+        parameters: &HashMap<String, String>,
+        interpreter: &'a Interpreter,
+    ) -> Rendered<'a> {
+        let rendered = Rendered {
+            script: self.render_script(inputs, parameters),
+            workdir: self.render_workdir(inputs, parameters),
+            stdin: self.render_stdin(inputs, parameters),
+            interpreter: self.effective_interpreter(interpreter),
+            inputs: self.recorded_inputs(inputs),
+        };
+
+        #[cfg(feature = "tracing")]
+        tracing::debug!(
+            script_bytes = rendered.script.len(),
+            workdir = rendered.workdir.as_deref(),
+            stdin = rendered.stdin.is_some(),
+            "script rendered"
+        );
+        rendered
+    }
+
+    /// Rejects an empty `image`, empty `interpreter_args` entries, and
+    /// artifacts that are unnamed, have no path or belong to a `foreach` step.
+    fn validate_execution_settings(&self, step_name: &str) -> Result<()> {
+        if self.foreach.is_some() && !self.artifacts.is_empty() {
+            return Err(AtentoError::Validation(format!(
+                "Step '{step_name}' cannot combine 'foreach' with 'artifacts'"
+            )));
+        }
+
+        if let Some((name, _)) = self
+            .artifacts
+            .iter()
+            .find(|(name, artifact)| name.trim().is_empty() || artifact.path.trim().is_empty())
+        {
+            return Err(AtentoError::Validation(format!(
+                "Step '{step_name}' has artifact '{name}' without a name or 'path'"
+            )));
+        }
+
+        if self
+            .image
+            .as_deref()
+            .is_some_and(|image| image.trim().is_empty())
+        {
+            return Err(AtentoError::Validation(format!(
+                "Step '{step_name}' has an empty 'image'"
+            )));
+        }
+
+        if self
+            .interpreter_args
+            .as_ref()
+            .is_some_and(|step_args| step_args.args.iter().any(|arg| arg.trim().is_empty()))
+        {
+            return Err(AtentoError::Validation(format!(
+                "Step '{step_name}' has an empty entry in 'interpreter_args'"
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Returns the interpreter the step's script runs with: `interpreter` with
+    /// the step's `interpreter_args` applied, see [`Interpreter::with_step_args`].
+    #[must_use]
+    pub fn effective_interpreter<'a>(&self, interpreter: &'a Interpreter) -> Cow<'a, Interpreter> {
+        match &self.interpreter_args {
+            Some(step_args) => Cow::Owned(interpreter.with_step_args(step_args)),
+            None => Cow::Borrowed(interpreter),
+        }
+    }
+
+    /// Combines the per-item results of a `foreach` step into a single result.
+    ///
+    /// Outputs become maps keyed by the iteration index, stdout and stderr are
+    /// joined line by line, and the first failing iteration determines the exit
+    /// code and error of the step.
+    #[cfg(feature = "exec")]
+    pub(crate) fn combine_iterations(
+        &self,
+        inputs: &HashMap<String, String>,
+        env: &HashMap<String, String>,
+        iterations: Vec<StepResult>,
+        duration_ms: u128,
+    ) -> StepResult {
+        let mut outputs: HashMap<String, serde_json::Map<String, JsonValue>> = HashMap::new();
+        for (index, iteration) in iterations.iter().enumerate() {
+            for (out_name, value) in &iteration.typed_outputs {
+                outputs
+                    .entry(out_name.clone())
+                    .or_default()
+                    .insert(index.to_string(), value.clone());
+            }
+        }
+
+        let typed_outputs: HashMap<String, JsonValue> = outputs
+            .into_iter()
+            .map(|(out_name, values)| (out_name, JsonValue::Object(values)))
+            .collect();
+
+        let failed = iterations
+            .iter()
+            .enumerate()
+            .find(|(_, iteration)| iteration.error.is_some());
+        let exit_code = failed
+            .map(|(_, iteration)| iteration)
+            .or(iterations.last())
+            .map_or(0, |iteration| iteration.exit_code);
+        let error = failed.and_then(|(index, iteration)| {
+            let item = iteration
+                .inputs
+                .get(FOREACH_ITEM_INPUT)
+                .map_or("", String::as_str);
+            iteration.error.as_ref().map(|err| {
+                AtentoError::Execution(format!("Iteration {index} ('{item}') failed: {err}"))
+            })
+        });
+
+        let join = |stream: fn(&StepResult) -> Option<&String>| {
+            let lines: Vec<&str> = iterations
+                .iter()
+                .filter_map(stream)
+                .map(String::as_str)
+                .collect();
+            Some(lines.join("\n")).filter(|s| !s.is_empty())
+        };
+        let stdout = join(|iteration| iteration.stdout.as_ref());
+        let stderr = join(|iteration| iteration.stderr.as_ref());
+        let combined_output = join(|iteration| iteration.combined_output.as_ref());
+
+        StepResult {
+            name: self.name.clone(),
+            duration_ms,
+            exit_code,
+            attempts: 1,
+            attempt_history: Vec::new(),
+            inputs: self.recorded_inputs(inputs).into_owned(),
+            outputs: self.output_texts(&typed_outputs),
+            typed_outputs,
+            env: env_names(env),
+            script: None,
+            stdout,
+            stderr,
+            stdout_truncated: iterations
+                .iter()
+                .any(|iteration| iteration.stdout_truncated),
+            stderr_truncated: iterations
+                .iter()
+                .any(|iteration| iteration.stderr_truncated),
+            truncated: iterations.iter().any(|iteration| iteration.truncated),
+            error,
+            resolved_command: iterations
+                .iter()
+                .find_map(|iteration| iteration.resolved_command.clone()),
+            resource_usage: iterations
+                .iter()
+                .filter_map(|iteration| iteration.resource_usage)
+                .reduce(ResourceUsage::combine),
+            iterations,
+            combined_output,
+            artifacts: HashMap::new(),
+            started_at: None,
+            finished_at: None,
+        }
+    }
+
+    /// Runs the script once with `executor`, capturing output as configured.
+    #[cfg(feature = "exec")]
+    fn execute<E: CommandExecutor>(
+        &self,
+        executor: &E,
+        invocation: &Invocation<'_>,
+        timeout: u64,
+    ) -> Result<ExecutionResult> {
+        let request = invocation.request(timeout);
+        match self.capture {
+            Capture::Separate => executor.execute(&request),
+            Capture::Combined => executor.execute_combined(&request),
+        }
+    }
+
+    /// Runs the script once with `executor` like [`Step::execute`], awaiting it.
+    #[cfg(feature = "async")]
+    async fn execute_async<E: AsyncCommandExecutor>(
+        &self,
+        executor: &E,
+        invocation: &Invocation<'_>,
+        timeout: u64,
+    ) -> Result<ExecutionResult> {
+        let request = invocation.request(timeout);
+        match self.capture {
+            Capture::Separate => executor.execute(&request).await,
+            Capture::Combined => executor.execute_combined(&request).await,
+        }
+    }
+
+    #[cfg(feature = "exec")]
+    fn run_attempt<E: CommandExecutor>(
+        &self,
+        executor: &E,
+        invocation: &Invocation<'_>,
+        timeout: u64,
+    ) -> StepResult {
+        let start_time = Instant::now();
+        let outcome = match &self.image {
+            Some(image) => self.execute(&DockerExecutor::new(image), invocation, timeout),
+            None => self.execute(executor, invocation, timeout),
+        };
+        self.attempt_result(invocation, timeout, outcome, &start_time)
+    }
+
+    /// Builds the result of one attempt started at `start_time` from what the
+    /// executor returned.
+    #[cfg(feature = "exec")]
+    fn attempt_result(
+        &self,
+        invocation: &Invocation<'_>,
+        timeout: u64,
+        outcome: Result<ExecutionResult>,
+        start_time: &Instant,
+    ) -> StepResult {
+        let Invocation {
+            inputs,
+            env,
+            max_output_bytes,
+            kill,
+            ..
+        } = *invocation;
+
+        #[cfg(feature = "tracing")]
+        if let Ok(result) = &outcome {
+            trace_truncation(result, max_output_bytes);
+        }
+
+        match outcome {
             Ok(result) => {
                 let duration_ms = start_time.elapsed().as_millis();
 
                 let mut stdout = result.stdout;
-                let step_outputs = match self.extract_outputs(&mut stdout) {
-                    Ok(outputs) => outputs,
-                    Err(e) => {
-                        return StepResult {
-                            name: self.name.clone(),
-                            duration_ms,
-                            exit_code: result.exit_code,
-                            stdout: Some(stdout.trim().to_string()).filter(|s| !s.is_empty()),
-                            stderr: Some(result.stderr).filter(|s| !s.is_empty()),
-                            inputs: inputs.clone(),
-                            outputs: HashMap::new(),
-                            error: Some(e),
-                        };
+                let mut stderr = result.stderr;
+                let (outputs, mut error) = if result.timed_out {
+                    // Keep the partial output, but do not extract from it
+                    let timeout_error = timeout_error(timeout, kill, result.force_killed);
+                    (HashMap::new(), Some(timeout_error))
+                } else {
+                    match self.extract_outputs(&mut stdout, &mut stderr, result.exit_code) {
+                        Ok(outputs) => (outputs, None),
+                        Err(mut e) => {
+                            if let (AtentoError::Execution(msg), Some(max)) =
+                                (&mut e, max_output_bytes)
+                                && (result.stdout_truncated || result.stderr_truncated)
+                            {
+                                let _ = write!(
+                                    msg,
+                                    "; the output was truncated to its first {max} bytes"
+                                );
+                            }
+                            #[cfg(feature = "tracing")]
+                            tracing::warn!(error = %e, "output extraction failed");
+                            (HashMap::new(), Some(e))
+                        }
                     }
                 };
 
+                if !result.timed_out && !self.is_success_exit_code(result.exit_code) {
+                    error = Some(AtentoError::Execution(format!(
+                        "Script exited with code {}",
+                        result.exit_code
+                    )));
+                }
+
                 StepResult {
                     name: self.name.clone(),
                     duration_ms,
                     exit_code: result.exit_code,
+                    attempts: 1,
+                    attempt_history: Vec::new(),
                     stdout: Some(stdout.trim().to_string()).filter(|s| !s.is_empty()),
-                    stderr: Some(result.stderr).filter(|s| !s.is_empty()),
+                    stderr: Some(stderr).filter(|s| !s.is_empty()),
+                    stdout_truncated: result.stdout_truncated,
+                    stderr_truncated: result.stderr_truncated,
+                    truncated: result.stdout_truncated || result.stderr_truncated,
                     inputs: inputs.clone(),
-                    outputs: step_outputs,
-                    error: None,
+                    outputs: self.output_texts(&outputs),
+                    typed_outputs: outputs,
+                    env: env_names(env),
+                    error,
+                    script: None,
+                    iterations: Vec::new(),
+                    combined_output: result.combined,
+                    resolved_command: result.resolved_command,
+                    artifacts: HashMap::new(),
+                    started_at: None,
+                    finished_at: None,
+                    resource_usage: result.resource_usage,
                 }
             }
             Err(e) => {
@@ -234,13 +1552,510 @@ impl Step {
                     name: self.name.clone(),
                     duration_ms,
                     exit_code: 1,
+                    attempts: 1,
+                    attempt_history: Vec::new(),
                     stdout: None,
                     stderr: None,
                     inputs: inputs.clone(),
                     outputs: HashMap::new(),
+                    typed_outputs: HashMap::new(),
+                    env: env_names(env),
                     error: Some(e),
+                    script: None,
+                    iterations: Vec::new(),
+                    combined_output: None,
+                    resolved_command: None,
+                    artifacts: HashMap::new(),
+                    stdout_truncated: false,
+                    stderr_truncated: false,
+                    truncated: false,
+                    started_at: None,
+                    finished_at: None,
+                    resource_usage: None,
                 }
             }
         }
     }
 }
+
+impl StepResult {
+    /// Replaces every occurrence of the given secret values in the recorded
+    /// inputs, outputs, script, captured output and errors with a mask.
+    pub(crate) fn mask_secrets(&mut self, secrets: &[String]) {
+        if secrets.is_empty() {
+            return;
+        }
+
+        self.error = self.error.take().map(|err| err.masked(secrets));
+        for attempt in &mut self.attempt_history {
+            if let Some(error) = &mut attempt.error {
+                *error = mask_secrets(error, secrets);
+            }
+        }
+
+        for value in self.inputs.values_mut() {
+            *value = mask_secrets(value, secrets);
+        }
+
+        for value in self.outputs.values_mut() {
+            *value = mask_secrets(value, secrets);
+        }
+
+        for value in self.typed_outputs.values_mut() {
+            mask_json_secrets(value, secrets);
+        }
+
+        for artifact in self.artifacts.values_mut() {
+            artifact.path = mask_secrets(&artifact.path, secrets);
+        }
+
+        for text in [
+            &mut self.script,
+            &mut self.stdout,
+            &mut self.stderr,
+            &mut self.combined_output,
+        ]
+        .into_iter()
+        .flatten()
+        {
+            *text = mask_secrets(text, secrets);
+        }
+
+        for iteration in &mut self.iterations {
+            iteration.mask_secrets(secrets);
+        }
+    }
+}
+
+/// Replaces every occurrence of the given secret values in `text` with a mask.
+pub(crate) fn mask_secrets(text: &str, secrets: &[String]) -> String {
+    secrets
+        .iter()
+        .filter(|secret| !secret.is_empty())
+        .fold(text.to_string(), |masked, secret| {
+            masked.replace(secret.as_str(), SECRET_MASK)
+        })
+}
+
+/// Masks secret values inside a JSON output value; list and map items are masked individually.
+fn mask_json_secrets(value: &mut JsonValue, secrets: &[String]) {
+    match value {
+        JsonValue::Array(items) => {
+            for item in items {
+                mask_json_secrets(item, secrets);
+            }
+            return;
+        }
+        JsonValue::Object(items) => {
+            for item in items.values_mut() {
+                mask_json_secrets(item, secrets);
+            }
+            return;
+        }
+        _ => {}
+    }
+
+    let text = to_text(value);
+    let masked = mask_secrets(&text, secrets);
+    if masked != text {
+        *value = JsonValue::String(masked);
+    }
+}
+
+/// Converts captured text to the output's declared data type.
+fn convert_output(out_name: &str, out: &Output, captured: &str) -> Result<JsonValue> {
+    to_json_value(&out.type_, captured).map_err(|_| AtentoError::TypeConversion {
+        expected: out.type_.to_string(),
+        got: format!("'{captured}' for output '{out_name}'"),
+    })
+}
+
+/// Converts the `default` of an optional output to its declared data type.
+fn default_output(out_name: &str, out: &Output, default: &serde_yaml::Value) -> Result<JsonValue> {
+    let text = to_string_value(&out.type_, default).map_err(|_| AtentoError::TypeConversion {
+        expected: out.type_.to_string(),
+        got: format!("default {default:?} for output '{out_name}'"),
+    })?;
+    convert_output(out_name, out, &text)
+}
+
+/// Extracts an output's regex matches from the stream selected by its source and
+/// removes the matched text from the stream it was found in. Only the first match
+/// is taken unless the output sets `multiple`.
+///
+/// With `source: both`, the pattern is matched against stdout and stderr joined by
+/// a newline.
+fn extract_regex_output(
+    out_name: &str,
+    out: &Output,
+    stdout: &mut String,
+    stderr: &mut String,
+) -> Result<Vec<String>> {
+    let re = out.regex().map_err(|e| {
+        AtentoError::Execution(format!("Invalid regex for output '{out_name}': {e}"))
+    })?;
+
+    let (stream_name, captured) = match out.source {
+        OutputSource::Stderr => ("stderr", capture_and_strip(out_name, out, &re, stderr)?),
+        OutputSource::Both => {
+            let split = stdout.len();
+            let combined = format!("{stdout}\n{stderr}");
+            let (captured, ranges) = find_matches(out_name, out, &re, &combined)?;
+
+            for range in ranges.iter().rev() {
+                if range.start < split {
+                    stdout.replace_range(range.start..range.end.min(split), "");
+                }
+                if range.end > split + 1 {
+                    let start = range.start.max(split + 1) - split - 1;
+                    stderr.replace_range(start..range.end - split - 1, "");
+                }
+            }
+
+            ("stdout or stderr", captured)
+        }
+        OutputSource::Stdout | OutputSource::ExitCode => {
+            ("stdout", capture_and_strip(out_name, out, &re, stdout)?)
+        }
+    };
+
+    if captured.is_empty() && !out.optional {
+        return Err(AtentoError::Execution(match &out.key {
+            Some(key) => format!("Output '{out_name}' key '{key}' was not found in {stream_name}"),
+            None => format!(
+                "Output '{}' pattern '{}' did not match {}",
+                out_name, out.pattern, stream_name
+            ),
+        }));
+    }
+
+    Ok(captured)
+}
+
+/// Captures the output's matches in `text` and removes the matched text from it.
+fn capture_and_strip(
+    out_name: &str,
+    out: &Output,
+    re: &Regex,
+    text: &mut String,
+) -> Result<Vec<String>> {
+    let (captured, ranges) = find_matches(out_name, out, re, text)?;
+    for range in ranges.into_iter().rev() {
+        text.replace_range(range, "");
+    }
+    Ok(captured)
+}
+
+/// Returns the captured values and byte ranges of the output's matches in `text`.
+fn find_matches(
+    out_name: &str,
+    out: &Output,
+    re: &Regex,
+    text: &str,
+) -> Result<(Vec<String>, Vec<Range<usize>>)> {
+    let limit = if out.multiple || out.key.is_some() {
+        usize::MAX
+    } else {
+        1
+    };
+    let mut captured = Vec::new();
+    let mut ranges = Vec::new();
+
+    for caps in re.captures_iter(text).take(limit) {
+        captured.push(capture_group(out_name, out, re, &caps)?);
+        if let Some(whole) = caps.get(0) {
+            ranges.push(whole.range());
+        }
+    }
+
+    if out.key.is_some() && !out.multiple {
+        // The last line with the key wins, but every such line is removed
+        captured.drain(..captured.len().saturating_sub(1));
+    }
+
+    Ok((captured, ranges))
+}
+
+/// Returns the text of the capture group selected by the output.
+fn capture_group(
+    out_name: &str,
+    out: &Output,
+    re: &Regex,
+    caps: &regex::Captures<'_>,
+) -> Result<String> {
+    let captured = if let Some(group) = &out.group {
+        if !has_named_group(re, group) {
+            return Err(AtentoError::Execution(format!(
+                "Output '{}' regex '{}' has no capture group named '{}'",
+                out_name, out.pattern, group
+            )));
+        }
+
+        caps.name(group).ok_or_else(|| {
+            AtentoError::Execution(format!(
+                "Output '{}' regex '{}' did not capture group '{}'",
+                out_name, out.pattern, group
+            ))
+        })?
+    } else {
+        caps.get(1).ok_or_else(|| {
+            AtentoError::Execution(format!(
+                "Output '{}' regex '{}' did not capture a group",
+                out_name, out.pattern
+            ))
+        })?
+    };
+
+    Ok(captured.as_str().to_string())
+}
+
+/// Extracts an output by parsing a captured stream as JSON and resolving `path`.
+///
+/// When the whole stream is not valid JSON, its last non-empty line is parsed
+/// instead, so scripts can log freely before printing a JSON summary. Scalars are
+/// converted to the output's data type; arrays of scalars are accepted for `list`
+/// outputs.
+fn extract_json_output(
+    out_name: &str,
+    out: &Output,
+    path: &str,
+    stream: &str,
+    text: &str,
+) -> Result<JsonValue> {
+    let segments = parse_json_path(path).ok_or_else(|| {
+        AtentoError::Execution(format!(
+            "Output '{out_name}' has invalid JSON path '{path}'"
+        ))
+    })?;
+
+    let document: JsonValue = serde_json::from_str(text).or_else(|e| {
+        text.lines()
+            .rev()
+            .find(|line| !line.trim().is_empty())
+            .and_then(|line| serde_json::from_str(line).ok())
+            .ok_or_else(|| {
+                AtentoError::Execution(format!(
+                    "Output '{out_name}' could not parse {stream} as JSON (nor its last line): {e}"
+                ))
+            })
+    })?;
+
+    match resolve_json_path(&document, &segments) {
+        Some(value @ (JsonValue::String(_) | JsonValue::Number(_) | JsonValue::Bool(_))) => {
+            convert_output(out_name, out, &to_text(value))
+        }
+        Some(JsonValue::Array(items))
+            if out.type_ == DataType::List
+                && items
+                    .iter()
+                    .all(|item| !(item.is_array() || item.is_object())) =>
+        {
+            Ok(JsonValue::Array(
+                items
+                    .iter()
+                    .map(|item| JsonValue::String(to_text(item)))
+                    .collect(),
+            ))
+        }
+        Some(_) => Err(AtentoError::Execution(format!(
+            "Output '{out_name}' JSON path '{path}' did not resolve to a scalar value{}",
+            if out.type_ == DataType::List {
+                " or a list of scalars"
+            } else {
+                ""
+            }
+        ))),
+        None => Err(AtentoError::Execution(format!(
+            "Output '{out_name}' JSON path '{path}' was not found in {stream}"
+        ))),
+    }
+}
+
+/// Returns whether the compiled regex defines a capture group with the given name.
+fn has_named_group(re: &Regex, group: &str) -> bool {
+    re.capture_names().flatten().any(|name| name == group)
+}
+
+fn default_attempts() -> u32 {
+    1
+}
+
+#[allow(clippy::trivially_copy_pass_by_ref)]
+fn is_single_attempt(attempts: &u32) -> bool {
+    *attempts <= 1
+}
+
+/// Substitutes `{{ inputs.x }}`, `{{ parameters.x }}` and `{{ steps.x.outputs.y }}`
+/// placeholders in a template.
+pub(crate) fn render_template(
+    template: &str,
+    inputs: &HashMap<String, String>,
+    parameters: &HashMap<String, String>,
+) -> String {
+    render_quoted(template, inputs, parameters, Quote::None)
+}
+
+/// Substitutes placeholders like [`render_template`], quoting every substituted
+/// value as `quote` says.
+fn render_quoted(
+    template: &str,
+    inputs: &HashMap<String, String>,
+    parameters: &HashMap<String, String>,
+    quote: Quote,
+) -> String {
+    #[allow(clippy::expect_used)]
+    let raw_block = Regex::new(RAW_BLOCK_PATTERN).expect("Raw block regex pattern is valid");
+
+    let mut rendered = String::with_capacity(template.len());
+    let mut last = 0;
+    for cap in raw_block.captures_iter(template) {
+        let Some(block) = cap.get(0) else { continue };
+        rendered.push_str(&substitute(
+            &template[last..block.start()],
+            inputs,
+            parameters,
+            quote,
+        ));
+        rendered.push_str(&cap[1]);
+        last = block.end();
+    }
+    rendered.push_str(&substitute(&template[last..], inputs, parameters, quote));
+    rendered
+}
+
+/// Removes `{{ raw }}...{{ endraw }}` blocks so their content is not scanned for placeholders.
+fn strip_raw_blocks(template: &str) -> Cow<'_, str> {
+    #[allow(clippy::expect_used)]
+    let raw_block = Regex::new(RAW_BLOCK_PATTERN).expect("Raw block regex pattern is valid");
+    raw_block.replace_all(template, "")
+}
+
+/// Returns the names of the parameters referenced in a template.
+pub(crate) fn parameter_placeholders(template: &str) -> Vec<String> {
+    #[allow(clippy::expect_used)]
+    let re = Regex::new(PARAMETER_PLACEHOLDER_PATTERN)
+        .expect("Parameter placeholder regex pattern is valid");
+
+    re.captures_iter(&strip_raw_blocks(template))
+        .map(|cap| cap[1].to_string())
+        .collect()
+}
+
+/// Returns the step references, such as `steps.build.outputs.version`, used as
+/// placeholders in a template.
+fn step_placeholders(template: &str) -> Vec<String> {
+    #[allow(clippy::expect_used)]
+    let re = Regex::new(STEP_PLACEHOLDER_PATTERN).expect("Step placeholder regex pattern is valid");
+
+    re.captures_iter(&strip_raw_blocks(template))
+        .map(|cap| cap[1].to_string())
+        .collect()
+}
+
+/// Returns the names of the inputs referenced in a template without a default value.
+pub(crate) fn required_input_placeholders(template: &str) -> Vec<String> {
+    #[allow(clippy::expect_used)]
+    let re =
+        Regex::new(INPUT_PLACEHOLDER_PATTERN).expect("Input placeholder regex pattern is valid");
+
+    re.captures_iter(&strip_raw_blocks(template))
+        .filter(|cap| cap.get(2).is_none())
+        .map(|cap| cap[1].to_string())
+        .collect()
+}
+
+/// Replaces every placeholder in `text` with its value in a single pass, so
+/// substituted values are never scanned for placeholders themselves. Inputs
+/// without a value fall back to their default; placeholders with neither are
+/// left untouched.
+///
+/// The chain resolves step references into `inputs`, keyed by the reference.
+fn substitute(
+    text: &str,
+    inputs: &HashMap<String, String>,
+    parameters: &HashMap<String, String>,
+    quote: Quote,
+) -> String {
+    #[allow(clippy::expect_used)]
+    let re = Regex::new(&format!(
+        "{INPUT_PLACEHOLDER_PATTERN}|{PARAMETER_PLACEHOLDER_PATTERN}|{STEP_PLACEHOLDER_PATTERN}"
+    ))
+    .expect("Placeholder regex patterns are valid");
+
+    re.replace_all(text, |caps: &regex::Captures| {
+        let value = if let Some(input) = caps.get(1) {
+            inputs
+                .get(input.as_str())
+                .map(String::as_str)
+                .or_else(|| caps.get(2).map(|default| default.as_str()))
+        } else if let Some(parameter) = caps.get(3) {
+            parameters.get(parameter.as_str()).map(String::as_str)
+        } else {
+            caps.get(4)
+                .and_then(|reference| inputs.get(reference.as_str()))
+                .map(String::as_str)
+        };
+        value.map_or_else(|| caps[0].to_string(), |value| quote.apply(value))
+    })
+    .to_string()
+}
+
+/// Splits a `foreach` list into its items: one per line when the list spans
+/// several lines, otherwise comma separated. Blank items are dropped.
+#[cfg(feature = "exec")]
+pub(crate) fn foreach_items(list: &str) -> Vec<String> {
+    let separator = if list.contains('\n') { '\n' } else { ',' };
+    list.split(separator)
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Warns when the output of an attempt went past `max_output_bytes`.
+#[cfg(all(feature = "exec", feature = "tracing"))]
+fn trace_truncation(result: &ExecutionResult, max_output_bytes: Option<u64>) {
+    if result.stdout_truncated || result.stderr_truncated {
+        tracing::warn!(
+            stdout_truncated = result.stdout_truncated,
+            stderr_truncated = result.stderr_truncated,
+            max_output_bytes,
+            "output truncated"
+        );
+    }
+}
+
+/// The error of an attempt that ran past `timeout` seconds, noting whether the
+/// process exited on the `kill` signal or had to be force-killed.
+#[cfg(feature = "exec")]
+fn timeout_error(timeout: u64, kill: &KillPolicy, force_killed: bool) -> AtentoError {
+    let context = if !force_killed {
+        format!(
+            "Step execution timed out; the process exited gracefully after {}",
+            kill.signal
+        )
+    } else if cfg!(unix) && kill.signal != TimeoutSignal::Kill {
+        format!(
+            "Step execution timed out; the process did not exit within {}ms of {} and was force-killed",
+            kill.grace_ms, kill.signal
+        )
+    } else {
+        "Step execution timed out; the process was force-killed".to_string()
+    };
+    AtentoError::Timeout {
+        context,
+        timeout_secs: timeout,
+    }
+}
+
+/// Returns the sorted names of the given environment variables, without
+/// [`RUN_ID_ENV`], which every step of a run gets.
+fn env_names(env: &HashMap<String, String>) -> Vec<String> {
+    let mut names: Vec<String> = env
+        .keys()
+        .filter(|name| name.as_str() != RUN_ID_ENV)
+        .cloned()
+        .collect();
+    names.sort();
+    names
+}
@@ -0,0 +1,100 @@
+use crate::chain::Chain;
+use crate::errors::{AtentoError, Result};
+use crate::input::Input;
+use crate::step::Step;
+
+impl Chain {
+    /// Replaces every step that names a `template` with an instance of that
+    /// template. Steps whose template is unknown or whose inputs do not fit
+    /// are left as they are, for [`Chain::validate`] to report.
+    pub(crate) fn instantiate_templates(&mut self) {
+        let instances: Vec<(String, Step)> = self
+            .steps
+            .iter()
+            .filter(|(_, step)| step.template.is_some())
+            .filter_map(|(id, step)| {
+                self.instantiate(id, step)
+                    .ok()
+                    .map(|instance| (id.clone(), instance))
+            })
+            .collect();
+
+        for (id, instance) in instances {
+            self.steps.insert(id, instance);
+        }
+    }
+
+    /// Reports the step `id` if it still names a template after instantiation.
+    pub(crate) fn validate_template(&self, id: &str, step: &Step) -> Result<()> {
+        if let Some(name) = &step.template {
+            self.instantiate(id, step)?;
+            return Err(AtentoError::Validation(format!(
+                "Step '{id}' uses template '{name}' but was not instantiated from it"
+            )));
+        }
+        Ok(())
+    }
+
+    /// Builds the step `id` from its template: the template's definition with
+    /// the step's `name` and `inputs` applied over it.
+    fn instantiate(&self, id: &str, step: &Step) -> Result<Step> {
+        let Some(name) = &step.template else {
+            return Ok(step.clone());
+        };
+        let template = self.templates.get(name).ok_or_else(|| {
+            AtentoError::Validation(format!("Step '{id}' uses unknown template '{name}'"))
+        })?;
+
+        if template.template.is_some() {
+            return Err(AtentoError::Validation(format!(
+                "Template '{name}' cannot itself use a template"
+            )));
+        }
+        if !step.script.is_empty() || step.script_file.is_some() {
+            return Err(AtentoError::Validation(format!(
+                "Step '{id}' uses template '{name}' and cannot set 'script' or 'script_file'"
+            )));
+        }
+
+        let mut passed: Vec<&String> = step.inputs.keys().collect();
+        passed.sort();
+        if let Some(input) = passed
+            .into_iter()
+            .find(|input| !template.inputs.contains_key(*input))
+        {
+            return Err(AtentoError::Validation(format!(
+                "Step '{id}' passes input '{input}', which template '{name}' does not declare"
+            )));
+        }
+
+        let mut required: Vec<&String> = template
+            .inputs
+            .iter()
+            .filter(|(input, declared)| is_required(declared) && !step.inputs.contains_key(*input))
+            .map(|(input, _)| input)
+            .collect();
+        required.sort();
+        if let Some(input) = required.first() {
+            return Err(AtentoError::Validation(format!(
+                "Step '{id}' does not pass input '{input}' required by template '{name}'"
+            )));
+        }
+
+        let mut instance = template.clone();
+        instance.name = step.name.clone().or(instance.name);
+        instance.inputs.extend(step.inputs.clone());
+        instance.override_ = step.override_;
+        Ok(instance)
+    }
+}
+
+/// Template inputs declared without a `value` must be passed by each step.
+fn is_required(input: &Input) -> bool {
+    matches!(
+        input,
+        Input::Inline {
+            value: serde_yaml::Value::Null,
+            ..
+        }
+    )
+}
@@ -0,0 +1,110 @@
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use crate::artifact::{Artifact, is_within};
+    use std::path::Path;
+
+    fn artifact(path: &str) -> Artifact {
+        Artifact {
+            path: path.to_string(),
+            optional: false,
+            inline_max_bytes: None,
+        }
+    }
+
+    #[test]
+    fn test_collect_records_path_and_size() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("report.txt"), "hello").unwrap();
+        let workdir = dir.path().to_str().unwrap();
+
+        let found = artifact("report.txt")
+            .collect("report", "report.txt", Some(workdir), None)
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            found.path,
+            dir.path().join("report.txt").display().to_string()
+        );
+        assert_eq!(found.size, 5);
+        assert!(found.content_base64.is_none());
+    }
+
+    #[test]
+    fn test_collect_inlines_small_files_only() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("small.txt"), "hello").unwrap();
+        std::fs::write(dir.path().join("large.txt"), "hello world").unwrap();
+        let workdir = dir.path().to_str().unwrap();
+        let mut inlined = artifact("");
+        inlined.inline_max_bytes = Some(5);
+
+        let small = inlined
+            .collect("small", "small.txt", Some(workdir), None)
+            .unwrap()
+            .unwrap();
+        assert_eq!(small.content_base64.as_deref(), Some("aGVsbG8="));
+
+        let large = inlined
+            .collect("large", "large.txt", Some(workdir), None)
+            .unwrap()
+            .unwrap();
+        assert_eq!(large.size, 11);
+        assert!(large.content_base64.is_none());
+    }
+
+    #[test]
+    fn test_collect_missing_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let workdir = dir.path().to_str().unwrap();
+
+        let err = artifact("gone.txt")
+            .collect("gone", "gone.txt", Some(workdir), None)
+            .unwrap_err();
+        assert!(err.to_string().contains("Artifact 'gone'"));
+
+        let mut optional = artifact("gone.txt");
+        optional.optional = true;
+        assert!(
+            optional
+                .collect("gone", "gone.txt", Some(workdir), None)
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_collect_rejects_directory() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().to_str().unwrap();
+        let err = artifact(path).collect("dir", path, None, None).unwrap_err();
+        assert!(err.to_string().contains("is not a file"));
+    }
+
+    #[test]
+    fn test_collect_rejects_path_outside_root() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("out");
+        std::fs::create_dir(&root).unwrap();
+        std::fs::write(dir.path().join("secret.txt"), "x").unwrap();
+
+        let err = artifact("../secret.txt")
+            .collect(
+                "secret",
+                "../secret.txt",
+                Some(root.to_str().unwrap()),
+                Some(root.to_str().unwrap()),
+            )
+            .unwrap_err();
+        assert!(err.to_string().contains("outside the artifacts root"));
+    }
+
+    #[test]
+    fn test_is_within() {
+        assert!(is_within(Path::new("/data/out/report.txt"), "/data/out"));
+        assert!(is_within(Path::new("/data/out/./a/../b.txt"), "/data/out"));
+        assert!(!is_within(Path::new("/data/out/../secret"), "/data/out"));
+        assert!(!is_within(Path::new("/data/output.txt"), "/data/out"));
+        assert!(is_within(Path::new("out/report.txt"), "out"));
+    }
+}
@@ -0,0 +1,166 @@
+#[cfg(all(test, feature = "async"))]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use crate::async_executor::AsyncCommandExecutor;
+    use crate::chain::Chain;
+    use crate::errors::{AtentoError, Result};
+    use crate::executor::{ExecutionRequest, ExecutionResult};
+    use std::sync::Mutex;
+
+    /// Answers every script with `{script}-done` on stdout and records the
+    /// scripts it was given.
+    #[derive(Default)]
+    struct AsyncMockExecutor {
+        scripts: Mutex<Vec<String>>,
+    }
+
+    impl AsyncCommandExecutor for AsyncMockExecutor {
+        async fn execute(&self, request: &ExecutionRequest<'_>) -> Result<ExecutionResult> {
+            tokio::task::yield_now().await;
+            self.scripts
+                .lock()
+                .unwrap()
+                .push(request.script.to_string());
+            Ok(ExecutionResult {
+                stdout: format!("{}-done", request.script),
+                exit_code: i32::from(request.script.starts_with("fail")),
+                duration_ms: 1,
+                ..ExecutionResult::default()
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_run_async_passes_outputs_between_steps() {
+        let chain: Chain = r"
+steps:
+  first:
+    type: bash
+    script: build
+    outputs:
+      artifact:
+        pattern: '(\S+)-done'
+  second:
+    type: bash
+    script: 'deploy {{ inputs.artifact }}'
+    inputs:
+      artifact:
+        ref: steps.first.outputs.artifact
+  each:
+    type: bash
+    script: 'check {{ inputs.item }}'
+    foreach: parameters.targets
+  broken:
+    type: bash
+    script: fail
+    on_failure: cleanup
+  cleanup:
+    type: bash
+    script: cleanup
+parameters:
+  targets:
+    value: 'a,b'
+results:
+  deployed:
+    ref: steps.second.stdout
+"
+        .parse()
+        .unwrap();
+        let executor = AsyncMockExecutor::default();
+
+        let result = chain.run_async_with_executor(&executor).await;
+
+        assert_eq!(
+            *executor.scripts.lock().unwrap(),
+            vec![
+                "build",
+                "deploy build",
+                "check a",
+                "check b",
+                "fail",
+                "cleanup"
+            ]
+        );
+        assert_eq!(result.status, "nok");
+        assert_eq!(result.results.unwrap()["deployed"], "deploy build-done");
+        let steps = result.steps.unwrap();
+        assert_eq!(steps["each"].iterations.len(), 2);
+        assert!(steps["cleanup"].error.is_none());
+        assert!(matches!(
+            result.errors.as_slice(),
+            [AtentoError::StepExecution { step, .. }] if step == "broken"
+        ));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_run_async_times_out_a_real_script() {
+        let chain: Chain = r"
+steps:
+  slow:
+    type: bash
+    timeout: 1
+    script: |
+      echo started
+      sleep 10
+  after:
+    type: bash
+    script: echo after
+"
+        .parse()
+        .unwrap();
+
+        // The future can move to another task, as with a multi-threaded runtime
+        let result = tokio::spawn(async move { chain.run_async().await })
+            .await
+            .unwrap();
+
+        assert_eq!(result.status, "nok");
+        assert!(result.duration_ms < 8_000, "took {}ms", result.duration_ms);
+        let steps = result.steps.unwrap();
+        let slow = &steps["slow"];
+        assert!(matches!(slow.error, Some(AtentoError::Timeout { .. })));
+        assert_eq!(slow.exit_code, crate::runner::TIMEOUT_EXIT_CODE);
+        assert_eq!(slow.stdout.as_deref(), Some("started"));
+        assert!(!steps.contains_key("after"));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_run_async_runs_scripts_without_blocking_the_runtime() {
+        let chain: Chain = r#"
+steps:
+  greet:
+    type: bash
+    script: |
+      sleep 0.5
+      echo "greeting=hello $NAME"
+    env:
+      NAME: async
+    outputs:
+      greeting:
+        pattern: 'greeting=(.*)'
+results:
+  greeting:
+    ref: steps.greet.outputs.greeting
+"#
+        .parse()
+        .unwrap();
+
+        // A ticker on the same single-threaded runtime keeps running while
+        // the script sleeps
+        let ticks = tokio::spawn(async {
+            let mut ticks = 0;
+            while ticks < 100 {
+                tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+                ticks += 1;
+            }
+        });
+        let result = chain.run_async().await;
+
+        assert_eq!(result.status, "ok", "{:?}", result.errors);
+        assert_eq!(result.results.unwrap()["greeting"], "hello async");
+        assert!(!ticks.is_finished());
+        ticks.abort();
+    }
+}
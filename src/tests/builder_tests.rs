@@ -0,0 +1,210 @@
+#[cfg(all(test, feature = "exec"))]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use crate::builder::{ChainBuilder, StepBuilder};
+    use crate::chain::Chain;
+    use crate::data_type::DataType;
+    use crate::executor::ExecutionResult;
+    use crate::interpreter::Interpreter;
+    use crate::output::{Output, OutputSource};
+    use crate::tests::mock_executor::MockExecutor;
+
+    fn ok(stdout: &str) -> ExecutionResult {
+        ExecutionResult {
+            stdout: stdout.to_string(),
+            duration_ms: 1,
+            ..ExecutionResult::default()
+        }
+    }
+
+    fn release_chain() -> ChainBuilder {
+        ChainBuilder::new()
+            .name("release")
+            .metadata("owner", "platform")
+            .timeout(120)
+            .parameter("project", DataType::String, "atento")
+            .step(
+                "version",
+                StepBuilder::new()
+                    .name("Read version")
+                    .interpreter("bash")
+                    .script("echo {{ inputs.project }} version=1.2.3")
+                    .input("project", "parameters.project")
+                    .output("version", r"version=([\d.]+)"),
+            )
+            .step(
+                "tag",
+                StepBuilder::new()
+                    .interpreter("bash")
+                    .script("echo v{{ inputs.version }}")
+                    .input("version", "steps.version.outputs.version"),
+            )
+            .result("version", "steps.version.outputs.version")
+    }
+
+    #[test]
+    fn test_builder_builds_valid_chain() {
+        let chain = release_chain().build();
+        chain.validate().unwrap();
+
+        assert_eq!(chain.name.as_deref(), Some("release"));
+        assert_eq!(chain.timeout, 120);
+        let ids: Vec<&str> = chain.steps.keys().map(String::as_str).collect();
+        assert_eq!(ids, vec!["version", "tag"]);
+        assert_eq!(chain.steps["version"].name.as_deref(), Some("Read version"));
+        for key in ["bash", "python", "node"] {
+            assert!(chain.interpreters.contains_key(key), "missing {key}");
+        }
+    }
+
+    #[test]
+    fn test_built_chain_runs_like_parsed_chain() {
+        let parsed: Chain = r"
+name: release
+metadata:
+  owner: platform
+timeout: 120
+parameters:
+  project:
+    value: atento
+steps:
+  version:
+    name: Read version
+    type: bash
+    script: echo {{ inputs.project }} version=1.2.3
+    inputs:
+      project:
+        ref: parameters.project
+    outputs:
+      version:
+        pattern: 'version=([\d.]+)'
+  tag:
+    type: bash
+    script: echo v{{ inputs.version }}
+    inputs:
+      version:
+        ref: steps.version.outputs.version
+results:
+  version:
+    ref: steps.version.outputs.version
+"
+        .parse()
+        .unwrap();
+
+        let run = |chain: &Chain| {
+            let mut mock = MockExecutor::new();
+            mock.expect_call("echo atento version=1.2.3", ok("atento version=1.2.3"))
+                .expect_call("echo v1.2.3", ok("v1.2.3"));
+            let mut result = chain.run_with_executor(&mock);
+            result.duration_ms = 0;
+            result.run_id = None;
+            result.started_at = None;
+            result.finished_at = None;
+            for step in result.steps.iter_mut().flat_map(|steps| steps.values_mut()) {
+                step.duration_ms = 0;
+                step.started_at = None;
+                step.finished_at = None;
+            }
+            result
+        };
+
+        let built = run(&release_chain().build());
+        assert_eq!(built.status, "ok", "errors: {:?}", built.errors);
+        assert_eq!(built.results.as_ref().unwrap()["version"], "1.2.3");
+        assert_eq!(built.metadata["owner"], "platform");
+        assert_eq!(built, run(&parsed));
+    }
+
+    #[test]
+    fn test_builder_surfaces_validation_errors() {
+        let built = ChainBuilder::new()
+            .step(
+                "greet",
+                StepBuilder::new()
+                    .interpreter("bash")
+                    .script("echo {{ inputs.who }}")
+                    .input("who", "parameters.missing"),
+            )
+            .build();
+        let parsed: Chain = r"
+steps:
+  greet:
+    type: bash
+    script: echo {{ inputs.who }}
+    inputs:
+      who:
+        ref: parameters.missing
+"
+        .parse()
+        .unwrap();
+
+        let err = built.validate().unwrap_err();
+        assert_eq!(err, parsed.validate().unwrap_err());
+
+        let untyped = ChainBuilder::new()
+            .step("greet", StepBuilder::new().script("echo hi"))
+            .build();
+        assert!(
+            untyped
+                .validate()
+                .unwrap_err()
+                .to_string()
+                .contains("has no 'type'")
+        );
+    }
+
+    #[test]
+    fn test_builder_custom_and_default_interpreters() {
+        let ruby = Interpreter {
+            command: "ruby".to_string(),
+            args: vec![],
+            extension: ".rb".to_string(),
+            fallbacks: vec![],
+        };
+        let chain = ChainBuilder::new()
+            .interpreter("ruby", ruby)
+            .default_interpreter("ruby")
+            .parallel(true)
+            .step("hello", StepBuilder::new().script("puts 1").timeout(5))
+            .build();
+        chain.validate().unwrap();
+
+        assert!(chain.parallel);
+        assert!(chain.interpreters.contains_key("bash"));
+        assert_eq!(
+            chain
+                .step_interpreter(&chain.steps["hello"], "hello")
+                .unwrap()
+                .extension,
+            ".rb"
+        );
+        assert_eq!(chain.steps["hello"].timeout, 5);
+    }
+
+    #[test]
+    fn test_step_builder_inline_inputs_and_custom_outputs() {
+        let step = StepBuilder::new()
+            .interpreter("bash")
+            .script("echo {{ inputs.count }}")
+            .input_value("count", DataType::Int, 3)
+            .output_with(
+                "warnings",
+                Output {
+                    pattern: "(warn.*)".to_string(),
+                    source: OutputSource::Stderr,
+                    ..Output::default()
+                },
+            )
+            .workdir("/tmp")
+            .continue_on_error(true)
+            .build();
+
+        step.validate("s").unwrap();
+        assert_eq!(step.inputs["count"].to_string_value().unwrap(), "3");
+        assert_eq!(step.outputs["warnings"].source, OutputSource::Stderr);
+        assert_eq!(step.outputs["warnings"].type_, DataType::String);
+        assert_eq!(step.workdir.as_deref(), Some("/tmp"));
+        assert!(step.continue_on_error);
+        assert_eq!(step.success_exit_codes, vec![0]);
+    }
+}
@@ -1,4 +1,4 @@
-#[cfg(test)]
+#[cfg(all(test, feature = "exec"))]
 #[allow(
     clippy::unwrap_used,
     clippy::expect_used,
@@ -14,10 +14,11 @@ mod tests {
     use crate::input::Input;
 
     use crate::interpreter::default_interpreters;
-    use crate::output::Output;
+    use crate::output::{Output, OutputSource, RegexCache};
     use crate::parameter::Parameter;
     use crate::result_ref::ResultRef;
     use crate::step::Step;
+    use indexmap::IndexMap;
     use std::collections::HashMap;
 
     // Helper to create a Chain with default interpreters populated
@@ -54,9 +55,33 @@ mod tests {
             name: None,
             timeout: 60,
             inputs: HashMap::new(),
-            interpreter: "bash".to_string(),
+            interpreter: Some("bash".to_string()),
             script: String::new(),
             outputs: HashMap::new(),
+            env: HashMap::new(),
+            retry: None,
+            continue_on_error: false,
+            retries: 0,
+            retry_delay_ms: 0,
+            success_exit_codes: vec![0],
+            allow_nonzero_exit: false,
+            workdir: None,
+            foreach: None,
+            capture: crate::step::Capture::Separate,
+            image: None,
+            interpreter_args: None,
+            stdin: None,
+            on_failure: None,
+            artifacts: HashMap::new(),
+            max_output_bytes: None,
+            quote: crate::step::Quote::None,
+            override_: false,
+            template: None,
+            script_file: None,
+            matrix: IndexMap::new(),
+            needs: Vec::new(),
+            timeout_signal: None,
+            timeout_grace_ms: None,
         };
         step.script = "echo test".to_string();
         step.inputs.insert(
@@ -81,7 +106,10 @@ mod tests {
             "name".to_string(),
             Parameter {
                 type_: DataType::String,
-                value: serde_yaml::Value::String("test".to_string()),
+                value: Some(serde_yaml::Value::String("test".to_string())),
+                secret: false,
+                separator: None,
+                required: false,
             },
         );
 
@@ -89,9 +117,33 @@ mod tests {
             name: None,
             timeout: 60,
             inputs: HashMap::new(),
-            interpreter: "bash".to_string(),
+            interpreter: Some("bash".to_string()),
             script: String::new(),
             outputs: HashMap::new(),
+            env: HashMap::new(),
+            retry: None,
+            continue_on_error: false,
+            retries: 0,
+            retry_delay_ms: 0,
+            success_exit_codes: vec![0],
+            allow_nonzero_exit: false,
+            workdir: None,
+            foreach: None,
+            capture: crate::step::Capture::Separate,
+            image: None,
+            interpreter_args: None,
+            stdin: None,
+            on_failure: None,
+            artifacts: HashMap::new(),
+            max_output_bytes: None,
+            quote: crate::step::Quote::None,
+            override_: false,
+            template: None,
+            script_file: None,
+            matrix: IndexMap::new(),
+            needs: Vec::new(),
+            timeout_signal: None,
+            timeout_grace_ms: None,
         };
         step.script = "echo {{ inputs.param }}".to_string();
         step.inputs.insert(
@@ -114,9 +166,33 @@ mod tests {
             name: None,
             timeout: 60,
             inputs: HashMap::new(),
-            interpreter: "bash".to_string(),
+            interpreter: Some("bash".to_string()),
             script: String::new(),
             outputs: HashMap::new(),
+            env: HashMap::new(),
+            retry: None,
+            continue_on_error: false,
+            retries: 0,
+            retry_delay_ms: 0,
+            success_exit_codes: vec![0],
+            allow_nonzero_exit: false,
+            workdir: None,
+            foreach: None,
+            capture: crate::step::Capture::Separate,
+            image: None,
+            interpreter_args: None,
+            stdin: None,
+            on_failure: None,
+            artifacts: HashMap::new(),
+            max_output_bytes: None,
+            quote: crate::step::Quote::None,
+            override_: false,
+            template: None,
+            script_file: None,
+            matrix: IndexMap::new(),
+            needs: Vec::new(),
+            timeout_signal: None,
+            timeout_grace_ms: None,
         };
         step1.script = "echo {{ inputs.value }}".to_string();
         step1.inputs.insert(
@@ -131,9 +207,33 @@ mod tests {
             name: None,
             timeout: 60,
             inputs: HashMap::new(),
-            interpreter: "bash".to_string(),
+            interpreter: Some("bash".to_string()),
             script: String::new(),
             outputs: HashMap::new(),
+            env: HashMap::new(),
+            retry: None,
+            continue_on_error: false,
+            retries: 0,
+            retry_delay_ms: 0,
+            success_exit_codes: vec![0],
+            allow_nonzero_exit: false,
+            workdir: None,
+            foreach: None,
+            capture: crate::step::Capture::Separate,
+            image: None,
+            interpreter_args: None,
+            stdin: None,
+            on_failure: None,
+            artifacts: HashMap::new(),
+            max_output_bytes: None,
+            quote: crate::step::Quote::None,
+            override_: false,
+            template: None,
+            script_file: None,
+            matrix: IndexMap::new(),
+            needs: Vec::new(),
+            timeout_signal: None,
+            timeout_grace_ms: None,
         };
         step2.script = "echo test".to_string();
         step2.outputs.insert(
@@ -141,6 +241,15 @@ mod tests {
             Output {
                 pattern: r"(.+)".to_string(),
                 type_: DataType::String,
+                group: None,
+                json_path: None,
+                key: None,
+                source: OutputSource::Stdout,
+                multiple: false,
+                optional: false,
+                delimiter: None,
+                default: None,
+                regex_cache: RegexCache::default(),
             },
         );
         wf.steps.insert("step2".to_string(), step2);
@@ -160,9 +269,33 @@ mod tests {
             name: None,
             timeout: 60,
             inputs: HashMap::new(),
-            interpreter: "bash".to_string(),
+            interpreter: Some("bash".to_string()),
             script: String::new(),
             outputs: HashMap::new(),
+            env: HashMap::new(),
+            retry: None,
+            continue_on_error: false,
+            retries: 0,
+            retry_delay_ms: 0,
+            success_exit_codes: vec![0],
+            allow_nonzero_exit: false,
+            workdir: None,
+            foreach: None,
+            capture: crate::step::Capture::Separate,
+            image: None,
+            interpreter_args: None,
+            stdin: None,
+            on_failure: None,
+            artifacts: HashMap::new(),
+            max_output_bytes: None,
+            quote: crate::step::Quote::None,
+            override_: false,
+            template: None,
+            script_file: None,
+            matrix: IndexMap::new(),
+            needs: Vec::new(),
+            timeout_signal: None,
+            timeout_grace_ms: None,
         };
         step1.script = "echo 'result: 42'".to_string();
         step1.outputs.insert(
@@ -170,6 +303,15 @@ mod tests {
             Output {
                 pattern: r"result: (\d+)".to_string(),
                 type_: DataType::Int,
+                group: None,
+                json_path: None,
+                key: None,
+                source: OutputSource::Stdout,
+                multiple: false,
+                optional: false,
+                delimiter: None,
+                default: None,
+                regex_cache: RegexCache::default(),
             },
         );
         wf.steps.insert("step1".to_string(), step1);
@@ -178,9 +320,33 @@ mod tests {
             name: None,
             timeout: 60,
             inputs: HashMap::new(),
-            interpreter: "bash".to_string(),
+            interpreter: Some("bash".to_string()),
             script: String::new(),
             outputs: HashMap::new(),
+            env: HashMap::new(),
+            retry: None,
+            continue_on_error: false,
+            retries: 0,
+            retry_delay_ms: 0,
+            success_exit_codes: vec![0],
+            allow_nonzero_exit: false,
+            workdir: None,
+            foreach: None,
+            capture: crate::step::Capture::Separate,
+            image: None,
+            interpreter_args: None,
+            stdin: None,
+            on_failure: None,
+            artifacts: HashMap::new(),
+            max_output_bytes: None,
+            quote: crate::step::Quote::None,
+            override_: false,
+            template: None,
+            script_file: None,
+            matrix: IndexMap::new(),
+            needs: Vec::new(),
+            timeout_signal: None,
+            timeout_grace_ms: None,
         };
         step2.script = "echo {{ inputs.prev }}".to_string();
         step2.inputs.insert(
@@ -202,9 +368,33 @@ mod tests {
             name: None,
             timeout: 60,
             inputs: HashMap::new(),
-            interpreter: "bash".to_string(),
+            interpreter: Some("bash".to_string()),
             script: String::new(),
             outputs: HashMap::new(),
+            env: HashMap::new(),
+            retry: None,
+            continue_on_error: false,
+            retries: 0,
+            retry_delay_ms: 0,
+            success_exit_codes: vec![0],
+            allow_nonzero_exit: false,
+            workdir: None,
+            foreach: None,
+            capture: crate::step::Capture::Separate,
+            image: None,
+            interpreter_args: None,
+            stdin: None,
+            on_failure: None,
+            artifacts: HashMap::new(),
+            max_output_bytes: None,
+            quote: crate::step::Quote::None,
+            override_: false,
+            template: None,
+            script_file: None,
+            matrix: IndexMap::new(),
+            needs: Vec::new(),
+            timeout_signal: None,
+            timeout_grace_ms: None,
         };
         step.script = "echo test".to_string();
         step.outputs.insert(
@@ -212,6 +402,15 @@ mod tests {
             Output {
                 pattern: String::new(),
                 type_: DataType::String,
+                group: None,
+                json_path: None,
+                key: None,
+                source: OutputSource::Stdout,
+                multiple: false,
+                optional: false,
+                delimiter: None,
+                default: None,
+                regex_cache: RegexCache::default(),
             },
         );
         wf.steps.insert("step1".to_string(), step);
@@ -232,9 +431,33 @@ mod tests {
                 name: None,
                 timeout: 60,
                 inputs: HashMap::new(),
-                interpreter: "bash".to_string(),
+                interpreter: Some("bash".to_string()),
                 script: String::new(),
                 outputs: HashMap::new(),
+                env: HashMap::new(),
+                retry: None,
+                continue_on_error: false,
+                retries: 0,
+                retry_delay_ms: 0,
+                success_exit_codes: vec![0],
+                allow_nonzero_exit: false,
+                workdir: None,
+                foreach: None,
+                capture: crate::step::Capture::Separate,
+                image: None,
+                interpreter_args: None,
+                stdin: None,
+                on_failure: None,
+                artifacts: HashMap::new(),
+                max_output_bytes: None,
+                quote: crate::step::Quote::None,
+                override_: false,
+                template: None,
+                script_file: None,
+                matrix: IndexMap::new(),
+                needs: Vec::new(),
+                timeout_signal: None,
+                timeout_grace_ms: None,
             }
         };
         wf.steps.insert("step1".to_string(), step);
@@ -242,7 +465,8 @@ mod tests {
             "final".to_string(),
             ResultRef {
                 ref_: "steps.step1.outputs.nonexistent".to_string(),
-            },
+            }
+            .into(),
         );
 
         let result = wf.validate();
@@ -261,9 +485,33 @@ mod tests {
                 name: None,
                 timeout: 60,
                 inputs: HashMap::new(),
-                interpreter: "bash".to_string(),
+                interpreter: Some("bash".to_string()),
                 script: String::new(),
                 outputs: HashMap::new(),
+                env: HashMap::new(),
+                retry: None,
+                continue_on_error: false,
+                retries: 0,
+                retry_delay_ms: 0,
+                success_exit_codes: vec![0],
+                allow_nonzero_exit: false,
+                workdir: None,
+                foreach: None,
+                capture: crate::step::Capture::Separate,
+                image: None,
+                interpreter_args: None,
+                stdin: None,
+                on_failure: None,
+                artifacts: HashMap::new(),
+                max_output_bytes: None,
+                quote: crate::step::Quote::None,
+                override_: false,
+                template: None,
+                script_file: None,
+                matrix: IndexMap::new(),
+                needs: Vec::new(),
+                timeout_signal: None,
+                timeout_grace_ms: None,
             }
         };
         step.outputs.insert(
@@ -271,6 +519,15 @@ mod tests {
             Output {
                 pattern: r"value: (\d+)".to_string(),
                 type_: DataType::Int,
+                group: None,
+                json_path: None,
+                key: None,
+                source: OutputSource::Stdout,
+                multiple: false,
+                optional: false,
+                delimiter: None,
+                default: None,
+                regex_cache: RegexCache::default(),
             },
         );
         wf.steps.insert("step1".to_string(), step);
@@ -278,7 +535,8 @@ mod tests {
             "final".to_string(),
             ResultRef {
                 ref_: "steps.step1.outputs.num".to_string(),
-            },
+            }
+            .into(),
         );
 
         let result = wf.validate();
@@ -304,9 +562,33 @@ mod tests {
                 name: None,
                 timeout: 60,
                 inputs: HashMap::new(),
-                interpreter: "bash".to_string(),
+                interpreter: Some("bash".to_string()),
                 script: String::new(),
                 outputs: HashMap::new(),
+                env: HashMap::new(),
+                retry: None,
+                continue_on_error: false,
+                retries: 0,
+                retry_delay_ms: 0,
+                success_exit_codes: vec![0],
+                allow_nonzero_exit: false,
+                workdir: None,
+                foreach: None,
+                capture: crate::step::Capture::Separate,
+                image: None,
+                interpreter_args: None,
+                stdin: None,
+                on_failure: None,
+                artifacts: HashMap::new(),
+                max_output_bytes: None,
+                quote: crate::step::Quote::None,
+                override_: false,
+                template: None,
+                script_file: None,
+                matrix: IndexMap::new(),
+                needs: Vec::new(),
+                timeout_signal: None,
+                timeout_grace_ms: None,
             }
         };
         wf.steps.insert("step1".to_string(), step);
@@ -316,9 +598,8 @@ mod tests {
             "echo hello",
             ExecutionResult {
                 stdout: "hello\n".to_string(),
-                stderr: String::new(),
-                exit_code: 0,
                 duration_ms: 10,
+                ..ExecutionResult::default()
             },
         );
 
@@ -340,9 +621,33 @@ mod tests {
                 name: None,
                 timeout: 60,
                 inputs: HashMap::new(),
-                interpreter: "bash".to_string(),
+                interpreter: Some("bash".to_string()),
                 script: String::new(),
                 outputs: HashMap::new(),
+                env: HashMap::new(),
+                retry: None,
+                continue_on_error: false,
+                retries: 0,
+                retry_delay_ms: 0,
+                success_exit_codes: vec![0],
+                allow_nonzero_exit: false,
+                workdir: None,
+                foreach: None,
+                capture: crate::step::Capture::Separate,
+                image: None,
+                interpreter_args: None,
+                stdin: None,
+                on_failure: None,
+                artifacts: HashMap::new(),
+                max_output_bytes: None,
+                quote: crate::step::Quote::None,
+                override_: false,
+                template: None,
+                script_file: None,
+                matrix: IndexMap::new(),
+                needs: Vec::new(),
+                timeout_signal: None,
+                timeout_grace_ms: None,
             }
         };
         let step2 = Step {
@@ -351,9 +656,33 @@ mod tests {
                 name: None,
                 timeout: 60,
                 inputs: HashMap::new(),
-                interpreter: "bash".to_string(),
+                interpreter: Some("bash".to_string()),
                 script: String::new(),
                 outputs: HashMap::new(),
+                env: HashMap::new(),
+                retry: None,
+                continue_on_error: false,
+                retries: 0,
+                retry_delay_ms: 0,
+                success_exit_codes: vec![0],
+                allow_nonzero_exit: false,
+                workdir: None,
+                foreach: None,
+                capture: crate::step::Capture::Separate,
+                image: None,
+                interpreter_args: None,
+                stdin: None,
+                on_failure: None,
+                artifacts: HashMap::new(),
+                max_output_bytes: None,
+                quote: crate::step::Quote::None,
+                override_: false,
+                template: None,
+                script_file: None,
+                matrix: IndexMap::new(),
+                needs: Vec::new(),
+                timeout_signal: None,
+                timeout_grace_ms: None,
             }
         };
 
@@ -373,7 +702,10 @@ mod tests {
             "greeting".to_string(),
             Parameter {
                 type_: DataType::String,
-                value: serde_yaml::Value::String("hello".to_string()),
+                value: Some(serde_yaml::Value::String("hello".to_string())),
+                secret: false,
+                separator: None,
+                required: false,
             },
         );
 
@@ -383,9 +715,33 @@ mod tests {
                 name: None,
                 timeout: 60,
                 inputs: HashMap::new(),
-                interpreter: "bash".to_string(),
+                interpreter: Some("bash".to_string()),
                 script: String::new(),
                 outputs: HashMap::new(),
+                env: HashMap::new(),
+                retry: None,
+                continue_on_error: false,
+                retries: 0,
+                retry_delay_ms: 0,
+                success_exit_codes: vec![0],
+                allow_nonzero_exit: false,
+                workdir: None,
+                foreach: None,
+                capture: crate::step::Capture::Separate,
+                image: None,
+                interpreter_args: None,
+                stdin: None,
+                on_failure: None,
+                artifacts: HashMap::new(),
+                max_output_bytes: None,
+                quote: crate::step::Quote::None,
+                override_: false,
+                template: None,
+                script_file: None,
+                matrix: IndexMap::new(),
+                needs: Vec::new(),
+                timeout_signal: None,
+                timeout_grace_ms: None,
             }
         };
         step.inputs.insert(
@@ -412,34 +768,29 @@ mod tests {
 
         let mut step1 = Step {
             script: "echo 'output: 42'".to_string(),
-            ..Step {
-                name: None,
-                timeout: 60,
-                inputs: HashMap::new(),
-                interpreter: "bash".to_string(),
-                script: String::new(),
-                outputs: HashMap::new(),
-            }
+            ..Step::new("bash")
         };
         step1.outputs.insert(
             "value".to_string(),
             Output {
                 pattern: r"output: (\d+)".to_string(),
                 type_: DataType::Int,
+                group: None,
+                json_path: None,
+                key: None,
+                source: OutputSource::Stdout,
+                multiple: false,
+                optional: false,
+                delimiter: None,
+                default: None,
+                regex_cache: RegexCache::default(),
             },
         );
         wf.steps.insert("step1".to_string(), step1);
 
         let mut step2 = Step {
             script: "echo {{ inputs.prev }}".to_string(),
-            ..Step {
-                name: None,
-                timeout: 60,
-                inputs: HashMap::new(),
-                interpreter: "bash".to_string(),
-                script: String::new(),
-                outputs: HashMap::new(),
-            }
+            ..Step::new("bash")
         };
         step2.inputs.insert(
             "prev".to_string(),
@@ -456,9 +807,8 @@ mod tests {
             "echo 'output: 42'",
             ExecutionResult {
                 stdout: "output: 42\n".to_string(),
-                stderr: String::new(),
-                exit_code: 0,
                 duration_ms: 10,
+                ..ExecutionResult::default()
             },
         );
 
@@ -467,9 +817,8 @@ mod tests {
             "echo 42",
             ExecutionResult {
                 stdout: "42\n".to_string(),
-                stderr: String::new(),
-                exit_code: 0,
                 duration_ms: 10,
+                ..ExecutionResult::default()
             },
         );
 
@@ -493,13 +842,37 @@ mod tests {
                 name: None,
                 timeout: 60,
                 inputs: HashMap::new(),
-                interpreter: if cfg!(windows) {
+                interpreter: Some(if cfg!(windows) {
                     "batch".to_string()
                 } else {
                     "bash".to_string()
-                },
+                }),
                 script: String::new(),
                 outputs: HashMap::new(),
+                env: HashMap::new(),
+                retry: None,
+                continue_on_error: false,
+                retries: 0,
+                retry_delay_ms: 0,
+                success_exit_codes: vec![0],
+                allow_nonzero_exit: false,
+                workdir: None,
+                foreach: None,
+                capture: crate::step::Capture::Separate,
+                image: None,
+                interpreter_args: None,
+                stdin: None,
+                on_failure: None,
+                artifacts: HashMap::new(),
+                max_output_bytes: None,
+                quote: crate::step::Quote::None,
+                override_: false,
+                template: None,
+                script_file: None,
+                matrix: IndexMap::new(),
+                needs: Vec::new(),
+                timeout_signal: None,
+                timeout_grace_ms: None,
             }
         };
         step.outputs.insert(
@@ -507,6 +880,15 @@ mod tests {
             Output {
                 pattern: r"final: (\w+)".to_string(),
                 type_: DataType::String,
+                group: None,
+                json_path: None,
+                key: None,
+                source: OutputSource::Stdout,
+                multiple: false,
+                optional: false,
+                delimiter: None,
+                default: None,
+                regex_cache: RegexCache::default(),
             },
         );
         wf.steps.insert("step1".to_string(), step);
@@ -515,7 +897,8 @@ mod tests {
             "outcome".to_string(),
             ResultRef {
                 ref_: "steps.step1.outputs.status".to_string(),
-            },
+            }
+            .into(),
         );
 
         let result = wf.run();
@@ -542,13 +925,37 @@ mod tests {
                 name: None,
                 timeout: 60,
                 inputs: HashMap::new(),
-                interpreter: if cfg!(windows) {
+                interpreter: Some(if cfg!(windows) {
                     "powershell".to_string()
                 } else {
                     "bash".to_string()
-                },
+                }),
                 script: String::new(),
                 outputs: HashMap::new(),
+                env: HashMap::new(),
+                retry: None,
+                continue_on_error: false,
+                retries: 0,
+                retry_delay_ms: 0,
+                success_exit_codes: vec![0],
+                allow_nonzero_exit: false,
+                workdir: None,
+                foreach: None,
+                capture: crate::step::Capture::Separate,
+                image: None,
+                interpreter_args: None,
+                stdin: None,
+                on_failure: None,
+                artifacts: HashMap::new(),
+                max_output_bytes: None,
+                quote: crate::step::Quote::None,
+                override_: false,
+                template: None,
+                script_file: None,
+                matrix: IndexMap::new(),
+                needs: Vec::new(),
+                timeout_signal: None,
+                timeout_grace_ms: None,
             }
         };
         wf.steps.insert("step1".to_string(), step);
@@ -579,9 +986,33 @@ mod tests {
                 name: None,
                 timeout: 60,
                 inputs: HashMap::new(),
-                interpreter: "bash".to_string(),
+                interpreter: Some("bash".to_string()),
                 script: String::new(),
                 outputs: HashMap::new(),
+                env: HashMap::new(),
+                retry: None,
+                continue_on_error: false,
+                retries: 0,
+                retry_delay_ms: 0,
+                success_exit_codes: vec![0],
+                allow_nonzero_exit: false,
+                workdir: None,
+                foreach: None,
+                capture: crate::step::Capture::Separate,
+                image: None,
+                interpreter_args: None,
+                stdin: None,
+                on_failure: None,
+                artifacts: HashMap::new(),
+                max_output_bytes: None,
+                quote: crate::step::Quote::None,
+                override_: false,
+                template: None,
+                script_file: None,
+                matrix: IndexMap::new(),
+                needs: Vec::new(),
+                timeout_signal: None,
+                timeout_grace_ms: None,
             }
         };
         step.outputs.insert(
@@ -589,6 +1020,15 @@ mod tests {
             Output {
                 pattern: r"result: (\d+)".to_string(),
                 type_: DataType::Int,
+                group: None,
+                json_path: None,
+                key: None,
+                source: OutputSource::Stdout,
+                multiple: false,
+                optional: false,
+                delimiter: None,
+                default: None,
+                regex_cache: RegexCache::default(),
             },
         );
         wf.steps.insert("step1".to_string(), step);
@@ -632,6 +1072,10 @@ name: minimal
             results: None,
             errors: Vec::new(),
             status: "ok".to_string(),
+            started_at: None,
+            finished_at: None,
+            run_id: None,
+            metadata: IndexMap::new(),
         };
 
         let json = serde_json::to_string(&result).unwrap();
@@ -639,6 +1083,93 @@ name: minimal
         assert!(json.contains("1000"));
     }
 
+    #[test]
+    fn test_chain_result_deserialize_roundtrip() {
+        use crate::chain::ChainResult;
+        use crate::retry::AttemptSummary;
+        use crate::step::StepResult;
+
+        let failure = AtentoError::StepExecution {
+            step: "deploy".to_string(),
+            reason: "exit 2".to_string(),
+        };
+        let build = StepResult {
+            name: Some("Build".to_string()),
+            duration_ms: 120,
+            attempts: 2,
+            attempt_history: vec![
+                AttemptSummary {
+                    exit_code: 1,
+                    duration_ms: 50,
+                    error: Some("flaky".to_string()),
+                },
+                AttemptSummary {
+                    exit_code: 0,
+                    duration_ms: 70,
+                    error: None,
+                },
+            ],
+            inputs: HashMap::from([("version".to_string(), "1.2".to_string())]),
+            outputs: HashMap::from([
+                ("artifact".to_string(), "app.tar".to_string()),
+                ("size".to_string(), "42".to_string()),
+                ("files".to_string(), "a\nb".to_string()),
+            ]),
+            typed_outputs: HashMap::from([
+                ("artifact".to_string(), serde_json::json!("app.tar")),
+                ("size".to_string(), serde_json::json!(42)),
+                ("files".to_string(), serde_json::json!(["a", "b"])),
+            ]),
+            env: vec!["TOKEN".to_string()],
+            script: Some("make build".to_string()),
+            stdout: Some("built".to_string()),
+            ..StepResult::default()
+        };
+        let deploy = StepResult {
+            duration_ms: 30,
+            exit_code: 2,
+            attempts: 1,
+            script: Some("make deploy".to_string()),
+            stderr: Some("denied".to_string()),
+            error: Some(failure.clone()),
+            ..StepResult::default()
+        };
+
+        let result = ChainResult {
+            name: Some("release".to_string()),
+            duration_ms: 150,
+            parameters: Some(HashMap::from([("env".to_string(), "prod".to_string())])),
+            steps: Some(
+                [("build".to_string(), build), ("deploy".to_string(), deploy)]
+                    .into_iter()
+                    .collect(),
+            ),
+            results: Some(HashMap::from([(
+                "artifact".to_string(),
+                "app.tar".to_string(),
+            )])),
+            errors: vec![
+                failure,
+                AtentoError::Io {
+                    path: "chain.yaml".to_string(),
+                    source: std::io::Error::new(std::io::ErrorKind::NotFound, "missing"),
+                },
+            ],
+            status: "nok".to_string(),
+            started_at: None,
+            finished_at: None,
+            run_id: None,
+            metadata: IndexMap::new(),
+        };
+
+        let json = serde_json::to_string(&result).unwrap();
+        let restored: ChainResult = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored, result);
+
+        let order: Vec<&String> = restored.steps.as_ref().unwrap().keys().collect();
+        assert_eq!(order, vec!["build", "deploy"]);
+    }
+
     #[test]
     fn test_chain_result_skip_none_fields() {
         use crate::chain::ChainResult;
@@ -651,6 +1182,10 @@ name: minimal
             results: None,
             errors: Vec::new(),
             status: "ok".to_string(),
+            started_at: None,
+            finished_at: None,
+            run_id: None,
+            metadata: IndexMap::new(),
         };
 
         let json = serde_json::to_string(&result).unwrap();
@@ -673,13 +1208,37 @@ name: minimal
                 name: None,
                 timeout: 60,
                 inputs: HashMap::new(),
-                interpreter: if cfg!(windows) {
+                interpreter: Some(if cfg!(windows) {
                     "batch".to_string()
                 } else {
                     "bash".to_string()
-                },
+                }),
                 script: String::new(),
                 outputs: HashMap::new(),
+                env: HashMap::new(),
+                retry: None,
+                continue_on_error: false,
+                retries: 0,
+                retry_delay_ms: 0,
+                success_exit_codes: vec![0],
+                allow_nonzero_exit: false,
+                workdir: None,
+                foreach: None,
+                capture: crate::step::Capture::Separate,
+                image: None,
+                interpreter_args: None,
+                stdin: None,
+                on_failure: None,
+                artifacts: HashMap::new(),
+                max_output_bytes: None,
+                quote: crate::step::Quote::None,
+                override_: false,
+                template: None,
+                script_file: None,
+                matrix: IndexMap::new(),
+                needs: Vec::new(),
+                timeout_signal: None,
+                timeout_grace_ms: None,
             }
         };
         step.inputs.insert(
@@ -687,6 +1246,7 @@ name: minimal
             Input::Inline {
                 type_: DataType::String,
                 value: serde_yaml::Value::String("inline_test".to_string()),
+                separator: None,
             },
         );
         wf.steps.insert("step1".to_string(), step);
@@ -714,14 +1274,20 @@ name: minimal
             "count".to_string(),
             Parameter {
                 type_: DataType::Int,
-                value: serde_yaml::Value::Number(42.into()),
+                value: Some(serde_yaml::Value::Number(42.into())),
+                secret: false,
+                separator: None,
+                required: false,
             },
         );
         wf.parameters.insert(
             "enabled".to_string(),
             Parameter {
                 type_: DataType::Bool,
-                value: serde_yaml::Value::Bool(true),
+                value: Some(serde_yaml::Value::Bool(true)),
+                secret: false,
+                separator: None,
+                required: false,
             },
         );
 
@@ -743,9 +1309,33 @@ name: minimal
                     name: None,
                     timeout: 60,
                     inputs: HashMap::new(),
-                    interpreter: "bash".to_string(),
+                    interpreter: Some("bash".to_string()),
                     script: String::new(),
                     outputs: HashMap::new(),
+                    env: HashMap::new(),
+                    retry: None,
+                    continue_on_error: false,
+                    retries: 0,
+                    retry_delay_ms: 0,
+                    success_exit_codes: vec![0],
+                    allow_nonzero_exit: false,
+                    workdir: None,
+                    foreach: None,
+                    capture: crate::step::Capture::Separate,
+                    image: None,
+                    interpreter_args: None,
+                    stdin: None,
+                    on_failure: None,
+                    artifacts: HashMap::new(),
+                    max_output_bytes: None,
+                    quote: crate::step::Quote::None,
+                    override_: false,
+                    template: None,
+                    script_file: None,
+                    matrix: IndexMap::new(),
+                    needs: Vec::new(),
+                    timeout_signal: None,
+                    timeout_grace_ms: None,
                 }
             };
             wf.steps.insert(format!("step{i}"), step);
@@ -775,9 +1365,33 @@ name: minimal
                 name: None,
                 timeout: 60,
                 inputs: HashMap::new(),
-                interpreter: interpreter.to_string(),
+                interpreter: Some(interpreter.to_string()),
                 script: String::new(),
                 outputs: HashMap::new(),
+                env: HashMap::new(),
+                retry: None,
+                continue_on_error: false,
+                retries: 0,
+                retry_delay_ms: 0,
+                success_exit_codes: vec![0],
+                allow_nonzero_exit: false,
+                workdir: None,
+                foreach: None,
+                capture: crate::step::Capture::Separate,
+                image: None,
+                interpreter_args: None,
+                stdin: None,
+                on_failure: None,
+                artifacts: HashMap::new(),
+                max_output_bytes: None,
+                quote: crate::step::Quote::None,
+                override_: false,
+                template: None,
+                script_file: None,
+                matrix: IndexMap::new(),
+                needs: Vec::new(),
+                timeout_signal: None,
+                timeout_grace_ms: None,
             }
         };
         let step2 = Step {
@@ -786,9 +1400,33 @@ name: minimal
                 name: None,
                 timeout: 60,
                 inputs: HashMap::new(),
-                interpreter: interpreter.to_string(),
+                interpreter: Some(interpreter.to_string()),
                 script: String::new(),
                 outputs: HashMap::new(),
+                env: HashMap::new(),
+                retry: None,
+                continue_on_error: false,
+                retries: 0,
+                retry_delay_ms: 0,
+                success_exit_codes: vec![0],
+                allow_nonzero_exit: false,
+                workdir: None,
+                foreach: None,
+                capture: crate::step::Capture::Separate,
+                image: None,
+                interpreter_args: None,
+                stdin: None,
+                on_failure: None,
+                artifacts: HashMap::new(),
+                max_output_bytes: None,
+                quote: crate::step::Quote::None,
+                override_: false,
+                template: None,
+                script_file: None,
+                matrix: IndexMap::new(),
+                needs: Vec::new(),
+                timeout_signal: None,
+                timeout_grace_ms: None,
             }
         };
 
@@ -814,8 +1452,11 @@ name: minimal
         chain.parameters.insert(
             "invalid_param".to_string(),
             Parameter {
-                value: serde_yaml::Value::Null,
+                value: Some(serde_yaml::Value::Null),
                 type_: crate::data_type::DataType::Int,
+                secret: false,
+                separator: None,
+                required: false,
             },
         );
         chain.steps.insert(
@@ -824,9 +1465,33 @@ name: minimal
                 name: None,
                 timeout: 60,
                 inputs: std::collections::HashMap::new(),
-                interpreter: "bash".to_string(),
+                interpreter: Some("bash".to_string()),
                 script: "echo 'test'".to_string(),
                 outputs: std::collections::HashMap::new(),
+                env: HashMap::new(),
+                retry: None,
+                continue_on_error: false,
+                retries: 0,
+                retry_delay_ms: 0,
+                success_exit_codes: vec![0],
+                allow_nonzero_exit: false,
+                workdir: None,
+                foreach: None,
+                capture: crate::step::Capture::Separate,
+                image: None,
+                interpreter_args: None,
+                stdin: None,
+                on_failure: None,
+                artifacts: HashMap::new(),
+                max_output_bytes: None,
+                quote: crate::step::Quote::None,
+                override_: false,
+                template: None,
+                script_file: None,
+                matrix: IndexMap::new(),
+                needs: Vec::new(),
+                timeout_signal: None,
+                timeout_grace_ms: None,
             },
         );
 
@@ -847,17 +1512,41 @@ name: minimal
                 name: None,
                 timeout: 60,
                 inputs: std::collections::HashMap::new(),
-                interpreter: if cfg!(windows) {
+                interpreter: Some(if cfg!(windows) {
                     "powershell".to_string()
                 } else {
                     "bash".to_string()
-                },
+                }),
                 script: if cfg!(windows) {
                     "Start-Sleep -Seconds 30; Write-Host 'done'".to_string()
                 } else {
                     "sleep 30 && echo 'done'".to_string()
                 },
                 outputs: std::collections::HashMap::new(),
+                env: HashMap::new(),
+                retry: None,
+                continue_on_error: false,
+                retries: 0,
+                retry_delay_ms: 0,
+                success_exit_codes: vec![0],
+                allow_nonzero_exit: false,
+                workdir: None,
+                foreach: None,
+                capture: crate::step::Capture::Separate,
+                image: None,
+                interpreter_args: None,
+                stdin: None,
+                on_failure: None,
+                artifacts: HashMap::new(),
+                max_output_bytes: None,
+                quote: crate::step::Quote::None,
+                override_: false,
+                template: None,
+                script_file: None,
+                matrix: IndexMap::new(),
+                needs: Vec::new(),
+                timeout_signal: None,
+                timeout_grace_ms: None,
             },
         );
 
@@ -891,16 +1580,41 @@ name: minimal
                 name: None,
                 timeout: 60,
                 inputs: std::collections::HashMap::new(),
-                interpreter: "bash".to_string(),
+                interpreter: Some("bash".to_string()),
                 script: "echo 'test'".to_string(),
                 outputs: std::collections::HashMap::new(), // No outputs defined
+                env: HashMap::new(),
+                retry: None,
+                continue_on_error: false,
+                retries: 0,
+                retry_delay_ms: 0,
+                success_exit_codes: vec![0],
+                allow_nonzero_exit: false,
+                workdir: None,
+                foreach: None,
+                capture: crate::step::Capture::Separate,
+                image: None,
+                interpreter_args: None,
+                stdin: None,
+                on_failure: None,
+                artifacts: HashMap::new(),
+                max_output_bytes: None,
+                quote: crate::step::Quote::None,
+                override_: false,
+                template: None,
+                script_file: None,
+                matrix: IndexMap::new(),
+                needs: Vec::new(),
+                timeout_signal: None,
+                timeout_grace_ms: None,
             },
         );
         chain.results.insert(
             "missing_result".to_string(),
             crate::result_ref::ResultRef {
                 ref_: "steps.test_step.outputs.nonexistent".to_string(),
-            },
+            }
+            .into(),
         );
 
         let result = chain.run();
@@ -913,14 +1627,18 @@ name: minimal
     }
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "exec"))]
 #[allow(clippy::unwrap_used)]
 mod unit_tests {
-    use crate::chain::Chain;
+    use crate::chain::{Chain, ValidationReport};
+    use crate::data_type::DataType;
     use crate::errors::AtentoError;
-
+    use crate::input::Input;
+    use crate::output::{Output, OutputSource, RegexCache};
     use crate::parameter::Parameter;
+    use crate::runner::{KillPolicy, TimeoutSignal};
     use crate::step::Step;
+    use indexmap::IndexMap;
     use std::collections::HashMap;
 
     // Pure unit tests for Chain struct (no I/O)
@@ -976,7 +1694,7 @@ steps:
 
     #[test]
     fn test_chain_validation_step_validation_error() {
-        let mut chain = Chain::default();
+        let mut chain = chain_with_interpreters();
         chain.steps.insert(
             "step1".to_string(),
             Step {
@@ -985,9 +1703,33 @@ steps:
                     name: None,
                     timeout: 60,
                     inputs: HashMap::new(),
-                    interpreter: "bash".to_string(),
+                    interpreter: Some("bash".to_string()),
                     script: String::new(),
                     outputs: HashMap::new(),
+                    env: HashMap::new(),
+                    retry: None,
+                    continue_on_error: false,
+                    retries: 0,
+                    retry_delay_ms: 0,
+                    success_exit_codes: vec![0],
+                    allow_nonzero_exit: false,
+                    workdir: None,
+                    foreach: None,
+                    capture: crate::step::Capture::Separate,
+                    image: None,
+                    interpreter_args: None,
+                    stdin: None,
+                    on_failure: None,
+                    artifacts: HashMap::new(),
+                    max_output_bytes: None,
+                    quote: crate::step::Quote::None,
+                    override_: false,
+                    template: None,
+                    script_file: None,
+                    matrix: IndexMap::new(),
+                    needs: Vec::new(),
+                    timeout_signal: None,
+                    timeout_grace_ms: None,
                 }
             },
         );
@@ -1001,7 +1743,7 @@ steps:
 
     #[test]
     fn test_chain_validation_success() {
-        let mut chain = Chain::default();
+        let mut chain = chain_with_interpreters();
         chain.steps.insert(
             "step1".to_string(),
             Step {
@@ -1010,9 +1752,33 @@ steps:
                     name: None,
                     timeout: 60,
                     inputs: HashMap::new(),
-                    interpreter: "bash".to_string(),
+                    interpreter: Some("bash".to_string()),
                     script: String::new(),
                     outputs: HashMap::new(),
+                    env: HashMap::new(),
+                    retry: None,
+                    continue_on_error: false,
+                    retries: 0,
+                    retry_delay_ms: 0,
+                    success_exit_codes: vec![0],
+                    allow_nonzero_exit: false,
+                    workdir: None,
+                    foreach: None,
+                    capture: crate::step::Capture::Separate,
+                    image: None,
+                    interpreter_args: None,
+                    stdin: None,
+                    on_failure: None,
+                    artifacts: HashMap::new(),
+                    max_output_bytes: None,
+                    quote: crate::step::Quote::None,
+                    override_: false,
+                    template: None,
+                    script_file: None,
+                    matrix: IndexMap::new(),
+                    needs: Vec::new(),
+                    timeout_signal: None,
+                    timeout_grace_ms: None,
                 }
             },
         );
@@ -1024,9 +1790,33 @@ steps:
                     name: None,
                     timeout: 60,
                     inputs: HashMap::new(),
-                    interpreter: "bash".to_string(),
+                    interpreter: Some("bash".to_string()),
                     script: String::new(),
                     outputs: HashMap::new(),
+                    env: HashMap::new(),
+                    retry: None,
+                    continue_on_error: false,
+                    retries: 0,
+                    retry_delay_ms: 0,
+                    success_exit_codes: vec![0],
+                    allow_nonzero_exit: false,
+                    workdir: None,
+                    foreach: None,
+                    capture: crate::step::Capture::Separate,
+                    image: None,
+                    interpreter_args: None,
+                    stdin: None,
+                    on_failure: None,
+                    artifacts: HashMap::new(),
+                    max_output_bytes: None,
+                    quote: crate::step::Quote::None,
+                    override_: false,
+                    template: None,
+                    script_file: None,
+                    matrix: IndexMap::new(),
+                    needs: Vec::new(),
+                    timeout_signal: None,
+                    timeout_grace_ms: None,
                 }
             },
         );
@@ -1114,7 +1904,10 @@ results:
             "test_param".to_string(),
             Parameter {
                 type_: crate::data_type::DataType::String,
-                value: serde_yaml::Value::String("test_value".to_string()),
+                value: Some(serde_yaml::Value::String("test_value".to_string())),
+                secret: false,
+                separator: None,
+                required: false,
             },
         );
 
@@ -1127,9 +1920,33 @@ results:
                     name: None,
                     timeout: 60,
                     inputs: HashMap::new(),
-                    interpreter: "bash".to_string(),
+                    interpreter: Some("bash".to_string()),
                     script: String::new(),
                     outputs: HashMap::new(),
+                    env: HashMap::new(),
+                    retry: None,
+                    continue_on_error: false,
+                    retries: 0,
+                    retry_delay_ms: 0,
+                    success_exit_codes: vec![0],
+                    allow_nonzero_exit: false,
+                    workdir: None,
+                    foreach: None,
+                    capture: crate::step::Capture::Separate,
+                    image: None,
+                    interpreter_args: None,
+                    stdin: None,
+                    on_failure: None,
+                    artifacts: HashMap::new(),
+                    max_output_bytes: None,
+                    quote: crate::step::Quote::None,
+                    override_: false,
+                    template: None,
+                    script_file: None,
+                    matrix: IndexMap::new(),
+                    needs: Vec::new(),
+                    timeout_signal: None,
+                    timeout_grace_ms: None,
                 }
             },
         );
@@ -1149,6 +1966,7 @@ results:
             command: "/bin/bash".to_string(),
             args: vec!["-c".to_string()],
             extension: ".sh".to_string(),
+            fallbacks: vec![],
         };
 
         chain
@@ -1161,10 +1979,34 @@ results:
             Step {
                 name: Some("Test Step".to_string()),
                 script: "echo 'custom interpreter'".to_string(),
-                interpreter: "bash".to_string(),
+                interpreter: Some("bash".to_string()),
                 timeout: 60,
                 inputs: HashMap::new(),
                 outputs: HashMap::new(),
+                env: HashMap::new(),
+                retry: None,
+                continue_on_error: false,
+                retries: 0,
+                retry_delay_ms: 0,
+                success_exit_codes: vec![0],
+                allow_nonzero_exit: false,
+                workdir: None,
+                foreach: None,
+                capture: crate::step::Capture::Separate,
+                image: None,
+                interpreter_args: None,
+                stdin: None,
+                on_failure: None,
+                artifacts: HashMap::new(),
+                max_output_bytes: None,
+                quote: crate::step::Quote::None,
+                override_: false,
+                template: None,
+                script_file: None,
+                matrix: IndexMap::new(),
+                needs: Vec::new(),
+                timeout_signal: None,
+                timeout_grace_ms: None,
             },
         );
 
@@ -1210,8 +2052,8 @@ results: {}
         assert!(chain.is_ok());
 
         let chain = chain.unwrap();
-        // Should have 6 defaults (bash, cmd, powershell, pwsh, python, python3), 2 override defaults (bash, python)
-        assert_eq!(chain.interpreters.len(), 6);
+        // Should have 9 defaults (bash, sh, zsh, cmd, powershell, pwsh, python, python3, node), 2 override defaults (bash, python)
+        assert_eq!(chain.interpreters.len(), 9);
 
         // Check bash config (overridden)
         let bash_config = chain.interpreters.get("bash").unwrap();
@@ -1230,4 +2072,4045 @@ results: {}
         assert!(chain.interpreters.contains_key("python"));
         assert!(chain.interpreters.contains_key("python3"));
     }
+
+    #[test]
+    fn test_chain_dry_run_renders_scripts() {
+        let yaml = r#"
+name: dry_run_chain
+parameters:
+  greeting:
+    value: "hello"
+steps:
+  first:
+    type: bash
+    script: echo "{{ inputs.msg }}"
+    inputs:
+      msg:
+        ref: parameters.greeting
+    outputs:
+      word:
+        pattern: "(\\w+)"
+  second:
+    type: bash
+    script: echo "got {{ inputs.prev }}"
+    inputs:
+      prev:
+        ref: steps.first.outputs.word
+results:
+  final:
+    ref: steps.first.outputs.word
+"#;
+
+        let chain: Chain = serde_yaml::from_str(yaml).unwrap();
+        chain.validate().unwrap();
+        let result = chain.dry_run();
+
+        assert_eq!(result.status, "dry-run");
+        assert!(result.errors.is_empty());
+
+        let steps = result.steps.unwrap();
+        assert_eq!(steps["first"].script.as_deref(), Some("echo \"hello\""));
+        assert_eq!(
+            steps["second"].script.as_deref(),
+            Some("echo \"got <steps.first.outputs.word>\"")
+        );
+        assert!(steps["second"].stdout.is_none());
+        assert_eq!(
+            result.results.unwrap()["final"],
+            "<steps.first.outputs.word>"
+        );
+
+        let json = serde_json::to_string(&chain.dry_run()).unwrap();
+        assert!(json.contains("\"status\":\"dry-run\""));
+    }
+
+    #[test]
+    fn test_chain_dry_run_unknown_interpreter() {
+        let mut wf = Chain::default();
+        let mut step = Step::new("unknown");
+        step.script = "echo hi".to_string();
+        wf.steps.insert("step1".to_string(), step);
+
+        let result = wf.dry_run();
+        assert_eq!(result.status, "nok");
+        assert!(matches!(result.errors[0], AtentoError::Validation(_)));
+    }
+
+    #[test]
+    fn test_chain_from_str_merges_default_interpreters() {
+        use std::str::FromStr;
+
+        let yaml = r"
+name: from_str_chain
+interpreters:
+  node:
+    command: node
+    extension: .js
+steps:
+  step1:
+    type: node
+    script: console.log('hi')
+";
+
+        let chain = Chain::from_str(yaml).unwrap();
+        assert_eq!(chain.name.as_deref(), Some("from_str_chain"));
+        assert!(chain.interpreters.contains_key("node"));
+        assert!(chain.interpreters.contains_key("bash"));
+        assert_eq!(chain.steps.len(), 1);
+    }
+
+    #[test]
+    fn test_chain_from_str_invalid_yaml() {
+        let result: Result<Chain, _> = "steps: [unclosed".parse();
+        match result {
+            Err(AtentoError::YamlParse { context, .. }) => assert_eq!(context, "<string>"),
+            other => panic!("Expected YamlParse error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_chain_from_reader() {
+        let yaml = b"steps:\n  step1:\n    type: bash\n    script: echo hi\n";
+        let chain = Chain::from_reader(&yaml[..]).unwrap();
+        assert!(chain.interpreters.contains_key("python3"));
+        assert_eq!(chain.steps["step1"].script, "echo hi");
+
+        let result = Chain::from_reader(&b"steps: [unclosed"[..]);
+        match result {
+            Err(AtentoError::YamlParse { context, .. }) => assert_eq!(context, "<reader>"),
+            other => panic!("Expected YamlParse error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_chain_env_passed_to_executor() {
+        use crate::tests::mock_executor::MockExecutor;
+
+        let yaml = r#"
+parameters:
+  token:
+    value: "s3cret"
+env:
+  SHARED:
+    value: "chain"
+  OVERRIDDEN:
+    value: "chain"
+steps:
+  first:
+    type: bash
+    script: echo "VERSION=1.2"
+    outputs:
+      version:
+        pattern: "VERSION=(.*)"
+  second:
+    type: bash
+    script: echo "$TOKEN"
+    env:
+      TOKEN:
+        ref: parameters.token
+      VERSION:
+        ref: steps.first.outputs.version
+      OVERRIDDEN:
+        value: "step"
+"#;
+
+        let chain: Chain = serde_yaml::from_str(yaml).unwrap();
+        chain.validate().unwrap();
+
+        let mut mock = MockExecutor::new();
+        mock.expect_call("echo \"VERSION=1.2\"", mock_output("VERSION=1.2"));
+
+        let result = chain.run_with_executor(&mock);
+        assert_eq!(result.status, "ok");
+
+        let env = mock.last_env().unwrap();
+        assert_eq!(env["SHARED"], "chain");
+        assert_eq!(env["OVERRIDDEN"], "step");
+        assert_eq!(env["TOKEN"], "s3cret");
+        assert_eq!(env["VERSION"], "1.2");
+
+        let steps = result.steps.unwrap();
+        assert_eq!(
+            steps["second"].env,
+            vec!["OVERRIDDEN", "SHARED", "TOKEN", "VERSION"]
+        );
+        let (script, _, _) = mock.last_call().unwrap();
+        assert!(!script.contains("s3cret"));
+
+        let json = serde_json::to_string(&steps["second"]).unwrap();
+        assert!(!json.contains("s3cret"));
+    }
+
+    #[test]
+    fn test_chain_env_forward_reference() {
+        let yaml = r#"
+env:
+  LATER:
+    ref: steps.second.outputs.value
+steps:
+  first:
+    type: bash
+    script: echo first
+  second:
+    type: bash
+    script: echo "VALUE=1"
+    outputs:
+      value:
+        pattern: "VALUE=(.*)"
+"#;
+
+        let chain: Chain = serde_yaml::from_str(yaml).unwrap();
+        match chain.validate() {
+            Err(AtentoError::Validation(msg)) => {
+                assert!(msg.contains("Env var 'LATER'"));
+                assert!(msg.contains("future step output"));
+            }
+            other => panic!("Expected validation error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_step_env_unresolved_reference() {
+        let yaml = r"
+steps:
+  first:
+    type: bash
+    script: echo first
+    env:
+      MISSING:
+        ref: parameters.missing
+";
+
+        let chain: Chain = serde_yaml::from_str(yaml).unwrap();
+        assert!(matches!(
+            chain.validate(),
+            Err(AtentoError::UnresolvedReference { .. })
+        ));
+    }
+
+    #[test]
+    fn test_chain_continue_on_error_runs_next_step() {
+        use crate::tests::mock_executor::MockExecutor;
+
+        let yaml = r#"
+steps:
+  build:
+    type: bash
+    script: echo build
+    continue_on_error: true
+    outputs:
+      artifact:
+        pattern: "ARTIFACT=(.*)"
+  cleanup:
+    type: bash
+    script: echo cleanup
+"#;
+
+        let chain: Chain = serde_yaml::from_str(yaml).unwrap();
+        assert!(chain.steps["build"].continue_on_error);
+        assert!(!chain.steps["cleanup"].continue_on_error);
+
+        let mut mock = MockExecutor::new();
+        mock.expect_call("echo build", mock_output("no artifact"));
+
+        let result = chain.run_with_executor(&mock);
+        assert_eq!(mock.call_count(), 2);
+        assert_eq!(result.status, "partial");
+        assert_eq!(result.errors.len(), 1);
+        assert!(matches!(
+            &result.errors[0],
+            AtentoError::StepExecution { step, .. } if step == "build"
+        ));
+
+        let steps = result.steps.unwrap();
+        assert!(steps["build"].error.is_some());
+        assert!(steps["cleanup"].error.is_none());
+    }
+
+    #[test]
+    fn test_chain_stops_on_error_without_continue() {
+        use crate::tests::mock_executor::MockExecutor;
+
+        let yaml = r#"
+steps:
+  build:
+    type: bash
+    script: echo build
+    outputs:
+      artifact:
+        pattern: "ARTIFACT=(.*)"
+  cleanup:
+    type: bash
+    script: echo cleanup
+"#;
+
+        let chain: Chain = serde_yaml::from_str(yaml).unwrap();
+        let mock = MockExecutor::new();
+        let result = chain.run_with_executor(&mock);
+
+        assert_eq!(mock.call_count(), 1);
+        assert_eq!(result.status, "nok");
+        assert!(!result.steps.unwrap().contains_key("cleanup"));
+    }
+
+    #[test]
+    fn test_chain_partial_status_with_dependent_step() {
+        use crate::tests::mock_executor::MockExecutor;
+
+        let yaml = r#"
+steps:
+  fetch:
+    type: bash
+    script: echo fetch
+    outputs:
+      data:
+        pattern: "DATA=(.*)"
+  transform:
+    type: bash
+    script: echo transform
+    continue_on_error: true
+    outputs:
+      result:
+        pattern: "RESULT=(.*)"
+  report:
+    type: bash
+    script: echo report
+  publish:
+    type: bash
+    script: echo "{{ inputs.result }}"
+    continue_on_error: true
+    inputs:
+      result:
+        ref: steps.transform.outputs.result
+  notify:
+    type: bash
+    script: echo notify
+"#;
+
+        let chain: Chain = serde_yaml::from_str(yaml).unwrap();
+        chain.validate().unwrap();
+
+        let mut mock = MockExecutor::new();
+        mock.expect_call("echo fetch", mock_output("DATA=raw"));
+        mock.expect_error("echo transform", 1, "boom");
+
+        let result = chain.run_with_executor(&mock);
+
+        // fetch, transform, report and notify ran; publish could not resolve its input
+        assert_eq!(mock.call_count(), 4);
+        assert_eq!(result.status, "partial");
+        assert_eq!(result.errors.len(), 2);
+        assert!(matches!(
+            &result.errors[1],
+            AtentoError::UnresolvedReference { reference, .. }
+                if reference == "steps.transform.outputs.result"
+        ));
+
+        let steps = result.steps.unwrap();
+        assert!(steps["transform"].error.is_some());
+        assert!(steps.contains_key("report"));
+        assert!(!steps.contains_key("publish"));
+        assert!(steps.contains_key("notify"));
+    }
+
+    #[test]
+    fn test_chain_nok_when_non_tolerant_step_fails_after_partial() {
+        use crate::tests::mock_executor::MockExecutor;
+
+        let yaml = r#"
+steps:
+  first:
+    type: bash
+    script: echo first
+    continue_on_error: true
+    outputs:
+      value:
+        pattern: "VALUE=(.*)"
+  second:
+    type: bash
+    script: echo second
+    outputs:
+      value:
+        pattern: "VALUE=(.*)"
+  third:
+    type: bash
+    script: echo third
+"#;
+
+        let chain: Chain = serde_yaml::from_str(yaml).unwrap();
+        let mock = MockExecutor::new();
+        let result = chain.run_with_executor(&mock);
+
+        assert_eq!(mock.call_count(), 2);
+        assert_eq!(result.status, "nok");
+        assert_eq!(result.errors.len(), 2);
+    }
+
+    #[test]
+    fn test_chain_without_fail_fast_runs_every_step_and_collects_all_errors() {
+        // The executor echoes the script, so outputs that look for
+        // `VERSION=` fail to extract
+        let yaml = r#"
+fail_fast: false
+steps:
+  lint:
+    type: bash
+    script: echo lint
+    outputs:
+      warnings:
+        pattern: "VERSION=(.*)"
+  build:
+    type: bash
+    script: echo build
+    outputs:
+      version:
+        pattern: "VERSION=(.*)"
+  publish:
+    type: bash
+    script: echo "{{ inputs.version }}"
+    inputs:
+      version:
+        ref: steps.build.outputs.version
+  docs:
+    type: bash
+    script: echo docs
+"#;
+
+        for parallel in [false, true] {
+            let mut chain: Chain = serde_yaml::from_str(yaml).unwrap();
+            chain.parallel = parallel;
+            chain.validate().unwrap();
+
+            let result = if parallel {
+                chain.run_parallel_with_executor(&SleepingExecutor::new(0))
+            } else {
+                chain.run_with_executor(&SleepingExecutor::new(0))
+            };
+
+            // lint and build failed, publish could not resolve its input, docs still ran
+            assert_eq!(result.status, "nok");
+            let mut failed: Vec<String> = result
+                .errors
+                .iter()
+                .map(|err| match err {
+                    AtentoError::StepExecution { step, .. } => step.clone(),
+                    AtentoError::UnresolvedReference { reference, .. } => reference.clone(),
+                    other => panic!("Unexpected error: {other:?}"),
+                })
+                .collect();
+            failed.sort();
+            assert_eq!(failed, ["build", "lint", "steps.build.outputs.version"]);
+            let steps = result.steps.unwrap();
+            assert!(!steps.contains_key("publish"));
+            assert!(steps["docs"].error.is_none());
+        }
+
+        assert!(Chain::default().fail_fast);
+        let chain: Chain = "steps: {}".parse().unwrap();
+        assert!(chain.fail_fast);
+    }
+
+    #[test]
+    fn test_chain_parameter_placeholders_in_script() {
+        use crate::tests::mock_executor::MockExecutor;
+
+        let yaml = r#"
+parameters:
+  target:
+    value: "staging"
+  replicas:
+    type: int
+    value: 3
+steps:
+  deploy:
+    type: bash
+    script: deploy --env {{ parameters.target }} --replicas {{parameters.replicas}}
+"#;
+
+        let chain: Chain = serde_yaml::from_str(yaml).unwrap();
+        chain.validate().unwrap();
+
+        let mock = MockExecutor::new();
+        let result = chain.run_with_executor(&mock);
+        assert_eq!(result.status, "ok");
+
+        let (script, _, _) = mock.last_call().unwrap();
+        assert_eq!(script, "deploy --env staging --replicas 3");
+
+        let dry = chain.dry_run();
+        assert_eq!(
+            dry.steps.unwrap()["deploy"].script.as_deref(),
+            Some("deploy --env staging --replicas 3")
+        );
+    }
+
+    #[test]
+    fn test_chain_validate_unknown_parameter_placeholder() {
+        let yaml = r"
+steps:
+  deploy:
+    type: bash
+    script: deploy --env {{ parameters.target }}
+";
+
+        let chain: Chain = serde_yaml::from_str(yaml).unwrap();
+        match chain.validate() {
+            Err(AtentoError::UnresolvedReference { reference, context }) => {
+                assert_eq!(reference, "parameters.target");
+                assert!(context.contains("deploy"));
+            }
+            other => panic!("Expected UnresolvedReference, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_chain_env_templates_substitute_inputs() {
+        use crate::tests::mock_executor::MockExecutor;
+
+        let yaml = r#"
+parameters:
+  region:
+    value: "eu-west-1"
+env:
+  REGION: "{{ parameters.region }}"
+steps:
+  first:
+    type: bash
+    script: echo first
+    env:
+      FIRST_ONLY: "yes"
+  second:
+    type: bash
+    script: echo "$USER_NAME"
+    inputs:
+      user:
+        value: "alice"
+    env:
+      USER_NAME: "user-{{ inputs.user }}"
+"#;
+
+        let chain: Chain = serde_yaml::from_str(yaml).unwrap();
+        chain.validate().unwrap();
+
+        let mock = MockExecutor::new();
+        let result = chain.run_with_executor(&mock);
+        assert_eq!(result.status, "ok");
+
+        let env = mock.last_env().unwrap();
+        assert_eq!(env["USER_NAME"], "user-alice");
+        assert_eq!(env["REGION"], "eu-west-1");
+        assert!(!env.contains_key("FIRST_ONLY"));
+
+        let (script, _, _) = mock.last_call().unwrap();
+        assert!(!script.contains("alice"));
+    }
+
+    #[test]
+    fn test_chain_env_template_undeclared_input() {
+        let yaml = r#"
+steps:
+  first:
+    type: bash
+    script: echo "$TOKEN"
+    env:
+      TOKEN: "{{ inputs.token }}"
+"#;
+
+        let chain: Chain = serde_yaml::from_str(yaml).unwrap();
+        assert!(matches!(
+            chain.validate(),
+            Err(AtentoError::Validation(msg)) if msg.contains("TOKEN") && msg.contains("token")
+        ));
+    }
+
+    #[test]
+    fn test_chain_env_template_unknown_parameter() {
+        let yaml = r#"
+env:
+  REGION: "{{ parameters.region }}"
+steps:
+  first:
+    type: bash
+    script: echo "$REGION"
+"#;
+
+        let chain: Chain = serde_yaml::from_str(yaml).unwrap();
+        assert!(matches!(
+            chain.validate(),
+            Err(AtentoError::UnresolvedReference { reference, .. }) if reference == "parameters.region"
+        ));
+    }
+
+    #[test]
+    fn test_chain_implicit_step_references() {
+        use crate::executor::ExecutionResult;
+        use crate::tests::mock_executor::MockExecutor;
+
+        let yaml = r#"
+steps:
+  check:
+    type: bash
+    script: ./check.sh
+    success_exit_codes: [0, 2]
+  report:
+    type: bash
+    script: echo "code={{ inputs.code }} out={{ inputs.out }} err={{ inputs.err }}"
+    inputs:
+      code:
+        ref: steps.check.exit_code
+      out:
+        ref: steps.check.stdout
+      err:
+        ref: steps.check.stderr
+results:
+  check_code:
+    ref: steps.check.exit_code
+  report_output:
+    ref: steps.report.stdout
+"#;
+
+        let chain: Chain = serde_yaml::from_str(yaml).unwrap();
+        chain.validate().unwrap();
+
+        let mut mock = MockExecutor::new();
+        mock.expect_call(
+            "./check.sh",
+            ExecutionResult {
+                stdout: "  drift found  \n".to_string(),
+                stderr: "warning".to_string(),
+                exit_code: 2,
+                duration_ms: 1,
+                ..ExecutionResult::default()
+            },
+        );
+        mock.expect_call(
+            "echo \"code=2 out=drift found err=warning\"",
+            mock_output("reported"),
+        );
+
+        let result = chain.run_with_executor(&mock);
+        assert_eq!(result.status, "ok");
+
+        let results = result.results.unwrap();
+        assert_eq!(results["check_code"], "2");
+        assert_eq!(results["report_output"], "reported");
+    }
+
+    #[test]
+    fn test_chain_implicit_reference_forward() {
+        let yaml = r#"
+steps:
+  first:
+    type: bash
+    script: echo "{{ inputs.code }}"
+    inputs:
+      code:
+        ref: steps.second.exit_code
+  second:
+    type: bash
+    script: echo second
+"#;
+
+        let chain: Chain = serde_yaml::from_str(yaml).unwrap();
+        assert!(matches!(
+            chain.validate(),
+            Err(AtentoError::Validation(msg)) if msg.contains("future step output")
+        ));
+    }
+
+    #[test]
+    fn test_chain_implicit_reference_unknown_step() {
+        let yaml = r"
+steps:
+  first:
+    type: bash
+    script: echo first
+results:
+  code:
+    ref: steps.missing.exit_code
+";
+
+        let chain: Chain = serde_yaml::from_str(yaml).unwrap();
+        assert!(matches!(
+            chain.validate(),
+            Err(AtentoError::UnresolvedReference { .. })
+        ));
+    }
+
+    #[test]
+    fn test_chain_secret_parameters_are_masked() {
+        use crate::executor::ExecutionResult;
+        use crate::tests::mock_executor::MockExecutor;
+
+        let yaml = r#"
+parameters:
+  api_token:
+    value: "tok-12345"
+    secret: true
+  user:
+    value: "alice"
+steps:
+  login:
+    type: bash
+    script: login {{ inputs.user }} {{ inputs.token }}
+    inputs:
+      user:
+        ref: parameters.user
+      token:
+        ref: parameters.api_token
+    outputs:
+      session:
+        pattern: "SESSION=(.*)"
+results:
+  session:
+    ref: steps.login.outputs.session
+"#;
+
+        let chain: Chain = serde_yaml::from_str(yaml).unwrap();
+        chain.validate().unwrap();
+
+        let mut mock = MockExecutor::new();
+        mock.expect_call(
+            "login alice tok-12345",
+            ExecutionResult {
+                stdout: "using tok-12345\nSESSION=tok-12345-abc".to_string(),
+                stderr: "debug: tok-12345".to_string(),
+                duration_ms: 1,
+                ..ExecutionResult::default()
+            },
+        );
+
+        let result = chain.run_with_executor(&mock);
+        assert_eq!(result.status, "ok");
+
+        // The real value is still used at execution time
+        let (script, _, _) = mock.last_call().unwrap();
+        assert_eq!(script, "login alice tok-12345");
+
+        let params = result.parameters.as_ref().unwrap();
+        assert_eq!(params["api_token"], "***");
+        assert_eq!(params["user"], "alice");
+
+        let steps = result.steps.as_ref().unwrap();
+        assert_eq!(steps["login"].inputs["token"], "***");
+        assert_eq!(steps["login"].inputs["user"], "alice");
+
+        let json = serde_json::to_string(&result).unwrap();
+        assert!(!json.contains("tok-12345"));
+
+        let dry_json = serde_json::to_string(&chain.dry_run()).unwrap();
+        assert!(!dry_json.contains("tok-12345"));
+    }
+
+    #[test]
+    fn test_chain_secret_parameters_are_masked_in_errors() {
+        use crate::executor::SystemExecutor;
+
+        let yaml = r#"
+parameters:
+  token:
+    value: hunter2
+    secret: true
+steps:
+  enter:
+    type: bash
+    script: pwd
+    workdir: "/nonexistent/{{ parameters.token }}"
+    retries: 1
+    retry_delay_ms: 0
+    continue_on_error: true
+  count:
+    type: bash
+    script: echo "n={{ parameters.token }}"
+    outputs:
+      n:
+        pattern: "n=(.*)"
+        type: int
+"#;
+        let chain: Chain = yaml.parse().unwrap();
+
+        let result = chain.run_with_executor(&SystemExecutor);
+
+        assert_eq!(result.status, "nok");
+        let steps = result.steps.as_ref().unwrap();
+        assert!(steps["enter"].error.is_some());
+        assert_eq!(steps["enter"].attempt_history.len(), 2);
+        assert!(steps["count"].error.is_some());
+        assert_eq!(result.errors.len(), 2);
+        let json = serde_json::to_string(&result).unwrap();
+        assert!(!json.contains("hunter2"), "json: {json}");
+        assert!(json.contains("/nonexistent/***"), "json: {json}");
+    }
+
+    #[test]
+    fn test_chain_workdir_substitution() {
+        use crate::tests::mock_executor::MockExecutor;
+
+        let yaml = r#"
+steps:
+  prepare:
+    type: bash
+    script: mktemp -d
+    outputs:
+      dir:
+        pattern: "DIR=(.*)"
+  build:
+    type: bash
+    script: make
+    workdir: "{{ inputs.dir }}/src"
+    inputs:
+      dir:
+        ref: steps.prepare.outputs.dir
+"#;
+
+        let chain: Chain = serde_yaml::from_str(yaml).unwrap();
+        chain.validate().unwrap();
+
+        let mut mock = MockExecutor::new();
+        mock.expect_call("mktemp -d", mock_output("DIR=/tmp/build"));
+
+        let result = chain.run_with_executor(&mock);
+        assert_eq!(result.status, "ok");
+        assert_eq!(mock.last_workdir().as_deref(), Some("/tmp/build/src"));
+    }
+
+    #[test]
+    fn test_chain_timeout_signal_and_grace_period_apply_to_steps() {
+        use crate::tests::mock_executor::MockExecutor;
+
+        let yaml = r"
+timeout_signal: SIGINT
+timeout_grace_ms: 2000
+steps:
+  build:
+    type: bash
+    script: make
+  deploy:
+    type: bash
+    script: ./deploy
+    timeout_signal: SIGKILL
+    timeout_grace_ms: 0
+";
+
+        let chain: Chain = serde_yaml::from_str(yaml).unwrap();
+        chain.validate().unwrap();
+        assert_eq!(chain.timeout_signal, TimeoutSignal::Int);
+
+        let mock = MockExecutor::new();
+        let only = |id: &str| {
+            let mut single = chain.clone();
+            single.steps.retain(|key, _| key == id);
+            assert_eq!(single.run_with_executor(&mock).status, "ok");
+            mock.last_kill().unwrap()
+        };
+        assert_eq!(
+            only("build"),
+            KillPolicy {
+                signal: TimeoutSignal::Int,
+                grace_ms: 2000,
+                ..KillPolicy::default()
+            }
+        );
+        assert_eq!(
+            only("deploy"),
+            KillPolicy {
+                signal: TimeoutSignal::Kill,
+                grace_ms: 0,
+                ..KillPolicy::default()
+            }
+        );
+
+        let err = serde_yaml::from_str::<Chain>("timeout_signal: SIGUSR1\n").unwrap_err();
+        assert!(err.to_string().contains("SIGUSR1"), "{err}");
+    }
+
+    #[test]
+    fn test_chain_workdir_undeclared_input() {
+        let yaml = r#"
+steps:
+  build:
+    type: bash
+    script: make
+    workdir: "{{ inputs.dir }}"
+"#;
+
+        let chain: Chain = serde_yaml::from_str(yaml).unwrap();
+        assert!(matches!(
+            chain.validate(),
+            Err(AtentoError::Validation(msg)) if msg.contains("workdir")
+        ));
+    }
+
+    #[test]
+    fn test_chain_exit_code_output_with_continue_on_error() {
+        use crate::tests::mock_executor::MockExecutor;
+
+        let yaml = r#"
+steps:
+  check:
+    type: bash
+    script: ./check.sh
+    continue_on_error: true
+    outputs:
+      code:
+        from: exit_code
+        type: int
+  report:
+    type: bash
+    inputs:
+      code:
+        ref: steps.check.outputs.code
+    script: echo "check exited with {{ inputs.code }}"
+results:
+  check_code:
+    ref: steps.check.outputs.code
+"#;
+
+        let chain: Chain = serde_yaml::from_str(yaml).unwrap();
+        chain.validate().unwrap();
+
+        let mut mock = MockExecutor::new();
+        mock.expect_error("./check.sh", 3, "check failed");
+        let result = chain.run_with_executor(&mock);
+
+        assert_eq!(mock.call_count(), 2);
+        assert_eq!(result.status, "partial");
+        assert_eq!(mock.last_call().unwrap().0, "echo \"check exited with 3\"");
+
+        let steps = result.steps.unwrap();
+        assert_eq!(steps["check"].typed_outputs["code"], serde_json::json!(3));
+        assert_eq!(result.results.unwrap()["check_code"], "3");
+    }
+
+    #[test]
+    fn test_chain_multiple_output_joined_downstream() {
+        use crate::tests::mock_executor::MockExecutor;
+
+        let yaml = r#"
+steps:
+  test:
+    type: bash
+    script: ./run-tests.sh
+    outputs:
+      failed:
+        pattern: "FAILED_TEST=(.*)"
+        multiple: true
+        delimiter: ","
+  report:
+    type: bash
+    inputs:
+      failed:
+        ref: steps.test.outputs.failed
+    script: echo "{{ inputs.failed }}"
+"#;
+
+        let chain: Chain = serde_yaml::from_str(yaml).unwrap();
+        chain.validate().unwrap();
+
+        let mut mock = MockExecutor::new();
+        mock.expect_call(
+            "./run-tests.sh",
+            mock_output("FAILED_TEST=auth\nFAILED_TEST=billing\n"),
+        );
+
+        let result = chain.run_with_executor(&mock);
+        assert_eq!(result.status, "ok");
+        assert_eq!(mock.last_call().unwrap().0, "echo \"auth,billing\"");
+
+        let json = serde_json::to_value(&result).unwrap();
+        assert_eq!(
+            json["steps"]["test"]["typed_outputs"]["failed"],
+            serde_json::json!(["auth", "billing"])
+        );
+    }
+
+    #[test]
+    fn test_chain_optional_outputs_downstream() {
+        use crate::tests::mock_executor::MockExecutor;
+
+        let yaml = r#"
+steps:
+  build:
+    type: bash
+    script: ./build.sh
+    outputs:
+      warnings:
+        pattern: "warnings=([0-9]+)"
+        type: int
+        optional: true
+        default: 0
+      errors:
+        pattern: "errors=([0-9]+)"
+        optional: true
+  report:
+    type: bash
+    inputs:
+      warnings:
+        ref: steps.build.outputs.warnings
+    script: echo "warnings {{ inputs.warnings }}"
+  fail_report:
+    type: bash
+    inputs:
+      errors:
+        ref: steps.build.outputs.errors
+    script: echo "errors {{ inputs.errors }}"
+"#;
+
+        let chain: Chain = serde_yaml::from_str(yaml).unwrap();
+        chain.validate().unwrap();
+
+        let mock = MockExecutor::new();
+        let result = chain.run_with_executor(&mock);
+
+        assert_eq!(mock.call_count(), 2);
+        assert_eq!(mock.last_call().unwrap().0, "echo \"warnings 0\"");
+        assert_eq!(result.status, "nok");
+        match &result.errors[0] {
+            AtentoError::UnresolvedReference { reference, context } => {
+                assert_eq!(reference, "steps.build.outputs.errors");
+                assert!(context.contains("optional output did not match"));
+            }
+            other => panic!("Expected UnresolvedReference, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_chain_results_from_parameters_and_literals() {
+        use crate::tests::mock_executor::MockExecutor;
+
+        let yaml = r"
+parameters:
+  env:
+    value: staging
+steps:
+  deploy:
+    type: bash
+    script: echo deploy
+results:
+  environment:
+    ref: parameters.env
+  version:
+    value: 1.2.3
+  replicas:
+    type: int
+    value: 3
+";
+
+        let chain: Chain = serde_yaml::from_str(yaml).unwrap();
+        chain.validate().unwrap();
+
+        let mock = MockExecutor::new();
+        let result = chain.run_with_executor(&mock);
+        assert_eq!(result.status, "ok");
+
+        let results = result.results.unwrap();
+        assert_eq!(results["environment"], "staging");
+        assert_eq!(results["version"], "1.2.3");
+        assert_eq!(results["replicas"], "3");
+    }
+
+    #[test]
+    fn test_chain_validate_result_unknown_parameter() {
+        let yaml = r"
+steps:
+  deploy:
+    type: bash
+    script: echo deploy
+results:
+  environment:
+    ref: parameters.missing
+";
+
+        let chain: Chain = serde_yaml::from_str(yaml).unwrap();
+        match chain.validate() {
+            Err(AtentoError::UnresolvedReference { reference, context }) => {
+                assert_eq!(reference, "parameters.missing");
+                assert!(context.contains("environment"));
+            }
+            other => panic!("Expected UnresolvedReference, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_chain_validate_result_inline_type_mismatch() {
+        let yaml = r"
+steps:
+  deploy:
+    type: bash
+    script: echo deploy
+results:
+  replicas:
+    type: int
+    value: many
+";
+
+        let chain: Chain = serde_yaml::from_str(yaml).unwrap();
+        let err = chain.validate().unwrap_err();
+        assert!(matches!(err, AtentoError::Validation(_)));
+        assert!(err.to_string().contains("replicas"));
+    }
+
+    /// Thread-safe executor that sleeps before echoing the script back, recording
+    /// when each script starts and finishes.
+    struct SleepingExecutor {
+        delay: std::time::Duration,
+        events: std::sync::Mutex<Vec<String>>,
+    }
+
+    impl SleepingExecutor {
+        fn new(delay_ms: u64) -> Self {
+            Self {
+                delay: std::time::Duration::from_millis(delay_ms),
+                events: std::sync::Mutex::new(Vec::new()),
+            }
+        }
+
+        fn events(&self) -> Vec<String> {
+            match self.events.lock() {
+                Ok(events) => events.clone(),
+                Err(poisoned) => poisoned.into_inner().clone(),
+            }
+        }
+
+        fn record(&self, event: String) {
+            match self.events.lock() {
+                Ok(mut events) => events.push(event),
+                Err(poisoned) => poisoned.into_inner().push(event),
+            }
+        }
+    }
+
+    impl crate::executor::CommandExecutor for SleepingExecutor {
+        fn execute(
+            &self,
+            request: &crate::executor::ExecutionRequest<'_>,
+        ) -> crate::errors::Result<crate::executor::ExecutionResult> {
+            self.record(format!("start {}", request.script));
+            std::thread::sleep(self.delay);
+            self.record(format!("end {}", request.script));
+            Ok(crate::executor::ExecutionResult {
+                stdout: request.script.to_string(),
+                ..crate::executor::ExecutionResult::default()
+            })
+        }
+    }
+
+    fn capture(pattern: &str) -> Output {
+        Output {
+            pattern: pattern.to_string(),
+            type_: DataType::String,
+            group: None,
+            json_path: None,
+            key: None,
+            source: OutputSource::Stdout,
+            multiple: false,
+            optional: false,
+            delimiter: None,
+            default: None,
+            regex_cache: RegexCache::default(),
+        }
+    }
+
+    fn chain_with_interpreters() -> Chain {
+        Chain {
+            interpreters: crate::interpreter::default_interpreters()
+                .into_iter()
+                .collect(),
+            ..Chain::default()
+        }
+    }
+
+    fn echo_step(script: &str) -> Step {
+        let mut step = Step::new("bash");
+        step.script = script.to_string();
+        step
+    }
+
+    #[test]
+    fn test_chain_run_parallel_runs_independent_steps_concurrently() {
+        let mut chain = chain_with_interpreters();
+        for name in ["first", "second", "third"] {
+            chain.steps.insert(name.to_string(), echo_step(name));
+        }
+
+        let executor = SleepingExecutor::new(300);
+        let start = std::time::Instant::now();
+        let result = chain.run_parallel_with_executor(&executor);
+
+        assert_eq!(result.status, "ok");
+        assert!(start.elapsed() < std::time::Duration::from_millis(800));
+        let steps = result.steps.unwrap();
+        let order: Vec<&str> = steps.keys().map(String::as_str).collect();
+        assert_eq!(order, vec!["first", "second", "third"]);
+    }
+
+    #[test]
+    fn test_chain_run_parallel_waits_for_referenced_steps() {
+        let mut chain = chain_with_interpreters();
+
+        let mut producer = echo_step("produce");
+        producer
+            .outputs
+            .insert("value".to_string(), capture("(.+)"));
+
+        let mut consumer = echo_step("consume {{ inputs.value }}");
+        consumer.inputs.insert(
+            "value".to_string(),
+            Input::Ref {
+                ref_: "steps.producer.outputs.value".to_string(),
+            },
+        );
+
+        // Declared first, but must wait for the producer
+        chain.steps.insert("consumer".to_string(), consumer);
+        chain.steps.insert("producer".to_string(), producer);
+        chain.steps.insert("other".to_string(), echo_step("other"));
+
+        let executor = SleepingExecutor::new(100);
+        let result = chain.run_parallel_with_executor(&executor);
+
+        assert_eq!(result.status, "ok", "errors: {:?}", result.errors);
+        let events = executor.events();
+        let producer_end = events.iter().position(|e| e == "end produce").unwrap();
+        let consumer_start = events
+            .iter()
+            .position(|e| e == "start consume produce")
+            .unwrap();
+        assert!(producer_end < consumer_start, "events: {events:?}");
+
+        let steps = result.steps.unwrap();
+        let order: Vec<&str> = steps.keys().map(String::as_str).collect();
+        assert_eq!(order, vec!["consumer", "producer", "other"]);
+    }
+
+    #[test]
+    fn test_chain_run_parallel_stops_on_step_failure() {
+        let mut chain = chain_with_interpreters();
+
+        let mut failing = echo_step("fail");
+        failing
+            .outputs
+            .insert("missing".to_string(), capture(r"never (\d+)"));
+        let mut dependent = echo_step("after {{ inputs.value }}");
+        dependent.inputs.insert(
+            "value".to_string(),
+            Input::Ref {
+                ref_: "steps.failing.outputs.missing".to_string(),
+            },
+        );
+        chain.steps.insert("failing".to_string(), failing);
+        chain.steps.insert("dependent".to_string(), dependent);
+
+        let executor = SleepingExecutor::new(10);
+        let result = chain.run_parallel_with_executor(&executor);
+
+        assert_eq!(result.status, "nok");
+        assert!(!result.errors.is_empty());
+        assert!(!result.steps.unwrap().contains_key("dependent"));
+        assert!(executor.events().iter().all(|e| !e.contains("after")));
+    }
+
+    #[test]
+    fn test_chain_run_parallel_notifies_observer() {
+        #[derive(Default)]
+        struct CountingObserver {
+            started: Vec<String>,
+            completed: Vec<String>,
+            chain_status: Option<String>,
+        }
+
+        impl crate::observer::ChainObserver for CountingObserver {
+            fn on_step_start(&mut self, step_id: &str, _step_name: Option<&str>) {
+                self.started.push(step_id.to_string());
+            }
+
+            fn on_step_complete(&mut self, step_id: &str, _result: &crate::step::StepResult) {
+                assert!(self.started.iter().any(|started| started == step_id));
+                self.completed.push(step_id.to_string());
+            }
+
+            fn on_chain_complete(&mut self, result: &crate::chain::ChainResult) {
+                self.chain_status = Some(result.status.clone());
+            }
+        }
+
+        let mut chain = chain_with_interpreters();
+        chain.steps.insert("first".to_string(), echo_step("first"));
+        chain
+            .steps
+            .insert("second".to_string(), echo_step("second"));
+
+        let executor = SleepingExecutor::new(10);
+        let mut observer = CountingObserver::default();
+        let result = chain.run_parallel_with_observer(&executor, &mut observer);
+
+        assert_eq!(result.status, "ok");
+        assert_eq!(observer.started.len(), 2);
+        assert_eq!(observer.completed.len(), 2);
+        assert_eq!(observer.chain_status.as_deref(), Some("ok"));
+    }
+
+    #[test]
+    fn test_chain_parallel_flag_deserializes() {
+        let yaml = r"
+parallel: true
+steps:
+  build:
+    type: bash
+    script: echo build
+";
+
+        let chain: Chain = serde_yaml::from_str(yaml).unwrap();
+        assert!(chain.parallel);
+        assert!(!Chain::default().parallel);
+    }
+
+    fn mock_output(stdout: &str) -> crate::executor::ExecutionResult {
+        crate::executor::ExecutionResult {
+            stdout: stdout.to_string(),
+            duration_ms: 1,
+            ..crate::executor::ExecutionResult::default()
+        }
+    }
+
+    #[test]
+    fn test_chain_foreach_runs_step_per_item() {
+        use crate::tests::mock_executor::MockExecutor;
+
+        let yaml = r#"
+parameters:
+  targets:
+    value: "eu, us"
+steps:
+  deploy:
+    type: bash
+    foreach: parameters.targets
+    script: deploy {{ inputs.item }}
+    outputs:
+      url:
+        pattern: "url=(\\S+)"
+  report:
+    type: bash
+    script: report {{ inputs.urls }}
+    inputs:
+      urls:
+        ref: steps.deploy.outputs.url
+"#;
+        let chain: Chain = yaml.parse().unwrap();
+        chain.validate().unwrap();
+
+        let mut mock = MockExecutor::new();
+        mock.expect_call("deploy eu", mock_output("url=eu.example"))
+            .expect_call("deploy us", mock_output("url=us.example"))
+            .expect_call(
+                r#"report {"0":"eu.example","1":"us.example"}"#,
+                mock_output("ok"),
+            );
+
+        let result = chain.run_with_executor(&mock);
+        assert_eq!(result.status, "ok", "errors: {:?}", result.errors);
+
+        let steps = result.steps.unwrap();
+        let deploy = &steps["deploy"];
+        assert_eq!(deploy.iterations.len(), 2);
+        assert_eq!(deploy.iterations[1].inputs["item"], "us");
+        assert!(!deploy.inputs.contains_key("item"));
+        assert_eq!(
+            deploy.typed_outputs["url"],
+            serde_json::json!({"0": "eu.example", "1": "us.example"})
+        );
+        assert_eq!(mock.call_count(), 3);
+    }
+
+    #[test]
+    fn test_chain_foreach_over_step_output_list() {
+        use crate::tests::mock_executor::MockExecutor;
+
+        let yaml = r#"
+steps:
+  list:
+    type: bash
+    script: ls
+    outputs:
+      files:
+        pattern: "(\\w+\\.txt)"
+        multiple: true
+  read:
+    type: bash
+    foreach: steps.list.outputs.files
+    script: cat {{ inputs.item }}
+"#;
+        let chain: Chain = yaml.parse().unwrap();
+        chain.validate().unwrap();
+
+        let mut mock = MockExecutor::new();
+        mock.expect_call("ls", mock_output("a.txt b.txt"))
+            .expect_call("cat a.txt", mock_output("A"))
+            .expect_call("cat b.txt", mock_output("B"));
+
+        let result = chain.run_with_executor(&mock);
+        assert_eq!(result.status, "ok", "errors: {:?}", result.errors);
+        let steps = result.steps.unwrap();
+        assert_eq!(steps["read"].stdout.as_deref(), Some("A\nB"));
+    }
+
+    #[test]
+    fn test_chain_foreach_failing_item_stops_iterations() {
+        use crate::tests::mock_executor::MockExecutor;
+
+        let yaml = r#"
+parameters:
+  items:
+    value: "a,b,c"
+steps:
+  each:
+    type: bash
+    foreach: parameters.items
+    script: run {{ inputs.item }}
+"#;
+        let chain: Chain = yaml.parse().unwrap();
+
+        let mut mock = MockExecutor::new();
+        mock.expect_call("run a", mock_output("a"))
+            .expect_error("run b", 2, "boom")
+            .expect_call("run c", mock_output("c"));
+
+        let result = chain.run_with_executor(&mock);
+        assert_eq!(result.status, "nok");
+        let steps = result.steps.unwrap();
+        assert_eq!(steps["each"].iterations.len(), 2);
+        assert_eq!(steps["each"].exit_code, 2);
+        assert_eq!(mock.call_count(), 2);
+    }
+
+    #[test]
+    fn test_chain_foreach_continue_on_error_runs_every_item() {
+        use crate::tests::mock_executor::MockExecutor;
+
+        let yaml = r#"
+parameters:
+  items:
+    value: "a,b,c"
+steps:
+  each:
+    type: bash
+    foreach: parameters.items
+    continue_on_error: true
+    script: run {{ inputs.item }}
+"#;
+        let chain: Chain = yaml.parse().unwrap();
+
+        let mut mock = MockExecutor::new();
+        mock.expect_call("run a", mock_output("a"))
+            .expect_error("run b", 2, "boom")
+            .expect_call("run c", mock_output("c"));
+
+        let result = chain.run_with_executor(&mock);
+        assert_eq!(result.status, "partial");
+        let steps = result.steps.unwrap();
+        assert_eq!(steps["each"].iterations.len(), 3);
+        assert!(steps["each"].error.is_some());
+        assert_eq!(mock.call_count(), 3);
+    }
+
+    #[test]
+    fn test_chain_validate_foreach_unknown_reference() {
+        let yaml = r"
+steps:
+  each:
+    type: bash
+    foreach: parameters.missing
+    script: echo {{ inputs.item }}
+";
+        let chain: Chain = yaml.parse().unwrap();
+        match chain.validate() {
+            Err(AtentoError::UnresolvedReference { reference, .. }) => {
+                assert_eq!(reference, "parameters.missing");
+            }
+            other => panic!("Expected UnresolvedReference, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_chain_input_default_for_unmatched_optional_output() {
+        use crate::tests::mock_executor::MockExecutor;
+
+        let yaml = r#"
+steps:
+  probe:
+    type: bash
+    script: probe
+    outputs:
+      region:
+        pattern: "region=(\\w+)"
+        optional: true
+  deploy:
+    type: bash
+    script: deploy {{ inputs.region | default("eu") }}
+    inputs:
+      region:
+        ref: steps.probe.outputs.region
+"#;
+        let chain: Chain = yaml.parse().unwrap();
+        chain.validate().unwrap();
+
+        let mut mock = MockExecutor::new();
+        mock.expect_call("probe", mock_output("nothing"))
+            .expect_call("deploy eu", mock_output("deployed"));
+
+        let result = chain.run_with_executor(&mock);
+        assert_eq!(result.status, "ok", "errors: {:?}", result.errors);
+        let (script, _, _) = mock.last_call().unwrap();
+        assert_eq!(script, "deploy eu");
+    }
+
+    #[test]
+    fn test_chain_input_without_default_still_fails_when_unresolved() {
+        use crate::tests::mock_executor::MockExecutor;
+
+        let yaml = r#"
+steps:
+  probe:
+    type: bash
+    script: probe
+    outputs:
+      region:
+        pattern: "region=(\\w+)"
+        optional: true
+  deploy:
+    type: bash
+    script: deploy {{ inputs.region }}
+    inputs:
+      region:
+        ref: steps.probe.outputs.region
+"#;
+        let chain: Chain = yaml.parse().unwrap();
+
+        let mut mock = MockExecutor::new();
+        mock.expect_call("probe", mock_output("nothing"));
+
+        let result = chain.run_with_executor(&mock);
+        assert_eq!(result.status, "nok");
+        assert!(matches!(
+            result.errors.first(),
+            Some(AtentoError::UnresolvedReference { .. })
+        ));
+    }
+
+    #[test]
+    fn test_chain_list_parameter_substitution() {
+        use crate::tests::mock_executor::MockExecutor;
+
+        let yaml = r#"
+parameters:
+  flags:
+    type: list
+    value: ["-v", "--fast"]
+steps:
+  build:
+    type: bash
+    script: cargo build {{ inputs.flags }}
+    inputs:
+      flags:
+        ref: parameters.flags
+"#;
+        let chain: Chain = yaml.parse().unwrap();
+        chain.validate().unwrap();
+
+        let mut mock = MockExecutor::new();
+        mock.expect_call("cargo build -v --fast", mock_output("ok"));
+
+        let result = chain.run_with_executor(&mock);
+        assert_eq!(result.status, "ok", "errors: {:?}", result.errors);
+        let (script, _, _) = mock.last_call().unwrap();
+        assert!(script.contains("-v --fast"));
+        assert_eq!(result.parameters.unwrap()["flags"], "-v --fast");
+    }
+
+    const OVERRIDE_CHAIN: &str = r#"
+parameters:
+  retries:
+    type: int
+    value: 1
+  verbose:
+    type: bool
+    value: false
+  since:
+    type: datetime
+    value: "2024-01-01T00:00:00Z"
+steps:
+  report:
+    type: bash
+    script: report {{ parameters.retries }} {{ parameters.verbose }} {{ parameters.since }}
+"#;
+
+    fn overrides(pairs: &[(&str, &str)]) -> HashMap<String, String> {
+        pairs
+            .iter()
+            .map(|(k, v)| ((*k).to_string(), (*v).to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_with_parameters_converts_values() {
+        let chain: Chain = OVERRIDE_CHAIN.parse().unwrap();
+        let overridden = chain
+            .with_parameters(&overrides(&[
+                ("retries", "5"),
+                ("verbose", "true"),
+                ("since", "2025-06-30T12:00:00+02:00"),
+            ]))
+            .unwrap();
+
+        assert_eq!(
+            overridden.parameters["retries"]
+                .value
+                .as_ref()
+                .and_then(serde_yaml::Value::as_i64),
+            Some(5)
+        );
+        assert_eq!(
+            overridden.parameters["verbose"]
+                .value
+                .as_ref()
+                .and_then(serde_yaml::Value::as_bool),
+            Some(true)
+        );
+        assert_eq!(
+            overridden.parameters["since"]
+                .value
+                .as_ref()
+                .and_then(serde_yaml::Value::as_str),
+            Some("2025-06-30T12:00:00+02:00")
+        );
+        // The original chain is left untouched
+        assert_eq!(
+            chain.parameters["retries"]
+                .value
+                .as_ref()
+                .and_then(serde_yaml::Value::as_i64),
+            Some(1)
+        );
+    }
+
+    #[test]
+    fn test_with_parameters_rejects_invalid_values() {
+        let chain: Chain = OVERRIDE_CHAIN.parse().unwrap();
+
+        for (key, value) in [
+            ("retries", "abc"),
+            ("verbose", "yes"),
+            ("since", "2024-13-01"),
+        ] {
+            match chain.with_parameters(&overrides(&[(key, value)])) {
+                Err(AtentoError::TypeConversion { got, .. }) => {
+                    assert!(got.contains(value) && got.contains(key), "got: {got}");
+                }
+                other => panic!("Expected TypeConversion for {key}, got {other:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_with_parameters_rejects_unknown_parameter() {
+        let chain: Chain = OVERRIDE_CHAIN.parse().unwrap();
+        match chain.with_parameters(&overrides(&[("region", "eu")])) {
+            Err(AtentoError::Validation(msg)) => assert!(msg.contains("'region'")),
+            other => panic!("Expected Validation error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_run_with_parameters_and_executor() {
+        use crate::tests::mock_executor::MockExecutor;
+
+        let chain: Chain = OVERRIDE_CHAIN.parse().unwrap();
+        let mut mock = MockExecutor::new();
+        mock.expect_call("report 3 true 2024-01-01T00:00:00Z", mock_output("done"));
+
+        let result = chain
+            .run_with_parameters_and_executor(
+                &mock,
+                &overrides(&[("retries", "3"), ("verbose", "1")]),
+            )
+            .unwrap();
+        assert_eq!(result.status, "ok", "errors: {:?}", result.errors);
+        assert_eq!(result.parameters.unwrap()["retries"], "3");
+
+        let err = chain.run_with_parameters_and_executor(&mock, &overrides(&[("x", "1")]));
+        assert!(err.is_err());
+    }
+
+    #[test]
+    fn test_validate_rejects_mistyped_parameter() {
+        let yaml = r#"
+parameters:
+  count:
+    type: int
+    value: "not a number"
+steps:
+  s:
+    type: bash
+    script: echo {{ parameters.count }}
+"#;
+        let chain: Chain = yaml.parse().unwrap();
+        match chain.validate() {
+            Err(AtentoError::TypeConversion { expected, got }) => {
+                assert_eq!(expected, "int");
+                assert!(got.contains("parameter 'count'"), "got: {got}");
+            }
+            other => panic!("Expected TypeConversion, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_validate_rejects_mistyped_datetime_and_bool_parameters() {
+        for (type_, value) in [("datetime", "\"yesterday\""), ("bool", "\"yes\"")] {
+            let yaml = format!(
+                "parameters:\n  p:\n    type: {type_}\n    value: {value}\nsteps:\n  s:\n    type: bash\n    script: echo hi\n"
+            );
+            let chain: Chain = yaml.parse().unwrap();
+            assert!(
+                matches!(chain.validate(), Err(AtentoError::TypeConversion { .. })),
+                "{type_} {value} should be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn test_validate_rejects_mistyped_inline_input() {
+        let yaml = r"
+steps:
+  s:
+    type: bash
+    script: echo {{ inputs.flag }}
+    inputs:
+      flag:
+        type: bool
+        value: 3
+";
+        let chain: Chain = yaml.parse().unwrap();
+        match chain.validate() {
+            Err(AtentoError::TypeConversion { got, .. }) => {
+                assert!(got.contains("input 'flag' of step 's'"), "got: {got}");
+            }
+            other => panic!("Expected TypeConversion, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_validate_accepts_numeric_string_parameter() {
+        let yaml = r#"
+parameters:
+  port:
+    type: int
+    value: "8080"
+steps:
+  s:
+    type: bash
+    script: echo {{ parameters.port }}
+"#;
+        let chain: Chain = yaml.parse().unwrap();
+        assert!(chain.validate().is_ok());
+    }
+
+    const REQUIRED_CHAIN: &str = r"
+parameters:
+  token:
+    required: true
+    secret: true
+  region:
+    type: string
+    required: true
+  retries:
+    type: int
+    value: 2
+steps:
+  deploy:
+    type: bash
+    script: deploy {{ parameters.region }} {{ parameters.retries }}
+";
+
+    #[test]
+    fn test_validate_lists_all_missing_parameters() {
+        let chain: Chain = REQUIRED_CHAIN.parse().unwrap();
+        match chain.validate() {
+            Err(AtentoError::Validation(msg)) => {
+                assert!(msg.contains("region, token"), "msg: {msg}");
+            }
+            other => panic!("Expected Validation error, got {other:?}"),
+        }
+
+        let supplied = chain
+            .with_parameters(&overrides(&[("token", "s3cret"), ("region", "eu")]))
+            .unwrap();
+        assert!(supplied.validate().is_ok());
+    }
+
+    #[test]
+    fn test_run_with_parameters_requires_missing_values() {
+        use crate::tests::mock_executor::MockExecutor;
+
+        let chain: Chain = REQUIRED_CHAIN.parse().unwrap();
+        let mut mock = MockExecutor::new();
+        mock.expect_call("deploy eu 2", mock_output("deployed"));
+
+        let missing =
+            chain.run_with_parameters_and_executor(&mock, &overrides(&[("region", "eu")]));
+        match missing {
+            Err(AtentoError::Validation(msg)) => assert!(msg.contains("token")),
+            other => panic!("Expected Validation error, got {other:?}"),
+        }
+
+        let result = chain
+            .run_with_parameters_and_executor(
+                &mock,
+                &overrides(&[("region", "eu"), ("token", "s3cret")]),
+            )
+            .unwrap();
+        assert_eq!(result.status, "ok", "errors: {:?}", result.errors);
+        assert_eq!(result.parameters.unwrap()["token"], "***");
+    }
+
+    #[test]
+    fn test_run_without_required_parameter_reports_it() {
+        use crate::tests::mock_executor::MockExecutor;
+
+        let chain: Chain = REQUIRED_CHAIN.parse().unwrap();
+        let mock = MockExecutor::new();
+
+        let result = chain.run_with_executor(&mock);
+        assert_eq!(result.status, "nok");
+        assert!(
+            result.errors.iter().any(
+                |e| matches!(e, AtentoError::Validation(msg) if msg.contains("region, token"))
+            )
+        );
+        let parameters = result.parameters.unwrap();
+        assert_eq!(parameters["retries"], "2");
+        assert!(!parameters.contains_key("region"));
+    }
+
+    #[test]
+    fn test_default_interpreter_applies_to_untyped_steps() {
+        use crate::tests::mock_executor::MockExecutor;
+
+        let chain: Chain = r"
+default_interpreter: python
+steps:
+  first:
+    script: print('first')
+  second:
+    type: bash
+    script: echo second
+"
+        .parse()
+        .unwrap();
+        chain.validate().unwrap();
+
+        assert_eq!(
+            chain
+                .step_interpreter(&chain.steps["first"], "first")
+                .unwrap()
+                .extension,
+            ".py"
+        );
+        assert_eq!(
+            chain
+                .step_interpreter(&chain.steps["second"], "second")
+                .unwrap()
+                .extension,
+            ".sh"
+        );
+
+        let mut mock = MockExecutor::new();
+        mock.expect_call("print('first')", mock_output("first"))
+            .expect_call("echo second", mock_output("second"));
+        let result = chain.run_with_executor(&mock);
+        assert_eq!(result.status, "ok", "errors: {:?}", result.errors);
+    }
+
+    #[test]
+    fn test_validate_rejects_unknown_default_interpreter() {
+        let chain: Chain = r"
+default_interpreter: ruby
+steps:
+  s:
+    script: puts 1
+"
+        .parse()
+        .unwrap();
+        match chain.validate() {
+            Err(AtentoError::Validation(msg)) => {
+                assert!(msg.contains("default_interpreter 'ruby'"), "msg: {msg}");
+            }
+            other => panic!("Expected Validation error, got {other:?}"),
+        }
+
+        let custom: Chain = r"
+default_interpreter: ruby
+interpreters:
+  ruby:
+    command: ruby
+    extension: .rb
+steps:
+  s:
+    script: puts 1
+"
+        .parse()
+        .unwrap();
+        assert!(custom.validate().is_ok());
+    }
+
+    #[test]
+    fn test_chain_remote_section() {
+        let chain: Chain = r"
+remote:
+  host: build.example.com
+  user: ci
+  key_path: ~/.ssh/id_ed25519
+steps:
+  s:
+    type: bash
+    script: uname -a
+"
+        .parse()
+        .unwrap();
+        chain.validate().unwrap();
+
+        let remote = chain.remote.as_ref().unwrap();
+        assert_eq!(remote.destination(), "ci@build.example.com");
+        assert_eq!(remote.key_path.as_deref(), Some("~/.ssh/id_ed25519"));
+        assert_eq!(remote.port, None);
+
+        let local: Chain = "steps: {}".parse().unwrap();
+        assert!(local.remote.is_none());
+
+        let empty_host: Chain = "remote:\n  host: ''\nsteps: {}".parse().unwrap();
+        assert!(matches!(
+            empty_host.validate(),
+            Err(AtentoError::Validation(_))
+        ));
+    }
+
+    #[test]
+    fn test_validate_rejects_remote_host_or_user_starting_with_dash() {
+        for (remote, field) in [
+            ("host: -oProxyCommand=id", "host"),
+            ("host: build.example.com\n  user: -oProxyCommand=id", "user"),
+        ] {
+            let chain: Chain = format!("remote:\n  {remote}\nsteps: {{}}").parse().unwrap();
+            match chain.validate() {
+                Err(AtentoError::Validation(msg)) => {
+                    assert!(msg.contains(&format!("'remote' {field}")), "msg: {msg}");
+                }
+                other => panic!("Expected Validation error, got {other:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_validate_rejects_env_names_that_are_not_identifiers() {
+        for name in ["'A B'", "'X;id'", "'$(id)'", "1ST"] {
+            let chain: Chain =
+                format!("env:\n  {name}: value\nsteps:\n  s:\n    type: bash\n    script: echo\n")
+                    .parse()
+                    .unwrap();
+            match chain.validate() {
+                Err(AtentoError::Validation(msg)) => {
+                    assert!(msg.contains("not a valid name"), "msg: {msg}");
+                }
+                other => panic!("Expected Validation error for {name}, got {other:?}"),
+            }
+        }
+
+        let chain: Chain =
+            "env:\n  _BUILD_2: value\nsteps:\n  s:\n    type: bash\n    script: echo\n"
+                .parse()
+                .unwrap();
+        chain.validate().unwrap();
+    }
+
+    #[test]
+    fn test_validate_rejects_unknown_step_type() {
+        let chain: Chain = r"
+steps:
+  s:
+    type: ruby
+    script: puts 1
+"
+        .parse()
+        .unwrap();
+        match chain.validate() {
+            Err(AtentoError::Validation(msg)) => {
+                assert!(
+                    msg.contains("Unknown interpreter 'ruby' in step 's'"),
+                    "msg: {msg}"
+                );
+                assert!(msg.contains("interpreters"), "msg: {msg}");
+            }
+            other => panic!("Expected Validation error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_custom_interpreter_runs_by_key() {
+        use crate::tests::mock_executor::MockExecutor;
+
+        let chain: Chain = r"
+interpreters:
+  deno:
+    command: deno
+    args: [run, --quiet]
+    extension: .ts
+steps:
+  s:
+    type: deno
+    script: console.log('hi')
+"
+        .parse()
+        .unwrap();
+        chain.validate().unwrap();
+
+        let interpreter = chain.step_interpreter(&chain.steps["s"], "s").unwrap();
+        assert_eq!(interpreter.command, "deno");
+        assert_eq!(interpreter.args, ["run", "--quiet"]);
+
+        let mut mock = MockExecutor::new();
+        mock.expect_call("console.log('hi')", mock_output("hi"));
+        let result = chain.run_with_executor(&mock);
+        assert_eq!(result.status, "ok", "errors: {:?}", result.errors);
+    }
+
+    fn missing_interpreter() -> crate::interpreter::Interpreter {
+        crate::interpreter::Interpreter {
+            command: "atento-no-such-interpreter".to_string(),
+            args: vec![],
+            extension: ".sh".to_string(),
+            fallbacks: vec![],
+        }
+    }
+
+    #[test]
+    fn test_check_interpreters_reports_each_step_with_missing_interpreter() {
+        let mut chain = chain_with_interpreters();
+        chain
+            .interpreters
+            .insert("ghost".to_string(), missing_interpreter());
+        chain.steps.insert("build".to_string(), Step::new("ghost"));
+        chain.steps.insert("test".to_string(), Step::new("ghost"));
+
+        let missing = chain.check_interpreters().unwrap_err();
+        assert_eq!(missing.len(), 2);
+        assert_eq!(missing[0].step, "build");
+        assert_eq!(missing[0].interpreter, "ghost");
+        assert_eq!(
+            missing[0].to_string(),
+            "interpreter 'atento-no-such-interpreter' for step 'build' not found on PATH"
+        );
+        assert_eq!(missing[1].step, "test");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_check_interpreters_probes_each_interpreter_once() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = dir.path().join("probes.log");
+        let mut chain = chain_with_interpreters();
+        chain.interpreters.insert(
+            "counted".to_string(),
+            crate::interpreter::Interpreter {
+                command: "sh".to_string(),
+                args: vec![
+                    "-c".to_string(),
+                    format!("echo probe >> '{}'", log.display()),
+                    "probe".to_string(),
+                ],
+                extension: ".sh".to_string(),
+                fallbacks: vec![],
+            },
+        );
+        for name in ["one", "two", "three"] {
+            chain.steps.insert(name.to_string(), Step::new("counted"));
+        }
+
+        assert!(chain.check_interpreters().is_ok());
+        assert_eq!(std::fs::read_to_string(&log).unwrap().lines().count(), 1);
+    }
+
+    #[test]
+    fn test_check_interpreters_skips_container_steps_and_remote_chains() {
+        let mut chain = chain_with_interpreters();
+        chain
+            .interpreters
+            .insert("ghost".to_string(), missing_interpreter());
+        let mut step = Step::new("ghost");
+        step.image = Some("alpine:3".to_string());
+        chain.steps.insert("boxed".to_string(), step);
+        assert!(chain.check_interpreters().is_ok());
+
+        chain.steps.insert("local".to_string(), Step::new("ghost"));
+        assert!(chain.check_interpreters().is_err());
+
+        chain.remote = Some(crate::remote::SshExecutor::new("build.example.com"));
+        assert!(chain.check_interpreters().is_ok());
+    }
+
+    #[test]
+    fn test_check_interpreters_skips_chains_with_an_image() {
+        let mut chain = chain_with_interpreters();
+        chain
+            .interpreters
+            .insert("ghost".to_string(), missing_interpreter());
+        chain.steps.insert("local".to_string(), Step::new("ghost"));
+        assert!(chain.check_interpreters().is_err());
+
+        chain.image = Some("alpine:3".to_string());
+        assert!(chain.check_interpreters().is_ok());
+    }
+
+    #[test]
+    fn test_validate_rejects_empty_chain_image() {
+        let chain: Chain = r#"
+image: " "
+steps:
+  s:
+    type: bash
+    script: echo 1
+"#
+        .parse()
+        .unwrap();
+        match chain.validate() {
+            Err(AtentoError::Validation(msg)) => {
+                assert!(msg.contains("empty 'image'"), "msg: {msg}");
+            }
+            other => panic!("Expected Validation error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_run_with_preflight_reports_missing_interpreter_without_running_steps() {
+        let mut chain = chain_with_interpreters();
+        chain.preflight = true;
+        chain
+            .interpreters
+            .insert("ghost".to_string(), missing_interpreter());
+        chain.steps.insert("build".to_string(), Step::new("ghost"));
+
+        let result = chain.run();
+        assert_eq!(result.status, "nok");
+        assert!(result.steps.is_none());
+        assert_eq!(
+            result.errors,
+            vec![AtentoError::Validation(
+                "interpreter 'atento-no-such-interpreter' for step 'build' not found on PATH"
+                    .to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn test_run_subset_with_preflight_reports_missing_interpreter_without_running_steps() {
+        let mut chain = chain_with_interpreters();
+        chain.preflight = true;
+        chain
+            .interpreters
+            .insert("ghost".to_string(), missing_interpreter());
+        chain.steps.insert("build".to_string(), Step::new("ghost"));
+
+        let result = chain
+            .run_subset(Some(&["build".to_string()]), None, HashMap::new())
+            .unwrap();
+        assert_eq!(result.status, "nok");
+        assert!(result.steps.is_none());
+        assert_eq!(
+            result.errors,
+            vec![AtentoError::Validation(
+                "interpreter 'atento-no-such-interpreter' for step 'build' not found on PATH"
+                    .to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn test_run_subset_with_preflight_still_rejects_unknown_steps() {
+        let mut chain = chain_with_interpreters();
+        chain.preflight = true;
+        chain
+            .interpreters
+            .insert("ghost".to_string(), missing_interpreter());
+        chain.steps.insert("build".to_string(), Step::new("ghost"));
+
+        for (only, from) in [(Some(vec!["typo".to_string()]), None), (None, Some("typo"))] {
+            match chain.run_subset(only.as_deref(), from, HashMap::new()) {
+                Err(AtentoError::Validation(msg)) => {
+                    assert!(msg.contains("'typo'"), "msg: {msg}");
+                }
+                other => panic!("Expected Validation error, got {other:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_preflight_is_read_from_yaml() {
+        let chain: Chain = "preflight: true\nsteps: {}\n".parse().unwrap();
+        assert!(chain.preflight);
+        let chain: Chain = "steps: {}\n".parse().unwrap();
+        assert!(!chain.preflight);
+    }
+
+    #[test]
+    fn test_validate_rejects_interpreter_args_for_unknown_interpreter() {
+        let chain: Chain = r"
+steps:
+  s:
+    type: ruby
+    interpreter_args: [-w]
+    script: puts 1
+"
+        .parse()
+        .unwrap();
+        match chain.validate() {
+            Err(AtentoError::Validation(msg)) => {
+                assert!(msg.contains("Unknown interpreter 'ruby'"), "msg: {msg}");
+            }
+            other => panic!("Expected Validation error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_chain_step_interpreter_args_apply_to_one_step() {
+        use crate::tests::mock_executor::MockExecutor;
+
+        let chain: Chain = r"
+interpreters:
+  bash:
+    command: bash
+    args: [--noprofile]
+    extension: .sh
+steps:
+  strict:
+    type: bash
+    interpreter_args: [-e]
+    script: echo strict
+  plain:
+    type: bash
+    script: echo plain
+"
+        .parse()
+        .unwrap();
+        chain.validate().unwrap();
+
+        let mock = MockExecutor::new();
+        let mut calls = Vec::new();
+        for (name, step) in &chain.steps {
+            let interpreter = chain.step_interpreter(step, name).unwrap();
+            let _ = step.run(
+                &mock,
+                &HashMap::new(),
+                &HashMap::new(),
+                &HashMap::new(),
+                None,
+                30,
+                &KillPolicy::default(),
+                interpreter,
+            );
+            calls.push(mock.last_call().unwrap().1.args);
+        }
+        assert_eq!(calls, vec![vec!["--noprofile", "-e"], vec!["--noprofile"]]);
+    }
+
+    #[test]
+    fn test_validate_rejects_non_http_webhook_url() {
+        let chain: Chain = "on_complete_webhook: ftp://example.com/hook\nsteps: {}\n"
+            .parse()
+            .unwrap();
+        match chain.validate() {
+            Err(AtentoError::Validation(msg)) => {
+                assert!(msg.contains("http:// or https://"), "msg: {msg}");
+            }
+            other => panic!("Expected Validation error, got {other:?}"),
+        }
+    }
+
+    #[cfg(not(feature = "http"))]
+    #[test]
+    fn test_validate_rejects_webhook_without_http_feature() {
+        let chain: Chain = "on_complete_webhook: https://example.com/hook\nsteps: {}\n"
+            .parse()
+            .unwrap();
+        match chain.validate() {
+            Err(AtentoError::Validation(msg)) => {
+                assert!(msg.contains("'http' feature"), "msg: {msg}");
+            }
+            other => panic!("Expected Validation error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_chain_stdin_from_step_output() {
+        use crate::tests::mock_executor::MockExecutor;
+
+        let yaml = r#"
+steps:
+  fetch:
+    type: bash
+    script: curl
+    outputs:
+      body:
+        pattern: "(?s)(.*)"
+  parse:
+    type: bash
+    script: jq .id
+    stdin:
+      ref: steps.fetch.outputs.body
+"#;
+        let chain: Chain = yaml.parse().unwrap();
+        chain.validate().unwrap();
+
+        let mut mock = MockExecutor::new();
+        mock.expect_call("curl", mock_output("{\"id\": 7}"));
+
+        let result = chain.run_with_executor(&mock);
+        assert_eq!(result.status, "ok", "errors: {:?}", result.errors);
+        assert_eq!(mock.last_stdin().as_deref(), Some("{\"id\": 7}"));
+        let steps = result.steps.unwrap();
+        assert!(steps["parse"].inputs.is_empty());
+    }
+
+    #[test]
+    fn test_chain_stdin_inline_value() {
+        use crate::tests::mock_executor::MockExecutor;
+
+        let yaml = r"
+steps:
+  count:
+    type: bash
+    script: wc -l
+    stdin:
+      type: int
+      value: 42
+";
+        let chain: Chain = yaml.parse().unwrap();
+        let mock = MockExecutor::new();
+        let result = chain.run_with_executor(&mock);
+        assert_eq!(result.status, "ok", "errors: {:?}", result.errors);
+        assert_eq!(mock.last_stdin().as_deref(), Some("42"));
+    }
+
+    #[test]
+    fn test_chain_validate_stdin_unknown_reference() {
+        let yaml = r"
+steps:
+  read:
+    type: bash
+    script: cat
+    stdin:
+      ref: steps.missing.outputs.body
+";
+        let chain: Chain = yaml.parse().unwrap();
+        match chain.validate() {
+            Err(AtentoError::UnresolvedReference { reference, .. }) => {
+                assert_eq!(reference, "steps.missing.outputs.body");
+            }
+            other => panic!("Expected UnresolvedReference, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_chain_run_parallel_waits_for_stdin_reference() {
+        let mut chain = chain_with_interpreters();
+
+        let mut producer = echo_step("produce");
+        producer
+            .outputs
+            .insert("value".to_string(), capture("(.+)"));
+
+        let mut consumer = echo_step("consume");
+        consumer.stdin = Some(crate::env::EnvValue::Input(Input::Ref {
+            ref_: "steps.producer.outputs.value".to_string(),
+        }));
+
+        // Declared first, but must wait for the producer
+        chain.steps.insert("consumer".to_string(), consumer);
+        chain.steps.insert("producer".to_string(), producer);
+
+        let executor = SleepingExecutor::new(100);
+        let result = chain.run_parallel_with_executor(&executor);
+
+        assert_eq!(result.status, "ok", "errors: {:?}", result.errors);
+        let events = executor.events();
+        let producer_end = events.iter().position(|e| e == "end produce").unwrap();
+        let consumer_start = events.iter().position(|e| e == "start consume").unwrap();
+        assert!(producer_end < consumer_start, "events: {events:?}");
+    }
+
+    #[test]
+    fn test_chain_on_failure_runs_handler_then_stops() {
+        use crate::tests::mock_executor::MockExecutor;
+
+        let yaml = r"
+steps:
+  deploy:
+    type: bash
+    script: deploy
+    on_failure: rollback
+  verify:
+    type: bash
+    script: verify
+  rollback:
+    type: bash
+    script: rollback {{ inputs.code }}
+    inputs:
+      code:
+        ref: steps.deploy.exit_code
+";
+        let chain: Chain = yaml.parse().unwrap();
+        chain.validate().unwrap();
+
+        let mut mock = MockExecutor::new();
+        mock.expect_error("deploy", 3, "boom")
+            .expect_call("rollback 3", mock_output("rolled back"));
+
+        let result = chain.run_with_executor(&mock);
+        assert_eq!(result.status, "nok");
+        assert_eq!(mock.call_count(), 2);
+        assert_eq!(result.errors.len(), 1);
+        let steps = result.steps.unwrap();
+        let order: Vec<&str> = steps.keys().map(String::as_str).collect();
+        assert_eq!(order, vec!["deploy", "rollback"]);
+        assert!(steps["rollback"].error.is_none());
+        assert_eq!(steps["rollback"].stdout.as_deref(), Some("rolled back"));
+    }
+
+    #[test]
+    fn test_chain_on_failure_handler_skipped_when_step_succeeds() {
+        use crate::tests::mock_executor::MockExecutor;
+
+        let yaml = r"
+steps:
+  deploy:
+    type: bash
+    script: deploy
+    on_failure: rollback
+  rollback:
+    type: bash
+    script: rollback
+";
+        let chain: Chain = yaml.parse().unwrap();
+        let mock = MockExecutor::new();
+
+        let result = chain.run_with_executor(&mock);
+        assert_eq!(result.status, "ok");
+        assert_eq!(mock.call_count(), 1);
+        assert!(!result.steps.unwrap().contains_key("rollback"));
+    }
+
+    #[test]
+    fn test_chain_on_failure_with_continue_on_error_goes_on() {
+        use crate::tests::mock_executor::MockExecutor;
+
+        let yaml = r"
+steps:
+  lint:
+    type: bash
+    script: lint
+    continue_on_error: true
+    on_failure: report
+  test:
+    type: bash
+    script: test
+  report:
+    type: bash
+    script: report
+";
+        let chain: Chain = yaml.parse().unwrap();
+        let mut mock = MockExecutor::new();
+        mock.expect_error("lint", 1, "style");
+
+        let result = chain.run_with_executor(&mock);
+        assert_eq!(result.status, "partial");
+        let steps = result.steps.unwrap();
+        let order: Vec<&str> = steps.keys().map(String::as_str).collect();
+        assert_eq!(order, vec!["lint", "report", "test"]);
+    }
+
+    #[test]
+    fn test_chain_on_failure_handler_failure_is_reported() {
+        use crate::tests::mock_executor::MockExecutor;
+
+        let yaml = r"
+steps:
+  deploy:
+    type: bash
+    script: deploy
+    continue_on_error: true
+    on_failure: rollback
+  verify:
+    type: bash
+    script: verify
+  rollback:
+    type: bash
+    script: rollback
+";
+        let chain: Chain = yaml.parse().unwrap();
+        let mut mock = MockExecutor::new();
+        mock.expect_error("deploy", 1, "boom")
+            .expect_error("rollback", 2, "stuck");
+
+        let result = chain.run_with_executor(&mock);
+        assert_eq!(result.status, "nok");
+        assert_eq!(result.errors.len(), 2);
+        assert!(matches!(
+            &result.errors[1],
+            AtentoError::StepExecution { step, .. } if step == "rollback"
+        ));
+        // The failed handler stops the chain
+        assert_eq!(mock.call_count(), 2);
+    }
+
+    #[test]
+    fn test_chain_on_failure_runs_in_parallel_chains() {
+        let mut chain = chain_with_interpreters();
+        chain.parallel = true;
+
+        let mut failing = echo_step("fail");
+        failing
+            .outputs
+            .insert("missing".to_string(), capture(r"never (\d+)"));
+        failing.on_failure = Some("cleanup".to_string());
+        chain.steps.insert("failing".to_string(), failing);
+        chain
+            .steps
+            .insert("cleanup".to_string(), echo_step("cleanup"));
+
+        let executor = SleepingExecutor::new(10);
+        let result = chain.run_parallel_with_executor(&executor);
+
+        assert_eq!(result.status, "nok");
+        let events = executor.events();
+        assert_eq!(
+            events,
+            vec!["start fail", "end fail", "start cleanup", "end cleanup"]
+        );
+        let steps = result.steps.unwrap();
+        assert!(steps["cleanup"].error.is_none());
+    }
+
+    #[test]
+    fn test_validate_on_failure_rules() {
+        let cases = [
+            (
+                "  a:\n    type: bash\n    script: a\n    on_failure: missing\n",
+                "'missing' that does not exist",
+            ),
+            (
+                "  a:\n    type: bash\n    script: a\n    on_failure: a\n",
+                "its own 'on_failure' step",
+            ),
+            (
+                "  a:\n    type: bash\n    script: a\n    on_failure: b\n  b:\n    type: bash\n    script: b\n    on_failure: c\n  c:\n    type: bash\n    script: c\n",
+                "cannot set 'on_failure' itself",
+            ),
+            (
+                "  a:\n    type: bash\n    script: a\n    on_failure: c\n  b:\n    type: bash\n    script: b\n  c:\n    type: bash\n    script: c {{ inputs.out }}\n    inputs:\n      out:\n        ref: steps.b.stdout\n",
+                "references step 'b', which has not run when 'a' fails",
+            ),
+            (
+                "  a:\n    type: bash\n    script: a\n    on_failure: b\n  b:\n    type: bash\n    script: b\n  c:\n    type: bash\n    script: c {{ inputs.out }}\n    inputs:\n      out:\n        ref: steps.b.stdout\n",
+                "which only runs as an 'on_failure' handler",
+            ),
+        ];
+
+        for (steps, expected) in cases {
+            let chain: Chain = format!("steps:\n{steps}").parse().unwrap();
+            match chain.validate() {
+                Err(AtentoError::Validation(msg)) => {
+                    assert!(msg.contains(expected), "msg: {msg}");
+                }
+                other => panic!("Expected Validation error for {steps}, got {other:?}"),
+            }
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_artifacts_are_collected_and_referenced() {
+        let dir = tempfile::tempdir().unwrap();
+        let yaml = format!(
+            r"
+artifacts_root: '{root}'
+steps:
+  build:
+    type: bash
+    workdir: '{root}'
+    script: printf 'built' > report.txt
+    artifacts:
+      report:
+        path: report.txt
+        inline_max_bytes: 100
+      coverage:
+        path: coverage.xml
+        optional: true
+  publish:
+    type: bash
+    script: cat {{{{ inputs.report }}}}
+    inputs:
+      report:
+        ref: steps.build.artifacts.report
+",
+            root = dir.path().display()
+        );
+        let chain: Chain = yaml.parse().unwrap();
+        chain.validate().unwrap();
+
+        let result = chain.run();
+        assert_eq!(result.status, "ok", "errors: {:?}", result.errors);
+        let steps = result.steps.unwrap();
+        let artifacts = &steps["build"].artifacts;
+        assert_eq!(artifacts.len(), 1);
+        assert_eq!(
+            artifacts["report"].path,
+            dir.path().join("report.txt").display().to_string()
+        );
+        assert_eq!(artifacts["report"].size, 5);
+        assert_eq!(
+            artifacts["report"].content_base64.as_deref(),
+            Some("YnVpbHQ=")
+        );
+        assert_eq!(steps["publish"].stdout.as_deref(), Some("built"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_missing_artifact_fails_step() {
+        let dir = tempfile::tempdir().unwrap();
+        let yaml = format!(
+            r"
+steps:
+  build:
+    type: bash
+    workdir: '{}'
+    script: 'true'
+    artifacts:
+      report:
+        path: report.txt
+  after:
+    type: bash
+    script: echo never
+",
+            dir.path().display()
+        );
+        let chain: Chain = yaml.parse().unwrap();
+
+        let result = chain.run();
+        assert_eq!(result.status, "nok");
+        assert!(
+            result
+                .errors
+                .iter()
+                .any(|e| e.to_string().contains("Artifact 'report'"))
+        );
+        let steps = result.steps.unwrap();
+        assert!(steps["build"].artifacts.is_empty());
+        assert!(!steps.contains_key("after"));
+    }
+
+    #[test]
+    fn test_validate_artifacts() {
+        let cases = [
+            (
+                "remote:\n  host: build.example.com\nsteps:\n  a:\n    type: bash\n    script: a\n    artifacts:\n      out:\n        path: out.txt\n",
+                "not supported with 'remote'",
+            ),
+            (
+                "artifacts_root: /data/out\nsteps:\n  a:\n    type: bash\n    script: a\n    artifacts:\n      out:\n        path: /etc/passwd\n",
+                "outside the artifacts root",
+            ),
+            (
+                "artifacts_root: /data/out\nsteps:\n  a:\n    type: bash\n    workdir: /data/out\n    script: a\n    artifacts:\n      out:\n        path: ../secret\n",
+                "outside the artifacts root",
+            ),
+            (
+                "steps:\n  a:\n    type: bash\n    script: a\n    artifacts:\n      out:\n        path: out.txt\n  b:\n    type: bash\n    script: b {{ inputs.x }}\n    inputs:\n      x:\n        ref: steps.a.artifacts.missing\n",
+                "steps.a.artifacts.missing",
+            ),
+        ];
+
+        for (yaml, expected) in cases {
+            let chain: Chain = yaml.parse().unwrap();
+            let err = chain.validate().unwrap_err();
+            assert!(err.to_string().contains(expected), "err: {err}");
+        }
+
+        let chain: Chain = "artifacts_root: /data/out\nsteps:\n  a:\n    type: bash\n    workdir: /data/out\n    script: a\n    artifacts:\n      out:\n        path: 'reports/{{ parameters.name }}.txt'\nparameters:\n  name:\n    value: x\n"
+            .parse()
+            .unwrap();
+        assert!(chain.validate().is_ok());
+    }
+
+    #[test]
+    fn test_default_interpreter_applies_to_parallel_and_dry_runs() {
+        let mut chain: Chain = r"
+default_interpreter: bash
+parallel: true
+steps:
+  first:
+    script: first
+  second:
+    script: second
+"
+        .parse()
+        .unwrap();
+        chain.validate().unwrap();
+
+        let dry_run = chain.dry_run();
+        assert_eq!(dry_run.status, "dry-run");
+        assert!(dry_run.errors.is_empty(), "errors: {:?}", dry_run.errors);
+
+        let result = chain.run_parallel_with_executor(&SleepingExecutor::new(0));
+        assert_eq!(result.status, "ok", "errors: {:?}", result.errors);
+
+        chain.default_interpreter = None;
+        let result = chain.run_parallel_with_executor(&SleepingExecutor::new(0));
+        assert_eq!(result.status, "nok");
+        assert!(result.errors[0].to_string().contains("has no 'type'"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_max_output_bytes_truncates_step_output() {
+        let chain: Chain = r"
+max_output_bytes: 200
+steps:
+  noisy:
+    type: bash
+    script: for i in $(seq 1 5000); do echo line $i; done
+    outputs:
+      last:
+        pattern: (line 5000)
+"
+        .parse()
+        .unwrap();
+
+        let result = chain.run();
+        assert_eq!(result.status, "nok");
+        let steps = result.steps.unwrap();
+        let noisy = &steps["noisy"];
+        assert!(noisy.stdout_truncated);
+        assert!(noisy.truncated);
+        let (kept, marker) = noisy.stdout.as_ref().unwrap().rsplit_once('\n').unwrap();
+        assert!(kept.len() <= 200);
+        assert!(marker.starts_with("...[truncated "), "{marker}");
+        let error = noisy.error.as_ref().unwrap().to_string();
+        assert!(error.contains("did not match"), "{error}");
+        assert!(
+            error.contains("truncated to its first 200 bytes"),
+            "{error}"
+        );
+
+        let json = serde_json::to_string(&steps["noisy"]).unwrap();
+        assert!(json.contains("\"stdout_truncated\":true"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_max_output_bytes_default_and_disabled() {
+        let chain: Chain = "steps:\n  s:\n    type: bash\n    script: echo hi\n"
+            .parse()
+            .unwrap();
+        assert_eq!(chain.max_output_bytes, 4 * 1024 * 1024);
+        assert_eq!(Chain::default().max_output_bytes, 4 * 1024 * 1024);
+
+        let unlimited: Chain = r"
+max_output_bytes: 0
+steps:
+  s:
+    type: bash
+    script: for i in $(seq 1 500); do echo line $i; done
+    outputs:
+      last:
+        pattern: (line 500)
+"
+        .parse()
+        .unwrap();
+        let result = unlimited.run();
+        assert_eq!(result.status, "ok", "errors: {:?}", result.errors);
+        let steps = result.steps.unwrap();
+        assert!(!steps["s"].stdout_truncated);
+        let json = serde_json::to_string(&steps["s"]).unwrap();
+        assert!(!json.contains("truncated"));
+    }
+
+    #[test]
+    fn test_validate_detailed_reports_unused_inputs_as_warnings() {
+        let chain: Chain = r"
+steps:
+  first:
+    type: bash
+    script: echo hello
+    inputs:
+      unused:
+        type: string
+        value: never used
+  second:
+    type: bash
+    script: echo {{ inputs.greeting }}
+    inputs:
+      greeting:
+        type: string
+        value: hi
+      extra:
+        type: int
+        value: 1
+"
+        .parse()
+        .unwrap();
+
+        let report = chain.validate_detailed();
+        assert!(report.is_valid(), "errors: {:?}", report.errors);
+        assert_eq!(
+            report.warnings,
+            vec![
+                "Step 'first' has input 'unused' that is declared but never used in the script",
+                "Step 'second' has input 'extra' that is declared but never used in the script",
+            ]
+        );
+
+        let err = chain.validate().unwrap_err();
+        assert_eq!(err, AtentoError::Validation(report.warnings[0].clone()));
+    }
+
+    #[test]
+    fn test_validate_detailed_keeps_hard_errors() {
+        let undeclared: Chain = r"
+steps:
+  s:
+    type: bash
+    script: echo {{ inputs.missing }}
+"
+        .parse()
+        .unwrap();
+        let report = undeclared.validate_detailed();
+        assert!(!report.is_valid());
+        assert_eq!(report.errors, vec![undeclared.validate().unwrap_err()]);
+
+        let forward: Chain = r"
+steps:
+  first:
+    type: bash
+    script: echo {{ inputs.v }}
+    inputs:
+      v:
+        ref: steps.second.outputs.v
+  second:
+    type: bash
+    script: echo v=1
+    outputs:
+      v:
+        pattern: v=(\d)
+"
+        .parse()
+        .unwrap();
+        let report = forward.validate_detailed();
+        assert_eq!(report.errors.len(), 1);
+        assert!(report.errors[0].to_string().contains("future step output"));
+
+        let bad_regex: Chain = r"
+steps:
+  s:
+    type: bash
+    script: echo hi
+    inputs:
+      unused:
+        type: string
+        value: x
+    outputs:
+      v:
+        pattern: '(unclosed'
+"
+        .parse()
+        .unwrap();
+        let report = bad_regex.validate_detailed();
+        assert_eq!(report.errors.len(), 1);
+        assert_eq!(report.warnings.len(), 1);
+    }
+
+    #[test]
+    fn test_validate_detailed_clean_chain() {
+        let chain: Chain = "steps:\n  s:\n    type: bash\n    script: echo hi\n"
+            .parse()
+            .unwrap();
+        assert_eq!(chain.validate_detailed(), ValidationReport::default());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_json_path_output_errors_name_the_step() {
+        let chain: Chain = r#"
+steps:
+  report:
+    type: bash
+    script: |
+      echo "building"
+      echo '{"build": {"version": "1.2.3"}}'
+    outputs:
+      version:
+        json_path: build.version
+  broken:
+    type: bash
+    script: |
+      echo '{"build": {}}'
+    outputs:
+      version:
+        json_path: build.version
+"#
+        .parse()
+        .unwrap();
+
+        let result = chain.run();
+        let steps = result.steps.unwrap();
+        assert_eq!(steps["report"].outputs["version"], "1.2.3");
+        assert_eq!(result.errors.len(), 1);
+        let error = result.errors[0].to_string();
+        assert!(error.contains("broken"), "{error}");
+        assert!(
+            error.contains("'build.version' was not found in stdout"),
+            "{error}"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_key_value_outputs_feed_later_steps() {
+        let chain: Chain = r#"
+steps:
+  build:
+    type: bash
+    script: |
+      echo "compiling"
+      echo "BUILD_DIR=/tmp/out"
+      echo "WARNINGS=2"
+    outputs:
+      dir:
+        key: BUILD_DIR
+      warnings:
+        key: WARNINGS
+        type: int
+  report:
+    type: bash
+    script: echo "{{ inputs.dir }} {{ inputs.warnings }}"
+    inputs:
+      dir:
+        ref: steps.build.outputs.dir
+      warnings:
+        ref: steps.build.outputs.warnings
+"#
+        .parse()
+        .unwrap();
+        chain.validate().unwrap();
+
+        let result = chain.run();
+        assert_eq!(result.status, "ok", "errors: {:?}", result.errors);
+        let steps = result.steps.unwrap();
+        assert_eq!(
+            steps["build"].typed_outputs["warnings"],
+            serde_json::json!(2)
+        );
+        assert_eq!(steps["build"].stdout.as_deref(), Some("compiling"));
+        assert_eq!(steps["report"].stdout.as_deref(), Some("/tmp/out 2"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_step_max_output_bytes_overrides_chain() {
+        let chain: Chain = r"
+max_output_bytes: 0
+steps:
+  capped:
+    type: bash
+    max_output_bytes: 10
+    script: for i in $(seq 1 100); do echo line $i; done
+  unlimited:
+    type: bash
+    script: for i in $(seq 1 100); do echo line $i; done
+  uncapped:
+    type: bash
+    max_output_bytes: 0
+    script: for i in $(seq 1 100); do echo line $i; done
+"
+        .parse()
+        .unwrap();
+
+        let result = chain.run();
+        assert_eq!(result.status, "ok", "errors: {:?}", result.errors);
+        let json = serde_json::to_value(&result).unwrap();
+        assert_eq!(json["steps"]["capped"]["truncated"], true);
+        assert!(json["steps"]["uncapped"].get("truncated").is_none());
+        let steps = result.steps.unwrap();
+        assert!(steps["capped"].truncated);
+        assert!(
+            steps["capped"]
+                .stdout
+                .as_deref()
+                .unwrap()
+                .starts_with("line 1\nlin\n...[truncated ")
+        );
+        assert!(!steps["unlimited"].truncated);
+        assert!(!steps["uncapped"].truncated);
+        assert_eq!(
+            steps["uncapped"]
+                .stdout
+                .as_deref()
+                .map(|out| out.lines().count()),
+            Some(100)
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_raw_block_writes_template_with_literal_placeholders() {
+        let dir = tempfile::tempdir().unwrap();
+        let chain: Chain = format!(
+            r"
+parameters:
+  app:
+    value: billing
+steps:
+  render:
+    type: bash
+    workdir: {}
+    script: |
+      cat > app.j2 <<'EOF'
+      # {{{{ parameters.app }}}}
+      {{{{ raw }}}}name: {{{{ inputs.something }}}}
+      env: {{{{ parameters.env }}}}{{{{ endraw }}}}
+      EOF
+",
+            dir.path().display()
+        )
+        .parse()
+        .unwrap();
+        chain.validate().unwrap();
+
+        let result = chain.run();
+        assert_eq!(result.status, "ok", "errors: {:?}", result.errors);
+        let template = std::fs::read_to_string(dir.path().join("app.j2")).unwrap();
+        assert_eq!(
+            template,
+            "# billing\nname: {{ inputs.something }}\nenv: {{ parameters.env }}\n"
+        );
+    }
+
+    fn step_placeholder_chain(parallel: bool) -> Chain {
+        format!(
+            r"
+parallel: {parallel}
+steps:
+  build:
+    type: bash
+    script: echo version=1.2.3
+    outputs:
+      version:
+        pattern: version=([\d.]+)
+  tag:
+    type: bash
+    script: tag v{{{{ steps.build.outputs.version }}}} {{{{steps.build.exit_code}}}} {{{{ inputs.who }}}}
+    inputs:
+      who:
+        value: ci
+"
+        )
+        .parse()
+        .unwrap()
+    }
+
+    #[test]
+    fn test_step_output_placeholders_in_script() {
+        use crate::tests::mock_executor::MockExecutor;
+
+        for parallel in [false, true] {
+            let chain = step_placeholder_chain(parallel);
+            chain.validate().unwrap();
+
+            let mut mock = MockExecutor::new();
+            mock.expect_call("echo version=1.2.3", mock_output("version=1.2.3"));
+            let result = chain.run_with_executor(&mock);
+            assert_eq!(result.status, "ok", "errors: {:?}", result.errors);
+
+            let (script, _, _) = mock.last_call().unwrap();
+            assert_eq!(script, "tag v1.2.3 0 ci");
+            let steps = result.steps.unwrap();
+            assert_eq!(steps["tag"].inputs["steps.build.outputs.version"], "1.2.3");
+        }
+
+        let dry = step_placeholder_chain(false).dry_run();
+        assert_eq!(
+            dry.steps.unwrap()["tag"].script.as_deref(),
+            Some("tag v<steps.build.outputs.version> <steps.build.exit_code> ci")
+        );
+    }
+
+    #[test]
+    fn test_validate_step_output_placeholders() {
+        let forward: Chain = r"
+steps:
+  first:
+    type: bash
+    script: echo {{ steps.second.outputs.v }}
+  second:
+    type: bash
+    script: echo v=1
+    outputs:
+      v:
+        pattern: v=(\d)
+"
+        .parse()
+        .unwrap();
+        let err = forward.validate().unwrap_err().to_string();
+        assert!(err.contains("future step output"), "{err}");
+        assert!(err.contains("steps.second.outputs.v"), "{err}");
+
+        let missing: Chain = r"
+steps:
+  first:
+    type: bash
+    script: echo v=1
+  second:
+    type: bash
+    script: echo {{ steps.first.outputs.nope }}
+"
+        .parse()
+        .unwrap();
+        match missing.validate() {
+            Err(AtentoError::UnresolvedReference { reference, context }) => {
+                assert_eq!(reference, "steps.first.outputs.nope");
+                assert!(context.contains("second"), "{context}");
+            }
+            other => panic!("Expected UnresolvedReference, got {other:?}"),
+        }
+
+        let raw: Chain = r"
+steps:
+  only:
+    type: bash
+    script: echo {{ raw }}{{ steps.other.outputs.v }}{{ endraw }}
+"
+        .parse()
+        .unwrap();
+        raw.validate().unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_quote_shell_round_trips_values() {
+        let value = "it's \"quoted\"; $(echo injected) `id` $HOME\nsecond  line";
+        let mut chain: Chain = r"
+parameters:
+  tricky:
+    value: placeholder
+steps:
+  produce:
+    type: bash
+    quote: shell
+    script: printf '%s' {{ parameters.tricky }}
+  echo:
+    type: bash
+    quote: shell
+    script: printf '%s' {{ steps.produce.stdout }}
+"
+        .parse()
+        .unwrap();
+        chain.parameters.get_mut("tricky").unwrap().value = Some(value.into());
+        chain.validate().unwrap();
+
+        let result = chain.run();
+        assert_eq!(result.status, "ok", "errors: {:?}", result.errors);
+        let steps = result.steps.unwrap();
+        assert_eq!(steps["produce"].stdout.as_deref(), Some(value));
+        assert_eq!(steps["echo"].stdout.as_deref(), Some(value));
+    }
+
+    #[test]
+    fn test_quote_must_match_interpreter() {
+        let chain: Chain = r"
+steps:
+  s:
+    type: bash
+    quote: powershell
+    script: echo {{ inputs.v }}
+    inputs:
+      v:
+        value: x
+"
+        .parse()
+        .unwrap();
+        let err = chain.validate().unwrap_err().to_string();
+        assert!(err.contains("'.ps1' scripts"), "{err}");
+
+        let chain: Chain = r"
+steps:
+  s:
+    type: pwsh
+    quote: powershell
+    script: Write-Output {{ inputs.v }}
+    inputs:
+      v:
+        value: x
+"
+        .parse()
+        .unwrap();
+        chain.validate().unwrap();
+    }
+
+    #[test]
+    fn test_resolve_script_files_loads_relative_to_base_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("scripts")).unwrap();
+        std::fs::write(
+            dir.path().join("scripts/greet.sh"),
+            "echo Hello {{ inputs.who }}\n",
+        )
+        .unwrap();
+
+        let mut chain: Chain = r"
+templates:
+  greeter:
+    type: bash
+    script_file: scripts/greet.sh
+    inputs:
+      who:
+        type: string
+steps:
+  inline:
+    type: bash
+    script_file: scripts/greet.sh
+    inputs:
+      who:
+        value: world
+  templated:
+    template: greeter
+    inputs:
+      who:
+        value: atento
+"
+        .parse()
+        .unwrap();
+
+        let err = chain.validate().unwrap_err().to_string();
+        assert!(err.contains("was not loaded"), "{err}");
+
+        chain.resolve_script_files(dir.path()).unwrap();
+        chain.validate().unwrap();
+        for id in ["inline", "templated"] {
+            assert_eq!(chain.steps[id].script, "echo Hello {{ inputs.who }}\n");
+            assert!(chain.steps[id].script_file.is_none());
+        }
+        assert_eq!(
+            chain.templates["greeter"].script,
+            "echo Hello {{ inputs.who }}\n"
+        );
+    }
+
+    #[test]
+    fn test_script_and_script_file_are_exclusive() {
+        let yaml = "steps:\n  s:\n    type: bash\n    script: echo hi\n    script_file: hi.sh\n";
+        let chain: Chain = yaml.parse().unwrap();
+        assert_eq!(
+            chain.validate().unwrap_err().to_string(),
+            AtentoError::Validation("Step 's' sets both 'script' and 'script_file'".to_string())
+                .to_string()
+        );
+
+        let mut chain: Chain = yaml.parse().unwrap();
+        let err = chain.resolve_script_files(".").unwrap_err();
+        assert!(err.to_string().contains("sets both"), "{err}");
+    }
+
+    #[test]
+    fn test_missing_script_file_is_io_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut chain: Chain = "steps:\n  s:\n    type: bash\n    script_file: gone.sh\n"
+            .parse()
+            .unwrap();
+
+        match chain.resolve_script_files(dir.path()).unwrap_err() {
+            AtentoError::Io { path, .. } => {
+                assert_eq!(path, dir.path().join("gone.sh").display().to_string());
+            }
+            other => panic!("expected an Io error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_success_codes_alias_counts_exit_code_as_success() {
+        use crate::tests::mock_executor::MockExecutor;
+
+        let chain: Chain = r"
+steps:
+  compare:
+    type: bash
+    script: diff old.txt new.txt
+    success_codes: [0, 1]
+    retries: 2
+    on_failure: report
+  report:
+    type: bash
+    script: echo files differ unexpectedly
+"
+        .parse()
+        .unwrap();
+        chain.validate().unwrap();
+        assert_eq!(chain.steps["compare"].success_exit_codes, vec![0, 1]);
+
+        let mut mock = MockExecutor::new();
+        mock.expect_error("diff old.txt new.txt", 1, "");
+        let result = chain.run_with_executor(&mock);
+
+        assert_eq!(result.status, "ok", "errors: {:?}", result.errors);
+        assert!(result.errors.is_empty());
+        assert_eq!(mock.call_count(), 1);
+        let steps = result.steps.unwrap();
+        assert_eq!(steps["compare"].exit_code, 1);
+        assert_eq!(steps["compare"].attempts, 1);
+        assert!(steps["compare"].error.is_none());
+        assert!(!steps.contains_key("report"));
+
+        let mut mock = MockExecutor::new();
+        mock.expect_error("diff old.txt new.txt", 2, "diff: old.txt: No such file")
+            .expect_call(
+                "echo files differ unexpectedly",
+                mock_output("files differ unexpectedly"),
+            );
+        let result = chain.run_with_executor(&mock);
+
+        assert_eq!(result.status, "nok");
+        let steps = result.steps.unwrap();
+        assert_eq!(steps["compare"].attempts, 3);
+        assert!(steps.contains_key("report"));
+    }
+
+    fn needs_chain(yaml: &str) -> Chain {
+        yaml.parse().unwrap()
+    }
+
+    #[test]
+    fn test_needs_runs_steps_in_topological_order() {
+        use crate::tests::mock_executor::MockExecutor;
+
+        let chain = needs_chain(
+            r"
+steps:
+  report:
+    type: bash
+    script: echo built {{ steps.build.outputs.version }}
+    needs: [build]
+  lint:
+    type: bash
+    script: echo lint
+  build:
+    type: bash
+    script: echo VERSION=1.0
+    outputs:
+      version:
+        pattern: VERSION=(\S+)
+",
+        );
+        chain.validate().unwrap();
+        let order: Vec<&str> = chain
+            .execution_order()
+            .unwrap()
+            .into_iter()
+            .map(String::as_str)
+            .collect();
+        assert_eq!(order, vec!["lint", "build", "report"]);
+
+        let ok = |stdout: &str| crate::executor::ExecutionResult {
+            stdout: stdout.to_string(),
+            duration_ms: 1,
+            ..crate::executor::ExecutionResult::default()
+        };
+        let mut mock = MockExecutor::new();
+        mock.expect_call("echo lint", ok("lint"))
+            .expect_call("echo VERSION=1.0", ok("VERSION=1.0"))
+            .expect_call("echo built 1.0", ok("built 1.0"));
+        let result = chain.run_with_executor(&mock);
+
+        assert_eq!(result.status, "ok", "errors: {:?}", result.errors);
+        let ran: Vec<&str> = result
+            .steps
+            .as_ref()
+            .unwrap()
+            .keys()
+            .map(String::as_str)
+            .collect();
+        assert_eq!(ran, vec!["lint", "build", "report"]);
+
+        let dry = chain.dry_run();
+        assert!(dry.errors.is_empty(), "errors: {:?}", dry.errors);
+        assert_eq!(
+            dry.steps.unwrap()["report"].script.as_deref(),
+            Some("echo built <steps.build.outputs.version>")
+        );
+    }
+
+    #[test]
+    fn test_needs_without_data_keeps_declared_order_for_ties() {
+        let chain = needs_chain(
+            r"
+steps:
+  a:
+    type: bash
+    script: echo a
+  b:
+    type: bash
+    script: echo b
+    needs: [d]
+  c:
+    type: bash
+    script: echo c
+  d:
+    type: bash
+    script: echo d
+",
+        );
+        chain.validate().unwrap();
+        let order: Vec<&str> = chain
+            .execution_order()
+            .unwrap()
+            .into_iter()
+            .map(String::as_str)
+            .collect();
+        assert_eq!(order, vec!["a", "c", "d", "b"]);
+    }
+
+    #[test]
+    fn test_needs_unknown_and_self() {
+        let err = needs_chain("steps:\n  a:\n    type: bash\n    script: echo a\n    needs: [b]\n")
+            .validate()
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            AtentoError::Validation("Step 'a' needs step 'b' that does not exist".to_string())
+                .to_string()
+        );
+
+        let err = needs_chain("steps:\n  a:\n    type: bash\n    script: echo a\n    needs: [a]\n")
+            .validate()
+            .unwrap_err();
+        assert!(err.to_string().contains("cannot need itself"), "{err}");
+    }
+
+    #[test]
+    fn test_needs_cycle_reports_path() {
+        let chain = needs_chain(
+            r"
+steps:
+  setup:
+    type: bash
+    script: echo setup
+    needs: [c]
+  a:
+    type: bash
+    script: echo a
+    needs: [b]
+  b:
+    type: bash
+    script: echo b
+    needs: [c]
+  c:
+    type: bash
+    script: echo c
+    needs: [a]
+",
+        );
+        match chain.validate().unwrap_err() {
+            AtentoError::Validation(message) => {
+                assert_eq!(message, "Step dependency cycle: c -> a -> b -> c");
+            }
+            other => panic!("expected a validation error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_forward_reference_without_needs_is_still_rejected() {
+        let chain = needs_chain(
+            r"
+steps:
+  report:
+    type: bash
+    script: echo {{ steps.build.outputs.version }}
+  build:
+    type: bash
+    script: echo VERSION=1.0
+    outputs:
+      version:
+        pattern: VERSION=(\S+)
+",
+        );
+        let err = chain.validate().unwrap_err();
+        assert!(err.to_string().contains("future step output"), "{err}");
+    }
+
+    #[test]
+    fn test_env_input_resolves_from_host_environment() {
+        use crate::tests::env_guard::EnvGuard;
+        use crate::tests::mock_executor::MockExecutor;
+
+        let chain: Chain = r"
+steps:
+  deploy:
+    type: bash
+    script: deploy --token {{ inputs.token }} --region {{ inputs.region }}
+    inputs:
+      token:
+        env: ATENTO_TEST_CHAIN_ENV_TOKEN
+      region:
+        env: ATENTO_TEST_CHAIN_ENV_REGION
+        default: eu-west-1
+"
+        .parse()
+        .unwrap();
+        chain.validate().unwrap();
+
+        let _token = EnvGuard::set("ATENTO_TEST_CHAIN_ENV_TOKEN", "t0k3n");
+        let _region = EnvGuard::unset("ATENTO_TEST_CHAIN_ENV_REGION");
+        let mut mock = MockExecutor::new();
+        mock.expect_call(
+            "deploy --token t0k3n --region eu-west-1",
+            mock_output("deployed"),
+        );
+        let result = chain.run_with_executor(&mock);
+        assert_eq!(result.status, "ok", "errors: {:?}", result.errors);
+        assert_eq!(mock.call_count(), 1);
+    }
+
+    #[test]
+    fn test_env_input_unset_without_default_is_unresolved() {
+        use crate::tests::env_guard::EnvGuard;
+        use crate::tests::mock_executor::MockExecutor;
+
+        let chain: Chain = r"
+steps:
+  deploy:
+    type: bash
+    script: deploy --token {{ inputs.token }}
+    inputs:
+      token:
+        env: ATENTO_TEST_CHAIN_ENV_UNSET
+"
+        .parse()
+        .unwrap();
+        chain.validate().unwrap();
+
+        let _guard = EnvGuard::unset("ATENTO_TEST_CHAIN_ENV_UNSET");
+        let mock = MockExecutor::new();
+        let result = chain.run_with_executor(&mock);
+        assert_eq!(result.status, "nok");
+        assert_eq!(mock.call_count(), 0);
+        match &result.errors[..] {
+            [AtentoError::UnresolvedReference { reference, context }] => {
+                assert_eq!(
+                    reference,
+                    "environment variable 'ATENTO_TEST_CHAIN_ENV_UNSET'"
+                );
+                assert_eq!(context, "input 'token' of step 'deploy'");
+            }
+            other => panic!("expected an unresolved reference, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_env_input_counts_as_used_only_when_referenced() {
+        let unused: Chain = r"
+steps:
+  deploy:
+    type: bash
+    script: deploy
+    inputs:
+      token:
+        env: ATENTO_TEST_CHAIN_ENV_UNUSED
+"
+        .parse()
+        .unwrap();
+        let err = unused.validate().unwrap_err();
+        assert!(err.to_string().contains("declared but never used"), "{err}");
+
+        let empty: Chain = "steps:\n  s:\n    type: bash\n    script: echo {{ inputs.v }}\n    inputs:\n      v:\n        env: ''\n"
+            .parse()
+            .unwrap();
+        let err = empty.validate().unwrap_err();
+        assert!(
+            err.to_string().contains("names no environment variable"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn test_prev_placeholders_name_the_previous_step() {
+        use crate::tests::mock_executor::MockExecutor;
+
+        let chain: Chain = r"
+steps:
+  version:
+    type: bash
+    script: git describe
+  tag:
+    type: bash
+    script: echo {{ prev.stdout }} {{ prev.exit_code }} {{ steps.version.stdout }}
+  report:
+    type: bash
+    script: echo '{{ prev.stderr }}'
+"
+        .parse()
+        .unwrap();
+        chain.validate().unwrap();
+
+        let mut mock = MockExecutor::new();
+        mock.expect_call("git describe", mock_output("  v1.2\n"))
+            .expect_call(
+                "echo v1.2 0 v1.2",
+                crate::executor::ExecutionResult {
+                    stdout: "tagged".to_string(),
+                    stderr: "tag pushed".to_string(),
+                    duration_ms: 1,
+                    ..crate::executor::ExecutionResult::default()
+                },
+            );
+        let result = chain.run_with_executor(&mock);
+
+        assert_eq!(result.status, "ok", "errors: {:?}", result.errors);
+        let steps = result.steps.unwrap();
+        assert_eq!(steps["tag"].stdout.as_deref(), Some("tagged"));
+        assert_eq!(mock.last_call().unwrap().0, "echo 'tag pushed'");
+    }
+
+    #[test]
+    fn test_prev_placeholder_in_first_step_is_rejected() {
+        let chain: Chain = "steps:\n  first:\n    type: bash\n    script: echo {{ prev.stdout }}\n"
+            .parse()
+            .unwrap();
+
+        let err = chain.validate().unwrap_err();
+        assert!(
+            matches!(&err, AtentoError::UnresolvedReference { reference, .. } if reference == "prev.stdout"),
+            "{err}"
+        );
+        assert!(err.to_string().contains("no previous step"), "{err}");
+    }
+
+    #[test]
+    fn test_prev_placeholder_orders_after_the_previous_step() {
+        let chain: Chain = r"
+steps:
+  a:
+    type: bash
+    script: echo a
+  b:
+    type: bash
+    script: echo {{ prev.stdout }}
+    needs: [c]
+  c:
+    type: bash
+    script: echo c
+"
+        .parse()
+        .unwrap();
+        chain.validate().unwrap();
+
+        let plan = chain.execution_plan();
+        assert_eq!(plan.steps[1].depends_on, vec!["a", "c"]);
+        let order: Vec<&String> = chain.execution_order().unwrap();
+        assert_eq!(order, vec!["a", "c", "b"]);
+    }
+
+    #[test]
+    fn test_unmatched_optional_output_fails_the_result_not_the_step() {
+        use crate::tests::mock_executor::MockExecutor;
+
+        let chain: Chain = r#"
+steps:
+  scan:
+    type: bash
+    script: ./scan.sh
+    outputs:
+      cve:
+        pattern: "CVE-([0-9-]+)"
+        optional: true
+results:
+  first_cve:
+    ref: steps.scan.outputs.cve
+  exit:
+    ref: steps.scan.exit_code
+"#
+        .parse()
+        .unwrap();
+        chain.validate().unwrap();
+
+        let result = chain.run_with_executor(&MockExecutor::new());
+
+        let steps = result.steps.unwrap();
+        assert!(steps["scan"].error.is_none());
+        assert!(!steps["scan"].outputs.contains_key("cve"));
+        assert_eq!(result.results.unwrap()["exit"], "0");
+        assert_eq!(result.errors.len(), 1);
+        match &result.errors[0] {
+            AtentoError::UnresolvedReference { reference, context } => {
+                assert_eq!(reference, "steps.scan.outputs.cve");
+                assert!(context.contains("chain result 'first_cve'"), "{context}");
+                assert!(
+                    context.contains("optional output did not match"),
+                    "{context}"
+                );
+            }
+            other => panic!("Expected UnresolvedReference, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_run_records_step_and_chain_timestamps_in_order() {
+        let chain: Chain = r"
+steps:
+  first:
+    type: bash
+    script: sleep 0.01
+  second:
+    type: bash
+    script: sleep 0.01
+  third:
+    type: bash
+    script: echo done
+"
+        .parse()
+        .unwrap();
+
+        let result = chain.run();
+        let chain_started = result.started_at.clone().unwrap();
+        let chain_finished = result.finished_at.clone().unwrap();
+        let steps = result.steps.clone().unwrap();
+
+        // Fixed-width UTC timestamps compare in time order as strings
+        let mut previous_finish = chain_started.clone();
+        for (step_key, step) in &steps {
+            let started = step.started_at.clone().unwrap();
+            let finished = step.finished_at.clone().unwrap();
+            assert!(
+                started >= previous_finish,
+                "{step_key} started before the previous step finished"
+            );
+            assert!(finished >= started, "{step_key} finished before it started");
+            previous_finish = finished;
+        }
+        assert!(chain_finished >= previous_finish);
+
+        let json = serde_json::to_value(&result).unwrap();
+        assert_eq!(json["started_at"], serde_json::json!(chain_started));
+        assert!(json["steps"]["first"]["finished_at"].is_string());
+    }
+
+    #[test]
+    fn test_timestamps_of_foreach_steps_and_dry_runs() {
+        let chain: Chain = r"
+parameters:
+  items:
+    value: a,b
+steps:
+  each:
+    type: bash
+    script: echo {{ inputs.item }}
+    foreach: parameters.items
+"
+        .parse()
+        .unwrap();
+
+        let result = chain.run();
+        let step = &result.steps.unwrap()["each"];
+        let first = &step.iterations[0];
+        let last = &step.iterations[1];
+        assert!(step.started_at.clone().unwrap() <= first.started_at.clone().unwrap());
+        assert!(last.finished_at.clone().unwrap() <= step.finished_at.clone().unwrap());
+
+        let dry = chain.dry_run();
+        assert!(dry.started_at.is_some() && dry.finished_at.is_some());
+        assert!(dry.steps.unwrap()["each"].started_at.is_none());
+    }
+
+    #[test]
+    fn test_run_id_and_metadata_are_recorded_and_passed_to_steps() {
+        let chain: Chain = r"
+metadata:
+  owner: platform-team
+  ticket: OPS-42
+steps:
+  first:
+    type: bash
+    script: echo $ATENTO_RUN_ID
+  second:
+    type: bash
+    script: echo second
+    env:
+      STAGE: test
+"
+        .parse()
+        .unwrap();
+        chain.validate().unwrap();
+
+        let result = chain.run();
+        assert_eq!(result.status, "ok", "{:?}", result.errors);
+        let run_id = result.run_id.clone().unwrap();
+        let parsed = uuid::Uuid::parse_str(&run_id).unwrap();
+        assert_eq!(parsed.get_version_num(), 4);
+        let steps = result.steps.as_ref().unwrap();
+        assert_eq!(steps["first"].stdout.as_deref(), Some(run_id.as_str()));
+        // The run id is not listed among the step's own variables
+        assert!(steps["first"].env.is_empty());
+        assert_eq!(steps["second"].env, vec!["STAGE"]);
+
+        let keys: Vec<&str> = result.metadata.keys().map(String::as_str).collect();
+        assert_eq!(keys, vec!["owner", "ticket"]);
+        assert_eq!(result.metadata["ticket"], "OPS-42");
+        let json = serde_json::to_value(&result).unwrap();
+        assert_eq!(json["run_id"], run_id.as_str());
+        assert_eq!(json["metadata"]["owner"], "platform-team");
+
+        // Every run gets its own id, in parallel runs too
+        let mut parallel = chain.clone();
+        parallel.parallel = true;
+        let other = parallel.run();
+        assert_ne!(other.run_id, result.run_id);
+        assert_eq!(other.steps.unwrap()["first"].stdout, other.run_id.clone());
+    }
+
+    #[test]
+    fn test_dry_runs_keep_metadata_without_a_run_id() {
+        let chain: Chain =
+            "metadata:\n  env: staging\nsteps:\n  s:\n    type: bash\n    script: echo\n"
+                .parse()
+                .unwrap();
+
+        let dry = chain.dry_run();
+        assert_eq!(dry.run_id, None);
+        assert_eq!(dry.metadata["env"], "staging");
+
+        let json = serde_json::to_value(Chain::default().dry_run()).unwrap();
+        assert!(json.get("run_id").is_none() && json.get("metadata").is_none());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_cancelling_stops_the_running_step_and_the_chain() {
+        use crate::cancel::CancellationToken;
+        use crate::executor::SystemExecutor;
+
+        let chain: Chain = r"
+steps:
+  slow:
+    type: bash
+    script: sleep 30
+    continue_on_error: true
+    on_failure: cleanup
+  after:
+    type: bash
+    script: echo after
+  cleanup:
+    type: bash
+    script: echo cleanup
+"
+        .parse()
+        .unwrap();
+
+        let token = CancellationToken::new();
+        let client = token.clone();
+        let handle = std::thread::spawn(move || {
+            std::thread::sleep(std::time::Duration::from_millis(300));
+            client.cancel();
+        });
+        let result = chain.run_with_cancellation(&SystemExecutor, &token);
+        handle.join().unwrap();
+
+        assert_eq!(result.status, "cancelled");
+        assert!(result.duration_ms < 10_000, "took {}ms", result.duration_ms);
+        let cancelled = AtentoError::Cancelled {
+            context: "while the script was running".to_string(),
+        };
+        assert_eq!(result.errors, vec![cancelled.clone()]);
+        let steps = result.steps.unwrap();
+        assert_eq!(steps["slow"].error, Some(cancelled));
+        assert!(!steps.contains_key("after") && !steps.contains_key("cleanup"));
+    }
+
+    #[test]
+    #[cfg(all(unix, feature = "resource-metrics"))]
+    fn test_steps_record_their_cpu_time_and_peak_memory() {
+        let chain: Chain = r"
+steps:
+  busy:
+    type: bash
+    script: |
+      i=0
+      while [ $i -lt 200000 ]; do i=$((i + 1)); done
+      echo $i
+"
+        .parse()
+        .unwrap();
+
+        let result = chain.run();
+
+        assert_eq!(result.status, "ok", "{:?}", result.errors);
+        let steps = result.steps.unwrap();
+        let usage = steps["busy"].resource_usage.unwrap();
+        assert!(usage.cpu_ms() > 0, "{usage:?}");
+        assert!(usage.max_rss_kb > 0, "{usage:?}");
+        let json = serde_json::to_value(&steps["busy"]).unwrap();
+        assert!(json["resource_usage"]["user_cpu_ms"].is_u64());
+    }
+
+    #[test]
+    fn test_cancelled_token_stops_the_chain_before_the_next_step() {
+        use crate::cancel::CancellationToken;
+        use crate::tests::mock_executor::MockExecutor;
+
+        let chain: Chain =
+            "steps:\n  first:\n    type: bash\n    script: echo one\n  second:\n    type: bash\n    script: echo two\n"
+                .parse()
+                .unwrap();
+        let mock = MockExecutor::new();
+        let token = CancellationToken::new();
+
+        let result = chain.run_with_cancellation(&mock, &token);
+        assert_eq!(result.status, "ok");
+        assert_eq!(mock.call_count(), 2);
+        assert_eq!(mock.last_kill().unwrap().cancel, Some(token.clone()));
+
+        token.cancel();
+
+        let result = chain.run_with_cancellation(&mock, &token);
+        assert_eq!(result.status, "cancelled");
+        assert_eq!(mock.call_count(), 2);
+        assert!(result.steps.is_none());
+        assert_eq!(
+            result.errors[0].to_string(),
+            "Cancelled before step 'first'"
+        );
+    }
+
+    const SUBSET_CHAIN: &str = r#"
+steps:
+  build:
+    type: bash
+    script: build
+    outputs:
+      version:
+        pattern: "v(.*)"
+  test:
+    type: bash
+    script: test {{ inputs.version }}
+    inputs:
+      version:
+        ref: steps.build.outputs.version
+  publish:
+    type: bash
+    script: publish {{ inputs.version }}
+    inputs:
+      version:
+        ref: steps.build.outputs.version
+    outputs:
+      url:
+        pattern: "url=(.*)"
+results:
+  version:
+    ref: steps.build.outputs.version
+  url:
+    ref: steps.publish.outputs.url
+"#;
+
+    #[test]
+    fn test_run_subset_only_runs_named_steps_with_seeded_outputs() {
+        use crate::tests::mock_executor::MockExecutor;
+
+        let chain: Chain = SUBSET_CHAIN.parse().unwrap();
+        let mut mock = MockExecutor::new();
+        mock.expect_call("publish 2.0", mock_output("url=https://example.com"));
+
+        let seed = HashMap::from([("steps.build.outputs.version".to_string(), "2.0".to_string())]);
+        let result = chain
+            .run_subset_with_executor(&mock, Some(&["publish".to_string()]), None, seed)
+            .unwrap();
+
+        assert_eq!(result.status, "ok", "errors: {:?}", result.errors);
+        assert_eq!(mock.call_count(), 1);
+        let steps = result.steps.unwrap();
+        assert_eq!(steps.keys().collect::<Vec<_>>(), ["publish"]);
+        let results = result.results.unwrap();
+        assert_eq!(results["version"], "2.0");
+        assert_eq!(results["url"], "https://example.com");
+    }
+
+    #[test]
+    fn test_run_subset_from_runs_to_the_end_and_leaves_out_skipped_results() {
+        use crate::tests::mock_executor::MockExecutor;
+
+        let chain: Chain = SUBSET_CHAIN.parse().unwrap();
+        let mut mock = MockExecutor::new();
+        mock.expect_call("test 3.1", mock_output("passed"));
+        mock.expect_call("publish 3.1", mock_output("url=https://example.com"));
+
+        let seed = HashMap::from([("steps.build.outputs.version".to_string(), "3.1".to_string())]);
+        let result = chain
+            .run_subset_with_executor(&mock, None, Some("test"), seed.clone())
+            .unwrap();
+        assert_eq!(result.status, "ok", "errors: {:?}", result.errors);
+        assert_eq!(
+            result.steps.unwrap().keys().collect::<Vec<_>>(),
+            ["test", "publish"]
+        );
+
+        let only = ["build".to_string(), "test".to_string()];
+        let result = chain
+            .run_subset_with_executor(&mock, Some(&only), Some("test"), seed)
+            .unwrap();
+        assert_eq!(result.status, "ok", "errors: {:?}", result.errors);
+        assert_eq!(result.steps.unwrap().keys().collect::<Vec<_>>(), ["test"]);
+        let results = result.results.unwrap();
+        assert!(!results.contains_key("url"));
+    }
+
+    #[test]
+    fn test_run_subset_without_seed_reports_the_missing_output() {
+        use crate::tests::mock_executor::MockExecutor;
+
+        let chain: Chain = SUBSET_CHAIN.parse().unwrap();
+        let mock = MockExecutor::new();
+
+        let result = chain
+            .run_subset_with_executor(&mock, Some(&["test".to_string()]), None, HashMap::new())
+            .unwrap();
+
+        assert_eq!(result.status, "nok");
+        assert_eq!(mock.call_count(), 0);
+        assert!(
+            result.errors[0]
+                .to_string()
+                .contains("steps.build.outputs.version"),
+            "errors: {:?}",
+            result.errors
+        );
+    }
+
+    #[test]
+    fn test_run_subset_rejects_unknown_steps() {
+        let chain: Chain = SUBSET_CHAIN.parse().unwrap();
+
+        for (only, from) in [
+            (Some(vec!["deploy".to_string()]), None),
+            (None, Some("deploy")),
+        ] {
+            match chain.run_subset(only.as_deref(), from, HashMap::new()) {
+                Err(AtentoError::Validation(msg)) => {
+                    assert!(msg.contains("'deploy'"), "msg: {msg}");
+                }
+                other => panic!("Expected Validation error, got {other:?}"),
+            }
+        }
+    }
 }
@@ -0,0 +1,240 @@
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use crate::chain::Chain;
+    use crate::env::EnvValue;
+    use crate::errors::AtentoError;
+    use crate::input::Input;
+
+    const SETUP: &str = r#"
+default_interpreter: bash
+env:
+  STAGE:
+    value: setup
+parameters:
+  repo:
+    value: atento
+steps:
+  prepare:
+    script: echo "DIR=/tmp/{{ inputs.repo }}"
+    inputs:
+      repo:
+        ref: parameters.repo
+    outputs:
+      dir:
+        pattern: DIR=(.*)
+  check:
+    type: bash
+    script: echo "{{ steps.prepare.outputs.dir }} $LABEL {{ inputs.code }}"
+    needs: [prepare]
+    on_failure: cleanup
+    env:
+      LABEL: "in {{ steps.prepare.outputs.dir }}"
+      DIR:
+        ref: steps.prepare.outputs.dir
+    inputs:
+      code:
+        ref: steps.prepare.exit_code
+  cleanup:
+    type: bash
+    script: echo cleanup
+"#;
+
+    fn ref_of(input: Option<&Input>) -> &str {
+        match input {
+            Some(Input::Ref { ref_ }) => ref_,
+            other => panic!("Expected a reference, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_merge_prefixes_steps_and_rewrites_references() {
+        let mut chain: Chain = "steps:\n  main:\n    type: bash\n    script: echo main\n"
+            .parse()
+            .unwrap();
+        chain.merge(SETUP.parse().unwrap(), "setup_").unwrap();
+
+        let keys: Vec<&str> = chain.steps.keys().map(String::as_str).collect();
+        assert_eq!(
+            keys,
+            vec!["main", "setup_prepare", "setup_check", "setup_cleanup"]
+        );
+
+        let check = &chain.steps["setup_check"];
+        assert_eq!(
+            check.script,
+            "echo \"{{ steps.setup_prepare.outputs.dir }} $LABEL {{ inputs.code }}\""
+        );
+        assert_eq!(check.needs, vec!["setup_prepare"]);
+        assert_eq!(check.on_failure.as_deref(), Some("setup_cleanup"));
+        assert_eq!(
+            ref_of(check.inputs.get("code")),
+            "steps.setup_prepare.exit_code"
+        );
+        match &check.env["LABEL"] {
+            EnvValue::Template(text) => {
+                assert_eq!(text, "in {{ steps.setup_prepare.outputs.dir }}");
+            }
+            other @ EnvValue::Input(_) => panic!("Expected a template, got {other:?}"),
+        }
+        match &check.env["DIR"] {
+            EnvValue::Input(input) => {
+                assert_eq!(ref_of(Some(input)), "steps.setup_prepare.outputs.dir");
+            }
+            other @ EnvValue::Template(_) => panic!("Expected a reference, got {other:?}"),
+        }
+        // Parameters keep their names
+        assert_eq!(
+            ref_of(chain.steps["setup_prepare"].inputs.get("repo")),
+            "parameters.repo"
+        );
+        assert!(chain.validate().is_ok());
+    }
+
+    #[test]
+    fn test_merge_keeps_the_chain_env_and_default_interpreter_of_the_merged_chain() {
+        let mut chain = Chain::default();
+        chain.merge(SETUP.parse().unwrap(), "").unwrap();
+
+        let prepare = &chain.steps["prepare"];
+        assert_eq!(prepare.interpreter.as_deref(), Some("bash"));
+        assert!(prepare.env.contains_key("STAGE"));
+        assert!(chain.env.is_empty());
+        assert!(chain.parameters.contains_key("repo"));
+        assert!(chain.interpreters.contains_key("bash"));
+    }
+
+    #[test]
+    fn test_merge_copies_the_chain_image_into_steps_without_one() {
+        let mut chain = Chain::default();
+        let other: Chain = r"
+image: alpine:3.20
+steps:
+  boxed:
+    type: sh
+    script: echo boxed
+  pinned:
+    type: sh
+    image: busybox:1.36
+    script: echo pinned
+"
+        .parse()
+        .unwrap();
+        chain.merge(other, "").unwrap();
+
+        assert_eq!(chain.steps["boxed"].image.as_deref(), Some("alpine:3.20"));
+        assert_eq!(chain.steps["pinned"].image.as_deref(), Some("busybox:1.36"));
+        assert!(chain.image.is_none());
+    }
+
+    #[test]
+    fn test_merge_leaves_references_to_other_steps_alone() {
+        let mut chain = Chain::default();
+        chain.merge(SETUP.parse().unwrap(), "setup_").unwrap();
+        let deploy: Chain = r"
+steps:
+  deploy:
+    type: bash
+    script: echo deploying {{ inputs.dir }} {{ steps.build.stdout }}
+    inputs:
+      dir:
+        ref: steps.setup_prepare.outputs.dir
+  build:
+    type: bash
+    script: echo built
+"
+        .parse()
+        .unwrap();
+        chain.merge(deploy, "").unwrap();
+
+        let deploy = &chain.steps["deploy"];
+        assert_eq!(
+            ref_of(deploy.inputs.get("dir")),
+            "steps.setup_prepare.outputs.dir"
+        );
+        assert!(deploy.script.contains("{{ steps.build.stdout }}"));
+    }
+
+    #[cfg(feature = "exec")]
+    #[test]
+    fn test_merged_chain_runs_with_rewritten_references() {
+        let mut chain = Chain::default();
+        chain.merge(SETUP.parse().unwrap(), "setup_").unwrap();
+        chain
+            .merge(
+                r#"
+steps:
+  use:
+    type: bash
+    script: echo "USING={{ inputs.dir }}"
+    inputs:
+      dir:
+        ref: steps.setup_prepare.outputs.dir
+    outputs:
+      used:
+        pattern: USING=(.*)
+results:
+  ignored:
+    ref: steps.use.outputs.used
+"#
+                .parse()
+                .unwrap(),
+                "",
+            )
+            .unwrap();
+        chain.validate().unwrap();
+
+        let result = chain.run();
+        assert!(result.errors.is_empty(), "{:?}", result.errors);
+        let steps = result.steps.unwrap();
+        assert_eq!(
+            steps["use"].outputs["used"],
+            serde_json::json!("/tmp/atento")
+        );
+        assert_eq!(
+            steps["setup_check"].stdout.as_deref(),
+            Some("/tmp/atento in /tmp/atento 0")
+        );
+        // Results of merged chains are not merged
+        assert!(chain.results.is_empty());
+    }
+
+    #[test]
+    fn test_merge_rejects_parameter_collisions_without_changing_the_chain() {
+        let mut chain: Chain =
+            "parameters:\n  repo:\n    value: other\nsteps:\n  main:\n    type: bash\n    script: echo\n"
+                .parse()
+                .unwrap();
+
+        match chain.merge(SETUP.parse().unwrap(), "setup_") {
+            Err(AtentoError::Validation(msg)) => {
+                assert!(msg.contains("parameter 'repo'"), "{msg}");
+            }
+            other => panic!("Expected Validation error, got {other:?}"),
+        }
+        assert_eq!(chain.steps.len(), 1);
+    }
+
+    #[test]
+    fn test_merge_rejects_step_key_collisions() {
+        let mut chain: Chain = "steps:\n  setup_prepare:\n    type: bash\n    script: echo\n"
+            .parse()
+            .unwrap();
+
+        let err = chain.merge(SETUP.parse().unwrap(), "setup_").unwrap_err();
+        assert!(err.to_string().contains("step 'setup_prepare'"), "{err}");
+    }
+
+    #[test]
+    fn test_merge_rejects_conflicting_interpreters() {
+        let mut chain: Chain = "interpreters:\n  ruby:\n    command: ruby\n    extension: .rb\n"
+            .parse()
+            .unwrap();
+        let other: Chain = "interpreters:\n  ruby:\n    command: jruby\n    extension: .rb\n"
+            .parse()
+            .unwrap();
+
+        let err = chain.merge(other, "x_").unwrap_err();
+        assert!(err.to_string().contains("interpreter 'ruby'"), "{err}");
+    }
+}
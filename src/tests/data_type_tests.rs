@@ -1,7 +1,7 @@
 #[cfg(test)]
 #[allow(clippy::unwrap_used, clippy::approx_constant)]
 mod tests {
-    use crate::data_type::{DataType, to_string_value};
+    use crate::data_type::{DataType, rfc3339_timestamp, to_json_value, to_string_value, to_text};
     use crate::errors::AtentoError;
     use serde_yaml::Value;
 
@@ -200,4 +200,213 @@ mod tests {
         let dt: DataType = serde_json::from_str(json).unwrap();
         assert_eq!(dt, DataType::Int);
     }
+
+    #[test]
+    fn test_to_json_value_int_and_float() {
+        assert_eq!(
+            to_json_value(&DataType::Int, "42").unwrap(),
+            serde_json::json!(42)
+        );
+        assert_eq!(
+            to_json_value(&DataType::Int, "-7").unwrap(),
+            serde_json::json!(-7)
+        );
+        assert_eq!(
+            to_json_value(&DataType::Float, "3.5").unwrap(),
+            serde_json::json!(3.5)
+        );
+
+        let err = to_json_value(&DataType::Int, "4.2").unwrap_err();
+        assert!(matches!(err, AtentoError::TypeConversion { .. }));
+        assert!(to_json_value(&DataType::Float, "abc").is_err());
+        assert!(to_json_value(&DataType::Float, "NaN").is_err());
+    }
+
+    #[test]
+    fn test_to_json_value_bool() {
+        for (text, expected) in [("true", true), ("1", true), ("false", false), ("0", false)] {
+            assert_eq!(
+                to_json_value(&DataType::Bool, text).unwrap(),
+                serde_json::json!(expected)
+            );
+        }
+        assert!(to_json_value(&DataType::Bool, "yes").is_err());
+    }
+
+    #[test]
+    fn test_to_json_value_datetime() {
+        for text in [
+            "2024-01-15T10:30:00Z",
+            "2024-02-29T23:59:59.123+02:00",
+            "2024-12-31 00:00:00-05:30",
+        ] {
+            assert_eq!(
+                to_json_value(&DataType::DateTime, text).unwrap(),
+                serde_json::json!(text)
+            );
+        }
+
+        for text in [
+            "2024-01-15",
+            "2023-02-29T10:00:00Z",
+            "2024-13-01T10:00:00Z",
+            "2024-01-15T24:00:00Z",
+            "2024-01-15T10:30:00",
+        ] {
+            assert!(
+                to_json_value(&DataType::DateTime, text).is_err(),
+                "{text} should be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn test_to_json_value_string_and_to_text() {
+        let value = to_json_value(&DataType::String, "hello").unwrap();
+        assert_eq!(value, serde_json::json!("hello"));
+        assert_eq!(to_text(&value), "hello");
+        assert_eq!(to_text(&serde_json::json!(42)), "42");
+        assert_eq!(to_text(&serde_json::json!(true)), "true");
+    }
+
+    #[test]
+    fn test_list_to_string_value() {
+        let value: Value = serde_yaml::from_str(r#"["-v", "--fast", 3, true]"#).unwrap();
+        assert_eq!(DataType::List.to_string(), "list");
+        assert_eq!(
+            to_string_value(&DataType::List, &value).unwrap(),
+            "-v --fast 3 true"
+        );
+        assert_eq!(
+            crate::data_type::to_list_string(&value, ",").unwrap(),
+            "-v,--fast,3,true"
+        );
+    }
+
+    #[test]
+    fn test_list_rejects_non_scalar_elements() {
+        let nested: Value = serde_yaml::from_str("[a, [b, c]]").unwrap();
+        match to_string_value(&DataType::List, &nested) {
+            Err(AtentoError::TypeConversion { expected, .. }) => {
+                assert_eq!(expected, "list of scalars");
+            }
+            other => panic!("Expected TypeConversion, got {other:?}"),
+        }
+
+        let scalar = Value::String("-v".to_string());
+        match to_string_value(&DataType::List, &scalar) {
+            Err(AtentoError::TypeConversion { expected, .. }) => assert_eq!(expected, "list"),
+            other => panic!("Expected TypeConversion, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_list_to_json_value() {
+        assert_eq!(
+            to_json_value(&DataType::List, " a b\nc ").unwrap(),
+            serde_json::json!(["a", "b", "c"])
+        );
+        assert_eq!(
+            to_json_value(&DataType::List, "").unwrap(),
+            serde_json::json!([])
+        );
+    }
+
+    #[test]
+    fn test_list_deserialize() {
+        let data_type: DataType = serde_yaml::from_str("list").unwrap();
+        assert_eq!(data_type, DataType::List);
+    }
+
+    #[test]
+    fn test_from_text() {
+        use crate::data_type::from_text;
+
+        assert_eq!(
+            from_text(&DataType::Int, "42").unwrap(),
+            Value::Number(42.into())
+        );
+        assert_eq!(
+            from_text(&DataType::Bool, "false").unwrap(),
+            Value::Bool(false)
+        );
+        assert_eq!(
+            from_text(&DataType::String, " keep spaces ").unwrap(),
+            Value::String(" keep spaces ".to_string())
+        );
+        assert_eq!(
+            from_text(&DataType::List, "a b").unwrap(),
+            serde_yaml::from_str::<Value>("[a, b]").unwrap()
+        );
+        assert!(matches!(
+            from_text(&DataType::Int, "abc"),
+            Err(AtentoError::TypeConversion { .. })
+        ));
+    }
+
+    #[test]
+    fn test_validate_value() {
+        let yaml = |text: &str| serde_yaml::from_str::<Value>(text).unwrap();
+
+        assert!(DataType::Int.validate_value(&yaml("42")).is_ok());
+        assert!(DataType::Int.validate_value(&yaml("\"42\"")).is_ok());
+        assert!(
+            DataType::Int
+                .validate_value(&yaml("\"not a number\""))
+                .is_err()
+        );
+        assert!(DataType::Float.validate_value(&yaml("\"2.5\"")).is_ok());
+        assert!(DataType::Bool.validate_value(&yaml("true")).is_ok());
+        assert!(DataType::Bool.validate_value(&yaml("\"true\"")).is_err());
+        assert!(DataType::String.validate_value(&yaml("hello")).is_ok());
+        assert!(DataType::List.validate_value(&yaml("[a, 1]")).is_ok());
+        assert!(DataType::List.validate_value(&yaml("[[a]]")).is_err());
+
+        assert!(
+            DataType::DateTime
+                .validate_value(&yaml("\"2024-01-15T10:30:00Z\""))
+                .is_ok()
+        );
+        match DataType::DateTime.validate_value(&yaml("\"next tuesday\"")) {
+            Err(AtentoError::TypeConversion { expected, .. }) => {
+                assert_eq!(expected, "RFC 3339 datetime");
+            }
+            other => panic!("Expected TypeConversion, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_numeric_strings_coerce() {
+        assert_eq!(
+            to_string_value(&DataType::Int, &Value::String(" 7 ".to_string())).unwrap(),
+            "7"
+        );
+        assert_eq!(
+            to_string_value(&DataType::Float, &Value::String("1.5".to_string())).unwrap(),
+            "1.5"
+        );
+    }
+
+    #[test]
+    fn test_rfc3339_timestamp() {
+        use std::time::{Duration, UNIX_EPOCH};
+
+        assert_eq!(rfc3339_timestamp(UNIX_EPOCH), "1970-01-01T00:00:00.000Z");
+        assert_eq!(
+            rfc3339_timestamp(UNIX_EPOCH + Duration::from_millis(1_709_210_096_789)),
+            "2024-02-29T12:34:56.789Z"
+        );
+        assert_eq!(
+            rfc3339_timestamp(UNIX_EPOCH + Duration::from_secs(946_684_799)),
+            "1999-12-31T23:59:59.000Z"
+        );
+
+        let now = rfc3339_timestamp(std::time::SystemTime::now());
+        assert!(
+            DataType::DateTime
+                .validate_value(&Value::String(now.clone()))
+                .is_ok(),
+            "{now}"
+        );
+    }
 }
@@ -0,0 +1,225 @@
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use crate::chain::Chain;
+    use crate::diagnostic::{Diagnostic, Severity};
+
+    fn codes(diagnostics: &[Diagnostic]) -> Vec<(&str, Option<&str>)> {
+        diagnostics
+            .iter()
+            .map(|d| (d.code.as_str(), d.step.as_deref()))
+            .collect()
+    }
+
+    #[test]
+    fn test_validate_all_clean_chain_has_no_findings() {
+        let chain: Chain = r"
+steps:
+  build:
+    type: bash
+    script: echo version=1
+    outputs:
+      version:
+        pattern: version=(\d)
+results:
+  version:
+    ref: steps.build.outputs.version
+"
+        .parse()
+        .unwrap();
+
+        assert!(chain.validate_all().is_empty());
+    }
+
+    #[test]
+    fn test_validate_all_reports_every_error() {
+        let chain: Chain = r"
+steps:
+  early:
+    type: bash
+    script: echo {{ inputs.late }}
+    inputs:
+      late:
+        ref: steps.late.outputs.value
+  missing:
+    type: bash
+    script: echo {{ inputs.value }}
+    inputs:
+      value:
+        ref: parameters.nope
+  late:
+    type: bash
+    script: echo value=1
+    outputs:
+      value:
+        pattern: '(unclosed'
+  odd:
+    type: cobol
+    script: DISPLAY 'hi'
+"
+        .parse()
+        .unwrap();
+
+        let diagnostics = chain.validate_all();
+        assert_eq!(
+            codes(&diagnostics),
+            vec![
+                ("forward-reference", Some("early")),
+                ("unresolved-reference", Some("missing")),
+                ("invalid-regex", Some("late")),
+                ("unknown-interpreter", Some("odd")),
+            ]
+        );
+        assert!(
+            diagnostics
+                .iter()
+                .all(|d| d.severity == Severity::Error && d.result.is_none())
+        );
+        assert!(diagnostics[2].message.contains("invalid regex pattern"));
+    }
+
+    #[test]
+    fn test_validate_all_first_error_matches_validate() {
+        let chain: Chain = r"
+steps:
+  a:
+    type: bash
+    script: echo {{ inputs.x }}
+    inputs:
+      x:
+        ref: parameters.nope
+  b:
+    type: cobol
+    script: echo hi
+"
+        .parse()
+        .unwrap();
+
+        let first = chain
+            .validate_all()
+            .into_iter()
+            .find(|d| d.severity == Severity::Error)
+            .unwrap();
+        assert_eq!(first.message, chain.validate().unwrap_err().to_string());
+    }
+
+    #[test]
+    fn test_validate_all_warnings_do_not_fail_the_chain() {
+        let chain: Chain = r"
+steps:
+  build:
+    type: bash
+    script: echo version=1
+    inputs:
+      unused:
+        type: string
+        value: x
+    outputs:
+      version:
+        pattern: version=(\d)
+      build:
+        pattern: build=(\d)
+  report:
+    type: bash
+    script: echo {{ inputs.version }}
+    inputs:
+      version:
+        ref: steps.build.outputs.version
+"
+        .parse()
+        .unwrap();
+
+        let diagnostics = chain.validate_all();
+        assert_eq!(
+            codes(&diagnostics),
+            vec![
+                ("unused-input", Some("build")),
+                ("unreferenced-output", Some("build")),
+            ]
+        );
+        assert!(diagnostics.iter().all(|d| d.severity == Severity::Warning));
+        assert!(diagnostics[1].message.contains("'build'"));
+
+        // Unreferenced outputs are only reported by validate_all
+        assert_eq!(chain.validate_detailed().warnings.len(), 1);
+    }
+
+    #[test]
+    fn test_validate_all_names_the_result() {
+        let chain: Chain = r"
+steps:
+  s:
+    type: bash
+    script: echo hi
+results:
+  good:
+    ref: steps.s.exit_code
+  bad:
+    ref: steps.s.outputs.missing
+  worse:
+    type: int
+    value: abc
+"
+        .parse()
+        .unwrap();
+
+        let diagnostics = chain.validate_all();
+        let found: Vec<(&str, Option<&str>)> = diagnostics
+            .iter()
+            .map(|d| (d.code.as_str(), d.result.as_deref()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("unresolved-reference", Some("bad")),
+                ("invalid-value", Some("worse")),
+            ]
+        );
+        assert!(diagnostics.iter().all(|d| d.step.is_none()));
+    }
+
+    #[test]
+    fn test_validate_all_reports_chain_level_findings() {
+        let chain: Chain = r"
+default_interpreter: cobol
+parameters:
+  missing:
+    type: string
+steps:
+  a:
+    type: bash
+    script: echo hi
+    needs: [b]
+  b:
+    type: bash
+    script: echo hi
+    needs: [a]
+"
+        .parse()
+        .unwrap();
+
+        assert_eq!(
+            codes(&chain.validate_all()),
+            vec![
+                ("invalid-parameter", None),
+                ("invalid-config", None),
+                ("dependency-cycle", None),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_diagnostic_serializes_for_tooling() {
+        let chain: Chain = "steps:\n  s:\n    type: cobol\n    script: echo hi\n"
+            .parse()
+            .unwrap();
+
+        let json = serde_json::to_value(chain.validate_all()).unwrap();
+        let finding = &json[0];
+        assert_eq!(finding["severity"], "error");
+        assert_eq!(finding["code"], "unknown-interpreter");
+        assert_eq!(finding["step"], "s");
+        assert!(finding.get("result").is_none());
+        assert!(finding["message"].as_str().unwrap().contains("cobol"));
+    }
+}
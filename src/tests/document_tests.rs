@@ -0,0 +1,224 @@
+#[cfg(all(test, feature = "toml", feature = "json-input"))]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use crate::chain::{Chain, ChainResult};
+    use crate::errors::AtentoError;
+    use crate::location::SourceLocation;
+
+    const YAML: &str = r#"
+name: release
+timeout: 120
+metadata:
+  owner: platform
+parameters:
+  project:
+    value: atento
+  retries:
+    type: int
+    value: 3
+steps:
+  version:
+    type: bash
+    script: echo "{{ inputs.project }} version=1.2.3"
+    inputs:
+      project:
+        ref: parameters.project
+    outputs:
+      version:
+        pattern: 'version=([\d.]+)'
+  tag:
+    type: bash
+    script: echo v{{ inputs.version }}
+    needs: [version]
+    env:
+      STAGE: release
+    inputs:
+      version:
+        ref: steps.version.outputs.version
+results:
+  version:
+    ref: steps.version.outputs.version
+"#;
+
+    const TOML: &str = r#"
+name = "release"
+timeout = 120
+
+[metadata]
+owner = "platform"
+
+[parameters.project]
+value = "atento"
+
+[parameters.retries]
+type = "int"
+value = 3
+
+[steps.version]
+type = "bash"
+script = 'echo "{{ inputs.project }} version=1.2.3"'
+inputs.project.ref = "parameters.project"
+outputs.version.pattern = 'version=([\d.]+)'
+
+[steps.tag]
+type = "bash"
+script = "echo v{{ inputs.version }}"
+needs = ["version"]
+env.STAGE = "release"
+inputs.version.ref = "steps.version.outputs.version"
+
+[results.version]
+ref = "steps.version.outputs.version"
+"#;
+
+    const JSON: &str = r#"{
+  "name": "release",
+  "timeout": 120,
+  "metadata": { "owner": "platform" },
+  "parameters": {
+    "project": { "value": "atento" },
+    "retries": { "type": "int", "value": 3 }
+  },
+  "steps": {
+    "version": {
+      "type": "bash",
+      "script": "echo \"{{ inputs.project }} version=1.2.3\"",
+      "inputs": { "project": { "ref": "parameters.project" } },
+      "outputs": { "version": { "pattern": "version=([\\d.]+)" } }
+    },
+    "tag": {
+      "type": "bash",
+      "script": "echo v{{ inputs.version }}",
+      "needs": ["version"],
+      "env": { "STAGE": "release" },
+      "inputs": { "version": { "ref": "steps.version.outputs.version" } }
+    }
+  },
+  "results": { "version": { "ref": "steps.version.outputs.version" } }
+}"#;
+
+    fn dry_run(chain: &Chain) -> ChainResult {
+        let mut result = chain.dry_run();
+        result.duration_ms = 0;
+        result.started_at = None;
+        result.finished_at = None;
+        result
+    }
+
+    fn assert_same_chain(chain: &Chain, expected: &Chain) {
+        chain.validate().unwrap();
+        assert_eq!(chain.name, expected.name);
+        assert_eq!(chain.timeout, expected.timeout);
+        assert_eq!(chain.metadata, expected.metadata);
+        assert_eq!(chain.interpreters, expected.interpreters);
+        assert_eq!(
+            serde_json::to_value(&chain.parameters).unwrap(),
+            serde_json::to_value(&expected.parameters).unwrap()
+        );
+        assert_eq!(chain.execution_plan(), expected.execution_plan());
+        assert_eq!(dry_run(chain), dry_run(expected));
+    }
+
+    #[test]
+    fn test_same_chain_from_yaml_toml_and_json() {
+        let yaml: Chain = YAML.parse().unwrap();
+
+        assert_same_chain(&Chain::from_toml(TOML).unwrap(), &yaml);
+        assert_same_chain(&Chain::from_json(JSON).unwrap(), &yaml);
+        // Default interpreters are merged in whatever the format
+        assert!(
+            Chain::from_json("{}")
+                .unwrap()
+                .interpreters
+                .contains_key("bash")
+        );
+    }
+
+    #[test]
+    fn test_chain_files_are_parsed_by_extension() {
+        let dir = tempfile::tempdir().unwrap();
+        let yaml: Chain = YAML.parse().unwrap();
+
+        for (name, text) in [("chain.toml", TOML), ("chain.json", JSON)] {
+            let path = dir.path().join(name);
+            std::fs::write(&path, text).unwrap();
+            let chain = Chain::load_with_includes(&path).unwrap();
+            assert_same_chain(&chain, &yaml);
+            assert!(chain.source_map.is_none());
+        }
+
+        // Files of different formats can include each other
+        std::fs::write(
+            dir.path().join("main.yaml"),
+            "include: [chain.toml]\nsteps:\n  extra:\n    type: bash\n    script: echo extra\n",
+        )
+        .unwrap();
+        let chain = Chain::load_with_includes(dir.path().join("main.yaml")).unwrap();
+        let ids: Vec<&str> = chain.steps.keys().map(String::as_str).collect();
+        assert_eq!(ids, vec!["version", "tag", "extra"]);
+    }
+
+    #[test]
+    fn test_syntax_errors_are_parse_errors_with_a_location() {
+        match Chain::from_toml("name = \"release\"\ntimeout = \n").unwrap_err() {
+            AtentoError::Parse {
+                format,
+                context,
+                message,
+                location,
+            } => {
+                assert_eq!(format, "TOML");
+                assert_eq!(context, "<string>");
+                assert_eq!(location.map(|l| l.line), Some(2));
+                assert!(
+                    message.ends_with(&location.unwrap().to_string()),
+                    "{message}"
+                );
+            }
+            other => panic!("Expected Parse error, got {other:?}"),
+        }
+
+        let err = Chain::from_json("{\n  \"name\": release\n}").unwrap_err();
+        match &err {
+            AtentoError::Parse {
+                format, location, ..
+            } => {
+                assert_eq!(format, "JSON");
+                assert_eq!(
+                    *location,
+                    Some(SourceLocation {
+                        line: 2,
+                        column: 11
+                    })
+                );
+            }
+            other => panic!("Expected Parse error, got {other:?}"),
+        }
+        assert!(
+            err.to_string()
+                .starts_with("Failed to parse JSON in '<string>': ")
+        );
+    }
+
+    #[test]
+    fn test_toml_and_json_chains_are_checked_like_yaml_chains() {
+        let err =
+            Chain::from_json(r#"{"steps": {"s": {"type": "bash", "outpts": {}}}}"#).unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("'outpts' (did you mean 'outputs'?)"),
+            "{err}"
+        );
+        assert!(Chain::from_toml("lenient = true\nunknown = 1\n").is_ok());
+
+        match Chain::from_toml("timeout = \"fast\"\n").unwrap_err() {
+            AtentoError::Parse {
+                format, location, ..
+            } => {
+                assert_eq!(format, "TOML");
+                assert_eq!(location, None);
+            }
+            other => panic!("Expected Parse error, got {other:?}"),
+        }
+    }
+}
@@ -0,0 +1,44 @@
+/// Sets or removes a host environment variable for the lifetime of the guard,
+/// restoring the previous value on drop.
+///
+/// Tests run on parallel threads, so each test must use a variable name of
+/// its own.
+pub struct EnvGuard {
+    name: String,
+    previous: Option<std::ffi::OsString>,
+}
+
+impl EnvGuard {
+    pub fn set(name: &str, value: &str) -> Self {
+        let guard = Self::capture(name);
+        // SAFETY: the variable is only read by the test that owns this guard
+        unsafe { std::env::set_var(name, value) };
+        guard
+    }
+
+    pub fn unset(name: &str) -> Self {
+        let guard = Self::capture(name);
+        // SAFETY: the variable is only read by the test that owns this guard
+        unsafe { std::env::remove_var(name) };
+        guard
+    }
+
+    fn capture(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            previous: std::env::var_os(name),
+        }
+    }
+}
+
+impl Drop for EnvGuard {
+    fn drop(&mut self) {
+        // SAFETY: the variable is only read by the test that owns this guard
+        unsafe {
+            match &self.previous {
+                Some(value) => std::env::set_var(&self.name, value),
+                None => std::env::remove_var(&self.name),
+            }
+        }
+    }
+}
@@ -6,6 +6,7 @@
 )]
 mod tests {
     use crate::errors::AtentoError;
+    use crate::location::SourceLocation;
     use std::error::Error;
 
     #[test]
@@ -25,6 +26,7 @@ mod tests {
         let err = AtentoError::YamlParse {
             context: "chain.yaml".to_string(),
             source: yaml_err,
+            location: None,
         };
         let display = format!("{err}");
         assert!(display.contains("Failed to parse YAML in 'chain.yaml'"));
@@ -117,6 +119,7 @@ mod tests {
         let err = AtentoError::YamlParse {
             context: "test".to_string(),
             source: yaml_err,
+            location: None,
         };
         assert!(err.source().is_some());
     }
@@ -196,6 +199,7 @@ mod tests {
         let err = AtentoError::YamlParse {
             context: "test.yaml".to_string(),
             source: yaml_err,
+            location: None,
         };
         let json = serde_json::to_string(&err).unwrap();
         assert!(json.contains("YamlParse"));
@@ -213,6 +217,7 @@ mod tests {
             AtentoError::YamlParse {
                 context: "context".to_string(),
                 source: serde_yaml::from_str::<serde_yaml::Value>("bad: yaml: {").unwrap_err(),
+                location: None,
             },
             AtentoError::JsonSerialize {
                 message: "json error".to_string(),
@@ -235,6 +240,9 @@ mod tests {
                 context: "timeout".to_string(),
                 timeout_secs: 30,
             },
+            AtentoError::Cancelled {
+                context: "before step 'deploy'".to_string(),
+            },
             AtentoError::Runner("runner error".to_string()),
         ];
 
@@ -243,4 +251,100 @@ mod tests {
             assert!(json.is_ok(), "Failed to serialize error: {err:?}");
         }
     }
+
+    #[test]
+    fn test_error_deserialize_roundtrip() {
+        let err = AtentoError::StepExecution {
+            step: "build".to_string(),
+            reason: "exit 1".to_string(),
+        };
+        let json = serde_json::to_string(&err).unwrap();
+        let restored: AtentoError = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.to_string(), err.to_string());
+
+        let io_err = AtentoError::Io {
+            path: "chain.yaml".to_string(),
+            source: std::io::Error::new(std::io::ErrorKind::NotFound, "missing"),
+        };
+        let json = serde_json::to_string(&io_err).unwrap();
+        let restored: AtentoError = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.to_string(), io_err.to_string());
+
+        let parse_err = AtentoError::Parse {
+            format: "TOML".to_string(),
+            context: "chain.toml".to_string(),
+            message: "invalid string at line 2 column 9".to_string(),
+            location: Some(SourceLocation { line: 2, column: 9 }),
+        };
+        let json = serde_json::to_string(&parse_err).unwrap();
+        let restored: AtentoError = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored, parse_err);
+        assert_eq!(restored.clone(), parse_err);
+        assert_eq!(
+            parse_err.to_string(),
+            "Failed to parse TOML in 'chain.toml': invalid string at line 2 column 9"
+        );
+
+        let cancelled = AtentoError::Cancelled {
+            context: "while the script was running".to_string(),
+        };
+        let json = serde_json::to_string(&cancelled).unwrap();
+        let restored: AtentoError = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored, cancelled);
+        assert_ne!(
+            restored,
+            AtentoError::Runner("while the script was running".to_string())
+        );
+        assert_eq!(
+            cancelled.to_string(),
+            "Cancelled while the script was running"
+        );
+    }
+
+    #[test]
+    fn test_error_clone_keeps_message() {
+        let io_err = AtentoError::Io {
+            path: "chain.yaml".to_string(),
+            source: std::io::Error::new(std::io::ErrorKind::NotFound, "missing"),
+        };
+        let cloned = io_err.clone();
+        assert_eq!(cloned.to_string(), io_err.to_string());
+        assert!(matches!(
+            cloned,
+            AtentoError::Io { source, .. } if source.kind() == std::io::ErrorKind::NotFound
+        ));
+
+        let yaml_err: AtentoError = "steps: [".parse::<crate::chain::Chain>().unwrap_err();
+        assert_eq!(yaml_err.clone().to_string(), yaml_err.to_string());
+    }
+
+    #[test]
+    fn test_error_equality() {
+        let io_err = AtentoError::Io {
+            path: "chain.yaml".to_string(),
+            source: std::io::Error::new(std::io::ErrorKind::NotFound, "missing"),
+        };
+        let json = serde_json::to_string(&io_err).unwrap();
+        let restored: AtentoError = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored, io_err);
+
+        assert_eq!(
+            AtentoError::Validation("bad".to_string()),
+            AtentoError::Validation("bad".to_string())
+        );
+        assert_ne!(
+            AtentoError::Validation("bad".to_string()),
+            AtentoError::Execution("bad".to_string())
+        );
+        assert_ne!(
+            AtentoError::Timeout {
+                context: "step".to_string(),
+                timeout_secs: 1,
+            },
+            AtentoError::Timeout {
+                context: "step".to_string(),
+                timeout_secs: 2,
+            }
+        );
+    }
 }
@@ -1,15 +1,23 @@
-#[cfg(test)]
+#[cfg(all(test, feature = "exec"))]
 #[allow(clippy::unwrap_used)]
 mod tests {
-    use crate::executor::{CommandExecutor, ExecutionResult};
+    use crate::errors::AtentoError;
+    use crate::executor::{
+        CommandExecutor, ExecutionRequest, ExecutionResult, StreamingExecutor, SystemExecutor,
+        remote_script,
+    };
     use crate::interpreter::Interpreter;
+    use crate::remote::SshExecutor;
+    use crate::runner::{KillPolicy, OutputStream, TimeoutSignal};
     use crate::tests::mock_executor::MockExecutor;
+    use std::collections::HashMap;
 
     fn bash_interpreter() -> Interpreter {
         Interpreter {
             command: "bash".to_string(),
             args: vec![],
             extension: ".sh".to_string(),
+            fallbacks: vec![],
         }
     }
 
@@ -24,7 +32,15 @@ mod tests {
     fn test_mock_executor_default_response() {
         let executor = MockExecutor::new();
         let result = executor
-            .execute("echo 'test'", &bash_interpreter(), 30)
+            .execute(&ExecutionRequest {
+                timeout: 30,
+                ..ExecutionRequest::new(
+                    "echo 'test'",
+                    &bash_interpreter(),
+                    &HashMap::new(),
+                    &KillPolicy::default(),
+                )
+            })
             .unwrap();
 
         assert_eq!(result.stdout, "mock output");
@@ -41,14 +57,21 @@ mod tests {
             "echo 'hello'",
             ExecutionResult {
                 stdout: "hello".to_string(),
-                stderr: String::new(),
-                exit_code: 0,
                 duration_ms: 5,
+                ..ExecutionResult::default()
             },
         );
 
         let result = executor
-            .execute("echo 'hello'", &bash_interpreter(), 30)
+            .execute(&ExecutionRequest {
+                timeout: 30,
+                ..ExecutionRequest::new(
+                    "echo 'hello'",
+                    &bash_interpreter(),
+                    &HashMap::new(),
+                    &KillPolicy::default(),
+                )
+            })
             .unwrap();
 
         assert_eq!(result.stdout, "hello");
@@ -62,7 +85,15 @@ mod tests {
         executor.expect_timeout("slow_command");
 
         let result = executor
-            .execute("slow_command", &bash_interpreter(), 10)
+            .execute(&ExecutionRequest {
+                timeout: 10,
+                ..ExecutionRequest::new(
+                    "slow_command",
+                    &bash_interpreter(),
+                    &HashMap::new(),
+                    &KillPolicy::default(),
+                )
+            })
             .unwrap();
 
         assert_eq!(result.stdout, "");
@@ -77,7 +108,15 @@ mod tests {
         executor.expect_error("failing_command", 1, "Command not found");
 
         let result = executor
-            .execute("failing_command", &bash_interpreter(), 30)
+            .execute(&ExecutionRequest {
+                timeout: 30,
+                ..ExecutionRequest::new(
+                    "failing_command",
+                    &bash_interpreter(),
+                    &HashMap::new(),
+                    &KillPolicy::default(),
+                )
+            })
             .unwrap();
 
         assert_eq!(result.stdout, "");
@@ -91,7 +130,15 @@ mod tests {
         let executor = MockExecutor::new();
 
         executor
-            .execute("test_script", &bash_interpreter(), 60)
+            .execute(&ExecutionRequest {
+                timeout: 60,
+                ..ExecutionRequest::new(
+                    "test_script",
+                    &bash_interpreter(),
+                    &HashMap::new(),
+                    &KillPolicy::default(),
+                )
+            })
             .unwrap();
 
         let last_call = executor.last_call().unwrap();
@@ -106,13 +153,43 @@ mod tests {
         let executor = MockExecutor::new();
         assert_eq!(executor.call_count(), 0);
 
-        executor.execute("cmd1", &bash_interpreter(), 30).unwrap();
+        executor
+            .execute(&ExecutionRequest {
+                timeout: 30,
+                ..ExecutionRequest::new(
+                    "cmd1",
+                    &bash_interpreter(),
+                    &HashMap::new(),
+                    &KillPolicy::default(),
+                )
+            })
+            .unwrap();
         assert_eq!(executor.call_count(), 1);
 
-        executor.execute("cmd2", &bash_interpreter(), 30).unwrap();
+        executor
+            .execute(&ExecutionRequest {
+                timeout: 30,
+                ..ExecutionRequest::new(
+                    "cmd2",
+                    &bash_interpreter(),
+                    &HashMap::new(),
+                    &KillPolicy::default(),
+                )
+            })
+            .unwrap();
         assert_eq!(executor.call_count(), 2);
 
-        executor.execute("cmd3", &bash_interpreter(), 30).unwrap();
+        executor
+            .execute(&ExecutionRequest {
+                timeout: 30,
+                ..ExecutionRequest::new(
+                    "cmd3",
+                    &bash_interpreter(),
+                    &HashMap::new(),
+                    &KillPolicy::default(),
+                )
+            })
+            .unwrap();
         assert_eq!(executor.call_count(), 3);
     }
 
@@ -124,9 +201,8 @@ mod tests {
             "cmd1",
             ExecutionResult {
                 stdout: "output1".to_string(),
-                stderr: String::new(),
-                exit_code: 0,
                 duration_ms: 10,
+                ..ExecutionResult::default()
             },
         );
 
@@ -134,22 +210,51 @@ mod tests {
             "cmd2",
             ExecutionResult {
                 stdout: "output2".to_string(),
-                stderr: String::new(),
-                exit_code: 0,
                 duration_ms: 20,
+                ..ExecutionResult::default()
             },
         );
 
-        let result1 = executor.execute("cmd1", &bash_interpreter(), 30).unwrap();
+        let result1 = executor
+            .execute(&ExecutionRequest {
+                timeout: 30,
+                ..ExecutionRequest::new(
+                    "cmd1",
+                    &bash_interpreter(),
+                    &HashMap::new(),
+                    &KillPolicy::default(),
+                )
+            })
+            .unwrap();
         assert_eq!(result1.stdout, "output1");
         assert_eq!(result1.duration_ms, 10);
 
-        let result2 = executor.execute("cmd2", &bash_interpreter(), 30).unwrap();
+        let result2 = executor
+            .execute(&ExecutionRequest {
+                timeout: 30,
+                ..ExecutionRequest::new(
+                    "cmd2",
+                    &bash_interpreter(),
+                    &HashMap::new(),
+                    &KillPolicy::default(),
+                )
+            })
+            .unwrap();
         assert_eq!(result2.stdout, "output2");
         assert_eq!(result2.duration_ms, 20);
 
         // Unmapped command should return default
-        let result3 = executor.execute("cmd3", &bash_interpreter(), 30).unwrap();
+        let result3 = executor
+            .execute(&ExecutionRequest {
+                timeout: 30,
+                ..ExecutionRequest::new(
+                    "cmd3",
+                    &bash_interpreter(),
+                    &HashMap::new(),
+                    &KillPolicy::default(),
+                )
+            })
+            .unwrap();
         assert_eq!(result3.stdout, "mock output");
     }
 
@@ -162,21 +267,50 @@ mod tests {
                 "cmd1",
                 ExecutionResult {
                     stdout: "first".to_string(),
-                    stderr: String::new(),
-                    exit_code: 0,
                     duration_ms: 5,
+                    ..ExecutionResult::default()
                 },
             )
             .expect_timeout("cmd2")
             .expect_error("cmd3", 127, "not found");
 
-        let result1 = executor.execute("cmd1", &bash_interpreter(), 30).unwrap();
+        let result1 = executor
+            .execute(&ExecutionRequest {
+                timeout: 30,
+                ..ExecutionRequest::new(
+                    "cmd1",
+                    &bash_interpreter(),
+                    &HashMap::new(),
+                    &KillPolicy::default(),
+                )
+            })
+            .unwrap();
         assert_eq!(result1.stdout, "first");
 
-        let result2 = executor.execute("cmd2", &bash_interpreter(), 30).unwrap();
+        let result2 = executor
+            .execute(&ExecutionRequest {
+                timeout: 30,
+                ..ExecutionRequest::new(
+                    "cmd2",
+                    &bash_interpreter(),
+                    &HashMap::new(),
+                    &KillPolicy::default(),
+                )
+            })
+            .unwrap();
         assert_eq!(result2.exit_code, 124);
 
-        let result3 = executor.execute("cmd3", &bash_interpreter(), 30).unwrap();
+        let result3 = executor
+            .execute(&ExecutionRequest {
+                timeout: 30,
+                ..ExecutionRequest::new(
+                    "cmd3",
+                    &bash_interpreter(),
+                    &HashMap::new(),
+                    &KillPolicy::default(),
+                )
+            })
+            .unwrap();
         assert_eq!(result3.exit_code, 127);
         assert_eq!(result3.stderr, "not found");
     }
@@ -188,6 +322,7 @@ mod tests {
             stderr: "test error".to_string(),
             exit_code: 42,
             duration_ms: 100,
+            ..ExecutionResult::default()
         };
 
         let cloned = result.clone();
@@ -204,6 +339,7 @@ mod tests {
             stderr: "error".to_string(),
             exit_code: 1,
             duration_ms: 50,
+            ..ExecutionResult::default()
         };
 
         let debug_str = format!("{result:?}");
@@ -216,26 +352,361 @@ mod tests {
     fn test_execution_result_partial_eq() {
         let result1 = ExecutionResult {
             stdout: "test".to_string(),
-            stderr: String::new(),
-            exit_code: 0,
             duration_ms: 10,
+            ..ExecutionResult::default()
         };
 
         let result2 = ExecutionResult {
             stdout: "test".to_string(),
-            stderr: String::new(),
-            exit_code: 0,
             duration_ms: 10,
+            ..ExecutionResult::default()
         };
 
         let result3 = ExecutionResult {
             stdout: "different".to_string(),
-            stderr: String::new(),
-            exit_code: 0,
             duration_ms: 10,
+            ..ExecutionResult::default()
         };
 
         assert_eq!(result1, result2);
         assert_ne!(result1, result3);
     }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_streaming_executor_reports_lines() {
+        let mut lines = Vec::new();
+        let result = {
+            let executor = StreamingExecutor::new(|stream, line: &str| {
+                lines.push((stream, line.to_string()));
+            });
+            executor
+                .execute(&ExecutionRequest {
+                    timeout: 30,
+                    ..ExecutionRequest::new(
+                        "echo one\necho two >&2\necho three",
+                        &bash_interpreter(),
+                        &HashMap::new(),
+                        &KillPolicy::default(),
+                    )
+                })
+                .unwrap()
+        };
+
+        assert_eq!(lines.len(), 3);
+        assert!(lines.contains(&(OutputStream::Stdout, "one".to_string())));
+        assert!(lines.contains(&(OutputStream::Stderr, "two".to_string())));
+        assert!(lines.contains(&(OutputStream::Stdout, "three".to_string())));
+
+        let stdout_lines: Vec<_> = lines
+            .iter()
+            .filter(|(stream, _)| *stream == OutputStream::Stdout)
+            .map(|(_, line)| line.as_str())
+            .collect();
+        assert_eq!(stdout_lines, ["one", "three"]);
+
+        assert_eq!(result.exit_code, 0);
+        assert_eq!(result.stdout, "one\nthree");
+        assert_eq!(result.stderr, "two");
+    }
+
+    fn python_interpreter() -> Interpreter {
+        Interpreter {
+            command: "python3".to_string(),
+            args: vec!["-u".to_string()],
+            extension: ".py".to_string(),
+            fallbacks: vec![],
+        }
+    }
+
+    #[test]
+    fn test_ssh_executor_command_args() {
+        let executor = SshExecutor {
+            host: "build.example.com".to_string(),
+            user: Some("ci".to_string()),
+            key_path: Some("/home/ci/.ssh/id_ed25519".to_string()),
+            port: Some(2222),
+        };
+        assert_eq!(executor.destination(), "ci@build.example.com");
+
+        let cmd = executor.command(
+            &python_interpreter(),
+            &HashMap::new(),
+            None,
+            30,
+            &KillPolicy::default(),
+        );
+        assert_eq!(cmd.get_program(), "ssh");
+        let args: Vec<String> = cmd
+            .get_args()
+            .map(|arg| arg.to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(
+            args[..10],
+            [
+                "-o",
+                "BatchMode=yes",
+                "-o",
+                "ConnectTimeout=10",
+                "-p",
+                "2222",
+                "-i",
+                "/home/ci/.ssh/id_ed25519",
+                "--",
+                "ci@build.example.com",
+            ]
+        );
+        assert!(args[10].contains("timeout -k 5 30 'python3' '-u' \"$f\""));
+        assert!(args[10].contains("umask 077; d=$(mktemp -d "));
+        assert!(args[10].contains("f=\"$d\"/script'.py'"));
+    }
+
+    #[test]
+    fn test_ssh_remote_script_quotes_env_and_workdir() {
+        let env = HashMap::from([("GREETING".to_string(), "it's here".to_string())]);
+        let script = remote_script(
+            &bash_interpreter(),
+            &env,
+            Some("/srv/my app"),
+            0,
+            &KillPolicy::default(),
+        );
+        assert!(script.contains("cd '/srv/my app' && GREETING='it'\\''s here' 'bash' \"$f\""));
+        assert!(!script.contains("timeout"));
+    }
+
+    #[test]
+    fn test_ssh_remote_script_passes_timeout_signal_and_grace_period() {
+        let kill = KillPolicy {
+            signal: TimeoutSignal::Int,
+            grace_ms: 1500,
+            ..KillPolicy::default()
+        };
+        let script = remote_script(&bash_interpreter(), &HashMap::new(), None, 30, &kill);
+        assert!(script.contains("timeout -s INT -k 1.500 30 'bash' \"$f\""));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_ssh_remote_script_runs_in_posix_shell() {
+        // Run the remote side locally to check the generated shell command
+        let dir = tempfile::TempDir::new().unwrap();
+        let workdir = dir.path().to_str().unwrap();
+        let env = HashMap::from([("NAME".to_string(), "o'brien".to_string())]);
+        let remote = remote_script(
+            &bash_interpreter(),
+            &env,
+            Some(workdir),
+            5,
+            &KillPolicy::default(),
+        );
+
+        let mut cmd = std::process::Command::new("sh");
+        cmd.arg("-c").arg(remote);
+        let result = crate::runner::run_with_input(
+            &mut cmd,
+            "echo \"hi $NAME from $(pwd)\"\nexit 3\n",
+            None,
+            10,
+            &KillPolicy::default(),
+            &mut |_, _| {},
+        )
+        .unwrap();
+
+        assert_eq!(result.exit_code, 3);
+        assert_eq!(
+            result.stdout.as_deref(),
+            Some(format!("hi o'brien from {workdir}").as_str())
+        );
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_ssh_remote_script_keeps_the_script_private_and_removes_it() {
+        let remote = remote_script(
+            &bash_interpreter(),
+            &HashMap::new(),
+            None,
+            5,
+            &KillPolicy::default(),
+        );
+
+        let mut cmd = std::process::Command::new("sh");
+        cmd.arg("-c").arg(remote);
+        let result = crate::runner::run_with_input(
+            &mut cmd,
+            "stat -c '%a' \"$0\" \"$(dirname \"$0\")\"\necho \"$0\"\n",
+            None,
+            10,
+            &KillPolicy::default(),
+            &mut |_, _| {},
+        )
+        .unwrap();
+
+        assert_eq!(result.exit_code, 0);
+        let stdout = result.stdout.unwrap();
+        let lines: Vec<&str> = stdout.lines().collect();
+        assert_eq!(lines[..2], ["600", "700"]);
+        assert!(lines[2].ends_with("/script.sh"), "path: {}", lines[2]);
+        assert!(!std::path::Path::new(lines[2]).exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_ssh_remote_script_enforces_timeout() {
+        let remote = remote_script(
+            &bash_interpreter(),
+            &HashMap::new(),
+            None,
+            1,
+            &KillPolicy::default(),
+        );
+
+        let mut cmd = std::process::Command::new("sh");
+        cmd.arg("-c").arg(remote);
+        let result = crate::runner::run_with_input(
+            &mut cmd,
+            "sleep 30\n",
+            None,
+            10,
+            &KillPolicy::default(),
+            &mut |_, _| {},
+        )
+        .unwrap();
+
+        assert_eq!(result.exit_code, crate::runner::TIMEOUT_EXIT_CODE);
+        assert!(!result.timed_out, "the remote timeout should fire first");
+    }
+
+    #[test]
+    fn test_ssh_executor_connection_failure_is_runner_error() {
+        let executor = SshExecutor {
+            port: Some(1),
+            ..SshExecutor::new("127.0.0.1")
+        };
+
+        let result = executor.execute(&ExecutionRequest {
+            timeout: 5,
+            ..ExecutionRequest::new(
+                "echo hi",
+                &bash_interpreter(),
+                &HashMap::new(),
+                &KillPolicy::default(),
+            )
+        });
+        match result {
+            Err(AtentoError::Runner(msg)) => assert!(msg.contains("127.0.0.1"), "msg: {msg}"),
+            other => panic!("Expected Runner error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_ssh_executor_rejects_env_names_that_are_not_identifiers() {
+        let env = HashMap::from([("X=1; id; Y".to_string(), "value".to_string())]);
+        let result = SshExecutor::new("127.0.0.1").execute(&ExecutionRequest::new(
+            "echo hi",
+            &bash_interpreter(),
+            &env,
+            &KillPolicy::default(),
+        ));
+        match result {
+            Err(AtentoError::Runner(msg)) => {
+                assert!(msg.contains("Invalid env var name"), "msg: {msg}");
+            }
+            other => panic!("Expected Runner error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_ssh_executor_rejects_stdin() {
+        let result = SshExecutor::new("127.0.0.1").execute(&ExecutionRequest {
+            stdin: Some("data"),
+            timeout: 5,
+            ..ExecutionRequest::new(
+                "cat",
+                &bash_interpreter(),
+                &HashMap::new(),
+                &KillPolicy::default(),
+            )
+        });
+        match result {
+            Err(AtentoError::Runner(msg)) => assert!(msg.contains("stdin"), "msg: {msg}"),
+            other => panic!("Expected Runner error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_streaming_executor_passes_stdin() {
+        let mut lines = Vec::new();
+        let result = StreamingExecutor::new(|_, line: &str| lines.push(line.to_string()))
+            .execute(&ExecutionRequest {
+                stdin: Some("one\ntwo\n"),
+                timeout: 10,
+                ..ExecutionRequest::new(
+                    "cat",
+                    &bash_interpreter(),
+                    &HashMap::new(),
+                    &KillPolicy::default(),
+                )
+            })
+            .unwrap();
+
+        assert_eq!(result.stdout, "one\ntwo");
+        assert_eq!(lines, ["one", "two"]);
+    }
+
+    #[test]
+    fn test_mock_executor_records_stdin() {
+        let executor = MockExecutor::new();
+        executor
+            .execute(&ExecutionRequest {
+                stdin: Some("payload"),
+                timeout: 30,
+                ..ExecutionRequest::new(
+                    "cat",
+                    &bash_interpreter(),
+                    &HashMap::new(),
+                    &KillPolicy::default(),
+                )
+            })
+            .unwrap();
+        assert_eq!(executor.last_stdin().as_deref(), Some("payload"));
+    }
+
+    /// Returns true while `pid` is a live (non-zombie) process.
+    #[cfg(unix)]
+    fn process_alive(pid: &str) -> bool {
+        let output = std::process::Command::new("ps")
+            .args(["-o", "stat=", "-p", pid])
+            .output()
+            .unwrap();
+        let stat = String::from_utf8_lossy(&output.stdout);
+        output.status.success() && !stat.trim().is_empty() && !stat.trim().starts_with('Z')
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_system_executor_timeout_kills_process_group() {
+        let result = SystemExecutor
+            .execute(&ExecutionRequest {
+                timeout: 1,
+                ..ExecutionRequest::new(
+                    "bash -c 'sleep 30 & echo $!; wait'",
+                    &bash_interpreter(),
+                    &HashMap::new(),
+                    &KillPolicy::default(),
+                )
+            })
+            .unwrap();
+        assert!(result.timed_out);
+        let pid = result.stdout.trim().to_string();
+        assert!(!pid.is_empty());
+
+        let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+        while process_alive(&pid) && std::time::Instant::now() < deadline {
+            std::thread::sleep(std::time::Duration::from_millis(50));
+        }
+        assert!(!process_alive(&pid), "sleep {pid} survived the timeout");
+    }
 }
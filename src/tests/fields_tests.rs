@@ -0,0 +1,170 @@
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use crate::chain::Chain;
+    use crate::errors::AtentoError;
+    use crate::fields::edit_distance;
+
+    fn unknown_fields_error(yaml: &str) -> String {
+        match yaml.parse::<Chain>().unwrap_err() {
+            AtentoError::Validation(message) => message,
+            other => panic!("Expected Validation, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_misspelled_step_field_suggests_the_field() {
+        let message = unknown_fields_error(
+            r"
+steps:
+  build:
+    type: bash
+    script: echo version=1
+    outpts:
+      version:
+        pattern: version=(\d)
+",
+        );
+
+        assert_eq!(
+            message,
+            "Unknown fields in '<string>': step 'build' has unknown field 'outpts' \
+             (did you mean 'outputs'?); set 'lenient: true' to ignore them"
+        );
+    }
+
+    #[test]
+    fn test_every_unknown_field_is_listed() {
+        let message = unknown_fields_error(
+            r"
+nmae: release
+parameters:
+  target:
+    value: prod
+    requierd: true
+steps:
+  build:
+    type: bash
+    script: echo {{ inputs.target }}
+    inputs:
+      target:
+        rfe: parameters.target
+    outputs:
+      version:
+        patern: version=(\d)
+        colour: blue
+results:
+  version:
+    ref: steps.build.outputs.version
+    tpye: string
+",
+        );
+
+        for expected in [
+            "the chain has unknown field 'nmae' (did you mean 'name'?)",
+            "parameter 'target' has unknown field 'requierd' (did you mean 'required'?)",
+            "input 'target' of step 'build' has unknown field 'rfe' (did you mean 'ref'?)",
+            "output 'version' of step 'build' has unknown field 'colour', ",
+            "output 'version' of step 'build' has unknown field 'patern' (did you mean 'pattern'?)",
+            "result 'version' has unknown field 'tpye' (did you mean 'type'?)",
+        ] {
+            assert!(message.contains(expected), "{expected} not in {message}");
+        }
+    }
+
+    #[test]
+    fn test_templates_are_checked() {
+        let message = unknown_fields_error(
+            "templates:\n  base:\n    type: bash\n    retires: 2\nsteps:\n  s:\n    template: base\n    script: echo hi\n",
+        );
+
+        assert!(
+            message
+                .contains("template 'base' has unknown field 'retires' (did you mean 'retries'?)"),
+            "{message}"
+        );
+    }
+
+    #[test]
+    fn test_aliases_and_description_are_known() {
+        let chain: Chain = r"
+description: Builds the release
+steps:
+  build:
+    type: bash
+    script: echo version=1
+    success_codes: [0]
+    outputs:
+      version:
+        pattern: version=(\d)
+        from: stdout
+"
+        .parse()
+        .unwrap();
+
+        assert!(!chain.lenient);
+        assert!(chain.validate().is_ok());
+    }
+
+    #[test]
+    fn test_lenient_chain_ignores_unknown_fields() {
+        let chain: Chain =
+            "lenient: true\nsteps:\n  s:\n    type: bash\n    script: echo hi\n    cache: true\n"
+                .parse()
+                .unwrap();
+
+        assert!(chain.lenient);
+        assert!(chain.steps.contains_key("s"));
+    }
+
+    #[test]
+    fn test_loaded_chain_names_the_unknown_field_location() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("chain.yaml");
+        std::fs::write(
+            &path,
+            "steps:\n  s:\n    type: bash\n    script: echo hi\n    timout: 5\n",
+        )
+        .unwrap();
+
+        let err = Chain::load_with_includes(&path).unwrap_err();
+        let message = err.to_string();
+        assert!(
+            message.contains(
+                "step 's' has unknown field 'timout' (did you mean 'timeout'?) at line 5 column 5"
+            ),
+            "{message}"
+        );
+        assert!(message.contains(&path.display().to_string()), "{message}");
+    }
+
+    #[test]
+    fn test_from_reader_rejects_unknown_fields() {
+        let yaml = "steps:\n  s:\n    type: bash\n    script: echo hi\n    scrpit: echo\n";
+        let err = Chain::from_reader(yaml.as_bytes()).unwrap_err();
+
+        assert!(err.to_string().contains("'<reader>'"), "{err}");
+        assert!(err.to_string().contains("did you mean 'script'?"), "{err}");
+    }
+
+    #[test]
+    fn test_distant_keys_get_no_suggestion() {
+        let message = unknown_fields_error(
+            "steps:\n  s:\n    type: bash\n    script: echo hi\n    banana: 1\n",
+        );
+
+        assert!(
+            message.contains("step 's' has unknown field 'banana'; "),
+            "{message}"
+        );
+    }
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("outputs", "outputs"), 0);
+        assert_eq!(edit_distance("outpts", "outputs"), 1);
+        assert_eq!(edit_distance("tpye", "type"), 2);
+        assert_eq!(edit_distance("", "ref"), 3);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+    }
+}
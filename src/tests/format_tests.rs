@@ -0,0 +1,189 @@
+#[cfg(all(test, feature = "exec"))]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use crate::chain::{Chain, ChainResult};
+    use crate::executor::ExecutionResult;
+    use crate::format::{OutputFormat, format_result};
+    use crate::tests::mock_executor::MockExecutor;
+
+    fn ok(stdout: &str) -> ExecutionResult {
+        ExecutionResult {
+            stdout: stdout.to_string(),
+            duration_ms: 1,
+            ..ExecutionResult::default()
+        }
+    }
+
+    fn run_release_chain(test_exit_code: i32) -> ChainResult {
+        let chain: Chain = r"
+name: release
+steps:
+  build:
+    type: bash
+    script: make
+    outputs:
+      version:
+        pattern: 'version=(\S+)'
+  test:
+    type: bash
+    script: make test
+results:
+  version:
+    ref: steps.build.outputs.version
+"
+        .parse()
+        .unwrap();
+
+        let mut mock = MockExecutor::new();
+        mock.expect_call("make", ok("version=1.2.0"));
+        if test_exit_code == 0 {
+            mock.expect_call("make test", ok("passed"));
+        } else {
+            mock.expect_error("make test", test_exit_code, "2 failures");
+        }
+        chain.run_with_executor(&mock)
+    }
+
+    #[test]
+    fn test_text_format_summarizes_result() {
+        let mut result = run_release_chain(0);
+        result.duration_ms = 12;
+        for step in result.steps.as_mut().unwrap().values_mut() {
+            step.duration_ms = 3;
+        }
+
+        let text = format_result(&result, OutputFormat::Text).unwrap();
+        assert_eq!(
+            text,
+            "Chain: release\n\
+             Status: ok (12 ms)\n\
+             Steps:\n  \
+             build: exit 0 (3 ms)\n  \
+             test: exit 0 (3 ms)\n\
+             Results:\n  \
+             version: 1.2.0"
+        );
+    }
+
+    #[test]
+    fn test_text_format_shows_step_errors_once() {
+        let result = run_release_chain(2);
+        let text = format_result(&result, OutputFormat::Text).unwrap();
+
+        assert!(text.contains("Status: nok"));
+        assert!(text.contains("test: exit 2"));
+        assert!(text.contains(" - "));
+        // The failing step's error is not repeated in a separate section
+        assert!(!text.contains("Errors:"), "text: {text}");
+    }
+
+    #[test]
+    fn test_text_format_lists_chain_errors() {
+        let result = ChainResult {
+            name: None,
+            duration_ms: 0,
+            parameters: None,
+            steps: None,
+            results: None,
+            errors: vec![crate::AtentoError::Validation("bad parameter".to_string())],
+            status: "nok".to_string(),
+            started_at: None,
+            finished_at: None,
+            run_id: None,
+            metadata: indexmap::IndexMap::new(),
+        };
+
+        let text = format_result(&result, OutputFormat::Text).unwrap();
+        assert_eq!(
+            text,
+            "Status: nok (0 ms)\nErrors:\n  - Chain validation failed: bad parameter"
+        );
+    }
+
+    #[test]
+    fn test_yaml_and_json_formats_round_trip() {
+        let result = run_release_chain(0);
+
+        let yaml = format_result(&result, OutputFormat::Yaml).unwrap();
+        let from_yaml: ChainResult = serde_yaml::from_str(&yaml).unwrap();
+        assert_eq!(from_yaml, result);
+
+        let json = format_result(&result, OutputFormat::Json).unwrap();
+        assert_eq!(json, serde_json::to_string_pretty(&result).unwrap());
+        assert_eq!(OutputFormat::default(), OutputFormat::Json);
+    }
+
+    #[test]
+    fn test_junit_xml_reports_every_step_and_counts_failures() {
+        let result = run_release_chain(2);
+
+        let xml = result.to_junit_xml();
+        let document = roxmltree::Document::parse(&xml).unwrap();
+        let suite = document
+            .descendants()
+            .find(|node| node.has_tag_name("testsuite"))
+            .unwrap();
+        assert_eq!(suite.attribute("name"), Some("release"));
+        assert_eq!(suite.attribute("tests"), Some("2"));
+        assert_eq!(
+            suite.attribute("failures"),
+            Some(result.errors.len().to_string().as_str())
+        );
+
+        let cases: Vec<_> = suite
+            .children()
+            .filter(|node| node.has_tag_name("testcase"))
+            .collect();
+        let names: Vec<_> = cases.iter().map(|case| case.attribute("name")).collect();
+        assert_eq!(names, vec![Some("build"), Some("test")]);
+        let failures: Vec<_> = document
+            .descendants()
+            .filter(|node| node.has_tag_name("failure"))
+            .collect();
+        assert_eq!(failures.len(), result.errors.len());
+        assert_eq!(failures[0].parent().unwrap(), cases[1]);
+        let message = failures[0].attribute("message").unwrap();
+        assert!(message.contains("exited with code 2"), "{message}");
+    }
+
+    #[test]
+    fn test_junit_xml_escapes_output_and_reports_chain_errors() {
+        let mut result = run_release_chain(0);
+        result.name = Some("deploy \"prod\" & <eu>".to_string());
+        result.duration_ms = 1500;
+        let build = &mut result.steps.as_mut().unwrap()["build"];
+        build.duration_ms = 42;
+        build.stdout = Some("\u{1b}[32m<ok> & done\u{1b}[0m".to_string());
+        result.errors.push(crate::AtentoError::Timeout {
+            context: "chain".to_string(),
+            timeout_secs: 1,
+        });
+
+        let xml = result.to_junit_xml();
+        let document = roxmltree::Document::parse(&xml).unwrap();
+        let suite = document
+            .descendants()
+            .find(|node| node.has_tag_name("testsuite"))
+            .unwrap();
+        assert_eq!(suite.attribute("name"), Some("deploy \"prod\" & <eu>"));
+        assert_eq!(suite.attribute("time"), Some("1.500"));
+        assert_eq!(suite.attribute("tests"), Some("3"));
+        assert_eq!(suite.attribute("failures"), Some("1"));
+
+        let build = suite.first_element_child().unwrap();
+        assert_eq!(build.attribute("time"), Some("0.042"));
+        let stdout = build
+            .children()
+            .find(|node| node.has_tag_name("system-out"))
+            .unwrap();
+        assert_eq!(stdout.text(), Some("[32m<ok> & done[0m"));
+
+        let chain_case = suite.last_element_child().unwrap();
+        assert_eq!(chain_case.attribute("name"), Some("deploy \"prod\" & <eu>"));
+        assert!(
+            chain_case
+                .first_element_child()
+                .is_some_and(|failure| failure.has_tag_name("failure"))
+        );
+    }
+}
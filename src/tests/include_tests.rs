@@ -0,0 +1,228 @@
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use crate::chain::Chain;
+    use crate::errors::AtentoError;
+    use std::path::Path;
+
+    fn write(dir: &Path, name: &str, yaml: &str) {
+        let path = dir.join(name);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, yaml).unwrap();
+    }
+
+    const SETUP: &str = r"
+parameters:
+  env:
+    value: staging
+  region:
+    value: eu
+steps:
+  checkout:
+    type: bash
+    script: echo checkout
+  configure:
+    type: bash
+    script: echo configure {{ inputs.env }}
+    inputs:
+      env:
+        ref: parameters.env
+";
+
+    #[test]
+    fn test_includes_merge_in_order() {
+        let dir = tempfile::tempdir().unwrap();
+        write(dir.path(), "common/setup.yaml", SETUP);
+        write(
+            dir.path(),
+            "common/teardown.yaml",
+            r"
+steps:
+  cleanup:
+    type: bash
+    script: echo cleanup
+results:
+  cleaned:
+    value: 'yes'
+",
+        );
+        write(
+            dir.path(),
+            "deploy.yaml",
+            r"
+name: deploy
+timeout: 120
+include:
+  - common/setup.yaml
+  - common/teardown.yaml
+parameters:
+  env:
+    value: production
+steps:
+  deploy:
+    type: bash
+    script: echo deploy {{ inputs.env }}
+    inputs:
+      env:
+        ref: parameters.env
+",
+        );
+
+        let chain = Chain::load_with_includes(dir.path().join("deploy.yaml")).unwrap();
+        chain.validate().unwrap();
+
+        assert_eq!(chain.name.as_deref(), Some("deploy"));
+        assert_eq!(chain.timeout, 120);
+        assert!(chain.include.is_empty());
+        let ids: Vec<&str> = chain.steps.keys().map(String::as_str).collect();
+        assert_eq!(ids, vec!["checkout", "configure", "cleanup", "deploy"]);
+        assert_eq!(
+            chain.parameters["env"].value.as_ref().unwrap().as_str(),
+            Some("production")
+        );
+        assert!(chain.parameters.contains_key("region"));
+        assert!(chain.results.contains_key("cleaned"));
+    }
+
+    #[test]
+    fn test_duplicate_step_needs_override() {
+        let dir = tempfile::tempdir().unwrap();
+        write(dir.path(), "setup.yaml", SETUP);
+        let main = |marker: &str| {
+            format!(
+                "include:\n  - setup.yaml\nsteps:\n  checkout:\n    type: bash\n    script: echo shallow checkout\n{marker}"
+            )
+        };
+
+        write(dir.path(), "main.yaml", &main(""));
+        let err = Chain::load_with_includes(dir.path().join("main.yaml")).unwrap_err();
+        assert!(matches!(err, AtentoError::Validation(_)));
+        assert!(err.to_string().contains("Step 'checkout'"));
+        assert!(err.to_string().contains("override: true"));
+
+        write(dir.path(), "main.yaml", &main("    override: true\n"));
+        let chain = Chain::load_with_includes(dir.path().join("main.yaml")).unwrap();
+        let ids: Vec<&str> = chain.steps.keys().map(String::as_str).collect();
+        assert_eq!(ids, vec!["checkout", "configure"]);
+        assert_eq!(chain.steps["checkout"].script, "echo shallow checkout");
+    }
+
+    #[test]
+    fn test_include_cycle_is_reported() {
+        let dir = tempfile::tempdir().unwrap();
+        write(
+            dir.path(),
+            "a.yaml",
+            "include: [b.yaml]\nsteps:\n  a:\n    type: bash\n    script: echo a\n",
+        );
+        write(
+            dir.path(),
+            "b.yaml",
+            "include: [nested/c.yaml]\nsteps:\n  b:\n    type: bash\n    script: echo b\n",
+        );
+        write(
+            dir.path(),
+            "nested/c.yaml",
+            "include: [../a.yaml]\nsteps:\n  c:\n    type: bash\n    script: echo c\n",
+        );
+
+        let err = Chain::load_with_includes(dir.path().join("a.yaml")).unwrap_err();
+        let AtentoError::Validation(message) = err else {
+            panic!("expected a validation error, got {err:?}");
+        };
+        assert!(message.starts_with("Include cycle: "), "{message}");
+        let files: Vec<&str> = message["Include cycle: ".len()..].split(" -> ").collect();
+        assert_eq!(files.len(), 4);
+        assert!(files[0].ends_with("a.yaml"));
+        assert!(files[1].ends_with("b.yaml"));
+        assert!(files[2].ends_with("c.yaml"));
+        assert_eq!(files[0], files[3]);
+    }
+
+    #[test]
+    fn test_shared_include_is_merged_once() {
+        let dir = tempfile::tempdir().unwrap();
+        write(dir.path(), "setup.yaml", SETUP);
+        write(
+            dir.path(),
+            "build.yaml",
+            "include: [setup.yaml]\nsteps:\n  build:\n    type: bash\n    script: echo build\n",
+        );
+        write(
+            dir.path(),
+            "main.yaml",
+            "include: [setup.yaml, build.yaml]\nsteps:\n  ship:\n    type: bash\n    script: echo ship\n",
+        );
+
+        let chain = Chain::load_with_includes(dir.path().join("main.yaml")).unwrap();
+        let ids: Vec<&str> = chain.steps.keys().map(String::as_str).collect();
+        assert_eq!(ids, vec!["checkout", "configure", "build", "ship"]);
+    }
+
+    #[test]
+    fn test_included_interpreter_survives_defaults() {
+        let dir = tempfile::tempdir().unwrap();
+        write(
+            dir.path(),
+            "shell.yaml",
+            "interpreters:\n  bash:\n    command: /usr/local/bin/bash\n    extension: .sh\n",
+        );
+        write(
+            dir.path(),
+            "main.yaml",
+            "include: [shell.yaml]\nsteps:\n  s:\n    type: bash\n    script: echo hi\n",
+        );
+
+        let chain = Chain::load_with_includes(dir.path().join("main.yaml")).unwrap();
+        assert_eq!(chain.interpreters["bash"].command, "/usr/local/bin/bash");
+        assert!(chain.interpreters.contains_key("python"));
+    }
+
+    #[test]
+    fn test_missing_include_and_unloaded_include() {
+        let dir = tempfile::tempdir().unwrap();
+        let yaml = "include: [missing.yaml]\nsteps:\n  s:\n    type: bash\n    script: echo hi\n";
+        write(dir.path(), "main.yaml", yaml);
+
+        let err = Chain::load_with_includes(dir.path().join("main.yaml")).unwrap_err();
+        assert!(
+            matches!(&err, AtentoError::Io { path, .. } if path.ends_with("missing.yaml")),
+            "{err:?}"
+        );
+
+        let chain: Chain = yaml.parse().unwrap();
+        let err = chain.validate().unwrap_err();
+        assert!(err.to_string().contains("load_with_includes"), "{err}");
+    }
+
+    #[test]
+    fn test_steps_use_templates_from_included_files() {
+        let dir = tempfile::tempdir().unwrap();
+        write(
+            dir.path(),
+            "templates.yaml",
+            "templates:\n  say:\n    type: bash\n    script: echo {{ inputs.word }}\n    inputs:\n      word:\n        type: string\n",
+        );
+        write(
+            dir.path(),
+            "main.yaml",
+            "include: [templates.yaml]\nsteps:\n  hi:\n    template: say\n    inputs:\n      word:\n        value: hi\n",
+        );
+
+        let chain = Chain::load_with_includes(dir.path().join("main.yaml")).unwrap();
+        chain.validate().unwrap();
+        assert_eq!(chain.steps["hi"].script, "echo {{ inputs.word }}");
+        assert!(chain.templates.contains_key("say"));
+    }
+
+    #[cfg(not(feature = "toml"))]
+    #[test]
+    fn test_toml_chain_files_need_the_toml_feature() {
+        let dir = tempfile::tempdir().unwrap();
+        write(dir.path(), "chain.toml", "name = \"release\"\n");
+
+        let err = Chain::load_with_includes(dir.path().join("chain.toml")).unwrap_err();
+        assert!(matches!(err, AtentoError::Validation(_)), "{err}");
+        assert!(err.to_string().contains("'toml' feature"), "{err}");
+    }
+}
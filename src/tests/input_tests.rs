@@ -26,6 +26,7 @@ mod tests {
         let input = Input::Inline {
             type_: DataType::String,
             value: Value::String("hello".to_string()),
+            separator: None,
         };
         let result = input.to_string_value();
         assert_eq!(result.unwrap(), "hello");
@@ -36,6 +37,7 @@ mod tests {
         let input = Input::Inline {
             type_: DataType::Int,
             value: Value::Number(42.into()),
+            separator: None,
         };
         let result = input.to_string_value();
         assert_eq!(result.unwrap(), "42");
@@ -46,6 +48,7 @@ mod tests {
         let input = Input::Inline {
             type_: DataType::Float,
             value: Value::Number(serde_yaml::Number::from(3.14)),
+            separator: None,
         };
         let result = input.to_string_value();
         assert_eq!(result.unwrap(), "3.14");
@@ -56,6 +59,7 @@ mod tests {
         let input = Input::Inline {
             type_: DataType::Bool,
             value: Value::Bool(true),
+            separator: None,
         };
         let result = input.to_string_value();
         assert_eq!(result.unwrap(), "true");
@@ -66,6 +70,7 @@ mod tests {
         let input = Input::Inline {
             type_: DataType::DateTime,
             value: Value::String("2024-01-15T10:30:00Z".to_string()),
+            separator: None,
         };
         let result = input.to_string_value();
         assert_eq!(result.unwrap(), "2024-01-15T10:30:00Z");
@@ -76,6 +81,7 @@ mod tests {
         let input = Input::Inline {
             type_: DataType::Int,
             value: Value::String("not a number".to_string()),
+            separator: None,
         };
         let result = input.to_string_value();
         assert!(result.is_err());
@@ -124,7 +130,7 @@ type: string
 value: hello
 ";
         let input: Input = serde_yaml::from_str(yaml).unwrap();
-        if let Input::Inline { type_, value } = input {
+        if let Input::Inline { type_, value, .. } = input {
             assert_eq!(type_, DataType::String);
             assert_eq!(value.as_str().unwrap(), "hello");
         } else {
@@ -160,6 +166,7 @@ value: hello
         let input = Input::Inline {
             type_: DataType::Int,
             value: Value::Number(42.into()),
+            separator: None,
         };
         let yaml = serde_yaml::to_string(&input).unwrap();
         assert!(yaml.contains("type"));
@@ -184,6 +191,7 @@ value: hello
         let input = Input::Inline {
             type_: DataType::String,
             value: Value::String(String::new()),
+            separator: None,
         };
         let result = input.to_string_value();
         assert_eq!(result.unwrap(), "");
@@ -194,13 +202,64 @@ value: hello
         let input_int = Input::Inline {
             type_: DataType::Int,
             value: Value::Number(0.into()),
+            separator: None,
         };
         assert_eq!(input_int.to_string_value().unwrap(), "0");
 
         let input_float = Input::Inline {
             type_: DataType::Float,
             value: Value::Number(serde_yaml::Number::from(0.0)),
+            separator: None,
         };
         assert_eq!(input_float.to_string_value().unwrap(), "0");
     }
+
+    #[test]
+    fn test_input_inline_list() {
+        let input: Input =
+            serde_yaml::from_str("type: list\nvalue: [x, y]\nseparator: \"|\"").unwrap();
+        assert_eq!(input.to_string_value().unwrap(), "x|y");
+
+        let input = Input::Inline {
+            type_: DataType::List,
+            value: serde_yaml::from_str("[1, 2]").unwrap(),
+            separator: None,
+        };
+        assert_eq!(input.to_string_value().unwrap(), "1 2");
+    }
+
+    #[test]
+    fn test_input_env_deserialization() {
+        let input: Input = serde_yaml::from_str("env: API_TOKEN").unwrap();
+        assert!(matches!(&input, Input::Env { env, default: None } if env == "API_TOKEN"));
+
+        let input: Input = serde_yaml::from_str("env: REGION\ndefault: eu-west-1").unwrap();
+        assert!(matches!(
+            &input,
+            Input::Env { env, default: Some(default) } if env == "REGION" && default == "eu-west-1"
+        ));
+        assert!(input.to_string_value().is_err());
+    }
+
+    #[test]
+    fn test_input_env_value() {
+        use crate::tests::env_guard::EnvGuard;
+
+        let input = Input::Env {
+            env: "ATENTO_TEST_INPUT_ENV_VALUE".to_string(),
+            default: Some("fallback".to_string()),
+        };
+        {
+            let _guard = EnvGuard::set("ATENTO_TEST_INPUT_ENV_VALUE", "from env");
+            assert_eq!(input.env_value().unwrap().as_deref(), Some("from env"));
+        }
+        let _guard = EnvGuard::unset("ATENTO_TEST_INPUT_ENV_VALUE");
+        assert_eq!(input.env_value().unwrap().as_deref(), Some("fallback"));
+
+        let required = Input::Env {
+            env: "ATENTO_TEST_INPUT_ENV_VALUE".to_string(),
+            default: None,
+        };
+        assert_eq!(required.env_value().unwrap(), None);
+    }
 }
@@ -8,6 +8,7 @@ mod tests {
             command: "bash".to_string(),
             args: vec![],
             extension: ".sh".to_string(),
+            fallbacks: vec![],
         };
         assert_eq!(interp.extension(), ".sh");
     }
@@ -18,6 +19,7 @@ mod tests {
             command: "bash".to_string(),
             args: vec![],
             extension: ".sh".to_string(),
+            fallbacks: vec![],
         };
         assert!(interp.is_valid());
     }
@@ -28,6 +30,7 @@ mod tests {
             command: String::new(),
             args: vec![],
             extension: ".sh".to_string(),
+            fallbacks: vec![],
         };
         assert!(!interp.is_valid());
     }
@@ -38,6 +41,7 @@ mod tests {
             command: "bash".to_string(),
             args: vec![],
             extension: String::new(),
+            fallbacks: vec![],
         };
         assert!(!interp.is_valid());
     }
@@ -45,16 +49,30 @@ mod tests {
     #[test]
     fn test_default_interpreters_returns_vec() {
         let interpreters = default_interpreters();
-        assert_eq!(interpreters.len(), 6);
+        assert_eq!(interpreters.len(), 9);
 
         // Verify keys
         let keys: Vec<&String> = interpreters.iter().map(|(k, _)| k).collect();
         assert!(keys.contains(&&"bash".to_string()));
+        assert!(keys.contains(&&"sh".to_string()));
+        assert!(keys.contains(&&"zsh".to_string()));
         assert!(keys.contains(&&"batch".to_string()));
         assert!(keys.contains(&&"powershell".to_string()));
         assert!(keys.contains(&&"pwsh".to_string()));
         assert!(keys.contains(&&"python".to_string()));
         assert!(keys.contains(&&"python3".to_string()));
+        assert!(keys.contains(&&"node".to_string()));
+    }
+
+    #[test]
+    fn test_default_interpreters_node_config() {
+        let interpreters = default_interpreters();
+        let Some((_, node)) = interpreters.iter().find(|(k, _)| k == "node") else {
+            panic!("node interpreter missing from defaults");
+        };
+        assert_eq!(node.command, "node");
+        assert_eq!(node.extension, ".js");
+        assert!(node.args.is_empty());
     }
 
     #[test]
@@ -73,6 +91,19 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_default_interpreters_posix_shell_configs() {
+        let interpreters = default_interpreters();
+        for key in ["sh", "zsh"] {
+            let Some((_, shell)) = interpreters.iter().find(|(k, _)| k == key) else {
+                panic!("{key} interpreter missing from defaults");
+            };
+            assert_eq!(shell.command, key);
+            assert_eq!(shell.extension, ".sh");
+            assert!(shell.args.is_empty());
+        }
+    }
+
     #[test]
     fn test_default_interpreters_batch_config() {
         let interpreters = default_interpreters();
@@ -96,4 +127,132 @@ mod tests {
             assert!(interp.is_valid(), "Interpreter '{key}' should be valid");
         }
     }
+
+    #[cfg(all(unix, feature = "exec"))]
+    #[test]
+    fn test_is_available_for_installed_interpreter() {
+        let sh = Interpreter {
+            command: "sh".to_string(),
+            args: vec![],
+            extension: ".sh".to_string(),
+            fallbacks: vec![],
+        };
+        assert!(sh.is_available());
+    }
+
+    #[cfg(feature = "exec")]
+    #[test]
+    fn test_is_available_for_missing_command() {
+        let missing = Interpreter {
+            command: "atento-no-such-interpreter".to_string(),
+            args: vec![],
+            extension: ".sh".to_string(),
+            fallbacks: vec![],
+        };
+        assert!(!missing.is_available());
+    }
+
+    #[cfg(all(unix, feature = "exec"))]
+    #[test]
+    fn test_is_available_requires_zero_exit_code() {
+        let failing = Interpreter {
+            command: "false".to_string(),
+            args: vec![],
+            extension: ".sh".to_string(),
+            fallbacks: vec![],
+        };
+        assert!(!failing.is_available());
+    }
+
+    #[test]
+    fn test_candidates_split_fallback_arguments() {
+        let interp = Interpreter {
+            command: "/opt/my python/bin/python3".to_string(),
+            args: vec!["-u".to_string()],
+            extension: ".py".to_string(),
+            fallbacks: vec!["python".to_string(), "py -3".to_string()],
+        };
+        assert_eq!(
+            interp.candidates(),
+            vec![
+                ("/opt/my python/bin/python3", vec![]),
+                ("python", vec![]),
+                ("py", vec!["-3"]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_default_interpreters_fallbacks() {
+        let fallbacks = |key: &str| {
+            default_interpreters()
+                .into_iter()
+                .find(|(k, _)| k == key)
+                .map(|(_, v)| v.fallbacks)
+        };
+        assert_eq!(
+            fallbacks("python3"),
+            Some(vec!["python".to_string(), "py -3".to_string()])
+        );
+        assert_eq!(fallbacks("pwsh"), Some(vec!["powershell".to_string()]));
+        assert_eq!(fallbacks("powershell"), Some(vec!["pwsh".to_string()]));
+        assert_eq!(fallbacks("bash"), Some(vec![]));
+    }
+
+    #[test]
+    fn test_fallbacks_are_optional_in_yaml() {
+        let interp: Interpreter = serde_yaml::from_str("command: ruby\nextension: .rb\n")
+            .unwrap_or_else(|e| panic!("{e}"));
+        assert!(interp.fallbacks.is_empty());
+
+        let interp: Interpreter = serde_yaml::from_str(
+            "command: python3.12\nextension: .py\nfallbacks: [python3, py -3]\n",
+        )
+        .unwrap_or_else(|e| panic!("{e}"));
+        assert_eq!(interp.fallbacks, vec!["python3", "py -3"]);
+    }
+
+    #[test]
+    fn test_with_step_args_appends_or_overrides() {
+        use crate::interpreter::InterpreterArgs;
+
+        let bash = Interpreter {
+            command: "bash".to_string(),
+            args: vec!["--noprofile".to_string()],
+            extension: ".sh".to_string(),
+            fallbacks: vec![],
+        };
+        let appended = bash.with_step_args(&InterpreterArgs {
+            args: vec!["-e".to_string()],
+            override_: false,
+        });
+        assert_eq!(appended.args, vec!["--noprofile", "-e"]);
+        assert_eq!(appended.command, "bash");
+
+        let replaced = bash.with_step_args(&InterpreterArgs {
+            args: vec!["-x".to_string()],
+            override_: true,
+        });
+        assert_eq!(replaced.args, vec!["-x"]);
+        assert_eq!(bash.args, vec!["--noprofile"]);
+    }
+
+    #[test]
+    fn test_interpreter_args_yaml_forms() {
+        use crate::interpreter::InterpreterArgs;
+
+        let list: InterpreterArgs =
+            serde_yaml::from_str("[-u, -B]").unwrap_or_else(|e| panic!("{e}"));
+        assert_eq!(list.args, vec!["-u", "-B"]);
+        assert!(!list.override_);
+
+        let map: InterpreterArgs =
+            serde_yaml::from_str("args: [-e]\noverride: true\n").unwrap_or_else(|e| panic!("{e}"));
+        assert_eq!(map.args, vec!["-e"]);
+        assert!(map.override_);
+
+        let map: InterpreterArgs =
+            serde_yaml::from_str("args: [-e]\n").unwrap_or_else(|e| panic!("{e}"));
+        assert!(!map.override_);
+    }
 }
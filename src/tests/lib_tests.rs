@@ -1,4 +1,4 @@
-#[cfg(test)]
+#[cfg(all(test, feature = "exec"))]
 #[allow(
     clippy::unwrap_used,
     clippy::items_after_statements,
@@ -49,9 +49,8 @@ steps:
             "echo 'name: Alice'\necho 'age: 25'\n",
             ExecutionResult {
                 stdout: "name: Alice\\nage: 25\\n".to_string(),
-                stderr: String::new(),
-                exit_code: 0,
                 duration_ms: 50,
+                ..ExecutionResult::default()
             },
         );
 
@@ -93,9 +92,8 @@ steps:
             "echo ok\n",
             ExecutionResult {
                 stdout: "ok\n".to_string(),
-                stderr: String::new(),
-                exit_code: 0,
                 duration_ms: 50,
+                ..ExecutionResult::default()
             },
         );
 
@@ -119,9 +117,8 @@ steps:
             "echo hello\n",
             ExecutionResult {
                 stdout: "hello\n".to_string(),
-                stderr: String::new(),
-                exit_code: 0,
                 duration_ms: 50,
+                ..ExecutionResult::default()
             },
         );
 
@@ -152,9 +149,8 @@ steps:
             "echo hello\n",
             ExecutionResult {
                 stdout: "hello\n".to_string(),
-                stderr: String::new(),
-                exit_code: 0,
                 duration_ms: 50,
+                ..ExecutionResult::default()
             },
         );
 
@@ -183,9 +179,8 @@ steps:
             "echo 'result: 42'\n",
             ExecutionResult {
                 stdout: "result: 42\n".to_string(),
-                stderr: String::new(),
-                exit_code: 0,
                 duration_ms: 50,
+                ..ExecutionResult::default()
             },
         );
 
@@ -217,9 +212,8 @@ results:
             "echo 'status: success'\n",
             ExecutionResult {
                 stdout: "status: success\n".to_string(),
-                stderr: String::new(),
-                exit_code: 0,
                 duration_ms: 50,
+                ..ExecutionResult::default()
             },
         );
 
@@ -257,9 +251,8 @@ steps:
             "echo 'value: 100'\n",
             ExecutionResult {
                 stdout: "value: 100\n".to_string(),
-                stderr: String::new(),
-                exit_code: 0,
                 duration_ms: 50,
+                ..ExecutionResult::default()
             },
         );
 
@@ -268,9 +261,8 @@ steps:
             "echo 100\n",
             ExecutionResult {
                 stdout: "100\n".to_string(),
-                stderr: String::new(),
-                exit_code: 0,
                 duration_ms: 30,
+                ..ExecutionResult::default()
             },
         );
 
@@ -300,9 +292,8 @@ steps:
             "echo 'no match'\n",
             ExecutionResult {
                 stdout: "no match\n".to_string(),
-                stderr: String::new(),
-                exit_code: 0,
                 duration_ms: 50,
+                ..ExecutionResult::default()
             },
         );
 
@@ -334,9 +325,9 @@ steps:
         let chain: Chain = serde_yaml::from_str(yaml).unwrap();
         let result = chain.run_with_executor(&mock);
 
-        // The mock returns success, chain returns result directly
-        // For this test, let's just verify it doesn't crash
-        assert_eq!(result.status, "ok");
+        // The mock reports exit code 124, which fails the step
+        assert_eq!(result.status, "nok");
+        assert_eq!(result.steps.unwrap()["step1"].exit_code, 124);
     }
 
     #[test]
@@ -362,9 +353,8 @@ steps:
             "# Generate a very long string for testing JSON serialization limits\nfor i in {1..1000}; do echo -n \"A\"; done\necho \"\"\n",
             ExecutionResult {
                 stdout: long_output,
-                stderr: String::new(),
-                exit_code: 0,
                 duration_ms: 100,
+                ..ExecutionResult::default()
             },
         );
 
@@ -394,9 +384,8 @@ steps:
             "echo 'result: こんにちは世界 🌍'\n",
             ExecutionResult {
                 stdout: "result: こんにちは世界 🌍\n".to_string(),
-                stderr: String::new(),
-                exit_code: 0,
                 duration_ms: 50,
+                ..ExecutionResult::default()
             },
         );
 
@@ -479,4 +468,228 @@ steps:
         // check that it doesn't panic and returns a proper result
         assert!(result.is_ok() || result.is_err());
     }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_run_from_str_returns_result() {
+        let yaml = r"
+name: from_str_chain
+steps:
+  greet:
+    type: bash
+    script: echo 'hello'
+    outputs:
+      word:
+        pattern: '(\w+)'
+results:
+  word:
+    ref: steps.greet.outputs.word
+";
+
+        let result = crate::run_from_str(yaml).unwrap();
+        assert_eq!(result.status, "ok");
+        assert_eq!(result.results.as_ref().unwrap()["word"], "hello");
+
+        let json = serde_json::to_string(&result).unwrap();
+        let restored: crate::ChainResult = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.status, "ok");
+        assert_eq!(restored.name.as_deref(), Some("from_str_chain"));
+        assert_eq!(restored.steps.unwrap()["greet"].outputs["word"], "hello");
+
+        let cloned = result.clone();
+        assert_eq!(cloned.results, result.results);
+    }
+
+    #[test]
+    fn test_run_from_str_validation_error() {
+        let yaml = r"
+steps:
+  greet:
+    type: bash
+    script: echo hi
+results:
+  missing:
+    ref: steps.greet.outputs.nope
+";
+
+        let result = crate::run_from_str(yaml);
+        assert!(matches!(
+            result,
+            Err(crate::AtentoError::UnresolvedReference { .. })
+        ));
+
+        let result = crate::run_from_str("steps: [");
+        assert!(matches!(result, Err(crate::AtentoError::YamlParse { .. })));
+    }
+
+    #[test]
+    fn test_parse_parameter_overrides() {
+        let overrides =
+            crate::parse_parameter_overrides(&["env=prod", " retries =3", "query=a=b", "empty="])
+                .unwrap();
+        assert_eq!(overrides["env"], "prod");
+        assert_eq!(overrides["retries"], "3");
+        assert_eq!(overrides["query"], "a=b");
+        assert_eq!(overrides["empty"], "");
+
+        for pair in ["novalue", "=value"] {
+            assert!(matches!(
+                crate::parse_parameter_overrides(&[pair]),
+                Err(crate::AtentoError::Validation(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_run_with_parameters_rejects_unknown_override() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("chain.yaml");
+        std::fs::write(
+            &path,
+            "parameters:\n  env:\n    value: dev\nsteps:\n  s:\n    type: bash\n    script: echo hi\n",
+        )
+        .unwrap();
+
+        let result = crate::run_with_parameters(path.to_str().unwrap(), &["region=eu"]);
+        assert!(matches!(result, Err(crate::AtentoError::Validation(_))));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_run_with_parameters_supplies_required_parameter() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let path = temp_dir.path().join("chain.yaml");
+        std::fs::write(
+            &path,
+            "parameters:\n  env:\n    required: true\nsteps:\n  s:\n    type: bash\n    script: echo {{ parameters.env }}\n",
+        )
+        .unwrap();
+        let path = path.to_str().unwrap();
+
+        assert!(crate::run_with_parameters(path, &["env=prod"]).is_ok());
+        assert!(matches!(
+            crate::run(path),
+            Err(crate::AtentoError::Validation(_))
+        ));
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_run_to_file_writes_result_json() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let chain_path = temp_dir.path().join("chain.yaml");
+        std::fs::write(
+            &chain_path,
+            "name: to_file\nsteps:\n  greet:\n    type: bash\n    script: echo hello\n",
+        )
+        .unwrap();
+        let output_path = temp_dir.path().join("result.json");
+        std::fs::write(&output_path, "stale").unwrap();
+
+        crate::run_to_file(chain_path.to_str().unwrap(), output_path.to_str().unwrap()).unwrap();
+
+        let written = std::fs::read_to_string(&output_path).unwrap();
+        let result: crate::ChainResult = serde_json::from_str(&written).unwrap();
+        assert_eq!(result.status, "ok");
+        assert_eq!(result.name.as_deref(), Some("to_file"));
+        assert_eq!(
+            result.steps.unwrap()["greet"].stdout.as_deref(),
+            Some("hello")
+        );
+        // Only the result file is left behind
+        assert_eq!(std::fs::read_dir(temp_dir.path()).unwrap().count(), 2);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_run_to_file_writes_failed_result() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let chain_path = temp_dir.path().join("chain.yaml");
+        std::fs::write(
+            &chain_path,
+            "steps:\n  fail:\n    type: bash\n    script: exit 3\n",
+        )
+        .unwrap();
+        let output_path = temp_dir.path().join("result.json");
+
+        let outcome =
+            crate::run_to_file(chain_path.to_str().unwrap(), output_path.to_str().unwrap());
+        assert!(matches!(outcome, Err(crate::AtentoError::Execution(_))));
+
+        let result: crate::ChainResult =
+            serde_json::from_str(&std::fs::read_to_string(&output_path).unwrap()).unwrap();
+        assert_eq!(result.status, "nok");
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_run_to_file_reports_output_path_on_write_failure() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let chain_path = temp_dir.path().join("chain.yaml");
+        std::fs::write(
+            &chain_path,
+            "steps:\n  s:\n    type: bash\n    script: echo hi\n",
+        )
+        .unwrap();
+        let output_path = temp_dir.path().join("missing").join("result.json");
+        let output = output_path.to_str().unwrap();
+
+        match crate::run_to_file(chain_path.to_str().unwrap(), output) {
+            Err(err @ crate::AtentoError::IoWrite { .. }) => {
+                assert!(
+                    err.to_string()
+                        .starts_with(&format!("Failed to write file '{output}'")),
+                    "{err}"
+                );
+            }
+            other => panic!("Expected an IoWrite error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_validate_checks_without_running() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let marker = temp_dir.path().join("ran");
+        let chain_path = temp_dir.path().join("chain.yaml");
+        std::fs::write(
+            &chain_path,
+            format!(
+                "interpreters:\n  missing:\n    command: atento-no-such-interpreter\n    extension: .x\nsteps:\n  s:\n    type: missing\n    script: touch {}\n",
+                marker.display()
+            ),
+        )
+        .unwrap();
+
+        crate::validate(chain_path.to_str().unwrap()).unwrap();
+        assert!(!marker.exists());
+    }
+
+    #[test]
+    fn test_validate_reports_read_parse_and_validation_errors() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let missing = temp_dir.path().join("missing.yaml");
+        let missing = missing.to_str().unwrap();
+        match crate::validate(missing) {
+            Err(crate::AtentoError::Io { path, .. }) => assert_eq!(path, missing),
+            other => panic!("Expected Io error, got {other:?}"),
+        }
+
+        let invalid = temp_dir.path().join("invalid.yaml");
+        std::fs::write(&invalid, "steps: {").unwrap();
+        assert!(matches!(
+            crate::validate(invalid.to_str().unwrap()),
+            Err(crate::AtentoError::YamlParse { .. })
+        ));
+
+        let unresolved = temp_dir.path().join("unresolved.yaml");
+        std::fs::write(
+            &unresolved,
+            "steps:\n  s:\n    type: bash\n    script: echo {{ inputs.missing }}\n",
+        )
+        .unwrap();
+        assert!(matches!(
+            crate::validate(unresolved.to_str().unwrap()),
+            Err(crate::AtentoError::Validation(_))
+        ));
+    }
 }
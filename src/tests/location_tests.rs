@@ -0,0 +1,179 @@
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use crate::chain::Chain;
+    use crate::diagnostic::Severity;
+    use crate::errors::AtentoError;
+    use crate::location::{SourceLocation, SourceMap};
+
+    const CHAIN: &str = r#"# Release chain
+name: release
+steps:
+  build:
+    type: bash
+    timeout: 30
+    script: |
+      cat <<EOF
+      fake: key
+      EOF
+  "quoted step":
+    type: bash
+    script: echo hi
+results:
+  version:
+    ref: steps.build.stdout
+"#;
+
+    fn at(line: usize, column: usize) -> (&'static str, SourceLocation) {
+        ("chain.yaml", SourceLocation { line, column })
+    }
+
+    #[test]
+    fn test_scan_records_keys_by_path() {
+        let map = SourceMap::scan("chain.yaml", CHAIN);
+
+        assert_eq!(map.locate("name"), Some(at(2, 1)));
+        assert_eq!(map.locate("steps.build"), Some(at(4, 3)));
+        assert_eq!(map.locate("steps.build.timeout"), Some(at(6, 5)));
+        assert_eq!(map.locate("steps.quoted step"), Some(at(11, 3)));
+        assert_eq!(map.locate("results.version"), Some(at(15, 3)));
+        assert_eq!(map.locate("results.version.ref"), Some(at(16, 5)));
+    }
+
+    #[test]
+    fn test_scan_skips_block_scalars() {
+        let map = SourceMap::scan("chain.yaml", CHAIN);
+
+        assert_eq!(map.locate("steps.build.script.fake"), None);
+        assert_eq!(map.locate("fake"), None);
+    }
+
+    #[test]
+    fn test_matrix_steps_point_at_the_matrix_step() {
+        let map = SourceMap::scan("chain.yaml", "steps:\n  test:\n    script: echo\n");
+
+        assert_eq!(map.step("test[3.9,linux]"), Some(at(2, 3)));
+        assert_eq!(map.step("other"), None);
+    }
+
+    #[test]
+    fn test_source_location_display() {
+        let location = SourceLocation { line: 4, column: 3 };
+        assert_eq!(location.to_string(), "line 4 column 3");
+    }
+
+    #[test]
+    fn test_loaded_chain_validation_errors_name_the_step_location() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("chain.yaml");
+        std::fs::write(
+            &path,
+            "steps:\n  first:\n    type: bash\n    script: echo hi\n  empty:\n    type: bash\n    workdir: /tmp\n",
+        )
+        .unwrap();
+
+        let chain = Chain::load_with_includes(&path).unwrap();
+        let err = chain.validate().unwrap_err();
+        let file = path.display().to_string();
+        assert!(matches!(err, AtentoError::Validation(_)), "{err}");
+        assert!(
+            err.to_string()
+                .ends_with(&format!("(at line 5 column 3 of '{file}')")),
+            "{err}"
+        );
+
+        let diagnostics = chain.validate_all();
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].severity, Severity::Error);
+        assert_eq!(diagnostics[0].file.as_deref(), Some(file.as_str()));
+        assert_eq!(
+            diagnostics[0].location,
+            Some(SourceLocation { line: 5, column: 3 })
+        );
+        assert!(!diagnostics[0].message.contains("line 5"));
+    }
+
+    #[test]
+    fn test_loaded_chain_unresolved_result_names_the_result_location() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("chain.yaml");
+        std::fs::write(
+            &path,
+            "steps:\n  s:\n    type: bash\n    script: echo hi\nresults:\n  missing:\n    ref: steps.s.outputs.nope\n",
+        )
+        .unwrap();
+
+        let err = Chain::load_with_includes(&path)
+            .unwrap()
+            .validate()
+            .unwrap_err();
+        match err {
+            AtentoError::UnresolvedReference { context, .. } => {
+                assert!(context.contains("(at line 6 column 3 of "), "{context}");
+            }
+            other => panic!("Expected UnresolvedReference, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_parsed_chain_errors_have_no_location() {
+        let chain: Chain = "steps:\n  s:\n    type: cobol\n    script: echo hi\n"
+            .parse()
+            .unwrap();
+
+        assert!(chain.source_map.is_none());
+        assert!(
+            !chain
+                .validate()
+                .unwrap_err()
+                .to_string()
+                .contains("(at line")
+        );
+        assert_eq!(chain.validate_all()[0].location, None);
+    }
+
+    #[test]
+    fn test_yaml_parse_error_carries_location() {
+        let err = "steps:\n  s:\n    type: bash\n    timeout: fast\n    script: echo hi\n"
+            .parse::<Chain>()
+            .unwrap_err();
+
+        match err {
+            AtentoError::YamlParse { location, .. } => {
+                assert_eq!(location.map(|l| l.line), Some(4));
+            }
+            other => panic!("Expected YamlParse, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_included_steps_keep_their_own_file() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(
+            dir.path().join("common.yaml"),
+            "steps:\n  shared:\n    type: cobol\n    script: echo hi\n",
+        )
+        .unwrap();
+        let path = dir.path().join("chain.yaml");
+        std::fs::write(
+            &path,
+            "include:\n  - common.yaml\nsteps:\n  own:\n    type: bash\n    script: echo hi\n",
+        )
+        .unwrap();
+
+        let chain = Chain::load_with_includes(&path).unwrap();
+        let diagnostics = chain.validate_all();
+        assert_eq!(diagnostics[0].step.as_deref(), Some("shared"));
+        assert!(
+            diagnostics[0]
+                .file
+                .as_deref()
+                .unwrap()
+                .ends_with("common.yaml")
+        );
+        assert_eq!(
+            diagnostics[0].location,
+            Some(SourceLocation { line: 2, column: 3 })
+        );
+    }
+}
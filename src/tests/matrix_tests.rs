@@ -0,0 +1,189 @@
+#[cfg(all(test, feature = "exec"))]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use crate::chain::Chain;
+    use crate::errors::AtentoError;
+    use crate::executor::ExecutionResult;
+    use crate::tests::mock_executor::MockExecutor;
+
+    fn ok(stdout: &str) -> ExecutionResult {
+        ExecutionResult {
+            stdout: stdout.to_string(),
+            duration_ms: 1,
+            ..ExecutionResult::default()
+        }
+    }
+
+    fn validation_message(yaml: &str) -> String {
+        let chain: Chain = yaml.parse().unwrap();
+        match chain.validate().unwrap_err() {
+            AtentoError::Validation(message) => message,
+            other => panic!("expected a validation error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_matrix_expands_in_place() {
+        let chain: Chain = r#"
+steps:
+  setup:
+    type: bash
+    script: echo setup
+  test:
+    type: bash
+    script: tox -e py{{ inputs.python_version }} --os {{ inputs.os }}
+    matrix:
+      python_version: ["3.9", "3.10"]
+      os: [linux, macos]
+  report:
+    type: bash
+    script: echo report
+"#
+        .parse()
+        .unwrap();
+        chain.validate().unwrap();
+
+        let ids: Vec<&str> = chain.steps.keys().map(String::as_str).collect();
+        assert_eq!(
+            ids,
+            vec![
+                "setup",
+                "test[3.9,linux]",
+                "test[3.9,macos]",
+                "test[3.10,linux]",
+                "test[3.10,macos]",
+                "report",
+            ]
+        );
+
+        let step = &chain.steps["test[3.10,macos]"];
+        assert!(step.matrix.is_empty());
+        assert_eq!(
+            step.inputs["python_version"].to_string_value().unwrap(),
+            "3.10"
+        );
+        assert_eq!(step.inputs["os"].to_string_value().unwrap(), "macos");
+    }
+
+    #[test]
+    fn test_matrix_outputs_are_addressable() {
+        let chain: Chain = r#"
+steps:
+  test:
+    type: bash
+    script: echo "RESULT=py{{ inputs.python_version }}"
+    matrix:
+      python_version: ["3.9", "3.11"]
+    outputs:
+      result:
+        pattern: RESULT=(\S+)
+  summary:
+    type: bash
+    script: echo {{ steps.test[3.9].outputs.result }} {{ inputs.latest }}
+    inputs:
+      latest:
+        ref: steps.test[3.11].outputs.result
+results:
+  oldest:
+    ref: steps.test[3.9].outputs.result
+"#
+        .parse()
+        .unwrap();
+        chain.validate().unwrap();
+
+        let mut mock = MockExecutor::new();
+        mock.expect_call("echo \"RESULT=py3.9\"", ok("RESULT=py3.9"))
+            .expect_call("echo \"RESULT=py3.11\"", ok("RESULT=py3.11"))
+            .expect_call("echo py3.9 py3.11", ok("py3.9 py3.11"));
+        let result = chain.run_with_executor(&mock);
+
+        assert_eq!(result.status, "ok", "errors: {:?}", result.errors);
+        assert_eq!(mock.call_count(), 3);
+        assert_eq!(result.results.unwrap()["oldest"], "py3.9");
+        let steps = result.steps.unwrap();
+        assert_eq!(steps["test[3.11]"].outputs["result"], "py3.11");
+    }
+
+    #[test]
+    fn test_matrix_reference_to_unexpanded_step_is_rejected() {
+        let chain: Chain = r#"
+steps:
+  test:
+    type: bash
+    script: echo "RESULT={{ inputs.v }}"
+    matrix:
+      v: [a, b]
+    outputs:
+      result:
+        pattern: RESULT=(\S+)
+results:
+  value:
+    ref: steps.test.outputs.result
+"#
+        .parse()
+        .unwrap();
+        assert!(chain.validate().is_err());
+    }
+
+    #[test]
+    fn test_matrix_combination_cap() {
+        let message = validation_message(
+            r"
+steps:
+  grid:
+    type: bash
+    script: echo {{ inputs.x }} {{ inputs.y }} {{ inputs.z }}
+    matrix:
+      x: [1, 2, 3, 4, 5]
+      y: [1, 2, 3, 4, 5]
+      z: [1, 2, 3, 4, 5]
+",
+        );
+        assert_eq!(
+            message,
+            "Step 'grid' has a matrix of more than 100 combinations"
+        );
+
+        let chain: Chain = r"
+steps:
+  grid:
+    type: bash
+    script: echo {{ inputs.x }} {{ inputs.y }}
+    matrix:
+      x: [0, 1, 2, 3, 4, 5, 6, 7, 8, 9]
+      y: [0, 1, 2, 3, 4, 5, 6, 7, 8, 9]
+"
+        .parse()
+        .unwrap();
+        chain.validate().unwrap();
+        assert_eq!(chain.steps.len(), 100);
+    }
+
+    #[test]
+    fn test_invalid_matrices() {
+        assert_eq!(
+            validation_message(
+                "steps:\n  s:\n    type: bash\n    script: echo {{ inputs.v }}\n    matrix:\n      v: []\n"
+            ),
+            "Step 's' has no values for matrix key 'v'"
+        );
+        assert_eq!(
+            validation_message(
+                "steps:\n  s:\n    type: bash\n    script: echo {{ inputs.v }}\n    matrix:\n      v: [[1, 2]]\n"
+            ),
+            "Step 's' has a non-scalar value for matrix key 'v'"
+        );
+        assert_eq!(
+            validation_message(
+                "steps:\n  s:\n    type: bash\n    script: echo {{ inputs.v }}\n    inputs:\n      v:\n        value: x\n    matrix:\n      v: [a]\n"
+            ),
+            "Step 's' declares input 'v', which is also a matrix key"
+        );
+        assert_eq!(
+            validation_message(
+                "steps:\n  s[a]:\n    type: bash\n    script: echo a\n  s:\n    type: bash\n    script: echo {{ inputs.v }}\n    matrix:\n      v: [a]\n"
+            ),
+            "Step 's' expands to 's[a]', which is already a step"
+        );
+    }
+}
@@ -1,31 +1,41 @@
 use crate::errors::Result;
-use crate::executor::{CommandExecutor, ExecutionResult};
+use crate::executor::{CommandExecutor, ExecutionRequest, ExecutionResult};
 use crate::interpreter::Interpreter;
+use crate::runner::KillPolicy;
 use std::cell::RefCell;
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 
 type CallRecord = (String, Interpreter, u64);
 
 /// Mock implementation for unit tests
 pub struct MockExecutor {
     responses: HashMap<String, ExecutionResult>,
+    sequences: RefCell<HashMap<String, VecDeque<ExecutionResult>>>,
     default_response: ExecutionResult,
     call_count: RefCell<usize>,
     last_call: RefCell<Option<CallRecord>>,
+    last_env: RefCell<Option<HashMap<String, String>>>,
+    last_workdir: RefCell<Option<String>>,
+    last_stdin: RefCell<Option<String>>,
+    last_kill: RefCell<Option<KillPolicy>>,
 }
 
 impl MockExecutor {
     pub fn new() -> Self {
         Self {
             responses: HashMap::new(),
+            sequences: RefCell::new(HashMap::new()),
             default_response: ExecutionResult {
                 stdout: "mock output".to_string(),
-                stderr: String::new(),
-                exit_code: 0,
                 duration_ms: 10,
+                ..ExecutionResult::default()
             },
             call_count: RefCell::new(0),
             last_call: RefCell::new(None),
+            last_env: RefCell::new(None),
+            last_workdir: RefCell::new(None),
+            last_stdin: RefCell::new(None),
+            last_kill: RefCell::new(None),
         }
     }
 
@@ -34,14 +44,25 @@ impl MockExecutor {
         self
     }
 
+    /// Queues responses returned one per call for `script`; once the queue is
+    /// drained, calls fall back to `expect_call` responses or the default.
+    pub fn expect_calls(&mut self, script: &str, responses: Vec<ExecutionResult>) -> &mut Self {
+        self.sequences
+            .borrow_mut()
+            .insert(script.to_string(), responses.into());
+        self
+    }
+
     pub fn expect_timeout(&mut self, script: &str) -> &mut Self {
         self.responses.insert(
             script.to_string(),
             ExecutionResult {
-                stdout: String::new(),
                 stderr: "Timeout".to_string(),
                 exit_code: 124,
                 duration_ms: 1000,
+                timed_out: true,
+                force_killed: true,
+                ..ExecutionResult::default()
             },
         );
         self
@@ -51,10 +72,10 @@ impl MockExecutor {
         self.responses.insert(
             script.to_string(),
             ExecutionResult {
-                stdout: String::new(),
                 stderr: stderr.to_string(),
                 exit_code,
                 duration_ms: 5,
+                ..ExecutionResult::default()
             },
         );
         self
@@ -67,21 +88,49 @@ impl MockExecutor {
     pub fn last_call(&self) -> Option<(String, Interpreter, u64)> {
         self.last_call.borrow().clone()
     }
+
+    pub fn last_env(&self) -> Option<HashMap<String, String>> {
+        self.last_env.borrow().clone()
+    }
+
+    pub fn last_workdir(&self) -> Option<String> {
+        self.last_workdir.borrow().clone()
+    }
+
+    pub fn last_stdin(&self) -> Option<String> {
+        self.last_stdin.borrow().clone()
+    }
+
+    pub fn last_kill(&self) -> Option<KillPolicy> {
+        self.last_kill.borrow().clone()
+    }
 }
 
 impl CommandExecutor for MockExecutor {
-    fn execute(
-        &self,
-        script: &str,
-        interpreter: &Interpreter,
-        timeout: u64,
-    ) -> Result<ExecutionResult> {
+    fn execute(&self, request: &ExecutionRequest<'_>) -> Result<ExecutionResult> {
         *self.call_count.borrow_mut() += 1;
-        *self.last_call.borrow_mut() = Some((script.to_string(), interpreter.clone(), timeout));
+        *self.last_call.borrow_mut() = Some((
+            request.script.to_string(),
+            request.interpreter.clone(),
+            request.timeout,
+        ));
+        *self.last_env.borrow_mut() = Some(request.env.clone());
+        *self.last_workdir.borrow_mut() = request.workdir.map(ToString::to_string);
+        *self.last_kill.borrow_mut() = Some(request.kill.clone());
+        *self.last_stdin.borrow_mut() = request.stdin.map(ToString::to_string);
+
+        if let Some(response) = self
+            .sequences
+            .borrow_mut()
+            .get_mut(request.script)
+            .and_then(VecDeque::pop_front)
+        {
+            return Ok(response);
+        }
 
         Ok(self
             .responses
-            .get(script)
+            .get(request.script)
             .cloned()
             .unwrap_or_else(|| self.default_response.clone()))
     }
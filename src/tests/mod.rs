@@ -1,16 +1,36 @@
+// Combined tests that include both integration tests and unit tests
+// Note: Platform-specific integration tests are in tests/integration/
+pub mod artifact_tests;
+pub mod async_tests;
+pub mod builder_tests;
+pub mod chain_tests;
+pub mod compose_tests;
 pub mod data_type_tests;
+pub mod diagnostic_tests;
+pub mod document_tests;
+pub mod env_guard;
 pub mod errors_tests;
 pub mod executor_tests;
+pub mod fields_tests;
+pub mod format_tests;
+pub mod include_tests;
 pub mod input_tests;
 pub mod interpreter_tests;
 pub mod lib_tests;
+pub mod location_tests;
+pub mod matrix_tests;
+#[cfg(feature = "exec")]
 pub mod mock_executor;
+pub mod model_tests;
+pub mod observer_tests;
 pub mod output_tests;
 pub mod parameter_tests;
+pub mod plan_tests;
 pub mod result_ref_tests;
-
-// Combined tests that include both integration tests and unit tests
-// Note: Platform-specific integration tests are in tests/integration/
-pub mod chain_tests;
+pub mod retry_tests;
 pub mod runner_tests;
+pub mod schema_tests;
 pub mod step_tests;
+pub mod template_tests;
+pub mod tracing_tests;
+pub mod webhook_tests;
@@ -0,0 +1,103 @@
+// Tests for the chain model that build with and without the `exec` feature,
+// run with `cargo test --no-default-features --lib` for the wasm32 build
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use crate::chain::Chain;
+    use crate::errors::AtentoError;
+    use crate::input::Input;
+    use std::collections::HashMap;
+
+    fn chain() -> Chain {
+        r#"
+name: release
+parameters:
+  version:
+    value: "1.2.3"
+remote:
+  host: build.example.com
+  user: ci
+steps:
+  build:
+    type: bash
+    script: echo "building {{ inputs.version }}"
+    inputs:
+      version:
+        ref: parameters.version
+    outputs:
+      artifact:
+        pattern: "ARTIFACT=(.*)"
+  publish:
+    type: python
+    script: print("publishing {{ inputs.artifact }}")
+    inputs:
+      artifact:
+        ref: steps.build.outputs.artifact
+results:
+  artifact:
+    ref: steps.build.outputs.artifact
+"#
+        .parse()
+        .unwrap()
+    }
+
+    #[test]
+    fn test_chain_parses_and_validates() {
+        let chain = chain();
+
+        chain.validate().unwrap();
+        let remote = chain.remote.as_ref().unwrap();
+        assert_eq!(remote.destination(), "ci@build.example.com");
+    }
+
+    #[test]
+    fn test_validation_reports_unknown_references() {
+        let mut chain = chain();
+        chain.steps["publish"].inputs.insert(
+            "artifact".to_string(),
+            Input::Ref {
+                ref_: "steps.build.outputs.missing".to_string(),
+            },
+        );
+
+        let report = chain.validate_detailed();
+
+        assert!(!report.is_valid());
+        assert!(matches!(
+            report.errors.as_slice(),
+            [AtentoError::UnresolvedReference { reference, .. }] if reference == "steps.build.outputs.missing"
+        ));
+    }
+
+    #[test]
+    fn test_build_script_substitutes_inputs() {
+        let chain = chain();
+        let inputs = HashMap::from([("version".to_string(), "2.0.0".to_string())]);
+
+        let script = chain.steps["build"].build_script(&inputs);
+
+        assert_eq!(script, r#"echo "building 2.0.0""#);
+    }
+
+    #[test]
+    fn test_dry_run_renders_every_step() {
+        let result = chain().dry_run();
+
+        assert_eq!(result.status, "dry-run");
+        assert!(result.errors.is_empty(), "{:?}", result.errors);
+        let steps = result.steps.unwrap();
+        assert_eq!(
+            steps["build"].script.as_deref(),
+            Some(r#"echo "building 1.2.3""#)
+        );
+        assert_eq!(
+            steps["publish"].script.as_deref(),
+            Some(r#"print("publishing <steps.build.outputs.artifact>")"#)
+        );
+        assert_eq!(
+            result.results.unwrap()["artifact"],
+            "<steps.build.outputs.artifact>"
+        );
+        assert!(result.started_at.is_some());
+    }
+}
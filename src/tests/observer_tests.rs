@@ -0,0 +1,144 @@
+#[cfg(all(test, feature = "exec"))]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use crate::chain::{Chain, ChainResult};
+    use crate::executor::ExecutionResult;
+    use crate::interpreter::default_interpreters;
+    use crate::observer::{ChainObserver, NoopObserver};
+    use crate::step::{Step, StepResult};
+    use crate::tests::mock_executor::MockExecutor;
+
+    #[derive(Default)]
+    struct RecordingObserver {
+        events: Vec<String>,
+    }
+
+    impl ChainObserver for RecordingObserver {
+        fn on_step_start(&mut self, step_id: &str, step_name: Option<&str>) {
+            self.events
+                .push(format!("start {step_id} {}", step_name.unwrap_or("-")));
+        }
+
+        fn on_step_complete(&mut self, step_id: &str, result: &StepResult) {
+            self.events
+                .push(format!("complete {step_id} {}", result.exit_code));
+        }
+
+        fn on_chain_complete(&mut self, result: &ChainResult) {
+            self.events.push(format!("chain {}", result.status));
+        }
+    }
+
+    /// Keeps the stdout of the last completed step
+    struct StdoutObserver(Option<String>);
+
+    impl ChainObserver for StdoutObserver {
+        fn on_step_complete(&mut self, _step_id: &str, result: &StepResult) {
+            self.0.clone_from(&result.stdout);
+        }
+    }
+
+    fn ok(stdout: &str) -> ExecutionResult {
+        ExecutionResult {
+            stdout: stdout.to_string(),
+            duration_ms: 1,
+            ..ExecutionResult::default()
+        }
+    }
+
+    fn chain_with_steps(steps: &[(&str, &str)]) -> Chain {
+        let mut chain = Chain {
+            interpreters: default_interpreters().into_iter().collect(),
+            ..Chain::default()
+        };
+        for (id, script) in steps {
+            let mut step = Step::new("bash");
+            step.script = (*script).to_string();
+            chain.steps.insert((*id).to_string(), step);
+        }
+        chain
+    }
+
+    #[test]
+    fn test_observer_receives_events_in_order() {
+        let mut chain = chain_with_steps(&[("build", "make"), ("test", "make test")]);
+        chain.steps["build"].name = Some("Build".to_string());
+
+        let mut mock = MockExecutor::new();
+        mock.expect_call("make", ok("built"))
+            .expect_call("make test", ok("passed"));
+
+        let mut observer = RecordingObserver::default();
+        let result = chain.run_with_observer(&mock, &mut observer);
+
+        assert_eq!(result.status, "ok");
+        assert_eq!(
+            observer.events,
+            vec![
+                "start build Build",
+                "complete build 0",
+                "start test -",
+                "complete test 0",
+                "chain ok",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_observer_reports_failed_step_but_not_skipped_steps() {
+        let chain = chain_with_steps(&[("first", "fail"), ("second", "never")]);
+
+        let mut mock = MockExecutor::new();
+        mock.expect_error("fail", 2, "boom");
+
+        let mut observer = RecordingObserver::default();
+        let result = chain.run_with_observer(&mock, &mut observer);
+
+        assert_eq!(result.status, "nok");
+        assert_eq!(
+            observer.events,
+            vec!["start first -", "complete first 2", "chain nok"]
+        );
+    }
+
+    #[test]
+    fn test_observer_reports_timed_out_step() {
+        let chain = chain_with_steps(&[("slow", "sleep")]);
+
+        let mut mock = MockExecutor::new();
+        mock.expect_timeout("sleep");
+
+        let mut observer = RecordingObserver::default();
+        let result = chain.run_with_observer(&mock, &mut observer);
+
+        assert_eq!(result.status, "nok");
+        assert_eq!(
+            observer.events,
+            vec!["start slow -", "complete slow 124", "chain nok"]
+        );
+    }
+
+    #[test]
+    fn test_observer_complete_receives_step_result() {
+        let chain = chain_with_steps(&[("only", "echo")]);
+
+        let mut mock = MockExecutor::new();
+        mock.expect_call("echo", ok("hello"));
+
+        let mut observer = StdoutObserver(None);
+        let _ = chain.run_with_observer(&mock, &mut observer);
+        assert_eq!(observer.0.as_deref(), Some("hello"));
+    }
+
+    #[test]
+    fn test_noop_observer_matches_run_with_executor() {
+        let chain = chain_with_steps(&[("only", "echo")]);
+
+        let mut mock = MockExecutor::new();
+        mock.expect_call("echo", ok("hello"));
+
+        let result = chain.run_with_observer(&mock, &mut NoopObserver);
+        assert_eq!(result.status, "ok");
+        assert_eq!(mock.call_count(), 1);
+    }
+}
@@ -2,13 +2,24 @@
 #[allow(clippy::unwrap_used)]
 mod tests {
     use crate::data_type::DataType;
-    use crate::output::Output;
+    use crate::output::{
+        Output, OutputSource, PathSegment, RegexCache, parse_json_path, resolve_json_path,
+    };
 
     #[test]
     fn test_output_creation() {
         let output = Output {
             pattern: r"result: (\d+)".to_string(),
             type_: DataType::Int,
+            group: None,
+            json_path: None,
+            key: None,
+            source: OutputSource::Stdout,
+            multiple: false,
+            optional: false,
+            delimiter: None,
+            default: None,
+            regex_cache: RegexCache::default(),
         };
         assert_eq!(output.pattern, r"result: (\d+)");
         assert_eq!(output.type_, DataType::Int);
@@ -19,6 +30,15 @@ mod tests {
         let output = Output {
             pattern: r"value: (.+)".to_string(),
             type_: DataType::String,
+            group: None,
+            json_path: None,
+            key: None,
+            source: OutputSource::Stdout,
+            multiple: false,
+            optional: false,
+            delimiter: None,
+            default: None,
+            regex_cache: RegexCache::default(),
         };
         let cloned = output.clone();
         assert_eq!(output.pattern, cloned.pattern);
@@ -30,6 +50,15 @@ mod tests {
         let output = Output {
             pattern: r"(\w+)".to_string(),
             type_: DataType::Bool,
+            group: None,
+            json_path: None,
+            key: None,
+            source: OutputSource::Stdout,
+            multiple: false,
+            optional: false,
+            delimiter: None,
+            default: None,
+            regex_cache: RegexCache::default(),
         };
         let debug = format!("{output:?}");
         assert!(debug.contains("Output"));
@@ -62,6 +91,15 @@ pattern: "value: (.+)"
         let output = Output {
             pattern: r"(\d+\.\d+)".to_string(),
             type_: DataType::Float,
+            group: None,
+            json_path: None,
+            key: None,
+            source: OutputSource::Stdout,
+            multiple: false,
+            optional: false,
+            delimiter: None,
+            default: None,
+            regex_cache: RegexCache::default(),
         };
         let yaml = serde_yaml::to_string(&output).unwrap();
         assert!(yaml.contains("pattern"));
@@ -74,6 +112,15 @@ pattern: "value: (.+)"
         let output = Output {
             pattern: r"timestamp: (.+)".to_string(),
             type_: DataType::DateTime,
+            group: None,
+            json_path: None,
+            key: None,
+            source: OutputSource::Stdout,
+            multiple: false,
+            optional: false,
+            delimiter: None,
+            default: None,
+            regex_cache: RegexCache::default(),
         };
         let yaml = serde_yaml::to_string(&output).unwrap();
         let deserialized: Output = serde_yaml::from_str(&yaml).unwrap();
@@ -86,6 +133,15 @@ pattern: "value: (.+)"
         let output = Output {
             pattern: String::new(),
             type_: DataType::String,
+            group: None,
+            json_path: None,
+            key: None,
+            source: OutputSource::Stdout,
+            multiple: false,
+            optional: false,
+            delimiter: None,
+            default: None,
+            regex_cache: RegexCache::default(),
         };
         assert_eq!(output.pattern, "");
     }
@@ -95,6 +151,15 @@ pattern: "value: (.+)"
         let output = Output {
             pattern: r"^ERROR:\s+(.+?)$".to_string(),
             type_: DataType::String,
+            group: None,
+            json_path: None,
+            key: None,
+            source: OutputSource::Stdout,
+            multiple: false,
+            optional: false,
+            delimiter: None,
+            default: None,
+            regex_cache: RegexCache::default(),
         };
         assert!(output.pattern.contains("ERROR"));
     }
@@ -113,6 +178,15 @@ pattern: "value: (.+)"
             let output = Output {
                 pattern: r"(.+)".to_string(),
                 type_: dt.clone(),
+                group: None,
+                json_path: None,
+                key: None,
+                source: OutputSource::Stdout,
+                multiple: false,
+                optional: false,
+                delimiter: None,
+                default: None,
+                regex_cache: RegexCache::default(),
             };
             assert_eq!(output.type_, dt);
         }
@@ -123,7 +197,145 @@ pattern: "value: (.+)"
         let output = Output {
             pattern: r"value:\s+(\d+)".to_string(),
             type_: DataType::Int,
+            group: None,
+            json_path: None,
+            key: None,
+            source: OutputSource::Stdout,
+            multiple: false,
+            optional: false,
+            delimiter: None,
+            default: None,
+            regex_cache: RegexCache::default(),
         };
         assert!(output.pattern.contains(r"\s+"));
     }
+
+    #[test]
+    fn test_output_deserialize_group() {
+        let yaml = r"
+pattern: 'v(?P<version>\d+\.\d+)'
+group: version
+";
+        let output: Output = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(output.group.as_deref(), Some("version"));
+
+        let output: Output = serde_yaml::from_str("pattern: '(.+)'").unwrap();
+        assert!(output.group.is_none());
+        assert!(!serde_yaml::to_string(&output).unwrap().contains("group"));
+    }
+
+    #[test]
+    fn test_parse_json_path() {
+        assert_eq!(parse_json_path("$").unwrap(), vec![]);
+        assert_eq!(
+            parse_json_path("$.result.items[1].name").unwrap(),
+            vec![
+                PathSegment::Key("result".to_string()),
+                PathSegment::Key("items".to_string()),
+                PathSegment::Index(1),
+                PathSegment::Key("name".to_string()),
+            ]
+        );
+
+        assert_eq!(
+            parse_json_path("result.items[1]").unwrap(),
+            parse_json_path("$.result.items[1]").unwrap()
+        );
+        assert_eq!(
+            parse_json_path("[0].name").unwrap(),
+            vec![PathSegment::Index(0), PathSegment::Key("name".to_string())]
+        );
+
+        for invalid in ["", ".result", "result..x", "$..x", "$.a[", "$.a[x]", "$a"] {
+            assert!(
+                parse_json_path(invalid).is_none(),
+                "{invalid} should be rejected"
+            );
+        }
+    }
+
+    #[test]
+    fn test_resolve_json_path() {
+        let doc = serde_json::json!({"result": {"items": [{"name": "a"}, {"name": "b"}]}});
+
+        let segments = parse_json_path("$.result.items[1].name").unwrap();
+        assert_eq!(resolve_json_path(&doc, &segments).unwrap(), "b");
+
+        let segments = parse_json_path("$.result.items[5]").unwrap();
+        assert!(resolve_json_path(&doc, &segments).is_none());
+    }
+
+    #[test]
+    fn test_output_deserialize_json_path() {
+        let output: Output = serde_yaml::from_str("json_path: $.result.version").unwrap();
+        assert_eq!(output.json_path.as_deref(), Some("$.result.version"));
+        assert!(output.pattern.is_empty());
+    }
+
+    #[test]
+    fn test_output_source_deserialize() {
+        let output: Output = serde_yaml::from_str("pattern: '(.+)'").unwrap();
+        assert_eq!(output.source, OutputSource::Stdout);
+
+        let output: Output = serde_yaml::from_str("pattern: '(.+)'\nsource: stderr").unwrap();
+        assert_eq!(output.source, OutputSource::Stderr);
+
+        let output: Output = serde_yaml::from_str("pattern: '(.+)'\nsource: both").unwrap();
+        assert_eq!(output.source, OutputSource::Both);
+
+        let result: Result<Output, _> = serde_yaml::from_str("pattern: '(.+)'\nsource: stdin");
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_output_render_value() {
+        let mut output: Output = serde_yaml::from_str("pattern: 'X=(.*)'\nmultiple: true").unwrap();
+        assert!(output.multiple);
+        assert!(!output.optional);
+
+        let list = serde_json::json!(["a", 2, true]);
+        assert_eq!(output.render_value(&list), "a\n2\ntrue");
+
+        output.delimiter = Some(",".to_string());
+        assert_eq!(output.render_value(&list), "a,2,true");
+        assert_eq!(output.render_value(&serde_json::json!("single")), "single");
+    }
+
+    #[test]
+    fn test_output_regex_is_compiled_once() {
+        let output = Output {
+            pattern: r"v(\d+)".to_string(),
+            ..Output::default()
+        };
+        assert!(output.regex_cache.0.get().is_none());
+
+        let first = output.regex().unwrap();
+        let (cached, _) = output.regex_cache.0.get().unwrap();
+        assert_eq!(cached, r"v(\d+)");
+        assert_eq!(output.regex().unwrap().as_str(), first.as_str());
+
+        // Clones keep the compiled regex
+        assert!(output.clone().regex_cache.0.get().is_some());
+    }
+
+    #[test]
+    fn test_output_regex_follows_changed_pattern() {
+        let mut output = Output {
+            pattern: r"v(\d+)".to_string(),
+            ..Output::default()
+        };
+        output.regex().unwrap();
+
+        output.pattern = r"version (\d+)".to_string();
+        assert_eq!(output.regex().unwrap().as_str(), r"version (\d+)");
+
+        output.pattern = "(unclosed".to_string();
+        assert!(output.regex().is_err());
+
+        let keyed = Output {
+            key: Some("VERSION".to_string()),
+            ..Output::default()
+        };
+        assert!(keyed.regex().unwrap().is_match("VERSION=1"));
+    }
 }
@@ -9,7 +9,10 @@ mod tests {
     fn test_parameter_to_string_value_string() {
         let param = Parameter {
             type_: DataType::String,
-            value: Value::String("test".to_string()),
+            value: Some(Value::String("test".to_string())),
+            secret: false,
+            separator: None,
+            required: false,
         };
         assert_eq!(param.to_string_value().unwrap(), "test");
     }
@@ -18,7 +21,10 @@ mod tests {
     fn test_parameter_to_string_value_int() {
         let param = Parameter {
             type_: DataType::Int,
-            value: Value::Number(42.into()),
+            value: Some(Value::Number(42.into())),
+            secret: false,
+            separator: None,
+            required: false,
         };
         assert_eq!(param.to_string_value().unwrap(), "42");
     }
@@ -27,7 +33,12 @@ mod tests {
     fn test_parameter_to_string_value_float() {
         let param = Parameter {
             type_: DataType::Float,
-            value: Value::Number(serde_yaml::Number::from(std::f64::consts::PI)),
+            value: Some(Value::Number(serde_yaml::Number::from(
+                std::f64::consts::PI,
+            ))),
+            secret: false,
+            separator: None,
+            required: false,
         };
         assert_eq!(
             param.to_string_value().unwrap(),
@@ -39,7 +50,10 @@ mod tests {
     fn test_parameter_to_string_value_bool() {
         let param = Parameter {
             type_: DataType::Bool,
-            value: Value::Bool(true),
+            value: Some(Value::Bool(true)),
+            secret: false,
+            separator: None,
+            required: false,
         };
         assert_eq!(param.to_string_value().unwrap(), "true");
     }
@@ -48,7 +62,10 @@ mod tests {
     fn test_parameter_to_string_value_datetime() {
         let param = Parameter {
             type_: DataType::DateTime,
-            value: Value::String("2024-01-15T10:30:00Z".to_string()),
+            value: Some(Value::String("2024-01-15T10:30:00Z".to_string())),
+            secret: false,
+            separator: None,
+            required: false,
         };
         assert_eq!(param.to_string_value().unwrap(), "2024-01-15T10:30:00Z");
     }
@@ -57,7 +74,10 @@ mod tests {
     fn test_parameter_to_string_value_type_mismatch() {
         let param = Parameter {
             type_: DataType::Int,
-            value: Value::String("not a number".to_string()),
+            value: Some(Value::String("not a number".to_string())),
+            secret: false,
+            separator: None,
+            required: false,
         };
         assert!(param.to_string_value().is_err());
     }
@@ -66,7 +86,10 @@ mod tests {
     fn test_parameter_clone() {
         let param = Parameter {
             type_: DataType::Int,
-            value: Value::Number(42.into()),
+            value: Some(Value::Number(42.into())),
+            secret: false,
+            separator: None,
+            required: false,
         };
         let cloned = param.clone();
         assert_eq!(cloned.type_, param.type_);
@@ -76,7 +99,10 @@ mod tests {
     fn test_parameter_debug() {
         let param = Parameter {
             type_: DataType::String,
-            value: Value::String("test".to_string()),
+            value: Some(Value::String("test".to_string())),
+            secret: false,
+            separator: None,
+            required: false,
         };
         let debug = format!("{param:?}");
         assert!(debug.contains("Parameter"));
@@ -90,7 +116,7 @@ value: 42
 ";
         let param: Parameter = serde_yaml::from_str(yaml).unwrap();
         assert_eq!(param.type_, DataType::Int);
-        assert_eq!(param.value.as_i64().unwrap(), 42);
+        assert_eq!(param.value.as_ref().and_then(Value::as_i64).unwrap(), 42);
     }
 
     #[test]
@@ -106,7 +132,10 @@ value: hello
     fn test_parameter_serialize() {
         let param = Parameter {
             type_: DataType::Bool,
-            value: Value::Bool(false),
+            value: Some(Value::Bool(false)),
+            secret: false,
+            separator: None,
+            required: false,
         };
         let yaml = serde_yaml::to_string(&param).unwrap();
         assert!(yaml.contains("type"));
@@ -118,7 +147,10 @@ value: hello
     fn test_parameter_roundtrip() {
         let param = Parameter {
             type_: DataType::Float,
-            value: Value::Number(serde_yaml::Number::from(std::f64::consts::E)),
+            value: Some(Value::Number(serde_yaml::Number::from(std::f64::consts::E))),
+            secret: false,
+            separator: None,
+            required: false,
         };
         let yaml = serde_yaml::to_string(&param).unwrap();
         let deserialized: Parameter = serde_yaml::from_str(&yaml).unwrap();
@@ -129,7 +161,10 @@ value: hello
     fn test_parameter_empty_string() {
         let param = Parameter {
             type_: DataType::String,
-            value: Value::String(String::new()),
+            value: Some(Value::String(String::new())),
+            secret: false,
+            separator: None,
+            required: false,
         };
         assert_eq!(param.to_string_value().unwrap(), "");
     }
@@ -138,7 +173,10 @@ value: hello
     fn test_parameter_negative_int() {
         let param = Parameter {
             type_: DataType::Int,
-            value: Value::Number((-100).into()),
+            value: Some(Value::Number((-100).into())),
+            secret: false,
+            separator: None,
+            required: false,
         };
         assert_eq!(param.to_string_value().unwrap(), "-100");
     }
@@ -147,7 +185,10 @@ value: hello
     fn test_parameter_negative_float() {
         let param = Parameter {
             type_: DataType::Float,
-            value: Value::Number(serde_yaml::Number::from(-99.99)),
+            value: Some(Value::Number(serde_yaml::Number::from(-99.99))),
+            secret: false,
+            separator: None,
+            required: false,
         };
         assert_eq!(param.to_string_value().unwrap(), "-99.99");
     }
@@ -156,7 +197,10 @@ value: hello
     fn test_parameter_zero_int() {
         let param = Parameter {
             type_: DataType::Int,
-            value: Value::Number(0.into()),
+            value: Some(Value::Number(0.into())),
+            secret: false,
+            separator: None,
+            required: false,
         };
         assert_eq!(param.to_string_value().unwrap(), "0");
     }
@@ -165,7 +209,10 @@ value: hello
     fn test_parameter_zero_float() {
         let param = Parameter {
             type_: DataType::Float,
-            value: Value::Number(serde_yaml::Number::from(0.0)),
+            value: Some(Value::Number(serde_yaml::Number::from(0.0))),
+            secret: false,
+            separator: None,
+            required: false,
         };
         assert_eq!(param.to_string_value().unwrap(), "0");
     }
@@ -174,7 +221,10 @@ value: hello
     fn test_parameter_bool_false() {
         let param = Parameter {
             type_: DataType::Bool,
-            value: Value::Bool(false),
+            value: Some(Value::Bool(false)),
+            secret: false,
+            separator: None,
+            required: false,
         };
         assert_eq!(param.to_string_value().unwrap(), "false");
     }
@@ -183,8 +233,64 @@ value: hello
     fn test_parameter_null_value() {
         let param = Parameter {
             type_: DataType::String,
-            value: Value::Null,
+            value: Some(Value::Null),
+            secret: false,
+            separator: None,
+            required: false,
         };
         assert!(param.to_string_value().is_err());
     }
+
+    #[test]
+    fn test_parameter_secret_flag() {
+        let param: Parameter = serde_yaml::from_str("value: hunter2\nsecret: true\n").unwrap();
+        assert!(param.secret);
+        assert_eq!(param.to_string_value().unwrap(), "hunter2");
+
+        let param: Parameter = serde_yaml::from_str("value: visible\n").unwrap();
+        assert!(!param.secret);
+    }
+
+    #[test]
+    fn test_parameter_list_with_separator() {
+        let yaml = r#"
+type: list
+value: ["a.txt", "b.txt"]
+separator: ","
+"#;
+        let param: Parameter = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(param.to_string_value().unwrap(), "a.txt,b.txt");
+
+        let param = Parameter {
+            separator: None,
+            ..param
+        };
+        assert_eq!(param.to_string_value().unwrap(), "a.txt b.txt");
+    }
+
+    #[test]
+    fn test_parameter_parse_value() {
+        let param: Parameter =
+            serde_yaml::from_str("type: list\nvalue: []\nseparator: \",\"").unwrap();
+        let value = param.parse_value("a.txt, b c.txt,").unwrap();
+        assert_eq!(
+            value,
+            serde_yaml::from_str::<Value>("[a.txt, b c.txt]").unwrap()
+        );
+
+        let param: Parameter = serde_yaml::from_str("type: float\nvalue: 1.0").unwrap();
+        assert_eq!(param.parse_value("2.5").unwrap().as_f64(), Some(2.5));
+        assert!(param.parse_value("fast").is_err());
+    }
+
+    #[test]
+    fn test_parameter_required_without_value() {
+        let param: Parameter = serde_yaml::from_str("type: string\nrequired: true").unwrap();
+        assert!(param.required);
+        assert!(param.value.is_none());
+        assert!(param.to_string_value().is_err());
+
+        let json = serde_json::to_value(&param).unwrap();
+        assert!(json.get("value").is_none());
+    }
 }
@@ -0,0 +1,113 @@
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use crate::chain::Chain;
+    use crate::data_type::DataType;
+    use crate::plan::StepPlan;
+    use std::collections::HashMap;
+
+    fn chain() -> Chain {
+        r"
+timeout: 30
+parameters:
+  project:
+    value: atento
+  retries:
+    type: int
+    value: 2
+steps:
+  version:
+    name: Read version
+    type: bash
+    timeout: 90
+    script: echo {{ inputs.project }} version=1.2.3 build=7
+    inputs:
+      project:
+        ref: parameters.project
+    outputs:
+      version:
+        pattern: version=([\d.]+)
+      build:
+        pattern: build=(\d+)
+  lint:
+    type: python
+    timeout: 5
+    script: print('ok')
+  tag:
+    type: bash
+    script: echo v{{ inputs.version }} {{ inputs.note }} {{ steps.lint.exit_code }}
+    inputs:
+      version:
+        ref: steps.version.outputs.version
+      note:
+        value: manual
+results:
+  version:
+    ref: steps.version.outputs.version
+  kind:
+    value: release
+"
+        .parse()
+        .unwrap()
+    }
+
+    #[test]
+    fn test_execution_plan_steps_in_declared_order() {
+        let plan = chain().execution_plan();
+        let ids: Vec<&str> = plan.steps.iter().map(|step| step.id.as_str()).collect();
+        assert_eq!(ids, vec!["version", "lint", "tag"]);
+
+        assert_eq!(
+            plan.steps[0],
+            StepPlan {
+                id: "version".to_string(),
+                name: "Read version".to_string(),
+                interpreter: Some("bash".to_string()),
+                inputs: HashMap::from([("project".to_string(), "parameters.project".to_string())]),
+                outputs: vec!["build".to_string(), "version".to_string()],
+                timeout: 30,
+                depends_on: vec![],
+            }
+        );
+        assert_eq!(plan.steps[1].name, "lint");
+        assert_eq!(plan.steps[1].timeout, 5);
+
+        let tag = &plan.steps[2];
+        assert_eq!(tag.timeout, 30);
+        assert_eq!(tag.inputs.len(), 1);
+        assert_eq!(tag.depends_on, vec!["version", "lint"]);
+    }
+
+    #[test]
+    fn test_execution_plan_parameters_and_results() {
+        let plan = chain().execution_plan();
+        assert_eq!(
+            plan.parameters,
+            HashMap::from([
+                ("project".to_string(), DataType::String),
+                ("retries".to_string(), DataType::Int),
+            ])
+        );
+        assert_eq!(
+            plan.results,
+            HashMap::from([(
+                "version".to_string(),
+                "steps.version.outputs.version".to_string()
+            )])
+        );
+
+        let json = serde_json::to_value(&plan).unwrap();
+        assert_eq!(json["steps"][2]["depends_on"][0], "version");
+        assert_eq!(json["parameters"]["retries"], "int");
+    }
+
+    #[test]
+    fn test_execution_plan_unknown_interpreter() {
+        let chain: Chain = "steps:\n  s:\n    type: cobol\n    script: run\n"
+            .parse()
+            .unwrap();
+        let plan = chain.execution_plan();
+        assert!(plan.steps[0].interpreter.is_none());
+        assert_eq!(plan.steps[0].timeout, 60);
+    }
+}
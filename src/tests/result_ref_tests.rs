@@ -1,7 +1,8 @@
 #[cfg(test)]
 #[allow(clippy::unwrap_used)]
 mod tests {
-    use crate::result_ref::ResultRef;
+    use crate::data_type::DataType;
+    use crate::result_ref::{ResultRef, ResultValue};
 
     #[test]
     fn test_result_ref_creation() {
@@ -85,4 +86,28 @@ ref: steps.foo.outputs.bar
         };
         assert!(result_ref.ref_.len() > 20);
     }
+
+    #[test]
+    fn test_result_value_deserialize_ref_and_inline() {
+        let value: ResultValue = serde_yaml::from_str("ref: parameters.env").unwrap();
+        assert!(matches!(value, ResultValue::Ref(ResultRef { ref_ }) if ref_ == "parameters.env"));
+
+        let value: ResultValue = serde_yaml::from_str("value: 1.2.3").unwrap();
+        assert!(matches!(
+            value,
+            ResultValue::Inline {
+                type_: DataType::String,
+                ..
+            }
+        ));
+
+        let value: ResultValue = serde_yaml::from_str("type: int\nvalue: 42").unwrap();
+        assert!(matches!(
+            value,
+            ResultValue::Inline {
+                type_: DataType::Int,
+                ..
+            }
+        ));
+    }
 }
@@ -0,0 +1,81 @@
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use crate::retry::{Backoff, RetryPolicy};
+    use std::time::Duration;
+
+    #[test]
+    fn test_retry_policy_default() {
+        let policy = RetryPolicy::default();
+        assert_eq!(policy.attempts, 1);
+        assert_eq!(policy.delay_secs, 0);
+        assert_eq!(policy.backoff, Backoff::Fixed);
+    }
+
+    #[test]
+    fn test_retry_policy_deserialize() {
+        let yaml = "attempts: 3\ndelay_secs: 5\nbackoff: exponential\n";
+        let policy: RetryPolicy = serde_yaml::from_str(yaml).unwrap();
+        assert_eq!(policy.attempts, 3);
+        assert_eq!(policy.delay_secs, 5);
+        assert_eq!(policy.backoff, Backoff::Exponential);
+
+        let policy: RetryPolicy = serde_yaml::from_str("delay_secs: 2").unwrap();
+        assert_eq!(policy.attempts, 1);
+        assert_eq!(policy.backoff, Backoff::Fixed);
+    }
+
+    #[test]
+    fn test_retry_policy_delay_fixed() {
+        let policy = RetryPolicy {
+            attempts: 4,
+            delay_secs: 3,
+            backoff: Backoff::Fixed,
+            delay_ms: 0,
+        };
+        assert_eq!(policy.delay_after(1), Duration::from_secs(3));
+        assert_eq!(policy.delay_after(3), Duration::from_secs(3));
+    }
+
+    #[test]
+    fn test_retry_policy_delay_linear() {
+        let policy = RetryPolicy {
+            attempts: 4,
+            delay_secs: 3,
+            backoff: Backoff::Linear,
+            delay_ms: 0,
+        };
+        assert_eq!(policy.delay_after(1), Duration::from_secs(3));
+        assert_eq!(policy.delay_after(2), Duration::from_secs(6));
+        assert_eq!(policy.delay_after(3), Duration::from_secs(9));
+    }
+
+    #[test]
+    fn test_retry_policy_delay_exponential() {
+        let policy = RetryPolicy {
+            attempts: 4,
+            delay_secs: 3,
+            backoff: Backoff::Exponential,
+            delay_ms: 0,
+        };
+        assert_eq!(policy.delay_after(1), Duration::from_secs(3));
+        assert_eq!(policy.delay_after(2), Duration::from_secs(6));
+        assert_eq!(policy.delay_after(3), Duration::from_secs(12));
+        assert_eq!(
+            policy.delay_after(200),
+            Duration::from_secs(3).saturating_mul(u32::MAX)
+        );
+    }
+
+    #[test]
+    fn test_retry_policy_delay_ms() {
+        let policy = RetryPolicy {
+            attempts: 3,
+            delay_secs: 1,
+            delay_ms: 250,
+            backoff: Backoff::Linear,
+        };
+        assert_eq!(policy.base_delay(), Duration::from_millis(1250));
+        assert_eq!(policy.delay_after(2), Duration::from_millis(2500));
+    }
+}
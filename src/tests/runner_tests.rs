@@ -1,21 +1,27 @@
-#[cfg(test)]
+#[cfg(all(test, feature = "exec"))]
 mod tests {
     // Cross-platform runner integration tests (non-execution) go here
     // Currently, all runner tests require actual command execution
     // so they are in the tests/integration/ directory for platform-specific testing
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "exec"))]
+#[allow(clippy::unwrap_used)]
 mod unit_tests {
     use crate::errors::AtentoError;
     use crate::interpreter::Interpreter;
-    use crate::runner::run;
+    use crate::runner::{
+        KillPolicy, OutputStream, TIMEOUT_EXIT_CODE, TimeoutSignal, run, run_combined,
+        run_streaming,
+    };
+    use std::collections::HashMap;
 
     fn bash_interpreter() -> Interpreter {
         Interpreter {
             command: "bash".to_string(),
             args: vec![],
             extension: ".sh".to_string(),
+            fallbacks: vec![],
         }
     }
 
@@ -31,6 +37,7 @@ mod unit_tests {
                 "-File".to_string(),
             ],
             extension: ".ps1".to_string(),
+            fallbacks: vec![],
         }
     }
 
@@ -39,6 +46,7 @@ mod unit_tests {
             command: "cmd".to_string(),
             args: vec!["/c".to_string()],
             extension: ".bat".to_string(),
+            fallbacks: vec![],
         }
     }
 
@@ -47,6 +55,7 @@ mod unit_tests {
             command: String::new(),
             args: vec![],
             extension: ".sh".to_string(),
+            fallbacks: vec![],
         }
     }
 
@@ -59,7 +68,16 @@ mod unit_tests {
 
     #[test]
     fn test_run_with_timeout_empty_script() {
-        let result = run("", &bash_interpreter(), 60);
+        let result = run(
+            "",
+            &bash_interpreter(),
+            &HashMap::new(),
+            None,
+            None,
+            None,
+            60,
+            &KillPolicy::default(),
+        );
         assert!(result.is_err());
         if let Err(AtentoError::Runner(msg)) = result {
             assert!(msg.contains("Script cannot be empty"));
@@ -70,7 +88,16 @@ mod unit_tests {
 
     #[test]
     fn test_run_with_timeout_invalid_interpreter() {
-        let result = run("echo test", &invalid_interpreter(), 60);
+        let result = run(
+            "echo test",
+            &invalid_interpreter(),
+            &HashMap::new(),
+            None,
+            None,
+            None,
+            60,
+            &KillPolicy::default(),
+        );
         assert!(result.is_err());
         if let Err(AtentoError::Runner(msg)) = result {
             assert!(msg.contains("Interpreter has invalid configuration"));
@@ -84,7 +111,16 @@ mod unit_tests {
         // This test verifies that passing 0 timeout uses the default timeout
         // We can't easily test the actual execution with default timeout in unit tests
         // since it would require real command execution, but we can test the parameter validation
-        let result = run("echo test", &bash_interpreter(), 0);
+        let result = run(
+            "echo test",
+            &bash_interpreter(),
+            &HashMap::new(),
+            None,
+            None,
+            None,
+            0,
+            &KillPolicy::default(),
+        );
         // The function should accept 0 timeout and use default internally
         // Result may fail due to bash execution but not due to timeout parameter validation
         assert!(result.is_ok() || matches!(result, Err(AtentoError::Runner(_))));
@@ -92,7 +128,16 @@ mod unit_tests {
 
     #[test]
     fn test_run_with_timeout_valid_parameters() {
-        let result = run("echo hello", &bash_interpreter(), 30);
+        let result = run(
+            "echo hello",
+            &bash_interpreter(),
+            &HashMap::new(),
+            None,
+            None,
+            None,
+            30,
+            &KillPolicy::default(),
+        );
         // This should succeed (or fail only due to command execution, not parameter validation)
         match result {
             Ok(runner_result) => {
@@ -113,7 +158,16 @@ mod unit_tests {
     #[test]
     fn test_run_with_timeout_with_powershell_extension() {
         // Test that PowerShell extension is handled correctly
-        let result = run("Write-Host test", &pwsh_interpreter(), 30);
+        let result = run(
+            "Write-Host test",
+            &pwsh_interpreter(),
+            &HashMap::new(),
+            None,
+            None,
+            None,
+            30,
+            &KillPolicy::default(),
+        );
         // The function should accept .ps1 extension and set appropriate environment
         match result {
             Ok(_) | Err(AtentoError::Runner(_) | AtentoError::Timeout { .. }) => {
@@ -131,8 +185,18 @@ mod unit_tests {
             command: "nonexistent_command".to_string(),
             args: vec![],
             extension: ".sh".to_string(),
+            fallbacks: vec![],
         };
-        let result = run("echo test", &nonexistent, 30);
+        let result = run(
+            "echo test",
+            &nonexistent,
+            &HashMap::new(),
+            None,
+            None,
+            None,
+            30,
+            &KillPolicy::default(),
+        );
         assert!(result.is_err());
         // Should fail with Runner error when trying to start nonexistent command
         if let Err(AtentoError::Runner(msg)) = result {
@@ -145,7 +209,16 @@ mod unit_tests {
     #[test]
     fn test_run_with_timeout_stderr_filtering() {
         // Test that stderr filtering works correctly
-        let result = run("echo test", &bash_interpreter(), 30);
+        let result = run(
+            "echo test",
+            &bash_interpreter(),
+            &HashMap::new(),
+            None,
+            None,
+            None,
+            30,
+            &KillPolicy::default(),
+        );
 
         match result {
             Ok(runner_result) => {
@@ -166,7 +239,16 @@ mod unit_tests {
     #[cfg(not(target_os = "windows"))]
     fn test_run_with_timeout_exit_code_handling() {
         // Test that exit codes are properly captured
-        let result = run("exit 42", &bash_interpreter(), 30);
+        let result = run(
+            "exit 42",
+            &bash_interpreter(),
+            &HashMap::new(),
+            None,
+            None,
+            None,
+            30,
+            &KillPolicy::default(),
+        );
 
         match result {
             Ok(runner_result) => {
@@ -185,7 +267,16 @@ mod unit_tests {
     #[test]
     fn test_run_with_timeout_windows_permissions() {
         // Test Windows-specific permission handling
-        let result = run("echo test", &batch_interpreter(), 30);
+        let result = run(
+            "echo test",
+            &batch_interpreter(),
+            &HashMap::new(),
+            None,
+            None,
+            None,
+            30,
+            &KillPolicy::default(),
+        );
 
         // This test mainly ensures the Windows permission code path compiles
         // and doesn't crash on non-Windows systems
@@ -202,7 +293,16 @@ mod unit_tests {
     #[test]
     fn test_run_with_timeout_temp_file_creation() {
         // Test temporary file creation and cleanup
-        let result = run("echo 'temp test'", &bash_interpreter(), 30);
+        let result = run(
+            "echo 'temp test'",
+            &bash_interpreter(),
+            &HashMap::new(),
+            None,
+            None,
+            None,
+            30,
+            &KillPolicy::default(),
+        );
 
         // The temp file should be cleaned up regardless of success or failure
         if result.is_ok() {
@@ -219,7 +319,16 @@ mod unit_tests {
     fn test_run_with_timeout_process_wait_error() {
         // Test error handling when process wait fails
         // This is hard to trigger artificially, but we test the code path exists
-        let result = run("echo test", &bash_interpreter(), 30);
+        let result = run(
+            "echo test",
+            &bash_interpreter(),
+            &HashMap::new(),
+            None,
+            None,
+            None,
+            30,
+            &KillPolicy::default(),
+        );
 
         match result {
             Ok(_) | Err(AtentoError::Timeout { .. }) => {
@@ -238,7 +347,16 @@ mod unit_tests {
     #[test]
     fn test_run_with_timeout_utf8_handling() {
         // Test UTF-8 output handling
-        let result = run("echo 'test ñoñó'", &bash_interpreter(), 30);
+        let result = run(
+            "echo 'test ñoñó'",
+            &bash_interpreter(),
+            &HashMap::new(),
+            None,
+            None,
+            None,
+            30,
+            &KillPolicy::default(),
+        );
 
         match result {
             Ok(runner_result) => {
@@ -259,7 +377,16 @@ mod unit_tests {
     #[test]
     fn test_run_with_timeout_duration_measurement() {
         // Test that duration is measured correctly
-        let result = run("echo fast", &bash_interpreter(), 30);
+        let result = run(
+            "echo fast",
+            &bash_interpreter(),
+            &HashMap::new(),
+            None,
+            None,
+            None,
+            30,
+            &KillPolicy::default(),
+        );
 
         match result {
             Ok(runner_result) => {
@@ -279,7 +406,16 @@ mod unit_tests {
     #[cfg(unix)]
     fn test_run_with_timeout_exit_code_nonzero() {
         // Test non-zero exit code handling
-        let result = run("exit 42", &bash_interpreter(), 30);
+        let result = run(
+            "exit 42",
+            &bash_interpreter(),
+            &HashMap::new(),
+            None,
+            None,
+            None,
+            30,
+            &KillPolicy::default(),
+        );
 
         match result {
             Ok(runner_result) => {
@@ -291,6 +427,80 @@ mod unit_tests {
         }
     }
 
+    #[test]
+    #[cfg(unix)]
+    fn test_run_with_env_vars() {
+        let mut env = HashMap::new();
+        env.insert("ATENTO_TEST_VAR".to_string(), "from env".to_string());
+
+        let result = run(
+            "echo \"$ATENTO_TEST_VAR\"",
+            &bash_interpreter(),
+            &env,
+            None,
+            None,
+            None,
+            30,
+            &KillPolicy::default(),
+        );
+
+        match result {
+            Ok(runner_result) => {
+                assert_eq!(runner_result.stdout.as_deref(), Some("from env"));
+            }
+            Err(e) => {
+                panic!("Should succeed: {e:?}");
+            }
+        }
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_run_with_workdir() {
+        let result = run(
+            "pwd",
+            &bash_interpreter(),
+            &HashMap::new(),
+            Some("/"),
+            None,
+            None,
+            30,
+            &KillPolicy::default(),
+        );
+
+        match result {
+            Ok(runner_result) => {
+                assert_eq!(runner_result.stdout.as_deref(), Some("/"));
+            }
+            Err(e) => {
+                panic!("Should succeed: {e:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_run_with_missing_workdir() {
+        let result = run(
+            "echo test",
+            &bash_interpreter(),
+            &HashMap::new(),
+            Some("/definitely/not/a/real/dir"),
+            None,
+            None,
+            30,
+            &KillPolicy::default(),
+        );
+
+        match result {
+            Err(AtentoError::Runner(msg)) => {
+                assert!(msg.contains("Invalid workdir"));
+                assert!(msg.contains("/definitely/not/a/real/dir"));
+            }
+            Err(e) => panic!("Expected Runner error, got {e:?}"),
+            Ok(_) => panic!("Expected Runner error, got success"),
+        }
+    }
+
     #[test]
     fn test_run_with_powershell_telemetry_env() {
         // Test that PowerShell telemetry opt-out is set
@@ -301,7 +511,16 @@ if ($env:POWERSHELL_TELEMETRY_OPTOUT -eq "1") {
     Write-Output "TELEMETRY_ENABLED"
 }
 "#;
-        let result = run(script, &pwsh_interpreter(), 30);
+        let result = run(
+            script,
+            &pwsh_interpreter(),
+            &HashMap::new(),
+            None,
+            None,
+            None,
+            30,
+            &KillPolicy::default(),
+        );
 
         match result {
             Ok(runner_result) => {
@@ -326,7 +545,16 @@ if ($env:POWERSHELL_TELEMETRY_OPTOUT -eq "1") {
     #[cfg(unix)]
     fn test_run_empty_stdout() {
         // Test handling of empty stdout (lines 150-152)
-        let result = run("true", &bash_interpreter(), 30);
+        let result = run(
+            "true",
+            &bash_interpreter(),
+            &HashMap::new(),
+            None,
+            None,
+            None,
+            30,
+            &KillPolicy::default(),
+        );
 
         match result {
             Ok(runner_result) => {
@@ -336,8 +564,7 @@ if ($env:POWERSHELL_TELEMETRY_OPTOUT -eq "1") {
                         || runner_result
                             .stdout
                             .as_ref()
-                            .map(|s| s.trim().is_empty())
-                            .unwrap_or(false)
+                            .is_some_and(|s| s.trim().is_empty())
                 );
             }
             Err(AtentoError::Runner(_)) => {}
@@ -352,7 +579,12 @@ if ($env:POWERSHELL_TELEMETRY_OPTOUT -eq "1") {
     fn test_run_empty_stdout() {
         // Test handling of empty stdout (lines 150-152)
         // Windows batch: @echo off suppresses command echo, then just exit
-        let result = run("@echo off\nexit /b 0", &batch_interpreter(), 30);
+        let result = run(
+            "@echo off\nexit /b 0",
+            &batch_interpreter(),
+            &HashMap::new(),
+            30,
+        );
 
         match result {
             Ok(runner_result) => {
@@ -362,8 +594,7 @@ if ($env:POWERSHELL_TELEMETRY_OPTOUT -eq "1") {
                         || runner_result
                             .stdout
                             .as_ref()
-                            .map(|s| s.trim().is_empty())
-                            .unwrap_or(false)
+                            .is_some_and(|s| s.trim().is_empty())
                 );
             }
             Err(AtentoError::Runner(_)) => {}
@@ -372,4 +603,800 @@ if ($env:POWERSHELL_TELEMETRY_OPTOUT -eq "1") {
             }
         }
     }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_run_streaming_delivers_lines_before_exit() {
+        use std::time::Instant;
+
+        let start = Instant::now();
+        let mut first_line_at = None;
+        let mut on_line = |stream: OutputStream, line: &str| {
+            if stream == OutputStream::Stdout && line == "started" {
+                first_line_at = Some(start.elapsed());
+            }
+        };
+
+        let result = run_streaming(
+            "echo started\nsleep 1\necho done",
+            &bash_interpreter(),
+            &HashMap::new(),
+            None,
+            None,
+            None,
+            30,
+            &KillPolicy::default(),
+            &mut on_line,
+        );
+
+        match result {
+            Ok(runner_result) => {
+                assert_eq!(runner_result.exit_code, 0);
+                assert_eq!(runner_result.stdout.as_deref(), Some("started\ndone"));
+                match first_line_at {
+                    Some(elapsed) => assert!(elapsed.as_millis() < 900),
+                    None => panic!("The first line should have been streamed"),
+                }
+            }
+            Err(e) => panic!("Should succeed: {e:?}"),
+        }
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_run_streaming_timeout() {
+        let result = run_streaming(
+            "echo waiting\nsleep 5",
+            &bash_interpreter(),
+            &HashMap::new(),
+            None,
+            None,
+            None,
+            1,
+            &KillPolicy::default(),
+            &mut |_, _| {},
+        );
+
+        match result {
+            Ok(runner_result) => {
+                assert!(runner_result.timed_out);
+                assert_eq!(runner_result.exit_code, TIMEOUT_EXIT_CODE);
+                assert_eq!(runner_result.stdout.as_deref(), Some("waiting"));
+                assert!(runner_result.combined.is_none());
+            }
+            Err(e) => panic!("Timeout should return partial output: {e:?}"),
+        }
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_run_timeout_returns_partial_output() {
+        use std::time::Instant;
+
+        let start = Instant::now();
+        let result = run(
+            "echo \"before hang\"\necho oops >&2\nsleep 10\necho after",
+            &bash_interpreter(),
+            &HashMap::new(),
+            None,
+            None,
+            None,
+            1,
+            &KillPolicy::default(),
+        );
+
+        match result {
+            Ok(runner_result) => {
+                assert!(start.elapsed().as_secs() < 5);
+                assert!(runner_result.timed_out);
+                assert_eq!(runner_result.exit_code, TIMEOUT_EXIT_CODE);
+                assert_eq!(runner_result.stdout.as_deref(), Some("before hang"));
+                assert_eq!(runner_result.stderr.as_deref(), Some("oops"));
+            }
+            Err(e) => panic!("Timeout should return partial output: {e:?}"),
+        }
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_run_combined_keeps_write_order() {
+        let result = run_combined(
+            "echo first\nsleep 0.2\necho second >&2\nsleep 0.2\necho third",
+            &bash_interpreter(),
+            &HashMap::new(),
+            None,
+            None,
+            None,
+            30,
+            &KillPolicy::default(),
+            &mut |_, _| {},
+        );
+
+        match result {
+            Ok(runner_result) => {
+                assert!(!runner_result.timed_out);
+                assert_eq!(
+                    runner_result.combined.as_deref(),
+                    Some("first\nsecond\nthird")
+                );
+                assert_eq!(runner_result.stdout.as_deref(), Some("first\nthird"));
+                assert_eq!(runner_result.stderr.as_deref(), Some("second"));
+            }
+            Err(e) => panic!("Should succeed: {e:?}"),
+        }
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_run_combined_timeout_keeps_partial_output() {
+        let result = run_combined(
+            "echo before\necho warn >&2\nsleep 5\necho after",
+            &bash_interpreter(),
+            &HashMap::new(),
+            None,
+            None,
+            None,
+            1,
+            &KillPolicy::default(),
+            &mut |_, _| {},
+        );
+
+        match result {
+            Ok(runner_result) => {
+                assert!(runner_result.timed_out);
+                assert_eq!(runner_result.stdout.as_deref(), Some("before"));
+                match runner_result.combined.as_deref() {
+                    Some(combined) => {
+                        assert!(combined.contains("before"));
+                        assert!(combined.contains("warn"));
+                        assert!(!combined.contains("after"));
+                    }
+                    None => panic!("Partial combined output should be returned"),
+                }
+            }
+            Err(e) => panic!("Timeout should return partial output: {e:?}"),
+        }
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_timeout_signal_lets_the_script_exit_within_the_grace_period() {
+        let result = run(
+            "trap 'echo cleaning up; exit 0' INT\necho started\nwhile true; do sleep 0.1; done",
+            &bash_interpreter(),
+            &HashMap::new(),
+            None,
+            None,
+            None,
+            1,
+            &KillPolicy {
+                signal: TimeoutSignal::Int,
+                grace_ms: 5000,
+                ..KillPolicy::default()
+            },
+        )
+        .unwrap();
+
+        assert!(result.timed_out);
+        assert!(!result.force_killed);
+        assert_eq!(result.exit_code, TIMEOUT_EXIT_CODE);
+        assert_eq!(result.stdout.as_deref(), Some("started\ncleaning up"));
+        assert!(result.duration_ms < 5000, "took {}ms", result.duration_ms);
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_timeout_force_kills_after_the_grace_period() {
+        let result = run(
+            "trap '' TERM\necho started\nsleep 30",
+            &bash_interpreter(),
+            &HashMap::new(),
+            None,
+            None,
+            None,
+            1,
+            &KillPolicy {
+                signal: TimeoutSignal::Term,
+                grace_ms: 300,
+                ..KillPolicy::default()
+            },
+        )
+        .unwrap();
+
+        assert!(result.timed_out);
+        assert!(result.force_killed);
+        assert_eq!(result.stdout.as_deref(), Some("started"));
+        assert!(result.duration_ms < 10_000, "took {}ms", result.duration_ms);
+    }
+
+    #[test]
+    fn test_container_command_args() {
+        let interpreter = Interpreter {
+            command: "python3".to_string(),
+            args: vec!["-u".to_string()],
+            extension: ".py".to_string(),
+            fallbacks: vec![],
+        };
+        let env = HashMap::from([
+            ("TOKEN".to_string(), "s3cret".to_string()),
+            ("API_URL".to_string(), "http://api".to_string()),
+        ]);
+
+        let cmd = crate::runner::container_command(
+            "atento_1",
+            "python:3.12-slim",
+            &interpreter,
+            &env,
+            Some("/srv/app"),
+            std::path::Path::new("/tmp/atento_temp_file_1.py"),
+            false,
+        );
+
+        assert_eq!(cmd.get_program(), "docker");
+        let args: Vec<String> = cmd
+            .get_args()
+            .map(|arg| arg.to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(
+            args,
+            [
+                "run",
+                "--rm",
+                "--name",
+                "atento_1",
+                "-v",
+                "/tmp/atento_temp_file_1.py:/atento/script.py:ro",
+                "-v",
+                "/srv/app:/srv/app",
+                "-w",
+                "/srv/app",
+                "-e",
+                "API_URL",
+                "-e",
+                "TOKEN",
+                "--entrypoint",
+                "python3",
+                "python:3.12-slim",
+                "-u",
+                "/atento/script.py",
+            ]
+        );
+        // Env values are passed through the docker process, not its arguments
+        assert!(!args.iter().any(|arg| arg.contains("s3cret")));
+    }
+
+    #[test]
+    fn test_container_command_keeps_stdin_open_when_interactive() {
+        let cmd = crate::runner::container_command(
+            "atento_2",
+            "alpine",
+            &bash_interpreter(),
+            &HashMap::new(),
+            None,
+            std::path::Path::new("/tmp/atento_temp_file_2.sh"),
+            true,
+        );
+
+        let args: Vec<String> = cmd
+            .get_args()
+            .map(|arg| arg.to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(args[..5], ["run", "--rm", "--name", "atento_2", "-i"]);
+    }
+
+    #[test]
+    fn test_run_in_container_rejects_empty_image() {
+        let result = crate::runner::run_in_container(
+            "echo hi",
+            &bash_interpreter(),
+            " ",
+            &HashMap::new(),
+            None,
+            None,
+            None,
+            5,
+            &KillPolicy::default(),
+        );
+        match result {
+            Err(AtentoError::Runner(msg)) => assert!(msg.contains("image")),
+            Err(other) => panic!("Expected Runner error, got {other:?}"),
+            Ok(_) => panic!("Expected an error for an empty image"),
+        }
+    }
+
+    #[test]
+    fn test_docker_start_error_names_docker_when_it_is_missing() {
+        let missing = std::io::Error::from(std::io::ErrorKind::NotFound);
+        match crate::runner::docker_start_error(&missing) {
+            AtentoError::Runner(msg) => {
+                assert!(msg.contains("'docker' was not found"), "msg: {msg}");
+            }
+            other => panic!("Expected Runner error, got {other:?}"),
+        }
+
+        let denied = std::io::Error::from(std::io::ErrorKind::PermissionDenied);
+        match crate::runner::docker_start_error(&denied) {
+            AtentoError::Runner(msg) => assert!(!msg.contains("docker"), "msg: {msg}"),
+            other => panic!("Expected Runner error, got {other:?}"),
+        }
+    }
+
+    #[cfg(unix)]
+    fn interpreter_with_fallbacks(command: &str, fallbacks: &[&str]) -> Interpreter {
+        Interpreter {
+            command: command.to_string(),
+            args: vec![],
+            extension: ".sh".to_string(),
+            fallbacks: fallbacks.iter().map(ToString::to_string).collect(),
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_falls_back_when_command_is_not_found() {
+        let interpreter = interpreter_with_fallbacks("atento-missing-shell", &["sh"]);
+        match run(
+            "echo fallback",
+            &interpreter,
+            &HashMap::new(),
+            None,
+            None,
+            None,
+            10,
+            &KillPolicy::default(),
+        ) {
+            Ok(result) => {
+                assert_eq!(result.exit_code, 0);
+                assert_eq!(result.stdout.as_deref(), Some("fallback"));
+                assert_eq!(result.resolved_command.as_deref(), Some("sh"));
+            }
+            Err(e) => panic!("Expected the fallback to run, got {e:?}"),
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_prefers_first_available_command() {
+        let interpreter = interpreter_with_fallbacks("sh", &["bash"]);
+        match run(
+            "echo first",
+            &interpreter,
+            &HashMap::new(),
+            None,
+            None,
+            None,
+            10,
+            &KillPolicy::default(),
+        ) {
+            Ok(result) => assert_eq!(result.resolved_command.as_deref(), Some("sh")),
+            Err(e) => panic!("Expected sh to run, got {e:?}"),
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_passes_fallback_arguments_before_script() {
+        let interpreter = interpreter_with_fallbacks("atento-missing-shell", &["sh -e"]);
+        match run(
+            "false\necho unreachable",
+            &interpreter,
+            &HashMap::new(),
+            None,
+            None,
+            None,
+            10,
+            &KillPolicy::default(),
+        ) {
+            Ok(result) => {
+                assert_ne!(result.exit_code, 0);
+                assert_eq!(result.stdout, None);
+                assert_eq!(result.resolved_command.as_deref(), Some("sh -e"));
+            }
+            Err(e) => panic!("Expected the fallback to run, got {e:?}"),
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_does_not_fall_back_on_other_spawn_errors() {
+        // A directory exists but cannot be executed
+        let dir = std::env::temp_dir();
+        let interpreter = interpreter_with_fallbacks(&dir.display().to_string(), &["sh"]);
+        match run(
+            "echo hi",
+            &interpreter,
+            &HashMap::new(),
+            None,
+            None,
+            None,
+            10,
+            &KillPolicy::default(),
+        ) {
+            Err(AtentoError::Runner(msg)) => {
+                assert!(msg.starts_with("Failed to start command"), "{msg}");
+                assert!(!msg.contains("none of"), "{msg}");
+            }
+            Err(other) => panic!("Expected a Runner error, got {other:?}"),
+            Ok(_) => panic!("Expected the command to fail to start"),
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_reports_every_missing_fallback() {
+        let interpreter = interpreter_with_fallbacks(
+            "atento-missing-a",
+            &["atento-missing-b", "atento-missing-c -x"],
+        );
+        match run("echo hi", &interpreter, &HashMap::new(), None, None, None, 10, &KillPolicy::default()) {
+            Err(AtentoError::Runner(msg)) => assert!(
+                msg.contains("none of 'atento-missing-a', 'atento-missing-b', 'atento-missing-c -x' was found"),
+                "{msg}"
+            ),
+            Err(other) => panic!("Expected a Runner error, got {other:?}"),
+            Ok(_) => panic!("Expected the command to fail to start"),
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_pipes_stdin_to_the_script() {
+        match run(
+            "tr a-z A-Z",
+            &bash_interpreter(),
+            &HashMap::new(),
+            None,
+            Some("hello\nworld\n"),
+            None,
+            10,
+            &KillPolicy::default(),
+        ) {
+            Ok(result) => {
+                assert_eq!(result.exit_code, 0);
+                assert_eq!(result.stdout.as_deref(), Some("HELLO\nWORLD"));
+            }
+            Err(e) => panic!("Should succeed: {e:?}"),
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_large_stdin_does_not_deadlock() {
+        // Far larger than a pipe buffer, echoed back while it is still being written
+        let input = "x".repeat(4 * 1024 * 1024);
+        match run(
+            "cat",
+            &bash_interpreter(),
+            &HashMap::new(),
+            None,
+            Some(&input),
+            None,
+            30,
+            &KillPolicy::default(),
+        ) {
+            Ok(result) => {
+                assert!(!result.timed_out);
+                assert_eq!(result.stdout.map(|out| out.len()), Some(input.len()));
+            }
+            Err(e) => panic!("Should succeed: {e:?}"),
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_keeps_reading_after_invalid_utf8() {
+        // Far more output after the bad byte than a pipe buffer holds
+        match run(
+            r"printf 'bad \xff byte\n'; head -c 200000 /dev/zero | tr '\0' a; echo; echo after",
+            &bash_interpreter(),
+            &HashMap::new(),
+            None,
+            None,
+            None,
+            30,
+            &KillPolicy::default(),
+        ) {
+            Ok(result) => {
+                assert_eq!(result.exit_code, 0);
+                let stdout = result.stdout.unwrap_or_default();
+                assert!(
+                    stdout.starts_with("bad \u{fffd} byte\n"),
+                    "stdout: {stdout:.40}"
+                );
+                assert!(stdout.ends_with("\nafter"));
+                assert_eq!(stdout.matches('a').count(), 200_002);
+            }
+            Err(e) => panic!("Should succeed: {e:?}"),
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_ignores_stdin_the_script_does_not_read() {
+        let input = "y".repeat(1024 * 1024);
+        match run(
+            "echo skipped",
+            &bash_interpreter(),
+            &HashMap::new(),
+            None,
+            Some(&input),
+            None,
+            10,
+            &KillPolicy::default(),
+        ) {
+            Ok(result) => {
+                assert_eq!(result.exit_code, 0);
+                assert_eq!(result.stdout.as_deref(), Some("skipped"));
+            }
+            Err(e) => panic!("Should succeed: {e:?}"),
+        }
+    }
+
+    fn temp_dir() -> tempfile::TempDir {
+        match tempfile::tempdir() {
+            Ok(dir) => dir,
+            Err(e) => panic!("Failed to create temp dir: {e}"),
+        }
+    }
+
+    fn script_file(dir: &std::path::Path, script: &str) -> crate::runner::TempScript {
+        match crate::runner::create_script_file(dir, script, ".sh") {
+            Ok(temp_script) => temp_script,
+            Err(e) => panic!("Should create the script file: {e:?}"),
+        }
+    }
+
+    fn file_count(dir: &std::path::Path) -> usize {
+        std::fs::read_dir(dir).map_or(0, Iterator::count)
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_temp_script_is_created_owner_only() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = temp_dir();
+        let temp_script = script_file(dir.path(), "echo secret");
+        let mode = std::fs::metadata(temp_script.path()).map(|m| m.permissions().mode());
+        assert_eq!(mode.ok().map(|mode| mode & 0o777), Some(0o600));
+        assert_eq!(
+            std::fs::read_to_string(temp_script.path()).ok().as_deref(),
+            Some("echo secret\n")
+        );
+    }
+
+    #[test]
+    fn test_temp_script_is_removed_on_panic() {
+        let dir = temp_dir();
+        let result = std::panic::catch_unwind(|| {
+            let temp_script = script_file(dir.path(), "echo hi");
+            assert!(temp_script.path().exists());
+            panic!("executor panicked");
+        });
+        assert!(result.is_err());
+        assert_eq!(file_count(dir.path()), 0);
+    }
+
+    #[test]
+    fn test_temp_script_names_are_unique() {
+        let dir = temp_dir();
+        let scripts: Vec<_> = (0..50).map(|_| script_file(dir.path(), "echo")).collect();
+        assert_eq!(file_count(dir.path()), scripts.len());
+        drop(scripts);
+        assert_eq!(file_count(dir.path()), 0);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_temp_script_is_removed_after_run() {
+        match run(
+            "echo \"$0\"",
+            &bash_interpreter(),
+            &HashMap::new(),
+            None,
+            None,
+            None,
+            10,
+            &KillPolicy::default(),
+        ) {
+            Ok(result) => {
+                let path = result.stdout.unwrap_or_default();
+                assert!(path.contains("atento_temp_file_"), "path: {path}");
+                assert!(!std::path::Path::new(&path).exists());
+            }
+            Err(e) => panic!("Should succeed: {e:?}"),
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_temp_script_is_removed_after_timeout() {
+        match run(
+            "echo \"$0\"\nsleep 10",
+            &bash_interpreter(),
+            &HashMap::new(),
+            None,
+            None,
+            None,
+            1,
+            &KillPolicy::default(),
+        ) {
+            Ok(result) => {
+                assert!(result.timed_out);
+                let path = result.stdout.unwrap_or_default();
+                assert!(path.contains("atento_temp_file_"), "path: {path}");
+                assert!(!std::path::Path::new(&path).exists());
+            }
+            Err(e) => panic!("Should time out, not fail: {e:?}"),
+        }
+    }
+
+    #[test]
+    fn test_script_dir_uses_configured_directory() {
+        let dir = temp_dir();
+        let configured = crate::runner::script_dir(Some(dir.path().into()));
+        assert_eq!(configured.ok().as_deref(), Some(dir.path()));
+
+        let system = Some(std::env::temp_dir());
+        assert_eq!(crate::runner::script_dir(None).ok(), system);
+        assert_eq!(crate::runner::script_dir(Some("".into())).ok(), system);
+
+        match crate::runner::script_dir(Some(dir.path().join("missing").into())) {
+            Err(AtentoError::Runner(msg)) => {
+                assert!(msg.contains("Invalid ATENTO_TMPDIR"), "{msg}");
+            }
+            other => panic!("Expected Runner error, got {other:?}"),
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_keeps_head_of_output_past_limit() {
+        match run(
+            "for i in $(seq 1 100000); do echo \"line $i\"; done\necho small >&2",
+            &bash_interpreter(),
+            &HashMap::new(),
+            None,
+            None,
+            Some(1000),
+            30,
+            &KillPolicy::default(),
+        ) {
+            Ok(result) => {
+                assert_eq!(result.exit_code, 0);
+                assert!(!result.timed_out);
+                assert!(result.stdout_truncated);
+                assert!(!result.stderr_truncated);
+                let stdout = result.stdout.unwrap_or_default();
+                let (kept, marker) = stdout.rsplit_once('\n').unwrap_or_default();
+                assert!(kept.len() <= 1000, "kept {} bytes", kept.len());
+                assert!(kept.starts_with("line 1\nline 2\n"));
+                assert!(marker.starts_with("...[truncated "), "{marker}");
+                assert_eq!(result.stderr.as_deref(), Some("small"));
+            }
+            Err(e) => panic!("Should succeed: {e:?}"),
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_output_limit_cuts_on_char_boundary() {
+        match run(
+            "printf 'aé%.0s' $(seq 1 10)",
+            &bash_interpreter(),
+            &HashMap::new(),
+            None,
+            None,
+            Some(4),
+            10,
+            &KillPolicy::default(),
+        ) {
+            Ok(result) => {
+                assert!(result.stdout_truncated);
+                assert_eq!(
+                    result.stdout.as_deref(),
+                    Some("aéa\n...[truncated 27 bytes]")
+                );
+            }
+            Err(e) => panic!("Should succeed: {e:?}"),
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_without_limit_keeps_all_output() {
+        match run(
+            "for i in $(seq 1 2000); do echo \"line $i\"; done",
+            &bash_interpreter(),
+            &HashMap::new(),
+            None,
+            None,
+            None,
+            30,
+            &KillPolicy::default(),
+        ) {
+            Ok(result) => {
+                assert!(!result.stdout_truncated);
+                assert_eq!(result.stdout.map(|out| out.lines().count()), Some(2000));
+            }
+            Err(e) => panic!("Should succeed: {e:?}"),
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_marks_truncated_output_with_dropped_bytes() {
+        match run(
+            "for i in 1 2 3 4 5; do echo abcd; echo err >&2; done",
+            &bash_interpreter(),
+            &HashMap::new(),
+            None,
+            None,
+            Some(12),
+            10,
+            &KillPolicy::default(),
+        ) {
+            Ok(result) => {
+                assert!(result.stdout_truncated);
+                assert_eq!(
+                    result.stdout.as_deref(),
+                    Some("abcd\nabcd\nab\n...[truncated 13 bytes]")
+                );
+                assert!(result.stderr_truncated);
+                assert_eq!(
+                    result.stderr.as_deref(),
+                    Some("err\nerr\nerr\n...[truncated 8 bytes]")
+                );
+            }
+            Err(e) => panic!("Should succeed: {e:?}"),
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_output_limit_applies_within_one_long_line() {
+        match run(
+            "head -c 50000000 /dev/zero | tr '\\0' a",
+            &bash_interpreter(),
+            &HashMap::new(),
+            None,
+            None,
+            Some(1000),
+            30,
+            &KillPolicy::default(),
+        ) {
+            Ok(result) => {
+                assert_eq!(result.exit_code, 0);
+                assert!(result.stdout_truncated);
+                let stdout = result.stdout.unwrap_or_default();
+                let (kept, marker) = stdout.split_once('\n').unwrap_or_default();
+                assert_eq!(kept, "a".repeat(1000));
+                assert!(marker.starts_with("...[truncated 4999"), "{marker}");
+            }
+            Err(e) => panic!("Should succeed: {e:?}"),
+        }
+    }
+
+    #[test]
+    fn test_line_splitter_keeps_limit_and_counts_the_rest() {
+        use crate::runner::{LineSplitter, Piped};
+
+        let mut lines = LineSplitter::new(Some(9));
+        let chunks: [&[u8]; 4] = [b"ab\r\ncd", b"ef\xff", b"ghij\n\nxy", b"z"];
+        let mut piped: Vec<Piped> = chunks.iter().flat_map(|chunk| lines.feed(chunk)).collect();
+        piped.extend(lines.finish());
+
+        let mut kept = Vec::new();
+        let mut dropped = 0;
+        for piped in piped {
+            match piped {
+                Piped::Line(line) => kept.push(line),
+                Piped::Dropped(bytes) => dropped += bytes,
+            }
+        }
+
+        // "ab\n" and "cdef\u{fffd}" fit, the rest of that line and "\nxyz" do not
+        assert_eq!(kept, ["ab", "cdef\u{fffd}"]);
+        assert_eq!(dropped, 4 + 1 + 3);
+    }
 }
@@ -0,0 +1,220 @@
+#[cfg(all(test, feature = "schema"))]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use crate::fields::{CHAIN_FIELDS, OUTPUT_FIELDS, STEP_FIELDS};
+    use crate::interpreter::default_interpreters;
+    use crate::schema::chain_schema;
+    use serde_json::{Value, json};
+    use std::collections::BTreeSet;
+
+    /// Checks `value` against `schema`, following `$ref`s into `root`, and
+    /// returns a message for each violation. Supports the keywords the chain
+    /// schema uses.
+    fn violations(root: &Value, schema: &Value, value: &Value, path: &str) -> Vec<String> {
+        let Some(schema) = schema.as_object() else {
+            return if schema == &Value::Bool(false) {
+                vec![format!("{path}: not allowed")]
+            } else {
+                Vec::new()
+            };
+        };
+        let mut found = Vec::new();
+
+        if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+            let name = reference.trim_start_matches("#/$defs/");
+            found.extend(violations(root, &root["$defs"][name], value, path));
+        }
+        if let Some(types) = schema.get("type") {
+            let types: Vec<&str> = match types {
+                Value::Array(types) => types.iter().filter_map(Value::as_str).collect(),
+                other => other.as_str().into_iter().collect(),
+            };
+            let matches = |kind: &str| match kind {
+                "object" => value.is_object(),
+                "array" => value.is_array(),
+                "string" => value.is_string(),
+                "boolean" => value.is_boolean(),
+                "integer" => value.is_i64() || value.is_u64(),
+                "number" => value.is_number(),
+                "null" => value.is_null(),
+                _ => false,
+            };
+            if !types.iter().any(|kind| matches(kind)) {
+                found.push(format!("{path}: expected {types:?}, got {value}"));
+            }
+        }
+        if let Some(constant) = schema.get("const")
+            && constant != value
+        {
+            found.push(format!("{path}: expected {constant}, got {value}"));
+        }
+        if let Some(allowed) = schema.get("enum").and_then(Value::as_array)
+            && !allowed.contains(value)
+        {
+            found.push(format!("{path}: {value} is not one of {allowed:?}"));
+        }
+        for (keyword, needed) in [("anyOf", 1..=usize::MAX), ("oneOf", 1..=1)] {
+            if let Some(options) = schema.get(keyword).and_then(Value::as_array) {
+                let passing = options
+                    .iter()
+                    .filter(|option| violations(root, option, value, path).is_empty())
+                    .count();
+                if !needed.contains(&passing) {
+                    found.push(format!("{path}: {passing} of the {keyword} options match"));
+                }
+            }
+        }
+        if let Some(object) = value.as_object() {
+            let properties = schema.get("properties").and_then(Value::as_object);
+            for required in schema
+                .get("required")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .filter_map(Value::as_str)
+            {
+                if !object.contains_key(required) {
+                    found.push(format!("{path}: missing '{required}'"));
+                }
+            }
+            for (key, item) in object {
+                let item_path = format!("{path}.{key}");
+                match properties.and_then(|properties| properties.get(key)) {
+                    Some(property) => found.extend(violations(root, property, item, &item_path)),
+                    None => {
+                        if let Some(additional) = schema.get("additionalProperties") {
+                            found.extend(violations(root, additional, item, &item_path));
+                        }
+                    }
+                }
+            }
+        }
+        if let (Some(items), Some(array)) = (schema.get("items"), value.as_array()) {
+            for (index, item) in array.iter().enumerate() {
+                found.extend(violations(root, items, item, &format!("{path}[{index}]")));
+            }
+        }
+        found
+    }
+
+    fn check_yaml(yaml: &str) -> Vec<String> {
+        let schema = chain_schema();
+        let document: Value = serde_yaml::from_str(yaml).unwrap();
+        violations(&schema, &schema, &document, "$")
+    }
+
+    /// The first YAML block of the crate documentation, which is a whole chain.
+    fn doc_example_chain() -> String {
+        include_str!("../lib.rs")
+            .lines()
+            .filter_map(|line| line.strip_prefix("//!"))
+            .map(|line| line.strip_prefix(' ').unwrap_or(line))
+            .skip_while(|line| *line != "```yaml")
+            .skip(1)
+            .take_while(|line| *line != "```")
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn property_names(schema: &Value) -> BTreeSet<&str> {
+        schema["properties"]
+            .as_object()
+            .unwrap()
+            .keys()
+            .map(String::as_str)
+            .collect()
+    }
+
+    #[test]
+    fn test_doc_example_chain_matches_the_schema() {
+        let yaml = doc_example_chain();
+        assert!(yaml.contains("steps:"), "{yaml}");
+
+        assert_eq!(check_yaml(&yaml), Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_qa_chains_match_the_schema() {
+        for platform in ["unix", "windows", "cross-platform"] {
+            for entry in std::fs::read_dir(format!("tests/chains/{platform}")).unwrap() {
+                let path = entry.unwrap().path();
+                if path.extension().is_some_and(|ext| ext == "yaml") {
+                    let yaml = std::fs::read_to_string(&path).unwrap();
+                    assert_eq!(
+                        check_yaml(&yaml),
+                        Vec::<String>::new(),
+                        "{}",
+                        path.display()
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_schema_rejects_unknown_and_mistyped_fields() {
+        let found = check_yaml(
+            "steps:\n  s:\n    type: bash\n    script: echo\n    timeout: fast\n    outpts: {}\n",
+        );
+
+        assert_eq!(found.len(), 2, "{found:?}");
+        assert!(found[0].starts_with("$.steps.s.outpts: not allowed"));
+        assert!(found[1].starts_with("$.steps.s.timeout: expected"));
+    }
+
+    #[test]
+    fn test_schema_describes_defaults() {
+        let schema = chain_schema();
+        let step = &schema["$defs"]["Step"]["properties"];
+
+        assert_eq!(schema["title"], "Chain");
+        assert_eq!(schema["properties"]["timeout"]["default"], json!(300));
+        assert_eq!(step["timeout"]["default"], json!(60));
+        assert_eq!(step["success_exit_codes"]["default"], json!([0]));
+        assert_eq!(
+            schema["$defs"]["RetryPolicy"]["properties"]["attempts"]["default"],
+            json!(1)
+        );
+    }
+
+    #[test]
+    fn test_interpreter_keys_list_the_built_in_interpreters() {
+        let schema = chain_schema();
+        let built_in: Vec<Value> = default_interpreters()
+            .into_iter()
+            .map(|(key, _)| Value::String(key))
+            .collect();
+
+        for key_schema in [
+            &schema["$defs"]["Step"]["properties"]["type"],
+            &schema["properties"]["default_interpreter"],
+        ] {
+            assert_eq!(
+                key_schema["anyOf"][0]["enum"],
+                Value::Array(built_in.clone())
+            );
+        }
+        // Custom interpreters are keys too
+        assert!(check_yaml("default_interpreter: ruby\n").is_empty());
+    }
+
+    #[test]
+    fn test_schema_fields_match_the_unknown_field_check() {
+        let schema = chain_schema();
+        let aliases = ["success_codes", "from"];
+        let known = |fields: &[&'static str]| -> BTreeSet<&'static str> {
+            fields
+                .iter()
+                .copied()
+                .filter(|field| !aliases.contains(field))
+                .collect()
+        };
+
+        assert_eq!(property_names(&schema), known(CHAIN_FIELDS));
+        assert_eq!(property_names(&schema["$defs"]["Step"]), known(STEP_FIELDS));
+        assert_eq!(
+            property_names(&schema["$defs"]["Output"]),
+            known(OUTPUT_FIELDS)
+        );
+    }
+}
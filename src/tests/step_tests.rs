@@ -1,12 +1,13 @@
-#[cfg(test)]
+#[cfg(all(test, feature = "exec"))]
 #[allow(clippy::unwrap_used, clippy::expect_used)]
 mod tests {
     use crate::data_type::DataType;
     use crate::errors::AtentoError;
     use crate::input::Input;
     use crate::interpreter::Interpreter;
-    use crate::output::Output;
+    use crate::output::{Output, OutputSource, RegexCache};
     use crate::step::Step;
+    use indexmap::IndexMap;
     use std::collections::HashMap;
 
     // Helper to create a test interpreter
@@ -16,6 +17,7 @@ mod tests {
             command: "bash".to_string(),
             args: vec![],
             extension: ".sh".to_string(),
+            fallbacks: vec![],
         }
     }
 
@@ -32,7 +34,7 @@ script: print("test")
         let step: Step = serde_yaml::from_str(yaml).unwrap();
         assert_eq!(step.name.as_deref(), Some("build"));
         assert_eq!(step.timeout, 120);
-        assert!(step.interpreter == "python");
+        assert!(step.interpreter.as_deref() == Some("python"));
         assert_eq!(step.script, "print(\"test\")");
     }
 
@@ -46,7 +48,7 @@ script: |
         let step: Step = serde_yaml::from_str(yaml).unwrap();
         assert_eq!(step.name, None);
         assert_eq!(step.timeout, 60);
-        assert!(step.interpreter == "bash");
+        assert!(step.interpreter.as_deref() == Some("bash"));
         assert!(step.inputs.is_empty());
         assert!(step.outputs.is_empty());
     }
@@ -58,12 +60,9 @@ script: |
         let mut result = StepResult {
             name: Some("test".to_string()),
             duration_ms: 100,
-            exit_code: 0,
-            inputs: HashMap::new(),
-            outputs: HashMap::new(),
             stdout: Some("output".to_string()),
-            stderr: None,
-            error: None,
+            attempts: 1,
+            ..StepResult::default()
         };
         result
             .outputs
@@ -75,19 +74,68 @@ script: |
         assert!(json.contains("output"));
     }
 
+    #[test]
+    fn test_step_records_text_and_typed_outputs() {
+        use crate::executor::ExecutionResult;
+        use crate::runner::KillPolicy;
+        use crate::tests::mock_executor::MockExecutor;
+
+        let step: Step = serde_yaml::from_str(
+            r"
+type: bash
+script: echo
+outputs:
+  count:
+    pattern: count=(\d+)
+    type: int
+  ok:
+    pattern: ok=(\w+)
+    type: bool
+  tags:
+    pattern: tag=(\w+)
+    multiple: true
+    delimiter: ','
+",
+        )
+        .unwrap();
+        let mut mock = MockExecutor::new();
+        mock.expect_call(
+            "echo",
+            ExecutionResult {
+                stdout: "count=42\nok=true\ntag=a\ntag=b".to_string(),
+                ..ExecutionResult::default()
+            },
+        );
+
+        let result = step.run(
+            &mock,
+            &HashMap::new(),
+            &HashMap::new(),
+            &HashMap::new(),
+            None,
+            60,
+            &KillPolicy::default(),
+            &test_bash_interpreter(),
+        );
+        assert!(result.error.is_none(), "error: {:?}", result.error);
+
+        let json = serde_json::to_value(&result).unwrap();
+        assert_eq!(json["outputs"]["count"], serde_json::json!("42"));
+        assert_eq!(json["outputs"]["ok"], serde_json::json!("true"));
+        assert_eq!(json["outputs"]["tags"], serde_json::json!("a,b"));
+        assert_eq!(json["typed_outputs"]["count"], serde_json::json!(42));
+        assert_eq!(json["typed_outputs"]["ok"], serde_json::json!(true));
+        assert_eq!(json["typed_outputs"]["tags"], serde_json::json!(["a", "b"]));
+    }
+
     #[test]
     fn test_step_result_skip_empty_maps() {
         use crate::step::StepResult;
 
         let result = StepResult {
-            name: None,
             duration_ms: 50,
-            exit_code: 0,
-            inputs: HashMap::new(),
-            outputs: HashMap::new(),
-            stdout: None,
-            stderr: None,
-            error: None,
+            attempts: 1,
+            ..StepResult::default()
         };
 
         let json = serde_json::to_string(&result).unwrap();
@@ -104,7 +152,7 @@ script: |
         let step = Step::new("python");
         assert_eq!(step.name, None);
         assert_eq!(step.timeout, 60); // default_step_timeout
-        assert_eq!(step.interpreter, "python");
+        assert_eq!(step.interpreter.as_deref(), Some("python"));
         assert_eq!(step.script, "");
         assert!(step.inputs.is_empty());
         assert!(step.outputs.is_empty());
@@ -113,15 +161,39 @@ script: |
     #[test]
     fn test_step_validate_empty_script() {
         let step = Step {
-            interpreter: "bash".to_string(),
+            interpreter: Some("bash".to_string()),
             script: String::new(),
             ..Step {
                 name: None,
                 timeout: 60,
                 inputs: HashMap::new(),
-                interpreter: "bash".to_string(),
+                interpreter: Some("bash".to_string()),
                 script: String::new(),
                 outputs: HashMap::new(),
+                env: HashMap::new(),
+                retry: None,
+                continue_on_error: false,
+                retries: 0,
+                retry_delay_ms: 0,
+                success_exit_codes: vec![0],
+                allow_nonzero_exit: false,
+                workdir: None,
+                foreach: None,
+                capture: crate::step::Capture::Separate,
+                image: None,
+                interpreter_args: None,
+                stdin: None,
+                on_failure: None,
+                artifacts: HashMap::new(),
+                max_output_bytes: None,
+                quote: crate::step::Quote::None,
+                override_: false,
+                template: None,
+                script_file: None,
+                matrix: IndexMap::new(),
+                needs: Vec::new(),
+                timeout_signal: None,
+                timeout_grace_ms: None,
             }
         };
         let result = step.validate("test_id");
@@ -131,15 +203,39 @@ script: |
     #[test]
     fn test_step_validate_undeclared_input() {
         let step = Step {
-            interpreter: "bash".to_string(),
+            interpreter: Some("bash".to_string()),
             script: "echo {{ inputs.foo }}".to_string(),
             ..Step {
                 name: None,
                 timeout: 60,
                 inputs: HashMap::new(),
-                interpreter: "bash".to_string(),
+                interpreter: Some("bash".to_string()),
                 script: String::new(),
                 outputs: HashMap::new(),
+                env: HashMap::new(),
+                retry: None,
+                continue_on_error: false,
+                retries: 0,
+                retry_delay_ms: 0,
+                success_exit_codes: vec![0],
+                allow_nonzero_exit: false,
+                workdir: None,
+                foreach: None,
+                capture: crate::step::Capture::Separate,
+                image: None,
+                interpreter_args: None,
+                stdin: None,
+                on_failure: None,
+                artifacts: HashMap::new(),
+                max_output_bytes: None,
+                quote: crate::step::Quote::None,
+                override_: false,
+                template: None,
+                script_file: None,
+                matrix: IndexMap::new(),
+                needs: Vec::new(),
+                timeout_signal: None,
+                timeout_grace_ms: None,
             }
         };
         let result = step.validate("test_id");
@@ -153,14 +249,38 @@ script: |
     #[test]
     fn test_step_validate_unused_input() {
         let mut step = Step {
-            interpreter: "bash".to_string(),
+            interpreter: Some("bash".to_string()),
             ..Step {
                 name: None,
                 timeout: 60,
                 inputs: HashMap::new(),
-                interpreter: "bash".to_string(),
+                interpreter: Some("bash".to_string()),
                 script: String::new(),
                 outputs: HashMap::new(),
+                env: HashMap::new(),
+                retry: None,
+                continue_on_error: false,
+                retries: 0,
+                retry_delay_ms: 0,
+                success_exit_codes: vec![0],
+                allow_nonzero_exit: false,
+                workdir: None,
+                foreach: None,
+                capture: crate::step::Capture::Separate,
+                image: None,
+                interpreter_args: None,
+                stdin: None,
+                on_failure: None,
+                artifacts: HashMap::new(),
+                max_output_bytes: None,
+                quote: crate::step::Quote::None,
+                override_: false,
+                template: None,
+                script_file: None,
+                matrix: IndexMap::new(),
+                needs: Vec::new(),
+                timeout_signal: None,
+                timeout_grace_ms: None,
             }
         };
         step.script = "echo hello".to_string();
@@ -169,6 +289,7 @@ script: |
             Input::Inline {
                 type_: DataType::String,
                 value: serde_yaml::Value::String("value".to_string()),
+                separator: None,
             },
         );
         let result = step.validate("test_id");
@@ -181,14 +302,38 @@ script: |
     #[test]
     fn test_step_validate_valid_input() {
         let mut step = Step {
-            interpreter: "bash".to_string(),
+            interpreter: Some("bash".to_string()),
             ..Step {
                 name: None,
                 timeout: 60,
                 inputs: HashMap::new(),
-                interpreter: "bash".to_string(),
+                interpreter: Some("bash".to_string()),
                 script: String::new(),
                 outputs: HashMap::new(),
+                env: HashMap::new(),
+                retry: None,
+                continue_on_error: false,
+                retries: 0,
+                retry_delay_ms: 0,
+                success_exit_codes: vec![0],
+                allow_nonzero_exit: false,
+                workdir: None,
+                foreach: None,
+                capture: crate::step::Capture::Separate,
+                image: None,
+                interpreter_args: None,
+                stdin: None,
+                on_failure: None,
+                artifacts: HashMap::new(),
+                max_output_bytes: None,
+                quote: crate::step::Quote::None,
+                override_: false,
+                template: None,
+                script_file: None,
+                matrix: IndexMap::new(),
+                needs: Vec::new(),
+                timeout_signal: None,
+                timeout_grace_ms: None,
             }
         };
         step.script = "echo {{ inputs.name }}".to_string();
@@ -197,6 +342,7 @@ script: |
             Input::Inline {
                 type_: DataType::String,
                 value: serde_yaml::Value::String("test".to_string()),
+                separator: None,
             },
         );
         let result = step.validate("test_id");
@@ -206,14 +352,38 @@ script: |
     #[test]
     fn test_step_validate_empty_output_pattern() {
         let mut step = Step {
-            interpreter: "bash".to_string(),
+            interpreter: Some("bash".to_string()),
             ..Step {
                 name: None,
                 timeout: 60,
                 inputs: HashMap::new(),
-                interpreter: "bash".to_string(),
+                interpreter: Some("bash".to_string()),
                 script: String::new(),
                 outputs: HashMap::new(),
+                env: HashMap::new(),
+                retry: None,
+                continue_on_error: false,
+                retries: 0,
+                retry_delay_ms: 0,
+                success_exit_codes: vec![0],
+                allow_nonzero_exit: false,
+                workdir: None,
+                foreach: None,
+                capture: crate::step::Capture::Separate,
+                image: None,
+                interpreter_args: None,
+                stdin: None,
+                on_failure: None,
+                artifacts: HashMap::new(),
+                max_output_bytes: None,
+                quote: crate::step::Quote::None,
+                override_: false,
+                template: None,
+                script_file: None,
+                matrix: IndexMap::new(),
+                needs: Vec::new(),
+                timeout_signal: None,
+                timeout_grace_ms: None,
             }
         };
         step.outputs.insert(
@@ -221,6 +391,15 @@ script: |
             Output {
                 pattern: String::new(),
                 type_: DataType::String,
+                group: None,
+                json_path: None,
+                key: None,
+                source: OutputSource::Stdout,
+                multiple: false,
+                optional: false,
+                delimiter: None,
+                default: None,
+                regex_cache: RegexCache::default(),
             },
         );
         let result = step.validate("test_id");
@@ -233,14 +412,38 @@ script: |
     #[test]
     fn test_step_validate_whitespace_output_pattern() {
         let mut step = Step {
-            interpreter: "bash".to_string(),
+            interpreter: Some("bash".to_string()),
             ..Step {
                 name: None,
                 timeout: 60,
                 inputs: HashMap::new(),
-                interpreter: "bash".to_string(),
+                interpreter: Some("bash".to_string()),
                 script: String::new(),
                 outputs: HashMap::new(),
+                env: HashMap::new(),
+                retry: None,
+                continue_on_error: false,
+                retries: 0,
+                retry_delay_ms: 0,
+                success_exit_codes: vec![0],
+                allow_nonzero_exit: false,
+                workdir: None,
+                foreach: None,
+                capture: crate::step::Capture::Separate,
+                image: None,
+                interpreter_args: None,
+                stdin: None,
+                on_failure: None,
+                artifacts: HashMap::new(),
+                max_output_bytes: None,
+                quote: crate::step::Quote::None,
+                override_: false,
+                template: None,
+                script_file: None,
+                matrix: IndexMap::new(),
+                needs: Vec::new(),
+                timeout_signal: None,
+                timeout_grace_ms: None,
             }
         };
         step.outputs.insert(
@@ -248,6 +451,15 @@ script: |
             Output {
                 pattern: "   ".to_string(),
                 type_: DataType::String,
+                group: None,
+                json_path: None,
+                key: None,
+                source: OutputSource::Stdout,
+                multiple: false,
+                optional: false,
+                delimiter: None,
+                default: None,
+                regex_cache: RegexCache::default(),
             },
         );
         let result = step.validate("test_id");
@@ -260,14 +472,38 @@ script: |
     #[test]
     fn test_step_validate_invalid_regex_pattern() {
         let mut step = Step {
-            interpreter: "bash".to_string(),
+            interpreter: Some("bash".to_string()),
             ..Step {
                 name: None,
                 timeout: 60,
                 inputs: HashMap::new(),
-                interpreter: "bash".to_string(),
+                interpreter: Some("bash".to_string()),
                 script: String::new(),
                 outputs: HashMap::new(),
+                env: HashMap::new(),
+                retry: None,
+                continue_on_error: false,
+                retries: 0,
+                retry_delay_ms: 0,
+                success_exit_codes: vec![0],
+                allow_nonzero_exit: false,
+                workdir: None,
+                foreach: None,
+                capture: crate::step::Capture::Separate,
+                image: None,
+                interpreter_args: None,
+                stdin: None,
+                on_failure: None,
+                artifacts: HashMap::new(),
+                max_output_bytes: None,
+                quote: crate::step::Quote::None,
+                override_: false,
+                template: None,
+                script_file: None,
+                matrix: IndexMap::new(),
+                needs: Vec::new(),
+                timeout_signal: None,
+                timeout_grace_ms: None,
             }
         };
         step.outputs.insert(
@@ -275,6 +511,15 @@ script: |
             Output {
                 pattern: "[invalid".to_string(),
                 type_: DataType::String,
+                group: None,
+                json_path: None,
+                key: None,
+                source: OutputSource::Stdout,
+                multiple: false,
+                optional: false,
+                delimiter: None,
+                default: None,
+                regex_cache: RegexCache::default(),
             },
         );
         let result = step.validate("test_id");
@@ -287,14 +532,38 @@ script: |
     #[test]
     fn test_step_validate_valid_regex_pattern() {
         let mut step = Step {
-            interpreter: "bash".to_string(),
+            interpreter: Some("bash".to_string()),
             ..Step {
                 name: None,
                 timeout: 60,
                 inputs: HashMap::new(),
-                interpreter: "bash".to_string(),
+                interpreter: Some("bash".to_string()),
                 script: String::new(),
                 outputs: HashMap::new(),
+                env: HashMap::new(),
+                retry: None,
+                continue_on_error: false,
+                retries: 0,
+                retry_delay_ms: 0,
+                success_exit_codes: vec![0],
+                allow_nonzero_exit: false,
+                workdir: None,
+                foreach: None,
+                capture: crate::step::Capture::Separate,
+                image: None,
+                interpreter_args: None,
+                stdin: None,
+                on_failure: None,
+                artifacts: HashMap::new(),
+                max_output_bytes: None,
+                quote: crate::step::Quote::None,
+                override_: false,
+                template: None,
+                script_file: None,
+                matrix: IndexMap::new(),
+                needs: Vec::new(),
+                timeout_signal: None,
+                timeout_grace_ms: None,
             }
         };
         step.outputs.insert(
@@ -302,6 +571,15 @@ script: |
             Output {
                 pattern: r"(\d+)".to_string(),
                 type_: DataType::String,
+                group: None,
+                json_path: None,
+                key: None,
+                source: OutputSource::Stdout,
+                multiple: false,
+                optional: false,
+                delimiter: None,
+                default: None,
+                regex_cache: RegexCache::default(),
             },
         );
         let result = step.validate("test_id");
@@ -312,14 +590,38 @@ script: |
     fn test_step_validate_with_step_name() {
         let mut step = Step {
             name: Some("my_step".to_string()),
-            interpreter: "bash".to_string(),
+            interpreter: Some("bash".to_string()),
             ..Step {
                 name: None,
                 timeout: 60,
                 inputs: HashMap::new(),
-                interpreter: "bash".to_string(),
+                interpreter: Some("bash".to_string()),
                 script: String::new(),
                 outputs: HashMap::new(),
+                env: HashMap::new(),
+                retry: None,
+                continue_on_error: false,
+                retries: 0,
+                retry_delay_ms: 0,
+                success_exit_codes: vec![0],
+                allow_nonzero_exit: false,
+                workdir: None,
+                foreach: None,
+                capture: crate::step::Capture::Separate,
+                image: None,
+                interpreter_args: None,
+                stdin: None,
+                on_failure: None,
+                artifacts: HashMap::new(),
+                max_output_bytes: None,
+                quote: crate::step::Quote::None,
+                override_: false,
+                template: None,
+                script_file: None,
+                matrix: IndexMap::new(),
+                needs: Vec::new(),
+                timeout_signal: None,
+                timeout_grace_ms: None,
             }
         };
         step.script = "echo hello".to_string();
@@ -330,15 +632,39 @@ script: |
     #[test]
     fn test_step_validate_without_step_name() {
         let step = Step {
-            interpreter: "bash".to_string(),
+            interpreter: Some("bash".to_string()),
             script: "echo hello".to_string(),
             ..Step {
                 name: None,
                 timeout: 60,
                 inputs: HashMap::new(),
-                interpreter: "bash".to_string(),
+                interpreter: Some("bash".to_string()),
                 script: String::new(),
                 outputs: HashMap::new(),
+                env: HashMap::new(),
+                retry: None,
+                continue_on_error: false,
+                retries: 0,
+                retry_delay_ms: 0,
+                success_exit_codes: vec![0],
+                allow_nonzero_exit: false,
+                workdir: None,
+                foreach: None,
+                capture: crate::step::Capture::Separate,
+                image: None,
+                interpreter_args: None,
+                stdin: None,
+                on_failure: None,
+                artifacts: HashMap::new(),
+                max_output_bytes: None,
+                quote: crate::step::Quote::None,
+                override_: false,
+                template: None,
+                script_file: None,
+                matrix: IndexMap::new(),
+                needs: Vec::new(),
+                timeout_signal: None,
+                timeout_grace_ms: None,
             }
         };
         let result = step.validate("test_id");
@@ -352,7 +678,7 @@ type: python
 script: print("hello")
 "#;
         let step: Step = serde_yaml::from_str(yaml).unwrap();
-        assert!(step.interpreter == "python");
+        assert!(step.interpreter.as_deref() == Some("python"));
         assert_eq!(step.script, "print(\"hello\")");
     }
 
@@ -362,24 +688,64 @@ script: print("hello")
             name: None,
             timeout: 60,
             inputs: HashMap::new(),
-            interpreter: "bash".to_string(),
+            interpreter: Some("bash".to_string()),
             script: String::new(),
             outputs: HashMap::new(),
+            env: HashMap::new(),
+            retry: None,
+            continue_on_error: false,
+            retries: 0,
+            retry_delay_ms: 0,
+            success_exit_codes: vec![0],
+            allow_nonzero_exit: false,
+            workdir: None,
+            foreach: None,
+            capture: crate::step::Capture::Separate,
+            image: None,
+            interpreter_args: None,
+            stdin: None,
+            on_failure: None,
+            artifacts: HashMap::new(),
+            max_output_bytes: None,
+            quote: crate::step::Quote::None,
+            override_: false,
+            template: None,
+            script_file: None,
+            matrix: IndexMap::new(),
+            needs: Vec::new(),
+            timeout_signal: None,
+            timeout_grace_ms: None,
         };
-        assert!(step.interpreter == "bash");
+        assert!(step.interpreter.as_deref() == Some("bash"));
     }
 
     #[test]
     fn test_step_requires_type_field() {
+        // A step may omit `type`, but the chain then needs a `default_interpreter`
         let yaml = r"
 script: echo hello
 ";
-        let result: Result<Step, _> = serde_yaml::from_str(yaml);
-        assert!(result.is_err());
+        let step: Step = serde_yaml::from_str(yaml).unwrap();
+        assert!(step.interpreter.is_none());
+
+        let chain: crate::chain::Chain = r"
+steps:
+  greet:
+    script: echo hello
+"
+        .parse()
+        .unwrap();
+        match chain.validate() {
+            Err(AtentoError::Validation(msg)) => {
+                assert!(msg.contains("'greet' has no 'type'"), "msg: {msg}");
+                assert!(msg.contains("default_interpreter"), "msg: {msg}");
+            }
+            other => panic!("Expected Validation error, got {other:?}"),
+        }
     }
 }
 
-#[cfg(test)]
+#[cfg(all(test, feature = "exec"))]
 #[allow(clippy::unwrap_used, clippy::expect_used)]
 mod unit_tests {
     use crate::data_type::DataType;
@@ -387,9 +753,12 @@ mod unit_tests {
     use crate::executor::ExecutionResult;
     use crate::input::Input;
     use crate::interpreter::Interpreter;
-    use crate::output::Output;
+    use crate::output::{Output, OutputSource, RegexCache};
+    use crate::retry::{Backoff, RetryPolicy};
+    use crate::runner::{KillPolicy, TimeoutSignal};
     use crate::step::Step;
     use crate::tests::mock_executor::MockExecutor;
+    use indexmap::IndexMap;
     use std::collections::HashMap;
 
     // Helper to create a test interpreter
@@ -398,6 +767,7 @@ mod unit_tests {
             command: "bash".to_string(),
             args: vec![],
             extension: ".sh".to_string(),
+            fallbacks: vec![],
         }
     }
 
@@ -406,6 +776,7 @@ mod unit_tests {
             command: "python3".to_string(),
             args: vec![],
             extension: ".py".to_string(),
+            fallbacks: vec![],
         }
     }
 
@@ -417,16 +788,40 @@ mod unit_tests {
             name: None,
             timeout: 60,
             inputs: HashMap::new(),
-            interpreter: "bash".to_string(),
+            interpreter: Some("bash".to_string()),
             script: String::new(),
             outputs: HashMap::new(),
+            env: HashMap::new(),
+            retry: None,
+            continue_on_error: false,
+            retries: 0,
+            retry_delay_ms: 0,
+            success_exit_codes: vec![0],
+            allow_nonzero_exit: false,
+            workdir: None,
+            foreach: None,
+            capture: crate::step::Capture::Separate,
+            image: None,
+            interpreter_args: None,
+            stdin: None,
+            on_failure: None,
+            artifacts: HashMap::new(),
+            max_output_bytes: None,
+            quote: crate::step::Quote::None,
+            override_: false,
+            template: None,
+            script_file: None,
+            matrix: IndexMap::new(),
+            needs: Vec::new(),
+            timeout_signal: None,
+            timeout_grace_ms: None,
         };
         assert!(step.name.is_none());
         assert_eq!(step.timeout, 60);
         assert!(step.inputs.is_empty());
         assert!(step.outputs.is_empty());
         assert_eq!(step.script, "");
-        assert!(step.interpreter == "bash");
+        assert!(step.interpreter.as_deref() == Some("bash"));
     }
 
     #[test]
@@ -440,7 +835,7 @@ script: print("hello")
         let step: Step = serde_yaml::from_str(yaml).unwrap();
         assert_eq!(step.name.as_deref(), Some("test_step"));
         assert_eq!(step.timeout, 120);
-        assert!(step.interpreter == "python");
+        assert!(step.interpreter.as_deref() == Some("python"));
         assert_eq!(step.script, "print(\"hello\")");
     }
 
@@ -467,9 +862,33 @@ script: echo hello
                 name: None,
                 timeout: 60,
                 inputs: HashMap::new(),
-                interpreter: "bash".to_string(),
+                interpreter: Some("bash".to_string()),
                 script: String::new(),
                 outputs: HashMap::new(),
+                env: HashMap::new(),
+                retry: None,
+                continue_on_error: false,
+                retries: 0,
+                retry_delay_ms: 0,
+                success_exit_codes: vec![0],
+                allow_nonzero_exit: false,
+                workdir: None,
+                foreach: None,
+                capture: crate::step::Capture::Separate,
+                image: None,
+                interpreter_args: None,
+                stdin: None,
+                on_failure: None,
+                artifacts: HashMap::new(),
+                max_output_bytes: None,
+                quote: crate::step::Quote::None,
+                override_: false,
+                template: None,
+                script_file: None,
+                matrix: IndexMap::new(),
+                needs: Vec::new(),
+                timeout_signal: None,
+                timeout_grace_ms: None,
             }
         };
         assert_eq!(step.calculate_timeout(60), 30); // min(30, 60)
@@ -483,9 +902,33 @@ script: echo hello
                 name: None,
                 timeout: 60,
                 inputs: HashMap::new(),
-                interpreter: "bash".to_string(),
+                interpreter: Some("bash".to_string()),
                 script: String::new(),
                 outputs: HashMap::new(),
+                env: HashMap::new(),
+                retry: None,
+                continue_on_error: false,
+                retries: 0,
+                retry_delay_ms: 0,
+                success_exit_codes: vec![0],
+                allow_nonzero_exit: false,
+                workdir: None,
+                foreach: None,
+                capture: crate::step::Capture::Separate,
+                image: None,
+                interpreter_args: None,
+                stdin: None,
+                on_failure: None,
+                artifacts: HashMap::new(),
+                max_output_bytes: None,
+                quote: crate::step::Quote::None,
+                override_: false,
+                template: None,
+                script_file: None,
+                matrix: IndexMap::new(),
+                needs: Vec::new(),
+                timeout_signal: None,
+                timeout_grace_ms: None,
             }
         };
         assert_eq!(step.calculate_timeout(60), 60); // max(0, 60)
@@ -499,9 +942,33 @@ script: echo hello
                 name: None,
                 timeout: 60,
                 inputs: HashMap::new(),
-                interpreter: "bash".to_string(),
+                interpreter: Some("bash".to_string()),
                 script: String::new(),
                 outputs: HashMap::new(),
+                env: HashMap::new(),
+                retry: None,
+                continue_on_error: false,
+                retries: 0,
+                retry_delay_ms: 0,
+                success_exit_codes: vec![0],
+                allow_nonzero_exit: false,
+                workdir: None,
+                foreach: None,
+                capture: crate::step::Capture::Separate,
+                image: None,
+                interpreter_args: None,
+                stdin: None,
+                on_failure: None,
+                artifacts: HashMap::new(),
+                max_output_bytes: None,
+                quote: crate::step::Quote::None,
+                override_: false,
+                template: None,
+                script_file: None,
+                matrix: IndexMap::new(),
+                needs: Vec::new(),
+                timeout_signal: None,
+                timeout_grace_ms: None,
             }
         };
         assert_eq!(step.calculate_timeout(0), 30); // max(30, 0)
@@ -515,9 +982,33 @@ script: echo hello
                 name: None,
                 timeout: 60,
                 inputs: HashMap::new(),
-                interpreter: "bash".to_string(),
+                interpreter: Some("bash".to_string()),
                 script: String::new(),
                 outputs: HashMap::new(),
+                env: HashMap::new(),
+                retry: None,
+                continue_on_error: false,
+                retries: 0,
+                retry_delay_ms: 0,
+                success_exit_codes: vec![0],
+                allow_nonzero_exit: false,
+                workdir: None,
+                foreach: None,
+                capture: crate::step::Capture::Separate,
+                image: None,
+                interpreter_args: None,
+                stdin: None,
+                on_failure: None,
+                artifacts: HashMap::new(),
+                max_output_bytes: None,
+                quote: crate::step::Quote::None,
+                override_: false,
+                template: None,
+                script_file: None,
+                matrix: IndexMap::new(),
+                needs: Vec::new(),
+                timeout_signal: None,
+                timeout_grace_ms: None,
             }
         };
         assert_eq!(step.calculate_timeout(0), 0); // max(0, 0)
@@ -531,9 +1022,33 @@ script: echo hello
                 name: None,
                 timeout: 60,
                 inputs: HashMap::new(),
-                interpreter: "bash".to_string(),
+                interpreter: Some("bash".to_string()),
                 script: String::new(),
                 outputs: HashMap::new(),
+                env: HashMap::new(),
+                retry: None,
+                continue_on_error: false,
+                retries: 0,
+                retry_delay_ms: 0,
+                success_exit_codes: vec![0],
+                allow_nonzero_exit: false,
+                workdir: None,
+                foreach: None,
+                capture: crate::step::Capture::Separate,
+                image: None,
+                interpreter_args: None,
+                stdin: None,
+                on_failure: None,
+                artifacts: HashMap::new(),
+                max_output_bytes: None,
+                quote: crate::step::Quote::None,
+                override_: false,
+                template: None,
+                script_file: None,
+                matrix: IndexMap::new(),
+                needs: Vec::new(),
+                timeout_signal: None,
+                timeout_grace_ms: None,
             }
         };
         assert_eq!(step.calculate_timeout(45), 45); // min(45, 45)
@@ -549,9 +1064,33 @@ script: echo hello
                 name: None,
                 timeout: 60,
                 inputs: HashMap::new(),
-                interpreter: "bash".to_string(),
+                interpreter: Some("bash".to_string()),
                 script: String::new(),
                 outputs: HashMap::new(),
+                env: HashMap::new(),
+                retry: None,
+                continue_on_error: false,
+                retries: 0,
+                retry_delay_ms: 0,
+                success_exit_codes: vec![0],
+                allow_nonzero_exit: false,
+                workdir: None,
+                foreach: None,
+                capture: crate::step::Capture::Separate,
+                image: None,
+                interpreter_args: None,
+                stdin: None,
+                on_failure: None,
+                artifacts: HashMap::new(),
+                max_output_bytes: None,
+                quote: crate::step::Quote::None,
+                override_: false,
+                template: None,
+                script_file: None,
+                matrix: IndexMap::new(),
+                needs: Vec::new(),
+                timeout_signal: None,
+                timeout_grace_ms: None,
             }
         };
         let inputs = HashMap::new();
@@ -565,9 +1104,33 @@ script: echo hello
             name: None,
             timeout: 60,
             inputs: HashMap::new(),
-            interpreter: "bash".to_string(),
+            interpreter: Some("bash".to_string()),
             script: String::new(),
             outputs: HashMap::new(),
+            env: HashMap::new(),
+            retry: None,
+            continue_on_error: false,
+            retries: 0,
+            retry_delay_ms: 0,
+            success_exit_codes: vec![0],
+            allow_nonzero_exit: false,
+            workdir: None,
+            foreach: None,
+            capture: crate::step::Capture::Separate,
+            image: None,
+            interpreter_args: None,
+            stdin: None,
+            on_failure: None,
+            artifacts: HashMap::new(),
+            max_output_bytes: None,
+            quote: crate::step::Quote::None,
+            override_: false,
+            template: None,
+            script_file: None,
+            matrix: IndexMap::new(),
+            needs: Vec::new(),
+            timeout_signal: None,
+            timeout_grace_ms: None,
         };
         let inputs = HashMap::new();
         let result = step.build_script(&inputs);
@@ -582,9 +1145,33 @@ script: echo hello
                 name: None,
                 timeout: 60,
                 inputs: HashMap::new(),
-                interpreter: "bash".to_string(),
+                interpreter: Some("bash".to_string()),
                 script: String::new(),
                 outputs: HashMap::new(),
+                env: HashMap::new(),
+                retry: None,
+                continue_on_error: false,
+                retries: 0,
+                retry_delay_ms: 0,
+                success_exit_codes: vec![0],
+                allow_nonzero_exit: false,
+                workdir: None,
+                foreach: None,
+                capture: crate::step::Capture::Separate,
+                image: None,
+                interpreter_args: None,
+                stdin: None,
+                on_failure: None,
+                artifacts: HashMap::new(),
+                max_output_bytes: None,
+                quote: crate::step::Quote::None,
+                override_: false,
+                template: None,
+                script_file: None,
+                matrix: IndexMap::new(),
+                needs: Vec::new(),
+                timeout_signal: None,
+                timeout_grace_ms: None,
             }
         };
         let mut inputs = HashMap::new();
@@ -601,9 +1188,33 @@ script: echo hello
                 name: None,
                 timeout: 60,
                 inputs: HashMap::new(),
-                interpreter: "bash".to_string(),
+                interpreter: Some("bash".to_string()),
                 script: String::new(),
                 outputs: HashMap::new(),
+                env: HashMap::new(),
+                retry: None,
+                continue_on_error: false,
+                retries: 0,
+                retry_delay_ms: 0,
+                success_exit_codes: vec![0],
+                allow_nonzero_exit: false,
+                workdir: None,
+                foreach: None,
+                capture: crate::step::Capture::Separate,
+                image: None,
+                interpreter_args: None,
+                stdin: None,
+                on_failure: None,
+                artifacts: HashMap::new(),
+                max_output_bytes: None,
+                quote: crate::step::Quote::None,
+                override_: false,
+                template: None,
+                script_file: None,
+                matrix: IndexMap::new(),
+                needs: Vec::new(),
+                timeout_signal: None,
+                timeout_grace_ms: None,
             }
         };
         let mut inputs = HashMap::new();
@@ -621,9 +1232,33 @@ script: echo hello
                 name: None,
                 timeout: 60,
                 inputs: HashMap::new(),
-                interpreter: "bash".to_string(),
+                interpreter: Some("bash".to_string()),
                 script: String::new(),
                 outputs: HashMap::new(),
+                env: HashMap::new(),
+                retry: None,
+                continue_on_error: false,
+                retries: 0,
+                retry_delay_ms: 0,
+                success_exit_codes: vec![0],
+                allow_nonzero_exit: false,
+                workdir: None,
+                foreach: None,
+                capture: crate::step::Capture::Separate,
+                image: None,
+                interpreter_args: None,
+                stdin: None,
+                on_failure: None,
+                artifacts: HashMap::new(),
+                max_output_bytes: None,
+                quote: crate::step::Quote::None,
+                override_: false,
+                template: None,
+                script_file: None,
+                matrix: IndexMap::new(),
+                needs: Vec::new(),
+                timeout_signal: None,
+                timeout_grace_ms: None,
             }
         };
         let mut inputs = HashMap::new();
@@ -640,9 +1275,33 @@ script: echo hello
                 name: None,
                 timeout: 60,
                 inputs: HashMap::new(),
-                interpreter: "bash".to_string(),
+                interpreter: Some("bash".to_string()),
                 script: String::new(),
                 outputs: HashMap::new(),
+                env: HashMap::new(),
+                retry: None,
+                continue_on_error: false,
+                retries: 0,
+                retry_delay_ms: 0,
+                success_exit_codes: vec![0],
+                allow_nonzero_exit: false,
+                workdir: None,
+                foreach: None,
+                capture: crate::step::Capture::Separate,
+                image: None,
+                interpreter_args: None,
+                stdin: None,
+                on_failure: None,
+                artifacts: HashMap::new(),
+                max_output_bytes: None,
+                quote: crate::step::Quote::None,
+                override_: false,
+                template: None,
+                script_file: None,
+                matrix: IndexMap::new(),
+                needs: Vec::new(),
+                timeout_signal: None,
+                timeout_grace_ms: None,
             }
         };
         let mut inputs = HashMap::new();
@@ -659,9 +1318,33 @@ script: echo hello
                 name: None,
                 timeout: 60,
                 inputs: HashMap::new(),
-                interpreter: "bash".to_string(),
+                interpreter: Some("bash".to_string()),
                 script: String::new(),
                 outputs: HashMap::new(),
+                env: HashMap::new(),
+                retry: None,
+                continue_on_error: false,
+                retries: 0,
+                retry_delay_ms: 0,
+                success_exit_codes: vec![0],
+                allow_nonzero_exit: false,
+                workdir: None,
+                foreach: None,
+                capture: crate::step::Capture::Separate,
+                image: None,
+                interpreter_args: None,
+                stdin: None,
+                on_failure: None,
+                artifacts: HashMap::new(),
+                max_output_bytes: None,
+                quote: crate::step::Quote::None,
+                override_: false,
+                template: None,
+                script_file: None,
+                matrix: IndexMap::new(),
+                needs: Vec::new(),
+                timeout_signal: None,
+                timeout_grace_ms: None,
             }
         };
         let inputs = HashMap::new();
@@ -677,9 +1360,33 @@ script: echo hello
                 name: None,
                 timeout: 60,
                 inputs: HashMap::new(),
-                interpreter: "bash".to_string(),
+                interpreter: Some("bash".to_string()),
                 script: String::new(),
                 outputs: HashMap::new(),
+                env: HashMap::new(),
+                retry: None,
+                continue_on_error: false,
+                retries: 0,
+                retry_delay_ms: 0,
+                success_exit_codes: vec![0],
+                allow_nonzero_exit: false,
+                workdir: None,
+                foreach: None,
+                capture: crate::step::Capture::Separate,
+                image: None,
+                interpreter_args: None,
+                stdin: None,
+                on_failure: None,
+                artifacts: HashMap::new(),
+                max_output_bytes: None,
+                quote: crate::step::Quote::None,
+                override_: false,
+                template: None,
+                script_file: None,
+                matrix: IndexMap::new(),
+                needs: Vec::new(),
+                timeout_signal: None,
+                timeout_grace_ms: None,
             }
         };
         let mut inputs = HashMap::new();
@@ -698,9 +1405,33 @@ script: echo hello
             name: None,
             timeout: 60,
             inputs: HashMap::new(),
-            interpreter: "bash".to_string(),
+            interpreter: Some("bash".to_string()),
             script: String::new(),
             outputs: HashMap::new(),
+            env: HashMap::new(),
+            retry: None,
+            continue_on_error: false,
+            retries: 0,
+            retry_delay_ms: 0,
+            success_exit_codes: vec![0],
+            allow_nonzero_exit: false,
+            workdir: None,
+            foreach: None,
+            capture: crate::step::Capture::Separate,
+            image: None,
+            interpreter_args: None,
+            stdin: None,
+            on_failure: None,
+            artifacts: HashMap::new(),
+            max_output_bytes: None,
+            quote: crate::step::Quote::None,
+            override_: false,
+            template: None,
+            script_file: None,
+            matrix: IndexMap::new(),
+            needs: Vec::new(),
+            timeout_signal: None,
+            timeout_grace_ms: None,
         };
         let result = step.validate("test_id");
         assert!(result.is_ok());
@@ -714,9 +1445,33 @@ script: echo hello
                 name: None,
                 timeout: 60,
                 inputs: HashMap::new(),
-                interpreter: "bash".to_string(),
+                interpreter: Some("bash".to_string()),
                 script: String::new(),
                 outputs: HashMap::new(),
+                env: HashMap::new(),
+                retry: None,
+                continue_on_error: false,
+                retries: 0,
+                retry_delay_ms: 0,
+                success_exit_codes: vec![0],
+                allow_nonzero_exit: false,
+                workdir: None,
+                foreach: None,
+                capture: crate::step::Capture::Separate,
+                image: None,
+                interpreter_args: None,
+                stdin: None,
+                on_failure: None,
+                artifacts: HashMap::new(),
+                max_output_bytes: None,
+                quote: crate::step::Quote::None,
+                override_: false,
+                template: None,
+                script_file: None,
+                matrix: IndexMap::new(),
+                needs: Vec::new(),
+                timeout_signal: None,
+                timeout_grace_ms: None,
             }
         };
         let result = step.validate("test_id");
@@ -731,9 +1486,33 @@ script: echo hello
                 name: None,
                 timeout: 60,
                 inputs: HashMap::new(),
-                interpreter: "bash".to_string(),
+                interpreter: Some("bash".to_string()),
                 script: String::new(),
                 outputs: HashMap::new(),
+                env: HashMap::new(),
+                retry: None,
+                continue_on_error: false,
+                retries: 0,
+                retry_delay_ms: 0,
+                success_exit_codes: vec![0],
+                allow_nonzero_exit: false,
+                workdir: None,
+                foreach: None,
+                capture: crate::step::Capture::Separate,
+                image: None,
+                interpreter_args: None,
+                stdin: None,
+                on_failure: None,
+                artifacts: HashMap::new(),
+                max_output_bytes: None,
+                quote: crate::step::Quote::None,
+                override_: false,
+                template: None,
+                script_file: None,
+                matrix: IndexMap::new(),
+                needs: Vec::new(),
+                timeout_signal: None,
+                timeout_grace_ms: None,
             }
         };
         let result = step.validate("test_id");
@@ -752,9 +1531,33 @@ script: echo hello
                 name: None,
                 timeout: 60,
                 inputs: HashMap::new(),
-                interpreter: "bash".to_string(),
+                interpreter: Some("bash".to_string()),
                 script: String::new(),
                 outputs: HashMap::new(),
+                env: HashMap::new(),
+                retry: None,
+                continue_on_error: false,
+                retries: 0,
+                retry_delay_ms: 0,
+                success_exit_codes: vec![0],
+                allow_nonzero_exit: false,
+                workdir: None,
+                foreach: None,
+                capture: crate::step::Capture::Separate,
+                image: None,
+                interpreter_args: None,
+                stdin: None,
+                on_failure: None,
+                artifacts: HashMap::new(),
+                max_output_bytes: None,
+                quote: crate::step::Quote::None,
+                override_: false,
+                template: None,
+                script_file: None,
+                matrix: IndexMap::new(),
+                needs: Vec::new(),
+                timeout_signal: None,
+                timeout_grace_ms: None,
             }
         };
         step.inputs.insert(
@@ -762,6 +1565,7 @@ script: echo hello
             Input::Inline {
                 type_: DataType::String,
                 value: serde_yaml::Value::String("value".to_string()),
+                separator: None,
             },
         );
         let result = step.validate("test_id");
@@ -779,9 +1583,33 @@ script: echo hello
                 name: None,
                 timeout: 60,
                 inputs: HashMap::new(),
-                interpreter: "bash".to_string(),
+                interpreter: Some("bash".to_string()),
                 script: String::new(),
                 outputs: HashMap::new(),
+                env: HashMap::new(),
+                retry: None,
+                continue_on_error: false,
+                retries: 0,
+                retry_delay_ms: 0,
+                success_exit_codes: vec![0],
+                allow_nonzero_exit: false,
+                workdir: None,
+                foreach: None,
+                capture: crate::step::Capture::Separate,
+                image: None,
+                interpreter_args: None,
+                stdin: None,
+                on_failure: None,
+                artifacts: HashMap::new(),
+                max_output_bytes: None,
+                quote: crate::step::Quote::None,
+                override_: false,
+                template: None,
+                script_file: None,
+                matrix: IndexMap::new(),
+                needs: Vec::new(),
+                timeout_signal: None,
+                timeout_grace_ms: None,
             }
         };
         step.inputs.insert(
@@ -789,6 +1617,7 @@ script: echo hello
             Input::Inline {
                 type_: DataType::String,
                 value: serde_yaml::Value::String("test".to_string()),
+                separator: None,
             },
         );
         let result = step.validate("test_id");
@@ -801,15 +1630,48 @@ script: echo hello
             name: None,
             timeout: 60,
             inputs: HashMap::new(),
-            interpreter: "bash".to_string(),
+            interpreter: Some("bash".to_string()),
             script: String::new(),
             outputs: HashMap::new(),
+            env: HashMap::new(),
+            retry: None,
+            continue_on_error: false,
+            retries: 0,
+            retry_delay_ms: 0,
+            success_exit_codes: vec![0],
+            allow_nonzero_exit: false,
+            workdir: None,
+            foreach: None,
+            capture: crate::step::Capture::Separate,
+            image: None,
+            interpreter_args: None,
+            stdin: None,
+            on_failure: None,
+            artifacts: HashMap::new(),
+            max_output_bytes: None,
+            quote: crate::step::Quote::None,
+            override_: false,
+            template: None,
+            script_file: None,
+            matrix: IndexMap::new(),
+            needs: Vec::new(),
+            timeout_signal: None,
+            timeout_grace_ms: None,
         };
         step.outputs.insert(
             "result".to_string(),
             Output {
                 pattern: String::new(),
                 type_: DataType::String,
+                group: None,
+                json_path: None,
+                key: None,
+                source: OutputSource::Stdout,
+                multiple: false,
+                optional: false,
+                delimiter: None,
+                default: None,
+                regex_cache: RegexCache::default(),
             },
         );
         let result = step.validate("test_id");
@@ -825,15 +1687,48 @@ script: echo hello
             name: None,
             timeout: 60,
             inputs: HashMap::new(),
-            interpreter: "bash".to_string(),
+            interpreter: Some("bash".to_string()),
             script: String::new(),
             outputs: HashMap::new(),
+            env: HashMap::new(),
+            retry: None,
+            continue_on_error: false,
+            retries: 0,
+            retry_delay_ms: 0,
+            success_exit_codes: vec![0],
+            allow_nonzero_exit: false,
+            workdir: None,
+            foreach: None,
+            capture: crate::step::Capture::Separate,
+            image: None,
+            interpreter_args: None,
+            stdin: None,
+            on_failure: None,
+            artifacts: HashMap::new(),
+            max_output_bytes: None,
+            quote: crate::step::Quote::None,
+            override_: false,
+            template: None,
+            script_file: None,
+            matrix: IndexMap::new(),
+            needs: Vec::new(),
+            timeout_signal: None,
+            timeout_grace_ms: None,
         };
         step.outputs.insert(
             "result".to_string(),
             Output {
                 pattern: "   ".to_string(),
                 type_: DataType::String,
+                group: None,
+                json_path: None,
+                key: None,
+                source: OutputSource::Stdout,
+                multiple: false,
+                optional: false,
+                delimiter: None,
+                default: None,
+                regex_cache: RegexCache::default(),
             },
         );
         let result = step.validate("test_id");
@@ -849,15 +1744,48 @@ script: echo hello
             name: None,
             timeout: 60,
             inputs: HashMap::new(),
-            interpreter: "bash".to_string(),
+            interpreter: Some("bash".to_string()),
             script: String::new(),
             outputs: HashMap::new(),
+            env: HashMap::new(),
+            retry: None,
+            continue_on_error: false,
+            retries: 0,
+            retry_delay_ms: 0,
+            success_exit_codes: vec![0],
+            allow_nonzero_exit: false,
+            workdir: None,
+            foreach: None,
+            capture: crate::step::Capture::Separate,
+            image: None,
+            interpreter_args: None,
+            stdin: None,
+            on_failure: None,
+            artifacts: HashMap::new(),
+            max_output_bytes: None,
+            quote: crate::step::Quote::None,
+            override_: false,
+            template: None,
+            script_file: None,
+            matrix: IndexMap::new(),
+            needs: Vec::new(),
+            timeout_signal: None,
+            timeout_grace_ms: None,
         };
         step.outputs.insert(
             "result".to_string(),
             Output {
                 pattern: "[invalid".to_string(),
                 type_: DataType::String,
+                group: None,
+                json_path: None,
+                key: None,
+                source: OutputSource::Stdout,
+                multiple: false,
+                optional: false,
+                delimiter: None,
+                default: None,
+                regex_cache: RegexCache::default(),
             },
         );
         let result = step.validate("test_id");
@@ -873,15 +1801,48 @@ script: echo hello
             name: None,
             timeout: 60,
             inputs: HashMap::new(),
-            interpreter: "bash".to_string(),
+            interpreter: Some("bash".to_string()),
             script: String::new(),
             outputs: HashMap::new(),
+            env: HashMap::new(),
+            retry: None,
+            continue_on_error: false,
+            retries: 0,
+            retry_delay_ms: 0,
+            success_exit_codes: vec![0],
+            allow_nonzero_exit: false,
+            workdir: None,
+            foreach: None,
+            capture: crate::step::Capture::Separate,
+            image: None,
+            interpreter_args: None,
+            stdin: None,
+            on_failure: None,
+            artifacts: HashMap::new(),
+            max_output_bytes: None,
+            quote: crate::step::Quote::None,
+            override_: false,
+            template: None,
+            script_file: None,
+            matrix: IndexMap::new(),
+            needs: Vec::new(),
+            timeout_signal: None,
+            timeout_grace_ms: None,
         };
         step.outputs.insert(
             "result".to_string(),
             Output {
                 pattern: r"Result: (\d+)".to_string(),
                 type_: DataType::Int,
+                group: None,
+                json_path: None,
+                key: None,
+                source: OutputSource::Stdout,
+                multiple: false,
+                optional: false,
+                delimiter: None,
+                default: None,
+                regex_cache: RegexCache::default(),
             },
         );
         let result = step.validate("test_id");
@@ -897,9 +1858,33 @@ script: echo hello
                 name: None,
                 timeout: 60,
                 inputs: HashMap::new(),
-                interpreter: "bash".to_string(),
+                interpreter: Some("bash".to_string()),
                 script: String::new(),
                 outputs: HashMap::new(),
+                env: HashMap::new(),
+                retry: None,
+                continue_on_error: false,
+                retries: 0,
+                retry_delay_ms: 0,
+                success_exit_codes: vec![0],
+                allow_nonzero_exit: false,
+                workdir: None,
+                foreach: None,
+                capture: crate::step::Capture::Separate,
+                image: None,
+                interpreter_args: None,
+                stdin: None,
+                on_failure: None,
+                artifacts: HashMap::new(),
+                max_output_bytes: None,
+                quote: crate::step::Quote::None,
+                override_: false,
+                template: None,
+                script_file: None,
+                matrix: IndexMap::new(),
+                needs: Vec::new(),
+                timeout_signal: None,
+                timeout_grace_ms: None,
             }
         };
         let result = step.validate("test_id");
@@ -918,9 +1903,33 @@ script: echo hello
                 name: None,
                 timeout: 60,
                 inputs: HashMap::new(),
-                interpreter: "bash".to_string(),
+                interpreter: Some("bash".to_string()),
                 script: String::new(),
                 outputs: HashMap::new(),
+                env: HashMap::new(),
+                retry: None,
+                continue_on_error: false,
+                retries: 0,
+                retry_delay_ms: 0,
+                success_exit_codes: vec![0],
+                allow_nonzero_exit: false,
+                workdir: None,
+                foreach: None,
+                capture: crate::step::Capture::Separate,
+                image: None,
+                interpreter_args: None,
+                stdin: None,
+                on_failure: None,
+                artifacts: HashMap::new(),
+                max_output_bytes: None,
+                quote: crate::step::Quote::None,
+                override_: false,
+                template: None,
+                script_file: None,
+                matrix: IndexMap::new(),
+                needs: Vec::new(),
+                timeout_signal: None,
+                timeout_grace_ms: None,
             }
         };
         let result = step.validate("test_id");
@@ -938,12 +1947,38 @@ script: echo hello
             name: None,
             timeout: 60,
             inputs: HashMap::new(),
-            interpreter: "bash".to_string(),
+            interpreter: Some("bash".to_string()),
             script: String::new(),
             outputs: HashMap::new(),
+            env: HashMap::new(),
+            retry: None,
+            continue_on_error: false,
+            retries: 0,
+            retry_delay_ms: 0,
+            success_exit_codes: vec![0],
+            allow_nonzero_exit: false,
+            workdir: None,
+            foreach: None,
+            capture: crate::step::Capture::Separate,
+            image: None,
+            interpreter_args: None,
+            stdin: None,
+            on_failure: None,
+            artifacts: HashMap::new(),
+            max_output_bytes: None,
+            quote: crate::step::Quote::None,
+            override_: false,
+            template: None,
+            script_file: None,
+            matrix: IndexMap::new(),
+            needs: Vec::new(),
+            timeout_signal: None,
+            timeout_grace_ms: None,
         };
         let mut stdout = "some output".to_string();
-        let result = step.extract_outputs(&mut stdout).unwrap();
+        let result = step
+            .extract_outputs(&mut stdout, &mut String::new(), 0)
+            .unwrap();
         assert!(result.is_empty());
         assert_eq!(stdout, "some output"); // unchanged
     }
@@ -954,20 +1989,55 @@ script: echo hello
             name: None,
             timeout: 60,
             inputs: HashMap::new(),
-            interpreter: "bash".to_string(),
+            interpreter: Some("bash".to_string()),
             script: String::new(),
             outputs: HashMap::new(),
+            env: HashMap::new(),
+            retry: None,
+            continue_on_error: false,
+            retries: 0,
+            retry_delay_ms: 0,
+            success_exit_codes: vec![0],
+            allow_nonzero_exit: false,
+            workdir: None,
+            foreach: None,
+            capture: crate::step::Capture::Separate,
+            image: None,
+            interpreter_args: None,
+            stdin: None,
+            on_failure: None,
+            artifacts: HashMap::new(),
+            max_output_bytes: None,
+            quote: crate::step::Quote::None,
+            override_: false,
+            template: None,
+            script_file: None,
+            matrix: IndexMap::new(),
+            needs: Vec::new(),
+            timeout_signal: None,
+            timeout_grace_ms: None,
         };
         step.outputs.insert(
             "result".to_string(),
             Output {
                 pattern: r"Result: (\w+)".to_string(),
                 type_: DataType::String,
+                group: None,
+                json_path: None,
+                key: None,
+                source: OutputSource::Stdout,
+                multiple: false,
+                optional: false,
+                delimiter: None,
+                default: None,
+                regex_cache: RegexCache::default(),
             },
         );
 
         let mut stdout = "Processing...\nResult: success\nDone.".to_string();
-        let result = step.extract_outputs(&mut stdout).unwrap();
+        let result = step
+            .extract_outputs(&mut stdout, &mut String::new(), 0)
+            .unwrap();
 
         assert_eq!(result.get("result").unwrap(), "success");
         assert_eq!(stdout, "Processing...\n\nDone."); // matched portion removed
@@ -979,20 +2049,53 @@ script: echo hello
             name: None,
             timeout: 60,
             inputs: HashMap::new(),
-            interpreter: "bash".to_string(),
+            interpreter: Some("bash".to_string()),
             script: String::new(),
             outputs: HashMap::new(),
+            env: HashMap::new(),
+            retry: None,
+            continue_on_error: false,
+            retries: 0,
+            retry_delay_ms: 0,
+            success_exit_codes: vec![0],
+            allow_nonzero_exit: false,
+            workdir: None,
+            foreach: None,
+            capture: crate::step::Capture::Separate,
+            image: None,
+            interpreter_args: None,
+            stdin: None,
+            on_failure: None,
+            artifacts: HashMap::new(),
+            max_output_bytes: None,
+            quote: crate::step::Quote::None,
+            override_: false,
+            template: None,
+            script_file: None,
+            matrix: IndexMap::new(),
+            needs: Vec::new(),
+            timeout_signal: None,
+            timeout_grace_ms: None,
         };
         step.outputs.insert(
             "result".to_string(),
             Output {
                 pattern: r"Result: (\w+)".to_string(),
                 type_: DataType::String,
+                group: None,
+                json_path: None,
+                key: None,
+                source: OutputSource::Stdout,
+                multiple: false,
+                optional: false,
+                delimiter: None,
+                default: None,
+                regex_cache: RegexCache::default(),
             },
         );
 
         let mut stdout = "No match here".to_string();
-        let result = step.extract_outputs(&mut stdout);
+        let result = step.extract_outputs(&mut stdout, &mut String::new(), 0);
 
         assert!(result.is_err());
         if let Err(AtentoError::Execution(msg)) = result {
@@ -1006,20 +2109,53 @@ script: echo hello
             name: None,
             timeout: 60,
             inputs: HashMap::new(),
-            interpreter: "bash".to_string(),
+            interpreter: Some("bash".to_string()),
             script: String::new(),
             outputs: HashMap::new(),
+            env: HashMap::new(),
+            retry: None,
+            continue_on_error: false,
+            retries: 0,
+            retry_delay_ms: 0,
+            success_exit_codes: vec![0],
+            allow_nonzero_exit: false,
+            workdir: None,
+            foreach: None,
+            capture: crate::step::Capture::Separate,
+            image: None,
+            interpreter_args: None,
+            stdin: None,
+            on_failure: None,
+            artifacts: HashMap::new(),
+            max_output_bytes: None,
+            quote: crate::step::Quote::None,
+            override_: false,
+            template: None,
+            script_file: None,
+            matrix: IndexMap::new(),
+            needs: Vec::new(),
+            timeout_signal: None,
+            timeout_grace_ms: None,
         };
         step.outputs.insert(
             "result".to_string(),
             Output {
                 pattern: r"Result: \w+".to_string(), // No capture group
                 type_: DataType::String,
+                group: None,
+                json_path: None,
+                key: None,
+                source: OutputSource::Stdout,
+                multiple: false,
+                optional: false,
+                delimiter: None,
+                default: None,
+                regex_cache: RegexCache::default(),
             },
         );
 
         let mut stdout = "Result: success".to_string();
-        let result = step.extract_outputs(&mut stdout);
+        let result = step.extract_outputs(&mut stdout, &mut String::new(), 0);
 
         assert!(result.is_err());
         if let Err(AtentoError::Execution(msg)) = result {
@@ -1033,15 +2169,48 @@ script: echo hello
             name: None,
             timeout: 60,
             inputs: HashMap::new(),
-            interpreter: "bash".to_string(),
+            interpreter: Some("bash".to_string()),
             script: String::new(),
             outputs: HashMap::new(),
+            env: HashMap::new(),
+            retry: None,
+            continue_on_error: false,
+            retries: 0,
+            retry_delay_ms: 0,
+            success_exit_codes: vec![0],
+            allow_nonzero_exit: false,
+            workdir: None,
+            foreach: None,
+            capture: crate::step::Capture::Separate,
+            image: None,
+            interpreter_args: None,
+            stdin: None,
+            on_failure: None,
+            artifacts: HashMap::new(),
+            max_output_bytes: None,
+            quote: crate::step::Quote::None,
+            override_: false,
+            template: None,
+            script_file: None,
+            matrix: IndexMap::new(),
+            needs: Vec::new(),
+            timeout_signal: None,
+            timeout_grace_ms: None,
         };
         step.outputs.insert(
             "name".to_string(),
             Output {
                 pattern: r"Name: (\w+)".to_string(),
                 type_: DataType::String,
+                group: None,
+                json_path: None,
+                key: None,
+                source: OutputSource::Stdout,
+                multiple: false,
+                optional: false,
+                delimiter: None,
+                default: None,
+                regex_cache: RegexCache::default(),
             },
         );
         step.outputs.insert(
@@ -1049,14 +2218,25 @@ script: echo hello
             Output {
                 pattern: r"Age: (\d+)".to_string(),
                 type_: DataType::Int,
+                group: None,
+                json_path: None,
+                key: None,
+                source: OutputSource::Stdout,
+                multiple: false,
+                optional: false,
+                delimiter: None,
+                default: None,
+                regex_cache: RegexCache::default(),
             },
         );
 
         let mut stdout = "Name: John\nAge: 25\nOther info".to_string();
-        let result = step.extract_outputs(&mut stdout).unwrap();
+        let result = step
+            .extract_outputs(&mut stdout, &mut String::new(), 0)
+            .unwrap();
 
         assert_eq!(result.get("name").unwrap(), "John");
-        assert_eq!(result.get("age").unwrap(), "25");
+        assert_eq!(result.get("age").unwrap(), &serde_json::json!(25));
         assert_eq!(stdout, "\n\nOther info"); // Both matches removed
     }
 
@@ -1069,27 +2249,59 @@ script: echo hello
             "echo hello",
             ExecutionResult {
                 stdout: "hello\n".to_string(),
-                stderr: String::new(),
-                exit_code: 0,
                 duration_ms: 5,
+                ..ExecutionResult::default()
             },
         );
 
         let step = Step {
             script: "echo hello".to_string(),
-            interpreter: "bash".to_string(),
+            interpreter: Some("bash".to_string()),
             ..Step {
                 name: None,
                 timeout: 60,
                 inputs: HashMap::new(),
-                interpreter: "bash".to_string(),
+                interpreter: Some("bash".to_string()),
                 script: String::new(),
                 outputs: HashMap::new(),
+                env: HashMap::new(),
+                retry: None,
+                continue_on_error: false,
+                retries: 0,
+                retry_delay_ms: 0,
+                success_exit_codes: vec![0],
+                allow_nonzero_exit: false,
+                workdir: None,
+                foreach: None,
+                capture: crate::step::Capture::Separate,
+                image: None,
+                interpreter_args: None,
+                stdin: None,
+                on_failure: None,
+                artifacts: HashMap::new(),
+                max_output_bytes: None,
+                quote: crate::step::Quote::None,
+                override_: false,
+                template: None,
+                script_file: None,
+                matrix: IndexMap::new(),
+                needs: Vec::new(),
+                timeout_signal: None,
+                timeout_grace_ms: None,
             }
         };
 
         let inputs = HashMap::new();
-        let result = step.run(&mock, &inputs, 60, &test_bash_interpreter());
+        let result = step.run(
+            &mock,
+            &inputs,
+            &HashMap::new(),
+            &HashMap::new(),
+            None,
+            60,
+            &KillPolicy::default(),
+            &test_bash_interpreter(),
+        );
 
         assert_eq!(result.exit_code, 0);
         assert_eq!(result.stdout.as_deref(), Some("hello"));
@@ -1104,28 +2316,60 @@ script: echo hello
             "echo world",
             ExecutionResult {
                 stdout: "world\n".to_string(),
-                stderr: String::new(),
-                exit_code: 0,
                 duration_ms: 8,
+                ..ExecutionResult::default()
             },
         );
 
         let step = Step {
             script: "echo {{ inputs.message }}".to_string(),
-            interpreter: "bash".to_string(),
+            interpreter: Some("bash".to_string()),
             ..Step {
                 name: None,
                 timeout: 60,
                 inputs: HashMap::new(),
-                interpreter: "bash".to_string(),
+                interpreter: Some("bash".to_string()),
                 script: String::new(),
                 outputs: HashMap::new(),
+                env: HashMap::new(),
+                retry: None,
+                continue_on_error: false,
+                retries: 0,
+                retry_delay_ms: 0,
+                success_exit_codes: vec![0],
+                allow_nonzero_exit: false,
+                workdir: None,
+                foreach: None,
+                capture: crate::step::Capture::Separate,
+                image: None,
+                interpreter_args: None,
+                stdin: None,
+                on_failure: None,
+                artifacts: HashMap::new(),
+                max_output_bytes: None,
+                quote: crate::step::Quote::None,
+                override_: false,
+                template: None,
+                script_file: None,
+                matrix: IndexMap::new(),
+                needs: Vec::new(),
+                timeout_signal: None,
+                timeout_grace_ms: None,
             }
         };
 
         let mut inputs = HashMap::new();
         inputs.insert("message".to_string(), "world".to_string());
-        let result = step.run(&mock, &inputs, 60, &test_bash_interpreter());
+        let result = step.run(
+            &mock,
+            &inputs,
+            &HashMap::new(),
+            &HashMap::new(),
+            None,
+            60,
+            &KillPolicy::default(),
+            &test_bash_interpreter(),
+        );
 
         assert_eq!(result.exit_code, 0);
         assert_eq!(result.stdout.as_deref(), Some("world"));
@@ -1146,25 +2390,113 @@ script: echo hello
         let step = Step {
             script: "sleep 10".to_string(),
             timeout: 5,
-            interpreter: "bash".to_string(),
+            interpreter: Some("bash".to_string()),
             ..Step {
                 name: None,
                 timeout: 60,
                 inputs: HashMap::new(),
-                interpreter: "bash".to_string(),
+                interpreter: Some("bash".to_string()),
                 script: String::new(),
                 outputs: HashMap::new(),
+                env: HashMap::new(),
+                retry: None,
+                continue_on_error: false,
+                retries: 0,
+                retry_delay_ms: 0,
+                success_exit_codes: vec![0],
+                allow_nonzero_exit: false,
+                workdir: None,
+                foreach: None,
+                capture: crate::step::Capture::Separate,
+                image: None,
+                interpreter_args: None,
+                stdin: None,
+                on_failure: None,
+                artifacts: HashMap::new(),
+                max_output_bytes: None,
+                quote: crate::step::Quote::None,
+                override_: false,
+                template: None,
+                script_file: None,
+                matrix: IndexMap::new(),
+                needs: Vec::new(),
+                timeout_signal: None,
+                timeout_grace_ms: None,
             }
         };
 
         let inputs = HashMap::new();
-        let result = step.run(&mock, &inputs, 60, &test_bash_interpreter());
+        let result = step.run(
+            &mock,
+            &inputs,
+            &HashMap::new(),
+            &HashMap::new(),
+            None,
+            60,
+            &KillPolicy::default(),
+            &test_bash_interpreter(),
+        );
 
         // The mock should return the timeout error based on our expectation
         assert_eq!(result.exit_code, 124); // Timeout exit code
         assert_eq!(result.stderr.as_deref(), Some("Timeout"));
     }
 
+    #[test]
+    fn test_timeout_error_notes_how_the_process_was_stopped() {
+        let step = Step {
+            script: "sleep 10".to_string(),
+            interpreter: Some("bash".to_string()),
+            ..Step::default()
+        };
+        let kill = KillPolicy {
+            signal: TimeoutSignal::Int,
+            grace_ms: 250,
+            ..KillPolicy::default()
+        };
+        let context = |mock: &MockExecutor| {
+            let result = step.run(
+                mock,
+                &HashMap::new(),
+                &HashMap::new(),
+                &HashMap::new(),
+                None,
+                60,
+                &kill,
+                &test_bash_interpreter(),
+            );
+            assert_eq!(mock.last_kill(), Some(kill.clone()));
+            match result.error {
+                Some(AtentoError::Timeout { context, .. }) => context,
+                other => panic!("Expected Timeout error, got {other:?}"),
+            }
+        };
+
+        let mut mock = MockExecutor::new();
+        mock.expect_timeout("sleep 10");
+        let expected = if cfg!(unix) {
+            "Step execution timed out; the process did not exit within 250ms of SIGINT and was force-killed"
+        } else {
+            "Step execution timed out; the process was force-killed"
+        };
+        assert_eq!(context(&mock), expected);
+
+        let mut mock = MockExecutor::new();
+        mock.expect_call(
+            "sleep 10",
+            ExecutionResult {
+                exit_code: 124,
+                duration_ms: 1000,
+                timed_out: true,
+                ..ExecutionResult::default()
+            },
+        );
+        assert_eq!(
+            context(&mock),
+            "Step execution timed out; the process exited gracefully after SIGINT"
+        );
+    }
+
     #[test]
     fn test_run_with_mock_executor_output_extraction() {
         let mut mock = MockExecutor::new();
@@ -1172,22 +2504,45 @@ script: echo hello
             "echo 'Result: 42'",
             ExecutionResult {
                 stdout: "Result: 42\n".to_string(),
-                stderr: String::new(),
-                exit_code: 0,
                 duration_ms: 3,
+                ..ExecutionResult::default()
             },
         );
 
         let mut step = Step {
             script: "echo 'Result: 42'".to_string(),
-            interpreter: "bash".to_string(),
+            interpreter: Some("bash".to_string()),
             ..Step {
                 name: None,
                 timeout: 60,
                 inputs: HashMap::new(),
-                interpreter: "bash".to_string(),
+                interpreter: Some("bash".to_string()),
                 script: String::new(),
                 outputs: HashMap::new(),
+                env: HashMap::new(),
+                retry: None,
+                continue_on_error: false,
+                retries: 0,
+                retry_delay_ms: 0,
+                success_exit_codes: vec![0],
+                allow_nonzero_exit: false,
+                workdir: None,
+                foreach: None,
+                capture: crate::step::Capture::Separate,
+                image: None,
+                interpreter_args: None,
+                stdin: None,
+                on_failure: None,
+                artifacts: HashMap::new(),
+                max_output_bytes: None,
+                quote: crate::step::Quote::None,
+                override_: false,
+                template: None,
+                script_file: None,
+                matrix: IndexMap::new(),
+                needs: Vec::new(),
+                timeout_signal: None,
+                timeout_grace_ms: None,
             }
         };
         step.outputs.insert(
@@ -1195,14 +2550,35 @@ script: echo hello
             Output {
                 pattern: r"Result: (\d+)".to_string(),
                 type_: DataType::Int,
+                group: None,
+                json_path: None,
+                key: None,
+                source: OutputSource::Stdout,
+                multiple: false,
+                optional: false,
+                delimiter: None,
+                default: None,
+                regex_cache: RegexCache::default(),
             },
         );
 
         let inputs = HashMap::new();
-        let result = step.run(&mock, &inputs, 60, &test_bash_interpreter());
+        let result = step.run(
+            &mock,
+            &inputs,
+            &HashMap::new(),
+            &HashMap::new(),
+            None,
+            60,
+            &KillPolicy::default(),
+            &test_bash_interpreter(),
+        );
 
         assert_eq!(result.exit_code, 0);
-        assert_eq!(result.outputs.get("value").unwrap(), "42");
+        assert_eq!(
+            result.typed_outputs.get("value").unwrap(),
+            &serde_json::json!(42)
+        );
         // The matched portion should be removed from stdout, empty stdout becomes None
         assert_eq!(result.stdout.as_deref(), None);
     }
@@ -1214,19 +2590,52 @@ script: echo hello
 
         let step = Step {
             script: "exit 1".to_string(),
-            interpreter: "bash".to_string(),
+            interpreter: Some("bash".to_string()),
             ..Step {
                 name: None,
                 timeout: 60,
                 inputs: HashMap::new(),
-                interpreter: "bash".to_string(),
+                interpreter: Some("bash".to_string()),
                 script: String::new(),
                 outputs: HashMap::new(),
+                env: HashMap::new(),
+                retry: None,
+                continue_on_error: false,
+                retries: 0,
+                retry_delay_ms: 0,
+                success_exit_codes: vec![0],
+                allow_nonzero_exit: false,
+                workdir: None,
+                foreach: None,
+                capture: crate::step::Capture::Separate,
+                image: None,
+                interpreter_args: None,
+                stdin: None,
+                on_failure: None,
+                artifacts: HashMap::new(),
+                max_output_bytes: None,
+                quote: crate::step::Quote::None,
+                override_: false,
+                template: None,
+                script_file: None,
+                matrix: IndexMap::new(),
+                needs: Vec::new(),
+                timeout_signal: None,
+                timeout_grace_ms: None,
             }
         };
 
         let inputs = HashMap::new();
-        let result = step.run(&mock, &inputs, 60, &test_bash_interpreter());
+        let result = step.run(
+            &mock,
+            &inputs,
+            &HashMap::new(),
+            &HashMap::new(),
+            None,
+            60,
+            &KillPolicy::default(),
+            &test_bash_interpreter(),
+        );
 
         assert_eq!(result.exit_code, 1);
         assert_eq!(result.stderr.as_deref(), Some("command failed"));
@@ -1239,27 +2648,59 @@ script: echo hello
             "print('hello')",
             ExecutionResult {
                 stdout: "hello\n".to_string(),
-                stderr: String::new(),
-                exit_code: 0,
                 duration_ms: 15,
+                ..ExecutionResult::default()
             },
         );
 
         let step = Step {
             script: "print('hello')".to_string(),
-            interpreter: "python".to_string(),
+            interpreter: Some("python".to_string()),
             ..Step {
                 name: None,
                 timeout: 60,
                 inputs: HashMap::new(),
-                interpreter: "bash".to_string(),
+                interpreter: Some("bash".to_string()),
                 script: String::new(),
                 outputs: HashMap::new(),
+                env: HashMap::new(),
+                retry: None,
+                continue_on_error: false,
+                retries: 0,
+                retry_delay_ms: 0,
+                success_exit_codes: vec![0],
+                allow_nonzero_exit: false,
+                workdir: None,
+                foreach: None,
+                capture: crate::step::Capture::Separate,
+                image: None,
+                interpreter_args: None,
+                stdin: None,
+                on_failure: None,
+                artifacts: HashMap::new(),
+                max_output_bytes: None,
+                quote: crate::step::Quote::None,
+                override_: false,
+                template: None,
+                script_file: None,
+                matrix: IndexMap::new(),
+                needs: Vec::new(),
+                timeout_signal: None,
+                timeout_grace_ms: None,
             }
         };
 
         let inputs = HashMap::new();
-        let result = step.run(&mock, &inputs, 60, &test_python_interpreter());
+        let result = step.run(
+            &mock,
+            &inputs,
+            &HashMap::new(),
+            &HashMap::new(),
+            None,
+            60,
+            &KillPolicy::default(),
+            &test_python_interpreter(),
+        );
 
         assert_eq!(result.exit_code, 0);
 
@@ -1276,29 +2717,30 @@ script: echo hello
             "echo 'Name: Alice' && echo 'Age: 30'",
             ExecutionResult {
                 stdout: "Name: Alice\nAge: 30\n".to_string(),
-                stderr: String::new(),
-                exit_code: 0,
                 duration_ms: 12,
+                ..ExecutionResult::default()
             },
         );
 
         let mut step = Step {
             script: "echo 'Name: {{ inputs.name }}' && echo 'Age: {{ inputs.age }}'".to_string(),
-            interpreter: "bash".to_string(),
-            ..Step {
-                name: None,
-                timeout: 60,
-                inputs: HashMap::new(),
-                interpreter: "bash".to_string(),
-                script: String::new(),
-                outputs: HashMap::new(),
-            }
+            interpreter: Some("bash".to_string()),
+            ..Step::new("bash")
         };
         step.outputs.insert(
             "person_name".to_string(),
             Output {
                 pattern: r"Name: (\w+)".to_string(),
                 type_: DataType::String,
+                group: None,
+                json_path: None,
+                key: None,
+                source: OutputSource::Stdout,
+                multiple: false,
+                optional: false,
+                delimiter: None,
+                default: None,
+                regex_cache: RegexCache::default(),
             },
         );
         step.outputs.insert(
@@ -1306,6 +2748,15 @@ script: echo hello
             Output {
                 pattern: r"Age: (\d+)".to_string(),
                 type_: DataType::Int,
+                group: None,
+                json_path: None,
+                key: None,
+                source: OutputSource::Stdout,
+                multiple: false,
+                optional: false,
+                delimiter: None,
+                default: None,
+                regex_cache: RegexCache::default(),
             },
         );
 
@@ -1313,7 +2764,16 @@ script: echo hello
         inputs.insert("name".to_string(), "Alice".to_string());
         inputs.insert("age".to_string(), "30".to_string());
 
-        let result = step.run(&mock, &inputs, 60, &test_bash_interpreter());
+        let result = step.run(
+            &mock,
+            &inputs,
+            &HashMap::new(),
+            &HashMap::new(),
+            None,
+            60,
+            &KillPolicy::default(),
+            &test_bash_interpreter(),
+        );
 
         assert_eq!(result.exit_code, 0);
         assert_eq!(
@@ -1347,22 +2807,55 @@ script: echo hello
         // Test the run() method that uses SystemExecutor internally
         let step = Step {
             name: Some("system_test".to_string()),
-            interpreter: "bash".to_string(),
+            interpreter: Some("bash".to_string()),
             script: "echo 'test output'".to_string(),
             timeout: 30,
             ..Step {
                 name: None,
                 timeout: 60,
                 inputs: HashMap::new(),
-                interpreter: "bash".to_string(),
+                interpreter: Some("bash".to_string()),
                 script: String::new(),
                 outputs: HashMap::new(),
+                env: HashMap::new(),
+                retry: None,
+                continue_on_error: false,
+                retries: 0,
+                retry_delay_ms: 0,
+                success_exit_codes: vec![0],
+                allow_nonzero_exit: false,
+                workdir: None,
+                foreach: None,
+                capture: crate::step::Capture::Separate,
+                image: None,
+                interpreter_args: None,
+                stdin: None,
+                on_failure: None,
+                artifacts: HashMap::new(),
+                max_output_bytes: None,
+                quote: crate::step::Quote::None,
+                override_: false,
+                template: None,
+                script_file: None,
+                matrix: IndexMap::new(),
+                needs: Vec::new(),
+                timeout_signal: None,
+                timeout_grace_ms: None,
             }
         };
 
         let inputs = HashMap::new();
         let executor = crate::executor::SystemExecutor;
-        let result = step.run(&executor, &inputs, 60, &test_bash_interpreter());
+        let result = step.run(
+            &executor,
+            &inputs,
+            &HashMap::new(),
+            &HashMap::new(),
+            None,
+            60,
+            &KillPolicy::default(),
+            &test_bash_interpreter(),
+        );
 
         // Should succeed - step.run() now returns StepResult directly
         assert_eq!(result.name, Some("system_test".to_string()));
@@ -1377,27 +2870,60 @@ script: echo hello
             ExecutionResult {
                 stdout: "  test  ".to_string(),
                 stderr: "  error  ".to_string(),
-                exit_code: 0,
                 duration_ms: 10,
+                ..ExecutionResult::default()
             },
         );
 
         let step = Step {
             name: Some("filter_test".to_string()),
-            interpreter: "bash".to_string(),
+            interpreter: Some("bash".to_string()),
             script: "echo test".to_string(),
             timeout: 30,
             ..Step {
                 name: None,
                 timeout: 60,
                 inputs: HashMap::new(),
-                interpreter: "bash".to_string(),
+                interpreter: Some("bash".to_string()),
                 script: String::new(),
                 outputs: HashMap::new(),
+                env: HashMap::new(),
+                retry: None,
+                continue_on_error: false,
+                retries: 0,
+                retry_delay_ms: 0,
+                success_exit_codes: vec![0],
+                allow_nonzero_exit: false,
+                workdir: None,
+                foreach: None,
+                capture: crate::step::Capture::Separate,
+                image: None,
+                interpreter_args: None,
+                stdin: None,
+                on_failure: None,
+                artifacts: HashMap::new(),
+                max_output_bytes: None,
+                quote: crate::step::Quote::None,
+                override_: false,
+                template: None,
+                script_file: None,
+                matrix: IndexMap::new(),
+                needs: Vec::new(),
+                timeout_signal: None,
+                timeout_grace_ms: None,
             }
         };
 
-        let result = step.run(&mock, &HashMap::new(), 60, &test_bash_interpreter());
+        let result = step.run(
+            &mock,
+            &HashMap::new(),
+            &HashMap::new(),
+            &HashMap::new(),
+            None,
+            60,
+            &KillPolicy::default(),
+            &test_bash_interpreter(),
+        );
 
         // Should trim whitespace from stdout and stderr
         assert_eq!(result.stdout, Some("test".to_string()));
@@ -1410,29 +2936,60 @@ script: echo hello
         mock.expect_call(
             "echo",
             ExecutionResult {
-                stdout: String::new(),
-                stderr: String::new(),
-                exit_code: 0,
                 duration_ms: 5,
+                ..ExecutionResult::default()
             },
         );
 
         let step = Step {
             name: Some("empty_test".to_string()),
-            interpreter: "bash".to_string(),
+            interpreter: Some("bash".to_string()),
             script: "echo".to_string(),
             timeout: 30,
             ..Step {
                 name: None,
                 timeout: 60,
                 inputs: HashMap::new(),
-                interpreter: "bash".to_string(),
+                interpreter: Some("bash".to_string()),
                 script: String::new(),
                 outputs: HashMap::new(),
+                env: HashMap::new(),
+                retry: None,
+                continue_on_error: false,
+                retries: 0,
+                retry_delay_ms: 0,
+                success_exit_codes: vec![0],
+                allow_nonzero_exit: false,
+                workdir: None,
+                foreach: None,
+                capture: crate::step::Capture::Separate,
+                image: None,
+                interpreter_args: None,
+                stdin: None,
+                on_failure: None,
+                artifacts: HashMap::new(),
+                max_output_bytes: None,
+                quote: crate::step::Quote::None,
+                override_: false,
+                template: None,
+                script_file: None,
+                matrix: IndexMap::new(),
+                needs: Vec::new(),
+                timeout_signal: None,
+                timeout_grace_ms: None,
             }
         };
 
-        let result = step.run(&mock, &HashMap::new(), 60, &test_bash_interpreter());
+        let result = step.run(
+            &mock,
+            &HashMap::new(),
+            &HashMap::new(),
+            &HashMap::new(),
+            None,
+            60,
+            &KillPolicy::default(),
+            &test_bash_interpreter(),
+        );
 
         // Empty strings should be filtered to None
         assert_eq!(result.stdout, None);
@@ -1446,32 +3003,1931 @@ script: echo hello
             "print('test')",
             ExecutionResult {
                 stdout: "test".to_string(),
-                stderr: String::new(),
-                exit_code: 0,
                 duration_ms: 8,
+                ..ExecutionResult::default()
             },
         );
 
         let step = Step {
             name: Some("args_test".to_string()),
-            interpreter: "python".to_string(),
+            interpreter: Some("python".to_string()),
             script: "print('test')".to_string(),
             timeout: 30,
             ..Step {
                 name: None,
                 timeout: 60,
                 inputs: HashMap::new(),
-                interpreter: "bash".to_string(),
+                interpreter: Some("bash".to_string()),
                 script: String::new(),
                 outputs: HashMap::new(),
+                env: HashMap::new(),
+                retry: None,
+                continue_on_error: false,
+                retries: 0,
+                retry_delay_ms: 0,
+                success_exit_codes: vec![0],
+                allow_nonzero_exit: false,
+                workdir: None,
+                foreach: None,
+                capture: crate::step::Capture::Separate,
+                image: None,
+                interpreter_args: None,
+                stdin: None,
+                on_failure: None,
+                artifacts: HashMap::new(),
+                max_output_bytes: None,
+                quote: crate::step::Quote::None,
+                override_: false,
+                template: None,
+                script_file: None,
+                matrix: IndexMap::new(),
+                needs: Vec::new(),
+                timeout_signal: None,
+                timeout_grace_ms: None,
             }
         };
 
-        let _result = step.run(&mock, &HashMap::new(), 60, &test_python_interpreter());
+        let _result = step.run(
+            &mock,
+            &HashMap::new(),
+            &HashMap::new(),
+            &HashMap::new(),
+            None,
+            60,
+            &KillPolicy::default(),
+            &test_python_interpreter(),
+        );
 
         // Verify that Python interpreter was properly used
         let (_, interpreter, _) = mock.last_call().unwrap();
         assert_eq!(interpreter.extension, ".py");
         assert_eq!(interpreter.command, "python3");
     }
+
+    fn failing_result() -> ExecutionResult {
+        ExecutionResult {
+            stderr: "connection refused".to_string(),
+            exit_code: 1,
+            duration_ms: 5,
+            ..ExecutionResult::default()
+        }
+    }
+
+    fn retrying_step(attempts: u32) -> Step {
+        let mut step = Step::new("bash");
+        step.script = "curl example.com".to_string();
+        step.retry = Some(RetryPolicy {
+            attempts,
+            delay_secs: 0,
+            backoff: Backoff::Fixed,
+            delay_ms: 0,
+        });
+        step
+    }
+
+    #[test]
+    fn test_run_retries_until_success() {
+        let mut mock = MockExecutor::new();
+        mock.expect_calls(
+            "curl example.com",
+            vec![
+                failing_result(),
+                failing_result(),
+                ExecutionResult {
+                    stdout: "ok".to_string(),
+                    duration_ms: 5,
+                    ..ExecutionResult::default()
+                },
+            ],
+        );
+
+        let step = retrying_step(5);
+        let result = step.run(
+            &mock,
+            &HashMap::new(),
+            &HashMap::new(),
+            &HashMap::new(),
+            None,
+            60,
+            &KillPolicy::default(),
+            &test_bash_interpreter(),
+        );
+
+        assert_eq!(mock.call_count(), 3);
+        assert_eq!(result.exit_code, 0);
+        assert_eq!(result.attempts, 3);
+        assert_eq!(result.attempt_history.len(), 3);
+        assert_eq!(result.attempt_history[0].exit_code, 1);
+        assert_eq!(result.attempt_history[2].exit_code, 0);
+    }
+
+    #[test]
+    fn test_run_retries_exhausted() {
+        let mut mock = MockExecutor::new();
+        mock.expect_call("curl example.com", failing_result());
+
+        let step = retrying_step(3);
+        let result = step.run(
+            &mock,
+            &HashMap::new(),
+            &HashMap::new(),
+            &HashMap::new(),
+            None,
+            60,
+            &KillPolicy::default(),
+            &test_bash_interpreter(),
+        );
+
+        assert_eq!(mock.call_count(), 3);
+        assert_eq!(result.exit_code, 1);
+        assert_eq!(result.attempts, 3);
+
+        let json = serde_json::to_string(&result).unwrap();
+        assert!(json.contains("\"attempts\":3"));
+        assert!(json.contains("\"attempt_history\""));
+    }
+
+    #[test]
+    fn test_run_retries_on_output_extraction_failure() {
+        let mut mock = MockExecutor::new();
+        mock.expect_calls(
+            "curl example.com",
+            vec![ExecutionResult {
+                stdout: "no match".to_string(),
+                duration_ms: 5,
+                ..ExecutionResult::default()
+            }],
+        );
+        mock.expect_call(
+            "curl example.com",
+            ExecutionResult {
+                stdout: "STATUS=up".to_string(),
+                duration_ms: 5,
+                ..ExecutionResult::default()
+            },
+        );
+
+        let mut step = retrying_step(2);
+        step.outputs.insert(
+            "status".to_string(),
+            Output {
+                pattern: "STATUS=(.*)".to_string(),
+                type_: DataType::String,
+                group: None,
+                json_path: None,
+                key: None,
+                source: OutputSource::Stdout,
+                multiple: false,
+                optional: false,
+                delimiter: None,
+                default: None,
+                regex_cache: RegexCache::default(),
+            },
+        );
+
+        let result = step.run(
+            &mock,
+            &HashMap::new(),
+            &HashMap::new(),
+            &HashMap::new(),
+            None,
+            60,
+            &KillPolicy::default(),
+            &test_bash_interpreter(),
+        );
+
+        assert_eq!(mock.call_count(), 2);
+        assert!(result.error.is_none());
+        assert_eq!(result.outputs["status"], "up");
+    }
+
+    #[test]
+    fn test_run_without_retry_policy_runs_once() {
+        let mut mock = MockExecutor::new();
+        mock.expect_call("curl example.com", failing_result());
+
+        let mut step = Step::new("bash");
+        step.script = "curl example.com".to_string();
+        let result = step.run(
+            &mock,
+            &HashMap::new(),
+            &HashMap::new(),
+            &HashMap::new(),
+            None,
+            60,
+            &KillPolicy::default(),
+            &test_bash_interpreter(),
+        );
+
+        assert_eq!(mock.call_count(), 1);
+        assert_eq!(result.attempts, 1);
+        assert!(result.attempt_history.is_empty());
+
+        let json = serde_json::to_string(&result).unwrap();
+        assert!(!json.contains("attempts"));
+    }
+
+    #[test]
+    fn test_validate_retry_zero_attempts() {
+        let step = retrying_step(0);
+        let result = step.validate("retry_step");
+        assert!(matches!(result, Err(AtentoError::Validation(msg)) if msg.contains("at least 1")));
+    }
+
+    #[test]
+    fn test_validate_retry_delay_exceeds_timeout() {
+        let mut step = retrying_step(2);
+        step.timeout = 10;
+        step.retry.as_mut().unwrap().delay_secs = 30;
+        let result = step.validate("retry_step");
+        assert!(
+            matches!(result, Err(AtentoError::Validation(msg)) if msg.contains("exceeds the step timeout"))
+        );
+    }
+
+    #[test]
+    fn test_run_retries_shorthand() {
+        let yaml = r"
+type: bash
+script: curl example.com
+retries: 2
+retry_delay_ms: 1
+";
+        let step: Step = serde_yaml::from_str(yaml).unwrap();
+        let policy = step.retry_policy();
+        assert_eq!(policy.attempts, 3);
+        assert_eq!(policy.delay_ms, 1);
+
+        let mut mock = MockExecutor::new();
+        mock.expect_call("curl example.com", failing_result());
+
+        let result = step.run(
+            &mock,
+            &HashMap::new(),
+            &HashMap::new(),
+            &HashMap::new(),
+            None,
+            60,
+            &KillPolicy::default(),
+            &test_bash_interpreter(),
+        );
+
+        assert_eq!(mock.call_count(), 3);
+        assert_eq!(result.attempts, 3);
+    }
+
+    #[test]
+    fn test_retry_policy_defaults_to_single_attempt() {
+        let step = Step::new("bash");
+        assert_eq!(step.retry_policy().attempts, 1);
+    }
+
+    #[test]
+    fn test_validate_retry_block_and_shorthand_conflict() {
+        let mut step = retrying_step(2);
+        step.retries = 1;
+        let result = step.validate("retry_step");
+        assert!(
+            matches!(result, Err(AtentoError::Validation(msg)) if msg.contains("cannot combine"))
+        );
+    }
+
+    #[test]
+    fn test_render_script_with_parameters() {
+        let mut step = Step::new("bash");
+        step.script =
+            "deploy {{ inputs.app }} to {{ parameters.env }} ({{parameters.missing}})".to_string();
+
+        let mut inputs = HashMap::new();
+        inputs.insert("app".to_string(), "api".to_string());
+        let mut parameters = HashMap::new();
+        parameters.insert("env".to_string(), "prod".to_string());
+
+        assert_eq!(
+            step.render_script(&inputs, &parameters),
+            "deploy api to prod ({{parameters.missing}})"
+        );
+        assert_eq!(
+            step.build_script(&inputs),
+            "deploy api to {{ parameters.env }} ({{parameters.missing}})"
+        );
+        assert_eq!(step.parameter_refs(), vec!["env", "missing"]);
+    }
+
+    #[test]
+    fn test_validate_allows_parameter_placeholders() {
+        let mut step = Step::new("bash");
+        step.script = "echo {{ parameters.name }}".to_string();
+        assert!(step.validate("step1").is_ok());
+    }
+
+    #[test]
+    fn test_run_nonzero_exit_fails_step() {
+        let mut mock = MockExecutor::new();
+        mock.expect_error("exit 3", 3, "");
+
+        let mut step = Step::new("bash");
+        step.script = "exit 3".to_string();
+        let result = step.run(
+            &mock,
+            &HashMap::new(),
+            &HashMap::new(),
+            &HashMap::new(),
+            None,
+            60,
+            &KillPolicy::default(),
+            &test_bash_interpreter(),
+        );
+
+        assert_eq!(result.exit_code, 3);
+        assert!(
+            matches!(result.error, Some(AtentoError::Execution(msg)) if msg.contains("exited with code 3"))
+        );
+    }
+
+    #[test]
+    fn test_run_success_exit_codes_and_allow_nonzero() {
+        let mut mock = MockExecutor::new();
+        mock.expect_error("exit 3", 3, "");
+
+        let mut step = Step::new("bash");
+        step.script = "exit 3".to_string();
+        step.success_exit_codes = vec![0, 3];
+        let result = step.run(
+            &mock,
+            &HashMap::new(),
+            &HashMap::new(),
+            &HashMap::new(),
+            None,
+            60,
+            &KillPolicy::default(),
+            &test_bash_interpreter(),
+        );
+        assert!(result.error.is_none());
+
+        step.success_exit_codes = vec![0];
+        step.allow_nonzero_exit = true;
+        let result = step.run(
+            &mock,
+            &HashMap::new(),
+            &HashMap::new(),
+            &HashMap::new(),
+            None,
+            60,
+            &KillPolicy::default(),
+            &test_bash_interpreter(),
+        );
+        assert!(result.error.is_none());
+        assert_eq!(result.exit_code, 3);
+    }
+
+    #[test]
+    fn test_validate_empty_success_exit_codes() {
+        let mut step = Step::new("bash");
+        step.script = "echo hi".to_string();
+        step.success_exit_codes = vec![];
+        let result = step.validate("step1");
+        assert!(
+            matches!(result, Err(AtentoError::Validation(msg)) if msg.contains("success_exit_codes"))
+        );
+    }
+
+    #[test]
+    fn test_success_exit_codes_default() {
+        let step: Step = serde_yaml::from_str("type: bash\nscript: echo hi\n").unwrap();
+        assert_eq!(step.success_exit_codes, vec![0]);
+        assert!(!step.allow_nonzero_exit);
+    }
+
+    #[test]
+    fn test_validate_input_used_only_in_env() {
+        use crate::env::EnvValue;
+
+        let mut step = Step::new("bash");
+        step.script = "echo \"$TOKEN\"".to_string();
+        step.inputs.insert(
+            "token".to_string(),
+            Input::Ref {
+                ref_: "parameters.token".to_string(),
+            },
+        );
+        step.env.insert(
+            "TOKEN".to_string(),
+            EnvValue::Template("{{ inputs.token }}".to_string()),
+        );
+        assert!(step.validate("step1").is_ok());
+
+        step.env.insert(
+            "OTHER".to_string(),
+            EnvValue::Template("{{ parameters.other }}".to_string()),
+        );
+        assert_eq!(step.parameter_refs(), vec!["other"]);
+    }
+
+    #[test]
+    fn test_extract_outputs_type_conversion_error() {
+        let mut step = Step::new("bash");
+        step.outputs.insert(
+            "count".to_string(),
+            Output {
+                pattern: r"Count: (\w+)".to_string(),
+                type_: DataType::Int,
+                group: None,
+                json_path: None,
+                key: None,
+                source: OutputSource::Stdout,
+                multiple: false,
+                optional: false,
+                delimiter: None,
+                default: None,
+                regex_cache: RegexCache::default(),
+            },
+        );
+
+        let mut stdout = "Count: many".to_string();
+        let err = step
+            .extract_outputs(&mut stdout, &mut String::new(), 0)
+            .unwrap_err();
+        match err {
+            AtentoError::TypeConversion { expected, got } => {
+                assert_eq!(expected, "int");
+                assert!(got.contains("'many'"));
+                assert!(got.contains("output 'count'"));
+            }
+            other => panic!("Expected TypeConversion error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_extract_outputs_typed_bool() {
+        let mut step = Step::new("bash");
+        step.outputs.insert(
+            "ready".to_string(),
+            Output {
+                pattern: r"Ready: (\d)".to_string(),
+                type_: DataType::Bool,
+                group: None,
+                json_path: None,
+                key: None,
+                source: OutputSource::Stdout,
+                multiple: false,
+                optional: false,
+                delimiter: None,
+                default: None,
+                regex_cache: RegexCache::default(),
+            },
+        );
+
+        let mut stdout = "Ready: 1".to_string();
+        let outputs = step
+            .extract_outputs(&mut stdout, &mut String::new(), 0)
+            .unwrap();
+        assert_eq!(outputs["ready"], serde_json::json!(true));
+    }
+
+    #[test]
+    fn test_extract_outputs_named_group() {
+        let mut step = Step::new("bash");
+        step.outputs.insert(
+            "version".to_string(),
+            Output {
+                pattern: r"(\w+) v(?P<version>\d+\.\d+)".to_string(),
+                type_: DataType::String,
+                group: Some("version".to_string()),
+                json_path: None,
+                key: None,
+                source: OutputSource::Stdout,
+                multiple: false,
+                optional: false,
+                delimiter: None,
+                default: None,
+                regex_cache: RegexCache::default(),
+            },
+        );
+
+        let mut stdout = "atento v1.2 ready".to_string();
+        let outputs = step
+            .extract_outputs(&mut stdout, &mut String::new(), 0)
+            .unwrap();
+        assert_eq!(outputs["version"], "1.2");
+        assert_eq!(stdout, " ready");
+    }
+
+    #[test]
+    fn test_extract_outputs_missing_named_group() {
+        let mut step = Step::new("bash");
+        step.outputs.insert(
+            "version".to_string(),
+            Output {
+                pattern: r"v(\d+)".to_string(),
+                type_: DataType::String,
+                group: Some("version".to_string()),
+                json_path: None,
+                key: None,
+                source: OutputSource::Stdout,
+                multiple: false,
+                optional: false,
+                delimiter: None,
+                default: None,
+                regex_cache: RegexCache::default(),
+            },
+        );
+
+        let mut stdout = "v1".to_string();
+        let err = step
+            .extract_outputs(&mut stdout, &mut String::new(), 0)
+            .unwrap_err();
+        assert!(err.to_string().contains("no capture group named 'version'"));
+    }
+
+    #[test]
+    fn test_validate_unknown_named_group_fails() {
+        let mut step = Step::new("bash");
+        step.script = "echo v1".to_string();
+        step.outputs.insert(
+            "version".to_string(),
+            Output {
+                pattern: r"v(?P<major>\d+)".to_string(),
+                type_: DataType::String,
+                group: Some("version".to_string()),
+                json_path: None,
+                key: None,
+                source: OutputSource::Stdout,
+                multiple: false,
+                optional: false,
+                delimiter: None,
+                default: None,
+                regex_cache: RegexCache::default(),
+            },
+        );
+
+        let err = step.validate("build").unwrap_err();
+        assert!(matches!(err, AtentoError::Validation(_)));
+        assert!(err.to_string().contains("capture group 'version'"));
+
+        step.outputs.get_mut("version").unwrap().group = Some("major".to_string());
+        assert!(step.validate("build").is_ok());
+    }
+
+    fn json_output(path: &str, type_: DataType) -> Output {
+        Output {
+            pattern: String::new(),
+            type_,
+            group: None,
+            json_path: Some(path.to_string()),
+            key: None,
+            source: OutputSource::Stdout,
+            multiple: false,
+            optional: false,
+            delimiter: None,
+            default: None,
+            regex_cache: RegexCache::default(),
+        }
+    }
+
+    #[test]
+    fn test_extract_outputs_json_path() {
+        let mut step = Step::new("bash");
+        step.outputs.insert(
+            "version".to_string(),
+            json_output("$.result.version", DataType::String),
+        );
+        step.outputs.insert(
+            "count".to_string(),
+            json_output("$.result.items[1].count", DataType::Int),
+        );
+
+        let mut stdout =
+            r#"{"result": {"version": "1.4.2", "items": [{"count": 1}, {"count": 7}]}}"#
+                .to_string();
+        let outputs = step
+            .extract_outputs(&mut stdout, &mut String::new(), 0)
+            .unwrap();
+        assert_eq!(outputs["version"], "1.4.2");
+        assert_eq!(outputs["count"], serde_json::json!(7));
+    }
+
+    #[test]
+    fn test_extract_outputs_json_path_unresolved() {
+        let mut step = Step::new("bash");
+        step.outputs.insert(
+            "version".to_string(),
+            json_output("$.result.missing", DataType::String),
+        );
+
+        let mut stdout = r#"{"result": {}}"#.to_string();
+        let err = step
+            .extract_outputs(&mut stdout, &mut String::new(), 0)
+            .unwrap_err();
+        assert!(matches!(err, AtentoError::Execution(_)));
+        let message = err.to_string();
+        assert!(message.contains("'version'"));
+        assert!(message.contains("$.result.missing"));
+        assert!(message.contains("was not found in stdout"));
+
+        let mut stdout = "not json".to_string();
+        let err = step
+            .extract_outputs(&mut stdout, &mut String::new(), 0)
+            .unwrap_err();
+        assert!(err.to_string().contains("could not parse stdout as JSON"));
+    }
+
+    #[test]
+    fn test_extract_outputs_json_path_dotted_last_line_and_lists() {
+        let mut step = Step::new("bash");
+        step.outputs.insert(
+            "version".to_string(),
+            json_output("build.version", DataType::String),
+        );
+        step.outputs
+            .insert("ok".to_string(), json_output("$.build.ok", DataType::Bool));
+        step.outputs.insert(
+            "tags".to_string(),
+            json_output("build.tags", DataType::List),
+        );
+
+        let mut stdout = "compiling...\nwarning: unused\n\n{\"build\": {\"version\": \"2.0.1\", \"ok\": true, \"tags\": [\"x\", 3]}}\n".to_string();
+        let outputs = step
+            .extract_outputs(&mut stdout, &mut String::new(), 0)
+            .unwrap();
+        assert_eq!(outputs["version"], "2.0.1");
+        assert_eq!(outputs["ok"], serde_json::json!(true));
+        assert_eq!(outputs["tags"], serde_json::json!(["x", "3"]));
+
+        step.outputs.clear();
+        step.outputs.insert(
+            "tags".to_string(),
+            json_output("build.tags", DataType::String),
+        );
+        let err = step
+            .extract_outputs(&mut stdout, &mut String::new(), 0)
+            .unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("did not resolve to a scalar value")
+        );
+
+        let mut stdout = "{\"build\": \n not json".to_string();
+        let err = step
+            .extract_outputs(&mut stdout, &mut String::new(), 0)
+            .unwrap_err();
+        assert!(err.to_string().contains("(nor its last line)"));
+    }
+
+    fn key_output(key: &str, type_: DataType) -> Output {
+        Output {
+            key: Some(key.to_string()),
+            type_,
+            ..Output::default()
+        }
+    }
+
+    #[test]
+    fn test_extract_outputs_key_value_lines() {
+        let mut step = Step::new("bash");
+        step.outputs
+            .insert("dir".to_string(), key_output("BUILD_DIR", DataType::String));
+        step.outputs
+            .insert("count".to_string(), key_output("FILE_COUNT", DataType::Int));
+
+        let mut stdout =
+            "building\nBUILD_DIR=/tmp/out\nFILE_COUNT=3\nMY_BUILD_DIR=/elsewhere\ndone\n"
+                .to_string();
+        let outputs = step
+            .extract_outputs(&mut stdout, &mut String::new(), 0)
+            .unwrap();
+        assert_eq!(outputs["dir"], "/tmp/out");
+        assert_eq!(outputs["count"], serde_json::json!(3));
+        assert_eq!(stdout, "building\nMY_BUILD_DIR=/elsewhere\ndone\n");
+    }
+
+    #[test]
+    fn test_extract_outputs_key_last_occurrence_wins() {
+        let mut step = Step::new("bash");
+        step.outputs.insert(
+            "version".to_string(),
+            key_output("VERSION", DataType::String),
+        );
+
+        let mut stdout = "VERSION=1.0\nstep two\nVERSION=1.1\r\nVERSION=2.0".to_string();
+        let outputs = step
+            .extract_outputs(&mut stdout, &mut String::new(), 0)
+            .unwrap();
+        assert_eq!(outputs["version"], "2.0");
+        assert_eq!(stdout, "step two\n");
+
+        let mut stdout = "nothing here\n".to_string();
+        let err = step
+            .extract_outputs(&mut stdout, &mut String::new(), 0)
+            .unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("key 'VERSION' was not found in stdout")
+        );
+    }
+
+    #[test]
+    fn test_validate_output_key() {
+        let mut step = Step::new("bash");
+        step.script = "echo VERSION=1".to_string();
+        step.outputs.insert(
+            "version".to_string(),
+            key_output("VERSION", DataType::String),
+        );
+        assert!(step.validate("build").is_ok());
+
+        step.outputs.get_mut("version").unwrap().pattern = r"VERSION=(\d+)".to_string();
+        let err = step.validate("build").unwrap_err();
+        assert!(
+            err.to_string()
+                .contains("cannot set 'key' together with 'pattern' or 'json_path'")
+        );
+
+        let output = step.outputs.get_mut("version").unwrap();
+        output.pattern = String::new();
+        output.json_path = Some("$.version".to_string());
+        assert!(step.validate("build").is_err());
+
+        for invalid in ["", " ", "A=B"] {
+            let output = step.outputs.get_mut("version").unwrap();
+            output.json_path = None;
+            output.key = Some(invalid.to_string());
+            let err = step.validate("build").unwrap_err();
+            assert!(err.to_string().contains("invalid key"), "{invalid}");
+        }
+
+        let output = step.outputs.get_mut("version").unwrap();
+        output.key = Some("VERSION".to_string());
+        output.source = OutputSource::ExitCode;
+        assert!(step.validate("build").is_err());
+    }
+
+    #[test]
+    fn test_validate_output_extraction_modes() {
+        let mut step = Step::new("bash");
+        step.script = "echo '{}'".to_string();
+        step.outputs.insert(
+            "version".to_string(),
+            json_output("$.version", DataType::String),
+        );
+        assert!(step.validate("build").is_ok());
+
+        step.outputs.get_mut("version").unwrap().pattern = r"v(\d+)".to_string();
+        let err = step.validate("build").unwrap_err();
+        assert!(err.to_string().contains("both 'pattern' and 'json_path'"));
+
+        let output = step.outputs.get_mut("version").unwrap();
+        output.pattern = String::new();
+        output.json_path = None;
+        assert!(step.validate("build").is_err());
+
+        step.outputs.get_mut("version").unwrap().json_path = Some("$version".to_string());
+        let err = step.validate("build").unwrap_err();
+        assert!(err.to_string().contains("invalid JSON path"));
+    }
+
+    #[test]
+    fn test_run_with_mock_executor_output_from_stderr() {
+        let mut mock = MockExecutor::new();
+        mock.expect_call(
+            "git --version",
+            ExecutionResult {
+                stdout: "done".to_string(),
+                stderr: "git version 2.43.0\n".to_string(),
+                duration_ms: 5,
+                ..ExecutionResult::default()
+            },
+        );
+
+        let mut step = Step::new("bash");
+        step.script = "git --version".to_string();
+        step.outputs.insert(
+            "version".to_string(),
+            Output {
+                pattern: r"git version (\S+)".to_string(),
+                type_: DataType::String,
+                group: None,
+                json_path: None,
+                key: None,
+                source: OutputSource::Stderr,
+                multiple: false,
+                optional: false,
+                delimiter: None,
+                default: None,
+                regex_cache: RegexCache::default(),
+            },
+        );
+
+        let result = step.run(
+            &mock,
+            &HashMap::new(),
+            &HashMap::new(),
+            &HashMap::new(),
+            None,
+            60,
+            &KillPolicy::default(),
+            &test_bash_interpreter(),
+        );
+
+        assert!(result.error.is_none());
+        assert_eq!(result.outputs["version"], "2.43.0");
+        assert_eq!(result.stdout.as_deref(), Some("done"));
+        assert_eq!(result.stderr.as_deref(), Some("\n"));
+    }
+
+    #[test]
+    fn test_extract_outputs_source_selection() {
+        let mut step = Step::new("bash");
+        step.outputs.insert(
+            "version".to_string(),
+            Output {
+                pattern: r"version (\S+)".to_string(),
+                type_: DataType::String,
+                group: None,
+                json_path: None,
+                key: None,
+                source: OutputSource::Stdout,
+                multiple: false,
+                optional: false,
+                delimiter: None,
+                default: None,
+                regex_cache: RegexCache::default(),
+            },
+        );
+
+        let mut stdout = "nothing here".to_string();
+        let mut stderr = "version 1.0".to_string();
+        let err = step
+            .extract_outputs(&mut stdout, &mut stderr, 0)
+            .unwrap_err();
+        assert!(err.to_string().contains("did not match stdout"));
+
+        step.outputs.get_mut("version").unwrap().source = OutputSource::Both;
+        let outputs = step.extract_outputs(&mut stdout, &mut stderr, 0).unwrap();
+        assert_eq!(outputs["version"], "1.0");
+        assert_eq!(stdout, "nothing here");
+        assert!(stderr.is_empty());
+
+        let err = step
+            .extract_outputs(&mut stdout, &mut String::new(), 0)
+            .unwrap_err();
+        assert!(err.to_string().contains("did not match stdout or stderr"));
+    }
+
+    #[test]
+    fn test_run_with_mock_executor_exit_code_output() {
+        let mut mock = MockExecutor::new();
+        mock.expect_error("exit 3", 3, "");
+
+        let mut step = Step::new("bash");
+        step.script = "exit 3".to_string();
+        step.allow_nonzero_exit = true;
+        step.outputs.insert(
+            "code".to_string(),
+            Output {
+                pattern: String::new(),
+                type_: DataType::Int,
+                group: None,
+                json_path: None,
+                key: None,
+                source: OutputSource::ExitCode,
+                multiple: false,
+                optional: false,
+                delimiter: None,
+                default: None,
+                regex_cache: RegexCache::default(),
+            },
+        );
+
+        let result = step.run(
+            &mock,
+            &HashMap::new(),
+            &HashMap::new(),
+            &HashMap::new(),
+            None,
+            60,
+            &KillPolicy::default(),
+            &test_bash_interpreter(),
+        );
+
+        assert!(result.error.is_none());
+        assert_eq!(result.exit_code, 3);
+        assert_eq!(result.typed_outputs["code"], serde_json::json!(3));
+    }
+
+    #[test]
+    fn test_validate_exit_code_output_rejects_pattern() {
+        let mut step = Step::new("bash");
+        step.script = "exit 3".to_string();
+        step.outputs.insert(
+            "code".to_string(),
+            Output {
+                pattern: r"(\d+)".to_string(),
+                type_: DataType::Int,
+                group: None,
+                json_path: None,
+                key: None,
+                source: OutputSource::ExitCode,
+                multiple: false,
+                optional: false,
+                delimiter: None,
+                default: None,
+                regex_cache: RegexCache::default(),
+            },
+        );
+
+        let err = step.validate("check").unwrap_err();
+        assert!(err.to_string().contains("captures the exit code"));
+
+        step.outputs.get_mut("code").unwrap().pattern = String::new();
+        assert!(step.validate("check").is_ok());
+    }
+
+    fn multiple_output(pattern: &str, type_: DataType, optional: bool) -> Output {
+        Output {
+            pattern: pattern.to_string(),
+            type_,
+            group: None,
+            json_path: None,
+            key: None,
+            source: OutputSource::Stdout,
+            multiple: true,
+            optional,
+            delimiter: None,
+            default: None,
+            regex_cache: RegexCache::default(),
+        }
+    }
+
+    #[test]
+    fn test_extract_outputs_multiple_matches() {
+        let mut step = Step::new("bash");
+        step.outputs.insert(
+            "failed".to_string(),
+            multiple_output("FAILED_TEST=(.*)", DataType::String, false),
+        );
+        step.outputs.insert(
+            "codes".to_string(),
+            multiple_output(r"CODE=(\d+)", DataType::Int, false),
+        );
+
+        let mut stdout = "FAILED_TEST=a\nCODE=1\nok\nFAILED_TEST=b\nCODE=2".to_string();
+        let outputs = step
+            .extract_outputs(&mut stdout, &mut String::new(), 0)
+            .unwrap();
+
+        assert_eq!(outputs["failed"], serde_json::json!(["a", "b"]));
+        assert_eq!(outputs["codes"], serde_json::json!([1, 2]));
+        assert_eq!(stdout.trim(), "ok");
+    }
+
+    #[test]
+    fn test_extract_outputs_multiple_without_matches() {
+        let mut step = Step::new("bash");
+        step.outputs.insert(
+            "failed".to_string(),
+            multiple_output("FAILED_TEST=(.*)", DataType::String, false),
+        );
+
+        let mut stdout = "all passed".to_string();
+        let err = step
+            .extract_outputs(&mut stdout, &mut String::new(), 0)
+            .unwrap_err();
+        assert!(err.to_string().contains("did not match stdout"));
+
+        step.outputs.get_mut("failed").unwrap().optional = true;
+        let outputs = step
+            .extract_outputs(&mut stdout, &mut String::new(), 0)
+            .unwrap();
+        assert_eq!(outputs["failed"], serde_json::json!([]));
+    }
+
+    #[test]
+    fn test_validate_multiple_and_optional_flags() {
+        let mut step = Step::new("bash");
+        step.script = "echo".to_string();
+        step.outputs.insert(
+            "failed".to_string(),
+            multiple_output("FAILED_TEST=(.*)", DataType::String, true),
+        );
+        assert!(step.validate("test").is_ok());
+
+        step.outputs.get_mut("failed").unwrap().multiple = false;
+        assert!(step.validate("test").is_ok());
+
+        let output = step.outputs.get_mut("failed").unwrap();
+        output.multiple = true;
+        output.pattern = String::new();
+        output.json_path = Some("$.failed".to_string());
+        let err = step.validate("test").unwrap_err();
+        assert!(err.to_string().contains("requires a regex 'pattern'"));
+    }
+
+    #[test]
+    fn test_extract_outputs_both_matches_joined_streams() {
+        let mut step = Step::new("bash");
+        step.outputs.insert(
+            "summary".to_string(),
+            Output {
+                pattern: r"warnings=(\d+)\nerrors=\d+".to_string(),
+                type_: DataType::Int,
+                group: None,
+                json_path: None,
+                key: None,
+                source: OutputSource::Both,
+                multiple: false,
+                optional: false,
+                delimiter: None,
+                default: None,
+                regex_cache: RegexCache::default(),
+            },
+        );
+
+        let mut stdout = "build done\nwarnings=4".to_string();
+        let mut stderr = "errors=0\nlinker ok".to_string();
+        let outputs = step.extract_outputs(&mut stdout, &mut stderr, 0).unwrap();
+
+        assert_eq!(outputs["summary"], serde_json::json!(4));
+        assert_eq!(stdout, "build done\n");
+        assert_eq!(stderr, "\nlinker ok");
+    }
+
+    #[test]
+    fn test_run_with_mock_executor_both_sources_strip_each_buffer() {
+        let mut mock = MockExecutor::new();
+        mock.expect_call(
+            "lint",
+            ExecutionResult {
+                stdout: "checked 3 files\nISSUE=unused import".to_string(),
+                stderr: "ISSUE=missing docs\nlint finished".to_string(),
+                duration_ms: 1,
+                ..ExecutionResult::default()
+            },
+        );
+
+        let mut step = Step::new("bash");
+        step.script = "lint".to_string();
+        step.outputs.insert(
+            "issues".to_string(),
+            Output {
+                pattern: "ISSUE=(.*)".to_string(),
+                type_: DataType::String,
+                group: None,
+                json_path: None,
+                key: None,
+                source: OutputSource::Both,
+                multiple: true,
+                optional: false,
+                delimiter: None,
+                default: None,
+                regex_cache: RegexCache::default(),
+            },
+        );
+
+        let result = step.run(
+            &mock,
+            &HashMap::new(),
+            &HashMap::new(),
+            &HashMap::new(),
+            None,
+            60,
+            &KillPolicy::default(),
+            &test_bash_interpreter(),
+        );
+
+        assert!(result.error.is_none());
+        assert_eq!(
+            result.typed_outputs["issues"],
+            serde_json::json!(["unused import", "missing docs"])
+        );
+        assert_eq!(result.stdout.as_deref(), Some("checked 3 files"));
+        assert_eq!(result.stderr.as_deref(), Some("\nlint finished"));
+    }
+
+    fn optional_output(pattern: &str, default: Option<serde_yaml::Value>) -> Output {
+        Output {
+            pattern: pattern.to_string(),
+            type_: DataType::Int,
+            group: None,
+            json_path: None,
+            key: None,
+            source: OutputSource::Stdout,
+            multiple: false,
+            optional: true,
+            default,
+            delimiter: None,
+            regex_cache: RegexCache::default(),
+        }
+    }
+
+    #[test]
+    fn test_extract_outputs_optional_without_match() {
+        let mut step = Step::new("bash");
+        step.outputs.insert(
+            "warnings".to_string(),
+            optional_output(r"warnings=(\d+)", Some(serde_yaml::Value::from(0))),
+        );
+        step.outputs
+            .insert("errors".to_string(), optional_output(r"errors=(\d+)", None));
+
+        let mut stdout = "build ok".to_string();
+        let outputs = step
+            .extract_outputs(&mut stdout, &mut String::new(), 0)
+            .unwrap();
+        assert_eq!(outputs["warnings"], serde_json::json!(0));
+        assert!(!outputs.contains_key("errors"));
+
+        let mut stdout = "warnings=3 errors=1".to_string();
+        let outputs = step
+            .extract_outputs(&mut stdout, &mut String::new(), 0)
+            .unwrap();
+        assert_eq!(outputs["warnings"], serde_json::json!(3));
+        assert_eq!(outputs["errors"], serde_json::json!(1));
+    }
+
+    #[test]
+    fn test_validate_output_default_requires_optional() {
+        let mut step = Step::new("bash");
+        step.script = "echo".to_string();
+        step.outputs.insert(
+            "warnings".to_string(),
+            optional_output(r"warnings=(\d+)", Some(serde_yaml::Value::from(0))),
+        );
+        assert!(step.validate("build").is_ok());
+
+        step.outputs.get_mut("warnings").unwrap().optional = false;
+        let err = step.validate("build").unwrap_err();
+        assert!(matches!(err, AtentoError::Validation(_)));
+        assert!(err.to_string().contains("requires 'optional'"));
+
+        let output = step.outputs.get_mut("warnings").unwrap();
+        output.optional = true;
+        output.default = Some(serde_yaml::Value::from("many"));
+        let err = step.validate("build").unwrap_err();
+        assert!(err.to_string().contains("invalid default"));
+    }
+
+    #[test]
+    fn test_foreach_items_split() {
+        use crate::step::foreach_items;
+
+        assert_eq!(foreach_items("a, b ,c"), vec!["a", "b", "c"]);
+        assert_eq!(
+            foreach_items("one, two\nthree\n\n"),
+            vec!["one, two", "three"]
+        );
+        assert_eq!(foreach_items("single"), vec!["single"]);
+        assert!(foreach_items("").is_empty());
+    }
+
+    #[test]
+    fn test_step_validate_foreach_item_input() {
+        let mut step = Step::new("bash");
+        step.script = "deploy {{ inputs.item }}".to_string();
+        assert!(step.validate("deploy").is_err());
+
+        step.foreach = Some("parameters.targets".to_string());
+        assert!(step.validate("deploy").is_ok());
+
+        step.workdir = Some("/srv/{{ inputs.item }}".to_string());
+        assert!(step.validate("deploy").is_ok());
+    }
+
+    #[test]
+    fn test_step_validate_foreach_reserved_input() {
+        let mut step = Step::new("bash");
+        step.script = "deploy {{ inputs.item }}".to_string();
+        step.foreach = Some("parameters.targets".to_string());
+        step.inputs.insert(
+            "item".to_string(),
+            Input::Ref {
+                ref_: "parameters.target".to_string(),
+            },
+        );
+
+        let err = step.validate("deploy").unwrap_err();
+        assert!(err.to_string().contains("reserved for the 'foreach' item"));
+    }
+
+    #[test]
+    fn test_step_dry_run_foreach_placeholder() {
+        let mut step = Step::new("bash");
+        step.script = "deploy {{ inputs.item }}".to_string();
+        step.foreach = Some("parameters.targets".to_string());
+
+        let result = step.dry_run(&HashMap::new(), &HashMap::new(), &HashMap::new());
+        assert_eq!(result.script.as_deref(), Some("deploy <item>"));
+        assert!(result.inputs.is_empty());
+    }
+
+    #[test]
+    fn test_step_combine_iterations() {
+        let mut step = Step::new("bash");
+        step.script = "deploy {{ inputs.item }}".to_string();
+        step.foreach = Some("parameters.targets".to_string());
+        step.outputs.insert(
+            "host".to_string(),
+            Output {
+                pattern: r"host=(\S+)".to_string(),
+                type_: DataType::String,
+                group: None,
+                json_path: None,
+                key: None,
+                source: OutputSource::Stdout,
+                multiple: false,
+                optional: false,
+                delimiter: None,
+                default: None,
+                regex_cache: RegexCache::default(),
+            },
+        );
+
+        let mut mock = MockExecutor::new();
+        mock.expect_call(
+            "deploy a",
+            ExecutionResult {
+                stdout: "host=a.local\ndone a".to_string(),
+                duration_ms: 1,
+                ..ExecutionResult::default()
+            },
+        )
+        .expect_error("deploy b", 3, "refused");
+
+        let interpreter = test_bash_interpreter();
+        let iterations: Vec<_> = ["a", "b"]
+            .iter()
+            .map(|item| {
+                let inputs = HashMap::from([("item".to_string(), (*item).to_string())]);
+                step.run(
+                    &mock,
+                    &inputs,
+                    &HashMap::new(),
+                    &HashMap::new(),
+                    None,
+                    60,
+                    &KillPolicy::default(),
+                    &interpreter,
+                )
+            })
+            .collect();
+
+        let result = step.combine_iterations(&HashMap::new(), &HashMap::new(), iterations, 5);
+
+        assert_eq!(result.iterations.len(), 2);
+        assert_eq!(result.exit_code, 3);
+        assert_eq!(
+            result.typed_outputs["host"],
+            serde_json::json!({"0": "a.local"})
+        );
+        assert_eq!(result.stdout.as_deref(), Some("done a"));
+        assert_eq!(result.stderr.as_deref(), Some("refused"));
+        let err = result.error.unwrap().to_string();
+        assert!(err.contains("Iteration 1 ('b') failed"), "{err}");
+    }
+
+    /// Executor that only answers `execute_combined`, optionally as a timeout
+    struct CombinedExecutor {
+        timed_out: bool,
+    }
+
+    impl crate::executor::CommandExecutor for CombinedExecutor {
+        fn execute(
+            &self,
+            _request: &crate::executor::ExecutionRequest<'_>,
+        ) -> crate::errors::Result<ExecutionResult> {
+            Err(AtentoError::Execution(
+                "execute should not be called".to_string(),
+            ))
+        }
+
+        fn execute_combined(
+            &self,
+            _request: &crate::executor::ExecutionRequest<'_>,
+        ) -> crate::errors::Result<ExecutionResult> {
+            Ok(ExecutionResult {
+                stdout: "value=7".to_string(),
+                stderr: "warning".to_string(),
+                exit_code: if self.timed_out { -1 } else { 0 },
+                duration_ms: 1,
+                combined: Some("value=7\nwarning".to_string()),
+                timed_out: self.timed_out,
+                ..ExecutionResult::default()
+            })
+        }
+    }
+
+    fn combined_capture_step() -> Step {
+        let mut step = Step::new("bash");
+        step.script = "run".to_string();
+        step.capture = crate::step::Capture::Combined;
+        step.outputs.insert(
+            "value".to_string(),
+            Output {
+                pattern: r"value=(\d+)".to_string(),
+                type_: DataType::Int,
+                group: None,
+                json_path: None,
+                key: None,
+                source: OutputSource::Stdout,
+                multiple: false,
+                optional: false,
+                delimiter: None,
+                default: None,
+                regex_cache: RegexCache::default(),
+            },
+        );
+        step
+    }
+
+    #[test]
+    fn test_step_capture_combined_output() {
+        let step = combined_capture_step();
+        let executor = CombinedExecutor { timed_out: false };
+
+        let result = step.run(
+            &executor,
+            &HashMap::new(),
+            &HashMap::new(),
+            &HashMap::new(),
+            None,
+            60,
+            &KillPolicy::default(),
+            &test_bash_interpreter(),
+        );
+
+        assert!(result.error.is_none(), "{:?}", result.error);
+        assert_eq!(result.combined_output.as_deref(), Some("value=7\nwarning"));
+        assert_eq!(result.typed_outputs["value"], serde_json::json!(7));
+        assert_eq!(result.stderr.as_deref(), Some("warning"));
+    }
+
+    #[test]
+    fn test_step_capture_combined_timeout_keeps_partial_output() {
+        let step = combined_capture_step();
+        let executor = CombinedExecutor { timed_out: true };
+
+        let result = step.run(
+            &executor,
+            &HashMap::new(),
+            &HashMap::new(),
+            &HashMap::new(),
+            None,
+            60,
+            &KillPolicy::default(),
+            &test_bash_interpreter(),
+        );
+
+        assert!(matches!(result.error, Some(AtentoError::Timeout { .. })));
+        assert!(result.outputs.is_empty());
+        assert_eq!(result.stdout.as_deref(), Some("value=7"));
+        assert_eq!(result.combined_output.as_deref(), Some("value=7\nwarning"));
+    }
+
+    #[test]
+    fn test_step_capture_separate_by_default() {
+        let step = Step::new("bash");
+        assert_eq!(step.capture, crate::step::Capture::Separate);
+
+        let step: Step =
+            serde_yaml::from_str("type: bash\nscript: run\ncapture: combined\n").unwrap();
+        assert_eq!(step.capture, crate::step::Capture::Combined);
+    }
+
+    #[test]
+    fn test_build_script_default_with_present_value() {
+        let mut step = Step::new("bash");
+        step.script = r#"deploy --env {{ inputs.env | default("staging") }}"#.to_string();
+        step.inputs.insert(
+            "env".to_string(),
+            Input::Ref {
+                ref_: "parameters.env".to_string(),
+            },
+        );
+
+        let inputs = HashMap::from([("env".to_string(), "prod".to_string())]);
+        assert_eq!(step.build_script(&inputs), "deploy --env prod");
+    }
+
+    #[test]
+    fn test_build_script_default_with_absent_value() {
+        let mut step = Step::new("bash");
+        step.script = r#"deploy --env {{inputs.env|default( "staging" )}}"#.to_string();
+
+        assert!(step.validate("deploy").is_ok());
+        assert_eq!(step.build_script(&HashMap::new()), "deploy --env staging");
+    }
+
+    #[test]
+    fn test_build_script_absent_value_without_default() {
+        let mut step = Step::new("bash");
+        step.script = "deploy --env {{ inputs.env }}".to_string();
+
+        assert!(step.validate("deploy").is_err());
+        assert_eq!(
+            step.build_script(&HashMap::new()),
+            "deploy --env {{ inputs.env }}"
+        );
+    }
+
+    #[test]
+    fn test_build_script_empty_default() {
+        let mut step = Step::new("bash");
+        step.script = r#"run {{ inputs.flags | default("") }}--verbose"#.to_string();
+
+        assert_eq!(step.build_script(&HashMap::new()), "run --verbose");
+    }
+
+    #[test]
+    fn test_step_validate_rejects_placeholder_in_default() {
+        let mut step = Step::new("bash");
+        step.script = r#"echo {{ inputs.a | default("{{ inputs.b }}") }}"#.to_string();
+
+        let err = step.validate("echo").unwrap_err();
+        assert!(err.to_string().contains("literal strings"), "{err}");
+    }
+
+    #[test]
+    fn test_step_input_has_default() {
+        let mut step = Step::new("bash");
+        step.script = r#"echo {{ inputs.a | default("x") }} {{ inputs.b }}"#.to_string();
+        step.workdir = Some("/tmp/{{ inputs.a }}".to_string());
+
+        // `a` is also used without a default in the workdir
+        assert!(!step.input_has_default("a"));
+        assert!(!step.input_has_default("b"));
+
+        step.workdir = None;
+        assert!(step.input_has_default("a"));
+        assert!(!step.input_has_default("missing"));
+    }
+
+    #[test]
+    fn test_raw_block_with_awk_and_escaped_placeholder() {
+        let mut step = Step::new("bash");
+        step.script = "ls -l {{ inputs.dir }} | awk '{print $1}'\n\
+            {{ raw }}echo \"use {{ inputs.name }} in templates\"{{ endraw }}"
+            .to_string();
+        step.inputs.insert(
+            "dir".to_string(),
+            Input::Ref {
+                ref_: "parameters.dir".to_string(),
+            },
+        );
+
+        assert!(step.validate("list").is_ok());
+
+        let inputs = HashMap::from([
+            ("dir".to_string(), "/tmp".to_string()),
+            ("name".to_string(), "ignored".to_string()),
+        ]);
+        assert_eq!(
+            step.build_script(&inputs),
+            "ls -l /tmp | awk '{print $1}'\necho \"use {{ inputs.name }} in templates\""
+        );
+    }
+
+    #[test]
+    fn test_raw_block_keeps_parameter_placeholders() {
+        let mut step = Step::new("bash");
+        step.script = "echo {{ parameters.env }} {{raw}}{{ parameters.env }}{{endraw}}".to_string();
+
+        assert_eq!(step.parameter_refs(), vec!["env".to_string()]);
+        let params = HashMap::from([("env".to_string(), "prod".to_string())]);
+        assert_eq!(
+            step.render_script(&HashMap::new(), &params),
+            "echo prod {{ parameters.env }}"
+        );
+    }
+
+    #[test]
+    fn test_raw_block_multiline_and_multiple() {
+        let mut step = Step::new("bash");
+        step.script =
+            "{{ raw }}a {{ x }}\nb{{ endraw }} {{ inputs.v }} {{ raw }}{{ y }}{{ endraw }}"
+                .to_string();
+        step.inputs.insert(
+            "v".to_string(),
+            Input::Ref {
+                ref_: "parameters.v".to_string(),
+            },
+        );
+
+        assert!(step.validate("raw").is_ok());
+        let inputs = HashMap::from([("v".to_string(), "1".to_string())]);
+        assert_eq!(step.build_script(&inputs), "a {{ x }}\nb 1 {{ y }}");
+    }
+
+    #[test]
+    fn test_undeclared_input_error_mentions_raw_block() {
+        let mut step = Step::new("bash");
+        step.script = "echo {{ inputs.name }}".to_string();
+
+        let err = step.validate("echo").unwrap_err().to_string();
+        assert!(err.contains("{{ raw }}"), "{err}");
+    }
+
+    #[test]
+    fn test_step_image_deserialize_and_validate() {
+        let step: Step =
+            serde_yaml::from_str("type: bash\nimage: alpine:3.20\nscript: echo hi\n").unwrap();
+        assert_eq!(step.image.as_deref(), Some("alpine:3.20"));
+        assert!(step.validate("s").is_ok());
+
+        let step: Step = serde_yaml::from_str("type: bash\nscript: echo hi\n").unwrap();
+        assert!(step.image.is_none());
+
+        let step: Step =
+            serde_yaml::from_str("type: bash\nimage: \"\"\nscript: echo hi\n").unwrap();
+        match step.validate("s") {
+            Err(AtentoError::Validation(msg)) => assert!(msg.contains("empty 'image'")),
+            other => panic!("Expected Validation error, got {other:?}"),
+        }
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_step_result_records_resolved_fallback_command() {
+        let mut step = Step::new("bash");
+        step.script = "echo ran".to_string();
+        let interpreter = Interpreter {
+            command: "atento-missing-shell".to_string(),
+            args: vec![],
+            extension: ".sh".to_string(),
+            fallbacks: vec!["bash".to_string()],
+        };
+
+        let result = step.run(
+            &crate::executor::SystemExecutor,
+            &HashMap::new(),
+            &HashMap::new(),
+            &HashMap::new(),
+            None,
+            30,
+            &KillPolicy::default(),
+            &interpreter,
+        );
+
+        assert!(result.error.is_none(), "{:?}", result.error);
+        assert_eq!(result.stdout.as_deref(), Some("ran"));
+        assert_eq!(result.resolved_command.as_deref(), Some("bash"));
+        let json = serde_json::to_value(&result).unwrap();
+        assert_eq!(json["resolved_command"], "bash");
+    }
+
+    #[test]
+    fn test_step_interpreter_args_are_passed_to_executor() {
+        let interpreter = Interpreter {
+            command: "python3".to_string(),
+            args: vec!["-B".to_string()],
+            extension: ".py".to_string(),
+            fallbacks: vec![],
+        };
+        let mut step = Step::new("python3");
+        step.script = "print(1)".to_string();
+
+        let mock = MockExecutor::new();
+        let run = |step: &Step, mock: &MockExecutor| {
+            step.run(
+                mock,
+                &HashMap::new(),
+                &HashMap::new(),
+                &HashMap::new(),
+                None,
+                30,
+                &KillPolicy::default(),
+                &interpreter,
+            )
+        };
+
+        let _ = run(&step, &mock);
+        assert_eq!(mock.last_call().unwrap().1.args, vec!["-B"]);
+
+        step.interpreter_args = serde_yaml::from_str("[-u]").unwrap();
+        let _ = run(&step, &mock);
+        assert_eq!(mock.last_call().unwrap().1.args, vec!["-B", "-u"]);
+
+        step.interpreter_args = serde_yaml::from_str("{args: [-u], override: true}").unwrap();
+        let result = run(&step, &mock);
+        assert!(result.error.is_none());
+        assert_eq!(mock.last_call().unwrap().1.args, vec!["-u"]);
+        assert_eq!(mock.call_count(), 3);
+    }
+
+    #[test]
+    fn test_step_validate_rejects_empty_interpreter_arg() {
+        let mut step = Step::new("bash");
+        step.script = "echo hi".to_string();
+        step.interpreter_args = serde_yaml::from_str("['-e', ' ']").unwrap();
+        match step.validate("s") {
+            Err(AtentoError::Validation(msg)) => {
+                assert!(msg.contains("empty entry in 'interpreter_args'"), "{msg}");
+            }
+            other => panic!("Expected Validation error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_step_stdin_template_is_rendered_and_piped() {
+        let yaml = r"
+type: bash
+inputs:
+  name:
+    type: string
+    value: world
+script: cat
+stdin: 'hello {{ inputs.name }} from {{ parameters.env }}'
+";
+        let step: Step = serde_yaml::from_str(yaml).unwrap();
+        step.validate("greet").unwrap();
+        assert_eq!(step.parameter_refs(), vec!["env"]);
+
+        let mock = MockExecutor::new();
+        let inputs = HashMap::from([("name".to_string(), "world".to_string())]);
+        let parameters = HashMap::from([("env".to_string(), "prod".to_string())]);
+        let result = step.run(
+            &mock,
+            &inputs,
+            &parameters,
+            &HashMap::new(),
+            None,
+            30,
+            &KillPolicy::default(),
+            &test_bash_interpreter(),
+        );
+
+        assert!(result.error.is_none());
+        assert_eq!(mock.last_stdin().as_deref(), Some("hello world from prod"));
+    }
+
+    #[test]
+    fn test_step_without_stdin_passes_none() {
+        let mut step = Step::new("bash");
+        step.script = "echo hi".to_string();
+        let mock = MockExecutor::new();
+        let _ = step.run(
+            &mock,
+            &HashMap::new(),
+            &HashMap::new(),
+            &HashMap::new(),
+            None,
+            30,
+            &KillPolicy::default(),
+            &test_bash_interpreter(),
+        );
+        assert_eq!(mock.call_count(), 1);
+        assert!(mock.last_stdin().is_none());
+    }
+
+    #[test]
+    fn test_step_stdin_value_comes_from_synthetic_input() {
+        let mut step = Step::new("bash");
+        step.script = "cat".to_string();
+        step.stdin = serde_yaml::from_str("ref: steps.fetch.outputs.body").unwrap();
+
+        let mock = MockExecutor::new();
+        let inputs = HashMap::from([("stdin".to_string(), "{\"id\": 1}".to_string())]);
+        let result = step.run(
+            &mock,
+            &inputs,
+            &HashMap::new(),
+            &HashMap::new(),
+            None,
+            30,
+            &KillPolicy::default(),
+            &test_bash_interpreter(),
+        );
+
+        assert_eq!(mock.last_stdin().as_deref(), Some("{\"id\": 1}"));
+        // The value is piped to the script, not recorded as an input
+        assert!(result.inputs.is_empty());
+    }
+
+    #[test]
+    fn test_step_validate_rejects_undeclared_input_in_stdin() {
+        let mut step = Step::new("bash");
+        step.script = "cat".to_string();
+        step.stdin = Some(crate::env::EnvValue::Template(
+            "{{ inputs.missing }}".to_string(),
+        ));
+        match step.validate("s") {
+            Err(AtentoError::Validation(msg)) => {
+                assert!(msg.contains("stdin references input 'missing'"), "{msg}");
+            }
+            other => panic!("Expected Validation error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_step_validate_counts_stdin_as_input_use() {
+        let yaml = r"
+type: bash
+inputs:
+  body:
+    type: string
+    value: data
+script: cat
+stdin: '{{ inputs.body }}'
+";
+        let step: Step = serde_yaml::from_str(yaml).unwrap();
+        assert!(step.validate("s").is_ok());
+    }
+
+    #[test]
+    fn test_step_validate_reserves_stdin_input_for_stdin_value() {
+        let yaml = r"
+type: bash
+inputs:
+  stdin:
+    type: string
+    value: data
+script: echo {{ inputs.stdin }}
+stdin:
+  type: string
+  value: other
+";
+        let step: Step = serde_yaml::from_str(yaml).unwrap();
+        match step.validate("s") {
+            Err(AtentoError::Validation(msg)) => {
+                assert!(msg.contains("reserved for its 'stdin' value"), "{msg}");
+            }
+            other => panic!("Expected Validation error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_step_artifacts_deserialize_and_validate() {
+        let yaml = r"
+type: bash
+script: make
+artifacts:
+  report:
+    path: out/report.txt
+    inline_max_bytes: 1024
+  logs:
+    path: build.log
+    optional: true
+";
+        let step: Step = serde_yaml::from_str(yaml).unwrap();
+        assert!(step.validate("s").is_ok());
+        assert_eq!(step.artifacts["report"].path, "out/report.txt");
+        assert_eq!(step.artifacts["report"].inline_max_bytes, Some(1024));
+        assert!(!step.artifacts["report"].optional);
+        assert!(step.artifacts["logs"].optional);
+    }
+
+    #[test]
+    fn test_step_validate_rejects_invalid_artifacts() {
+        let cases = [
+            (
+                "type: bash\nscript: make\nforeach: parameters.items\nartifacts:\n  out:\n    path: out.txt\n",
+                "cannot combine 'foreach' with 'artifacts'",
+            ),
+            (
+                "type: bash\nscript: make\nartifacts:\n  out:\n    path: ''\n",
+                "without a name or 'path'",
+            ),
+            (
+                "type: bash\nscript: make\nartifacts:\n  out:\n    path: '{{ inputs.missing }}.txt'\n",
+                "references input 'missing'",
+            ),
+        ];
+        for (yaml, expected) in cases {
+            let step: Step = serde_yaml::from_str(yaml).unwrap();
+            match step.validate("s") {
+                Err(AtentoError::Validation(msg)) => {
+                    assert!(msg.contains(expected), "{msg}");
+                }
+                other => panic!("Expected Validation error, got {other:?}"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_step_collect_artifacts_renders_paths() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("v2.txt"), "release").unwrap();
+        let mut step = Step::new("bash");
+        step.workdir = Some(dir.path().display().to_string());
+        step.artifacts.insert(
+            "notes".to_string(),
+            crate::artifact::Artifact {
+                path: "{{ inputs.version }}.txt".to_string(),
+                optional: false,
+                inline_max_bytes: None,
+            },
+        );
+        let inputs = HashMap::from([("version".to_string(), "v2".to_string())]);
+
+        let artifacts = step
+            .collect_artifacts(&inputs, &HashMap::new(), None)
+            .unwrap();
+        assert_eq!(artifacts["notes"].size, 7);
+        assert_eq!(
+            artifacts["notes"].path,
+            dir.path().join("v2.txt").display().to_string()
+        );
+    }
+
+    #[test]
+    fn test_validate_compiles_output_regex_for_extraction() {
+        let mut step = Step::new("bash");
+        step.script = "echo v1".to_string();
+        step.outputs.insert(
+            "version".to_string(),
+            Output {
+                pattern: r"v(\d+)".to_string(),
+                ..Output::default()
+            },
+        );
+        assert!(step.outputs["version"].regex_cache.0.get().is_none());
+
+        step.validate("build").unwrap();
+        assert!(step.outputs["version"].regex_cache.0.get().is_some());
+
+        for _ in 0..3 {
+            let mut stdout = "v7".to_string();
+            let outputs = step
+                .extract_outputs(&mut stdout, &mut String::new(), 0)
+                .unwrap();
+            assert_eq!(outputs["version"], "7");
+        }
+    }
+
+    #[test]
+    fn test_step_refs_and_rendering() {
+        let mut step = Step::new("bash");
+        step.script = "echo {{ steps.build.outputs.version }} {{steps.build.stdout}} \
+            {{ steps.build.outputs.version }} {{ raw }}{{ steps.x.outputs.y }}{{ endraw }}"
+            .to_string();
+        step.workdir = Some("/tmp/{{ steps.fetch.artifacts.dir }}".to_string());
+
+        assert_eq!(
+            step.step_refs(),
+            vec![
+                "steps.build.outputs.version".to_string(),
+                "steps.build.stdout".to_string(),
+                "steps.fetch.artifacts.dir".to_string(),
+            ]
+        );
+
+        let inputs = HashMap::from([
+            ("steps.build.outputs.version".to_string(), "2.0".to_string()),
+            ("steps.build.stdout".to_string(), "ok".to_string()),
+        ]);
+        assert_eq!(
+            step.build_script(&inputs),
+            "echo 2.0 ok 2.0 {{ steps.x.outputs.y }}"
+        );
+    }
+
+    #[test]
+    fn test_quote_apply() {
+        use crate::step::Quote;
+
+        assert_eq!(Quote::None.apply("it's"), "it's");
+        assert_eq!(Quote::Shell.apply("it's $(id)"), r"'it'\''s $(id)'");
+        assert_eq!(Quote::Shell.apply(""), "''");
+        assert_eq!(
+            Quote::Powershell.apply("it's $env:HOME"),
+            "'it''s $env:HOME'"
+        );
+        assert_eq!(
+            Quote::Powershell.apply("a\u{2018}b\u{2019}c\u{201A}d\u{201B}e"),
+            "'a\u{2018}\u{2018}b\u{2019}\u{2019}c\u{201A}\u{201A}d\u{201B}\u{201B}e'"
+        );
+    }
+
+    #[test]
+    fn test_render_script_quotes_substituted_values() {
+        use crate::step::Quote;
+
+        let mut step = Step::new("bash");
+        step.script = "printf '%s' {{ inputs.v }} {{ inputs.missing | default(\"a b\") }} \
+            {{ parameters.p }} {{ steps.s.stdout }} {{ inputs.unknown }}"
+            .to_string();
+        step.quote = Quote::Shell;
+
+        let inputs = HashMap::from([
+            ("v".to_string(), "x'y".to_string()),
+            ("steps.s.stdout".to_string(), "$(id)".to_string()),
+        ]);
+        let parameters = HashMap::from([("p".to_string(), "1 2".to_string())]);
+        assert_eq!(
+            step.render_script(&inputs, &parameters),
+            r"printf '%s' 'x'\''y' 'a b' '1 2' '$(id)' {{ inputs.unknown }}"
+        );
+    }
+
+    #[test]
+    fn test_substituted_values_are_not_rendered_again() {
+        let mut step = Step::new("bash");
+        step.script = "echo {{ inputs.v }}".to_string();
+
+        let inputs = HashMap::from([("v".to_string(), "{{ parameters.secret }}".to_string())]);
+        let parameters = HashMap::from([("secret".to_string(), "hunter2".to_string())]);
+        assert_eq!(
+            step.render_script(&inputs, &parameters),
+            "echo {{ parameters.secret }}"
+        );
+    }
 }
@@ -0,0 +1,189 @@
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use crate::builder::{ChainBuilder, StepBuilder};
+    use crate::chain::Chain;
+    use crate::data_type::DataType;
+    use crate::errors::AtentoError;
+    use crate::input::Input;
+
+    fn chain(steps: &str) -> Chain {
+        format!(
+            r"
+parameters:
+  registry:
+    value: registry.example.com
+templates:
+  docker_build:
+    name: Build image
+    type: bash
+    timeout: 600
+    script: docker build -t {{{{ inputs.registry }}}}/{{{{ inputs.image }}}} --platform {{{{ inputs.platform }}}} .
+    inputs:
+      registry:
+        ref: parameters.registry
+      image:
+        type: string
+      platform:
+        value: linux/amd64
+    outputs:
+      digest:
+        pattern: 'sha256:(\w+)'
+steps:
+{steps}"
+        )
+        .parse()
+        .unwrap()
+    }
+
+    fn validation_message(chain: &Chain) -> String {
+        match chain.validate().unwrap_err() {
+            AtentoError::Validation(message) => message,
+            other => panic!("expected a validation error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_steps_are_instantiated_from_template() {
+        let chain = chain(
+            r"
+  api:
+    template: docker_build
+    inputs:
+      image:
+        value: api
+  worker:
+    name: Build worker
+    template: docker_build
+    inputs:
+      image:
+        value: worker
+      platform:
+        value: linux/arm64
+",
+        );
+        chain.validate().unwrap();
+
+        let ids: Vec<&str> = chain.steps.keys().map(String::as_str).collect();
+        assert_eq!(ids, vec!["api", "worker"]);
+
+        let api = &chain.steps["api"];
+        assert!(api.template.is_none());
+        assert_eq!(api.name.as_deref(), Some("Build image"));
+        assert_eq!(api.interpreter.as_deref(), Some("bash"));
+        assert_eq!(api.timeout, 600);
+        assert!(api.outputs.contains_key("digest"));
+        assert!(
+            matches!(&api.inputs["registry"], Input::Ref { ref_ } if ref_ == "parameters.registry")
+        );
+        assert_eq!(api.inputs["image"].to_string_value().unwrap(), "api");
+        assert_eq!(
+            api.inputs["platform"].to_string_value().unwrap(),
+            "linux/amd64"
+        );
+
+        let worker = &chain.steps["worker"];
+        assert_eq!(worker.name.as_deref(), Some("Build worker"));
+        assert_eq!(
+            worker.inputs["platform"].to_string_value().unwrap(),
+            "linux/arm64"
+        );
+
+        let result = chain.dry_run();
+        let steps = result.steps.unwrap();
+        assert_eq!(
+            steps["worker"].script.as_deref(),
+            Some("docker build -t registry.example.com/worker --platform linux/arm64 .")
+        );
+    }
+
+    #[test]
+    fn test_unknown_template() {
+        let chain = chain("  api:\n    template: docker_push\n");
+        assert_eq!(
+            validation_message(&chain),
+            "Step 'api' uses unknown template 'docker_push'"
+        );
+    }
+
+    #[test]
+    fn test_missing_required_template_input() {
+        let chain = chain("  api:\n    template: docker_build\n");
+        assert_eq!(
+            validation_message(&chain),
+            "Step 'api' does not pass input 'image' required by template 'docker_build'"
+        );
+    }
+
+    #[test]
+    fn test_undeclared_template_input() {
+        let chain = chain(
+            r"
+  api:
+    template: docker_build
+    inputs:
+      image:
+        value: api
+      tag:
+        value: latest
+",
+        );
+        assert_eq!(
+            validation_message(&chain),
+            "Step 'api' passes input 'tag', which template 'docker_build' does not declare"
+        );
+    }
+
+    #[test]
+    fn test_template_step_cannot_set_script() {
+        let chain = chain(
+            r"
+  api:
+    template: docker_build
+    script: echo hi
+    inputs:
+      image:
+        value: api
+",
+        );
+        assert!(validation_message(&chain).contains("cannot set 'script'"));
+    }
+
+    #[test]
+    fn test_step_without_script() {
+        let chain: Chain = "steps:\n  s:\n    type: bash\n".parse().unwrap();
+        assert_eq!(
+            validation_message(&chain),
+            "Step 's' has no 'script' or 'script_file'"
+        );
+    }
+
+    #[test]
+    fn test_builder_instantiates_templates() {
+        let chain = ChainBuilder::new()
+            .template(
+                "greet",
+                StepBuilder::new()
+                    .interpreter("bash")
+                    .script("echo Hello {{ inputs.who }}")
+                    .input_value("who", DataType::String, "world"),
+            )
+            .step("default", StepBuilder::new().template("greet"))
+            .step(
+                "custom",
+                StepBuilder::new()
+                    .template("greet")
+                    .input_value("who", DataType::String, "atento"),
+            )
+            .build();
+        chain.validate().unwrap();
+
+        assert_eq!(chain.steps["default"].script, "echo Hello {{ inputs.who }}");
+        assert_eq!(
+            chain.steps["custom"].inputs["who"]
+                .to_string_value()
+                .unwrap(),
+            "atento"
+        );
+    }
+}
@@ -0,0 +1,366 @@
+#[cfg(all(test, feature = "tracing"))]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use crate::chain::Chain;
+    use crate::errors::Result;
+    use crate::executor::{CommandExecutor, ExecutionRequest, ExecutionResult};
+    use crate::interpreter::default_interpreters;
+    use crate::step::Step;
+    use crate::tests::mock_executor::MockExecutor;
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::thread::ThreadId;
+    use tracing::field::{Field, Visit};
+    use tracing::span::{Attributes, Id, Record};
+    use tracing::{Event, Level, Metadata, Subscriber};
+
+    #[derive(Debug, Clone)]
+    struct RecordedSpan {
+        name: &'static str,
+        parent: Option<u64>,
+        fields: HashMap<String, String>,
+    }
+
+    #[derive(Debug, Clone)]
+    struct RecordedEvent {
+        level: Level,
+        span: Option<u64>,
+        fields: HashMap<String, String>,
+    }
+
+    #[derive(Default)]
+    struct FieldVisitor(HashMap<String, String>);
+
+    impl Visit for FieldVisitor {
+        fn record_str(&mut self, field: &Field, value: &str) {
+            self.0.insert(field.name().to_string(), value.to_string());
+        }
+
+        fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+            self.0
+                .insert(field.name().to_string(), format!("{value:?}"));
+        }
+    }
+
+    /// Records spans and events, tracking the entered span per thread.
+    #[derive(Default)]
+    struct Recorder {
+        next_id: AtomicU64,
+        spans: Mutex<HashMap<u64, RecordedSpan>>,
+        events: Mutex<Vec<RecordedEvent>>,
+        stacks: Mutex<HashMap<ThreadId, Vec<u64>>>,
+    }
+
+    impl Recorder {
+        fn current(&self) -> Option<u64> {
+            let stacks = self.stacks.lock().unwrap();
+            stacks
+                .get(&std::thread::current().id())
+                .and_then(|stack| stack.last().copied())
+        }
+    }
+
+    impl Subscriber for &'static Recorder {
+        fn enabled(&self, _metadata: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, attrs: &Attributes<'_>) -> Id {
+            let id = self.next_id.fetch_add(1, Ordering::SeqCst) + 1;
+            let mut visitor = FieldVisitor::default();
+            attrs.record(&mut visitor);
+            let parent = attrs
+                .parent()
+                .map(Id::into_u64)
+                .or_else(|| attrs.is_contextual().then(|| self.current()).flatten());
+            self.spans.lock().unwrap().insert(
+                id,
+                RecordedSpan {
+                    name: attrs.metadata().name(),
+                    parent,
+                    fields: visitor.0,
+                },
+            );
+            Id::from_u64(id)
+        }
+
+        fn record(&self, _span: &Id, _values: &Record<'_>) {}
+
+        fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+
+        fn event(&self, event: &Event<'_>) {
+            let mut visitor = FieldVisitor::default();
+            event.record(&mut visitor);
+            let recorded = RecordedEvent {
+                level: *event.metadata().level(),
+                span: self.current(),
+                fields: visitor.0,
+            };
+            self.events.lock().unwrap().push(recorded);
+        }
+
+        fn enter(&self, span: &Id) {
+            let mut stacks = self.stacks.lock().unwrap();
+            stacks
+                .entry(std::thread::current().id())
+                .or_default()
+                .push(span.into_u64());
+        }
+
+        fn exit(&self, _span: &Id) {
+            let mut stacks = self.stacks.lock().unwrap();
+            if let Some(stack) = stacks.get_mut(&std::thread::current().id()) {
+                stack.pop();
+            }
+        }
+    }
+
+    /// Runs `f` with a fresh recorder installed and returns the recorder.
+    fn record(f: impl FnOnce()) -> &'static Recorder {
+        let recorder: &'static Recorder = Box::leak(Box::default());
+        tracing::subscriber::with_default(recorder, f);
+        recorder
+    }
+
+    fn ok(stdout: &str) -> ExecutionResult {
+        ExecutionResult {
+            stdout: stdout.to_string(),
+            duration_ms: 1,
+            ..ExecutionResult::default()
+        }
+    }
+
+    /// Thread-safe executor that echoes the script, for parallel runs
+    struct EchoExecutor;
+
+    impl CommandExecutor for EchoExecutor {
+        fn execute(&self, request: &ExecutionRequest<'_>) -> Result<ExecutionResult> {
+            Ok(ok(request.script))
+        }
+    }
+
+    fn chain_with_steps(steps: &[(&str, &str)]) -> Chain {
+        let mut chain = Chain {
+            name: Some("traced".to_string()),
+            interpreters: default_interpreters().into_iter().collect(),
+            ..Chain::default()
+        };
+        for (id, script) in steps {
+            let mut step = Step::new("bash");
+            step.script = (*script).to_string();
+            chain.steps.insert((*id).to_string(), step);
+        }
+        chain
+    }
+
+    /// Returns the first recorded span with `name` whose fields include `field`.
+    fn find_span(recorder: &Recorder, name: &str, field: (&str, &str)) -> (u64, RecordedSpan) {
+        let spans = recorder.spans.lock().unwrap();
+        spans
+            .iter()
+            .find(|(_, span)| {
+                span.name == name && span.fields.get(field.0).map(String::as_str) == Some(field.1)
+            })
+            .map(|(id, span)| (*id, span.clone()))
+            .unwrap()
+    }
+
+    fn events_with_message(recorder: &Recorder, message: &str) -> Vec<RecordedEvent> {
+        recorder
+            .events
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|event| event.fields.get("message").map(String::as_str) == Some(message))
+            .cloned()
+            .collect()
+    }
+
+    #[test]
+    fn test_chain_and_step_spans_carry_documented_fields() {
+        let mut chain = chain_with_steps(&[("build", "make"), ("test", "make test")]);
+        chain.steps["build"].name = Some("Build".to_string());
+        let mut mock = MockExecutor::new();
+        mock.expect_call("make", ok("built"))
+            .expect_call("make test", ok("passed"));
+
+        let recorder = record(|| {
+            let result = chain.run_with_executor(&mock);
+            assert_eq!(result.status, "ok");
+        });
+
+        let (chain_id, chain_span) = find_span(recorder, "chain", ("chain.name", "traced"));
+        assert_eq!(chain_span.fields["chain.name"], "traced");
+        assert_eq!(chain_span.fields["chain.steps"], "2");
+        assert_eq!(chain_span.fields["chain.parallel"], "false");
+
+        let (build_id, build_span) = find_span(recorder, "step", ("step.id", "build"));
+        assert_eq!(build_span.parent, Some(chain_id));
+        assert_eq!(build_span.fields["step.name"], "Build");
+        assert_eq!(build_span.fields["step.interpreter"], "bash");
+
+        let started = events_with_message(recorder, "step started");
+        assert_eq!(started.len(), 2);
+        assert_eq!(started[0].span, Some(build_id));
+
+        let finished = events_with_message(recorder, "step finished");
+        assert_eq!(finished.len(), 2);
+        assert_eq!(finished[0].span, Some(build_id));
+        assert_eq!(finished[0].fields["exit_code"], "0");
+        assert_eq!(finished[0].fields["failed"], "false");
+        assert!(finished[0].fields.contains_key("duration_ms"));
+
+        let chain_finished = events_with_message(recorder, "chain finished");
+        assert_eq!(chain_finished.len(), 1);
+        assert_eq!(chain_finished[0].span, Some(chain_id));
+        assert_eq!(chain_finished[0].fields["status"], "ok");
+    }
+
+    #[test]
+    fn test_failed_output_extraction_emits_warning() {
+        let chain: Chain = r"
+steps:
+  parse:
+    type: bash
+    script: echo
+    outputs:
+      version:
+        pattern: 'version=(\d+)'
+"
+        .parse()
+        .unwrap();
+        let mut mock = MockExecutor::new();
+        mock.expect_call("echo", ok("no version here"));
+
+        let recorder = record(|| {
+            let result = chain.run_with_executor(&mock);
+            assert_eq!(result.status, "nok");
+        });
+
+        let warnings = events_with_message(recorder, "output extraction failed");
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].level, Level::WARN);
+        assert!(warnings[0].fields["error"].contains("version"));
+
+        let (step_id, _) = find_span(recorder, "step", ("step.id", "parse"));
+        assert_eq!(warnings[0].span, Some(step_id));
+        let finished = events_with_message(recorder, "step finished");
+        assert_eq!(finished[0].fields["failed"], "true");
+    }
+
+    #[test]
+    fn test_parallel_step_spans_are_children_of_chain_span() {
+        let mut chain = chain_with_steps(&[("a", "one"), ("b", "two")]);
+        chain.parallel = true;
+        let recorder = record(|| {
+            let result = chain.run_parallel_with_executor(&EchoExecutor);
+            assert_eq!(result.status, "ok");
+        });
+
+        let (chain_id, chain_span) = find_span(recorder, "chain", ("chain.name", "traced"));
+        assert_eq!(chain_span.fields["chain.parallel"], "true");
+        for step in ["a", "b"] {
+            let (_, span) = find_span(recorder, "step", ("step.id", step));
+            assert_eq!(span.parent, Some(chain_id));
+        }
+    }
+
+    #[test]
+    fn test_events_of_a_run_have_documented_levels() {
+        let chain: Chain = r"
+max_output_bytes: 5
+steps:
+  build:
+    type: bash
+    script: make
+    retries: 1
+  test:
+    type: bash
+    script: make test
+"
+        .parse()
+        .unwrap();
+        let failed = ExecutionResult {
+            exit_code: 2,
+            ..ok("")
+        };
+        let truncated = ExecutionResult {
+            stdout_truncated: true,
+            ..ok("built")
+        };
+        let mut mock = MockExecutor::new();
+        mock.expect_calls("make", vec![failed, truncated])
+            .expect_timeout("make test");
+
+        let recorder = record(|| {
+            let result = chain.run_with_executor(&mock);
+            assert_eq!(result.status, "nok");
+        });
+
+        let (build_id, _) = find_span(recorder, "step", ("step.id", "build"));
+        let (test_id, _) = find_span(recorder, "step", ("step.id", "test"));
+        let levels = |message: &str| -> Vec<(Level, Option<u64>)> {
+            events_with_message(recorder, message)
+                .iter()
+                .map(|event| (event.level, event.span))
+                .collect()
+        };
+
+        assert_eq!(
+            levels("interpreter selected"),
+            vec![
+                (Level::DEBUG, Some(build_id)),
+                (Level::DEBUG, Some(test_id))
+            ]
+        );
+        assert_eq!(
+            levels("script rendered"),
+            vec![
+                (Level::DEBUG, Some(build_id)),
+                (Level::DEBUG, Some(test_id))
+            ]
+        );
+        assert_eq!(levels("retrying step"), vec![(Level::WARN, Some(build_id))]);
+        assert_eq!(
+            levels("output truncated"),
+            vec![(Level::WARN, Some(build_id))]
+        );
+        assert_eq!(levels("step failed"), vec![(Level::ERROR, Some(test_id))]);
+        assert_eq!(levels("step finished").len(), 2);
+
+        let selected = &events_with_message(recorder, "interpreter selected")[0];
+        assert_eq!(selected.fields["interpreter"], "bash");
+        assert_eq!(selected.fields["command"], "bash");
+        let rendered = &events_with_message(recorder, "script rendered")[0];
+        assert_eq!(rendered.fields["script_bytes"], "4");
+        let retry = &events_with_message(recorder, "retrying step")[0];
+        assert!(retry.fields["error"].contains("code 2"), "{retry:?}");
+        let step_failed = &events_with_message(recorder, "step failed")[0];
+        assert_eq!(step_failed.fields["timed_out"], "true");
+    }
+
+    #[test]
+    fn test_chain_timeout_emits_error() {
+        struct SlowExecutor;
+
+        impl CommandExecutor for SlowExecutor {
+            fn execute(&self, request: &ExecutionRequest<'_>) -> Result<ExecutionResult> {
+                std::thread::sleep(std::time::Duration::from_millis(1100));
+                Ok(ok(request.script))
+            }
+        }
+
+        let mut chain = chain_with_steps(&[("slow", "sleep"), ("next", "echo")]);
+        chain.timeout = 1;
+        let recorder = record(|| {
+            let result = chain.run_with_executor(&SlowExecutor);
+            assert_eq!(result.status, "nok");
+        });
+
+        let timed_out = events_with_message(recorder, "chain timed out");
+        assert_eq!(timed_out.len(), 1);
+        assert_eq!(timed_out[0].level, Level::ERROR);
+        assert!(timed_out[0].fields["error"].contains("'next'"));
+    }
+}
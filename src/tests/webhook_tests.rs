@@ -0,0 +1,118 @@
+#[cfg(all(test, feature = "http"))]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use crate::chain::{Chain, ChainResult};
+    use crate::executor::ExecutionResult;
+    use crate::tests::mock_executor::MockExecutor;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::sync::mpsc;
+    use std::time::{Duration, Instant};
+
+    /// Accepts one HTTP request on a local port and sends its body to the
+    /// returned receiver; answers with `status`.
+    fn mock_server(status: u16) -> (String, mpsc::Receiver<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/hooks/atento", listener.local_addr().unwrap());
+        let (sender, receiver) = mpsc::channel();
+
+        std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream);
+            let mut content_length = 0;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                let line = line.trim_end();
+                if line.is_empty() {
+                    break;
+                }
+                if let Some((name, value)) = line.split_once(':')
+                    && name.eq_ignore_ascii_case("content-length")
+                {
+                    content_length = value.trim().parse().unwrap();
+                }
+            }
+            let mut body = vec![0; content_length];
+            reader.read_exact(&mut body).unwrap();
+            let response = format!("HTTP/1.1 {status} Status\r\nContent-Length: 0\r\n\r\n");
+            reader.get_mut().write_all(response.as_bytes()).unwrap();
+            sender.send(String::from_utf8(body).unwrap()).unwrap();
+        });
+
+        (url, receiver)
+    }
+
+    fn chain_with_webhook(url: &str) -> Chain {
+        let mut chain: Chain = r"
+name: notified
+steps:
+  greet:
+    type: bash
+    script: echo hello
+"
+        .parse()
+        .unwrap();
+        chain.on_complete_webhook = Some(url.to_string());
+        chain
+    }
+
+    fn ok(stdout: &str) -> ExecutionResult {
+        ExecutionResult {
+            stdout: stdout.to_string(),
+            duration_ms: 1,
+            ..ExecutionResult::default()
+        }
+    }
+
+    #[test]
+    fn test_webhook_receives_final_result() {
+        let (url, received) = mock_server(200);
+        let chain = chain_with_webhook(&url);
+        chain.validate().unwrap();
+
+        let mut mock = MockExecutor::new();
+        mock.expect_call("echo hello", ok("hello"));
+        let result = chain.run_with_executor(&mock);
+
+        let body = received.recv_timeout(Duration::from_secs(5)).unwrap();
+        let posted: ChainResult = serde_json::from_str(&body).unwrap();
+        assert_eq!(posted, result);
+        assert_eq!(posted.status, "ok");
+        assert_eq!(posted.name.as_deref(), Some("notified"));
+    }
+
+    #[test]
+    fn test_webhook_error_status_does_not_change_result() {
+        let (url, received) = mock_server(500);
+        let chain = chain_with_webhook(&url);
+
+        let mut mock = MockExecutor::new();
+        mock.expect_call("echo hello", ok("hello"));
+        let result = chain.run_with_executor(&mock);
+
+        assert!(received.recv_timeout(Duration::from_secs(5)).is_ok());
+        assert_eq!(result.status, "ok");
+        assert!(result.errors.is_empty());
+    }
+
+    #[test]
+    fn test_unreachable_webhook_does_not_change_result() {
+        // Bind and drop a listener to get a local port nothing listens on
+        let port = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let chain = chain_with_webhook(&format!("http://127.0.0.1:{port}/"));
+
+        let mut mock = MockExecutor::new();
+        mock.expect_call("echo hello", ok("hello"));
+        let start = Instant::now();
+        let result = chain.run_with_executor(&mock);
+
+        assert!(start.elapsed() < Duration::from_secs(10));
+        assert_eq!(result.status, "ok");
+        assert!(result.errors.is_empty());
+    }
+}
@@ -0,0 +1,28 @@
+use crate::chain::ChainResult;
+use crate::errors::{AtentoError, Result};
+use std::time::Duration;
+
+/// Seconds allowed to connect to the webhook endpoint
+const CONNECT_TIMEOUT_SECS: u64 = 5;
+/// Seconds allowed for the whole request once connected
+const REQUEST_TIMEOUT_SECS: u64 = 10;
+
+/// POSTs `result` as JSON to `url`.
+///
+/// # Errors
+/// Returns an `Execution` error if the result cannot be serialized, the
+/// endpoint cannot be reached in time, or it answers with an error status.
+pub(crate) fn post_result(url: &str, result: &ChainResult) -> Result<()> {
+    let body = serde_json::to_string(result)?;
+    let agent = ureq::AgentBuilder::new()
+        .timeout_connect(Duration::from_secs(CONNECT_TIMEOUT_SECS))
+        .timeout(Duration::from_secs(REQUEST_TIMEOUT_SECS))
+        .build();
+
+    agent
+        .post(url)
+        .set("Content-Type", "application/json")
+        .send_string(&body)
+        .map(|_| ())
+        .map_err(|e| AtentoError::Execution(format!("Webhook POST to '{url}' failed: {e}")))
+}
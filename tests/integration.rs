@@ -376,6 +376,720 @@ steps:
     assert!(result.is_ok());
 }
 
+#[cfg(unix)]
+#[test]
+fn test_run_file_returns_chain_result() {
+    let temp_dir = TempDir::new().unwrap();
+    let chain_path = temp_dir.path().join("to_result.yaml");
+
+    let chain_content = r#"
+name: "Result Chain"
+steps:
+  ok_step:
+    type: bash
+    script: echo "VALUE=42"
+    outputs:
+      value:
+        pattern: "VALUE=([0-9]+)"
+  bad_step:
+    type: bash
+    script: echo "no match here"
+    outputs:
+      missing:
+        pattern: "MISSING=([0-9]+)"
+"#;
+
+    fs::write(&chain_path, chain_content).unwrap();
+
+    let result = atento_core::run_file(&chain_path).unwrap();
+    assert_eq!(result.status, "nok");
+    assert_eq!(result.errors.len(), 1);
+    let steps = result.steps.unwrap();
+    assert_eq!(steps["ok_step"].outputs["value"], "42");
+}
+
+#[test]
+fn test_run_to_result_returns_chain_result() {
+    let temp_dir = TempDir::new().unwrap();
+    let chain_path = temp_dir.path().join("to_result.yaml");
+    fs::write(&chain_path, "name: \"Empty Chain\"\nsteps: {}\n").unwrap();
+
+    let result = atento_core::run_to_result(chain_path.to_str().unwrap()).unwrap();
+    assert_eq!(result.status, "ok");
+    assert_eq!(result.name.as_deref(), Some("Empty Chain"));
+
+    assert!(atento_core::run_to_result("missing_chain.yaml").is_err());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_run_file_merges_included_chains() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::create_dir(temp_dir.path().join("common")).unwrap();
+    fs::write(
+        temp_dir.path().join("common/setup.yaml"),
+        r#"
+parameters:
+  greeting:
+    value: hello
+steps:
+  setup:
+    type: bash
+    script: echo "READY=yes"
+    outputs:
+      ready:
+        pattern: "READY=([a-z]+)"
+"#,
+    )
+    .unwrap();
+    let chain_path = temp_dir.path().join("main.yaml");
+    fs::write(
+        &chain_path,
+        r#"
+include:
+  - common/setup.yaml
+steps:
+  greet:
+    type: bash
+    script: echo "{{ inputs.greeting }} {{ inputs.ready }}"
+    inputs:
+      greeting:
+        ref: parameters.greeting
+      ready:
+        ref: steps.setup.outputs.ready
+results:
+  ready:
+    ref: steps.setup.outputs.ready
+"#,
+    )
+    .unwrap();
+
+    let result = atento_core::run_file(&chain_path).unwrap();
+    assert_eq!(result.status, "ok", "errors: {:?}", result.errors);
+    let steps = result.steps.unwrap();
+    assert_eq!(
+        steps["greet"].stdout.as_deref().map(str::trim),
+        Some("hello yes")
+    );
+    assert_eq!(result.results.unwrap()["ready"], "yes");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_run_file_loads_script_files() {
+    let temp_dir = TempDir::new().unwrap();
+    fs::create_dir(temp_dir.path().join("scripts")).unwrap();
+    fs::write(
+        temp_dir.path().join("scripts/version.sh"),
+        "#!/bin/bash\necho \"VERSION={{ inputs.major }}.4\"\n",
+    )
+    .unwrap();
+    let chain_path = temp_dir.path().join("chain.yaml");
+    fs::write(
+        &chain_path,
+        r#"
+steps:
+  version:
+    type: bash
+    script_file: scripts/version.sh
+    inputs:
+      major:
+        type: int
+        value: 2
+    outputs:
+      version:
+        pattern: "VERSION=([0-9.]+)"
+"#,
+    )
+    .unwrap();
+
+    let result = atento_core::run_file(&chain_path).unwrap();
+    assert_eq!(result.status, "ok", "errors: {:?}", result.errors);
+    assert_eq!(result.steps.unwrap()["version"].outputs["version"], "2.4");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_run_nonzero_exit_halts_chain() {
+    let temp_dir = TempDir::new().unwrap();
+    let chain_path = temp_dir.path().join("exit_code.yaml");
+
+    let chain_content = r#"
+steps:
+  failing:
+    type: bash
+    script: |
+      echo "RESULT=partial"
+      exit 3
+    outputs:
+      result:
+        pattern: "RESULT=(.*)"
+  after:
+    type: bash
+    script: echo "never runs"
+"#;
+
+    fs::write(&chain_path, chain_content).unwrap();
+
+    let result = atento_core::run_file(&chain_path).unwrap();
+    assert_eq!(result.status, "nok");
+    let steps = result.steps.unwrap();
+    assert_eq!(steps["failing"].exit_code, 3);
+    assert!(steps["failing"].error.is_some());
+    assert!(!steps.contains_key("after"));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_run_parallel_chain() {
+    let temp_dir = TempDir::new().unwrap();
+    let chain_path = temp_dir.path().join("parallel.yaml");
+
+    let chain_content = r#"
+parallel: true
+steps:
+  left:
+    type: bash
+    script: |
+      sleep 1
+      echo "LEFT=1"
+    outputs:
+      value:
+        pattern: "LEFT=(.*)"
+        type: int
+  right:
+    type: bash
+    script: |
+      sleep 1
+      echo "RIGHT=2"
+    outputs:
+      value:
+        pattern: "RIGHT=(.*)"
+        type: int
+  sum:
+    type: bash
+    script: echo "SUM=$(( {{ inputs.left }} + {{ inputs.right }} ))"
+    inputs:
+      left:
+        ref: steps.left.outputs.value
+      right:
+        ref: steps.right.outputs.value
+    outputs:
+      total:
+        pattern: "SUM=(.*)"
+        type: int
+results:
+  total:
+    ref: steps.sum.outputs.total
+"#;
+
+    fs::write(&chain_path, chain_content).unwrap();
+
+    let start = std::time::Instant::now();
+    let result = atento_core::run_file(&chain_path).unwrap();
+    assert!(start.elapsed() < std::time::Duration::from_millis(1900));
+    assert_eq!(result.status, "ok", "errors: {:?}", result.errors);
+    assert_eq!(result.results.unwrap()["total"], "3");
+    let steps = result.steps.unwrap();
+    let order: Vec<&str> = steps.keys().map(String::as_str).collect();
+    assert_eq!(order, vec!["left", "right", "sum"]);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_run_parallel_chain_timeout() {
+    let temp_dir = TempDir::new().unwrap();
+    let chain_path = temp_dir.path().join("parallel_timeout.yaml");
+
+    let chain_content = r"
+parallel: true
+timeout: 1
+steps:
+  slow:
+    type: bash
+    script: sleep 10
+  fast:
+    type: bash
+    script: echo done
+";
+
+    fs::write(&chain_path, chain_content).unwrap();
+
+    let start = std::time::Instant::now();
+    let result = atento_core::run_file(&chain_path).unwrap();
+    assert!(start.elapsed() < std::time::Duration::from_secs(5));
+    assert_eq!(result.status, "nok");
+    assert!(!result.errors.is_empty());
+    assert_eq!(result.steps.unwrap()["fast"].exit_code, 0);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_run_chain_combined_capture() {
+    let temp_dir = TempDir::new().unwrap();
+    let chain_path = temp_dir.path().join("combined.yaml");
+
+    let chain_content = r#"
+steps:
+  noisy:
+    type: bash
+    capture: combined
+    script: |
+      echo "starting"
+      sleep 0.2
+      echo "disk almost full" >&2
+      sleep 0.2
+      echo "COUNT=3"
+    outputs:
+      count:
+        pattern: 'COUNT=(\d+)'
+        type: int
+"#;
+
+    fs::write(&chain_path, chain_content).unwrap();
+
+    let result = atento_core::run_file(&chain_path).unwrap();
+    assert_eq!(result.status, "ok", "errors: {:?}", result.errors);
+    let steps = result.steps.unwrap();
+    let noisy = &steps["noisy"];
+    assert_eq!(
+        noisy.combined_output.as_deref(),
+        Some("starting\ndisk almost full\nCOUNT=3")
+    );
+    assert_eq!(noisy.stderr.as_deref(), Some("disk almost full"));
+    assert_eq!(noisy.typed_outputs["count"], 3);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_run_step_timeout_keeps_partial_output() {
+    let temp_dir = TempDir::new().unwrap();
+    let chain_path = temp_dir.path().join("step_timeout.yaml");
+
+    let chain_content = r#"
+steps:
+  hanging:
+    type: bash
+    timeout: 1
+    script: |
+      echo "connecting to db"
+      sleep 10
+      echo "connected"
+  after:
+    type: bash
+    script: echo "never runs"
+"#;
+
+    fs::write(&chain_path, chain_content).unwrap();
+
+    let result = atento_core::run_file(&chain_path).unwrap();
+    assert_eq!(result.status, "nok");
+    let steps = result.steps.unwrap();
+    let hanging = &steps["hanging"];
+    assert_eq!(hanging.exit_code, 124);
+    assert_eq!(hanging.stdout.as_deref(), Some("connecting to db"));
+    assert!(matches!(
+        hanging.error,
+        Some(atento_core::AtentoError::Timeout { .. })
+    ));
+    assert!(!steps.contains_key("after"));
+}
+
+#[test]
+fn test_quote_powershell_round_trips_values() {
+    let pwsh_available = std::process::Command::new("pwsh")
+        .args(["-NoProfile", "-Command", "exit 0"])
+        .output()
+        .is_ok_and(|output| output.status.success());
+    if !pwsh_available {
+        eprintln!("Skipping PowerShell quoting test - pwsh not found");
+        return;
+    }
+
+    let value = "it's \u{2018}a\u{2019}; \u{201A}b\u{201B} $(Write-Output injected) $env:HOME";
+    let mut chain: atento_core::Chain = r"
+parameters:
+  tricky:
+    value: placeholder
+steps:
+  echo:
+    type: pwsh
+    quote: powershell
+    script: Write-Output {{ parameters.tricky }}
+"
+    .parse()
+    .unwrap();
+    chain.parameters.get_mut("tricky").unwrap().value = Some(value.into());
+    chain.validate().unwrap();
+
+    let result = chain.run();
+    assert_eq!(result.status, "ok", "errors: {:?}", result.errors);
+    assert_eq!(result.steps.unwrap()["echo"].stdout.as_deref(), Some(value));
+}
+
+#[test]
+fn test_run_node_chain() {
+    let node_available = std::process::Command::new("node")
+        .arg("--version")
+        .output()
+        .is_ok_and(|output| output.status.success());
+    if !node_available {
+        eprintln!("Skipping node chain test - node not found");
+        return;
+    }
+
+    let result = atento_core::run_from_str(
+        r#"
+steps:
+  greet:
+    type: node
+    script: console.log("hi")
+    outputs:
+      word:
+        pattern: '(\w+)'
+"#,
+    )
+    .unwrap();
+
+    assert_eq!(result.status, "ok", "errors: {:?}", result.errors);
+    assert_eq!(result.steps.unwrap()["greet"].outputs["word"], "hi");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_run_step_in_docker_image() {
+    let docker_available = std::process::Command::new("docker")
+        .arg("info")
+        .output()
+        .is_ok_and(|output| output.status.success());
+    if !docker_available {
+        eprintln!("Skipping docker step test - docker daemon not reachable");
+        return;
+    }
+
+    let result = atento_core::run_from_str(
+        r#"
+steps:
+  inside:
+    type: sh
+    image: alpine:3.20
+    script: |
+      echo "os=$(cat /etc/alpine-release | cut -d. -f1)"
+      echo "token=$TOKEN"
+    env:
+      TOKEN: abc
+    outputs:
+      os:
+        pattern: 'os=(\d+)'
+      token:
+        pattern: 'token=(\w+)'
+interpreters:
+  sh:
+    command: sh
+    extension: .sh
+"#,
+    )
+    .unwrap();
+
+    assert_eq!(result.status, "ok", "errors: {:?}", result.errors);
+    let inside = &result.steps.unwrap()["inside"];
+    assert_eq!(inside.outputs["os"], "3");
+    assert_eq!(inside.outputs["token"], "abc");
+}
+
+#[cfg(unix)]
+#[test]
+fn test_chain_image_applies_to_steps_without_their_own() {
+    let docker_available = std::process::Command::new("docker")
+        .arg("info")
+        .output()
+        .is_ok_and(|output| output.status.success());
+    if !docker_available {
+        eprintln!("Skipping chain image test - docker daemon not reachable");
+        return;
+    }
+
+    let result = atento_core::run_from_str(
+        r#"
+image: alpine:3.20
+steps:
+  inside:
+    type: sh
+    script: echo "os=$(cut -d. -f1 /etc/alpine-release)"
+    outputs:
+      os:
+        pattern: 'os=(\d+)'
+  pinned:
+    type: sh
+    image: busybox:1.36
+    script: echo "alpine=$(test -f /etc/alpine-release && echo yes || echo no)"
+    outputs:
+      alpine:
+        pattern: 'alpine=(\w+)'
+interpreters:
+  sh:
+    command: sh
+    extension: .sh
+"#,
+    )
+    .unwrap();
+
+    assert_eq!(result.status, "ok", "errors: {:?}", result.errors);
+    let steps = result.steps.unwrap();
+    assert_eq!(steps["inside"].outputs["os"], "3");
+    assert_eq!(steps["pinned"].outputs["alpine"], "no");
+}
+
+/// Runs only when `ATENTO_SSH_TEST_HOST` names a host reachable without a
+/// password prompt, e.g. `ATENTO_SSH_TEST_HOST=ci@localhost`.
+#[cfg(unix)]
+#[test]
+fn test_run_chain_over_ssh() {
+    let Ok(destination) = std::env::var("ATENTO_SSH_TEST_HOST") else {
+        eprintln!("Skipping SSH chain test - ATENTO_SSH_TEST_HOST not set");
+        return;
+    };
+    let (user, host) = match destination.split_once('@') {
+        Some((user, host)) => (Some(user), host),
+        None => (None, destination.as_str()),
+    };
+
+    let mut yaml = format!("remote:\n  host: {host}\n");
+    if let Some(user) = user {
+        yaml.push_str(&format!("  user: {user}\n"));
+    }
+    yaml.push_str(
+        r#"
+steps:
+  remote:
+    type: bash
+    script: echo "remote=$(hostname) $GREETING"
+    env:
+      GREETING: hello
+    outputs:
+      greeting:
+        pattern: 'remote=\S+ (\w+)'
+"#,
+    );
+
+    let result = atento_core::run_from_str(&yaml).unwrap();
+    assert_eq!(result.status, "ok", "errors: {:?}", result.errors);
+    assert_eq!(result.steps.unwrap()["remote"].outputs["greeting"], "hello");
+}
+
+#[test]
+fn test_run_custom_interpreter_chain() {
+    let node_available = std::process::Command::new("node")
+        .arg("--version")
+        .output()
+        .is_ok_and(|output| output.status.success());
+    if !node_available {
+        eprintln!("Skipping custom interpreter test - node not found");
+        return;
+    }
+
+    let result = atento_core::run_from_str(
+        r#"
+interpreters:
+  esm:
+    command: node
+    args: ["--no-warnings"]
+    extension: .mjs
+steps:
+  module:
+    type: esm
+    script: |
+      const { platform } = await import("node:os");
+      console.log(`kind=${typeof platform}`);
+    outputs:
+      kind:
+        pattern: 'kind=(\w+)'
+"#,
+    )
+    .unwrap();
+
+    assert_eq!(result.status, "ok", "errors: {:?}", result.errors);
+    assert_eq!(result.steps.unwrap()["module"].outputs["kind"], "function");
+}
+
+/// Returns true while `pid` is a live (non-zombie) process.
+#[cfg(unix)]
+fn process_alive(pid: &str) -> bool {
+    let output = std::process::Command::new("ps")
+        .args(["-o", "stat=", "-p", pid])
+        .output()
+        .unwrap();
+    let stat = String::from_utf8_lossy(&output.stdout);
+    output.status.success() && !stat.trim().is_empty() && !stat.trim().starts_with('Z')
+}
+
+#[cfg(unix)]
+#[test]
+fn test_run_step_timeout_kills_background_processes() {
+    let temp_dir = TempDir::new().unwrap();
+    let chain_path = temp_dir.path().join("step_timeout_tree.yaml");
+
+    let chain_content = r#"
+steps:
+  spawner:
+    type: bash
+    timeout: 1
+    script: |
+      sleep 300 &
+      echo "pid $!"
+      wait
+"#;
+
+    fs::write(&chain_path, chain_content).unwrap();
+
+    let result = atento_core::run_file(&chain_path).unwrap();
+    let steps = result.steps.unwrap();
+    let spawner = &steps["spawner"];
+    assert_eq!(spawner.exit_code, 124);
+    let stdout = spawner.stdout.as_deref().unwrap();
+    let pid = stdout.strip_prefix("pid ").unwrap();
+
+    // The group is killed with SIGKILL, give the OS a moment to tear it down
+    let deadline = std::time::Instant::now() + std::time::Duration::from_secs(5);
+    while process_alive(pid) && std::time::Instant::now() < deadline {
+        std::thread::sleep(std::time::Duration::from_millis(50));
+    }
+    assert!(
+        !process_alive(pid),
+        "background sleep {pid} survived the timeout"
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn test_run_parameter_placeholders_in_script() {
+    let temp_dir = TempDir::new().unwrap();
+    let chain_path = temp_dir.path().join("parameter_placeholders.yaml");
+
+    let chain_content = r#"
+parameters:
+  env:
+    value: staging
+steps:
+  deploy:
+    type: bash
+    script: |
+      echo "DEPLOY_TARGET={{ parameters.env }}"
+    outputs:
+      target:
+        pattern: DEPLOY_TARGET=(\w+)
+results:
+  target:
+    ref: steps.deploy.outputs.target
+"#;
+
+    fs::write(&chain_path, chain_content).unwrap();
+
+    let result = atento_core::run_file(&chain_path).unwrap();
+    assert_eq!(result.status, "ok", "errors: {:?}", result.errors);
+    assert_eq!(result.results.unwrap()["target"], "staging");
+
+    fs::write(
+        &chain_path,
+        chain_content.replace("parameters.env", "parameters.region"),
+    )
+    .unwrap();
+    match atento_core::run_file(&chain_path) {
+        Err(atento_core::AtentoError::UnresolvedReference { reference, context }) => {
+            assert_eq!(reference, "parameters.region");
+            assert!(context.contains("deploy"), "{context}");
+        }
+        other => panic!("Expected UnresolvedReference, got {other:?}"),
+    }
+}
+
+#[cfg(unix)]
+#[test]
+fn test_run_nonzero_exit_allowed() {
+    let temp_dir = TempDir::new().unwrap();
+    let chain_path = temp_dir.path().join("exit_code_allowed.yaml");
+
+    let chain_content = r#"
+steps:
+  failing:
+    type: bash
+    allow_nonzero_exit: true
+    script: |
+      echo "RESULT=partial"
+      exit 3
+    outputs:
+      result:
+        pattern: "RESULT=(.*)"
+  after:
+    type: bash
+    success_exit_codes: [0, 3]
+    script: exit 3
+"#;
+
+    fs::write(&chain_path, chain_content).unwrap();
+
+    let result = atento_core::run_file(&chain_path).unwrap();
+    assert_eq!(result.status, "ok");
+    let steps = result.steps.unwrap();
+    assert_eq!(steps["failing"].exit_code, 3);
+    assert_eq!(steps["failing"].outputs["result"], "partial");
+    assert_eq!(steps["after"].exit_code, 3);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_run_invalid_workdir_fails_step() {
+    let temp_dir = TempDir::new().unwrap();
+    let chain_path = temp_dir.path().join("workdir.yaml");
+
+    let chain_content = r#"
+steps:
+  build:
+    type: bash
+    workdir: /definitely/not/a/real/dir
+    script: echo "never runs"
+"#;
+
+    fs::write(&chain_path, chain_content).unwrap();
+
+    let result = atento_core::run_file(&chain_path).unwrap();
+    assert_eq!(result.status, "nok");
+    match &result.errors[0] {
+        atento_core::AtentoError::StepExecution { step, reason } => {
+            assert_eq!(step, "build");
+            assert!(reason.contains("Invalid workdir"));
+        }
+        other => panic!("Expected StepExecution error, got {other:?}"),
+    }
+}
+
+#[test]
+fn test_run_file_file_not_found() {
+    let result = atento_core::run_file("nonexistent_file.yaml");
+    assert!(matches!(result, Err(atento_core::AtentoError::Io { .. })));
+}
+
+#[test]
+fn test_run_file_validation_error() {
+    let temp_dir = TempDir::new().unwrap();
+    let chain_path = temp_dir.path().join("invalid.yaml");
+
+    let chain_content = r#"
+steps:
+  step1:
+    type: bash
+    script: echo "{{ inputs.undeclared }}"
+"#;
+
+    fs::write(&chain_path, chain_content).unwrap();
+
+    let result = atento_core::run_file(&chain_path);
+    assert!(matches!(
+        result,
+        Err(atento_core::AtentoError::Validation(_))
+    ));
+}
+
 #[test]
 fn test_run_nonexistent_file() {
     let result = atento_core::run("nonexistent_file.yaml");
@@ -640,66 +1354,11 @@ fn test_chain_smoke_tests_unix() {
             };
 
             // Pre-check that interpreters required by the chain steps are actually runnable on this host.
-            // This checks the exact program the runtime will invoke (for example 'python3' vs 'python').
-            let mut missing_progs = Vec::new();
-            for (_k, step) in &wf.steps {
-                // Get the program that will be invoked for this interpreter
-                let interpreter = match wf.interpreters.get(&step.interpreter) {
-                    Some(interp) => interp,
-                    None => continue,
-                };
-                let args = &interpreter.args;
-                if args.is_empty() {
-                    continue;
-                }
-                let prog = args[0].as_str();
-
-                // Build candidate commands to try: prefer the exact prog, but for common aliases try fallbacks
-                let candidates: Vec<Vec<&str>> = if prog == "python3" {
-                    vec![
-                        vec!["python3", "-c", "import sys; sys.exit(0)"],
-                        vec!["python", "-c", "import sys; sys.exit(0)"],
-                    ]
-                } else if prog == "python" {
-                    vec![
-                        vec!["python", "-c", "import sys; sys.exit(0)"],
-                        vec!["python3", "-c", "import sys; sys.exit(0)"],
-                    ]
-                } else if prog == "pwsh" {
-                    vec![
-                        vec!["pwsh", "-c", "exit 0"],
-                        vec!["powershell", "-Command", "exit 0"],
-                    ]
-                } else if prog == "powershell" {
-                    vec![
-                        vec!["powershell", "-Command", "exit 0"],
-                        vec!["pwsh", "-c", "exit 0"],
-                    ]
-                } else if prog == "bash" {
-                    vec![vec!["bash", "-c", "exit 0"]]
-                } else {
-                    vec![vec![prog, "--version"]]
-                };
-
-                let mut runnable = false;
-                for cand in candidates.iter() {
-                    let prog = cand[0];
-                    let args = &cand[1..];
-                    let attempted = std::process::Command::new(prog).args(args).output();
-                    if let Ok(output) = attempted
-                        && output.status.success()
-                    {
-                        runnable = true;
-                        break;
-                    }
-                }
-
-                if !runnable {
-                    missing_progs.push(prog.to_string());
-                }
-            }
-
-            if !missing_progs.is_empty() {
+            if let Err(missing) = wf.check_interpreters() {
+                let mut missing_progs: Vec<String> =
+                    missing.into_iter().map(|m| m.command).collect();
+                missing_progs.sort();
+                missing_progs.dedup();
                 let msg = format!(
                     "SKIPPED: Missing exact interpreter executables: {}",
                     missing_progs.join(", ")
@@ -751,8 +1410,8 @@ fn test_chain_smoke_tests_unix() {
                     }
 
                     // Check for platform-specific failures
-                    for (_output_name, output_value) in &step_res.outputs {
-                        let output_str = output_value.to_lowercase();
+                    for output_value in step_res.outputs.values() {
+                        let output_str = output_value.to_string().to_lowercase();
                         if output_str.contains("nok - expected unix platform")
                             || output_str.contains("nok - expected windows platform")
                             || output_str.contains("could not detect unix system")
@@ -1032,8 +1691,8 @@ fn test_chain_smoke_tests_windows() {
                     }
 
                     // Check for platform-specific failures
-                    for (_output_name, output_value) in &step_res.outputs {
-                        let output_str = output_value.to_lowercase();
+                    for output_value in step_res.outputs.values() {
+                        let output_str = output_value.to_string().to_lowercase();
                         if output_str.contains("nok - expected unix platform")
                             || output_str.contains("nok - expected windows platform")
                             || output_str.contains("could not detect unix system")
@@ -1185,6 +1844,17 @@ fn test_chain_smoke_tests_cross_platform() {
             if content_lc.contains("powershell") || content_lc.contains("pwsh") {
                 required_bins.insert("pwsh_or_powershell");
             }
+            if content_lc.contains("type: node") {
+                required_bins.insert("node");
+            }
+            for shell in ["sh", "zsh"] {
+                if content_lc
+                    .lines()
+                    .any(|line| line.trim() == format!("type: {shell}"))
+                {
+                    required_bins.insert(shell);
+                }
+            }
 
             // Helper to try running a minimal command with the given interpreter to ensure it's usable.
             fn is_runnable(bin: &str) -> bool {
@@ -1204,6 +1874,9 @@ fn test_chain_smoke_tests_cross_platform() {
                         ],
                     ],
                     "bash" => vec![vec!["bash".into(), "-c".into(), "exit 0".into()]],
+                    "sh" => vec![vec!["sh".into(), "-c".into(), "exit 0".into()]],
+                    "zsh" => vec![vec!["zsh".into(), "-c".into(), "exit 0".into()]],
+                    "node" => vec![vec!["node".into(), "--version".into()]],
                     "pwsh_or_powershell" => vec![
                         vec!["pwsh".into(), "-c".into(), "exit 0".into()],
                         vec!["powershell".into(), "-Command".into(), "exit 0".into()],
@@ -1267,59 +1940,12 @@ fn test_chain_smoke_tests_cross_platform() {
                 }
             };
 
-            // Pre-check exact interpreter executables required by steps (skip if missing)
-            let mut missing_progs = Vec::new();
-            for (_k, step) in &wf.steps {
-                let interpreter = match wf.interpreters.get(&step.interpreter) {
-                    Some(interp) => interp,
-                    None => continue,
-                };
-                let prog = interpreter.command.as_str();
-
-                let candidates: Vec<Vec<&str>> = if prog == "python3" {
-                    vec![
-                        vec!["python3", "-c", "import sys; sys.exit(0)"],
-                        vec!["python", "-c", "import sys; sys.exit(0)"],
-                    ]
-                } else if prog == "python" {
-                    vec![
-                        vec!["python", "-c", "import sys; sys.exit(0)"],
-                        vec!["python3", "-c", "import sys; sys.exit(0)"],
-                    ]
-                } else if prog == "pwsh" {
-                    vec![
-                        vec!["pwsh", "-c", "exit 0"],
-                        vec!["powershell", "-Command", "exit 0"],
-                    ]
-                } else if prog == "powershell" {
-                    vec![
-                        vec!["powershell", "-Command", "exit 0"],
-                        vec!["pwsh", "-c", "exit 0"],
-                    ]
-                } else if prog == "bash" {
-                    vec![vec!["bash", "-c", "exit 0"]]
-                } else {
-                    vec![vec![prog, "--version"]]
-                };
-
-                let mut runnable = false;
-                for cand in candidates.iter() {
-                    let prog = cand[0];
-                    let args = &cand[1..];
-                    if let Ok(output) = std::process::Command::new(prog).args(args).output()
-                        && output.status.success()
-                    {
-                        runnable = true;
-                        break;
-                    }
-                }
-
-                if !runnable {
-                    missing_progs.push(prog.to_string());
-                }
-            }
-
-            if !missing_progs.is_empty() {
+            // Pre-check that interpreters required by the chain steps are actually runnable on this host.
+            if let Err(missing) = wf.check_interpreters() {
+                let mut missing_progs: Vec<String> =
+                    missing.into_iter().map(|m| m.command).collect();
+                missing_progs.sort();
+                missing_progs.dedup();
                 let msg = format!(
                     "SKIPPED: Missing exact interpreter executables: {}",
                     missing_progs.join(", ")
@@ -1371,8 +1997,8 @@ fn test_chain_smoke_tests_cross_platform() {
 
                     // Check for platform-specific chain failures (e.g., Unix-specific tests on Windows)
                     // These chains contain platform checks that legitimately fail on the wrong platform
-                    for (_output_name, output_value) in &step_res.outputs {
-                        let output_str = output_value.to_lowercase();
+                    for output_value in step_res.outputs.values() {
+                        let output_str = output_value.to_string().to_lowercase();
                         if output_str.contains("nok - expected unix platform")
                             || output_str.contains("nok - expected windows platform")
                             || output_str.contains("could not detect unix system")
@@ -1518,23 +2144,10 @@ fn test_chain_file_validation() {
                     path.file_name().unwrap().to_str().unwrap()
                 );
 
-                // Read and basic validation - just ensure it's valid YAML
-                match fs::read_to_string(&path) {
-                    Ok(content) => {
-                        // Try to parse as YAML (basic validation)
-                        match serde_yaml::from_str::<serde_yaml::Value>(&content) {
-                            Ok(_) => {
-                                validation_results.push((chain_name, "VALID YAML".to_string()));
-                            }
-                            Err(e) => {
-                                validation_results
-                                    .push((chain_name, format!("INVALID YAML: {}", e)));
-                            }
-                        }
-                    }
-                    Err(e) => {
-                        validation_results.push((chain_name, format!("READ ERROR: {}", e)));
-                    }
+                // Parse and validate without running, as a CI lint would
+                match atento_core::validate(path.to_str().unwrap()) {
+                    Ok(()) => validation_results.push((chain_name, "VALID".to_string())),
+                    Err(e) => validation_results.push((chain_name, format!("INVALID: {}", e))),
                 }
             }
         }
@@ -1556,7 +2169,7 @@ fn test_chain_file_validation() {
         return;
     }
 
-    // Ensure all chains have valid YAML
+    // Ensure all chains are valid
     let invalid_count = validation_results
         .iter()
         .filter(|(_, result)| !result.starts_with("VALID"))
@@ -1564,12 +2177,12 @@ fn test_chain_file_validation() {
 
     assert_eq!(
         invalid_count, 0,
-        "{} out of {} chain files have invalid YAML",
+        "{} out of {} chain files are invalid",
         invalid_count, total_chains
     );
 
     eprintln!(
-        "\x1b[1m\x1b[32m✅ All {} chain files have valid YAML syntax!\x1b[0m",
+        "\x1b[1m\x1b[32m✅ All {} chain files are valid!\x1b[0m",
         total_chains
     );
 }